@@ -0,0 +1,478 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A backend for ACIR, the intermediate representation which the Noir
+//! compiler outputs, so that Noir programs can be proven with the Plonk
+//! provers of this crate.
+//!
+//! The ACIR types are mirrored here with the field of the circuit, the
+//! `acir` crate depending on another version of arkworks; a program
+//! deserialized with it converts into them with `F::from_le_bytes_mod_order`
+//! on its field elements. The supported opcodes are the quadratic `AssertZero`
+//! constraints, and the `RANGE`, `AND` and `XOR` black box functions. An
+//! `AssertZero` opcode is lowered as an R1CS constraint, c.f.
+//! `PlonkCircuit::import_r1cs`, its first product being the left hand side of
+//! the constraint and its other products being multiplication gates. The
+//! range checks use the range table of the circuit if it supports lookup, and
+//! the bitwise operations look up chunks of their operands in the logic
+//! table, or decompose them into bits otherwise.
+
+use super::{Circuit, PlonkCircuit, Variable};
+use crate::errors::{CircuitError::ParameterError, PlonkError};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{
+    cmp::min,
+    collections::{BTreeMap, BTreeSet},
+    format, vec,
+    vec::Vec,
+};
+
+/// The index of a witness of an ACIR circuit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Witness(pub u32);
+
+/// A quadratic expression of witnesses
+/// `sum(q_m * w_l * w_r) + sum(q_l * w) + q_c`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Expression<F: PrimeField> {
+    /// The products `(q_m, w_l, w_r)`.
+    pub mul_terms: Vec<(F, Witness, Witness)>,
+    /// The linear terms `(q_l, w)`.
+    pub linear_combinations: Vec<(F, Witness)>,
+    /// The constant term.
+    pub q_c: F,
+}
+
+/// A call to a black box function of ACIR supported by this backend, whose
+/// operands are witnesses of `num_bits` bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlackBoxFuncCall {
+    /// `output = lhs & rhs`.
+    And {
+        /// The left operand.
+        lhs: Witness,
+        /// The right operand.
+        rhs: Witness,
+        /// The result.
+        output: Witness,
+        /// The bit length of the operands.
+        num_bits: u32,
+    },
+    /// `output = lhs ^ rhs`.
+    Xor {
+        /// The left operand.
+        lhs: Witness,
+        /// The right operand.
+        rhs: Witness,
+        /// The result.
+        output: Witness,
+        /// The bit length of the operands.
+        num_bits: u32,
+    },
+    /// `input < 2^num_bits`.
+    Range {
+        /// The checked witness.
+        input: Witness,
+        /// The bit length of the range.
+        num_bits: u32,
+    },
+}
+
+/// An ACIR opcode supported by this backend.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Opcode<F: PrimeField> {
+    /// The expression is zero.
+    AssertZero(Expression<F>),
+    /// A call to a black box function.
+    BlackBoxFuncCall(BlackBoxFuncCall),
+}
+
+/// An ACIR circuit, i.e. the opcodes of a Noir program over the witnesses
+/// `0, ..., current_witness_index`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AcirCircuit<F: PrimeField> {
+    /// The largest index of a witness.
+    pub current_witness_index: u32,
+    /// The opcodes.
+    pub opcodes: Vec<Opcode<F>>,
+    /// The public inputs of the program.
+    pub public_parameters: BTreeSet<Witness>,
+    /// The public return values of the program.
+    pub return_values: BTreeSet<Witness>,
+}
+
+impl<F: PrimeField> AcirCircuit<F> {
+    /// The public witnesses, i.e. the public inputs and the return values, by
+    /// increasing index, which is the order of the public inputs of the Plonk
+    /// circuit.
+    pub fn public_witnesses(&self) -> BTreeSet<Witness> {
+        self.public_parameters
+            .union(&self.return_values)
+            .copied()
+            .collect()
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Lower the opcodes of the ACIR circuit `acir`, whose public witnesses
+    /// become public inputs of this circuit, c.f.
+    /// `AcirCircuit::public_witnesses`. The values of the witnesses are given
+    /// by `witness`, e.g. solved by the ACVM, a missing witness being zero,
+    /// or are zero if it is `None`, e.g. to preprocess the circuit in a
+    /// shape-only circuit, c.f. `is_shape_only`.
+    /// Return the variable of this circuit of each witness, by index.
+    /// Return error if an opcode has a witness whose index is larger than
+    /// `current_witness_index`, or an invalid bit length.
+    pub fn import_acir(
+        &mut self,
+        acir: &AcirCircuit<F>,
+        witness: Option<&BTreeMap<Witness, F>>,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let public_witnesses = acir.public_witnesses();
+        let mut vars = Vec::with_capacity(acir.current_witness_index as usize + 1);
+        for index in 0..=acir.current_witness_index {
+            let value = witness
+                .and_then(|witness| witness.get(&Witness(index)).copied())
+                .unwrap_or_else(F::zero);
+            let var = if public_witnesses.contains(&Witness(index)) {
+                self.create_public_variable(value)?
+            } else {
+                self.create_variable(value)?
+            };
+            vars.push(var);
+        }
+
+        let var = |w: Witness| {
+            vars.get(w.0 as usize).copied().ok_or_else(|| {
+                PlonkError::from(ParameterError(format!(
+                    "the witness {} is larger than the current witness index {}",
+                    w.0, acir.current_witness_index
+                )))
+            })
+        };
+        for opcode in acir.opcodes.iter() {
+            match opcode {
+                Opcode::AssertZero(expr) => {
+                    let mul_terms = expr
+                        .mul_terms
+                        .iter()
+                        .map(|&(q_m, w_l, w_r)| Ok((q_m, var(w_l)?, var(w_r)?)))
+                        .collect::<Result<Vec<_>, PlonkError>>()?;
+                    let linear_terms = expr
+                        .linear_combinations
+                        .iter()
+                        .map(|&(q_l, w)| Ok((q_l, var(w)?)))
+                        .collect::<Result<Vec<_>, PlonkError>>()?;
+                    self.acir_assert_zero_gate(&mul_terms, &linear_terms, expr.q_c)?;
+                },
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Range { input, num_bits }) => {
+                    self.enforce_in_range(var(*input)?, *num_bits as usize)?;
+                },
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::And {
+                    lhs,
+                    rhs,
+                    output,
+                    num_bits,
+                }) => {
+                    let (and, _) = self.acir_logic(var(*lhs)?, var(*rhs)?, *num_bits as usize)?;
+                    self.equal_gate(var(*output)?, and)?;
+                },
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Xor {
+                    lhs,
+                    rhs,
+                    output,
+                    num_bits,
+                }) => {
+                    let (_, xor) = self.acir_logic(var(*lhs)?, var(*rhs)?, *num_bits as usize)?;
+                    self.equal_gate(var(*output)?, xor)?;
+                },
+            }
+        }
+        Ok(vars)
+    }
+
+    // Constrain `sum(q_m * l * r) + sum(q_l * v) + q_c = 0` as the R1CS
+    // constraint `(q_m * l) * r = -sum(q_l * v) - q_c - ...`, the other
+    // products being variables of the right hand side.
+    fn acir_assert_zero_gate(
+        &mut self,
+        mul_terms: &[(F, Variable, Variable)],
+        linear_terms: &[(F, Variable)],
+        q_c: F,
+    ) -> Result<(), PlonkError> {
+        // the R1CS variables of the constraint, the first one being the
+        // constant one
+        let mut vars = vec![self.one()];
+        let mut a = Vec::new();
+        let mut b = Vec::new();
+        let mut c = vec![(-q_c, 0)];
+        for (i, &(q_m, l, r)) in mul_terms.iter().enumerate() {
+            if i == 0 {
+                a.push((q_m, vars.len()));
+                vars.push(l);
+                b.push((F::one(), vars.len()));
+                vars.push(r);
+            } else {
+                let product = self.mul(l, r)?;
+                c.push((-q_m, vars.len()));
+                vars.push(product);
+            }
+        }
+        for &(q_l, v) in linear_terms.iter() {
+            c.push((-q_l, vars.len()));
+            vars.push(v);
+        }
+        self.r1cs_constraint_gate(&a, &b, &c, &vars)
+    }
+
+    // Obtain the variables of `(a & b, a ^ b)` for operands of `num_bits`
+    // bits, which are constrained to be in range.
+    fn acir_logic(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        num_bits: usize,
+    ) -> Result<(Variable, Variable), PlonkError> {
+        if num_bits == 0 || num_bits >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "the bit length of a bitwise operation is between 1 and {}, got {}",
+                F::size_in_bits() - 1,
+                num_bits
+            ))
+            .into());
+        }
+        let chunk_bit_len = if self.support_lookup() {
+            self.logic_table_bit_len()?
+        } else {
+            1
+        };
+        let a_chunks = self.acir_chunks(a, num_bits, chunk_bit_len)?;
+        let b_chunks = self.acir_chunks(b, num_bits, chunk_bit_len)?;
+        let pairs: Vec<(Variable, Variable)> =
+            a_chunks.into_iter().zip(b_chunks.into_iter()).collect();
+        let outputs = if self.support_lookup() {
+            self.logic_lookup(&pairs)?
+        } else {
+            // x & y = x * y and x ^ y = x + y - 2 * x * y for bits x, y
+            let zero = self.zero();
+            pairs
+                .iter()
+                .map(|&(x, y)| {
+                    let and = self.mul(x, y)?;
+                    let xor = self.gen_quad_poly(
+                        &[x, y, zero, zero],
+                        &[F::one(), F::one(), F::zero(), F::zero()],
+                        &[-F::from(2u32), F::zero()],
+                        F::zero(),
+                    )?;
+                    Ok((and, xor))
+                })
+                .collect::<Result<Vec<_>, PlonkError>>()?
+        };
+
+        // the chunks of the outputs are in range as they are table elements
+        // or bits
+        let base = F::from(1u64 << chunk_bit_len);
+        let mut res = [self.zero(); 2];
+        for (k, res_k) in res.iter_mut().enumerate() {
+            let chunks: Vec<Variable> = outputs
+                .iter()
+                .map(|&(and, xor)| if k == 0 { and } else { xor })
+                .collect();
+            if chunks.len() == 1 {
+                *res_k = chunks[0];
+                continue;
+            }
+            let mut value = F::zero();
+            for &chunk in chunks.iter().rev() {
+                value = value * base + self.witness(chunk)?;
+            }
+            *res_k = self.create_variable(value)?;
+            self.decompose_vars_gate(chunks, *res_k, base)?;
+        }
+        Ok((res[0], res[1]))
+    }
+
+    // The little-endian chunks of `chunk_bit_len` bits of the `num_bits` bits
+    // of `a`, which are range checked and constrained to compose `a`, i.e.
+    // `a` is constrained to be smaller than `2^num_bits`.
+    fn acir_chunks(
+        &mut self,
+        a: Variable,
+        num_bits: usize,
+        chunk_bit_len: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let bits = self.witness(a)?.into_repr().to_bits_le();
+        let len = (num_bits + chunk_bit_len - 1) / chunk_bit_len;
+        let chunks = (0..len)
+            .map(|i| {
+                let (start, end) = (i * chunk_bit_len, min((i + 1) * chunk_bit_len, num_bits));
+                let value = bits[start..end].iter().rev().fold(F::zero(), |acc, &bit| {
+                    acc.double() + if bit { F::one() } else { F::zero() }
+                });
+                let chunk = self.create_variable(value)?;
+                if chunk_bit_len == 1 {
+                    self.bool_gate(chunk)?;
+                } else {
+                    self.enforce_in_range(chunk, end - start)?;
+                }
+                Ok(chunk)
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.decompose_vars_gate(chunks.clone(), a, F::from(1u64 << chunk_bit_len))?;
+        Ok(chunks)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    // The witnesses are the public input x, the private input y, z = x * y,
+    // a = x & y, b = x ^ y, the return value r = 2 * x^2 + y^2 + x + 3 and
+    // s = x + y + z + a + b, with x and y of 8 bits.
+    fn acir_circuit<F: PrimeField>() -> AcirCircuit<F> {
+        let w = Witness;
+        let expr = |mul_terms: &[(i64, u32, u32)], linear: &[(i64, u32)], q_c: i64| {
+            let field = |v: i64| {
+                if v < 0 {
+                    -F::from(v.unsigned_abs())
+                } else {
+                    F::from(v as u64)
+                }
+            };
+            Opcode::AssertZero(Expression {
+                mul_terms: mul_terms
+                    .iter()
+                    .map(|&(q, l, r)| (field(q), w(l), w(r)))
+                    .collect(),
+                linear_combinations: linear.iter().map(|&(q, v)| (field(q), w(v))).collect(),
+                q_c: field(q_c),
+            })
+        };
+        AcirCircuit {
+            current_witness_index: 6,
+            opcodes: vec![
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Range {
+                    input: w(0),
+                    num_bits: 8,
+                }),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Range {
+                    input: w(1),
+                    num_bits: 8,
+                }),
+                expr(&[(1, 0, 1)], &[(-1, 2)], 0),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::And {
+                    lhs: w(0),
+                    rhs: w(1),
+                    output: w(3),
+                    num_bits: 8,
+                }),
+                Opcode::BlackBoxFuncCall(BlackBoxFuncCall::Xor {
+                    lhs: w(0),
+                    rhs: w(1),
+                    output: w(4),
+                    num_bits: 8,
+                }),
+                expr(&[(2, 0, 0), (1, 1, 1)], &[(1, 0), (-1, 5)], 3),
+                expr(&[], &[(1, 0), (1, 1), (1, 2), (1, 3), (1, 4), (-1, 6)], 0),
+            ],
+            public_parameters: [w(0)].iter().copied().collect(),
+            return_values: [w(5)].iter().copied().collect(),
+        }
+    }
+
+    fn acir_witness<F: PrimeField>(x: u64, y: u64) -> BTreeMap<Witness, F> {
+        let values = [
+            x,
+            y,
+            x * y,
+            x & y,
+            x ^ y,
+            2 * x * x + y * y + x + 3,
+            x + y + x * y + (x & y) + (x ^ y),
+        ];
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| (Witness(i as u32), F::from(v)))
+            .collect()
+    }
+
+    #[test]
+    fn test_import_acir() -> Result<(), PlonkError> {
+        test_import_acir_helper::<FqEd254>()?;
+        test_import_acir_helper::<FqEd377>()?;
+        test_import_acir_helper::<FqEd381>()?;
+        test_import_acir_helper::<Fq377>()
+    }
+
+    fn test_import_acir_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let acir = acir_circuit::<F>();
+        let witness = acir_witness::<F>(202, 86);
+        let public_inputs = [witness[&Witness(0)], witness[&Witness(5)]];
+        assert_eq!(public_inputs[1], F::from(89209u32));
+
+        for lookup in [false, true] {
+            let new_circuit = || match lookup {
+                false => PlonkCircuit::<F>::new_turbo_plonk(),
+                true => PlonkCircuit::<F>::new_ultra_plonk(8),
+            };
+            let mut circuit = new_circuit();
+            let vars = circuit.import_acir(&acir, Some(&witness))?;
+            assert_eq!(vars.len(), 7);
+            assert_eq!(circuit.num_inputs(), 2);
+            assert_eq!(circuit.witness(vars[3])?, F::from(66u32));
+            assert_eq!(circuit.witness(vars[4])?, F::from(156u32));
+            assert!(circuit.check_circuit_satisfiability(&public_inputs).is_ok());
+            assert!(circuit
+                .check_circuit_satisfiability(&[public_inputs[0], F::zero()])
+                .is_err());
+
+            // wrong results of the opcodes
+            for &var in vars[2..].iter() {
+                let mut bad_circuit = circuit.clone();
+                *bad_circuit.witness_mut(var) += F::one();
+                assert!(bad_circuit
+                    .check_circuit_satisfiability(&public_inputs)
+                    .is_err());
+            }
+
+            // an operand out of range, with consistent results
+            let bad_witness = acir_witness::<F>(300, 86);
+            let mut bad_circuit = new_circuit();
+            bad_circuit.import_acir(&acir, Some(&bad_witness))?;
+            assert!(bad_circuit
+                .check_circuit_satisfiability(&[bad_witness[&Witness(0)], bad_witness[&Witness(5)]])
+                .is_err());
+        }
+
+        // the shape does not depend on the witness
+        let mut shape = PlonkCircuit::<F>::new_ultra_plonk_shape_only(8);
+        shape.import_acir(&acir, None)?;
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        circuit.import_acir(&acir, Some(&witness))?;
+        assert_eq!(shape.size()?, circuit.size()?);
+
+        // invalid opcodes
+        let mut bad_acir = acir.clone();
+        bad_acir.current_witness_index = 5;
+        assert!(circuit.import_acir(&bad_acir, Some(&witness)).is_err());
+        let mut bad_acir = acir;
+        bad_acir.opcodes = vec![Opcode::BlackBoxFuncCall(BlackBoxFuncCall::And {
+            lhs: Witness(0),
+            rhs: Witness(1),
+            output: Witness(3),
+            num_bits: 0,
+        })];
+        assert!(circuit.import_acir(&bad_acir, Some(&witness)).is_err());
+        Ok(())
+    }
+}
@@ -7,21 +7,28 @@
 //! Basic instantiations of Plonk-based constraint systems
 use super::{Arithmetization, Circuit, GateId, Variable, WireId};
 use crate::{
-    circuit::{gates::*, SortedLookupVecAndPolys},
+    circuit::{
+        customized::next_multiple,
+        gates::*,
+        profile::{CircuitSize, ConstraintProfile, NamespaceCost},
+        SortedLookupVecAndPolys,
+    },
     constants::{compute_coset_representatives, GATE_WIDTH, N_MUL_SELECTORS},
     errors::{CircuitError::*, PlonkError},
     MergeableCircuitType, PlonkType,
 };
-use ark_ff::{FftField, PrimeField};
+use ark_ff::{FftField, Field, PrimeField};
 use ark_poly::{
     domain::Radix2EvaluationDomain, univariate::DensePolynomial, EvaluationDomain, UVPolynomial,
 };
+use ark_serialize::*;
 use ark_std::{
     boxed::Box,
-    cmp::max,
+    cmp::{max, min},
     collections::{HashMap, HashSet},
-    format,
-    string::ToString,
+    fmt::{self, Display, Formatter},
+    format, mem,
+    string::{String, ToString},
     vec,
     vec::Vec,
 };
@@ -36,23 +43,33 @@ struct PlonkParams {
     /// The Plonk type of the circuit.
     plonk_type: PlonkType,
 
-    /// The bit length of a range-check. None for TurboPlonk.
+    /// The bit length of a range-check. None for TurboPlonk, and for an
+    /// UltraPlonk circuit with an automatic range table size until it is
+    /// finalized.
     range_bit_len: Option<usize>,
 }
 
 impl PlonkParams {
-    fn init(plonk_type: PlonkType, range_bit_len: Option<usize>) -> Result<Self, PlonkError> {
+    fn init(
+        plonk_type: PlonkType,
+        range_table_size: Option<RangeTableSize>,
+    ) -> Result<Self, PlonkError> {
         if plonk_type == PlonkType::TurboPlonk {
             return Ok(Self {
                 plonk_type,
                 range_bit_len: None,
             });
         }
-        if range_bit_len.is_none() {
-            return Err(
-                ParameterError("range bit len cannot be none for UltraPlonk".to_string()).into(),
-            );
-        }
+        let range_bit_len = match range_table_size {
+            Some(RangeTableSize::Fixed(range_bit_len)) => Some(range_bit_len),
+            Some(RangeTableSize::Auto) => None,
+            None => {
+                return Err(ParameterError(
+                    "range table size cannot be none for UltraPlonk".to_string(),
+                )
+                .into())
+            },
+        };
 
         Ok(Self {
             plonk_type,
@@ -61,6 +78,470 @@ impl PlonkParams {
     }
 }
 
+/// The largest bit length of the range table chosen for
+/// `RangeTableSize::Auto`.
+pub const MAX_AUTO_RANGE_BIT_LEN: usize = 16;
+
+/// The size of the range table of an UltraPlonk circuit, i.e. the bit length
+/// of its range gates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RangeTableSize {
+    /// A range table of `2^range_bit_len` elements.
+    Fixed(usize),
+    /// A range table whose bit length is chosen, between 1 and
+    /// `MAX_AUTO_RANGE_BIT_LEN`, when the circuit is finalized, so as to
+    /// minimize the evaluation domain given the range checks requested with
+    /// `range_gate` and `enforce_in_range`. These are recorded until then.
+    /// The gadgets which depend on the bit length of the range table, e.g.
+    /// the non-native arithmetic or the integer ones, choose it earlier with
+    /// `PlonkCircuit::resolve_range_bit_len`, which sets it to
+    /// `MAX_AUTO_RANGE_BIT_LEN`.
+    Auto,
+}
+
+impl From<usize> for RangeTableSize {
+    fn from(range_bit_len: usize) -> Self {
+        RangeTableSize::Fixed(range_bit_len)
+    }
+}
+
+/// The default maximum number of violations collected by
+/// `PlonkCircuit::satisfiability_report`.
+pub const DEFAULT_MAX_VIOLATIONS: usize = 64;
+
+/// The kind of a constraint violated by a witness, along with the values
+/// that caused the violation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ViolationKind<F> {
+    /// An algebraic gate named `gate`, whose computed output `q_o * w_o`
+    /// differs from the `expected` output derived from the input wires, the
+    /// selectors and the public input.
+    Gate {
+        /// The name of the gate.
+        gate: &'static str,
+        /// The computed gate output.
+        computed: F,
+        /// The expected gate output.
+        expected: F,
+    },
+    /// A range gate whose `value` is not in [0, `range_size`).
+    Range {
+        /// The value of the range-checked variable.
+        value: F,
+        /// The range size.
+        range_size: usize,
+    },
+    /// A range check deferred until the range table size is chosen, c.f.
+    /// `RangeTableSize::Auto`, whose `value` is not in [0, 2^`bit_len`).
+    DeferredRange {
+        /// The value of the range-checked variable.
+        value: F,
+        /// The bit length of the range.
+        bit_len: usize,
+    },
+    /// A lookup gate whose (key, val0, val1) tuple is not in the table.
+    Lookup {
+        /// The looked up key.
+        key: F,
+        /// The first looked up value.
+        val0: F,
+        /// The second looked up value.
+        val1: F,
+    },
+}
+
+/// A violated constraint of a circuit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GateViolation<F> {
+    /// The index of the violated gate. For range gates, this is the index of
+    /// the range gate among all range gates.
+    pub gate_id: GateId,
+    /// The namespace in which the gate was inserted, e.g. `"ecdsa/msm"`, or
+    /// the empty string for the root namespace.
+    pub namespace: String,
+    /// The kind of the violation.
+    pub kind: ViolationKind<F>,
+}
+
+/// The evaluation of the terms of an algebraic gate on the witness, e.g. to
+/// find the wire which makes an unsatisfied gate fail. The gate is satisfied
+/// iff `computed == expected`, where
+/// `expected = pub_input + sum(lc_terms) + sum(mul_terms) + sum(hash_terms) +
+/// ecc_term + constant` and `computed = q_o * w_o`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GateDiagnostics<F> {
+    /// The index of the gate.
+    pub gate_id: GateId,
+    /// The name of the gate.
+    pub gate: &'static str,
+    /// The namespace in which the gate was inserted.
+    pub namespace: String,
+    /// The variables of the 4 input wires and of the output wire.
+    pub wire_vars: [Variable; GATE_WIDTH + 1],
+    /// The values of the wires.
+    pub wire_values: [F; GATE_WIDTH + 1],
+    /// The public input value of the gate, zero if it is not an IO gate.
+    pub pub_input: F,
+    /// The linear combination terms `q_lc_i * w_i`.
+    pub lc_terms: [F; GATE_WIDTH],
+    /// The multiplication terms `q_mul0 * w0 * w1` and `q_mul1 * w2 * w3`.
+    pub mul_terms: [F; N_MUL_SELECTORS],
+    /// The hash terms `q_hash_i * w_i^5`.
+    pub hash_terms: [F; GATE_WIDTH],
+    /// The elliptic curve term `q_ecc * w0 * w1 * w2 * w3 * wo`.
+    pub ecc_term: F,
+    /// The constant selector `q_c`.
+    pub constant: F,
+    /// The expected gate output, i.e. the sum of the terms.
+    pub expected: F,
+    /// The computed gate output `q_o * w_o`.
+    pub computed: F,
+}
+
+impl<F: PartialEq> GateDiagnostics<F> {
+    /// Return true if the gate is satisfied.
+    pub fn is_satisfied(&self) -> bool {
+        self.expected == self.computed
+    }
+}
+
+impl<F: Display> Display for GateDiagnostics<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let list = |values: &[F]| {
+            values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        write!(
+            f,
+            "gate: {}, wire variables: {:?}, wire values: [{}], pub_input: {}, \
+             q_lc * w: [{}], q_mul * w * w: [{}], q_hash * w^5: [{}], q_ecc term: {}, q_c: {}, \
+             expected_gate_output: {}, gate_output: {}",
+            self.gate,
+            self.wire_vars,
+            list(&self.wire_values),
+            self.pub_input,
+            list(&self.lc_terms),
+            list(&self.mul_terms),
+            list(&self.hash_terms),
+            self.ecc_term,
+            self.constant,
+            self.expected,
+            self.computed
+        )
+    }
+}
+
+/// A structured report of the constraints violated by a circuit witness.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SatisfiabilityReport<F> {
+    /// The violations, in the order algebraic gates (public input gates
+    /// first), range gates and lookup gates are checked.
+    pub violations: Vec<GateViolation<F>>,
+    /// Whether some violations are omitted because the maximum number of
+    /// reported violations is reached.
+    pub truncated: bool,
+}
+
+impl<F> SatisfiabilityReport<F> {
+    /// Return true if no violation is found.
+    pub fn is_satisfied(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// The gates removed or rewritten by `PlonkCircuit::optimize`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OptimizationReport {
+    /// The number of gates before the optimization.
+    pub num_gates_before: usize,
+    /// The number of gates after the optimization.
+    pub num_gates_after: usize,
+    /// The number of gates whose constant inputs are folded into their
+    /// selectors.
+    pub num_folded_gates: usize,
+    /// The number of equality gates replaced by merging their variables.
+    pub num_merged_equality_gates: usize,
+    /// The number of gates removed as duplicates of another gate.
+    pub num_duplicate_gates: usize,
+    /// The number of gates removed as they are trivially satisfied once
+    /// folded, or as they constrain constants which are no longer used.
+    pub num_dead_gates: usize,
+}
+
+/// The kinds of the gates of a compiled circuit which are told apart by the
+/// circuit, the other gates being reloaded as `CompiledGate`s.
+const COMPILED_GATE: u8 = 0;
+const COMPILED_PADDING_GATE: u8 = 1;
+const COMPILED_IO_GATE: u8 = 2;
+const COMPILED_CONSTANT_GATE: u8 = 3;
+
+/// The constraint system of a finalized circuit without its witness, i.e.
+/// its selectors, wires, permutation and lookup tables, so that a circuit can
+/// be compiled once, serialized, e.g. cached to disk, and reloaded with
+/// `PlonkCircuit::from_compiled` instead of being synthesized again.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct CompiledCircuit<F: PrimeField> {
+    // 0 for TurboPlonk and 1 for UltraPlonk
+    plonk_type: u8,
+    range_bit_len: Option<usize>,
+    num_vars: usize,
+    eval_domain_size: usize,
+    // the kind of each gate, and the selectors in the order of the selector
+    // polynomials
+    gate_kinds: Vec<u8>,
+    selectors: Vec<Vec<F>>,
+    // the variable of each wire of each wire type, and the permutation
+    wire_variables: Vec<Vec<Variable>>,
+    wire_permutation: Vec<(WireId, GateId)>,
+    pub_input_gate_ids: Vec<GateId>,
+    // the UTF-8 names of the named public inputs with their positions
+    pub_input_names: Vec<(Vec<u8>, usize)>,
+    num_table_elems: usize,
+}
+
+impl<F: PrimeField> CompiledCircuit<F> {
+    /// The number of variables of the circuit.
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// The number of public inputs of the circuit.
+    pub fn num_inputs(&self) -> usize {
+        self.pub_input_gate_ids.len()
+    }
+
+    /// The size of the evaluation domain of the circuit.
+    pub fn eval_domain_size(&self) -> usize {
+        self.eval_domain_size
+    }
+}
+
+/// The witness of a circuit, i.e. the values of all its variables by index,
+/// generated separately from the proof, e.g. on the device of a user, c.f.
+/// `generate_witness`.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct WitnessVector<F: PrimeField> {
+    values: Vec<F>,
+}
+
+impl<F: PrimeField> WitnessVector<F> {
+    /// The number of variables.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Whether the witness is empty, which is never the case of the witness
+    /// of a circuit.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// The values of the variables.
+    pub fn values(&self) -> &[F] {
+        &self.values
+    }
+}
+
+/// Synthesize a circuit with `synthesize` on the inputs `inputs`, starting
+/// from the empty circuit `circuit`, e.g. `PlonkCircuit::new_turbo_plonk()`,
+/// and return its witness once finalized. The synthesis has to be that of the
+/// circuit which the witness is proven for, c.f.
+/// `PlonkKzgSnark::prove_with_witness`.
+/// Return error if the synthesis fails, or if the circuit is in shape-only
+/// mode.
+pub fn generate_witness<F, I, S>(
+    mut circuit: PlonkCircuit<F>,
+    synthesize: S,
+    inputs: I,
+) -> Result<WitnessVector<F>, PlonkError>
+where
+    F: PrimeField,
+    S: FnOnce(&mut PlonkCircuit<F>, I) -> Result<(), PlonkError>,
+{
+    synthesize(&mut circuit, inputs)?;
+    circuit.finalize_for_arithmetization()?;
+    circuit.witness_vector()
+}
+
+/// The classes of the variables constrained to be equal, each with the
+/// constant it is constrained to, if any. The root of a class is its smallest
+/// variable, so that the constants zero and one stay the variables `0` and
+/// `1`.
+struct VarClasses<F> {
+    parent: Vec<Variable>,
+    constants: Vec<Option<F>>,
+    // a variable of the class of each constant
+    by_constant: HashMap<F, Variable>,
+}
+
+impl<F: Field> VarClasses<F> {
+    fn new(num_vars: usize) -> Self {
+        Self {
+            parent: (0..num_vars).collect(),
+            constants: vec![None; num_vars],
+            by_constant: HashMap::new(),
+        }
+    }
+
+    fn find(&mut self, mut var: Variable) -> Variable {
+        while self.parent[var] != var {
+            self.parent[var] = self.parent[self.parent[var]];
+            var = self.parent[var];
+        }
+        var
+    }
+
+    // The constant of the class of `var`, if any.
+    fn constant(&mut self, var: Variable) -> Option<F> {
+        let root = self.find(var);
+        self.constants[root]
+    }
+
+    // Merge the classes of `a` and `b`, unless they are constrained to
+    // different constants. Return whether they are merged.
+    fn union(&mut self, a: Variable, b: Variable) -> bool {
+        let (a, b) = (self.find(a), self.find(b));
+        if a == b {
+            return true;
+        }
+        if let (Some(x), Some(y)) = (self.constants[a], self.constants[b]) {
+            if x != y {
+                return false;
+            }
+        }
+        let (root, child) = (min(a, b), max(a, b));
+        self.parent[child] = root;
+        self.constants[root] = self.constants[root].or(self.constants[child]);
+        true
+    }
+
+    // Constrain the class of `var` to `value`, merging it with the class of
+    // the other variables constrained to `value`. Return whether the class is
+    // not constrained to another constant.
+    fn set_constant(&mut self, var: Variable, value: F) -> bool {
+        match self.by_constant.get(&value) {
+            Some(&other) => self.union(var, other),
+            None => {
+                let root = self.find(var);
+                match self.constants[root] {
+                    Some(constant) => constant == value,
+                    None => {
+                        self.constants[root] = Some(value);
+                        self.by_constant.insert(value, var);
+                        true
+                    },
+                }
+            },
+        }
+    }
+}
+
+/// The separator of the labels of a namespace path.
+pub(crate) const NAMESPACE_SEPARATOR: char = '/';
+
+/// The hierarchical namespaces in which the gates of a circuit are inserted.
+/// A namespace is identified by its index in `paths`, the root namespace
+/// being the index 0 with the empty path.
+#[derive(Debug, Clone)]
+struct Namespaces {
+    /// The paths of the namespaces, e.g. `"ecdsa/msm"`.
+    paths: Vec<String>,
+    /// The index of each path.
+    ids: HashMap<String, usize>,
+    /// The namespaces pushed and not yet popped, innermost last.
+    stack: Vec<usize>,
+    /// The namespace of each algebraic/lookup gate.
+    gates: Vec<usize>,
+    /// The namespace of each range gate.
+    range_gates: Vec<usize>,
+}
+
+impl Namespaces {
+    fn new() -> Self {
+        Self {
+            paths: vec![String::new()],
+            ids: HashMap::new(),
+            stack: vec![],
+            gates: vec![],
+            range_gates: vec![],
+        }
+    }
+
+    fn current(&self) -> usize {
+        self.stack.last().copied().unwrap_or(0)
+    }
+
+    // The index of a path, which is added if it is new.
+    fn intern(&mut self, path: &str) -> usize {
+        if path.is_empty() {
+            return 0;
+        }
+        if let Some(&id) = self.ids.get(path) {
+            return id;
+        }
+        self.paths.push(path.to_string());
+        self.ids.insert(path.to_string(), self.paths.len() - 1);
+        self.paths.len() - 1
+    }
+
+    // The index of the namespace at the relative path `path` from the
+    // namespace `parent`, which is added if it is new.
+    fn descendant(&mut self, parent: usize, path: &str) -> usize {
+        let parent = &self.paths[parent];
+        let path = if parent.is_empty() {
+            path.to_string()
+        } else if path.is_empty() {
+            parent.clone()
+        } else {
+            format!("{}{}{}", parent, NAMESPACE_SEPARATOR, path)
+        };
+        self.intern(&path)
+    }
+
+    // The path of the namespace of a gate, which is the root namespace for
+    // the gates added when the circuit is finalized.
+    fn gate_path(&self, gate_id: GateId) -> &str {
+        &self.paths[self.gate_id(gate_id)]
+    }
+
+    fn range_gate_path(&self, idx: usize) -> &str {
+        &self.paths[self.range_gates.get(idx).copied().unwrap_or(0)]
+    }
+
+    fn gate_id(&self, gate_id: GateId) -> usize {
+        self.gates.get(gate_id).copied().unwrap_or(0)
+    }
+
+    // The namespaces of the merge of the first `n` gates of a circuit with
+    // the gates of `other` from the `n`-th one.
+    fn merge(&self, other: &Self, n: usize) -> Self {
+        let mut merged = Self::new();
+        for gate_id in 0..2 * n {
+            let path = if gate_id < n {
+                self.gate_path(gate_id)
+            } else {
+                other.gate_path(gate_id)
+            };
+            let id = merged.intern(path);
+            merged.gates.push(id);
+        }
+        merged
+    }
+}
+
+/// Prefix the message of a gate check failure with the namespace of the gate,
+/// if it is not the root namespace.
+fn with_namespace_msg(namespace: &str, msg: String) -> String {
+    if namespace.is_empty() {
+        msg
+    } else {
+        format!("namespace: {}, {}", namespace, msg)
+    }
+}
+
 /// A specific Plonk circuit instantiation.
 #[derive(Debug, Clone)]
 pub struct PlonkCircuit<F>
@@ -76,6 +557,8 @@ where
     wire_variables: [Vec<Variable>; GATE_WIDTH + 2],
     /// The IO gates for the list of public input variables.
     pub_input_gate_ids: Vec<GateId>,
+    /// The position among the public inputs of each named public input.
+    pub_input_names: HashMap<String, usize>,
     /// The actual values of variables.
     witness: Vec<F>,
 
@@ -113,6 +596,21 @@ where
     /// For each inserted table, the 1st value is the start id of the table,
     /// the 2nd values is the length of the table.
     table_gate_ids: Vec<(GateId, usize)>,
+
+    /// The bit length of the operands of the inserted bitwise logic table,
+    /// together with the key of its first element.
+    logic_table: Option<(usize, usize)>,
+
+    /// The namespaces of the gates, for debugging.
+    namespaces: Namespaces,
+
+    /// Whether the circuit only records its shape, i.e. its gates and wires,
+    /// and drops the values of its non-constant variables.
+    shape_only: bool,
+
+    /// The range checks `(var, bit_len, namespace)` recorded until the range
+    /// table size is chosen, c.f. `RangeTableSize::Auto`.
+    range_requests: Vec<(Variable, usize, usize)>,
 }
 
 impl<F: FftField> Default for PlonkCircuit<F> {
@@ -134,6 +632,7 @@ impl<F: FftField> PlonkCircuit<F> {
             // size is `num_wire_types`
             wire_variables: [vec![], vec![], vec![], vec![], vec![], vec![]],
             pub_input_gate_ids: vec![],
+            pub_input_names: HashMap::new(),
 
             wire_permutation: vec![],
             extended_id_permutation: vec![],
@@ -147,6 +646,10 @@ impl<F: FftField> PlonkCircuit<F> {
             plonk_params,
             num_table_elems: 0,
             table_gate_ids: vec![],
+            logic_table: None,
+            namespaces: Namespaces::new(),
+            shape_only: false,
+            range_requests: vec![],
         };
         // Constrain variables `0`/`1` to have value 0/1.
         circuit.constant_gate(0, zero).unwrap(); // safe unwrap
@@ -160,12 +663,85 @@ impl<F: FftField> PlonkCircuit<F> {
         Self::new(plonk_params)
     }
 
-    /// Construct a new UltraPlonk circuit.
-    pub fn new_ultra_plonk(range_bit_len: usize) -> Self {
-        let plonk_params = PlonkParams::init(PlonkType::UltraPlonk, Some(range_bit_len)).unwrap(); // safe unwrap
+    /// Construct a new UltraPlonk circuit whose range table has the size
+    /// `range_table_size`, e.g. a bit length `8` or `RangeTableSize::Auto`.
+    pub fn new_ultra_plonk<T: Into<RangeTableSize>>(range_table_size: T) -> Self {
+        let plonk_params =
+            PlonkParams::init(PlonkType::UltraPlonk, Some(range_table_size.into())).unwrap(); // safe unwrap
         Self::new(plonk_params)
     }
 
+    /// Construct a new TurboPlonk circuit in shape-only mode, c.f.
+    /// `is_shape_only`.
+    pub fn new_turbo_plonk_shape_only() -> Self {
+        let mut circuit = Self::new_turbo_plonk();
+        circuit.shape_only = true;
+        circuit
+    }
+
+    /// Construct a new UltraPlonk circuit in shape-only mode, c.f.
+    /// `is_shape_only`.
+    pub fn new_ultra_plonk_shape_only<T: Into<RangeTableSize>>(range_table_size: T) -> Self {
+        let mut circuit = Self::new_ultra_plonk(range_table_size);
+        circuit.shape_only = true;
+        circuit
+    }
+
+    /// Whether the circuit is in shape-only mode, in which the gates, lookups
+    /// and variables of a circuit description are built as usual, but the
+    /// values given to `create_variable` are dropped and every non-constant
+    /// variable takes the value zero. The gadgets can thus be synthesized
+    /// on arbitrary inputs, e.g. zeros, to obtain the `size` of the circuit,
+    /// and the circuit can be preprocessed into proving and verifying keys
+    /// since these do not depend on the witness, but it cannot be checked
+    /// nor proven. A gadget which rejects the all-zero witness, e.g. by
+    /// inverting one of its inputs, fails in this mode.
+    pub fn is_shape_only(&self) -> bool {
+        self.shape_only
+    }
+
+    /// Set the variable `var` public as `set_variable_public` does, under the
+    /// name `name`, so that its position among the public inputs can be
+    /// retrieved from the circuit and from its verifying key rather than
+    /// maintained by hand.
+    /// Return error if the name is already used, or if the variable is
+    /// invalid.
+    pub fn set_variable_public_named(
+        &mut self,
+        name: &str,
+        var: Variable,
+    ) -> Result<(), PlonkError> {
+        if self.pub_input_names.contains_key(name) {
+            return Err(
+                ParameterError(format!("the public input name {} is already used", name)).into(),
+            );
+        }
+        self.check_var_bound(var)?;
+        let position = self.num_inputs();
+        self.set_variable_public(var)?;
+        self.pub_input_names.insert(name.to_string(), position);
+        Ok(())
+    }
+
+    /// Create a public variable named `name`, c.f.
+    /// `set_variable_public_named`.
+    /// Return error if the name is already used.
+    pub fn create_named_public_variable(
+        &mut self,
+        name: &str,
+        val: F,
+    ) -> Result<Variable, PlonkError> {
+        let var = self.create_variable(val)?;
+        self.set_variable_public_named(name, var)?;
+        Ok(var)
+    }
+
+    /// The position among the public inputs of the public input named
+    /// `name`, if any.
+    pub fn public_input_position(&self, name: &str) -> Option<usize> {
+        self.pub_input_names.get(name).copied()
+    }
+
     /// Insert a general (algebraic) gate
     /// * `wire_vars` - wire variables. Each of these variables must be in range
     /// * `gate` - specific gate to be inserted
@@ -185,6 +761,8 @@ impl<F: FftField> PlonkCircuit<F> {
         }
 
         self.gates.push(gate);
+        let namespace = self.namespaces.current();
+        self.namespaces.gates.push(namespace);
         Ok(())
     }
 
@@ -196,9 +774,32 @@ impl<F: FftField> PlonkCircuit<F> {
         self.check_finalize_flag(false)?;
         self.check_var_bound(var)?;
         self.wire_variables[RANGE_WIRE_ID].push(var);
+        let namespace = self.namespaces.current();
+        self.namespaces.range_gates.push(namespace);
+        Ok(())
+    }
+
+    /// Record the range check of a variable to [0, 2^`bit_len`), which is
+    /// expanded into range gates once the range table size is chosen, c.f.
+    /// `RangeTableSize::Auto`.
+    pub(crate) fn defer_range_check(
+        &mut self,
+        var: Variable,
+        bit_len: usize,
+    ) -> Result<(), PlonkError> {
+        self.check_finalize_flag(false)?;
+        self.check_var_bound(var)?;
+        let namespace = self.namespaces.current();
+        self.range_requests.push((var, bit_len, namespace));
         Ok(())
     }
 
+    /// Whether the range table size of the circuit is not chosen yet, c.f.
+    /// `RangeTableSize::Auto`.
+    pub(crate) fn is_range_bit_len_deferred(&self) -> bool {
+        self.support_lookup() && self.plonk_params.range_bit_len.is_none()
+    }
+
     #[inline]
     /// Checks if a variable is strictly less than the number of variables.
     /// This function must be invoked for each gate as this check is not applied
@@ -246,7 +847,15 @@ impl<F: FftField> PlonkCircuit<F> {
         self.num_table_elems
     }
 
+    /// Get the mutable reference of the inserted bitwise logic table.
+    pub(crate) fn logic_table_mut(&mut self) -> &mut Option<(usize, usize)> {
+        &mut self.logic_table
+    }
+
     /// The bit length of UltraPlonk range gates.
+    /// Return error if the circuit is not an UltraPlonk one, or if its range
+    /// table size is chosen automatically and it is not chosen yet, c.f.
+    /// `resolve_range_bit_len`.
     pub fn range_bit_len(&self) -> Result<usize, PlonkError> {
         if self.plonk_params.plonk_type != PlonkType::UltraPlonk {
             return Err(ParameterError(
@@ -254,13 +863,303 @@ impl<F: FftField> PlonkCircuit<F> {
             )
             .into());
         }
-        Ok(self.plonk_params.range_bit_len.unwrap()) // safe unwrap
+        self.plonk_params.range_bit_len.ok_or_else(|| {
+            ParameterError(
+                "the automatic range table size is chosen when the circuit is finalized"
+                    .to_string(),
+            )
+            .into()
+        })
     }
 
     /// The range size of UltraPlonk range gates.
     pub fn range_size(&self) -> Result<usize, PlonkError> {
         Ok(1 << self.range_bit_len()?)
     }
+
+    /// Enter the namespace `label` within the current namespace, so that the
+    /// gates inserted until the matching `pop_namespace` are reported under
+    /// the path of the namespace, e.g. `"ecdsa/msm"`, when they are not
+    /// satisfied.
+    pub fn push_namespace(&mut self, label: &str) {
+        let id = self.namespaces.descendant(self.namespaces.current(), label);
+        self.namespaces.stack.push(id);
+    }
+
+    /// Leave the current namespace for its parent.
+    /// Return error if no namespace is entered.
+    pub fn pop_namespace(&mut self) -> Result<(), PlonkError> {
+        self.namespaces
+            .stack
+            .pop()
+            .map(|_| ())
+            .ok_or_else(|| ParameterError("no namespace to pop".to_string()).into())
+    }
+
+    /// Run `f` within the namespace `label`, which is left even if `f`
+    /// returns an error.
+    pub fn with_namespace<R>(
+        &mut self,
+        label: &str,
+        f: impl FnOnce(&mut Self) -> Result<R, PlonkError>,
+    ) -> Result<R, PlonkError> {
+        self.push_namespace(label);
+        let res = f(self);
+        self.pop_namespace()?;
+        res
+    }
+
+    /// The path of the current namespace, or the empty string for the root
+    /// namespace.
+    pub fn namespace(&self) -> &str {
+        &self.namespaces.paths[self.namespaces.current()]
+    }
+
+    /// The path of the namespace in which the `gate_id`-th gate was inserted.
+    /// Return error if the gate does not exist.
+    pub fn gate_namespace(&self, gate_id: GateId) -> Result<&str, PlonkError> {
+        if gate_id >= self.num_gates() {
+            return Err(IndexError.into());
+        }
+        Ok(self.namespaces.gate_path(gate_id))
+    }
+
+    /// Return the numbers of gates inserted in each namespace, c.f.
+    /// `push_namespace`, e.g. to find the gadgets which dominate the size of
+    /// the circuit.
+    pub fn constraint_profile(&self) -> ConstraintProfile {
+        let mut costs: Vec<NamespaceCost> = self
+            .namespaces
+            .paths
+            .iter()
+            .map(|path| NamespaceCost {
+                path: path.clone(),
+                ..NamespaceCost::default()
+            })
+            .collect();
+        for (gate_id, gate) in self.gates.iter().enumerate() {
+            if gate.as_any().is::<PaddingGate>() {
+                continue;
+            }
+            let cost = &mut costs[self.namespaces.gate_id(gate_id)];
+            cost.num_gates += 1;
+            if !gate.q_lookup().is_zero() {
+                cost.num_lookup_gates += 1;
+            }
+        }
+        for &id in self.namespaces.range_gates.iter() {
+            costs[id].num_range_gates += 1;
+        }
+        let mut namespaces: Vec<NamespaceCost> = costs
+            .into_iter()
+            .filter(|cost| cost.num_gates + cost.num_range_gates > 0)
+            .collect();
+        namespaces.sort_by(|a, b| a.path.cmp(&b.path));
+        ConstraintProfile { namespaces }
+    }
+
+    /// Return the variables which are not constrained by any gate, in
+    /// increasing order. A variable is constrained if it is on a wire of a
+    /// gate whose selectors involve the wire, or if it is range checked; a
+    /// variable which is only on wires that its gates ignore, e.g. on a
+    /// padding gate, can take any value in a satisfying witness, which is
+    /// usually a soundness bug of the gadget that created it.
+    pub fn unconstrained_variables(&self) -> Vec<Variable> {
+        let mut constrained = vec![false; self.num_vars];
+        for (gate_id, gate) in self.gates.iter().enumerate() {
+            let q_lc = gate.q_lc();
+            let q_hash = gate.q_hash();
+            let q_mul = gate.q_mul();
+            let q_ecc = !gate.q_ecc().is_zero();
+            let q_lookup = !gate.q_lookup().is_zero();
+            for i in 0..GATE_WIDTH + 1 {
+                let is_used = q_ecc
+                    || q_lookup
+                    || if i < GATE_WIDTH {
+                        !q_lc[i].is_zero() || !q_hash[i].is_zero() || !q_mul[i / 2].is_zero()
+                    } else {
+                        !gate.q_o().is_zero()
+                    };
+                if is_used {
+                    constrained[self.wire_variable(i, gate_id)] = true;
+                }
+            }
+        }
+        for &var in self.wire_variables[RANGE_WIRE_ID].iter() {
+            constrained[var] = true;
+        }
+        for &(var, ..) in self.range_requests.iter() {
+            constrained[var] = true;
+        }
+        constrained
+            .iter()
+            .enumerate()
+            .filter_map(|(var, &is_constrained)| (!is_constrained).then(|| var))
+            .collect()
+    }
+
+    /// Return the diagnostics of the `gate_id`-th algebraic gate, evaluated on
+    /// the witness and on the public input value `pub_input`, which is only
+    /// used by IO gates.
+    /// Return error if the gate does not exist.
+    pub fn gate_diagnostics(
+        &self,
+        gate_id: GateId,
+        pub_input: &F,
+    ) -> Result<GateDiagnostics<F>, PlonkError> {
+        if gate_id >= self.num_gates() {
+            return Err(IndexError.into());
+        }
+        Ok(self.gate_diagnostics_unchecked(gate_id, pub_input))
+    }
+
+    /// Return the diagnostics of the first unsatisfied algebraic gate, in the
+    /// order in which `check_circuit_satisfiability` checks them, or `None` if
+    /// all the algebraic gates are satisfied.
+    /// Return an error if the length of `pub_input` mismatches the number of
+    /// public inputs of the circuit, or if the circuit is shape-only.
+    pub fn first_unsatisfied_gate(
+        &self,
+        pub_input: &[F],
+    ) -> Result<Option<GateDiagnostics<F>>, PlonkError> {
+        self.check_witness_available()?;
+        if pub_input.len() != self.num_inputs() {
+            return Err(PubInputLenMismatch(pub_input.len(), self.pub_input_gate_ids.len()).into());
+        }
+        let io_gates = self
+            .pub_input_gate_ids
+            .iter()
+            .copied()
+            .zip(pub_input.iter().copied());
+        let other_gates = (0..self.num_gates())
+            .filter(|&gate_id| !self.is_io_gate(gate_id))
+            .map(|gate_id| (gate_id, F::zero()));
+        for (gate_id, pi) in io_gates.chain(other_gates) {
+            let (expected, computed) = self.gate_outputs(gate_id, &pi);
+            if expected != computed {
+                return Ok(Some(self.gate_diagnostics_unchecked(gate_id, &pi)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Check the circuit satisfiability like `check_circuit_satisfiability`,
+    /// but instead of stopping at the first unsatisfied gate, collect up to
+    /// `DEFAULT_MAX_VIOLATIONS` violated gates into a report.
+    /// Return an error if the length of `pub_input` mismatches the number of
+    /// public inputs of the circuit, or if the circuit is shape-only.
+    pub fn satisfiability_report(
+        &self,
+        pub_input: &[F],
+    ) -> Result<SatisfiabilityReport<F>, PlonkError> {
+        self.satisfiability_report_with_limit(pub_input, DEFAULT_MAX_VIOLATIONS)
+    }
+
+    /// Same as `satisfiability_report`, but collect up to `max_violations`
+    /// violated gates.
+    pub fn satisfiability_report_with_limit(
+        &self,
+        pub_input: &[F],
+        max_violations: usize,
+    ) -> Result<SatisfiabilityReport<F>, PlonkError> {
+        self.check_witness_available()?;
+        if pub_input.len() != self.num_inputs() {
+            return Err(PubInputLenMismatch(pub_input.len(), self.pub_input_gate_ids.len()).into());
+        }
+        let mut violations = Vec::new();
+        let mut truncated = false;
+        let mut report = |violation: GateViolation<F>| {
+            if violations.len() < max_violations {
+                violations.push(violation);
+            } else {
+                truncated = true;
+            }
+        };
+
+        // public I/O gates
+        for (i, gate_id) in self.pub_input_gate_ids.iter().enumerate() {
+            if let Some(kind) = self.gate_violation(*gate_id, &pub_input[i]) {
+                report(GateViolation {
+                    gate_id: *gate_id,
+                    namespace: self.namespaces.gate_path(*gate_id).to_string(),
+                    kind,
+                });
+            }
+        }
+        // rest of the gates
+        for gate_id in 0..self.num_gates() {
+            if !self.is_io_gate(gate_id) {
+                if let Some(kind) = self.gate_violation(gate_id, &F::zero()) {
+                    report(GateViolation {
+                        gate_id,
+                        namespace: self.namespaces.gate_path(gate_id).to_string(),
+                        kind,
+                    });
+                }
+            }
+        }
+        // range/lookup gates if the circuit supports lookup
+        if self.plonk_params.plonk_type == PlonkType::UltraPlonk {
+            // range gates
+            if !self.is_range_bit_len_deferred() {
+                let range_size = self.range_size()?;
+                for (idx, &var) in self.wire_variables[RANGE_WIRE_ID].iter().enumerate() {
+                    let value = self.witness[var];
+                    if value >= F::from(range_size as u32) {
+                        report(GateViolation {
+                            gate_id: idx,
+                            namespace: self.namespaces.range_gate_path(idx).to_string(),
+                            kind: ViolationKind::Range { value, range_size },
+                        });
+                    }
+                }
+            }
+            // deferred range checks
+            for (idx, &(var, bit_len, namespace)) in self.range_requests.iter().enumerate() {
+                let value = self.witness[var];
+                if value >= F::from(2u32).pow([bit_len as u64]) {
+                    report(GateViolation {
+                        gate_id: idx,
+                        namespace: self.namespaces.paths[namespace].clone(),
+                        kind: ViolationKind::DeferredRange { value, bit_len },
+                    });
+                }
+            }
+            // key-value map lookup gates
+            let mut key_val_table = HashSet::new();
+            key_val_table.insert((F::zero(), F::zero(), F::zero()));
+            let mut num_table_elems: u32 = 0;
+            let q_lookup_vec = self.q_lookup();
+            for (gate_id, &q_lookup) in q_lookup_vec.iter().enumerate() {
+                if q_lookup != F::zero() {
+                    let key = F::from(num_table_elems);
+                    let val0 = self.witness[self.wire_variable(3, gate_id)];
+                    let val1 = self.witness[self.wire_variable(4, gate_id)];
+                    key_val_table.insert((key, val0, val1));
+                    num_table_elems += 1;
+                }
+            }
+            for (gate_id, &q_lookup) in q_lookup_vec.iter().enumerate() {
+                if q_lookup != F::zero() {
+                    let key = self.witness[self.wire_variable(0, gate_id)];
+                    let val0 = self.witness[self.wire_variable(1, gate_id)];
+                    let val1 = self.witness[self.wire_variable(2, gate_id)];
+                    if !key_val_table.contains(&(key, val0, val1)) {
+                        report(GateViolation {
+                            gate_id,
+                            namespace: self.namespaces.gate_path(gate_id).to_string(),
+                            kind: ViolationKind::Lookup { key, val0, val1 },
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(SatisfiabilityReport {
+            violations,
+            truncated,
+        })
+    }
 }
 
 impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
@@ -291,6 +1190,7 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
     }
 
     fn check_circuit_satisfiability(&self, pub_input: &[F]) -> Result<(), PlonkError> {
+        self.check_witness_available()?;
         if pub_input.len() != self.num_inputs() {
             return Err(PubInputLenMismatch(pub_input.len(), self.pub_input_gate_ids.len()).into());
         }
@@ -312,6 +1212,20 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
             for idx in 0..self.wire_variables[RANGE_WIRE_ID].len() {
                 self.check_range_gate(idx)?
             }
+            // deferred range checks
+            for (idx, &(var, bit_len, namespace)) in self.range_requests.iter().enumerate() {
+                let value = self.witness[var];
+                if value >= F::from(2u32).pow([bit_len as u64]) {
+                    return Err(GateCheckFailure(
+                        idx,
+                        with_namespace_msg(
+                            &self.namespaces.paths[namespace],
+                            format!("Range check failed: {} >= 2^{}", value, bit_len),
+                        ),
+                    )
+                    .into());
+                }
+            }
             // key-value map lookup gates
             let mut key_val_table = HashSet::new();
             key_val_table.insert((F::zero(), F::zero(), F::zero()));
@@ -334,9 +1248,12 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
                     if !key_val_table.contains(&(key, val0, val1)) {
                         return Err(GateCheckFailure(
                             gate_id,
-                            format!(
-                                "Lookup gate failed: ({}, {}, {}) not in the table",
-                                key, val0, val1
+                            with_namespace_msg(
+                                self.namespaces.gate_path(gate_id),
+                                format!(
+                                    "Lookup gate failed: ({}, {}, {}) not in the table",
+                                    key, val0, val1
+                                ),
                             ),
                         )
                         .into());
@@ -348,17 +1265,16 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
     }
 
     fn create_constant_variable(&mut self, val: F) -> Result<Variable, PlonkError> {
-        let var = self.create_variable(val)?;
+        // the value of a constant is kept in shape-only mode since it is
+        // part of the circuit description
+        let var = self.push_variable(val)?;
         self.constant_gate(var, val)?;
         Ok(var)
     }
 
     fn create_variable(&mut self, val: F) -> Result<Variable, PlonkError> {
-        self.check_finalize_flag(false)?;
-        self.witness.push(val);
-        self.num_vars += 1;
-        // the index is from `0` to `num_vars - 1`
-        Ok(self.num_vars - 1)
+        let val = if self.shape_only { F::zero() } else { val };
+        self.push_variable(val)
     }
 
     fn create_public_variable(&mut self, val: F) -> Result<Variable, PlonkError> {
@@ -377,6 +1293,16 @@ impl<F: FftField> Circuit<F> for PlonkCircuit<F> {
         Ok(())
     }
 
+    fn public_input_names(&self) -> Vec<(String, usize)> {
+        let mut names: Vec<(String, usize)> = self
+            .pub_input_names
+            .iter()
+            .map(|(name, &position)| (name.clone(), position))
+            .collect();
+        names.sort_by_key(|&(_, position)| position);
+        names
+    }
+
     /// Default zero variable
     fn zero(&self) -> Variable {
         0
@@ -507,9 +1433,12 @@ impl<F: FftField> PlonkCircuit<F> {
         if self.witness[self.wire_variables[RANGE_WIRE_ID][idx]] >= F::from(range_size as u32) {
             return Err(GateCheckFailure(
                 idx,
-                format!(
-                    "Range gate failed: {} >= {}",
-                    self.witness[self.wire_variables[RANGE_WIRE_ID][idx]], range_size
+                with_namespace_msg(
+                    self.namespaces.range_gate_path(idx),
+                    format!(
+                        "Range gate failed: {} >= {}",
+                        self.witness[self.wire_variables[RANGE_WIRE_ID][idx]], range_size
+                    ),
                 ),
             )
             .into());
@@ -521,6 +1450,27 @@ impl<F: FftField> PlonkCircuit<F> {
         self.eval_domain.size() != 1
     }
 
+    // Create a variable with value `val`, even in shape-only mode.
+    fn push_variable(&mut self, val: F) -> Result<Variable, PlonkError> {
+        self.check_finalize_flag(false)?;
+        self.witness.push(val);
+        self.num_vars += 1;
+        // the index is from `0` to `num_vars - 1`
+        Ok(self.num_vars - 1)
+    }
+
+    // Return an error if the circuit is in shape-only mode, i.e. if its
+    // witness is not the actual one.
+    fn check_witness_available(&self) -> Result<(), PlonkError> {
+        if self.shape_only {
+            return Err(NotSupported(
+                "the witness of a shape-only circuit is not available".to_string(),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
     /// Re-arrange the order of the gates so that
     /// 1. io gates are in the front.
     /// 2. variable table lookup gate are at the rear so that they do not affect
@@ -533,6 +1483,7 @@ impl<F: FftField> PlonkCircuit<F> {
             if *io_gate_id > gate_id {
                 // Swap gate types
                 self.gates.swap(gate_id, *io_gate_id);
+                self.namespaces.gates.swap(gate_id, *io_gate_id);
                 // Swap wire variables
                 for i in 0..GATE_WIDTH + 1 {
                     self.wire_variables[i].swap(gate_id, *io_gate_id);
@@ -552,6 +1503,7 @@ impl<F: FftField> PlonkCircuit<F> {
                     if gate_id < cur_gate_id {
                         // Swap gate types
                         self.gates.swap(gate_id, cur_gate_id);
+                        self.namespaces.gates.swap(gate_id, cur_gate_id);
                         // Swap wire variables
                         for j in 0..GATE_WIDTH + 1 {
                             self.wire_variables[j].swap(gate_id, cur_gate_id);
@@ -576,6 +1528,7 @@ impl<F: FftField> PlonkCircuit<F> {
         for _ in self.num_gates()..n {
             self.gates.push(Box::new(PaddingGate));
         }
+        self.namespaces.gates.resize(n, 0);
         for wire_id in 0..self.num_wire_types() {
             self.wire_variables[wire_id].resize(n, self.zero());
         }
@@ -591,6 +1544,82 @@ impl<F: FftField> PlonkCircuit<F> {
     ///           q_hash0 * w0 + q_hash1 * w1 + q_hash2 * w2 + q_hash3 * w3 +
     ///           q_ecc * w0 * w1 * w2 * w3 * wo
     fn check_gate(&self, gate_id: Variable, pub_input: &F) -> Result<(), PlonkError> {
+        let (expected_gate_output, gate_output) = self.gate_outputs(gate_id, pub_input);
+        if expected_gate_output != gate_output {
+            return Err(GateCheckFailure(
+                gate_id,
+                with_namespace_msg(
+                    self.namespaces.gate_path(gate_id),
+                    self.gate_diagnostics_unchecked(gate_id, pub_input)
+                        .to_string(),
+                ),
+            )
+            .into());
+        }
+        Ok(())
+    }
+
+    // The diagnostics of the `gate_id`-th gate, which is guaranteed to be in
+    // the range, c.f. `gate_outputs`.
+    fn gate_diagnostics_unchecked(&self, gate_id: GateId, pub_input: &F) -> GateDiagnostics<F> {
+        let gate = &self.gates[gate_id];
+        let mut wire_vars = [0; GATE_WIDTH + 1];
+        let mut wire_values = [F::zero(); GATE_WIDTH + 1];
+        for (i, (var, value)) in wire_vars.iter_mut().zip(wire_values.iter_mut()).enumerate() {
+            *var = self.wire_variables[i][gate_id];
+            *value = self.witness[*var];
+        }
+        let w = &wire_values;
+        let (q_lc, q_mul, q_hash) = (gate.q_lc(), gate.q_mul(), gate.q_hash());
+        let mut lc_terms = [F::zero(); GATE_WIDTH];
+        let mut hash_terms = [F::zero(); GATE_WIDTH];
+        for (i, (lc_term, hash_term)) in lc_terms.iter_mut().zip(hash_terms.iter_mut()).enumerate()
+        {
+            *lc_term = q_lc[i] * w[i];
+            *hash_term = q_hash[i] * w[i].pow(&[5]);
+        }
+        let mul_terms = [q_mul[0] * w[0] * w[1], q_mul[1] * w[2] * w[3]];
+        let ecc_term = gate.q_ecc() * w[0] * w[1] * w[2] * w[3] * w[4];
+        let constant = gate.q_c();
+        let expected = lc_terms
+            .iter()
+            .chain(mul_terms.iter())
+            .chain(hash_terms.iter())
+            .fold(*pub_input + ecc_term + constant, |acc, term| acc + term);
+        GateDiagnostics {
+            gate_id,
+            gate: gate.name(),
+            namespace: self.namespaces.gate_path(gate_id).to_string(),
+            wire_vars,
+            wire_values,
+            pub_input: *pub_input,
+            lc_terms,
+            mul_terms,
+            hash_terms,
+            ecc_term,
+            constant,
+            expected,
+            computed: gate.q_o() * w[GATE_WIDTH],
+        }
+    }
+
+    // Return the violation of the gate-th gate, if any.
+    fn gate_violation(&self, gate_id: GateId, pub_input: &F) -> Option<ViolationKind<F>> {
+        let (expected, computed) = self.gate_outputs(gate_id, pub_input);
+        if expected != computed {
+            Some(ViolationKind::Gate {
+                gate: self.gates[gate_id].name(),
+                computed,
+                expected,
+            })
+        } else {
+            None
+        }
+    }
+
+    // Compute the expected gate output and the actual gate output `q_o * w_o`
+    // of the `gate_id`-th gate, c.f. `check_gate`.
+    fn gate_outputs(&self, gate_id: GateId, pub_input: &F) -> (F, F) {
         // Compute wire values
 
         let w_vals: Vec<F> = (0..GATE_WIDTH + 1)
@@ -619,22 +1648,7 @@ impl<F: FftField> PlonkCircuit<F> {
             + q_hash[3] * w_vals[3].pow(&[5])
             + q_c;
         let gate_output = q_o * w_vals[4];
-        if expected_gate_output != gate_output {
-            return Err(
-                GateCheckFailure(
-                    gate_id,
-                    format!(
-                        "gate: {:?}, wire values: {:?}, pub_input: {}, expected_gate_output: {}, gate_output: {}",
-                        self.gates[gate_id],
-                        w_vals,
-                        pub_input,
-                        expected_gate_output,
-                        gate_output
-                    )
-                )
-                .into());
-        }
-        Ok(())
+        (expected_gate_output, gate_output)
     }
 
     // Compute the permutation over wires.
@@ -696,7 +1710,7 @@ impl<F: FftField> PlonkCircuit<F> {
     // Check whether the Plonk type is the expected Plonk type. Return an error if
     // not.
     #[inline]
-    fn check_plonk_type(&self, expect_type: PlonkType) -> Result<(), PlonkError> {
+    pub(crate) fn check_plonk_type(&self, expect_type: PlonkType) -> Result<(), PlonkError> {
         if self.plonk_params.plonk_type != expect_type {
             return Err(WrongPlonkType.into());
         }
@@ -860,28 +1874,589 @@ impl<F: PrimeField> PlonkCircuit<F> {
             )
             .into());
         }
-        Ok(extended_perm)
+        Ok(extended_perm)
+    }
+}
+
+/// Methods for optimizing the circuits.
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Reduce the number of gates of the circuit before it is finalized, and
+    /// thus possibly its evaluation domain, e.g. for circuits generated
+    /// programmatically:
+    /// * the variables constrained equal by equality gates, or to the same
+    ///   constant by constant gates, are merged, i.e. these gates are replaced
+    ///   by copy constraints;
+    /// * the constant inputs of the arithmetic gates are folded into their
+    ///   selectors, and the gates which become trivially satisfied are
+    ///   removed, as well as the constant gates of the constants which are no
+    ///   longer used;
+    /// * only the first of the gates with identical selectors and wires is
+    ///   kept.
+    ///
+    /// The public input, lookup and elliptic curve gates are kept, and the
+    /// relation of the circuit is unchanged. As the witness of a merged
+    /// variable is no longer checked, the satisfiability of the circuit should
+    /// be checked before the optimization. The padding gates of `pad_gate`
+    /// are removed too, so the circuit should be padded afterwards.
+    /// Return error if the circuit is finalized.
+    pub fn optimize(&mut self) -> Result<OptimizationReport, PlonkError> {
+        self.check_finalize_flag(false)?;
+        let num_gates = self.num_gates();
+        let mut report = OptimizationReport {
+            num_gates_before: num_gates,
+            ..OptimizationReport::default()
+        };
+        let mut removed = vec![false; num_gates];
+
+        // merge the variables of the same constants, then of the equality
+        // gates
+        let mut classes = VarClasses::new(self.num_vars);
+        for (gate_id, gate) in self.gates.iter().enumerate() {
+            if let Some(gate) = gate.as_any().downcast_ref::<ConstantGate<F>>() {
+                classes.set_constant(self.wire_variable(GATE_WIDTH, gate_id), gate.0);
+            }
+        }
+        for (gate_id, gate) in self.gates.iter().enumerate() {
+            if gate.as_any().is::<EqualityGate>()
+                && classes.constant(self.wire_variable(GATE_WIDTH, gate_id)) == Some(F::zero())
+                && classes.union(
+                    self.wire_variable(0, gate_id),
+                    self.wire_variable(1, gate_id),
+                )
+            {
+                removed[gate_id] = true;
+                report.num_merged_equality_gates += 1;
+            }
+        }
+        for wire_vars in self.wire_variables.iter_mut() {
+            for var in wire_vars.iter_mut() {
+                *var = classes.find(*var);
+            }
+        }
+        for (var, ..) in self.range_requests.iter_mut() {
+            *var = classes.find(*var);
+        }
+
+        // fold the constant inputs
+        for gate_id in 0..num_gates {
+            let gate = &self.gates[gate_id];
+            if removed[gate_id]
+                || self.is_io_gate(gate_id)
+                || gate.as_any().is::<ConstantGate<F>>()
+                || !gate.q_ecc().is_zero()
+                || !gate.q_lookup().is_zero()
+            {
+                continue;
+            }
+            // the wires of the zero variable are left as is
+            let mut constants = [None; GATE_WIDTH + 1];
+            for (i, constant) in constants.iter_mut().enumerate() {
+                let var = self.wire_variable(i, gate_id);
+                if var != self.zero() {
+                    *constant = classes.constant(var);
+                }
+            }
+            if let Some(folded) = fold_constants(gate.as_ref(), &constants) {
+                for (i, constant) in constants.iter().enumerate() {
+                    if constant.is_some() {
+                        self.wire_variables[i][gate_id] = self.zero();
+                    }
+                }
+                if folded.q_lc.iter().all(|q| q.is_zero())
+                    && folded.q_mul.iter().all(|q| q.is_zero())
+                    && folded.q_hash.iter().all(|q| q.is_zero())
+                    && folded.q_o.is_zero()
+                    && folded.q_c.is_zero()
+                {
+                    removed[gate_id] = true;
+                    report.num_dead_gates += 1;
+                } else {
+                    self.gates[gate_id] = Box::new(folded);
+                    report.num_folded_gates += 1;
+                }
+            }
+        }
+
+        // remove the duplicate gates
+        let mut gate_keys = HashSet::new();
+        for (gate_id, gate) in self.gates.iter().enumerate() {
+            if removed[gate_id] || self.is_io_gate(gate_id) || !gate.q_lookup().is_zero() {
+                continue;
+            }
+            let wire_vars: Vec<Variable> = (0..GATE_WIDTH + 1)
+                .map(|i| self.wire_variable(i, gate_id))
+                .collect();
+            let mut selectors = gate.q_lc().to_vec();
+            selectors.extend_from_slice(&gate.q_mul());
+            selectors.extend_from_slice(&gate.q_hash());
+            selectors.extend_from_slice(&[gate.q_o(), gate.q_c(), gate.q_ecc()]);
+            if !gate_keys.insert((wire_vars, selectors)) {
+                removed[gate_id] = true;
+                report.num_duplicate_gates += 1;
+            }
+        }
+
+        // remove the constant gates of the unused constants, other than zero
+        // and one
+        let mut used = vec![false; self.num_vars];
+        for (gate_id, gate) in self.gates.iter().enumerate() {
+            if removed[gate_id] || gate.as_any().is::<ConstantGate<F>>() {
+                continue;
+            }
+            for i in 0..GATE_WIDTH + 1 {
+                used[self.wire_variable(i, gate_id)] = true;
+            }
+        }
+        for &var in self.wire_variables[RANGE_WIRE_ID].iter() {
+            used[var] = true;
+        }
+        for &(var, ..) in self.range_requests.iter() {
+            used[var] = true;
+        }
+        for (gate_id, gate) in self.gates.iter().enumerate() {
+            let var = self.wire_variable(GATE_WIDTH, gate_id);
+            if !removed[gate_id]
+                && gate.as_any().is::<ConstantGate<F>>()
+                && var > self.one()
+                && !used[var]
+            {
+                removed[gate_id] = true;
+                report.num_dead_gates += 1;
+            }
+        }
+
+        self.remove_gates(&removed);
+        report.num_gates_after = self.num_gates();
+        Ok(report)
+    }
+
+    // Remove the gates flagged in `removed`, which are neither IO nor lookup
+    // gates.
+    fn remove_gates(&mut self, removed: &[bool]) {
+        let mut new_gate_ids = Vec::with_capacity(removed.len());
+        let mut num_kept = 0;
+        for &is_removed in removed.iter() {
+            new_gate_ids.push(num_kept);
+            if !is_removed {
+                num_kept += 1;
+            }
+        }
+        for gate_id in self.pub_input_gate_ids.iter_mut() {
+            *gate_id = new_gate_ids[*gate_id];
+        }
+        for (start, _) in self.table_gate_ids.iter_mut() {
+            *start = new_gate_ids[*start];
+        }
+        retain_unremoved(&mut self.gates, removed);
+        for wire_vars in self.wire_variables.iter_mut().take(GATE_WIDTH + 1) {
+            retain_unremoved(wire_vars, removed);
+        }
+        retain_unremoved(&mut self.namespaces.gates, removed);
+    }
+}
+
+// The gate obtained by folding the constant values `constants` of the input
+// and output wires of `gate` into its selectors, or `None` if no selector
+// depends on these wires.
+fn fold_constants<F: Field>(
+    gate: &dyn Gate<F>,
+    constants: &[Option<F>; GATE_WIDTH + 1],
+) -> Option<FoldedGate<F>> {
+    let mut folded = FoldedGate {
+        q_lc: gate.q_lc(),
+        q_mul: gate.q_mul(),
+        q_hash: gate.q_hash(),
+        q_o: gate.q_o(),
+        q_c: gate.q_c(),
+    };
+    let mut is_folded = false;
+    for (j, q_mul) in folded.q_mul.iter_mut().enumerate() {
+        if q_mul.is_zero() {
+            continue;
+        }
+        match (constants[2 * j], constants[2 * j + 1]) {
+            (Some(a), Some(b)) => folded.q_c += *q_mul * a * b,
+            (Some(a), None) => folded.q_lc[2 * j + 1] += *q_mul * a,
+            (None, Some(b)) => folded.q_lc[2 * j] += *q_mul * b,
+            (None, None) => continue,
+        }
+        *q_mul = F::zero();
+        is_folded = true;
+    }
+    for (i, constant) in constants.iter().take(GATE_WIDTH).enumerate() {
+        if let Some(a) = constant {
+            if !folded.q_lc[i].is_zero() || !folded.q_hash[i].is_zero() {
+                folded.q_c += folded.q_lc[i] * a + folded.q_hash[i] * a.pow(&[5]);
+                folded.q_lc[i] = F::zero();
+                folded.q_hash[i] = F::zero();
+                is_folded = true;
+            }
+        }
+    }
+    if let Some(a) = constants[GATE_WIDTH] {
+        if !folded.q_o.is_zero() {
+            folded.q_c -= folded.q_o * a;
+            folded.q_o = F::zero();
+            is_folded = true;
+        }
+    }
+    is_folded.then(|| folded)
+}
+
+// Remove the elements of `values` flagged in `removed`.
+fn retain_unremoved<T>(values: &mut Vec<T>, removed: &[bool]) {
+    let mut removed = removed.iter();
+    values.retain(|_| !removed.next().unwrap()); // safe unwrap
+}
+
+/// Methods for composing the circuits.
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Append the constraints of the sub-circuit `other` to this circuit,
+    /// e.g. to prove in a single proof sub-circuits developed and tested
+    /// independently. Each pair `(other_var, var)` of `wire_map` identifies
+    /// the variable `other_var` of `other`, e.g. one of its inputs or
+    /// outputs, with the variable `var` of this circuit; the other variables
+    /// of `other` are added to this circuit with their values, and its
+    /// constants zero and one are identified with those of this circuit.
+    /// Return the variable of this circuit of each variable of `other`.
+    ///
+    /// The gates of `other` are inserted in the current namespace, and the
+    /// public inputs of `other` are not public in this circuit: they can be
+    /// exposed with `set_variable_public` on the returned variables. This
+    /// circuit becomes shape-only if `other` is.
+    /// If the range table size of one circuit is automatic and not chosen
+    /// yet, c.f. `RangeTableSize::Auto`, it takes the range bit length of the
+    /// other one.
+    /// Return error if a circuit is finalized, if the circuits do not have
+    /// the same Plonk type and range bit length, if both circuits have
+    /// lookup tables, or if `wire_map` has invalid or duplicate variables of
+    /// `other`, or variables whose values differ.
+    pub fn append(
+        &mut self,
+        other: &Self,
+        wire_map: &[(Variable, Variable)],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        self.check_finalize_flag(false)?;
+        other.check_finalize_flag(false)?;
+        if self.plonk_params.plonk_type != other.plonk_params.plonk_type
+            || matches!(
+                (self.plonk_params.range_bit_len, other.plonk_params.range_bit_len),
+                (Some(len), Some(other_len)) if len != other_len
+            )
+        {
+            return Err(ParameterError(
+                "cannot append a circuit of another type or range bit length".to_string(),
+            )
+            .into());
+        }
+        // the keys of the lookups of `other` are shifted by its table elements
+        // only
+        if self.num_table_elems > 0 && other.num_table_elems > 0 {
+            return Err(ParameterError(
+                "cannot append a circuit with lookup tables to another one".to_string(),
+            )
+            .into());
+        }
+        if self.is_range_bit_len_deferred() {
+            if let Some(range_bit_len) = other.plonk_params.range_bit_len {
+                self.expand_deferred_range_checks(range_bit_len)?;
+            }
+        }
+
+        let mut var_map: Vec<Option<Variable>> = vec![None; other.num_vars];
+        var_map[other.zero()] = Some(self.zero());
+        var_map[other.one()] = Some(self.one());
+        for &(other_var, var) in wire_map.iter() {
+            other.check_var_bound(other_var)?;
+            self.check_var_bound(var)?;
+            if var_map[other_var].map_or(false, |mapped| mapped != var) {
+                return Err(ParameterError(format!(
+                    "the variable {} of the appended circuit is mapped twice",
+                    other_var
+                ))
+                .into());
+            }
+            if !self.shape_only
+                && !other.shape_only
+                && self.witness[var] != other.witness[other_var]
+            {
+                return Err(ParameterError(format!(
+                    "the values of the variable {} of the appended circuit and of the \
+                     variable {} differ",
+                    other_var, var
+                ))
+                .into());
+            }
+            var_map[other_var] = Some(var);
+        }
+        let var_map = var_map
+            .into_iter()
+            .enumerate()
+            .map(|(other_var, var)| match var {
+                Some(var) => Ok(var),
+                None => self.create_variable(other.witness[other_var]),
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+
+        // the first two gates of `other` constrain its constants zero and one
+        let namespace = self.namespaces.current();
+        for gate_id in 2..other.num_gates() {
+            if other.is_io_gate(gate_id) {
+                continue;
+            }
+            if let Some(&(_, len)) = other
+                .table_gate_ids
+                .iter()
+                .find(|&&(start, _)| start == gate_id)
+            {
+                self.table_gate_ids.push((self.num_gates(), len));
+            }
+            for (i, wire_vars) in self
+                .wire_variables
+                .iter_mut()
+                .take(GATE_WIDTH + 1)
+                .enumerate()
+            {
+                wire_vars.push(var_map[other.wire_variable(i, gate_id)]);
+            }
+            self.gates.push(other.gates[gate_id].clone());
+            let id = self
+                .namespaces
+                .descendant(namespace, other.namespaces.gate_path(gate_id));
+            self.namespaces.gates.push(id);
+        }
+        for (idx, &var) in other.wire_variables[RANGE_WIRE_ID].iter().enumerate() {
+            self.wire_variables[RANGE_WIRE_ID].push(var_map[var]);
+            let id = self
+                .namespaces
+                .descendant(namespace, other.namespaces.range_gate_path(idx));
+            self.namespaces.range_gates.push(id);
+        }
+        for &(var, bit_len, other_namespace) in other.range_requests.iter() {
+            let id = self
+                .namespaces
+                .descendant(namespace, &other.namespaces.paths[other_namespace]);
+            if self.is_range_bit_len_deferred() {
+                self.range_requests.push((var_map[var], bit_len, id));
+            } else {
+                self.enforce_in_range_in_namespace(var_map[var], bit_len, id)?;
+            }
+        }
+        self.num_table_elems += other.num_table_elems;
+        self.shape_only |= other.shape_only;
+        if other.logic_table.is_some() {
+            self.logic_table = other.logic_table;
+        }
+        Ok(var_map)
+    }
+
+    /// Synthesize independent gadget subtrees in parallel, e.g. the hashes of
+    /// the leaves of a Merkle tree, so that their witnesses are computed on
+    /// several threads. The dependencies of the `i`-th subtree on this
+    /// circuit are the variables `inputs[i]`: they are copied in a new
+    /// sub-circuit of the same type, in which `synthesize` adds the subtree
+    /// and returns its outputs. The sub-circuits are then appended to this
+    /// circuit in order, their copied inputs being identified with
+    /// `inputs[i]`, c.f. `append`.
+    /// Return the variables of this circuit of the outputs of each subtree.
+    /// Return error if the circuit is finalized, if a variable is invalid,
+    /// if a synthesis fails, or if several sub-circuits, or this circuit and
+    /// a sub-circuit, have lookup tables.
+    pub fn par_synthesize<S>(
+        &mut self,
+        inputs: &[Vec<Variable>],
+        synthesize: S,
+    ) -> Result<Vec<Vec<Variable>>, PlonkError>
+    where
+        S: Fn(&mut Self, &[Variable]) -> Result<Vec<Variable>, PlonkError> + Sync,
+    {
+        self.check_finalize_flag(false)?;
+        let input_values = inputs
+            .iter()
+            .map(|vars| vars.iter().map(|&var| self.witness(var)).collect())
+            .collect::<Result<Vec<Vec<F>>, PlonkError>>()?;
+        let mut empty = Self::new(self.plonk_params);
+        empty.shape_only = self.shape_only;
+        let subtrees = input_values
+            .par_iter()
+            .map(|values| {
+                let mut sub = empty.clone();
+                let sub_inputs = values
+                    .iter()
+                    .map(|&value| sub.create_variable(value))
+                    .collect::<Result<Vec<_>, PlonkError>>()?;
+                let outputs = synthesize(&mut sub, &sub_inputs)?;
+                sub.check_vars_bound(&outputs)?;
+                Ok((sub, sub_inputs, outputs))
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+
+        subtrees
+            .into_iter()
+            .zip(inputs.iter())
+            .map(|((sub, sub_inputs, outputs), vars)| {
+                let wire_map: Vec<(Variable, Variable)> =
+                    sub_inputs.into_iter().zip(vars.iter().copied()).collect();
+                let var_map = self.append(&sub, &wire_map)?;
+                Ok(outputs.iter().map(|&var| var_map[var]).collect())
+            })
+            .collect()
+    }
+}
+
+/// Methods for finalizing and merging the circuits.
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Return the size of the circuit, including the evaluation domain and
+    /// SRS sizes it will have once finalized, e.g. to size the SRS before
+    /// running the prover. Combined with the shape-only mode, c.f.
+    /// `is_shape_only`, this requires no witness.
+    /// Return error if the evaluation domain of the circuit cannot be
+    /// created.
+    pub fn size(&self) -> Result<CircuitSize, PlonkError> {
+        let eval_domain_size = if self.is_finalized() {
+            self.eval_domain.size()
+        } else {
+            Radix2EvaluationDomain::<F>::new(self.num_slots_needed()?)
+                .ok_or(PlonkError::DomainCreationError)?
+                .size()
+        };
+        // the gates of the deferred range checks once they are expanded
+        let (num_deferred_vars, num_deferred_gates, num_deferred_range_gates) =
+            if self.is_range_bit_len_deferred() {
+                self.deferred_range_checks_cost(self.auto_range_bit_len()?)?
+            } else {
+                (0, 0, 0)
+            };
+        let gates = self
+            .gates
+            .iter()
+            .filter(|gate| !gate.as_any().is::<PaddingGate>());
+        Ok(CircuitSize {
+            num_vars: self.num_vars + num_deferred_vars,
+            num_inputs: self.num_inputs(),
+            num_gates: gates.clone().count() + num_deferred_gates,
+            num_lookup_gates: gates.filter(|gate| !gate.q_lookup().is_zero()).count(),
+            num_range_gates: self.wire_variables[RANGE_WIRE_ID].len() + num_deferred_range_gates,
+            num_table_elems: self.num_table_elems,
+            eval_domain_size,
+            // extra 2 degree for masking polynomial to make snark zero-knowledge
+            srs_size: eval_domain_size + 2,
+        })
+    }
+
+    // The number of slots of the evaluation domain needed by the gates of the
+    // circuit before padding.
+    fn num_slots_needed(&self) -> Result<usize, PlonkError> {
+        if !self.support_lookup() {
+            return Ok(self.num_gates());
+        }
+        let range_bit_len = match self.plonk_params.range_bit_len {
+            Some(range_bit_len) => range_bit_len,
+            None => self.auto_range_bit_len()?,
+        };
+        self.num_slots_with_range_bit_len(range_bit_len)
+    }
+
+    // The number of slots needed by an UltraPlonk circuit whose deferred range
+    // checks are expanded with a range table of `range_bit_len` bits.
+    fn num_slots_with_range_bit_len(&self, range_bit_len: usize) -> Result<usize, PlonkError> {
+        let (_, num_gates, num_range_gates) = self.deferred_range_checks_cost(range_bit_len)?;
+        Ok(max(
+            self.num_gates() + num_gates,
+            max(
+                1 << range_bit_len,
+                self.wire_variables[RANGE_WIRE_ID].len() + num_range_gates,
+            ) + self.num_table_elems()
+                + 1,
+        )) // range gates and lookup gates need to have separate slots
+    }
+
+    // The numbers of variables, gates and range gates added by the deferred
+    // range checks when they are expanded with a range table of
+    // `range_bit_len` bits, c.f. `enforce_in_range`.
+    fn deferred_range_checks_cost(
+        &self,
+        range_bit_len: usize,
+    ) -> Result<(usize, usize, usize), PlonkError> {
+        let (mut num_vars, mut num_gates, mut num_range_gates) = (0, 0, 0);
+        for &(_, bit_len, _) in self.range_requests.iter() {
+            let len = (bit_len + range_bit_len - 1) / range_bit_len;
+            // the product of the partial limb by a power of two
+            let num_partial_limbs = (bit_len % range_bit_len != 0) as usize;
+            num_vars += num_partial_limbs;
+            num_gates += num_partial_limbs;
+            num_range_gates += len + num_partial_limbs;
+            // the limbs and their linear combinations
+            if len > 1 {
+                let num_lc_gates = next_multiple(len - 1, GATE_WIDTH - 1)? / (GATE_WIDTH - 1);
+                num_vars += len + num_lc_gates - 1;
+                num_gates += num_lc_gates;
+            }
+        }
+        Ok((num_vars, num_gates, num_range_gates))
     }
-}
 
-/// Methods for finalizing and merging the circuits.
-impl<F: PrimeField> PlonkCircuit<F> {
-    /// Finalize the setup of the circuit before arithmetization.
+    // The bit length of the range table which minimizes the evaluation domain,
+    // then the number of slots, once the deferred range checks are expanded.
+    fn auto_range_bit_len(&self) -> Result<usize, PlonkError> {
+        let mut best = (usize::MAX, usize::MAX, 0);
+        for range_bit_len in 1..=MAX_AUTO_RANGE_BIT_LEN {
+            let num_slots = self.num_slots_with_range_bit_len(range_bit_len)?;
+            let candidate = (num_slots.next_power_of_two(), num_slots, range_bit_len);
+            if candidate < best {
+                best = candidate;
+            }
+        }
+        Ok(best.2)
+    }
+
+    /// The bit length of UltraPlonk range gates, for the gadgets which depend
+    /// on it. If the range table size is automatic and not chosen yet, c.f.
+    /// `RangeTableSize::Auto`, it is set to `MAX_AUTO_RANGE_BIT_LEN` and the
+    /// range checks recorded so far are expanded into range gates.
+    /// Return error if the circuit is not an UltraPlonk one.
+    pub fn resolve_range_bit_len(&mut self) -> Result<usize, PlonkError> {
+        if self.is_range_bit_len_deferred() {
+            self.expand_deferred_range_checks(MAX_AUTO_RANGE_BIT_LEN)?;
+        }
+        self.range_bit_len()
+    }
+
+    // Set the bit length of the range table, and expand the deferred range
+    // checks into range gates in their namespaces.
+    fn expand_deferred_range_checks(&mut self, range_bit_len: usize) -> Result<(), PlonkError> {
+        self.plonk_params.range_bit_len = Some(range_bit_len);
+        for (var, bit_len, namespace) in mem::take(&mut self.range_requests) {
+            self.enforce_in_range_in_namespace(var, bit_len, namespace)?;
+        }
+        Ok(())
+    }
+
+    // Enforce a range check in the namespace `namespace`.
+    fn enforce_in_range_in_namespace(
+        &mut self,
+        var: Variable,
+        bit_len: usize,
+        namespace: usize,
+    ) -> Result<(), PlonkError> {
+        self.namespaces.stack.push(namespace);
+        let res = self.enforce_in_range(var, bit_len);
+        self.namespaces.stack.pop();
+        res
+    }
+
+    /// Finalize the setup of the circuit before arithmetization. The range
+    /// table size of an UltraPlonk circuit is chosen at this point if it is
+    /// automatic, c.f. `RangeTableSize::Auto`.
     pub fn finalize_for_arithmetization(&mut self) -> Result<(), PlonkError> {
         if self.is_finalized() {
             return Ok(());
         }
-        let num_slots_needed = match self.support_lookup() {
-            false => self.num_gates(),
-            true => max(
-                self.num_gates(),
-                max(self.range_size()?, self.wire_variables[RANGE_WIRE_ID].len())
-                    + self.num_table_elems()
-                    + 1,
-            ), // range gates and lookup gates need to have separate slots
-        };
-        self.eval_domain =
-            Radix2EvaluationDomain::new(num_slots_needed).ok_or(PlonkError::DomainCreationError)?;
+        if self.is_range_bit_len_deferred() {
+            self.expand_deferred_range_checks(self.auto_range_bit_len()?)?;
+        }
+        self.eval_domain = Radix2EvaluationDomain::new(self.num_slots_needed()?)
+            .ok_or(PlonkError::DomainCreationError)?;
         self.pad()?;
         self.rearrange_gates()?;
         self.compute_wire_permutation();
@@ -908,6 +2483,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         for _ in 0..n {
             self.gates.push(Box::new(PaddingGate));
         }
+        self.namespaces.gates.resize(2 * n, 0);
         for wire_id in 0..self.num_wire_types() {
             self.wire_variables[wire_id].resize(2 * n, self.zero());
         }
@@ -923,6 +2499,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         } else {
             // reverse the gate indices.
             self.gates.reverse();
+            self.namespaces.gates.reverse();
             for wire_id in 0..self.num_wire_types() {
                 self.wire_variables[wire_id].reverse();
             }
@@ -990,6 +2567,14 @@ impl<F: PrimeField> PlonkCircuit<F> {
             other.pub_input_gate_ids.as_slice(),
         ]
         .concat();
+        // the names of the public inputs of `other` which are not names of
+        // those of `self` are kept
+        let mut pub_input_names = self.pub_input_names.clone();
+        for (name, &position) in other.pub_input_names.iter() {
+            pub_input_names
+                .entry(name.clone())
+                .or_insert(position + self.num_inputs());
+        }
 
         // merge gates and wire variables
         // the first circuit occupies the first n gates, the second circuit
@@ -1033,6 +2618,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
             gates,
             wire_variables,
             pub_input_gate_ids,
+            pub_input_names,
             wire_permutation,
             extended_id_permutation: self.extended_id_permutation.clone(),
             num_wire_types: self.num_wire_types,
@@ -1040,8 +2626,251 @@ impl<F: PrimeField> PlonkCircuit<F> {
             plonk_params: self.plonk_params,
             num_table_elems: 0,
             table_gate_ids: vec![],
+            logic_table: None,
+            namespaces: self.namespaces.merge(&other.namespaces, n),
+            shape_only: self.shape_only || other.shape_only,
+        })
+    }
+}
+
+/// Methods for compiling the circuits.
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Compile the finalized circuit into its constraint system without its
+    /// witness, c.f. `CompiledCircuit`. The namespaces of the gates, which
+    /// are only used for debugging, are not compiled.
+    /// Return error if the circuit is not finalized.
+    pub fn compile(&self) -> Result<CompiledCircuit<F>, PlonkError> {
+        self.check_finalize_flag(true)?;
+        let gate_kinds = self
+            .gates
+            .iter()
+            .map(|gate| {
+                let gate = gate.as_any();
+                if gate.is::<PaddingGate>() {
+                    COMPILED_PADDING_GATE
+                } else if gate.is::<IoGate>() {
+                    COMPILED_IO_GATE
+                } else if gate.is::<ConstantGate<F>>() {
+                    COMPILED_CONSTANT_GATE
+                } else {
+                    COMPILED_GATE
+                }
+            })
+            .collect();
+        Ok(CompiledCircuit {
+            plonk_type: match self.plonk_params.plonk_type {
+                PlonkType::TurboPlonk => 0,
+                PlonkType::UltraPlonk => 1,
+            },
+            range_bit_len: self.plonk_params.range_bit_len,
+            num_vars: self.num_vars,
+            eval_domain_size: self.eval_domain.size(),
+            gate_kinds,
+            selectors: self.all_selectors(),
+            wire_variables: self.wire_variables[..self.num_wire_types].to_vec(),
+            wire_permutation: self.wire_permutation.clone(),
+            pub_input_gate_ids: self.pub_input_gate_ids.clone(),
+            pub_input_names: self
+                .public_input_names()
+                .into_iter()
+                .map(|(name, position)| (name.into_bytes(), position))
+                .collect(),
+            num_table_elems: self.num_table_elems,
+        })
+    }
+
+    /// Reload a compiled circuit, e.g. deserialized from a cache, as a
+    /// finalized circuit in shape-only mode, c.f. `is_shape_only`: the values
+    /// of its constants are those of its constant gates, and the other
+    /// variables are zero. It can thus be preprocessed into proving and
+    /// verifying keys without synthesizing the circuit again.
+    /// Return error if the compiled circuit is inconsistent, e.g. if it is
+    /// corrupted.
+    pub fn from_compiled(compiled: &CompiledCircuit<F>) -> Result<Self, PlonkError> {
+        let inconsistent = |msg: &str| -> PlonkError {
+            ParameterError(format!("inconsistent compiled circuit: {}", msg)).into()
+        };
+        let plonk_params = match (compiled.plonk_type, compiled.range_bit_len) {
+            (0, None) => PlonkParams::init(PlonkType::TurboPlonk, None)?,
+            (1, Some(range_bit_len)) if range_bit_len >= 1 && range_bit_len < 32 => {
+                PlonkParams::init(PlonkType::UltraPlonk, Some(range_bit_len.into()))?
+            },
+            _ => return Err(inconsistent("invalid Plonk parameters")),
+        };
+        let mut circuit = Self::new(plonk_params);
+        let n = compiled.eval_domain_size;
+        let num_wire_types = circuit.num_wire_types;
+        let num_selectors =
+            2 * GATE_WIDTH + N_MUL_SELECTORS + 3 + circuit.support_lookup() as usize;
+        if n < 2 || !n.is_power_of_two() {
+            return Err(inconsistent("invalid evaluation domain size"));
+        }
+        if compiled.num_vars < 2 || compiled.num_table_elems >= n {
+            return Err(inconsistent(
+                "invalid numbers of variables or table elements",
+            ));
+        }
+        if compiled.gate_kinds.len() != n
+            || compiled.selectors.len() != num_selectors
+            || compiled
+                .selectors
+                .iter()
+                .any(|selector| selector.len() != n)
+        {
+            return Err(inconsistent("invalid number of gates or selectors"));
+        }
+        if compiled.wire_variables.len() != num_wire_types
+            || compiled
+                .wire_variables
+                .iter()
+                .any(|wires| wires.len() != n || wires.iter().any(|&var| var >= compiled.num_vars))
+        {
+            return Err(inconsistent("invalid wire variables"));
+        }
+        if compiled.wire_permutation.len() != num_wire_types * n
+            || compiled
+                .wire_permutation
+                .iter()
+                .any(|&(wire_id, gate_id)| wire_id > num_wire_types || gate_id >= n)
+        {
+            return Err(inconsistent("invalid wire permutation"));
+        }
+        if compiled
+            .pub_input_gate_ids
+            .iter()
+            .any(|&gate_id| gate_id >= n || compiled.gate_kinds[gate_id] != COMPILED_IO_GATE)
+        {
+            return Err(inconsistent("invalid public input gates"));
+        }
+
+        // the selectors of the gates, in the order of `all_selectors`
+        let selector = |i: usize, gate_id: GateId| compiled.selectors[i][gate_id];
+        let mut gates: Vec<Box<dyn Gate<F>>> = Vec::with_capacity(n);
+        let mut witness = vec![F::zero(); compiled.num_vars];
+        for (gate_id, &kind) in compiled.gate_kinds.iter().enumerate() {
+            let gate: Box<dyn Gate<F>> = match kind {
+                COMPILED_GATE => {
+                    let mut q_lc = [F::zero(); GATE_WIDTH];
+                    let mut q_mul = [F::zero(); N_MUL_SELECTORS];
+                    let mut q_hash = [F::zero(); GATE_WIDTH];
+                    for (i, q) in q_lc
+                        .iter_mut()
+                        .chain(q_mul.iter_mut())
+                        .chain(q_hash.iter_mut())
+                        .enumerate()
+                    {
+                        *q = selector(i, gate_id);
+                    }
+                    let offset = 2 * GATE_WIDTH + N_MUL_SELECTORS;
+                    Box::new(CompiledGate {
+                        q_lc,
+                        q_mul,
+                        q_hash,
+                        q_o: selector(offset, gate_id),
+                        q_c: selector(offset + 1, gate_id),
+                        q_ecc: selector(offset + 2, gate_id),
+                        q_lookup: match circuit.support_lookup() {
+                            true => selector(offset + 3, gate_id),
+                            false => F::zero(),
+                        },
+                    })
+                },
+                COMPILED_PADDING_GATE => Box::new(PaddingGate),
+                COMPILED_IO_GATE => Box::new(IoGate),
+                COMPILED_CONSTANT_GATE => {
+                    // the constant is the output wire of the gate
+                    let constant = selector(2 * GATE_WIDTH + N_MUL_SELECTORS + 1, gate_id);
+                    witness[compiled.wire_variables[GATE_WIDTH][gate_id]] = constant;
+                    Box::new(ConstantGate(constant))
+                },
+                _ => return Err(inconsistent("invalid gate kind")),
+            };
+            gates.push(gate);
+        }
+
+        let mut pub_input_names = HashMap::new();
+        for (name, position) in compiled.pub_input_names.iter() {
+            let name = String::from_utf8(name.clone())
+                .map_err(|_| inconsistent("invalid public input name"))?;
+            if *position >= compiled.pub_input_gate_ids.len() {
+                return Err(inconsistent("invalid public input name"));
+            }
+            pub_input_names.insert(name, *position);
+        }
+
+        circuit.num_vars = compiled.num_vars;
+        circuit.witness = witness;
+        circuit.gates = gates;
+        for (wires, compiled_wires) in circuit
+            .wire_variables
+            .iter_mut()
+            .zip(compiled.wire_variables.iter())
+        {
+            *wires = compiled_wires.clone();
+        }
+        circuit.pub_input_gate_ids = compiled.pub_input_gate_ids.clone();
+        circuit.pub_input_names = pub_input_names;
+        circuit.wire_permutation = compiled.wire_permutation.clone();
+        circuit.eval_domain =
+            Radix2EvaluationDomain::new(n).ok_or(PlonkError::DomainCreationError)?;
+        circuit.num_table_elems = compiled.num_table_elems;
+        circuit.namespaces.gates = vec![0; n];
+        circuit.namespaces.range_gates = vec![0; circuit.wire_variables[RANGE_WIRE_ID].len()];
+        circuit.shape_only = true;
+        if circuit.all_selectors() != compiled.selectors {
+            return Err(inconsistent("the selectors do not match the gate kinds"));
+        }
+        if circuit.support_lookup() && circuit.range_size()? > n {
+            return Err(inconsistent(
+                "the range table does not fit the evaluation domain",
+            ));
+        }
+        circuit.compute_extended_id_permutation();
+        Ok(circuit)
+    }
+}
+
+/// Methods for extracting and filling the witnesses of the circuits.
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// The witness of the circuit, c.f. `WitnessVector`.
+    /// Return error if the circuit is in shape-only mode.
+    pub fn witness_vector(&self) -> Result<WitnessVector<F>, PlonkError> {
+        self.check_witness_available()?;
+        Ok(WitnessVector {
+            values: self.witness.clone(),
         })
     }
+
+    /// Replace the witness of the circuit by `witness`, e.g. in a circuit
+    /// reloaded from its compiled form, c.f. `from_compiled`, which leaves
+    /// shape-only mode. The witness is not checked to satisfy the circuit.
+    /// Return error if `witness` does not have a value for each variable,
+    /// or if it changes the value of a constant.
+    pub fn set_witness(&mut self, witness: &WitnessVector<F>) -> Result<(), PlonkError> {
+        if witness.len() != self.num_vars {
+            return Err(ParameterError(format!(
+                "the witness has {} values for {} variables",
+                witness.len(),
+                self.num_vars
+            ))
+            .into());
+        }
+        for (gate_id, gate) in self.gates.iter().enumerate() {
+            if let Some(gate) = gate.as_any().downcast_ref::<ConstantGate<F>>() {
+                let var = self.wire_variables[GATE_WIDTH][gate_id];
+                if witness.values[var] != gate.0 {
+                    return Err(ParameterError(format!(
+                        "the witness changes the value of the constant variable {}",
+                        var
+                    ))
+                    .into());
+                }
+            }
+        }
+        self.witness = witness.values.clone();
+        self.shape_only = false;
+        Ok(())
+    }
 }
 
 impl<F> Arithmetization<F> for PlonkCircuit<F>
@@ -1133,6 +2962,7 @@ where
             ))
             .into());
         }
+        self.check_witness_available()?;
         let witness = &self.witness;
         let wire_polys: Vec<_> = self
             .wire_variables
@@ -1150,6 +2980,7 @@ where
 
     fn compute_pub_input_polynomial(&self) -> Result<DensePolynomial<F>, PlonkError> {
         self.check_finalize_flag(true)?;
+        self.check_witness_available()?;
         let domain = &self.eval_domain;
         let mut pub_input_vec = vec![F::zero(); domain.size()];
         self.pub_input_gate_ids.iter().for_each(|&io_gate_id| {
@@ -1377,10 +3208,18 @@ impl<F: PrimeField> PlonkCircuit<F> {
 
 #[cfg(test)]
 pub(crate) mod test {
+    use super::{
+        CompiledCircuit, GateViolation, OptimizationReport, RangeTableSize, ViolationKind,
+        MAX_AUTO_RANGE_BIT_LEN,
+    };
     use crate::{
-        circuit::{Arithmetization, Circuit, PlonkCircuit},
+        circuit::{
+            gates::EqualityGate,
+            profile::{ConstraintProfile, NamespaceCost},
+            Arithmetization, Circuit, PlonkCircuit, Variable,
+        },
         constants::compute_coset_representatives,
-        errors::PlonkError,
+        errors::{CircuitError, PlonkError},
     };
     use ark_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_377::Fq as FqEd377;
@@ -1388,7 +3227,8 @@ pub(crate) mod test {
     use ark_ed_on_bn254::Fq as FqEd254;
     use ark_ff::PrimeField;
     use ark_poly::{domain::Radix2EvaluationDomain, univariate::DensePolynomial, EvaluationDomain};
-    use ark_std::{test_rng, vec, vec::Vec};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{boxed::Box, string::String, string::ToString, test_rng, vec, vec::Vec};
 
     #[test]
     fn test_circuit_trait() -> Result<(), PlonkError> {
@@ -1439,6 +3279,840 @@ pub(crate) mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_satisfiability_report() -> Result<(), PlonkError> {
+        test_satisfiability_report_helper::<FqEd254>()?;
+        test_satisfiability_report_helper::<FqEd377>()?;
+        test_satisfiability_report_helper::<FqEd381>()?;
+        test_satisfiability_report_helper::<Fq377>()
+    }
+
+    fn test_satisfiability_report_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(1u32))?;
+        // gate 2: c = a + b = 4
+        let c = circuit.add(a, b)?;
+        // gate 3: d = c * a = 12
+        let d = circuit.mul(c, a)?;
+        // gate 4: io gate
+        let e = circuit.create_public_variable(F::from(12u32))?;
+        // gate 5: d = e
+        circuit.equal_gate(d, e)?;
+
+        let pub_input = &[F::from(12u32)];
+        let report = circuit.satisfiability_report(pub_input)?;
+        assert!(report.is_satisfied());
+        assert!(!report.truncated);
+
+        // break both the addition and the multiplication gates
+        *circuit.witness_mut(c) = F::from(5u32);
+        assert!(circuit.check_circuit_satisfiability(pub_input).is_err());
+        let report = circuit.satisfiability_report(pub_input)?;
+        assert!(!report.truncated);
+        assert_eq!(
+            report.violations,
+            vec![
+                GateViolation {
+                    gate_id: 2,
+                    namespace: String::new(),
+                    kind: ViolationKind::Gate {
+                        gate: "Addition Gate",
+                        computed: F::from(5u32),
+                        expected: F::from(4u32),
+                    },
+                },
+                GateViolation {
+                    gate_id: 3,
+                    namespace: String::new(),
+                    kind: ViolationKind::Gate {
+                        gate: "Multiplication Gate",
+                        computed: F::from(12u32),
+                        expected: F::from(15u32),
+                    },
+                },
+            ]
+        );
+
+        // cap the number of reported violations
+        let report = circuit.satisfiability_report_with_limit(pub_input, 1)?;
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].gate_id, 2);
+        assert!(report.truncated);
+
+        // wrong public input length
+        assert!(circuit.satisfiability_report(&[]).is_err());
+
+        // range gates
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(15u32))?;
+        circuit.add_range_check_variable(a)?;
+        circuit.add_range_check_variable(b)?;
+        assert!(circuit.satisfiability_report(&[])?.is_satisfied());
+        *circuit.witness_mut(b) = F::from(16u32);
+        assert_eq!(
+            circuit.satisfiability_report(&[])?.violations,
+            vec![GateViolation {
+                gate_id: 1,
+                namespace: String::new(),
+                kind: ViolationKind::Range {
+                    value: F::from(16u32),
+                    range_size: 16,
+                },
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_append() -> Result<(), PlonkError> {
+        test_append_helper::<FqEd254>()?;
+        test_append_helper::<FqEd377>()?;
+        test_append_helper::<FqEd381>()?;
+        test_append_helper::<Fq377>()
+    }
+
+    fn test_append_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // a sub-circuit with a range gate, a lookup table and a public output
+        let mut sub: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let x = sub.create_variable(F::from(3u32))?;
+        let y = sub.create_variable(F::from(4u32))?;
+        let z = sub.with_namespace("sub", |sub| sub.mul(x, y))?;
+        sub.add_range_check_variable(z)?;
+        let zero = sub.zero();
+        sub.create_table_and_lookup_variables(&[(zero, x, y)], &[(x, y)])?;
+        sub.set_variable_public(z)?;
+        assert!(sub.check_circuit_satisfiability(&[F::from(12u32)]).is_ok());
+
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(4u32))?;
+        let var_map =
+            circuit.with_namespace("main", |circuit| circuit.append(&sub, &[(x, a), (y, b)]))?;
+        assert_eq!(var_map.len(), sub.num_vars());
+        assert_eq!((var_map[x], var_map[y]), (a, b));
+        let c = var_map[z];
+        assert_eq!(circuit.witness(c)?, F::from(12u32));
+        // the constant and IO gates of `sub` are not appended
+        assert_eq!(circuit.num_gates(), sub.num_gates() - 1);
+        assert_eq!(circuit.gate_namespace(2)?, "main/sub");
+        assert_eq!(circuit.num_inputs(), 0);
+        circuit.set_variable_public(c)?;
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(12u32)])
+            .is_ok());
+        *circuit.witness_mut(a) += F::one();
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(12u32)])
+            .is_err());
+        *circuit.witness_mut(a) -= F::one();
+
+        // invalid compositions
+        let mut other: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let b = other.create_variable(F::from(4u32))?;
+        assert!(other.append(&sub, &[(x, b)]).is_err());
+        assert!(other.append(&sub, &[(y, b), (y, other.one())]).is_err());
+        assert!(other.append(&sub, &[(sub.num_vars(), b)]).is_err());
+        assert!(other.append(&sub, &[(y, other.num_vars())]).is_err());
+        assert!(circuit.append(&sub, &[]).is_err());
+        assert!(PlonkCircuit::new_turbo_plonk().append(&sub, &[]).is_err());
+        assert!(PlonkCircuit::new_ultra_plonk(8).append(&sub, &[]).is_err());
+        sub.finalize_for_arithmetization()?;
+        assert!(other.append(&sub, &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_par_synthesize() -> Result<(), PlonkError> {
+        test_par_synthesize_helper::<FqEd254>()?;
+        test_par_synthesize_helper::<FqEd377>()?;
+        test_par_synthesize_helper::<FqEd381>()?;
+        test_par_synthesize_helper::<Fq377>()
+    }
+
+    fn test_par_synthesize_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // (x + y)^3 with x of 8 bits
+        let synthesize = |circuit: &mut PlonkCircuit<F>,
+                          vars: &[Variable]|
+         -> Result<Vec<Variable>, PlonkError> {
+            circuit.with_namespace("cube", |circuit| {
+                circuit.enforce_in_range(vars[0], 8)?;
+                let s = circuit.add(vars[0], vars[1])?;
+                let s2 = circuit.mul(s, s)?;
+                Ok(vec![circuit.mul(s2, s)?])
+            })
+        };
+        for lookup in [false, true] {
+            let new_circuit = || match lookup {
+                false => PlonkCircuit::<F>::new_turbo_plonk(),
+                true => PlonkCircuit::<F>::new_ultra_plonk(4),
+            };
+            let mut circuit = new_circuit();
+            let mut expected = new_circuit();
+            let mut inputs = vec![];
+            for circuit in [&mut circuit, &mut expected] {
+                let y = circuit.create_variable(F::from(7u32))?;
+                inputs = (0..4u32)
+                    .map(|i| Ok(vec![circuit.create_variable(F::from(100 + i))?, y]))
+                    .collect::<Result<Vec<_>, PlonkError>>()?;
+            }
+
+            let outputs = circuit.par_synthesize(&inputs, synthesize)?;
+            let expected_outputs = inputs
+                .iter()
+                .map(|vars| synthesize(&mut expected, vars))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            assert_eq!(outputs.len(), 4);
+            for (output, expected_output) in outputs.iter().zip(expected_outputs.iter()) {
+                assert_eq!(
+                    circuit.witness(output[0])?,
+                    expected.witness(expected_output[0])?
+                );
+            }
+            assert_eq!(circuit.witness(outputs[1][0])?, F::from(108u32 * 108 * 108));
+            assert_eq!(circuit.num_gates(), expected.num_gates());
+            assert_eq!(circuit.num_vars(), expected.num_vars());
+            assert_eq!(circuit.size()?, expected.size()?);
+            assert_eq!(circuit.gate_namespace(circuit.num_gates() - 1)?, "cube");
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            let mut bad_circuit = circuit.clone();
+            *bad_circuit.witness_mut(outputs[2][0]) += F::one();
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+            let mut bad_circuit = circuit.clone();
+            *bad_circuit.witness_mut(inputs[3][0]) = F::from(256u32);
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // failing syntheses, or invalid variables
+            assert!(circuit
+                .par_synthesize(&inputs, |circuit, vars| {
+                    Ok(vec![circuit.mul(vars[0], circuit.num_vars())?])
+                })
+                .is_err());
+            assert!(circuit
+                .par_synthesize(&inputs, |circuit, _| Ok(vec![circuit.num_vars()]))
+                .is_err());
+            assert!(circuit
+                .par_synthesize(&[vec![circuit.num_vars()]], synthesize)
+                .is_err());
+            if lookup {
+                // the circuit is unchanged when the sub-circuits conflict,
+                // e.g. when several of them, or the circuit and one of them,
+                // have lookup tables
+                let with_table = |circuit: &mut PlonkCircuit<F>,
+                                  vars: &[Variable]|
+                 -> Result<Vec<Variable>, PlonkError> {
+                    circuit.create_logic_table(4)?;
+                    synthesize(circuit, vars)
+                };
+                let (num_gates, num_vars) = (circuit.num_gates(), circuit.num_vars());
+                assert!(circuit.par_synthesize(&inputs, with_table).is_err());
+                assert_eq!(circuit.num_gates(), num_gates);
+                assert_eq!(circuit.num_vars(), num_vars);
+                circuit.par_synthesize(&inputs[..1], with_table)?;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+                let (num_gates, num_vars) = (circuit.num_gates(), circuit.num_vars());
+                assert!(circuit.par_synthesize(&inputs[1..], with_table).is_err());
+                assert_eq!(circuit.num_gates(), num_gates);
+                assert_eq!(circuit.num_vars(), num_vars);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_public_inputs() -> Result<(), PlonkError> {
+        test_named_public_inputs_helper::<FqEd254>()?;
+        test_named_public_inputs_helper::<FqEd377>()?;
+        test_named_public_inputs_helper::<FqEd381>()?;
+        test_named_public_inputs_helper::<Fq377>()
+    }
+
+    fn test_named_public_inputs_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_public_variable(F::from(1u32))?;
+        let root = circuit.create_named_public_variable("root", F::from(2u32))?;
+        let b = circuit.create_variable(F::from(3u32))?;
+        circuit.set_variable_public_named("nullifier", b)?;
+        circuit.add(a, root)?;
+        assert_eq!(circuit.public_input_position("root"), Some(1));
+        assert_eq!(circuit.public_input_position("nullifier"), Some(2));
+        assert_eq!(circuit.public_input_position("commitment"), None);
+        assert_eq!(
+            circuit.public_input_names(),
+            vec![("root".to_string(), 1), ("nullifier".to_string(), 2)]
+        );
+        assert!(circuit.set_variable_public_named("root", a).is_err());
+        assert!(circuit
+            .set_variable_public_named("other", circuit.num_vars())
+            .is_err());
+        assert_eq!(circuit.num_inputs(), 3);
+
+        // the positions are kept by the finalization
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(circuit.public_input_position("root"), Some(1));
+        let pub_input = circuit.public_input()?;
+        assert_eq!(pub_input[1], F::from(2u32));
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_namespaces() -> Result<(), PlonkError> {
+        test_namespaces_helper::<FqEd254>()?;
+        test_namespaces_helper::<FqEd377>()?;
+        test_namespaces_helper::<FqEd381>()?;
+        test_namespaces_helper::<Fq377>()
+    }
+
+    fn test_namespaces_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(5u32))?;
+        assert_eq!(circuit.namespace(), "");
+        circuit.push_namespace("outer");
+        // gate 2
+        let c = circuit.add(a, b)?;
+        let d = circuit.with_namespace("inner", |circuit| {
+            assert_eq!(circuit.namespace(), "outer/inner");
+            circuit.add_range_check_variable(b)?;
+            // gate 3
+            circuit.mul(a, b)
+        })?;
+        assert_eq!(circuit.namespace(), "outer");
+        circuit.pop_namespace()?;
+        // gate 4, in a namespace entered again
+        let e = circuit.with_namespace("outer", |circuit| circuit.sub(b, a))?;
+        assert!(circuit.pop_namespace().is_err());
+        assert!(circuit
+            .with_namespace("failing", |circuit| circuit.add(a, circuit.num_vars()))
+            .is_err());
+        assert_eq!(circuit.namespace(), "");
+
+        assert_eq!(circuit.gate_namespace(0)?, "");
+        assert_eq!(circuit.gate_namespace(2)?, "outer");
+        assert_eq!(circuit.gate_namespace(3)?, "outer/inner");
+        assert_eq!(circuit.gate_namespace(4)?, "outer");
+        assert!(circuit.gate_namespace(circuit.num_gates()).is_err());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // the violated gates are reported with their namespaces
+        *circuit.witness_mut(b) = F::from(16u32);
+        let report = circuit.satisfiability_report(&[])?;
+        let namespaces: Vec<(usize, &str)> = report
+            .violations
+            .iter()
+            .map(|v| (v.gate_id, v.namespace.as_str()))
+            .collect();
+        assert_eq!(
+            namespaces,
+            vec![
+                (2, "outer"),
+                (3, "outer/inner"),
+                (4, "outer"),
+                (0, "outer/inner")
+            ]
+        );
+        match circuit.check_circuit_satisfiability(&[]) {
+            Err(PlonkError::CircuitError(CircuitError::GateCheckFailure(2, msg))) => {
+                assert!(msg.starts_with("namespace: outer, "))
+            },
+            _ => panic!("the addition gate should fail"),
+        }
+        *circuit.witness_mut(c) = F::from(19u32);
+        *circuit.witness_mut(d) = F::from(48u32);
+        *circuit.witness_mut(e) = F::from(13u32);
+        match circuit.check_circuit_satisfiability(&[]) {
+            Err(PlonkError::CircuitError(CircuitError::GateCheckFailure(0, msg))) => {
+                assert!(msg.starts_with("namespace: outer/inner, Range gate failed"))
+            },
+            _ => panic!("the range gate should fail"),
+        }
+
+        // the namespaces are kept when the circuit is finalized
+        circuit.finalize_for_arithmetization()?;
+        let namespaces: Vec<&str> = (0..circuit.num_gates())
+            .map(|gate_id| circuit.gate_namespace(gate_id))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        assert_eq!(namespaces.iter().filter(|&&ns| ns == "outer").count(), 2);
+        assert_eq!(
+            namespaces.iter().filter(|&&ns| ns == "outer/inner").count(),
+            1
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_gate_diagnostics() -> Result<(), PlonkError> {
+        test_gate_diagnostics_helper::<FqEd254>()?;
+        test_gate_diagnostics_helper::<FqEd377>()?;
+        test_gate_diagnostics_helper::<FqEd381>()?;
+        test_gate_diagnostics_helper::<Fq377>()
+    }
+
+    fn test_gate_diagnostics_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(5u32))?;
+        // gate 2: c = 2 * a + b * b + 1 = 32
+        let c = circuit.gen_quad_poly(
+            &[a, 0, b, b],
+            &[F::from(2u32), F::zero(), F::zero(), F::zero()],
+            &[F::zero(), F::one()],
+            F::one(),
+        )?;
+        // gate 3: io gate
+        let d = circuit.create_public_variable(F::from(32u32))?;
+        circuit.equal_gate(c, d)?;
+        let pub_input = &[F::from(32u32)];
+        assert_eq!(circuit.first_unsatisfied_gate(pub_input)?, None);
+        for gate_id in 0..circuit.num_gates() {
+            assert!(circuit
+                .gate_diagnostics(gate_id, &F::zero())?
+                .is_satisfied());
+        }
+
+        // the terms of an unsatisfied gate
+        *circuit.witness_mut(b) = F::from(4u32);
+        let diagnostics = circuit.first_unsatisfied_gate(pub_input)?.unwrap();
+        assert_eq!(diagnostics.gate_id, 2);
+        assert_eq!(diagnostics.wire_vars, [a, 0, b, b, c]);
+        assert_eq!(
+            diagnostics.wire_values,
+            [
+                F::from(3u32),
+                F::zero(),
+                F::from(4u32),
+                F::from(4u32),
+                F::from(32u32)
+            ]
+        );
+        assert_eq!(diagnostics.lc_terms[0], F::from(6u32));
+        assert_eq!(diagnostics.mul_terms, [F::zero(), F::from(16u32)]);
+        assert_eq!(diagnostics.constant, F::one());
+        assert_eq!(diagnostics.expected, F::from(23u32));
+        assert_eq!(diagnostics.computed, F::from(32u32));
+        assert!(!diagnostics.is_satisfied());
+        match circuit.check_circuit_satisfiability(pub_input) {
+            Err(PlonkError::CircuitError(CircuitError::GateCheckFailure(2, msg))) => {
+                assert_eq!(msg, diagnostics.to_string())
+            },
+            _ => panic!("the quadratic gate should fail"),
+        }
+
+        // a wrong public input fails the io gate first
+        *circuit.witness_mut(b) = F::from(5u32);
+        let diagnostics = circuit.first_unsatisfied_gate(&[F::one()])?.unwrap();
+        assert_eq!(diagnostics.gate_id, 3);
+        assert_eq!(diagnostics.pub_input, F::one());
+
+        // invalid inputs
+        assert!(circuit.first_unsatisfied_gate(&[]).is_err());
+        assert!(circuit
+            .gate_diagnostics(circuit.num_gates(), &F::zero())
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_constraint_profile() -> Result<(), PlonkError> {
+        test_constraint_profile_helper::<FqEd254>()?;
+        test_constraint_profile_helper::<FqEd377>()?;
+        test_constraint_profile_helper::<FqEd381>()?;
+        test_constraint_profile_helper::<Fq377>()
+    }
+
+    fn test_constraint_profile_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let a = circuit.create_variable(F::from(3u32))?;
+        circuit.with_namespace("hash", |circuit| {
+            circuit.mul(a, a)?;
+            circuit.with_namespace("round", |circuit| {
+                circuit.add(a, a)?;
+                circuit.add_range_check_variable(a)
+            })?;
+            circuit.with_namespace("round", |circuit| circuit.sub(a, a))
+        })?;
+        circuit.with_namespace("hashing", |circuit| circuit.add(a, a))?;
+        circuit.with_namespace("empty", |_| Ok(()))?;
+        let zero = circuit.zero();
+        circuit.create_table_and_lookup_variables(&[], &[(zero, zero)])?;
+
+        let cost = |path: &str, num_gates, num_lookup_gates, num_range_gates| NamespaceCost {
+            path: path.to_string(),
+            num_gates,
+            num_lookup_gates,
+            num_range_gates,
+        };
+        let profile = circuit.constraint_profile();
+        assert_eq!(
+            profile.namespaces,
+            vec![
+                cost("", 3, 1, 0),
+                cost("hash", 1, 0, 0),
+                cost("hash/round", 2, 0, 1),
+                cost("hashing", 1, 0, 0),
+            ]
+        );
+        assert_eq!(profile.total("hash"), cost("hash", 3, 0, 1));
+        assert_eq!(profile.total(""), cost("", 7, 1, 1));
+        assert_eq!(
+            profile.to_folded_stacks("circuit"),
+            "circuit 3\ncircuit;hash 1\ncircuit;hash;round 2\ncircuit;hashing 1\n"
+        );
+        let json = serde_json::to_string(&profile).unwrap();
+        assert_eq!(
+            serde_json::from_str::<ConstraintProfile>(&json).unwrap(),
+            profile
+        );
+
+        // the padding gates are ignored
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(circuit.constraint_profile(), profile);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shape_only() -> Result<(), PlonkError> {
+        test_shape_only_helper::<FqEd254>()?;
+        test_shape_only_helper::<FqEd377>()?;
+        test_shape_only_helper::<FqEd381>()?;
+        test_shape_only_helper::<Fq377>()
+    }
+
+    fn test_shape_only_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let build = |circuit: &mut PlonkCircuit<F>, x: F| -> Result<Variable, PlonkError> {
+            let a = circuit.create_public_variable(x)?;
+            let b = circuit.create_constant_variable(F::from(5u32))?;
+            let c = circuit.mul(a, b)?;
+            circuit.add_range_check_variable(c)?;
+            let zero = circuit.zero();
+            circuit.create_table_and_lookup_variables(&[(zero, c, b)], &[(c, b)])?;
+            Ok(c)
+        };
+        let mut circuit = PlonkCircuit::new_ultra_plonk(4);
+        build(&mut circuit, F::from(3u32))?;
+        let mut shape = PlonkCircuit::new_ultra_plonk_shape_only(4);
+        let c = build(&mut shape, F::from(3u32))?;
+        assert!(!circuit.is_shape_only());
+        assert!(shape.is_shape_only());
+
+        // the values of the constants are kept
+        assert_eq!(shape.witness(c)?, F::zero());
+        assert_eq!(shape.witness(c - 1)?, F::from(5u32));
+        assert!(shape.check_circuit_satisfiability(&[F::zero()]).is_err());
+        assert!(shape.satisfiability_report(&[F::zero()]).is_err());
+        assert!(shape.first_unsatisfied_gate(&[F::zero()]).is_err());
+
+        let size = shape.size()?;
+        assert_eq!(size, circuit.size()?);
+        assert_eq!(size.num_vars, circuit.num_vars());
+        assert_eq!(size.num_inputs, 1);
+        assert_eq!(size.num_gates, circuit.num_gates());
+        assert_eq!(size.num_lookup_gates, 1);
+        assert_eq!(size.num_range_gates, 1);
+        assert_eq!(size.num_table_elems, 1);
+        // the range table of size 16 and the lookup table are in separate slots
+        assert_eq!(size.eval_domain_size, 32);
+        assert_eq!(size.srs_size, 34);
+
+        // the sizes are those of the finalized circuit, whose selectors do not
+        // depend on the witness
+        circuit.finalize_for_arithmetization()?;
+        shape.finalize_for_arithmetization()?;
+        assert_eq!(shape.size()?, size);
+        assert_eq!(shape.eval_domain_size()?, size.eval_domain_size);
+        assert_eq!(shape.srs_size()?, size.srs_size);
+        assert_eq!(
+            shape.compute_selector_polynomials()?,
+            circuit.compute_selector_polynomials()?
+        );
+        assert!(shape.compute_wire_polynomials().is_err());
+        assert!(shape.compute_pub_input_polynomial().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_range_table_size() -> Result<(), PlonkError> {
+        test_auto_range_table_size_helper::<FqEd254>()?;
+        test_auto_range_table_size_helper::<FqEd377>()?;
+        test_auto_range_table_size_helper::<FqEd381>()?;
+        test_auto_range_table_size_helper::<Fq377>()
+    }
+
+    fn test_auto_range_table_size_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let build = |circuit: &mut PlonkCircuit<F>| -> Result<Vec<Variable>, PlonkError> {
+            let mut vars = vec![];
+            for _ in 0..20 {
+                for bit_len in [1usize, 13, 16, 48, 100] {
+                    let a = circuit.create_variable(F::from(2u32).pow([bit_len as u64 - 1]))?;
+                    circuit
+                        .with_namespace("range", |circuit| circuit.enforce_in_range(a, bit_len))?;
+                    vars.push(a);
+                }
+            }
+            let b = circuit.create_variable(F::from(300u32))?;
+            circuit.enforce_in_range(b, 9)?;
+            vars.push(b);
+            Ok(vars)
+        };
+        let mut circuit = PlonkCircuit::new_ultra_plonk(RangeTableSize::Auto);
+        let vars = build(&mut circuit)?;
+        // the range checks are recorded until the circuit is finalized
+        assert_eq!(circuit.num_gates(), 2);
+        assert!(circuit.range_bit_len().is_err());
+        assert!(circuit.num_range_blocks().is_err());
+        assert!(circuit.unconstrained_variables().is_empty());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let mut other = PlonkCircuit::<F>::new_ultra_plonk(RangeTableSize::Auto);
+        let x = other.create_variable(F::from(300u32))?;
+        other.range_gate(x, 9)?;
+        assert_eq!(other.num_gates(), 2);
+
+        // a value out of range
+        let mut bad_circuit = circuit.clone();
+        let value = F::from(1u32 << 13);
+        *bad_circuit.witness_mut(vars[1]) = value;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        let report = bad_circuit.satisfiability_report(&[])?;
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].namespace, "range");
+        assert_eq!(
+            report.violations[0].kind,
+            ViolationKind::DeferredRange { value, bit_len: 13 }
+        );
+
+        // the size is that of the finalized circuit, whose range table
+        // minimizes the evaluation domain
+        let size = circuit.size()?;
+        circuit.finalize_for_arithmetization()?;
+        let range_bit_len = circuit.range_bit_len()?;
+        assert!((1..=MAX_AUTO_RANGE_BIT_LEN).contains(&range_bit_len));
+        assert_eq!(circuit.size()?, size);
+        assert_eq!(
+            circuit.constraint_profile().total("range").num_range_gates,
+            size.num_range_gates - 1
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        for fixed_bit_len in 1..=MAX_AUTO_RANGE_BIT_LEN {
+            let mut fixed = PlonkCircuit::new_ultra_plonk(fixed_bit_len);
+            build(&mut fixed)?;
+            assert!(size.eval_domain_size <= fixed.size()?.eval_domain_size);
+            if fixed_bit_len == range_bit_len {
+                fixed.finalize_for_arithmetization()?;
+                assert_eq!(fixed.size()?, size);
+            }
+        }
+
+        // the gadgets which depend on the range bit length set it to the
+        // maximum, and the recorded range checks are expanded
+        let mut resolved = PlonkCircuit::new_ultra_plonk(RangeTableSize::Auto);
+        build(&mut resolved)?;
+        assert_eq!(resolved.resolve_range_bit_len()?, MAX_AUTO_RANGE_BIT_LEN);
+        assert_eq!(resolved.range_bit_len()?, MAX_AUTO_RANGE_BIT_LEN);
+        assert!(resolved.num_range_blocks().is_ok());
+        assert!(resolved.check_circuit_satisfiability(&[]).is_ok());
+
+        // a circuit whose range table size is not chosen yet takes that of the
+        // circuits appended to it, and the range checks of those appended to
+        // a circuit whose range table size is chosen are expanded
+        let mut appended = PlonkCircuit::<F>::new_ultra_plonk(RangeTableSize::Auto);
+        let y = appended.create_variable(F::from(3u32))?;
+        appended.range_gate(y, 2)?;
+        appended.append(&resolved, &[])?;
+        assert_eq!(appended.range_bit_len()?, MAX_AUTO_RANGE_BIT_LEN);
+        assert!(appended.check_circuit_satisfiability(&[]).is_ok());
+        let num_range_gates = resolved.size()?.num_range_gates;
+        resolved.append(&other, &[])?;
+        assert!(resolved.size()?.num_range_gates > num_range_gates);
+        assert!(resolved.check_circuit_satisfiability(&[]).is_ok());
+        let mut bad_other = other.clone();
+        *bad_other.witness_mut(x) = F::from(1u32 << 9);
+        let mut bad_circuit = appended.clone();
+        bad_circuit.append(&bad_other, &[])?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut fixed = PlonkCircuit::new_ultra_plonk(8);
+        assert!(fixed.append(&resolved, &[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_compile() -> Result<(), PlonkError> {
+        test_compile_helper::<FqEd254>()?;
+        test_compile_helper::<FqEd377>()?;
+        test_compile_helper::<FqEd381>()?;
+        test_compile_helper::<Fq377>()
+    }
+
+    fn test_compile_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let (turbo, _) = create_turbo_plonk_instance::<F>()?;
+        let (ultra, _) = create_ultra_plonk_instance::<F>()?;
+        for mut circuit in [turbo, ultra] {
+            circuit.create_named_public_variable("x", F::from(7u32))?;
+            assert!(circuit.compile().is_err());
+            circuit.finalize_for_arithmetization()?;
+
+            // the compiled circuit is serialized and reloaded
+            let mut bytes = vec![];
+            circuit.compile()?.serialize(&mut bytes)?;
+            let compiled = CompiledCircuit::<F>::deserialize(&bytes[..])?;
+            assert_eq!(compiled, circuit.compile()?);
+            assert_eq!(compiled.num_vars(), circuit.num_vars());
+            assert_eq!(compiled.num_inputs(), 3);
+            let reloaded = PlonkCircuit::from_compiled(&compiled)?;
+            assert!(reloaded.is_shape_only());
+            assert_eq!(reloaded.compile()?, compiled);
+            assert_eq!(reloaded.size()?, circuit.size()?);
+            assert_eq!(reloaded.public_input_position("x"), Some(2));
+            // the values of the constants are kept
+            assert_eq!(reloaded.witness(circuit.one())?, F::one());
+            assert_eq!(reloaded.witness(2)?, F::from(3u32));
+            assert_eq!(
+                reloaded.compute_selector_polynomials()?,
+                circuit.compute_selector_polynomials()?
+            );
+            assert_eq!(
+                reloaded.compute_extended_permutation_polynomials()?,
+                circuit.compute_extended_permutation_polynomials()?
+            );
+            if circuit.support_lookup() {
+                assert_eq!(
+                    reloaded.compute_range_table_polynomial()?,
+                    circuit.compute_range_table_polynomial()?
+                );
+                assert_eq!(
+                    reloaded.compute_key_table_polynomial()?,
+                    circuit.compute_key_table_polynomial()?
+                );
+            }
+            assert!(reloaded.compute_wire_polynomials().is_err());
+
+            // inconsistent compiled circuits
+            let n = compiled.eval_domain_size();
+            let mut bad = compiled.clone();
+            bad.gate_kinds[n - 1] = 4;
+            assert!(PlonkCircuit::from_compiled(&bad).is_err());
+            // the public input gates are in the front
+            let mut bad = compiled.clone();
+            bad.gate_kinds[0] = bad.gate_kinds[n - 1];
+            assert!(PlonkCircuit::from_compiled(&bad).is_err());
+            // a selector of a padding gate
+            let mut bad = compiled.clone();
+            bad.selectors[0][n - 1] = F::one();
+            assert!(PlonkCircuit::from_compiled(&bad).is_err());
+            let mut bad = compiled.clone();
+            bad.wire_variables[0][0] = bad.num_vars;
+            assert!(PlonkCircuit::from_compiled(&bad).is_err());
+            let mut bad = compiled.clone();
+            bad.wire_permutation.pop();
+            assert!(PlonkCircuit::from_compiled(&bad).is_err());
+            let mut bad = compiled;
+            bad.eval_domain_size = 3;
+            assert!(PlonkCircuit::from_compiled(&bad).is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_optimize() -> Result<(), PlonkError> {
+        test_optimize_helper::<FqEd254>()?;
+        test_optimize_helper::<FqEd377>()?;
+        test_optimize_helper::<FqEd381>()?;
+        test_optimize_helper::<Fq377>()
+    }
+
+    fn test_optimize_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(3u32))?;
+        // merged equality gate
+        circuit.equal_gate(a, b)?;
+        // duplicate constant gate
+        let k1 = circuit.create_constant_variable(F::from(5u32))?;
+        let k2 = circuit.create_constant_variable(F::from(5u32))?;
+        // folded gates
+        let c = circuit.mul(b, k2)?;
+        let d = circuit.add(k1, k2)?;
+        // duplicate gate once `a` and `b` are merged
+        let e = circuit.mul(a, a)?;
+        circuit.mul_gate(b, a, e)?;
+        // duplicate of the constant gate of the zero variable
+        circuit.create_constant_variable(F::zero())?;
+        // trivially satisfied once folded, with an unused constant
+        let k3 = circuit.create_constant_variable(F::from(10u32))?;
+        circuit.add_gate(k1, k2, k3)?;
+        let x = circuit.create_public_variable(F::from(3u32))?;
+        assert_eq!(circuit.num_gates(), 13);
+
+        let report = circuit.optimize()?;
+        assert_eq!(
+            report,
+            OptimizationReport {
+                num_gates_before: 13,
+                num_gates_after: 6,
+                num_folded_gates: 2,
+                num_merged_equality_gates: 1,
+                num_duplicate_gates: 3,
+                // the folded gate and the constant gates of `k1` and `k3`
+                num_dead_gates: 3,
+            }
+        );
+        assert_eq!(circuit.num_gates(), 6);
+        assert_eq!(circuit.public_input()?, vec![F::from(3u32)]);
+        assert_eq!(circuit.witness(x)?, F::from(3u32));
+        assert!(circuit
+            .check_circuit_satisfiability(&[F::from(3u32)])
+            .is_ok());
+        // the remaining gates still constrain the variables
+        for var in [c, d, e] {
+            *circuit.witness_mut(var) += F::one();
+            assert!(circuit
+                .check_circuit_satisfiability(&[F::from(3u32)])
+                .is_err());
+            *circuit.witness_mut(var) -= F::one();
+        }
+        // optimizing again changes nothing
+        assert_eq!(circuit.optimize()?.num_gates_after, 6);
+
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(circuit.eval_domain_size()?, 8);
+        assert!(circuit.optimize().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_unconstrained_variables() -> Result<(), PlonkError> {
+        test_unconstrained_variables_helper::<FqEd254>()?;
+        test_unconstrained_variables_helper::<FqEd377>()?;
+        test_unconstrained_variables_helper::<FqEd381>()?;
+        test_unconstrained_variables_helper::<Fq377>()
+    }
+
+    fn test_unconstrained_variables_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(4);
+        let a = circuit.create_variable(F::from(3u32))?;
+        let b = circuit.create_variable(F::from(5u32))?;
+        let c = circuit.mul(a, b)?;
+        circuit.create_public_variable(F::from(15u32))?;
+        assert_eq!(circuit.unconstrained_variables(), vec![]);
+
+        // a variable in no gate
+        let d = circuit.create_variable(F::from(7u32))?;
+        // a variable on a wire ignored by its gate
+        let e = circuit.create_variable(F::from(9u32))?;
+        circuit.insert_gate(&[c, c, e, 0, 0], Box::new(EqualityGate))?;
+        // a range checked variable
+        let f = circuit.create_variable(F::from(2u32))?;
+        circuit.add_range_check_variable(f)?;
+        assert_eq!(circuit.unconstrained_variables(), vec![d, e]);
+
+        // the padding gates do not constrain their variables
+        circuit.finalize_for_arithmetization()?;
+        assert_eq!(circuit.unconstrained_variables(), vec![d, e]);
+        Ok(())
+    }
+
     #[test]
     fn test_add() -> Result<(), PlonkError> {
         test_add_helper::<FqEd254>()?;
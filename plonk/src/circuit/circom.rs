@@ -0,0 +1,431 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Import of circom circuits from the binary `.r1cs` constraint files and
+//! `.wtns` witness files output by the circom compiler and its witness
+//! generators, so that they can be proven with the Plonk provers of this
+//! crate.
+//!
+//! Both formats start with a 4-byte magic string, a `u32` version and a
+//! `u32` number of sections, each section being a `u32` type and a `u64`
+//! size followed by its content. The integers are little-endian, and so are
+//! the field elements, which are in their canonical form. The wire `0` is the
+//! constant one, and it is followed by the public outputs, the public inputs,
+//! the private inputs and the internal wires. The constraints of the `.r1cs`
+//! file are converted as the arkworks R1CS constraints are, c.f.
+//! `PlonkCircuit::import_r1cs`.
+
+use super::{Circuit, PlonkCircuit, Variable};
+use crate::errors::{CircuitError::ParameterError, PlonkError};
+use ark_ff::{BigInteger, FpParameters, PrimeField};
+use ark_std::{
+    collections::HashMap,
+    convert::TryInto,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// The magic string of a `.r1cs` file.
+const R1CS_MAGIC: &[u8; 4] = b"r1cs";
+/// The magic string of a `.wtns` file.
+const WTNS_MAGIC: &[u8; 4] = b"wtns";
+
+/// The sections of a `.r1cs` file.
+const R1CS_HEADER_SECTION: u32 = 1;
+const R1CS_CONSTRAINTS_SECTION: u32 = 2;
+const R1CS_CUSTOM_GATES_LIST_SECTION: u32 = 4;
+const R1CS_CUSTOM_GATES_USES_SECTION: u32 = 5;
+
+/// The sections of a `.wtns` file.
+const WTNS_HEADER_SECTION: u32 = 1;
+const WTNS_VALUES_SECTION: u32 = 2;
+
+/// A linear combination of wires, as terms `(coeff, wire)`.
+type CircomLc<F> = Vec<(F, usize)>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A circom R1CS constraint system parsed from a `.r1cs` file.
+pub struct CircomR1cs<F: PrimeField> {
+    // the number of wires, including the constant one
+    num_wires: usize,
+    // the numbers of public outputs, public inputs and private inputs
+    num_pub_outputs: usize,
+    num_pub_inputs: usize,
+    num_prv_inputs: usize,
+    // the constraints `<a, w> * <b, w> = <c, w>`
+    constraints: Vec<(CircomLc<F>, CircomLc<F>, CircomLc<F>)>,
+}
+
+impl<F: PrimeField> CircomR1cs<F> {
+    /// Parse the content of a `.r1cs` file.
+    /// Return error if the file is malformed, if its prime is not the
+    /// modulus of `F`, or if it has custom gates.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, PlonkError> {
+        let sections = read_sections(bytes, R1CS_MAGIC)?;
+        if sections.contains_key(&R1CS_CUSTOM_GATES_LIST_SECTION)
+            || sections.contains_key(&R1CS_CUSTOM_GATES_USES_SECTION)
+        {
+            return Err(ParameterError("circom custom gates are not supported".to_string()).into());
+        }
+
+        let mut header = Reader::new(section(&sections, R1CS_HEADER_SECTION)?);
+        let field_len = read_prime::<F>(&mut header)?;
+        let num_wires = header.read_u32()? as usize;
+        let num_pub_outputs = header.read_u32()? as usize;
+        let num_pub_inputs = header.read_u32()? as usize;
+        let num_prv_inputs = header.read_u32()? as usize;
+        let _num_labels = header.read_u64()?;
+        let num_constraints = header.read_u32()? as usize;
+        if num_wires == 0 || 1 + num_pub_outputs + num_pub_inputs + num_prv_inputs > num_wires {
+            return Err(circom_error("the numbers of wires are inconsistent"));
+        }
+
+        let mut reader = Reader::new(section(&sections, R1CS_CONSTRAINTS_SECTION)?);
+        let mut read_lc = || -> Result<CircomLc<F>, PlonkError> {
+            let num_terms = reader.read_u32()? as usize;
+            (0..num_terms)
+                .map(|_| {
+                    let wire = reader.read_u32()? as usize;
+                    if wire >= num_wires {
+                        return Err(circom_error(&format!("invalid wire {}", wire)));
+                    }
+                    Ok((reader.read_field(field_len)?, wire))
+                })
+                .collect()
+        };
+        let constraints = (0..num_constraints)
+            .map(|_| Ok((read_lc()?, read_lc()?, read_lc()?)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+
+        Ok(Self {
+            num_wires,
+            num_pub_outputs,
+            num_pub_inputs,
+            num_prv_inputs,
+            constraints,
+        })
+    }
+
+    /// The number of wires, including the constant one.
+    pub fn num_wires(&self) -> usize {
+        self.num_wires
+    }
+
+    /// The number of public signals, i.e. the public outputs followed by the
+    /// public inputs.
+    pub fn num_public_signals(&self) -> usize {
+        self.num_pub_outputs + self.num_pub_inputs
+    }
+
+    /// The number of private inputs.
+    pub fn num_private_inputs(&self) -> usize {
+        self.num_prv_inputs
+    }
+
+    /// The number of constraints.
+    pub fn num_constraints(&self) -> usize {
+        self.constraints.len()
+    }
+}
+
+/// Parse the content of a `.wtns` file, i.e. the values of all the wires of
+/// a circom circuit, starting with the constant one.
+/// Return error if the file is malformed, or if its prime is not the modulus
+/// of `F`.
+pub fn circom_witness_from_bytes<F: PrimeField>(bytes: &[u8]) -> Result<Vec<F>, PlonkError> {
+    let sections = read_sections(bytes, WTNS_MAGIC)?;
+    let mut header = Reader::new(section(&sections, WTNS_HEADER_SECTION)?);
+    let field_len = read_prime::<F>(&mut header)?;
+    let num_wires = header.read_u32()? as usize;
+    let mut reader = Reader::new(section(&sections, WTNS_VALUES_SECTION)?);
+    (0..num_wires)
+        .map(|_| reader.read_field(field_len))
+        .collect()
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Append the constraints of the circom circuit `r1cs`, whose public
+    /// signals, i.e. its public outputs then its public inputs, become public
+    /// inputs of this circuit in the same order. The values of the wires are
+    /// given by `witness`, e.g. parsed with `circom_witness_from_bytes`, or
+    /// are zero if it is `None`, e.g. to preprocess the circuit in a
+    /// shape-only circuit, c.f. `is_shape_only`.
+    /// Return the variable of this circuit of each wire.
+    /// Return error if the witness does not have a value for each wire, or
+    /// if its first value, for the constant one wire, is not one.
+    pub fn import_circom(
+        &mut self,
+        r1cs: &CircomR1cs<F>,
+        witness: Option<&[F]>,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        if let Some(witness) = witness {
+            if witness.len() != r1cs.num_wires {
+                return Err(ParameterError(format!(
+                    "the witness has {} values for {} wires",
+                    witness.len(),
+                    r1cs.num_wires
+                ))
+                .into());
+            }
+            if witness[0] != F::one() {
+                return Err(ParameterError(
+                    "the value of the constant wire of the witness is not one".to_string(),
+                )
+                .into());
+            }
+        }
+        let value = |wire: usize| witness.map_or(F::zero(), |witness| witness[wire]);
+
+        let mut vars = Vec::with_capacity(r1cs.num_wires);
+        vars.push(self.one());
+        for wire in 1..r1cs.num_wires {
+            let var = if wire <= r1cs.num_public_signals() {
+                self.create_public_variable(value(wire))?
+            } else {
+                self.create_variable(value(wire))?
+            };
+            vars.push(var);
+        }
+        for (a, b, c) in r1cs.constraints.iter() {
+            self.r1cs_constraint_gate(a, b, c, &vars)?;
+        }
+        Ok(vars)
+    }
+}
+
+// A cursor over the bytes of a file.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+
+    fn read(&mut self, len: usize) -> Result<&'a [u8], PlonkError> {
+        if self.bytes.len() < len {
+            return Err(circom_error("unexpected end of file"));
+        }
+        let (read, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(read)
+    }
+
+    fn read_u32(&mut self) -> Result<u32, PlonkError> {
+        Ok(u32::from_le_bytes(self.read(4)?.try_into().unwrap())) // safe unwrap
+    }
+
+    fn read_u64(&mut self) -> Result<u64, PlonkError> {
+        Ok(u64::from_le_bytes(self.read(8)?.try_into().unwrap())) // safe unwrap
+    }
+
+    fn read_field<F: PrimeField>(&mut self, len: usize) -> Result<F, PlonkError> {
+        Ok(F::from_le_bytes_mod_order(self.read(len)?))
+    }
+}
+
+// Read the sections of a file starting with `magic`, by type.
+fn read_sections<'a>(
+    bytes: &'a [u8],
+    magic: &[u8; 4],
+) -> Result<HashMap<u32, &'a [u8]>, PlonkError> {
+    let mut reader = Reader::new(bytes);
+    if reader.read(4)? != magic {
+        return Err(circom_error("invalid magic string"));
+    }
+    let _version = reader.read_u32()?;
+    let num_sections = reader.read_u32()?;
+    let mut sections = HashMap::new();
+    for _ in 0..num_sections {
+        let section_type = reader.read_u32()?;
+        let len = reader.read_u64()? as usize;
+        sections.insert(section_type, reader.read(len)?);
+    }
+    Ok(sections)
+}
+
+fn section<'a>(
+    sections: &HashMap<u32, &'a [u8]>,
+    section_type: u32,
+) -> Result<&'a [u8], PlonkError> {
+    sections
+        .get(&section_type)
+        .copied()
+        .ok_or_else(|| circom_error(&format!("missing section {}", section_type)))
+}
+
+// Read the length of the field elements and the prime of a header, which has
+// to be the modulus of `F`.
+fn read_prime<F: PrimeField>(header: &mut Reader) -> Result<usize, PlonkError> {
+    let field_len = header.read_u32()? as usize;
+    let prime = header.read(field_len)?;
+    let modulus = F::Params::MODULUS.to_bytes_le();
+    let trim = |bytes: &[u8]| -> usize { bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1) };
+    if prime[..trim(prime)] != modulus[..trim(&modulus)] {
+        return Err(circom_error("the prime is not the modulus of the field"));
+    }
+    Ok(field_len)
+}
+
+fn circom_error(msg: &str) -> PlonkError {
+    ParameterError(String::from("invalid circom file: ") + msg).into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec;
+
+    fn field_len<F: PrimeField>() -> usize {
+        (F::size_in_bits() + 63) / 64 * 8
+    }
+
+    fn write_field<F: PrimeField>(bytes: &mut Vec<u8>, value: F) {
+        let mut value = value.into_repr().to_bytes_le();
+        value.resize(field_len::<F>(), 0);
+        bytes.extend_from_slice(&value);
+    }
+
+    fn write_file(magic: &[u8; 4], sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut bytes = magic.to_vec();
+        bytes.extend_from_slice(&2u32.to_le_bytes());
+        bytes.extend_from_slice(&(sections.len() as u32).to_le_bytes());
+        for (section_type, content) in sections.iter() {
+            bytes.extend_from_slice(&section_type.to_le_bytes());
+            bytes.extend_from_slice(&(content.len() as u64).to_le_bytes());
+            bytes.extend_from_slice(content);
+        }
+        bytes
+    }
+
+    fn write_header<F: PrimeField>(bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&(field_len::<F>() as u32).to_le_bytes());
+        let mut modulus = F::Params::MODULUS.to_bytes_le();
+        modulus.resize(field_len::<F>(), 0);
+        bytes.extend_from_slice(&modulus);
+    }
+
+    // The wires are the constant one, the public output y, the private input
+    // x and the internal wire x^2, with x^3 + x + 5 = y, i.e. the constraints
+    // x * x = x^2 and x^2 * x = y - x - 5.
+    fn r1cs_file<F: PrimeField>() -> Vec<u8> {
+        let mut header = vec![];
+        write_header::<F>(&mut header);
+        for n in [4u32, 1, 0, 1] {
+            header.extend_from_slice(&n.to_le_bytes());
+        }
+        header.extend_from_slice(&4u64.to_le_bytes());
+        header.extend_from_slice(&2u32.to_le_bytes());
+
+        let lcs: [&[(u32, F)]; 6] = [
+            &[(2, F::one())],
+            &[(2, F::one())],
+            &[(3, F::one())],
+            &[(3, F::one())],
+            &[(2, F::one())],
+            &[(1, F::one()), (2, -F::one()), (0, -F::from(5u32))],
+        ];
+        let mut constraints = vec![];
+        for lc in lcs.iter() {
+            constraints.extend_from_slice(&(lc.len() as u32).to_le_bytes());
+            for &(wire, coeff) in lc.iter() {
+                constraints.extend_from_slice(&wire.to_le_bytes());
+                write_field(&mut constraints, coeff);
+            }
+        }
+        let labels: Vec<u8> = (0..4u64).flat_map(|i| i.to_le_bytes()).collect();
+        write_file(
+            R1CS_MAGIC,
+            &[
+                (R1CS_HEADER_SECTION, header),
+                (R1CS_CONSTRAINTS_SECTION, constraints),
+                (3, labels),
+            ],
+        )
+    }
+
+    fn wtns_file<F: PrimeField>(values: &[F]) -> Vec<u8> {
+        let mut header = vec![];
+        write_header::<F>(&mut header);
+        header.extend_from_slice(&(values.len() as u32).to_le_bytes());
+        let mut content = vec![];
+        for &value in values.iter() {
+            write_field(&mut content, value);
+        }
+        write_file(
+            WTNS_MAGIC,
+            &[
+                (WTNS_HEADER_SECTION, header),
+                (WTNS_VALUES_SECTION, content),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_circom() -> Result<(), PlonkError> {
+        test_circom_helper::<FqEd254>()?;
+        test_circom_helper::<FqEd377>()?;
+        test_circom_helper::<FqEd381>()?;
+        test_circom_helper::<Fq377>()
+    }
+
+    fn test_circom_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let r1cs = CircomR1cs::<F>::from_bytes(&r1cs_file::<F>())?;
+        assert_eq!(r1cs.num_wires(), 4);
+        assert_eq!(r1cs.num_public_signals(), 1);
+        assert_eq!(r1cs.num_private_inputs(), 1);
+        assert_eq!(r1cs.num_constraints(), 2);
+        let values: Vec<F> = [1u32, 35, 3, 9].iter().map(|&v| F::from(v)).collect();
+        let witness = circom_witness_from_bytes::<F>(&wtns_file(&values))?;
+        assert_eq!(witness, values);
+
+        for mut circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            let vars = circuit.import_circom(&r1cs, Some(&witness))?;
+            assert_eq!(vars.len(), 4);
+            assert_eq!(circuit.num_inputs(), 1);
+            let y = F::from(35u32);
+            assert!(circuit.check_circuit_satisfiability(&[y]).is_ok());
+            assert!(circuit
+                .check_circuit_satisfiability(&[y + F::one()])
+                .is_err());
+            *circuit.witness_mut(vars[3]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[y]).is_err());
+        }
+        let mut shape = PlonkCircuit::<F>::new_turbo_plonk_shape_only();
+        shape.import_circom(&r1cs, None)?;
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        circuit.import_circom(&r1cs, Some(&witness))?;
+        assert_eq!(shape.size()?, circuit.size()?);
+
+        // invalid witnesses
+        assert!(circuit.import_circom(&r1cs, Some(&witness[1..])).is_err());
+        let mut bad_witness = witness.clone();
+        bad_witness[0] = F::zero();
+        assert!(circuit.import_circom(&r1cs, Some(&bad_witness)).is_err());
+
+        // malformed files
+        let bytes = r1cs_file::<F>();
+        assert!(CircomR1cs::<F>::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        assert!(circom_witness_from_bytes::<F>(&bytes).is_err());
+        let mut bad_bytes = bytes.clone();
+        bad_bytes[0] = b'x';
+        assert!(CircomR1cs::<F>::from_bytes(&bad_bytes).is_err());
+        // the prime is the first field element of the file
+        let mut bad_bytes = bytes;
+        bad_bytes[4 + 4 + 4 + 4 + 8 + 4] ^= 1;
+        assert!(CircomR1cs::<F>::from_bytes(&bad_bytes).is_err());
+        Ok(())
+    }
+}
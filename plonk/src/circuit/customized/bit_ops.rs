@@ -0,0 +1,372 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Bitwise operations on words that are represented by the little-endian list
+//! of their boolean variables. These are the building blocks of the bit-oriented
+//! hash gadgets, e.g., SHA-256.
+//!
+//! Rotations and shifts are free: they only permute the list of bit
+//! variables. The boolean functions take one or two gates per bit, and the
+//! modular additions pack the words into field elements, add them natively
+//! and unpack the result, dropping the carry bits.
+
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, log2, string::ToString, vec::Vec};
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    // The bits of the `bit_len`-bit constant `value`, made of the constant
+    // false and true variables of the circuit.
+    pub(crate) fn constant_bits(&self, value: u64, bit_len: usize) -> Vec<BoolVar> {
+        (0..bit_len)
+            .map(|i| {
+                if i < 64 && (value >> i) & 1 == 1 {
+                    self.true_var()
+                } else {
+                    self.false_var()
+                }
+            })
+            .collect()
+    }
+
+    // The boolean functions below output boolean variables without a boolean
+    // constraint, as their gate only takes the values zero and one on
+    // boolean inputs.
+
+    // Obtain `a XOR b` for boolean variables `a` and `b`, i.e., `a + b - 2ab`.
+    // Cost: 1 constraint.
+    pub(crate) fn xor_bit(&mut self, a: BoolVar, b: BoolVar) -> Result<BoolVar, PlonkError> {
+        let zero = self.zero();
+        let c = self.gen_quad_poly(
+            &[a.into(), b.into(), zero, zero],
+            &[F::one(), F::one(), F::zero(), F::zero()],
+            &[-F::from(2u8), F::zero()],
+            F::zero(),
+        )?;
+        Ok(BoolVar(c))
+    }
+
+    // Obtain `NOT a AND b` for boolean variables `a` and `b`, i.e., `b - ab`.
+    // Cost: 1 constraint.
+    pub(crate) fn and_not_bit(&mut self, a: BoolVar, b: BoolVar) -> Result<BoolVar, PlonkError> {
+        let zero = self.zero();
+        let c = self.gen_quad_poly(
+            &[a.into(), b.into(), zero, zero],
+            &[F::zero(), F::one(), F::zero(), F::zero()],
+            &[-F::one(), F::zero()],
+            F::zero(),
+        )?;
+        Ok(BoolVar(c))
+    }
+
+    // Obtain the choice `e ? f : g` for boolean variables `e`, `f` and `g`,
+    // i.e., `ef - eg + g`. Cost: 1 constraint.
+    pub(crate) fn ch_bit(
+        &mut self,
+        e: BoolVar,
+        f: BoolVar,
+        g: BoolVar,
+    ) -> Result<BoolVar, PlonkError> {
+        let c = self.gen_quad_poly(
+            &[e.into(), f.into(), e.into(), g.into()],
+            &[F::zero(), F::zero(), F::zero(), F::one()],
+            &[F::one(), -F::one()],
+            F::zero(),
+        )?;
+        Ok(BoolVar(c))
+    }
+
+    // Obtain the majority of boolean variables `a`, `b` and `c`: it is `a` if
+    // `a == b`, and `c` otherwise, i.e., `a + tc - ta` where `t = a XOR b`.
+    // Cost: 2 constraints.
+    pub(crate) fn maj_bit(
+        &mut self,
+        a: BoolVar,
+        b: BoolVar,
+        c: BoolVar,
+    ) -> Result<BoolVar, PlonkError> {
+        let t = self.xor_bit(a, b)?;
+        let d = self.gen_quad_poly(
+            &[t.into(), c.into(), t.into(), a.into()],
+            &[F::zero(), F::zero(), F::zero(), F::one()],
+            &[F::one(), -F::one()],
+            F::zero(),
+        )?;
+        Ok(BoolVar(d))
+    }
+
+    // Apply the bitwise function `f` to the words `a` and `b` bit by bit.
+    pub(crate) fn map_bits2(
+        &mut self,
+        a: &[BoolVar],
+        b: &[BoolVar],
+        f: impl Fn(&mut Self, BoolVar, BoolVar) -> Result<BoolVar, PlonkError>,
+    ) -> Result<Vec<BoolVar>, PlonkError> {
+        if a.len() != b.len() {
+            return Err(ParameterError(format!(
+                "words of different lengths {} and {}",
+                a.len(),
+                b.len()
+            ))
+            .into());
+        }
+        a.iter()
+            .zip(b.iter())
+            .map(|(&x, &y)| f(self, x, y))
+            .collect()
+    }
+
+    // Apply the bitwise function `f` to the words `a`, `b` and `c` bit by bit.
+    pub(crate) fn map_bits3(
+        &mut self,
+        a: &[BoolVar],
+        b: &[BoolVar],
+        c: &[BoolVar],
+        f: impl Fn(&mut Self, BoolVar, BoolVar, BoolVar) -> Result<BoolVar, PlonkError>,
+    ) -> Result<Vec<BoolVar>, PlonkError> {
+        if a.len() != b.len() || a.len() != c.len() {
+            return Err(ParameterError(format!(
+                "words of different lengths {}, {} and {}",
+                a.len(),
+                b.len(),
+                c.len()
+            ))
+            .into());
+        }
+        a.iter()
+            .zip(b.iter())
+            .zip(c.iter())
+            .map(|((&x, &y), &z)| f(self, x, y, z))
+            .collect()
+    }
+
+    // Obtain `a XOR b` for words `a` and `b`.
+    pub(crate) fn xor_words(
+        &mut self,
+        a: &[BoolVar],
+        b: &[BoolVar],
+    ) -> Result<Vec<BoolVar>, PlonkError> {
+        self.map_bits2(a, b, Self::xor_bit)
+    }
+
+    // Obtain `a XOR b XOR c` for words `a`, `b` and `c`.
+    pub(crate) fn xor3_words(
+        &mut self,
+        a: &[BoolVar],
+        b: &[BoolVar],
+        c: &[BoolVar],
+    ) -> Result<Vec<BoolVar>, PlonkError> {
+        let t = self.xor_words(a, b)?;
+        self.xor_words(&t, c)
+    }
+
+    // Obtain the variable of the value `sum_i bits[i] * 2^i` of a word.
+    pub(crate) fn pack_bits(&mut self, bits: &[BoolVar]) -> Result<Variable, PlonkError> {
+        if bits.is_empty() {
+            return Err(ParameterError("cannot pack an empty word".to_string()).into());
+        }
+        let bits: Vec<Variable> = bits.iter().map(|&bit| bit.into()).collect();
+        self.check_vars_bound(&bits)?;
+        let mut value = F::zero();
+        for &bit in bits.iter().rev() {
+            value = value.double() + self.witness(bit)?;
+        }
+        let packed = self.create_variable(value)?;
+        self.decompose_vars_gate(bits, packed, F::from(2u8))?;
+        Ok(packed)
+    }
+
+    // Obtain the lowest `bit_len` bits of the variable `a`, which is known to
+    // be smaller than `2^(bit_len + num_carry_bits)`, e.g., a sum of words.
+    pub(crate) fn unpack_mod(
+        &mut self,
+        a: Variable,
+        bit_len: usize,
+        num_carry_bits: usize,
+    ) -> Result<Vec<BoolVar>, PlonkError> {
+        let mut bits = self.unpack_bool(a, bit_len + num_carry_bits)?;
+        bits.truncate(bit_len);
+        Ok(bits)
+    }
+
+    // Obtain `(sum_i words[i] + constant) mod 2^bit_len`, where `bit_len` is
+    // the length of the words.
+    pub(crate) fn add_words_mod(
+        &mut self,
+        words: &[&[BoolVar]],
+        constant: u64,
+    ) -> Result<Vec<BoolVar>, PlonkError> {
+        if words.is_empty() {
+            return Err(ParameterError("no word to add".to_string()).into());
+        }
+        let bit_len = words[0].len();
+        let packed = words
+            .iter()
+            .map(|word| {
+                if word.len() != bit_len {
+                    return Err(ParameterError(format!(
+                        "words of different lengths {} and {}",
+                        bit_len,
+                        word.len()
+                    ))
+                    .into());
+                }
+                self.pack_bits(word)
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.add_packed_words_mod(&packed, constant, bit_len)
+    }
+
+    // Same as `add_words_mod`, for words that are already packed with
+    // `pack_bits`.
+    pub(crate) fn add_packed_words_mod(
+        &mut self,
+        packed: &[Variable],
+        constant: u64,
+        bit_len: usize,
+    ) -> Result<Vec<BoolVar>, PlonkError> {
+        let mut sum = self.sum(packed)?;
+        let mut num_terms = packed.len();
+        if constant != 0 {
+            sum = self.add_constant(sum, &F::from(constant))?;
+            num_terms += 1;
+        }
+        // the sum of `num_terms` words is smaller than `num_terms * 2^bit_len`
+        let num_carry_bits = log2(num_terms) as usize;
+        self.unpack_mod(sum, bit_len, num_carry_bits)
+    }
+}
+
+// Rotate the word `bits` right by `n` positions.
+pub(crate) fn rotr<T: Copy>(bits: &[T], n: usize) -> Vec<T> {
+    let len = bits.len();
+    (0..len).map(|i| bits[(i + n) % len]).collect()
+}
+
+// Rotate the word `bits` left by `n` positions.
+pub(crate) fn rotl<T: Copy>(bits: &[T], n: usize) -> Vec<T> {
+    let len = bits.len();
+    rotr(bits, (len - n % len) % len)
+}
+
+// Shift the word `bits` right by `n` positions, filling with `zero`.
+pub(crate) fn shr<T: Copy>(bits: &[T], n: usize, zero: T) -> Vec<T> {
+    (0..bits.len())
+        .map(|i| {
+            if i + n < bits.len() {
+                bits[i + n]
+            } else {
+                zero
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng, vec};
+
+    fn word_value<F: PrimeField>(circuit: &PlonkCircuit<F>, bits: &[BoolVar]) -> u64 {
+        bits.iter().enumerate().fold(0, |acc, (i, &bit)| {
+            if circuit.witness(bit.into()).unwrap() == F::one() {
+                acc | (1 << i)
+            } else {
+                acc
+            }
+        })
+    }
+
+    fn word_var<F: PrimeField>(circuit: &mut PlonkCircuit<F>, value: u32) -> Vec<BoolVar> {
+        (0..32)
+            .map(|i| circuit.create_bool_variable((value >> i) & 1 == 1).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_bit_ops() -> Result<(), PlonkError> {
+        test_bit_ops_helper::<FqEd254>()?;
+        test_bit_ops_helper::<FqEd377>()?;
+        test_bit_ops_helper::<FqEd381>()?;
+        test_bit_ops_helper::<Fq377>()
+    }
+
+    fn test_bit_ops_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        for _ in 0..4 {
+            let (x, y, z) = (rng.next_u32(), rng.next_u32(), rng.next_u32());
+            let (a, b, c) = (
+                word_var(&mut circuit, x),
+                word_var(&mut circuit, y),
+                word_var(&mut circuit, z),
+            );
+
+            let res = circuit.xor3_words(&a, &b, &c)?;
+            assert_eq!(word_value(&circuit, &res), (x ^ y ^ z) as u64);
+            let res = circuit.map_bits2(&a, &b, PlonkCircuit::and_not_bit)?;
+            assert_eq!(word_value(&circuit, &res), (!x & y) as u64);
+            let res = circuit.map_bits3(&a, &b, &c, PlonkCircuit::ch_bit)?;
+            assert_eq!(word_value(&circuit, &res), ((x & y) ^ (!x & z)) as u64);
+            let res = circuit.map_bits3(&a, &b, &c, PlonkCircuit::maj_bit)?;
+            assert_eq!(
+                word_value(&circuit, &res),
+                ((x & y) ^ (x & z) ^ (y & z)) as u64
+            );
+
+            let res = circuit.add_words_mod(&[&a, &b, &c], 0x428a2f98)?;
+            assert_eq!(
+                word_value(&circuit, &res),
+                x.wrapping_add(y).wrapping_add(z).wrapping_add(0x428a2f98) as u64
+            );
+            let packed = circuit.pack_bits(&a)?;
+            assert_eq!(circuit.witness(packed)?, F::from(x));
+
+            assert_eq!(word_value(&circuit, &rotr(&a, 7)), x.rotate_right(7) as u64);
+            assert_eq!(word_value(&circuit, &rotl(&a, 7)), x.rotate_left(7) as u64);
+            assert_eq!(
+                word_value(&circuit, &shr(&a, 10, circuit.false_var())),
+                (x >> 10) as u64
+            );
+            assert_eq!(
+                word_value(&circuit, &circuit.constant_bits(x as u64, 32)),
+                x as u64
+            );
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // words of different lengths
+        let a = circuit.constant_bits(1, 32);
+        let b = circuit.constant_bits(1, 16);
+        assert!(circuit.xor_words(&a, &b).is_err());
+        assert!(circuit.add_words_mod(&[&a, &b], 0).is_err());
+        assert!(circuit.add_words_mod(&[], 0).is_err());
+
+        // a wrong xor output is rejected
+        let a = word_var(&mut circuit, 0xdeadbeef);
+        let b = word_var(&mut circuit, 0x12345678);
+        let res = circuit.xor_words(&a, &b)?;
+        *circuit.witness_mut(res[0].into()) = F::from(2u8);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_rotations() {
+        let vars: Vec<Variable> = (0..8).collect();
+        assert_eq!(rotr(&vars, 3), vec![3, 4, 5, 6, 7, 0, 1, 2]);
+        assert_eq!(rotl(&vars, 3), vec![5, 6, 7, 0, 1, 2, 3, 4]);
+        assert_eq!(rotl(&vars, 0), vars);
+        assert_eq!(shr(&vars, 3, 100), vec![3, 4, 5, 6, 7, 100, 100, 100]);
+    }
+}
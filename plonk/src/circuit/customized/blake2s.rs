@@ -0,0 +1,498 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the BLAKE2s-256 hash function, with optional
+//! personalization as used by Zcash.
+//!
+//! Messages and digests are represented by byte variables, i.e., variables
+//! whose values are in `[0, 256)`. On UltraPlonk circuits whose range bit
+//! length divides 32, the hash functions represent words by `U32Var`s, so
+//! that the additions are range checked with the range table and the
+//! rotations and XORs are computed on chunks looked up in the range and logic
+//! tables. Otherwise, and in `blake2s_compress`, words are represented by
+//! their little-endian boolean variables, c.f. `bit_ops`. The message length
+//! is fixed at circuit generation time, so that the padding and the block
+//! counters are constants.
+
+use super::{bit_ops::rotr, bytes::BytesVar, ultraplonk::uint::U32Var};
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+
+/// BLAKE2s initialization vector.
+const BLAKE2S_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// BLAKE2s message word permutations, one per round.
+const BLAKE2S_SIGMA: [[usize; 16]; 10] = [
+    [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15],
+    [14, 10, 4, 8, 9, 15, 13, 6, 1, 12, 0, 2, 11, 7, 5, 3],
+    [11, 8, 12, 0, 5, 2, 15, 13, 10, 14, 3, 6, 7, 1, 9, 4],
+    [7, 9, 3, 1, 13, 12, 11, 14, 2, 6, 5, 10, 4, 0, 15, 8],
+    [9, 0, 5, 7, 2, 4, 10, 15, 14, 1, 11, 12, 6, 8, 3, 13],
+    [2, 12, 6, 10, 0, 11, 8, 3, 4, 13, 7, 5, 15, 14, 1, 9],
+    [12, 5, 1, 15, 14, 13, 4, 10, 0, 7, 6, 3, 9, 2, 8, 11],
+    [13, 11, 7, 14, 12, 1, 3, 9, 5, 0, 15, 4, 8, 6, 2, 10],
+    [6, 15, 14, 9, 11, 3, 0, 8, 12, 2, 13, 7, 1, 4, 10, 5],
+    [10, 2, 8, 4, 7, 6, 1, 5, 15, 11, 9, 14, 3, 12, 13, 0],
+];
+
+/// Number of bits of a BLAKE2s word.
+const WORD_LEN: usize = 32;
+
+/// Number of bytes of a BLAKE2s block.
+const BLOCK_LEN: usize = 64;
+
+/// The initialization vector of a compression, in which the counter and the
+/// finalization flag, as constants, are folded.
+fn compress_iv(counter: u64, is_last: bool) -> [u32; 8] {
+    let mut iv = BLAKE2S_IV;
+    iv[4] ^= counter as u32;
+    iv[5] ^= (counter >> 32) as u32;
+    if is_last {
+        iv[6] ^= u32::MAX;
+    }
+    iv
+}
+
+/// The initial chaining state with the parameter block of a 32-byte digest,
+/// no key, fanout and depth 1, and the given personalization.
+fn initial_state(personalization: &[u8; 8]) -> [u32; 8] {
+    let mut h = BLAKE2S_IV;
+    h[0] ^= 0x0101_0020;
+    h[6] ^= u32::from_le_bytes([
+        personalization[0],
+        personalization[1],
+        personalization[2],
+        personalization[3],
+    ]);
+    h[7] ^= u32::from_le_bytes([
+        personalization[4],
+        personalization[5],
+        personalization[6],
+        personalization[7],
+    ]);
+    h
+}
+
+/// The counter of the `i`-th of `num_blocks` blocks of a message of `msg_len`
+/// bytes, i.e. the number of message bytes hashed up to this block.
+fn block_counter(i: usize, num_blocks: usize, msg_len: usize) -> u64 {
+    if i + 1 == num_blocks {
+        msg_len as u64
+    } else {
+        ((i + 1) * BLOCK_LEN) as u64
+    }
+}
+
+/// Trait for the BLAKE2s gadgets.
+pub trait Blake2sGadget<F: PrimeField> {
+    /// Apply the BLAKE2s compression function to a message block.
+    /// * `state` - the 8 words of the chaining state, each given by its 32
+    ///   little-endian boolean variables
+    /// * `block` - the 16 words of the message block, in the same form
+    /// * `counter` - the number of message bytes hashed so far, including
+    ///   those of this block
+    /// * `is_last` - whether this block is the last one
+    /// * `returns` - the 8 words of the new chaining state
+    fn blake2s_compress(
+        &mut self,
+        state: &[Vec<BoolVar>],
+        block: &[Vec<BoolVar>],
+        counter: u64,
+        is_last: bool,
+    ) -> Result<Vec<Vec<BoolVar>>, PlonkError>;
+
+    /// BLAKE2s-256 hash of a message, without key nor personalization.
+    /// * `msg_vars` - the bytes of the message; each of them is constrained
+    ///   to be in `[0, 256)`
+    /// * `returns` - the 32 bytes of the digest
+    fn blake2s(&mut self, msg_vars: &[Variable]) -> Result<Vec<Variable>, PlonkError> {
+        self.blake2s_personalized(msg_vars, &[0u8; 8])
+    }
+
+    /// BLAKE2s-256 hash of a byte string, without key nor personalization,
+    /// whose digest carries the range guarantee of its bytes, as it is
+    /// packed from bits or split into range checked bytes.
+    fn blake2s_bytes(&mut self, msg: &BytesVar) -> Result<BytesVar, PlonkError> {
+        Ok(BytesVar::new_unchecked(self.blake2s(&msg.vars())?))
+    }
+
+    /// BLAKE2s-256 hash of a message with the 8-byte `personalization`, e.g.,
+    /// `b"Zcash_PH"`.
+    /// * `msg_vars` - the bytes of the message; each of them is constrained
+    ///   to be in `[0, 256)`
+    /// * `returns` - the 32 bytes of the digest
+    fn blake2s_personalized(
+        &mut self,
+        msg_vars: &[Variable],
+        personalization: &[u8; 8],
+    ) -> Result<Vec<Variable>, PlonkError>;
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    // The BLAKE2s mixing function `G` applied to the words at indices `a`,
+    // `b`, `c` and `d` of `v`, with message words `x` and `y`.
+    fn blake2s_mix(
+        &mut self,
+        v: &mut [Vec<BoolVar>],
+        (a, b, c, d): (usize, usize, usize, usize),
+        x: &[BoolVar],
+        y: &[BoolVar],
+    ) -> Result<(), PlonkError> {
+        v[a] = self.add_words_mod(&[&v[a], &v[b], x], 0)?;
+        v[d] = rotr(&self.xor_words(&v[d], &v[a])?, 16);
+        v[c] = self.add_words_mod(&[&v[c], &v[d]], 0)?;
+        v[b] = rotr(&self.xor_words(&v[b], &v[c])?, 12);
+        v[a] = self.add_words_mod(&[&v[a], &v[b], y], 0)?;
+        v[d] = rotr(&self.xor_words(&v[d], &v[a])?, 8);
+        v[c] = self.add_words_mod(&[&v[c], &v[d]], 0)?;
+        v[b] = rotr(&self.xor_words(&v[b], &v[c])?, 7);
+        Ok(())
+    }
+
+    // `blake2s_mix` with integer words, where the right rotations by `n` are
+    // left rotations by `32 - n`.
+    fn blake2s_mix_uint(
+        &mut self,
+        v: &mut [U32Var],
+        (a, b, c, d): (usize, usize, usize, usize),
+        x: U32Var,
+        y: U32Var,
+    ) -> Result<(), PlonkError> {
+        v[a] = self.uint_sum(&[v[a], v[b], x], 0)?;
+        let t = self.uint_xor(v[d], v[a])?;
+        v[d] = self.uint_rotl(t, 16)?;
+        v[c] = self.uint_add(v[c], v[d])?;
+        let t = self.uint_xor(v[b], v[c])?;
+        v[b] = self.uint_rotl(t, 20)?;
+        v[a] = self.uint_sum(&[v[a], v[b], y], 0)?;
+        let t = self.uint_xor(v[d], v[a])?;
+        v[d] = self.uint_rotl(t, 24)?;
+        v[c] = self.uint_add(v[c], v[d])?;
+        let t = self.uint_xor(v[b], v[c])?;
+        v[b] = self.uint_rotl(t, 25)?;
+        Ok(())
+    }
+
+    // `blake2s_compress` with integer words.
+    fn blake2s_compress_uint(
+        &mut self,
+        state: &[U32Var],
+        block: &[U32Var],
+        counter: u64,
+        is_last: bool,
+    ) -> Result<Vec<U32Var>, PlonkError> {
+        let mut v: Vec<U32Var> = state.to_vec();
+        for &x in compress_iv(counter, is_last).iter() {
+            v.push(self.create_constant_u32_variable(x)?);
+        }
+
+        for sigma in BLAKE2S_SIGMA.iter() {
+            let m = |i: usize| block[sigma[i]];
+            self.blake2s_mix_uint(&mut v, (0, 4, 8, 12), m(0), m(1))?;
+            self.blake2s_mix_uint(&mut v, (1, 5, 9, 13), m(2), m(3))?;
+            self.blake2s_mix_uint(&mut v, (2, 6, 10, 14), m(4), m(5))?;
+            self.blake2s_mix_uint(&mut v, (3, 7, 11, 15), m(6), m(7))?;
+            self.blake2s_mix_uint(&mut v, (0, 5, 10, 15), m(8), m(9))?;
+            self.blake2s_mix_uint(&mut v, (1, 6, 11, 12), m(10), m(11))?;
+            self.blake2s_mix_uint(&mut v, (2, 7, 8, 13), m(12), m(13))?;
+            self.blake2s_mix_uint(&mut v, (3, 4, 9, 14), m(14), m(15))?;
+        }
+
+        (0..8)
+            .map(|i| {
+                let t = self.uint_xor(state[i], v[i])?;
+                self.uint_xor(t, v[i + 8])
+            })
+            .collect()
+    }
+
+    // `blake2s_personalized` with integer words, whose bytes are range checked
+    // with lookups.
+    fn blake2s_personalized_uint(
+        &mut self,
+        msg_vars: &[Variable],
+        personalization: &[u8; 8],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let mut bytes = msg_vars.to_vec();
+        while bytes.is_empty() || bytes.len() % BLOCK_LEN != 0 {
+            bytes.push(self.zero());
+        }
+        let mut state = initial_state(personalization)
+            .iter()
+            .map(|&x| self.create_constant_u32_variable(x))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+
+        let num_blocks = bytes.len() / BLOCK_LEN;
+        for (i, block) in bytes.chunks(BLOCK_LEN).enumerate() {
+            let block = block
+                .chunks(4)
+                .map(|word| self.uint_from_bytes_le(word))
+                .collect::<Result<Vec<U32Var>, PlonkError>>()?;
+            let counter = block_counter(i, num_blocks, msg_vars.len());
+            state = self.blake2s_compress_uint(&state, &block, counter, i + 1 == num_blocks)?;
+        }
+
+        let mut digest = Vec::with_capacity(32);
+        for &word in state.iter() {
+            digest.extend(self.uint_to_bytes_le(word)?);
+        }
+        Ok(digest)
+    }
+}
+
+impl<F> Blake2sGadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn blake2s_compress(
+        &mut self,
+        state: &[Vec<BoolVar>],
+        block: &[Vec<BoolVar>],
+        counter: u64,
+        is_last: bool,
+    ) -> Result<Vec<Vec<BoolVar>>, PlonkError> {
+        if state.len() != 8 || block.len() != 16 {
+            return Err(ParameterError(format!(
+                "BLAKE2s compression takes 8 state words and 16 block words, got {} and {}",
+                state.len(),
+                block.len()
+            ))
+            .into());
+        }
+        if let Some(word) = state
+            .iter()
+            .chain(block.iter())
+            .find(|word| word.len() != WORD_LEN)
+        {
+            return Err(ParameterError(format!(
+                "BLAKE2s words have {} bits, got {}",
+                WORD_LEN,
+                word.len()
+            ))
+            .into());
+        }
+
+        let mut v: Vec<Vec<BoolVar>> = state.to_vec();
+        for x in compress_iv(counter, is_last).iter() {
+            v.push(self.constant_bits(*x as u64, WORD_LEN));
+        }
+
+        for sigma in BLAKE2S_SIGMA.iter() {
+            let m = |i: usize| &block[sigma[i]];
+            self.blake2s_mix(&mut v, (0, 4, 8, 12), m(0), m(1))?;
+            self.blake2s_mix(&mut v, (1, 5, 9, 13), m(2), m(3))?;
+            self.blake2s_mix(&mut v, (2, 6, 10, 14), m(4), m(5))?;
+            self.blake2s_mix(&mut v, (3, 7, 11, 15), m(6), m(7))?;
+            self.blake2s_mix(&mut v, (0, 5, 10, 15), m(8), m(9))?;
+            self.blake2s_mix(&mut v, (1, 6, 11, 12), m(10), m(11))?;
+            self.blake2s_mix(&mut v, (2, 7, 8, 13), m(12), m(13))?;
+            self.blake2s_mix(&mut v, (3, 4, 9, 14), m(14), m(15))?;
+        }
+
+        (0..8)
+            .map(|i| self.xor3_words(&state[i], &v[i], &v[i + 8]))
+            .collect()
+    }
+
+    fn blake2s_personalized(
+        &mut self,
+        msg_vars: &[Variable],
+        personalization: &[u8; 8],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        if self.support_uint() {
+            return self.blake2s_personalized_uint(msg_vars, personalization);
+        }
+        // the bits of each byte of the message padded with zeros to a
+        // non-empty multiple of the block length, little-endian within the
+        // byte
+        let mut bytes: Vec<Vec<BoolVar>> = msg_vars
+            .iter()
+            .map(|&byte| self.unpack_bool(byte, 8))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        while bytes.is_empty() || bytes.len() % BLOCK_LEN != 0 {
+            bytes.push(self.constant_bits(0, 8));
+        }
+
+        let mut state: Vec<Vec<BoolVar>> = initial_state(personalization)
+            .iter()
+            .map(|&x| self.constant_bits(x as u64, WORD_LEN))
+            .collect();
+
+        let num_blocks = bytes.len() / BLOCK_LEN;
+        for (i, block) in bytes.chunks(BLOCK_LEN).enumerate() {
+            // words are little-endian
+            let block: Vec<Vec<BoolVar>> = block
+                .chunks(4)
+                .map(|word| word.iter().flatten().copied().collect())
+                .collect();
+            let counter = block_counter(i, num_blocks, msg_vars.len());
+            state = self.blake2s_compress(&state, &block, counter, i + 1 == num_blocks)?;
+        }
+
+        // the digest is the little-endian encoding of the state
+        let mut digest = Vec::with_capacity(32);
+        for word in state.iter() {
+            for byte in word.chunks(8) {
+                digest.push(self.pack_bits(byte)?);
+            }
+        }
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng, vec};
+    use blake2::{Blake2s256, Digest};
+
+    fn byte_vars<F: PrimeField>(circuit: &mut PlonkCircuit<F>, msg: &[u8]) -> Vec<Variable> {
+        msg.iter()
+            .map(|&b| circuit.create_variable(F::from(b)).unwrap())
+            .collect()
+    }
+
+    fn check_digest<F: PrimeField>(
+        circuit: &PlonkCircuit<F>,
+        digest_vars: &[Variable],
+        expected: &[u8],
+    ) -> Result<(), PlonkError> {
+        assert_eq!(digest_vars.len(), expected.len());
+        for (var, byte) in digest_vars.iter().zip(expected.iter()) {
+            assert_eq!(circuit.witness(*var)?, F::from(*byte));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_blake2s() -> Result<(), PlonkError> {
+        test_blake2s_helper::<FqEd254>()?;
+        test_blake2s_helper::<FqEd381>()?;
+        test_blake2s_helper::<Fq377>()
+    }
+
+    fn test_blake2s_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        for len in [0, 3, 64, 65] {
+            let mut msg = vec![0u8; len];
+            rng.fill_bytes(&mut msg);
+            let expected = Blake2s256::digest(&msg);
+
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let msg_vars = byte_vars(&mut circuit, &msg);
+            let digest_vars = circuit.blake2s(&msg_vars)?;
+            check_digest(&circuit, &digest_vars, &expected)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong digest byte is rejected
+            *circuit.witness_mut(digest_vars[0]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // personalization, test vector from Python's `hashlib.blake2s`
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let msg_vars = byte_vars(&mut circuit, b"abc");
+        let digest_vars = circuit.blake2s_personalized(&msg_vars, b"Zcash_PH")?;
+        let expected =
+            hex::decode("08e45664fe334fb302d65e6072d9f343967a9670bf8ed31c1a44f89282a3bdbe")
+                .unwrap();
+        check_digest(&circuit, &digest_vars, &expected)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a message byte out of range is rejected
+        let byte_var = circuit.create_variable(F::from(256u32))?;
+        assert!(circuit.blake2s(&[byte_var]).is_err());
+
+        // with integer words on UltraPlonk, in fewer gates
+        for len in [0, 3, 65] {
+            let mut msg = vec![0u8; len];
+            rng.fill_bytes(&mut msg);
+            let expected = Blake2s256::digest(&msg);
+
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+            let msg_vars = byte_vars(&mut circuit, &msg);
+            let digest_vars = circuit.blake2s(&msg_vars)?;
+            check_digest(&circuit, &digest_vars, &expected)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            let mut turbo_circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let msg_vars = byte_vars(&mut turbo_circuit, &msg);
+            turbo_circuit.blake2s(&msg_vars)?;
+            assert!(circuit.num_gates() < turbo_circuit.num_gates());
+
+            *circuit.witness_mut(digest_vars[31]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let msg_vars = byte_vars(&mut circuit, b"abc");
+        let digest_vars = circuit.blake2s_personalized(&msg_vars, b"Zcash_PH")?;
+        let expected =
+            hex::decode("08e45664fe334fb302d65e6072d9f343967a9670bf8ed31c1a44f89282a3bdbe")
+                .unwrap();
+        check_digest(&circuit, &digest_vars, &expected)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // a message byte out of range is range checked with a lookup
+        let byte_var = circuit.create_variable(F::from(256u32))?;
+        circuit.blake2s(&[byte_var])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // the compression of the single block of "abc", with boolean words
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let bool_words = |circuit: &mut PlonkCircuit<F>, values: &[u32]| {
+            values
+                .iter()
+                .map(|&x| {
+                    (0..WORD_LEN)
+                        .map(|i| circuit.create_bool_variable((x >> i) & 1 == 1).unwrap())
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut h = BLAKE2S_IV;
+        h[0] ^= 0x0101_0020;
+        let mut block = [0u32; 16];
+        block[0] = u32::from_le_bytes([b'a', b'b', b'c', 0]);
+        let state = bool_words(&mut circuit, &h);
+        let block = bool_words(&mut circuit, &block);
+        let new_state = circuit.blake2s_compress(&state, &block, 3, true)?;
+        let expected = Blake2s256::digest(b"abc");
+        for (word, w) in new_state.iter().zip(expected.chunks(4)) {
+            let value = word.iter().enumerate().fold(0u32, |acc, (i, &bit)| {
+                if circuit.witness(bit.into()).unwrap() == F::one() {
+                    acc | (1 << i)
+                } else {
+                    acc
+                }
+            });
+            assert_eq!(value, u32::from_le_bytes([w[0], w[1], w[2], w[3]]));
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // a non-boolean input bit is rejected
+        *circuit.witness_mut(state[2][9].into()) = F::from(2u8);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // malformed compression inputs
+        let word = bool_words(&mut circuit, &[0]).remove(0);
+        assert!(circuit
+            .blake2s_compress(&vec![word.clone(); 8], &vec![word.clone(); 15], 0, true)
+            .is_err());
+        assert!(circuit
+            .blake2s_compress(&vec![word[..16].to_vec(); 8], &vec![word; 16], 0, true)
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Byte variables carrying their range guarantee, e.g. to pass the digest of
+//! a hash gadget to another gadget, or to a conversion into a field element,
+//! without range checking its bytes again.
+//!
+//! A `U8Var` is a variable whose value is constrained to be in `[0, 256)`,
+//! either by the gadget which created it or by a range check when it is
+//! obtained from an arbitrary variable. A byte string is little-endian when
+//! converted from and to a field element, whose value has to be smaller than
+//! `2^(8 * num_bytes)`, with `8 * num_bytes` smaller than the bit length of
+//! the field so that the conversion is one-to-one.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::{format, vec::Vec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+/// A variable whose value is constrained to be a byte, i.e. in `[0, 256)`.
+pub struct U8Var(pub(crate) Variable);
+
+impl U8Var {
+    /// The variable of the value of the byte.
+    pub fn var(&self) -> Variable {
+        self.0
+    }
+}
+
+impl From<U8Var> for Variable {
+    fn from(b: U8Var) -> Self {
+        b.0
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+/// Variables of a byte string, each constrained to be in `[0, 256)`.
+pub struct BytesVar(pub(crate) Vec<U8Var>);
+
+impl BytesVar {
+    /// Wrap variables which are already constrained to be bytes, e.g. the
+    /// digest of a hash gadget, without adding constraints.
+    pub(crate) fn new_unchecked(vars: Vec<Variable>) -> Self {
+        BytesVar(vars.into_iter().map(U8Var).collect())
+    }
+
+    /// The number of bytes.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Whether the string is empty.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The byte variables.
+    pub fn bytes(&self) -> &[U8Var] {
+        &self.0
+    }
+
+    /// The variables of the values of the bytes, e.g. for the gadgets taking
+    /// byte variables.
+    pub fn vars(&self) -> Vec<Variable> {
+        self.0.iter().map(|b| b.0).collect()
+    }
+}
+
+impl From<Vec<U8Var>> for BytesVar {
+    fn from(bytes: Vec<U8Var>) -> Self {
+        BytesVar(bytes)
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a byte variable.
+    pub fn create_u8_variable(&mut self, value: u8) -> Result<U8Var, PlonkError> {
+        let var = self.create_variable(F::from(value))?;
+        self.u8_from_variable(var)
+    }
+
+    /// Create a constant byte variable.
+    pub fn create_constant_u8_variable(&mut self, value: u8) -> Result<U8Var, PlonkError> {
+        Ok(U8Var(self.create_constant_variable(F::from(value))?))
+    }
+
+    /// Obtain a byte variable from a variable whose value is range checked
+    /// to `[0, 256)` by this function.
+    /// Return error if the variable is invalid.
+    pub fn u8_from_variable(&mut self, var: Variable) -> Result<U8Var, PlonkError> {
+        self.range_gate(var, 8)?;
+        Ok(U8Var(var))
+    }
+
+    /// The value of a byte variable.
+    /// Return error if the variable is invalid.
+    pub fn u8_witness(&self, b: U8Var) -> Result<u8, PlonkError> {
+        Ok(self.witness(b.0)?.into_repr().to_bytes_le()[0])
+    }
+
+    /// Create the variables of a byte string.
+    pub fn create_bytes_variable(&mut self, bytes: &[u8]) -> Result<BytesVar, PlonkError> {
+        bytes
+            .iter()
+            .map(|&b| self.create_u8_variable(b))
+            .collect::<Result<Vec<_>, PlonkError>>()
+            .map(BytesVar)
+    }
+
+    /// Create the constant variables of a byte string.
+    pub fn create_constant_bytes_variable(&mut self, bytes: &[u8]) -> Result<BytesVar, PlonkError> {
+        bytes
+            .iter()
+            .map(|&b| self.create_constant_u8_variable(b))
+            .collect::<Result<Vec<_>, PlonkError>>()
+            .map(BytesVar)
+    }
+
+    /// Obtain the variables of a byte string from variables whose values are
+    /// range checked to `[0, 256)` by this function.
+    /// Return error if a variable is invalid.
+    pub fn bytes_from_variables(&mut self, vars: &[Variable]) -> Result<BytesVar, PlonkError> {
+        vars.iter()
+            .map(|&var| self.u8_from_variable(var))
+            .collect::<Result<Vec<_>, PlonkError>>()
+            .map(BytesVar)
+    }
+
+    /// The value of the variables of a byte string.
+    /// Return error if a variable is invalid.
+    pub fn bytes_witness(&self, bytes: &BytesVar) -> Result<Vec<u8>, PlonkError> {
+        bytes.0.iter().map(|&b| self.u8_witness(b)).collect()
+    }
+
+    /// Obtain the variable of the field element whose little-endian bytes are
+    /// `bytes`, without range checks as the bytes carry theirs.
+    /// Return error if the string is empty or too long for the field, or if
+    /// a variable is invalid.
+    pub fn bytes_to_field_le(&mut self, bytes: &BytesVar) -> Result<Variable, PlonkError> {
+        self.check_bytes_len(bytes.len())?;
+        let vars = bytes.vars();
+        self.check_vars_bound(&vars)?;
+        let mut value = F::zero();
+        for &var in vars.iter().rev() {
+            value = value * F::from(256u32) + self.witness(var)?;
+        }
+        let packed = self.create_variable(value)?;
+        self.decompose_vars_gate(vars, packed, F::from(256u32))?;
+        Ok(packed)
+    }
+
+    /// Obtain the `num_bytes` little-endian bytes of the variable `a`, which
+    /// is constrained to be smaller than `2^(8 * num_bytes)`.
+    /// Return error if `num_bytes` is zero or too large for the field, if the
+    /// value of `a` is too large, or if the variable is invalid.
+    pub fn field_to_bytes_le(
+        &mut self,
+        a: Variable,
+        num_bytes: usize,
+    ) -> Result<BytesVar, PlonkError> {
+        self.check_bytes_len(num_bytes)?;
+        self.check_var_bound(a)?;
+        let repr = self.witness(a)?.into_repr();
+        if repr.num_bits() as usize > 8 * num_bytes {
+            return Err(
+                ParameterError(format!("the value does not fit in {} bytes", num_bytes)).into(),
+            );
+        }
+        let values = repr.to_bytes_le();
+        let bytes = (0..num_bytes)
+            .map(|i| self.create_u8_variable(values[i]))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let bytes = BytesVar(bytes);
+        self.decompose_vars_gate(bytes.vars(), a, F::from(256u32))?;
+        Ok(bytes)
+    }
+
+    // Check that byte strings of `num_bytes` bytes map one-to-one to field
+    // elements.
+    fn check_bytes_len(&self, num_bytes: usize) -> Result<(), PlonkError> {
+        if num_bytes == 0 || 8 * num_bytes >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "a field element is converted from and to between 1 and {} bytes, got {}",
+                (F::size_in_bits() - 1) / 8,
+                num_bytes
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::sha2::Sha256Gadget;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec;
+
+    #[test]
+    fn test_bytes() -> Result<(), PlonkError> {
+        test_bytes_helper::<FqEd254>()?;
+        test_bytes_helper::<FqEd377>()?;
+        test_bytes_helper::<FqEd381>()?;
+        test_bytes_helper::<Fq377>()
+    }
+
+    fn test_bytes_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let values: Vec<u8> = (0..64).map(|i| (i * 37 + 5) as u8).collect();
+        let bytes = circuit.create_bytes_variable(&values)?;
+        assert_eq!(bytes.len(), 64);
+        assert_eq!(circuit.bytes_witness(&bytes)?, values);
+        let constant = circuit.create_constant_bytes_variable(&[0xff, 0])?;
+        assert_eq!(circuit.bytes_witness(&constant)?, vec![0xff, 0]);
+
+        // to and from field elements, up to the largest supported length
+        let max_len = (F::size_in_bits() - 1) / 8;
+        let prefix = BytesVar::from(bytes.bytes()[..max_len].to_vec());
+        let packed = circuit.bytes_to_field_le(&prefix)?;
+        assert_eq!(
+            circuit.witness(packed)?,
+            F::from_le_bytes_mod_order(&values[..max_len])
+        );
+        let unpacked = circuit.field_to_bytes_le(packed, max_len)?;
+        assert_eq!(
+            circuit.bytes_witness(&unpacked)?,
+            values[..max_len].to_vec()
+        );
+        assert!(circuit.field_to_bytes_le(packed, max_len + 1).is_err());
+
+        // the digest of a hash gadget is converted without range checks
+        let digest = circuit.sha256_bytes(&bytes)?;
+        assert_eq!(digest.len(), 32);
+        let digest_prefix = BytesVar::from(digest.bytes()[..16].to_vec());
+        let num_gates = circuit.num_gates();
+        circuit.bytes_to_field_le(&digest_prefix)?;
+        assert!(circuit.num_gates() - num_gates <= 6);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong values
+        *circuit.witness_mut(bytes.bytes()[0].var()) += F::from(256u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(bytes.bytes()[0].var()) -= F::from(256u32);
+        *circuit.witness_mut(packed) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(packed) -= F::one();
+        let var = circuit.create_variable(F::from(256u32))?;
+        circuit.u8_from_variable(var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid inputs
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        assert!(circuit.bytes_to_field_le(&BytesVar::default()).is_err());
+        let long = circuit.create_bytes_variable(&vec![0; max_len + 1])?;
+        assert!(circuit.bytes_to_field_le(&long).is_err());
+        let var = circuit.create_variable(F::from(256u32))?;
+        assert!(circuit.field_to_bytes_le(var, 1).is_err());
+        assert!(circuit.field_to_bytes_le(var, 0).is_err());
+        assert!(circuit.field_to_bytes_le(circuit.num_vars(), 2).is_err());
+        Ok(())
+    }
+}
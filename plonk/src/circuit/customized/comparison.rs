@@ -0,0 +1,197 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Comparison gadgets over bounded integers, which output boolean variables.
+//!
+//! `a < b` holds iff `a - b + 2^bit_len`, which is in `(0, 2^{bit_len + 1})`
+//! for operands of `bit_len` bits, is smaller than `2^bit_len`. The gadgets
+//! split it into its most significant bit and a remainder of `bit_len` bits,
+//! which is range checked with the lookup range table if the circuit supports
+//! lookup and `bit_len` is a multiple of its range bit length, and with a bit
+//! decomposition otherwise.
+
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::{BigInteger, PrimeField};
+use ark_std::format;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Obtain a bool variable representing whether `a < b`, where `a` and `b`
+    /// are expected to be constrained to be in `[0, 2^bit_len)`.
+    /// Return error if the variables are invalid, or if `bit_len` is zero or
+    /// too large for the native field.
+    pub fn is_lt(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        bit_len: usize,
+    ) -> Result<BoolVar, PlonkError> {
+        self.compare_lt(a, b, bit_len)
+    }
+
+    /// Obtain a bool variable representing whether `a <= b`, where `a` and
+    /// `b` are expected to be constrained to be in `[0, 2^bit_len)`.
+    /// Return error if the variables are invalid, or if `bit_len` is zero or
+    /// too large for the native field.
+    pub fn is_leq(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        bit_len: usize,
+    ) -> Result<BoolVar, PlonkError> {
+        let b_lt_a = self.compare_lt(b, a, bit_len)?;
+        self.logic_neg(b_lt_a)
+    }
+
+    /// Obtain a bool variable representing whether `a < b` for signed
+    /// integers, where `a` and `b` are expected to be constrained to be in
+    /// `[-2^{bit_len - 1}, 2^{bit_len - 1})`, negative integers being
+    /// represented by their opposites in the field.
+    /// Return error if the variables are invalid, or if `bit_len` is zero or
+    /// too large for the native field.
+    pub fn is_lt_signed(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        bit_len: usize,
+    ) -> Result<BoolVar, PlonkError> {
+        // a - b is in (-2^bit_len, 2^bit_len) as for unsigned integers
+        self.compare_lt(a, b, bit_len)
+    }
+
+    /// Obtain a bool variable representing whether `a <= b` for signed
+    /// integers, where `a` and `b` are expected to be constrained to be in
+    /// `[-2^{bit_len - 1}, 2^{bit_len - 1})`, negative integers being
+    /// represented by their opposites in the field.
+    /// Return error if the variables are invalid, or if `bit_len` is zero or
+    /// too large for the native field.
+    pub fn is_leq_signed(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        bit_len: usize,
+    ) -> Result<BoolVar, PlonkError> {
+        let b_lt_a = self.compare_lt(b, a, bit_len)?;
+        self.logic_neg(b_lt_a)
+    }
+
+    // Obtain a bool variable representing whether a < b, provided that a - b
+    // is in (-2^bit_len, 2^bit_len).
+    fn compare_lt(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        bit_len: usize,
+    ) -> Result<BoolVar, PlonkError> {
+        self.check_var_bound(a)?;
+        self.check_var_bound(b)?;
+        if bit_len == 0 || bit_len + 1 >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "unsupported bit length {} of compared integers",
+                bit_len
+            ))
+            .into());
+        }
+        // a - b + 2^bit_len = 2^bit_len * msb + rem, with a < b iff msb = 0
+        let two_power = F::from(2u8).pow(&[bit_len as u64]);
+        let diff = self.witness(a)? - self.witness(b)? + two_power;
+        let msb = diff.into_repr().get_bit(bit_len);
+        let msb_var = self.create_bool_variable(msb)?;
+        let rem = if msb { diff - two_power } else { diff };
+        let rem_var = self.create_variable(rem)?;
+        self.range_gate(rem_var, bit_len)?;
+        let wires = [a, b, msb_var.into(), rem_var, self.zero()];
+        let coeffs = [F::one(), -F::one(), -two_power, -F::one()];
+        self.quad_poly_gate(
+            &wires,
+            &coeffs,
+            &[F::zero(), F::zero()],
+            F::zero(),
+            two_power,
+        )?;
+        self.logic_neg(msb_var)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::Rng, test_rng, vec, vec::Vec};
+
+    #[test]
+    fn test_comparison() -> Result<(), PlonkError> {
+        test_comparison_helper::<FqEd254>()?;
+        test_comparison_helper::<FqEd377>()?;
+        test_comparison_helper::<FqEd381>()?;
+        test_comparison_helper::<Fq377>()
+    }
+
+    fn signed<F: PrimeField>(x: i64) -> F {
+        if x < 0 {
+            -F::from(x.unsigned_abs())
+        } else {
+            F::from(x as u64)
+        }
+    }
+
+    fn test_comparison_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ]
+        .iter()
+        {
+            let mut circuit = circuit.clone();
+            let mut outputs: Vec<Variable> = vec![];
+            let mut cases: Vec<(u64, u64)> = (0..8)
+                .map(|_| (rng.gen_range(0..1 << 16), rng.gen_range(0..1 << 16)))
+                .collect();
+            cases.extend_from_slice(&[(0, 0), (0, 65535), (65535, 0), (7, 7), (7, 8)]);
+            for &(x, y) in cases.iter() {
+                let a = circuit.create_variable(F::from(x))?;
+                let b = circuit.create_variable(F::from(y))?;
+                let lt = circuit.is_lt(a, b, 16)?;
+                assert_eq!(circuit.witness(lt.into())?, F::from(x < y));
+                let leq = circuit.is_leq(a, b, 16)?;
+                assert_eq!(circuit.witness(leq.into())?, F::from(x <= y));
+                outputs.push(lt.into());
+                outputs.push(leq.into());
+            }
+            for &(x, y) in [(-5i64, 3), (3, -5), (-5, -5), (-32768, 32767), (-1, 0)].iter() {
+                let a = circuit.create_variable(signed::<F>(x))?;
+                let b = circuit.create_variable(signed::<F>(y))?;
+                let lt = circuit.is_lt_signed(a, b, 16)?;
+                assert_eq!(circuit.witness(lt.into())?, F::from(x < y));
+                let leq = circuit.is_leq_signed(a, b, 16)?;
+                assert_eq!(circuit.witness(leq.into())?, F::from(x <= y));
+                outputs.push(lt.into());
+                outputs.push(leq.into());
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // wrong outputs
+            for &output in outputs.iter() {
+                let value = circuit.witness(output)?;
+                *circuit.witness_mut(output) = F::one() - value;
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                *circuit.witness_mut(output) = value;
+            }
+
+            // invalid bit lengths
+            let a = circuit.create_variable(F::one())?;
+            assert!(circuit.is_lt(a, a, 0).is_err());
+            assert!(circuit.is_leq(a, a, F::size_in_bits()).is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,147 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Euclidean division of bounded integers.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::format;
+use num_bigint::BigUint;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Obtain the quotient `q` and the remainder `r` of the Euclidean division
+    /// of `a` by `b`, where `a` and `b` are expected to be constrained to be in
+    /// `[0, 2^bit_len)`. The gadget enforces `a = q * b + r` with `q` and `r`
+    /// in `[0, 2^bit_len)` and `r < b`, so that the circuit is not satisfied
+    /// if `b` is zero. The range checks use the lookup range table when
+    /// possible, as for `range_gate`.
+    /// Return error if the variables are invalid, or if `bit_len` is zero or
+    /// too large for the native field.
+    pub fn div_rem(
+        &mut self,
+        a: Variable,
+        b: Variable,
+        bit_len: usize,
+    ) -> Result<(Variable, Variable), PlonkError> {
+        self.check_var_bound(a)?;
+        self.check_var_bound(b)?;
+        // q * b + r must not wrap around the native modulus
+        if bit_len == 0 || 2 * bit_len + 1 >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "unsupported bit length {} of divided integers",
+                bit_len
+            ))
+            .into());
+        }
+        let a_int: BigUint = self.witness(a)?.into();
+        let b_int: BigUint = self.witness(b)?.into();
+        let (q_int, r_int) = if b_int == BigUint::from(0u8) {
+            // the circuit is not satisfiable
+            (BigUint::from(0u8), a_int)
+        } else {
+            (&a_int / &b_int, &a_int % &b_int)
+        };
+        let q = self.create_variable(F::from(q_int))?;
+        let r = self.create_variable(F::from(r_int))?;
+        self.range_gate(q, bit_len)?;
+        self.range_gate(r, bit_len)?;
+
+        // a = q * b + r
+        let wires = [q, b, r, self.zero(), a];
+        self.quad_poly_gate(
+            &wires,
+            &[F::zero(), F::zero(), F::one(), F::zero()],
+            &[F::one(), F::zero()],
+            F::one(),
+            F::zero(),
+        )?;
+        // r < b iff b - r - 1 is in [0, 2^bit_len)
+        let gap = self.gen_quad_poly(
+            &[b, r, self.zero(), self.zero()],
+            &[F::one(), -F::one(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            -F::one(),
+        )?;
+        self.range_gate(gap, bit_len)?;
+        Ok((q, r))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::Rng, test_rng, vec::Vec};
+
+    #[test]
+    fn test_div_rem() -> Result<(), PlonkError> {
+        test_div_rem_helper::<FqEd254>()?;
+        test_div_rem_helper::<FqEd377>()?;
+        test_div_rem_helper::<FqEd381>()?;
+        test_div_rem_helper::<Fq377>()
+    }
+
+    fn test_div_rem_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ]
+        .iter()
+        {
+            let mut circuit = circuit.clone();
+            let mut cases: Vec<(u64, u64)> = (0..8)
+                .map(|_| (rng.gen_range(0..1 << 32), rng.gen_range(1..1 << 16)))
+                .collect();
+            cases.extend_from_slice(&[
+                (0, 1),
+                (7, 7),
+                (6, 7),
+                (u32::MAX as u64, 1),
+                (1, u32::MAX as u64),
+            ]);
+            for &(x, y) in cases.iter() {
+                let a = circuit.create_variable(F::from(x))?;
+                let b = circuit.create_variable(F::from(y))?;
+                let (q, r) = circuit.div_rem(a, b, 32)?;
+                assert_eq!(circuit.witness(q)?, F::from(x / y));
+                assert_eq!(circuit.witness(r)?, F::from(x % y));
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a remainder which is not reduced
+            let mut bad_circuit = circuit.clone();
+            let a = bad_circuit.create_variable(F::from(10u8))?;
+            let b = bad_circuit.create_variable(F::from(3u8))?;
+            let (q, r) = bad_circuit.div_rem(a, b, 32)?;
+            *bad_circuit.witness_mut(q) -= F::one();
+            *bad_circuit.witness_mut(r) += F::from(3u8);
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+            // a wrong quotient
+            *bad_circuit.witness_mut(r) -= F::from(3u8);
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // division by zero
+            let mut bad_circuit = circuit.clone();
+            let a = bad_circuit.create_variable(F::from(10u8))?;
+            let zero = bad_circuit.zero();
+            bad_circuit.div_rem(a, zero, 32)?;
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // invalid bit lengths
+            let a = circuit.create_variable(F::one())?;
+            assert!(circuit.div_rem(a, a, 0).is_err());
+            assert!(circuit.div_rem(a, a, F::size_in_bits() / 2).is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,274 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! The Baby Jubjub curve of circomlib, and circuits for the verification of
+//! the EdDSA-Poseidon signatures of circomlib.
+//!
+//! Baby Jubjub is the twisted Edwards curve `168700 * x^2 + y^2 = 1 + 168696 *
+//! x^2 * y^2` over the scalar field of BN254. The curve of `ark-ed-on-bn254`
+//! is isomorphic to it but uses different coordinates, whereas keys and
+//! signatures produced by circomlib or circomlibjs are given in the
+//! coordinates of the parameters defined here.
+
+use super::PointVariable;
+use crate::{
+    circuit::{
+        customized::poseidon::{circomlib_poseidon_params, PoseidonGadget},
+        Circuit, PlonkCircuit, Variable,
+    },
+    errors::PlonkError,
+};
+use ark_bn254::Fr;
+use ark_ec::{
+    twisted_edwards_extended::GroupAffine, AffineCurve, ModelParameters, MontgomeryModelParameters,
+    TEModelParameters,
+};
+use ark_ed_on_bn254::Fr as BabyJubjubScalar;
+use ark_ff::{field_new, One, PrimeField};
+use ark_std::vec::Vec;
+use jf_utils::fr_to_fq;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+/// Parameters of the Baby Jubjub curve, with the coefficients and the base
+/// point of circomlib.
+pub struct BabyJubjubParameters;
+
+impl ModelParameters for BabyJubjubParameters {
+    type BaseField = Fr;
+    type ScalarField = BabyJubjubScalar;
+}
+
+impl TEModelParameters for BabyJubjubParameters {
+    /// COEFF_A = 168700
+    const COEFF_A: Fr = field_new!(Fr, "168700");
+
+    /// COEFF_D = 168696
+    const COEFF_D: Fr = field_new!(Fr, "168696");
+
+    /// COFACTOR = 8
+    const COFACTOR: &'static [u64] = &[8];
+
+    /// COFACTOR_INV = COFACTOR^{-1} mod r
+    const COFACTOR_INV: BabyJubjubScalar = field_new!(
+        BabyJubjubScalar,
+        "2394026564107420727433200628387514462817212225638746351800188703329891451411"
+    );
+
+    /// `Base8` of circomlib, which generates the subgroup of prime order
+    const AFFINE_GENERATOR_COEFFS: (Fr, Fr) = (GENERATOR_X, GENERATOR_Y);
+
+    type MontgomeryModelParameters = BabyJubjubParameters;
+}
+
+impl MontgomeryModelParameters for BabyJubjubParameters {
+    /// COEFF_A = 168698
+    const COEFF_A: Fr = field_new!(Fr, "168698");
+
+    /// COEFF_B = 1
+    const COEFF_B: Fr = field_new!(Fr, "1");
+
+    type TEModelParameters = BabyJubjubParameters;
+}
+
+/// GENERATOR_X =
+/// 5299619240641551281634865583518297030282874472190772894086521144482721001553
+const GENERATOR_X: Fr = field_new!(
+    Fr,
+    "5299619240641551281634865583518297030282874472190772894086521144482721001553"
+);
+
+/// GENERATOR_Y =
+/// 16950150798460657717958625567821834550301663161624707787222815936182638968203
+const GENERATOR_Y: Fr = field_new!(
+    Fr,
+    "16950150798460657717958625567821834550301663161624707787222815936182638968203"
+);
+
+impl PlonkCircuit<Fr> {
+    /// Verify an EdDSA-Poseidon signature `(sig_r8, sig_s)` of `msg` under the
+    /// public key `pk`, as the `EdDSAPoseidonVerifier` template of circomlib:
+    /// the circuit is satisfied iff `sig_s * Base8 = sig_r8 + (8 * h) * pk`,
+    /// where `h = Poseidon(sig_r8.x, sig_r8.y, pk.x, pk.y, msg)`. In addition,
+    /// the points are constrained to be on the curve, `pk` not to be of
+    /// small order, and the bits of `h` to be its canonical representation.
+    /// * `returns` - error if `sig_s` is not smaller than the order of
+    ///   `Base8`, or if the input variables are invalid
+    pub fn eddsa_poseidon_verify(
+        &mut self,
+        pk: &PointVariable,
+        msg: Variable,
+        sig_r8: &PointVariable,
+        sig_s: Variable,
+    ) -> Result<(), PlonkError> {
+        self.on_curve_gate::<BabyJubjubParameters>(pk)?;
+        self.on_curve_gate::<BabyJubjubParameters>(sig_r8)?;
+
+        // sig_s and r - 1 - sig_s both fit in the bit length of r
+        let num_bits = BabyJubjubScalar::size_in_bits();
+        let order_minus_one = fr_to_fq::<Fr, BabyJubjubParameters>(&-BabyJubjubScalar::one());
+        self.unpack(sig_s, num_bits)?;
+        let neg_s = self.mul_constant(sig_s, &-Fr::one())?;
+        let diff = self.add_constant(neg_s, &order_minus_one)?;
+        self.unpack(diff, num_bits)?;
+
+        // 8 * pk is not the neutral point
+        let mut pk8 = *pk;
+        for _ in 0..3 {
+            pk8 = self.ecc_add::<BabyJubjubParameters>(&pk8, &pk8)?;
+        }
+        self.non_zero_gate(pk8.get_x())?;
+
+        let params = circomlib_poseidon_params(5)?;
+        let h = self.poseidon_hash(
+            &params,
+            &[sig_r8.get_x(), sig_r8.get_y(), pk.get_x(), pk.get_y(), msg],
+        )?;
+        // the bits of h + p would be accepted by the decomposition as well
+        let h_bits = self.unpack_bool(h, Fr::size_in_bits())?;
+        self.enforce_canonical_bits(&h_bits)?;
+        let h_bits_le: Vec<Variable> = h_bits.into_iter().map(Variable::from).collect();
+        let h_pk8 =
+            self.variable_base_binary_scalar_mul::<BabyJubjubParameters>(&h_bits_le, &pk8)?;
+        let right = self.ecc_add::<BabyJubjubParameters>(sig_r8, &h_pk8)?;
+        let base = GroupAffine::<BabyJubjubParameters>::prime_subgroup_generator();
+        let left = self.fixed_base_scalar_mul(sig_s, &base)?;
+        self.point_equal_gate(&left, &right)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::ecc::Point;
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::{BigInteger, FpParameters, Zero};
+    use ark_std::{str::FromStr, test_rng, UniformRand};
+    use num_bigint::BigUint;
+
+    type BabyJubjubAffine = GroupAffine<BabyJubjubParameters>;
+
+    // signature of the message 1234 in the format of circomlib
+    const PK_X: &str =
+        "18670696440720339346060032516283347156903487621291502142059847955865755451150";
+    const PK_Y: &str =
+        "17894656094664129467982076987717502880425208048825187894684562697640154888284";
+    const R8_X: &str =
+        "10302347487355200246939141515748439289819646335107021447845739430927251479748";
+    const R8_Y: &str =
+        "20347549733836686080178138377552386523075311825920810863874878795519844962395";
+    const S: &str = "1890982528289986883987510676366362122625018979277084172546158686259060918129";
+    const MSG: u64 = 1234;
+
+    fn sign(
+        sk: BabyJubjubScalar,
+        msg: Fr,
+    ) -> Result<(BabyJubjubAffine, BabyJubjubAffine, BabyJubjubScalar), PlonkError> {
+        let rng = &mut test_rng();
+        let base = BabyJubjubAffine::prime_subgroup_generator();
+        let pk = base.mul(sk).into_affine();
+        let r = BabyJubjubScalar::rand(rng);
+        let r8 = base.mul(r).into_affine();
+        let params = circomlib_poseidon_params(5)?;
+        let h = params.eval(&[Fr::zero(), r8.x, r8.y, pk.x, pk.y, msg])?[0];
+        let h = BabyJubjubScalar::from_le_bytes_mod_order(&h.into_repr().to_bytes_le());
+        let s = r + h * BabyJubjubScalar::from(8u64) * sk;
+        Ok((pk, r8, s))
+    }
+
+    fn build_eddsa_circuit(
+        pk: Point<Fr>,
+        msg: Fr,
+        r8: Point<Fr>,
+        s: Fr,
+    ) -> Result<(PlonkCircuit<Fr>, Variable), PlonkError> {
+        let mut circuit = PlonkCircuit::<Fr>::new_turbo_plonk();
+        let pk_var = circuit.create_point_variable(pk)?;
+        let msg_var = circuit.create_variable(msg)?;
+        let r8_var = circuit.create_point_variable(r8)?;
+        let s_var = circuit.create_variable(s)?;
+        circuit.eddsa_poseidon_verify(&pk_var, msg_var, &r8_var, s_var)?;
+        Ok((circuit, msg_var))
+    }
+
+    #[test]
+    fn test_baby_jubjub_parameters() {
+        let base = BabyJubjubAffine::prime_subgroup_generator();
+        assert!(base.is_on_curve());
+        assert!(base.is_in_correct_subgroup_assuming_on_curve());
+        assert!(!base.is_zero());
+        assert_eq!(
+            BabyJubjubParameters::COFACTOR_INV * BabyJubjubScalar::from(8u64),
+            BabyJubjubScalar::one()
+        );
+    }
+
+    #[test]
+    fn test_eddsa_poseidon_verify() -> Result<(), PlonkError> {
+        let parse = |s: &str| Fr::from_str(s).unwrap();
+        let pk = Point::from(BabyJubjubAffine::new(parse(PK_X), parse(PK_Y)));
+        let r8 = Point::from(BabyJubjubAffine::new(parse(R8_X), parse(R8_Y)));
+        let s = parse(S);
+        let msg = Fr::from(MSG);
+
+        let (mut circuit, msg_var) = build_eddsa_circuit(pk, msg, r8, s)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        *circuit.witness_mut(msg_var) = Fr::from(MSG + 1);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong signature
+        let (circuit, _) = build_eddsa_circuit(pk, msg, r8, s + Fr::one())?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let (circuit, _) = build_eddsa_circuit(r8, msg, pk, s)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // random signature
+        let rng = &mut test_rng();
+        let msg = Fr::rand(rng);
+        let (pk, r8, s_scalar) = sign(BabyJubjubScalar::rand(rng), msg)?;
+        let s = fr_to_fq::<Fr, BabyJubjubParameters>(&s_scalar);
+        let (circuit, _) = build_eddsa_circuit(Point::from(pk), msg, Point::from(r8), s)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // the neutral point as public key, for which the signature equation
+        // holds with r8 = s * Base8
+        let neutral = Point::from(BabyJubjubAffine::zero());
+        let s_base = BabyJubjubAffine::prime_subgroup_generator()
+            .mul(s_scalar)
+            .into_affine();
+        let (circuit, _) = build_eddsa_circuit(neutral, msg, Point::from(s_base), s)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // the bits of h + p, which are packed to h as well, are rejected; h is
+        // the hash of the signature of a message for which h + p fits in the
+        // bits of h
+        let params = circomlib_poseidon_params(5)?;
+        let modulus: BigUint = <Fr as PrimeField>::Params::MODULUS.into();
+        let (h, h_plus_p) = (0u64..)
+            .map(|i| {
+                let input = [Fr::zero(), r8.x, r8.y, pk.x, pk.y, Fr::from(i)];
+                let h = params.eval(&input).unwrap()[0];
+                (h, BigUint::from(h) + &modulus)
+            })
+            .find(|(_, h_plus_p)| h_plus_p.bits() <= Fr::size_in_bits() as u64)
+            .unwrap();
+        let mut circuit = PlonkCircuit::<Fr>::new_turbo_plonk();
+        let h_var = circuit.create_variable(h)?;
+        let digits = h_plus_p.to_radix_le(2);
+        let bits = (0..Fr::size_in_bits())
+            .map(|i| circuit.create_bool_variable(digits.get(i) == Some(&1)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let packed = circuit.pack_bits(&bits)?;
+        circuit.equal_gate(packed, h_var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        circuit.enforce_canonical_bits(&bits)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // s is not reduced
+        let order = fr_to_fq::<Fr, BabyJubjubParameters>(&-BabyJubjubScalar::one()) + Fr::one();
+        assert!(build_eddsa_circuit(Point::from(pk), msg, Point::from(r8), s + order).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,233 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuits for the verification of aggregated BLS signatures with a deferred
+//! pairing check.
+//!
+//! Public keys are in G1 and signatures in G2 of a pairing-friendly curve
+//! whose G1 base field is the native field of the circuit, e.g. BLS12-377
+//! inside a circuit over the scalar field of BW6-761, so that public keys are
+//! handled in their twisted Edwards form with the native ECC gates. The
+//! circuit aggregates the public keys of the signers and exposes the
+//! aggregated key `apk` as a public input in short Weierstrass form. The
+//! pairing check `e(apk, H(m)) = e(g1, sig)` is left to the verifier of the
+//! proof, c.f. `bls_deferred_pairing_check`, in the same way as the final
+//! pairing of the Plonk verifier circuit is deferred.
+
+use super::{PointVariable, SWPointVariable, SWToTEConParam};
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ec::{AffineCurve, PairingEngine, TEModelParameters as Parameters};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+
+/// Circuit gadget for the aggregation of BLS public keys.
+pub trait BlsAggregationGadget<F, P>
+where
+    F: PrimeField + SWToTEConParam,
+    P: Parameters<BaseField = F> + Clone,
+{
+    /// Aggregate the public keys of the signers of a message, and expose the
+    /// aggregated key as public inputs in short Weierstrass form: its x and y
+    /// coordinates and its infinity flag, in this order.
+    ///
+    /// The public keys are constrained to be on the curve. The caller
+    /// guarantees that they are in the prime order subgroup, e.g. as they are
+    /// registered with a proof of possession, or with
+    /// `enforce_in_prime_subgroup` for untrusted keys. The caller also binds
+    /// them, e.g. as public inputs, as the aggregated key is otherwise chosen
+    /// by the prover.
+    /// * `pks` - the public keys, in twisted Edwards form
+    /// * `signer_bits` - boolean variables indicating which keys signed
+    /// * `returns` - the aggregated public key, in short Weierstrass form, and
+    ///   the number of signers, e.g. for a threshold check
+    fn aggregate_bls_public_keys(
+        &mut self,
+        pks: &[PointVariable],
+        signer_bits: &[BoolVar],
+    ) -> Result<(SWPointVariable, Variable), PlonkError>;
+}
+
+impl<F, P> BlsAggregationGadget<F, P> for PlonkCircuit<F>
+where
+    F: PrimeField + SWToTEConParam,
+    P: Parameters<BaseField = F> + Clone,
+{
+    fn aggregate_bls_public_keys(
+        &mut self,
+        pks: &[PointVariable],
+        signer_bits: &[BoolVar],
+    ) -> Result<(SWPointVariable, Variable), PlonkError> {
+        if pks.len() != signer_bits.len() {
+            return Err(ParameterError(format!(
+                "the number of public keys {} is different from the number of signer bits {}",
+                pks.len(),
+                signer_bits.len()
+            ))
+            .into());
+        }
+        let neutral = self.neutral_point_variable();
+        let mut apk = neutral;
+        for (pk, &bit) in pks.iter().zip(signer_bits.iter()) {
+            self.enforce_on_curve::<P>(pk)?;
+            let point = self.binary_point_vars_select(bit, &neutral, pk)?;
+            apk = self.ecc_add::<P>(&apk, &point)?;
+        }
+        let sw_apk = self.te_to_sw_point(&apk)?;
+        self.set_variable_public(sw_apk.get_x())?;
+        self.set_variable_public(sw_apk.get_y())?;
+        self.set_variable_public(sw_apk.get_infinity().into())?;
+
+        let signer_bits: Vec<Variable> = signer_bits.iter().map(|&bit| bit.into()).collect();
+        let num_signers = self.sum(&signer_bits)?;
+        Ok((sw_apk, num_signers))
+    }
+}
+
+/// The deferred pairing check of an aggregated BLS signature:
+/// `e(apk, msg_point) = e(g1, sig)`, where `apk` is the aggregated public key
+/// exposed by the circuit as a public input, `msg_point` is the hash of the
+/// message to G2, and `sig` is the aggregated signature.
+pub fn bls_deferred_pairing_check<E: PairingEngine>(
+    apk: &E::G1Affine,
+    msg_point: &E::G2Affine,
+    sig: &E::G2Affine,
+) -> bool {
+    E::pairing(*apk, *msg_point) == E::pairing(E::G1Affine::prime_subgroup_generator(), *sig)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::ecc::Point;
+    use ark_bls12_377::{
+        g1::Parameters as Param377, Bls12_377, Fq as Fq377, Fr, G1Affine, G1Projective, G2Affine,
+        G2Projective,
+    };
+    use ark_ec::ProjectiveCurve;
+    use ark_ff::One;
+    use ark_std::{test_rng, vec::Vec, UniformRand, Zero};
+
+    // Build the aggregation circuit of the public keys `pks`, which are public
+    // inputs, and return it with the aggregated key of its public inputs.
+    fn build_aggregation_circuit(
+        pks: &[G1Affine],
+        signers: &[bool],
+    ) -> Result<(PlonkCircuit<Fq377>, G1Affine), PlonkError> {
+        let mut circuit = PlonkCircuit::<Fq377>::new_turbo_plonk();
+        let pk_vars = pks
+            .iter()
+            .map(|pk| circuit.create_public_point_variable(Point::from(pk)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let bit_vars = signers
+            .iter()
+            .map(|&b| circuit.create_bool_variable(b))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let (_, num_signers_var) = BlsAggregationGadget::<_, Param377>::aggregate_bls_public_keys(
+            &mut circuit,
+            &pk_vars,
+            &bit_vars,
+        )?;
+        let num_signers = signers.iter().filter(|&&b| b).count() as u32;
+        assert_eq!(circuit.witness(num_signers_var)?, Fq377::from(num_signers));
+
+        let pub_input = circuit.public_input()?;
+        assert_eq!(pub_input.len(), 2 * pks.len() + 3);
+        let apk = &pub_input[2 * pks.len()..];
+        let apk = G1Affine::new(apk[0], apk[1], apk[2] == Fq377::one());
+        Ok((circuit, apk))
+    }
+
+    #[test]
+    fn test_bls_aggregation() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let num_keys = 5;
+        let sks: Vec<Fr> = (0..num_keys).map(|_| Fr::rand(rng)).collect();
+        let pks: Vec<G1Affine> = sks
+            .iter()
+            .map(|sk| G1Affine::prime_subgroup_generator().mul(*sk).into_affine())
+            .collect();
+        let msg_point = G2Affine::prime_subgroup_generator()
+            .mul(Fr::rand(rng))
+            .into_affine();
+        let signers = [true, false, true, true, false];
+
+        let mut expected_apk = G1Projective::zero();
+        let mut sig = G2Projective::zero();
+        for ((sk, pk), &signed) in sks.iter().zip(pks.iter()).zip(signers.iter()) {
+            if signed {
+                expected_apk += pk.into_projective();
+                sig += msg_point.mul(*sk);
+            }
+        }
+        let expected_apk = expected_apk.into_affine();
+        let sig = sig.into_affine();
+
+        // the aggregated key of the circuit passes the deferred pairing check
+        let (circuit, apk) = build_aggregation_circuit(&pks, &signers)?;
+        assert_eq!(apk, expected_apk);
+        let pub_input = circuit.public_input()?;
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_ok());
+        assert!(bls_deferred_pairing_check::<Bls12_377>(
+            &apk, &msg_point, &sig
+        ));
+
+        // no signers
+        let (circuit, apk) = build_aggregation_circuit(&pks, &[false; 5])?;
+        assert!(apk.is_zero());
+        assert!(circuit
+            .check_circuit_satisfiability(&circuit.public_input()?)
+            .is_ok());
+
+        // a wrong signer set: the second key did not sign
+        let wrong_signers = [true, true, true, true, false];
+        let (wrong_circuit, wrong_apk) = build_aggregation_circuit(&pks, &wrong_signers)?;
+        assert!(wrong_circuit
+            .check_circuit_satisfiability(&wrong_circuit.public_input()?)
+            .is_ok());
+        assert!(!bls_deferred_pairing_check::<Bls12_377>(
+            &wrong_apk, &msg_point, &sig
+        ));
+        // nor does it prove the aggregated key of the actual signers
+        assert!(wrong_circuit
+            .check_circuit_satisfiability(&pub_input)
+            .is_err());
+
+        // a public key off the curve
+        let mut circuit = PlonkCircuit::<Fq377>::new_turbo_plonk();
+        let mut pk_points: Vec<Point<Fq377>> = pks.iter().map(Point::from).collect();
+        pk_points[0] = Point(Fq377::one(), Fq377::one());
+        let pk_vars = pk_points
+            .iter()
+            .map(|&pk| circuit.create_public_point_variable(pk))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let bit_vars = signers
+            .iter()
+            .map(|&b| circuit.create_bool_variable(b))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        BlsAggregationGadget::<_, Param377>::aggregate_bls_public_keys(
+            &mut circuit,
+            &pk_vars,
+            &bit_vars,
+        )?;
+        assert!(circuit
+            .check_circuit_satisfiability(&circuit.public_input()?)
+            .is_err());
+
+        // mismatched lengths
+        assert!(
+            BlsAggregationGadget::<_, Param377>::aggregate_bls_public_keys(
+                &mut circuit,
+                &pk_vars,
+                &bit_vars[1..],
+            )
+            .is_err()
+        );
+        Ok(())
+    }
+}
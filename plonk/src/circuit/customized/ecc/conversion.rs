@@ -8,16 +8,20 @@
 //! - short Weierstrass form
 //! - twisted Edwards form
 //!
-//! Note that the APIs below create no circuits.
-//! An entity should either know both the SW and TE form of a
-//! point; or know none of the two. There is no need to generate
-//! a circuit for arguing secret knowledge of one form while
-//! the other form is public. In practice a prover will convert all of the
-//! points to the TE form and work on the TE form inside the circuits.
-
-use super::Point;
+//! The native conversions create no circuits: in practice a prover converts
+//! all of the points to the TE form and works on the TE form inside the
+//! circuits. When a point is only known in one form by the verifier, e.g. a
+//! commitment in SW form, the gadgets `sw_to_te_point` and `te_to_sw_point`
+//! convert it inside the circuit instead.
+
+use super::{Point, PointVariable};
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
 use ark_ec::{short_weierstrass_jacobian::GroupAffine as SWAffine, SWModelParameters as SWParam};
-use ark_ff::{BigInteger256, BigInteger384, BigInteger768, PrimeField};
+use ark_ff::{batch_inversion, BigInteger256, BigInteger384, BigInteger768, PrimeField};
+use ark_std::{vec, vec::Vec};
 
 impl<F, P> From<&SWAffine<P>> for Point<F>
 where
@@ -57,6 +61,248 @@ where
     }
 }
 
+impl<F> Point<F>
+where
+    F: PrimeField + SWToTEConParam,
+{
+    /// Convert a slice of short Weierstrass points into twisted Edwards form.
+    ///
+    /// The output is identical to converting each point with `From`, but the
+    /// two field inversions required per point are shared across the whole
+    /// slice with Montgomery's batch inversion trick, i.e., a single field
+    /// inversion is performed for the entire slice.
+    pub fn batch_from_sw<P>(points: &[SWAffine<P>]) -> Vec<Self>
+    where
+        P: SWParam<BaseField = F> + Clone,
+    {
+        // safe unwrap
+        let s = F::from_repr(F::S).unwrap();
+        let neg_alpha = F::from_repr(F::NEG_ALPHA).unwrap();
+        let beta = F::from_repr(F::BETA).unwrap();
+
+        // Montgomery form (mx, my) of each point, c.f. the `From` implementation
+        let montgomery: Vec<(F, F)> = points
+            .iter()
+            .map(|p| (s * (p.x + neg_alpha), s * p.y))
+            .collect();
+
+        // denominators my and mx + 1 of each point; the ones of infinity points
+        // are set to 1 and discarded afterwards
+        let mut denominators = vec![F::one(); 2 * points.len()];
+        for (i, (p, (mx, my))) in points.iter().zip(montgomery.iter()).enumerate() {
+            if !p.infinity {
+                denominators[2 * i] = *my;
+                denominators[2 * i + 1] = *mx + F::one();
+            }
+        }
+        batch_inversion(&mut denominators);
+
+        points
+            .iter()
+            .zip(montgomery.iter())
+            .zip(denominators.chunks(2))
+            .map(|((p, (mx, _)), inv)| {
+                if p.infinity {
+                    Self(F::zero(), F::one())
+                } else {
+                    // ex = beta * mx / my
+                    // ey = (mx - 1) / (mx + 1)
+                    Self(beta * mx * inv[0], (*mx - F::one()) * inv[1])
+                }
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// Represent variable of a short Weierstrass point `(x, y)`, together with a
+/// boolean variable indicating the point at infinity, whose coordinates are
+/// `(0, 0)`.
+pub struct SWPointVariable(pub(crate) Variable, pub(crate) Variable, pub(crate) BoolVar);
+
+impl SWPointVariable {
+    /// Get the variable representing the x coordinate of the point.
+    pub fn get_x(&self) -> Variable {
+        self.0
+    }
+
+    /// Get the variable representing the y coordinate of the point.
+    pub fn get_y(&self) -> Variable {
+        self.1
+    }
+
+    /// Get the boolean variable indicating the point at infinity.
+    pub fn get_infinity(&self) -> BoolVar {
+        self.2
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField + SWToTEConParam,
+{
+    /// Add a new short Weierstrass point (as witness) to the circuit.
+    pub fn create_sw_point_variable<P>(
+        &mut self,
+        point: &SWAffine<P>,
+    ) -> Result<SWPointVariable, PlonkError>
+    where
+        P: SWParam<BaseField = F> + Clone,
+    {
+        let (x, y) = if point.infinity {
+            (F::zero(), F::zero())
+        } else {
+            (point.x, point.y)
+        };
+        let x_var = self.create_variable(x)?;
+        let y_var = self.create_variable(y)?;
+        let infinity_var = self.create_bool_variable(point.infinity)?;
+        Ok(SWPointVariable(x_var, y_var, infinity_var))
+    }
+
+    /// Return the witness short Weierstrass point for the circuit.
+    pub fn sw_point_witness<P>(
+        &self,
+        point_var: &SWPointVariable,
+    ) -> Result<SWAffine<P>, PlonkError>
+    where
+        P: SWParam<BaseField = F> + Clone,
+    {
+        let x = self.witness(point_var.0)?;
+        let y = self.witness(point_var.1)?;
+        let infinity = self.witness(point_var.2.into())? == F::one();
+        Ok(SWAffine::new(x, y, infinity))
+    }
+
+    /// Obtain the variable of the twisted Edwards form of a short Weierstrass
+    /// point, which is expected to be on the curve.
+    /// The circuit is not satisfied for points of order 2, which have no
+    /// twisted Edwards form.
+    /// Return error if the variables are invalid.
+    pub fn sw_to_te_point(
+        &mut self,
+        point_var: &SWPointVariable,
+    ) -> Result<PointVariable, PlonkError> {
+        self.check_sw_point_var_bound(point_var)?;
+        let x = self.witness(point_var.0)?;
+        let y = self.witness(point_var.1)?;
+        let te_point = if self.witness(point_var.2.into())? == F::one() {
+            Point(F::zero(), F::one())
+        } else {
+            // c.f. the `From` implementation
+            let s = F::from_repr(F::S).unwrap();
+            let neg_alpha = F::from_repr(F::NEG_ALPHA).unwrap();
+            let beta = F::from_repr(F::BETA).unwrap();
+            let montgomery_x = s * (x + neg_alpha);
+            let montgomery_y = s * y;
+            Point(
+                beta * montgomery_x * montgomery_y.inverse().unwrap_or_else(F::zero),
+                (montgomery_x - F::one())
+                    * (montgomery_x + F::one()).inverse().unwrap_or_else(F::zero),
+            )
+        };
+        let te_point_var = self.create_point_variable(te_point)?;
+        self.sw_to_te_gate(point_var, &te_point_var)?;
+        Ok(te_point_var)
+    }
+
+    /// Obtain the variable of the short Weierstrass form of a twisted Edwards
+    /// point, which is expected to be on the curve.
+    /// The circuit is not satisfied for points of order 2, which have no
+    /// short Weierstrass form with the conversion.
+    /// Return error if the variables are invalid.
+    pub fn te_to_sw_point(
+        &mut self,
+        point_var: &PointVariable,
+    ) -> Result<SWPointVariable, PlonkError> {
+        self.check_point_var_bound(point_var)?;
+        let Point(ex, ey) = self.point_witness(point_var)?;
+        let (x, y, infinity) = if ex.is_zero() && ey == F::one() {
+            (F::zero(), F::zero(), true)
+        } else {
+            // mx = (1 + ey) / (1 - ey), my = beta * mx / ex
+            // px = mx / s - alpha, py = my / s
+            let s_inv = F::from_repr(F::S)
+                .unwrap()
+                .inverse()
+                .unwrap_or_else(F::zero);
+            let neg_alpha = F::from_repr(F::NEG_ALPHA).unwrap();
+            let beta = F::from_repr(F::BETA).unwrap();
+            let montgomery_x = (F::one() + ey) * (F::one() - ey).inverse().unwrap_or_else(F::zero);
+            let montgomery_y = beta * montgomery_x * ex.inverse().unwrap_or_else(F::zero);
+            (
+                montgomery_x * s_inv - neg_alpha,
+                montgomery_y * s_inv,
+                false,
+            )
+        };
+        let sw_point_var = SWPointVariable(
+            self.create_variable(x)?,
+            self.create_variable(y)?,
+            self.create_bool_variable(infinity)?,
+        );
+        self.sw_to_te_gate(&sw_point_var, point_var)?;
+        Ok(sw_point_var)
+    }
+
+    // Constrain the twisted Edwards point `te_point_var` to be the conversion
+    // of the short Weierstrass point `sw_point_var`.
+    fn sw_to_te_gate(
+        &mut self,
+        sw_point_var: &SWPointVariable,
+        te_point_var: &PointVariable,
+    ) -> Result<(), PlonkError> {
+        let s = F::from_repr(F::S).unwrap();
+        let neg_alpha = F::from_repr(F::NEG_ALPHA).unwrap();
+        let beta = F::from_repr(F::BETA).unwrap();
+        let SWPointVariable(px, py, infinity) = *sw_point_var;
+        let infinity: Variable = infinity.into();
+        let PointVariable(ex, ey) = *te_point_var;
+        let zero = self.zero();
+
+        // the point at infinity is (0, 0) and is mapped to (0, 1)
+        self.mul_gate(infinity, px, zero)?;
+        self.mul_gate(infinity, py, zero)?;
+        self.mul_gate(infinity, ex, zero)?;
+        // my = s * py is non-zero unless at infinity
+        let py_or_infinity = self.add(py, infinity)?;
+        self.non_zero_gate(py_or_infinity)?;
+
+        // ex * my = beta * mx unless at infinity, i.e. as infinity * px = 0
+        // s * ex * py - beta * s * px + beta * s * alpha' * infinity
+        //      = beta * s * alpha'
+        // where alpha' = -alpha
+        let beta_s = beta * s;
+        self.quad_poly_gate(
+            &[ex, py, px, infinity, zero],
+            &[F::zero(), F::zero(), -beta_s, beta_s * neg_alpha],
+            &[s, F::zero()],
+            F::zero(),
+            -beta_s * neg_alpha,
+        )?;
+        // ey * (mx + 1) = mx - 1 unless at infinity, and ey = 1 otherwise, i.e.
+        // (1 - infinity) * (ey * (mx + 1) - mx + 1) + infinity * (ey - 1) = 0
+        // which simplifies with infinity * px = 0 and mx = s * (px + alpha') to
+        // s * ey * px + (s * alpha' + 1) * ey - s * px
+        //      + (s * alpha' - 2) * infinity - s * alpha' * infinity * ey
+        //      + 1 - s * alpha' = 0
+        let s_alpha = s * neg_alpha;
+        self.quad_poly_gate(
+            &[ey, px, infinity, ey, zero],
+            &[s_alpha + F::one(), -s, s_alpha - F::from(2u8), F::zero()],
+            &[s, -s_alpha],
+            F::zero(),
+            F::one() - s_alpha,
+        )
+    }
+
+    fn check_sw_point_var_bound(&self, point_var: &SWPointVariable) -> Result<(), PlonkError> {
+        self.check_var_bound(point_var.0)?;
+        self.check_var_bound(point_var.1)?;
+        self.check_var_bound(point_var.2.into())
+    }
+}
+
 /// This trait holds constants that are used for curve conversion from
 /// short Weierstrass form to twisted Edwards form.
 pub trait SWToTEConParam: PrimeField {
@@ -176,4 +422,73 @@ mod test {
         let p: Point<Fq377> = g1.into();
         assert!(is_on_bls12_377_ed_curve(&p));
     }
+
+    #[test]
+    fn test_sw_to_te_batch_conversion() {
+        let mut rng = test_rng();
+
+        // 42 sigma + 33 selector commitments, with an infinity point in between
+        let mut points: Vec<G1Affine> = (0..75)
+            .map(|_| G1Projective::rand(&mut rng).into_affine())
+            .collect();
+        points[17] = G1Affine::zero();
+        points.push(G1Affine::prime_subgroup_generator());
+
+        // the per-element conversion takes two inversions per point, i.e., 150
+        // inversions for the 75 random points, while the batch conversion
+        // takes a single one
+        let expected: Vec<Point<Fq377>> = points.iter().map(|p| p.into()).collect();
+        let batch = Point::<Fq377>::batch_from_sw(&points);
+        assert_eq!(batch, expected);
+        assert_eq!(batch[17], Point(Fq377::zero(), Fq377::one()));
+        assert!(batch.iter().all(is_on_bls12_377_ed_curve));
+
+        // empty input
+        let empty: Vec<G1Affine> = Vec::new();
+        assert!(Point::<Fq377>::batch_from_sw(&empty).is_empty());
+    }
+
+    #[test]
+    fn test_sw_te_conversion_gadgets() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let mut points: Vec<G1Affine> = (0..4)
+            .map(|_| G1Projective::rand(&mut rng).into_affine())
+            .collect();
+        points.push(G1Affine::prime_subgroup_generator());
+        points.push(G1Affine::zero());
+
+        let mut circuit = PlonkCircuit::<Fq377>::new_turbo_plonk();
+        for point in points.iter() {
+            let sw_point_var = circuit.create_sw_point_variable(point)?;
+            assert_eq!(circuit.sw_point_witness(&sw_point_var)?, *point);
+            let te_point_var = circuit.sw_to_te_point(&sw_point_var)?;
+            let te_point = Point::from(point);
+            assert_eq!(circuit.point_witness(&te_point_var)?, te_point);
+
+            let te_point_var = circuit.create_point_variable(te_point)?;
+            let sw_point_var = circuit.te_to_sw_point(&te_point_var)?;
+            assert_eq!(circuit.sw_point_witness(&sw_point_var)?, *point);
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong conversions
+        for point in points.iter() {
+            let mut bad_circuit = PlonkCircuit::<Fq377>::new_turbo_plonk();
+            let sw_point_var = bad_circuit.create_sw_point_variable(point)?;
+            let te_point_var = bad_circuit.sw_to_te_point(&sw_point_var)?;
+            *bad_circuit.witness_mut(te_point_var.0) += Fq377::one();
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+            *bad_circuit.witness_mut(te_point_var.0) -= Fq377::one();
+            *bad_circuit.witness_mut(te_point_var.1) += Fq377::one();
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+            let mut bad_circuit = PlonkCircuit::<Fq377>::new_turbo_plonk();
+            let te_point_var = bad_circuit.create_point_variable(Point::from(point))?;
+            let sw_point_var = bad_circuit.te_to_sw_point(&te_point_var)?;
+            *bad_circuit.witness_mut(sw_point_var.2.into()) =
+                Fq377::one() - bad_circuit.witness(sw_point_var.2.into())?;
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        Ok(())
+    }
 }
@@ -0,0 +1,186 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Decompression of twisted Edwards points, e.g. for circuits consuming
+//! compressed public keys and commitments.
+//!
+//! A point is compressed as in arkworks, into its x coordinate and a sign bit
+//! which is set iff `y > -y`, i.e. iff `y > (p - 1) / 2` as an integer. The
+//! curve equation determines `y` up to its sign given `x`, and the sign bit
+//! is enforced by a canonical comparison of `y` or `-y` with `(p - 1) / 2`.
+
+use super::{Point, PointVariable};
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use ark_ec::TEModelParameters as Parameters;
+use ark_ff::{FpParameters, PrimeField, SquareRootField};
+use num_bigint::BigUint;
+
+impl<F: PrimeField + SquareRootField> PlonkCircuit<F> {
+    /// Obtain the variable of the point of the curve whose x coordinate is
+    /// `x` and whose y coordinate is the greater one of `y` and `-y` iff the
+    /// boolean `sign` is true. The circuit is not satisfied if there is no
+    /// such point.
+    /// Return error if the variables are invalid.
+    pub fn decompress_point<P: Parameters<BaseField = F> + Clone>(
+        &mut self,
+        x: Variable,
+        sign: BoolVar,
+    ) -> Result<PointVariable, PlonkError> {
+        let sign: Variable = sign.into();
+        self.check_var_bound(x)?;
+        self.check_var_bound(sign)?;
+
+        // y^2 = (1 - a * x^2) / (1 - d * x^2)
+        let x_val = self.witness(x)?;
+        let x2 = x_val.square();
+        let y_val = ((F::one() - P::mul_by_a(&x2))
+            * (F::one() - P::COEFF_D * x2)
+                .inverse()
+                .unwrap_or_else(F::zero))
+        .sqrt()
+        .unwrap_or_else(F::zero);
+        let y_val = if Point(x_val, y_val).compress().1 == (self.witness(sign)? == F::one()) {
+            y_val
+        } else {
+            -y_val
+        };
+        let point_var = PointVariable(x, self.create_variable(y_val)?);
+        self.on_curve_gate::<P>(&point_var)?;
+
+        // the absolute value y - 2 * sign * y is at most (p - 1) / 2, and is
+        // non-zero if sign is set, as -0 is not greater than 0
+        let zero = self.zero();
+        let abs_y = self.gen_quad_poly(
+            &[point_var.1, sign, zero, zero],
+            &[F::one(), F::zero(), F::zero(), F::zero()],
+            &[-F::from(2u8), F::zero()],
+            F::zero(),
+        )?;
+        self.leq_half_modulus_gate(abs_y)?;
+        let non_zero = self.gen_quad_poly(
+            &[sign, abs_y, zero, zero],
+            &[-F::one(), F::zero(), F::zero(), F::zero()],
+            &[F::one(), F::zero()],
+            F::one(),
+        )?;
+        self.non_zero_gate(non_zero)?;
+        Ok(point_var)
+    }
+
+    // Constrain `a` to be at most (p - 1) / 2 as an integer. `a` is split into
+    // limbs `hi * 2^k + lo`, which are compared lexicographically with those of
+    // the bound; the decomposition is unique as the bound is below p.
+    fn leq_half_modulus_gate(&mut self, a: Variable) -> Result<(), PlonkError> {
+        let num_bits = F::size_in_bits();
+        let lo_bits = num_bits / 2;
+        let hi_bits = num_bits - lo_bits;
+        let two_power = F::from(2u8).pow(&[lo_bits as u64]);
+        let value = self.witness(a)?;
+        let hi = F::from(BigUint::from(value) >> lo_bits);
+        let hi_var = self.create_variable(hi)?;
+        let lo_var = self.create_variable(value - hi * two_power)?;
+        self.range_gate(hi_var, hi_bits)?;
+        self.range_gate(lo_var, lo_bits)?;
+        let zero = self.zero();
+        self.lc_gate(
+            &[hi_var, lo_var, zero, zero, a],
+            &[two_power, F::one(), F::zero(), F::zero()],
+        )?;
+        let bound = F::from_repr(F::Params::MODULUS_MINUS_ONE_DIV_TWO).unwrap();
+        self.leq_constant_limbs_gate(hi_var, lo_var, lo_bits, &bound)
+    }
+}
+
+impl<F: PrimeField> Point<F> {
+    /// Compress the point into its x coordinate and a sign bit, which is set
+    /// iff `y > -y`, as expected by `decompress_point`.
+    pub fn compress(&self) -> (F, bool) {
+        (self.0, self.1.into_repr() > (-self.1).into_repr())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::{g1::Parameters as Param761, Fq as Fq377};
+    use ark_ec::{
+        twisted_edwards_extended::{GroupAffine, GroupProjective},
+        ProjectiveCurve,
+    };
+    use ark_ed_on_bls12_377::{EdwardsParameters as Param377, Fq as FqEd377};
+    use ark_ed_on_bls12_381::{EdwardsParameters as Param381, Fq as FqEd381};
+    use ark_ed_on_bls12_381_bandersnatch::{EdwardsParameters as Param381b, Fq as FqEd381b};
+    use ark_ed_on_bn254::{EdwardsParameters as Param254, Fq as FqEd254};
+    use ark_ff::Zero;
+    use ark_std::{test_rng, vec, vec::Vec, UniformRand};
+
+    #[test]
+    fn test_decompress_point() -> Result<(), PlonkError> {
+        test_decompress_point_helper::<FqEd254, Param254>()?;
+        test_decompress_point_helper::<FqEd377, Param377>()?;
+        test_decompress_point_helper::<FqEd381, Param381>()?;
+        test_decompress_point_helper::<FqEd381b, Param381b>()?;
+        test_decompress_point_helper::<Fq377, Param761>()
+    }
+
+    fn test_decompress_point_helper<F, P>() -> Result<(), PlonkError>
+    where
+        F: PrimeField + SquareRootField,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = test_rng();
+        let mut points: Vec<Point<F>> = (0..4)
+            .map(|_| Point::from(GroupProjective::<P>::rand(&mut rng).into_affine()))
+            .collect();
+        points.push(Point::from(GroupAffine::<P>::zero()));
+        points.push(Point(F::zero(), -F::one()));
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let mut outputs = vec![];
+        for point in points.iter() {
+            let (x, sign) = point.compress();
+            let expected = GroupAffine::<P>::get_point_from_x(x, sign).unwrap();
+            assert_eq!(Point::from(expected), *point);
+            let x_var = circuit.create_variable(x)?;
+            let sign_var = circuit.create_bool_variable(sign)?;
+            let point_var = circuit.decompress_point::<P>(x_var, sign_var)?;
+            assert_eq!(circuit.point_witness(&point_var)?, *point);
+            outputs.push(point_var);
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // the other square roots
+        for point_var in outputs.iter() {
+            let y = circuit.witness(point_var.1)?;
+            *circuit.witness_mut(point_var.1) = -y;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(point_var.1) = y;
+        }
+
+        // an x coordinate of no point
+        let mut x = F::rand(&mut rng);
+        while GroupAffine::<P>::get_point_from_x(x, false).is_some() {
+            x = F::rand(&mut rng);
+        }
+        let mut bad_circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let x_var = bad_circuit.create_variable(x)?;
+        let sign_var = bad_circuit.create_bool_variable(false)?;
+        bad_circuit.decompress_point::<P>(x_var, sign_var)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // a sign which is not boolean
+        let mut bad_circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let x_var = bad_circuit.create_variable(points[0].0)?;
+        let sign_var = bad_circuit.create_variable(F::from(2u8))?;
+        let sign_var = bad_circuit.enforce_bool(sign_var)?;
+        bad_circuit.decompress_point::<P>(x_var, sign_var)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        Ok(())
+    }
+}
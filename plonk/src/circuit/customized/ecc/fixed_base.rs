@@ -0,0 +1,149 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Fixed-base scalar multiplication with window tables precomputed once per
+//! base point, e.g. at key generation time for the generators of a scheme.
+//!
+//! The scalar is split into 2-bit windows, and the `i`-th window selects one
+//! of the precomputed points `{0, 1, 2, 3} * 4^i * [G]`, which are baked in the
+//! selectors of the quaternary point selection gates. Hence each window only
+//! costs a selection and a point addition, without any point doubling.
+
+use super::{compute_base_points, Point, PointVariable};
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use ark_ec::{twisted_edwards_extended::GroupAffine, TEModelParameters as Parameters};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+#[derive(Debug, Clone, PartialEq)]
+/// The precomputed window table of a fixed base point `[G]`, i.e. the points
+/// `{4^i * [G]}`, `{2 * 4^i * [G]}` and `{3 * 4^i * [G]}` for all the 2-bit
+/// windows of a scalar.
+pub struct FixedBaseTable<P: Parameters + Clone> {
+    bases: [Vec<GroupAffine<P>>; 3],
+}
+
+impl<P: Parameters + Clone> FixedBaseTable<P> {
+    /// Precompute the window table of `base` for scalars of the scalar field.
+    pub fn new(base: &GroupAffine<P>) -> Result<Self, PlonkError> {
+        let mut num_bits = P::ScalarField::size_in_bits();
+        // `num_bits` needs to be an even number
+        num_bits += num_bits & 1;
+        Ok(Self {
+            bases: compute_base_points(base, num_bits / 2)?,
+        })
+    }
+
+    /// The number of bits of the scalars supported by the table.
+    pub fn num_bits(&self) -> usize {
+        2 * self.bases[0].len()
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    /// Obtain the fixed-based scalar multiplication result of `scalar` *
+    /// `Base`, where the window table of the base point is precomputed.
+    /// Return error if the scalar variable is invalid.
+    pub fn fixed_base_scalar_mul_with_table<P: Parameters<BaseField = F> + Clone>(
+        &mut self,
+        scalar: Variable,
+        table: &FixedBaseTable<P>,
+    ) -> Result<PointVariable, PlonkError> {
+        self.check_var_bound(scalar)?;
+
+        let scalar_bits_le = self.unpack(scalar, table.num_bits())?;
+        let mut accum: Option<PointVariable> = None;
+        for (i, bits) in scalar_bits_le.chunks(2).enumerate() {
+            let selected = self.quaternary_point_select::<P>(
+                bits[0],
+                bits[1],
+                &Point::from(table.bases[0][i]),
+                &Point::from(table.bases[1][i]),
+                &Point::from(table.bases[2][i]),
+            )?;
+            // the first selected point needs no addition
+            accum = Some(match accum {
+                Some(accum) => self.ecc_add::<P>(&accum, &selected)?,
+                None => selected,
+            });
+        }
+        // safe unwrap: the table has at least one window
+        Ok(accum.unwrap())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::{g1::Parameters as Param761, Fq as Fq377};
+    use ark_ed_on_bls12_377::{EdwardsParameters as Param377, Fq as FqEd377};
+    use ark_ed_on_bls12_381::{EdwardsParameters as Param381, Fq as FqEd381};
+    use ark_ed_on_bls12_381_bandersnatch::{EdwardsParameters as Param381b, Fq as FqEd381b};
+    use ark_ed_on_bn254::{EdwardsParameters as Param254, Fq as FqEd254};
+    use ark_ff::{UniformRand, Zero};
+    use ark_std::vec;
+    use jf_utils::fr_to_fq;
+
+    #[test]
+    fn test_fixed_base_scalar_mul_with_table() -> Result<(), PlonkError> {
+        test_fixed_base_scalar_mul_with_table_helper::<FqEd254, Param254>()?;
+        test_fixed_base_scalar_mul_with_table_helper::<FqEd377, Param377>()?;
+        test_fixed_base_scalar_mul_with_table_helper::<FqEd381, Param381>()?;
+        test_fixed_base_scalar_mul_with_table_helper::<FqEd381b, Param381b>()?;
+        test_fixed_base_scalar_mul_with_table_helper::<Fq377, Param761>()
+    }
+
+    fn test_fixed_base_scalar_mul_with_table_helper<F, P>() -> Result<(), PlonkError>
+    where
+        F: PrimeField,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = ark_std::test_rng();
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let base = GroupAffine::<P>::rand(&mut rng);
+        let table = FixedBaseTable::new(&base)?;
+        assert!(table.num_bits() >= P::ScalarField::size_in_bits());
+
+        // the table is reused for several scalars
+        let mut scalars = vec![P::ScalarField::zero(), -P::ScalarField::from(1u8)];
+        scalars.extend((0..4).map(|_| P::ScalarField::rand(&mut rng)));
+        for s in scalars.iter() {
+            let scalar = circuit.create_variable(fr_to_fq::<F, P>(s))?;
+            let result = circuit.fixed_base_scalar_mul_with_table(scalar, &table)?;
+            let mut expected = base;
+            expected *= *s;
+            assert_eq!(Point::from(expected), circuit.point_witness(&result)?);
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // the same result as without a precomputed table, with fewer gates
+        let mut other_circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let scalar = other_circuit.create_variable(fr_to_fq::<F, P>(&scalars[2]))?;
+        let num_gates = other_circuit.num_gates();
+        let result = other_circuit.fixed_base_scalar_mul_with_table(scalar, &table)?;
+        let table_num_gates = other_circuit.num_gates() - num_gates;
+        let expected = other_circuit.point_witness(&result)?;
+        let num_gates = other_circuit.num_gates();
+        let result = other_circuit.fixed_base_scalar_mul(scalar, &base)?;
+        assert!(table_num_gates <= other_circuit.num_gates() - num_gates);
+        assert_eq!(expected, other_circuit.point_witness(&result)?);
+
+        // wrong witness should fail
+        *circuit.witness_mut(2) = F::rand(&mut rng);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .fixed_base_scalar_mul_with_table(circuit.num_vars(), &table)
+            .is_err());
+        Ok(())
+    }
+}
@@ -7,12 +7,13 @@
 use crate::{
     circuit::{
         customized::ecc::{MultiScalarMultiplicationCircuit, PointVariable},
-        Circuit, PlonkCircuit, Variable,
+        BoolVar, Circuit, PlonkCircuit, Variable,
     },
-    errors::PlonkError,
+    errors::{CircuitError::ParameterError, PlonkError},
 };
 use ark_ec::{twisted_edwards_extended::GroupProjective, ProjectiveCurve, TEModelParameters};
 use ark_ff::{FpParameters, PrimeField, Zero};
+use ark_std::string::ToString;
 use jf_utils::field_switching;
 use num_bigint::{BigInt, BigUint};
 
@@ -85,11 +86,21 @@ where
 {
     /// Perform GLV multiplication in circuit (which costs a few less
     /// constraints).
+    /// Only the bandersnatch curve has an efficient endomorphism among the
+    /// supported embedded curves, see `is_glv_curve`.
+    /// Return error if the curve is not bandersnatch, or if the variables are
+    /// invalid.
     pub fn glv_mul<P: TEModelParameters<BaseField = F> + Clone>(
         &mut self,
         scalar: Variable,
         base: &PointVariable,
     ) -> Result<PointVariable, PlonkError> {
+        if !is_glv_curve::<P>() {
+            return Err(ParameterError(
+                "the curve has no efficient endomorphism for GLV multiplication".to_string(),
+            )
+            .into());
+        }
         self.check_var_bound(scalar)?;
         self.check_point_var_bound(base)?;
 
@@ -102,11 +113,21 @@ where
             k_vars[0],
             &endo_base_var,
             k_vars[1],
-            k_vars[2],
+            BoolVar::new_unchecked(k_vars[2]),
         )
     }
 }
 
+/// Whether GLV multiplication is supported for the curve, i.e. whether the
+/// curve is bandersnatch, which is identified by the order of its scalar
+/// field `r = r1 + 2^128 r2`.
+pub fn is_glv_curve<P: TEModelParameters + Clone>() -> bool {
+    let r1 = BigUint::from_bytes_le(R1.as_ref());
+    let r2 = BigUint::from_bytes_le(R2.as_ref());
+    let fr_order: BigUint = <P::ScalarField as PrimeField>::Params::MODULUS.into();
+    P::BaseField::size_in_bits() == 255 && fr_order == r1 + (r2 << 128)
+}
+
 /// The circuit for 2 base scalar multiplication with scalar bit length 128.
 fn multi_scalar_mul_circuit<F, P>(
     circuit: &mut PlonkCircuit<F>,
@@ -114,7 +135,7 @@ fn multi_scalar_mul_circuit<F, P>(
     scalar_1: Variable,
     endo_base: &PointVariable,
     scalar_2: Variable,
-    scalar_2_sign_var: Variable,
+    scalar_2_sign_var: BoolVar,
 ) -> Result<PointVariable, PlonkError>
 where
     F: PrimeField,
@@ -418,10 +439,10 @@ where
         //  (a) k1 < 2^128
         //  (b) k2 < 2^128
         let k1_bits = get_bits(&k1.into_repr().to_bits_le());
-        let k2_bits = get_bits(&k1.into_repr().to_bits_le());
+        let k2_bits = get_bits(&k2.into_repr().to_bits_le());
 
         assert!(k1_bits < 128, "k1 bits {}", k1_bits);
-        assert!(k2_bits < 128, "k2 bits {}", k1_bits);
+        assert!(k2_bits < 128, "k2 bits {}", k2_bits);
 
         //  (c) tmp1 = 0
         //  (d) tmp2 < 2^128
@@ -493,7 +514,7 @@ where
     // ============================================
     //  (f) tmp + t * k2_sign * r1 + k1 =  lambda_1 * k2_sign * k2 + s
     //  (note that we cannot do subtraction because subtraction is over Fq)
-    let k2_is_pos_sat = {
+    let k2_is_pos_sat: Variable = {
         //  (f.1) if k2_sign = 1, then, we prove over Z
         //      tmp + t * r1 + k1 =  lambda_1 * k2 + s
         let left_wire = [tmp_var, t_var, k1_var, circuit.zero()];
@@ -504,10 +525,10 @@ where
         let right_coeff = [lambda_1, F::one(), F::zero(), F::zero()];
         let right_var = circuit.lc(&right_wire, &right_coeff)?;
 
-        circuit.is_equal(left_var, right_var)?
+        circuit.is_equal(left_var, right_var)?.into()
     };
 
-    let k2_is_neg_sat = {
+    let k2_is_neg_sat: Variable = {
         //  (f.2) if k2_sign = -1, then, we prove over Z
         //    lambda_1 * k2 +  tmp + k1 =   s  + t * r1
         let left_wire = [k2_var, tmp_var, k1_var, circuit.zero()];
@@ -517,7 +538,7 @@ where
         let right_wire = [*s_var, t_var, circuit.zero(), circuit.zero()];
         let right_coeff = [F::one(), r1, F::zero(), F::zero()];
         let right_var = circuit.lc(&right_wire, &right_coeff)?;
-        circuit.is_equal(left_var, right_var)?
+        circuit.is_equal(left_var, right_var)?.into()
     };
 
     //  (f.3) either f.1 or f.2 is satisfied
@@ -526,7 +547,7 @@ where
 
     //  (g) tmp2 + lambda_2 * k2_sign * k2 + s2  = t * t_sign * r2
 
-    let k2_is_pos_sat = {
+    let k2_is_pos_sat: Variable = {
         //  (g.1) if k2_sign = 1 then
         //      tmp2 + lambda_2 * k_2_var = t * r2
         let left_wire = [tmp2_var, k2_var, circuit.zero(), circuit.zero()];
@@ -535,10 +556,10 @@ where
 
         let right_var = circuit.mul_constant(t_var, &r2)?;
 
-        circuit.is_equal(left_var, right_var)?
+        circuit.is_equal(left_var, right_var)?.into()
     };
 
-    let k2_is_neg_sat = {
+    let k2_is_neg_sat: Variable = {
         //  (g.2) if k2_sign = -1 then
         //      tmp2  + t * r2 = lambda_2 * k_2_var
         let left_wire = [tmp2_var, t_var, circuit.zero(), circuit.zero()];
@@ -547,7 +568,7 @@ where
 
         let right_var = circuit.mul_constant(k2_var, &lambda_2)?;
 
-        circuit.is_equal(left_var, right_var)?
+        circuit.is_equal(left_var, right_var)?.into()
     };
 
     //  (g.3) either g.1 or g.2 is satisfied
@@ -555,7 +576,7 @@ where
     circuit.enforce_true(sat)?;
 
     // extract the output
-    Ok([k1_var, k2_var, k2_sign_var])
+    Ok([k1_var, k2_var, k2_sign_var.into()])
 }
 
 #[cfg(test)]
@@ -660,6 +681,29 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_glv_curve() -> Result<(), PlonkError> {
+        assert!(is_glv_curve::<EdwardsParameters>());
+        assert!(!is_glv_curve::<ark_ed_on_bls12_381::EdwardsParameters>());
+        assert!(!is_glv_curve::<ark_ed_on_bn254::EdwardsParameters>());
+
+        // GLV multiplication is rejected for curves without the endomorphism
+        type Jubjub = ark_ed_on_bls12_381::EdwardsParameters;
+        let mut rng = test_rng();
+        let base = GroupAffine::<Jubjub>::rand(&mut rng);
+        let mut circuit = PlonkCircuit::<ark_ed_on_bls12_381::Fq>::new_ultra_plonk(16);
+        let s_var = circuit.create_variable(ark_ed_on_bls12_381::Fq::from(7u8))?;
+        let base_var = circuit.create_point_variable(Point::from(base))?;
+        assert!(circuit.glv_mul::<Jubjub>(s_var, &base_var).is_err());
+        // and the variable base multiplication falls back to the MSM
+        let result = circuit.variable_base_scalar_mul::<Jubjub>(s_var, &base_var)?;
+        let mut expected = base;
+        expected *= ark_ed_on_bls12_381::Fr::from(7u8);
+        assert_eq!(Point::from(expected), circuit.point_witness(&result)?);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+
     #[test]
     fn test_endomorphism() {
         let base_point = EdwardsAffine::from_str(
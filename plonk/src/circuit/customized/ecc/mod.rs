@@ -9,24 +9,31 @@
 
 use super::gates::*;
 use crate::{
-    circuit::{gates::Gate, Circuit, PlonkCircuit, Variable},
+    circuit::{gates::Gate, BoolVar, Circuit, CircuitGadget, PlonkCircuit, Variable},
     errors::{CircuitError, PlonkError},
 };
 use ark_ec::{
     group::Group,
     short_weierstrass_jacobian::GroupAffine as SWGroupAffine,
     twisted_edwards_extended::{GroupAffine, GroupProjective},
-    AffineCurve, ModelParameters, ProjectiveCurve, SWModelParameters,
-    TEModelParameters as Parameters,
+    AffineCurve, ProjectiveCurve, SWModelParameters, TEModelParameters as Parameters,
 };
-use ark_ff::{PrimeField, Zero};
+use ark_ff::{BitIteratorBE, PrimeField, Zero};
 use ark_std::{borrow::ToOwned, boxed::Box, string::ToString, vec, vec::Vec};
 use core::marker::PhantomData;
 
+mod baby_jubjub;
+mod bls;
 mod conversion;
+mod decompression;
+mod fixed_base;
 mod glv;
 mod msm;
+pub use baby_jubjub::*;
+pub use bls::*;
 pub use conversion::*;
+pub use fixed_base::*;
+pub use glv::is_glv_curve;
 pub use msm::*;
 
 #[derive(Debug, Copy, Clone, PartialEq)]
@@ -229,14 +236,14 @@ where
     /// Return error if invalid input parameters are provided.
     fn binary_point_vars_select(
         &mut self,
-        b: Variable,
+        b: BoolVar,
         point0: &PointVariable,
         point1: &PointVariable,
     ) -> Result<PointVariable, PlonkError> {
-        self.check_var_bound(b)?;
+        self.check_var_bound(b.into())?;
         self.check_point_var_bound(point0)?;
         self.check_point_var_bound(point1)?;
-        self.check_bool(b)?;
+        self.check_bool(b.into())?;
 
         let selected_x = self.conditional_select(b, point0.0, point1.0)?;
         let selected_y = self.conditional_select(b, point0.1, point1.1)?;
@@ -263,12 +270,12 @@ where
         &mut self,
         point0: &PointVariable,
         point1: &PointVariable,
-    ) -> Result<Variable, PlonkError> {
+    ) -> Result<BoolVar, PlonkError> {
         self.check_point_var_bound(point0)?;
         self.check_point_var_bound(point1)?;
         let x_eq = self.is_equal(point0.0, point1.0)?;
         let y_eq = self.is_equal(point0.1, point1.1)?;
-        self.mul(x_eq, y_eq)
+        self.logic_and(x_eq, y_eq)
     }
 }
 
@@ -309,14 +316,14 @@ where
         // constraint 2: b_y = is_equal(y, 1);
         let b_y = self.is_equal(point_var.1, self.one())?;
         // constraint 3: b = b_x * b_y;
-        self.mul_gate(b_x, b_y, expected_neutral)?;
+        self.mul_gate(b_x.into(), b_y.into(), expected_neutral)?;
         Ok(())
     }
 
     /// Obtain a boolean variable indicating whether a point is the neutral
     /// point (0, 1) Return variable with value 1 if it is, or 0 otherwise
     /// Return error if input variables are invalid
-    pub fn is_neutral_point<P>(&mut self, point_var: &PointVariable) -> Result<Variable, PlonkError>
+    pub fn is_neutral_point<P>(&mut self, point_var: &PointVariable) -> Result<BoolVar, PlonkError>
     where
         P: Parameters<BaseField = F> + Clone,
     {
@@ -331,7 +338,7 @@ where
         };
 
         self.neutral_point_gate(point_var, b)?;
-        Ok(b)
+        Ok(BoolVar(b))
     }
     /// Constrain a point to be on certain curve, namely its coordinates satisfy
     /// the curve equation, which is curve-dependent. Currently we only support
@@ -356,6 +363,51 @@ where
         Ok(())
     }
 
+    /// Constrain a point to be on the curve of `P`, e.g. for an untrusted
+    /// point given as witness. This is the same as `on_curve_gate`.
+    ///
+    /// Returns error if input variables are invalid
+    pub fn enforce_on_curve<P: Parameters<BaseField = F> + Clone>(
+        &mut self,
+        point_var: &PointVariable,
+    ) -> Result<(), PlonkError> {
+        self.on_curve_gate::<P>(point_var)
+    }
+
+    /// Constrain a point to be on the curve of `P` and in its prime order
+    /// subgroup, e.g. for an untrusted point given as witness.
+    /// As the cofactor `h` is coprime with the prime order, the subgroup is the
+    /// image of the multiplication by `h`: the gadget enforces that the point
+    /// is `h` times a witness point on the curve, which takes a number of
+    /// point additions linear in the bit length of `h`.
+    ///
+    /// Returns error if input variables are invalid
+    pub fn enforce_in_prime_subgroup<P: Parameters<BaseField = F> + Clone>(
+        &mut self,
+        point_var: &PointVariable,
+    ) -> Result<(), PlonkError> {
+        self.check_point_var_bound(point_var)?;
+        let point: GroupAffine<P> = self.point_witness(point_var)?.into();
+        // a wrong witness makes the circuit unsatisfiable
+        let base = if point.is_on_curve() {
+            point.mul_by_cofactor_inv()
+        } else {
+            GroupAffine::<P>::zero()
+        };
+        let base_var = self.create_point_variable(Point::from(base))?;
+        self.on_curve_gate::<P>(&base_var)?;
+
+        // double-and-add from the most significant bit of the cofactor
+        let mut acc = base_var;
+        for bit in BitIteratorBE::without_leading_zeros(P::COFACTOR).skip(1) {
+            acc = self.ecc_add::<P>(&acc, &acc)?;
+            if bit {
+                acc = self.ecc_add::<P>(&acc, &base_var)?;
+            }
+        }
+        self.point_equal_gate(&acc, point_var)
+    }
+
     /// Constrain variable `point_c` to be the point addition of `point_a` and
     /// `point_b` over an elliptic curve.
     /// Currently only supports GroupAffine::<P> addition.
@@ -426,43 +478,8 @@ where
         base: &GroupAffine<P>,
     ) -> Result<PointVariable, PlonkError> {
         self.check_var_bound(scalar)?;
-
-        let mut num_bits = <P as ModelParameters>::ScalarField::size_in_bits();
-        // `num_bits` needs to be an even number
-        num_bits += num_bits & 1;
-        let scalar_bits_le = self.unpack(scalar, num_bits)?;
-        let fixed_bases = compute_base_points(base, num_bits / 2)?;
-        let mut accum = self.neutral_point_variable();
-        for i in 0..num_bits / 2 {
-            let b0 = scalar_bits_le.get(2 * i).ok_or_else(|| {
-                CircuitError::InternalError(
-                    "scalar binary representation has the wrong length".to_string(),
-                )
-            })?;
-            let b1 = scalar_bits_le.get(2 * i + 1).ok_or_else(|| {
-                CircuitError::InternalError(
-                    "scalar binary representation has the wrong length".to_string(),
-                )
-            })?;
-            let p1 = fixed_bases[0].get(i).ok_or_else(|| {
-                CircuitError::InternalError("fixed_bases_1 has the wrong length".to_string())
-            })?;
-            let p2 = fixed_bases[1].get(i).ok_or_else(|| {
-                CircuitError::InternalError("fixed_bases_2 has the wrong length".to_string())
-            })?;
-            let p3 = fixed_bases[2].get(i).ok_or_else(|| {
-                CircuitError::InternalError("fixed_bases_3 has the wrong length".to_string())
-            })?;
-            let selected = self.quaternary_point_select::<P>(
-                *b0,
-                *b1,
-                &Point::from(*p1),
-                &Point::from(*p2),
-                &Point::from(*p3),
-            )?;
-            accum = self.ecc_add::<P>(&accum, &selected)?;
-        }
-        Ok(accum)
+        let table = FixedBaseTable::new(base)?;
+        self.fixed_base_scalar_mul_with_table(scalar, &table)
     }
 
     /// Obtain a variable of the result of a variable base scalar
@@ -477,13 +494,8 @@ where
         self.check_var_bound(scalar)?;
         self.check_point_var_bound(base)?;
 
-        if self.support_lookup()
-            && P::ScalarField::size_in_bits() == 253
-            && P::BaseField::size_in_bits() == 255
-        {
+        if self.support_lookup() && is_glv_curve::<P>() {
             // bandersnatch glv multiplication
-            // FIXME: we do not have an easier flag to tell if a parameter
-            // is bandersnatch or not, yet.
             self.glv_mul::<P>(scalar, base)
         } else {
             // non-bandersantch multiplication
@@ -509,7 +521,11 @@ where
         let neutral_point_var = self.neutral_point_variable();
         let mut accum = neutral_point_var;
         for i in (0..scalar_bits_le.len()).rev() {
-            let z = self.binary_point_vars_select(scalar_bits_le[i], &neutral_point_var, base)?;
+            let z = self.binary_point_vars_select(
+                BoolVar::new_unchecked(scalar_bits_le[i]),
+                &neutral_point_var,
+                base,
+            )?;
             accum = self.ecc_add::<P>(&accum, &accum)?;
             accum = self.ecc_add::<P>(&accum, &z)?;
         }
@@ -517,6 +533,121 @@ where
     }
 }
 
+#[derive(Derivative)]
+#[derivative(
+    Debug(bound = ""),
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Default(bound = "")
+)]
+/// The addition of two points as a [`CircuitGadget`], c.f.
+/// `PlonkCircuit::ecc_add`.
+pub struct EccAddGadget<P>(PhantomData<P>);
+
+impl<P> EccAddGadget<P> {
+    /// The addition of points of the curve `P`.
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<F, P> CircuitGadget<F> for EccAddGadget<P>
+where
+    F: PrimeField,
+    P: Parameters<BaseField = F> + Clone,
+{
+    type Input = (PointVariable, PointVariable);
+    type Output = PointVariable;
+
+    fn name(&self) -> &'static str {
+        "ecc_add"
+    }
+
+    fn synthesize(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        (point_a, point_b): (PointVariable, PointVariable),
+    ) -> Result<PointVariable, PlonkError> {
+        circuit.ecc_add::<P>(&point_a, &point_b)
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(
+    Debug(bound = ""),
+    Clone(bound = ""),
+    Copy(bound = ""),
+    Default(bound = "")
+)]
+/// The multiplication of a point variable by a scalar variable as a
+/// [`CircuitGadget`], c.f. `PlonkCircuit::variable_base_scalar_mul`. The
+/// input is the scalar and the point.
+pub struct VariableBaseScalarMulGadget<P>(PhantomData<P>);
+
+impl<P> VariableBaseScalarMulGadget<P> {
+    /// The scalar multiplication of points of the curve `P`.
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<F, P> CircuitGadget<F> for VariableBaseScalarMulGadget<P>
+where
+    F: PrimeField,
+    P: Parameters<BaseField = F> + Clone,
+{
+    type Input = (Variable, PointVariable);
+    type Output = PointVariable;
+
+    fn name(&self) -> &'static str {
+        "variable_base_scalar_mul"
+    }
+
+    fn synthesize(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        (scalar, base): (Variable, PointVariable),
+    ) -> Result<PointVariable, PlonkError> {
+        circuit.variable_base_scalar_mul::<P>(scalar, &base)
+    }
+}
+
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""), Clone(bound = ""), Copy(bound = ""))]
+/// The multiplication of a fixed base point by a scalar variable as a
+/// [`CircuitGadget`], c.f. `PlonkCircuit::fixed_base_scalar_mul`.
+pub struct FixedBaseScalarMulGadget<P: Parameters> {
+    base: GroupAffine<P>,
+}
+
+impl<P: Parameters> FixedBaseScalarMulGadget<P> {
+    /// The scalar multiplication of the point `base`.
+    pub fn new(base: GroupAffine<P>) -> Self {
+        Self { base }
+    }
+}
+
+impl<F, P> CircuitGadget<F> for FixedBaseScalarMulGadget<P>
+where
+    F: PrimeField,
+    P: Parameters<BaseField = F> + Clone,
+{
+    type Input = Variable;
+    type Output = PointVariable;
+
+    fn name(&self) -> &'static str {
+        "fixed_base_scalar_mul"
+    }
+
+    fn synthesize(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        scalar: Variable,
+    ) -> Result<PointVariable, PlonkError> {
+        circuit.fixed_base_scalar_mul(scalar, &self.base)
+    }
+}
+
 // private helper functions
 impl<F> PlonkCircuit<F>
 where
@@ -603,7 +734,7 @@ mod test {
     use ark_ed_on_bls12_381::{EdwardsParameters as Param381, Fq as FqEd381};
     use ark_ed_on_bls12_381_bandersnatch::{EdwardsParameters as Param381b, Fq as FqEd381b};
     use ark_ed_on_bn254::{EdwardsParameters as Param254, Fq as FqEd354};
-    use ark_ff::{One, UniformRand, Zero};
+    use ark_ff::{One, SquareRootField, UniformRand, Zero};
     use jf_utils::fr_to_fq;
     use std::str::FromStr;
 
@@ -627,8 +758,8 @@ mod test {
         let p1_check = circuit.is_neutral_point::<P>(&p1)?;
         let p2_check = circuit.is_neutral_point::<P>(&p2)?;
 
-        assert_eq!(circuit.witness(p1_check)?, F::one());
-        assert_eq!(circuit.witness(p2_check)?, F::zero());
+        assert_eq!(circuit.witness(p1_check.into())?, F::one());
+        assert_eq!(circuit.witness(p2_check.into())?, F::zero());
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
         *circuit.witness_mut(p1.0) = F::one();
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
@@ -759,6 +890,60 @@ mod test {
         Ok(circuit)
     }
 
+    #[test]
+    fn test_enforce_in_prime_subgroup() -> Result<(), PlonkError> {
+        test_enforce_in_prime_subgroup_helper::<FqEd354, Param254>()?;
+        test_enforce_in_prime_subgroup_helper::<FqEd377, Param377>()?;
+        test_enforce_in_prime_subgroup_helper::<FqEd381, Param381>()?;
+        test_enforce_in_prime_subgroup_helper::<FqEd381b, Param381b>()?;
+        test_enforce_in_prime_subgroup_helper::<Fq377, Param761>()
+    }
+
+    fn test_enforce_in_prime_subgroup_helper<F, P>() -> Result<(), PlonkError>
+    where
+        F: PrimeField + SquareRootField,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = ark_std::test_rng();
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        for point in [
+            GroupAffine::<P>::rand(&mut rng),
+            GroupAffine::<P>::prime_subgroup_generator(),
+            GroupAffine::<P>::zero(),
+        ]
+        .iter()
+        {
+            let point_var = circuit.create_point_variable(Point::from(*point))?;
+            circuit.enforce_on_curve::<P>(&point_var)?;
+            circuit.enforce_in_prime_subgroup::<P>(&point_var)?;
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a point on the curve out of the subgroup
+        let mut point = None;
+        while point.is_none() {
+            point = GroupAffine::<P>::get_point_from_x(F::rand(&mut rng), false)
+                .filter(|p| !p.is_in_correct_subgroup_assuming_on_curve());
+        }
+        let mut bad_circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let point_var = bad_circuit.create_point_variable(Point::from(point.unwrap()))?;
+        bad_circuit.enforce_on_curve::<P>(&point_var)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_ok());
+        bad_circuit.enforce_in_prime_subgroup::<P>(&point_var)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // a point out of the curve
+        let mut bad_circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
+        let point_var = bad_circuit.create_point_variable(Point(F::one(), F::one()))?;
+        bad_circuit.enforce_in_prime_subgroup::<P>(&point_var)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        // Check variable out of bound error.
+        assert!(circuit
+            .enforce_in_prime_subgroup::<P>(&PointVariable(0, circuit.num_vars()))
+            .is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_curve_point_addition() -> Result<(), PlonkError> {
         test_curve_point_addition_helper::<FqEd354, Param254>()?;
@@ -1022,8 +1207,8 @@ mod test {
         let p1_p2_eq = circuit.is_equal_point(&p1_var, &p2_var)?;
         let p1_p3_eq = circuit.is_equal_point(&p1_var, &p3_var)?;
 
-        assert_eq!(circuit.witness(p1_p2_eq)?, F::one());
-        assert_eq!(circuit.witness(p1_p3_eq)?, F::zero());
+        assert_eq!(circuit.witness(p1_p2_eq.into())?, F::one());
+        assert_eq!(circuit.witness(p1_p3_eq.into())?, F::zero());
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
         *circuit.witness_mut(p2_var.0) = F::zero();
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
@@ -1188,14 +1373,14 @@ mod test {
         let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
         let p0_var = circuit.create_point_variable(Point::from(p0))?;
         let p1_var = circuit.create_point_variable(Point::from(p1))?;
-        let select_p0 = circuit.binary_point_vars_select(circuit.zero(), &p0_var, &p1_var)?;
+        let select_p0 = circuit.binary_point_vars_select(circuit.false_var(), &p0_var, &p1_var)?;
         assert_eq!(circuit.point_witness(&select_p0)?, Point::from(p0));
-        let select_p1 = circuit.binary_point_vars_select(circuit.one(), &p0_var, &p1_var)?;
+        let select_p1 = circuit.binary_point_vars_select(circuit.true_var(), &p0_var, &p1_var)?;
         assert_eq!(circuit.point_witness(&select_p1)?, Point::from(p1));
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
 
         // non boolean selection variable should fail
-        let two = circuit.create_variable(F::from(2u32))?;
+        let two = BoolVar::new_unchecked(circuit.create_variable(F::from(2u32))?);
         assert!(circuit
             .binary_point_vars_select(two, &p0_var, &p1_var)
             .is_err());
@@ -1205,14 +1390,14 @@ mod test {
         // Check variable out of bound error.
         assert!(circuit
             .binary_point_vars_select(
-                circuit.zero(),
+                circuit.false_var(),
                 &PointVariable(circuit.num_vars(), p0_var.1),
                 &p1_var
             )
             .is_err());
         assert!(circuit
             .binary_point_vars_select(
-                circuit.zero(),
+                circuit.false_var(),
                 &p0_var,
                 &PointVariable(p1_var.0, circuit.num_vars()),
             )
@@ -1244,6 +1429,7 @@ mod test {
     {
         let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
         let b_var = circuit.create_variable(b)?;
+        let b_var = circuit.enforce_bool(b_var)?;
         let p0_var = circuit.create_point_variable(p0)?;
         let p1_var = circuit.create_point_variable(p1)?;
         circuit.binary_point_vars_select(b_var, &p0_var, &p1_var)?;
@@ -0,0 +1,178 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of HMAC-SHA256 (RFC 2104), e.g. for API tokens or
+//! TLS transcripts.
+//!
+//! Keys, messages and tags are represented by byte variables, as for the
+//! SHA-256 gadgets. A key longer than a block is first hashed; the key is
+//! then padded with zeros to a block, and XORed with the constant `ipad` and
+//! `opad` bytes, which only flips some of its bits.
+
+use super::sha2::Sha256Gadget;
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+
+/// The block length of SHA-256 in bytes.
+const SHA256_BLOCK_LEN: usize = 64;
+
+/// The inner padding byte of HMAC.
+const HMAC_IPAD: u8 = 0x36;
+
+/// The outer padding byte of HMAC.
+const HMAC_OPAD: u8 = 0x5c;
+
+/// Trait for the HMAC-SHA256 gadget.
+pub trait HmacSha256Gadget<F: PrimeField> {
+    /// HMAC-SHA256 tag of a message.
+    /// * `key_vars` - the bytes of the key, of any length; each of them is
+    ///   constrained to be in `[0, 256)`
+    /// * `msg_vars` - the bytes of the message; each of them is constrained
+    ///   to be in `[0, 256)`
+    /// * `returns` - the 32 bytes of the tag
+    fn hmac_sha256(
+        &mut self,
+        key_vars: &[Variable],
+        msg_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError>;
+}
+
+impl<F> HmacSha256Gadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn hmac_sha256(
+        &mut self,
+        key_vars: &[Variable],
+        msg_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let hashed_key;
+        let key_vars = if key_vars.len() > SHA256_BLOCK_LEN {
+            hashed_key = self.sha256(key_vars)?;
+            &hashed_key
+        } else {
+            key_vars
+        };
+
+        let mut inner_input = Vec::with_capacity(SHA256_BLOCK_LEN + msg_vars.len());
+        let mut outer_input = Vec::with_capacity(SHA256_BLOCK_LEN + 32);
+        for &byte in key_vars.iter() {
+            let bits = self.unpack_bool(byte, 8)?;
+            inner_input.push(self.xor_constant_byte(&bits, HMAC_IPAD)?);
+            outer_input.push(self.xor_constant_byte(&bits, HMAC_OPAD)?);
+        }
+        // the zero padding of the key
+        for _ in key_vars.len()..SHA256_BLOCK_LEN {
+            inner_input.push(self.create_constant_variable(F::from(HMAC_IPAD))?);
+            outer_input.push(self.create_constant_variable(F::from(HMAC_OPAD))?);
+        }
+
+        inner_input.extend_from_slice(msg_vars);
+        let inner_hash = self.sha256(&inner_input)?;
+        outer_input.extend_from_slice(&inner_hash);
+        self.sha256(&outer_input)
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    // Obtain the variable of the byte whose little-endian bits are `bits`,
+    // XORed with the constant `mask`.
+    fn xor_constant_byte(&mut self, bits: &[BoolVar], mask: u8) -> Result<Variable, PlonkError> {
+        let masked = bits
+            .iter()
+            .enumerate()
+            .map(|(i, &bit)| {
+                if (mask >> i) & 1 == 1 {
+                    self.logic_neg(bit)
+                } else {
+                    Ok(bit)
+                }
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.pack_bits(&masked)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec;
+
+    // the test cases 1, 2 and 6 of RFC 4231, with keys shorter than, and
+    // longer than a block
+    fn test_vectors() -> Vec<(Vec<u8>, Vec<u8>, &'static str)> {
+        vec![
+            (
+                vec![0x0b; 20],
+                b"Hi There".to_vec(),
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?".to_vec(),
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            ),
+            (
+                vec![0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First".to_vec(),
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_hmac_sha256() -> Result<(), PlonkError> {
+        test_hmac_sha256_helper::<FqEd254>()?;
+        test_hmac_sha256_helper::<FqEd377>()?;
+        test_hmac_sha256_helper::<FqEd381>()?;
+        test_hmac_sha256_helper::<Fq377>()
+    }
+
+    fn test_hmac_sha256_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        for (key, msg, tag) in test_vectors().iter() {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let key_vars = key
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let msg_vars = msg
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let tag_vars = circuit.hmac_sha256(&key_vars, &msg_vars)?;
+            assert_eq!(tag_vars.len(), 32);
+            for (i, &var) in tag_vars.iter().enumerate() {
+                let byte = u8::from_str_radix(&tag[2 * i..2 * i + 2], 16).unwrap();
+                assert_eq!(circuit.witness(var)?, F::from(byte));
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong tag byte, or a wrong key byte, is rejected
+            *circuit.witness_mut(tag_vars[31]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(tag_vars[31]) -= F::one();
+            *circuit.witness_mut(key_vars[0]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // a key byte out of range is rejected
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let byte_var = circuit.create_variable(F::from(256u32))?;
+        assert!(circuit.hmac_sha256(&[byte_var], &[]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the Keccak-f\[1600\] permutation and of the
+//! `keccak256` hash function used by Ethereum.
+//!
+//! Lanes are represented by their 64 little-endian boolean variables, c.f.
+//! `bit_ops`, and messages and digests by byte variables, i.e., variables
+//! whose values are in `[0, 256)`. The state is indexed by `x + 5 * y`.
+
+use super::{bit_ops::rotl, bytes::BytesVar};
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+
+/// Number of bits of a Keccak lane.
+const LANE_LEN: usize = 64;
+
+/// Number of lanes of the Keccak-f\[1600\] state.
+const NUM_LANES: usize = 25;
+
+/// Number of rounds of Keccak-f\[1600\].
+const NUM_ROUNDS: usize = 24;
+
+/// Rate in bytes of `keccak256`.
+const KECCAK256_RATE: usize = 136;
+
+/// Keccak-f\[1600\] round constants.
+const KECCAK_RC: [u64; NUM_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+/// Rotation offsets of the rho step, indexed by `x + 5 * y`.
+const KECCAK_RHO: [usize; NUM_LANES] = [
+    0, 1, 62, 28, 27, 36, 44, 6, 55, 20, 3, 10, 43, 25, 39, 41, 45, 15, 21, 8, 18, 2, 61, 56, 14,
+];
+
+/// Trait for the Keccak gadgets.
+pub trait KeccakGadget<F: PrimeField> {
+    /// Apply the Keccak-f\[1600\] permutation.
+    /// * `state` - the 25 lanes of the state, each given by its 64
+    ///   little-endian boolean variables
+    /// * `returns` - the 25 lanes of the permuted state
+    fn keccak_f1600(&mut self, state: &[Vec<BoolVar>]) -> Result<Vec<Vec<BoolVar>>, PlonkError>;
+
+    /// `keccak256` hash of a message, as computed by the EVM, i.e., with the
+    /// original Keccak padding rather than the SHA-3 one.
+    /// * `msg_vars` - the bytes of the message; each of them is constrained
+    ///   to be in `[0, 256)`
+    /// * `returns` - the 32 bytes of the digest
+    fn keccak256(&mut self, msg_vars: &[Variable]) -> Result<Vec<Variable>, PlonkError>;
+
+    /// `keccak256` hash of a byte string, whose digest carries the range
+    /// guarantee of its bytes, as it is packed from bits.
+    fn keccak256_bytes(&mut self, msg: &BytesVar) -> Result<BytesVar, PlonkError> {
+        Ok(BytesVar::new_unchecked(self.keccak256(&msg.vars())?))
+    }
+
+    /// `keccak256` hash of a message of variable length, e.g. an RLP encoded
+    /// trie node. All the blocks are absorbed, and the digest is selected
+    /// from the state after the last block of the padded message.
+    /// * `msg_vars` - the bytes of the message, followed by arbitrary bytes
+    ///   which are ignored; the bytes of the message are constrained to be in
+    ///   `[0, 256)`
+    /// * `len` - the length of the message; the circuit is not satisfied if
+    ///   it exceeds `msg_vars.len()`
+    /// * `returns` - the 32 bytes of the digest
+    fn keccak256_var_len(
+        &mut self,
+        msg_vars: &[Variable],
+        len: Variable,
+    ) -> Result<Vec<Variable>, PlonkError>;
+}
+
+impl<F> KeccakGadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn keccak_f1600(&mut self, state: &[Vec<BoolVar>]) -> Result<Vec<Vec<BoolVar>>, PlonkError> {
+        if state.len() != NUM_LANES {
+            return Err(ParameterError(format!(
+                "Keccak-f[1600] state has {} lanes, got {}",
+                NUM_LANES,
+                state.len()
+            ))
+            .into());
+        }
+        if let Some(lane) = state.iter().find(|lane| lane.len() != LANE_LEN) {
+            return Err(ParameterError(format!(
+                "Keccak lanes have {} bits, got {}",
+                LANE_LEN,
+                lane.len()
+            ))
+            .into());
+        }
+
+        let mut a = state.to_vec();
+        for rc in KECCAK_RC.iter() {
+            // theta
+            let mut c = Vec::with_capacity(5);
+            for x in 0..5 {
+                let t = self.xor3_words(&a[x], &a[x + 5], &a[x + 10])?;
+                c.push(self.xor3_words(&t, &a[x + 15], &a[x + 20])?);
+            }
+            for x in 0..5 {
+                let d = self.xor_words(&c[(x + 4) % 5], &rotl(&c[(x + 1) % 5], 1))?;
+                for y in 0..5 {
+                    a[x + 5 * y] = self.xor_words(&a[x + 5 * y], &d)?;
+                }
+            }
+
+            // rho and pi only permute the bit variables
+            let mut b = a.clone();
+            for x in 0..5 {
+                for y in 0..5 {
+                    b[y + 5 * ((2 * x + 3 * y) % 5)] = rotl(&a[x + 5 * y], KECCAK_RHO[x + 5 * y]);
+                }
+            }
+
+            // chi
+            for x in 0..5 {
+                for y in 0..5 {
+                    let t = self.map_bits2(
+                        &b[(x + 1) % 5 + 5 * y],
+                        &b[(x + 2) % 5 + 5 * y],
+                        Self::and_not_bit,
+                    )?;
+                    a[x + 5 * y] = self.xor_words(&b[x + 5 * y], &t)?;
+                }
+            }
+
+            // iota: only the bits set in the round constant are flipped
+            let one = self.true_var();
+            for (i, bit) in a[0].iter_mut().enumerate() {
+                if (rc >> i) & 1 == 1 {
+                    *bit = self.xor_bit(*bit, one)?;
+                }
+            }
+        }
+        Ok(a)
+    }
+
+    fn keccak256(&mut self, msg_vars: &[Variable]) -> Result<Vec<Variable>, PlonkError> {
+        // the bits of each byte of the padded message, little-endian within
+        // the byte
+        let mut bytes: Vec<Vec<BoolVar>> = msg_vars
+            .iter()
+            .map(|&byte| self.unpack_bool(byte, 8))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let mut padding = ark_std::vec![0u8; KECCAK256_RATE - msg_vars.len() % KECCAK256_RATE];
+        padding[0] |= 0x01;
+        *padding.last_mut().unwrap() |= 0x80;
+        for byte in padding.iter() {
+            bytes.push(self.constant_bits(*byte as u64, 8));
+        }
+
+        let states = self.keccak256_sponge(&bytes)?;
+        self.keccak256_digest(&states[states.len() - 1])
+    }
+
+    fn keccak256_var_len(
+        &mut self,
+        msg_vars: &[Variable],
+        len: Variable,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        self.check_vars_bound(msg_vars)?;
+        self.check_var_bound(len)?;
+        let max_len = msg_vars.len();
+        let num_blocks = max_len / KECCAK256_RATE + 1;
+        // the selector of the first padding byte and the mask of the message
+        let selector = self.one_hot(len, max_len + 1)?;
+        let mask = self.prefix_mask(&selector)?;
+        // the padding ends in the block of its first byte
+        let mut is_final = Vec::with_capacity(num_blocks);
+        for b in 0..num_blocks {
+            let end = ark_std::cmp::min((b + 1) * KECCAK256_RATE, max_len + 1);
+            is_final.push(self.sum(&selector[b * KECCAK256_RATE..end])?);
+        }
+
+        let zero = self.zero();
+        let mut bytes = Vec::with_capacity(num_blocks * KECCAK256_RATE);
+        for i in 0..num_blocks * KECCAK256_RATE {
+            let msg_byte = if i < max_len {
+                self.mul(mask[i], msg_vars[i])?
+            } else {
+                zero
+            };
+            let first_padding = selector.get(i).copied().unwrap_or(zero);
+            let last_padding = if i % KECCAK256_RATE == KECCAK256_RATE - 1 {
+                is_final[i / KECCAK256_RATE]
+            } else {
+                zero
+            };
+            // msg_byte + 0x01 * [i == len] + 0x80 * [i ends the final block]
+            let byte = self.lc(
+                &[msg_byte, first_padding, last_padding, zero],
+                &[F::one(), F::one(), F::from(0x80u8), F::zero()],
+            )?;
+            bytes.push(self.unpack_bool(byte, 8)?);
+        }
+
+        // the digest of the final state
+        let states = self.keccak256_sponge(&bytes)?;
+        let digests = states
+            .iter()
+            .map(|state| self.keccak256_digest(state))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        (0..32)
+            .map(|i| {
+                let candidates: Vec<Variable> = digests.iter().map(|digest| digest[i]).collect();
+                self.one_hot_select(&is_final, &candidates, 0)
+            })
+            .collect()
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    // Absorb the bits of the bytes of a padded message, whose length is a
+    // multiple of the rate, and return the state after each block.
+    fn keccak256_sponge(
+        &mut self,
+        bytes: &[Vec<BoolVar>],
+    ) -> Result<Vec<Vec<Vec<BoolVar>>>, PlonkError> {
+        let mut states: Vec<Vec<Vec<BoolVar>>> = Vec::with_capacity(bytes.len() / KECCAK256_RATE);
+        for block in bytes.chunks(KECCAK256_RATE) {
+            // lanes are little-endian
+            let lanes: Vec<Vec<BoolVar>> = block
+                .chunks(8)
+                .map(|lane| lane.iter().flatten().copied().collect())
+                .collect();
+            let absorbed = match states.last() {
+                // the initial state is zero, so that the first block is
+                // absorbed for free
+                None => {
+                    let mut absorbed = lanes;
+                    absorbed.resize(NUM_LANES, self.constant_bits(0, LANE_LEN));
+                    absorbed
+                },
+                Some(s) => {
+                    let mut s = s.clone();
+                    for (lane, block_lane) in s.iter_mut().zip(lanes.iter()) {
+                        *lane = self.xor_words(lane, block_lane)?;
+                    }
+                    s
+                },
+            };
+            states.push(self.keccak_f1600(&absorbed)?);
+        }
+        Ok(states)
+    }
+
+    // The digest is the little-endian encoding of the first 4 lanes.
+    fn keccak256_digest(&mut self, state: &[Vec<BoolVar>]) -> Result<Vec<Variable>, PlonkError> {
+        let mut digest = Vec::with_capacity(32);
+        for lane in state[..4].iter() {
+            for byte in lane.chunks(8) {
+                digest.push(self.pack_bits(byte)?);
+            }
+        }
+        Ok(digest)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng, vec};
+    use sha3::{Digest, Keccak256};
+
+    #[test]
+    fn test_keccak256() -> Result<(), PlonkError> {
+        test_keccak256_helper::<FqEd254>()?;
+        test_keccak256_helper::<Fq377>()
+    }
+
+    fn test_keccak256_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        // the padding is a single byte for 135 bytes, and takes a second block
+        // for 136 bytes
+        for len in [0, 32, 135, 136] {
+            let mut msg = vec![0u8; len];
+            rng.fill_bytes(&mut msg);
+            let expected = Keccak256::digest(&msg);
+
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let msg_vars = msg
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let digest_vars = circuit.keccak256(&msg_vars)?;
+            assert_eq!(digest_vars.len(), 32);
+            for (var, byte) in digest_vars.iter().zip(expected.iter()) {
+                assert_eq!(circuit.witness(*var)?, F::from(*byte));
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong digest byte is rejected
+            *circuit.witness_mut(digest_vars[31]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // messages of variable length in a buffer of two blocks, followed by
+        // garbage bytes
+        let mut buffer = vec![0u8; 140];
+        rng.fill_bytes(&mut buffer);
+        for len in [0, 1, 135, 136, 140] {
+            let expected = Keccak256::digest(&buffer[..len]);
+
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let msg_vars = buffer
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let len_var = circuit.create_variable(F::from(len as u64))?;
+            let digest_vars = circuit.keccak256_var_len(&msg_vars, len_var)?;
+            for (var, byte) in digest_vars.iter().zip(expected.iter()) {
+                assert_eq!(circuit.witness(*var)?, F::from(*byte));
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong digest byte is rejected
+            *circuit.witness_mut(digest_vars[0]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        // a length exceeding the buffer is rejected
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let msg_vars = buffer
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let len_var = circuit.create_variable(F::from(141u32))?;
+        circuit.keccak256_var_len(&msg_vars, len_var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // a message byte out of range is rejected
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let byte_var = circuit.create_variable(F::from(256u32))?;
+        assert!(circuit.keccak256(&[byte_var]).is_err());
+
+        // the permutation of the zero state, with boolean lanes
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let lane: Vec<BoolVar> = (0..LANE_LEN)
+            .map(|_| circuit.create_bool_variable(false))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let state = circuit.keccak_f1600(&vec![lane.clone(); NUM_LANES])?;
+        let lane_value = |circuit: &PlonkCircuit<F>, lane: &[BoolVar]| {
+            lane.iter().enumerate().fold(0u64, |acc, (i, &bit)| {
+                if circuit.witness(bit.into()).unwrap() == F::one() {
+                    acc | (1 << i)
+                } else {
+                    acc
+                }
+            })
+        };
+        assert_eq!(lane_value(&circuit, &state[0]), 0xf1258f7940e1dde7);
+        assert_eq!(lane_value(&circuit, &state[1]), 0x84d5ccf933c0478a);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // a non-boolean input bit is rejected
+        *circuit.witness_mut(lane[7].into()) = F::from(2u8);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // malformed permutation inputs
+        assert!(circuit.keccak_f1600(&vec![lane.clone(); 24]).is_err());
+        assert!(circuit
+            .keccak_f1600(&vec![lane[..63].to_vec(); NUM_LANES])
+            .is_err());
+        Ok(())
+    }
+}
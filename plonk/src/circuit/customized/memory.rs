@@ -0,0 +1,280 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Random access memory with offline memory checking, e.g. for VM-style
+//! circuits.
+//!
+//! Every access to a `RamVar` is recorded as a tuple `(addr, time, value,
+//! is_write)` with constant times and access types, instead of being
+//! enforced with a linear scan of the memory. `ram_consistency_gate` then
+//! proves that the accesses are consistent: the recorded tuples are a
+//! permutation (see `multiset_equality_gate`) of a list sorted by address
+//! and time, in which every read returns the value of the previous access to
+//! the same address. A read-only memory is a `RamVar` which is never written.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::BigInteger;
+use ark_std::{format, string::ToString, vec, vec::Vec};
+use jf_rescue::RescueParameter;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A random access memory whose accesses are recorded for the consistency
+/// check of `ram_consistency_gate`.
+pub struct RamVar {
+    // the variable of the last value written at each address, only used for
+    // the witness
+    memory: Vec<Variable>,
+    // the (addr, time, value, is_write) tuples of the accesses
+    accesses: Vec<[Variable; 4]>,
+}
+
+impl RamVar {
+    /// The number of addresses of the memory.
+    pub fn size(&self) -> usize {
+        self.memory.len()
+    }
+
+    /// The number of accesses to the memory, including the initial writes.
+    pub fn num_accesses(&self) -> usize {
+        self.accesses.len()
+    }
+}
+
+impl<F: RescueParameter> PlonkCircuit<F> {
+    /// Create a memory initialized with the values of `init`, whose addresses
+    /// are `[0, init.len())`.
+    /// Return error if `init` is empty or if a variable is invalid.
+    pub fn create_ram(&mut self, init: &[Variable]) -> Result<RamVar, PlonkError> {
+        if init.is_empty() {
+            return Err(ParameterError("empty memory".to_string()).into());
+        }
+        self.check_vars_bound(init)?;
+        let mut ram = RamVar {
+            memory: init.to_vec(),
+            accesses: vec![],
+        };
+        for (addr, &value) in init.iter().enumerate() {
+            let addr = self.create_constant_variable(F::from(addr as u64))?;
+            self.record_ram_access(&mut ram, addr, value, true)?;
+        }
+        Ok(ram)
+    }
+
+    /// Obtain the value stored at address `addr` of the memory.
+    /// Return error if the variable is invalid, or if the address is out of
+    /// range.
+    pub fn ram_read(&mut self, ram: &mut RamVar, addr: Variable) -> Result<Variable, PlonkError> {
+        let index = self.ram_index(ram, addr)?;
+        let value = self.create_variable(self.witness(ram.memory[index])?)?;
+        self.record_ram_access(ram, addr, value, false)?;
+        Ok(value)
+    }
+
+    /// Store `value` at address `addr` of the memory.
+    /// Return error if a variable is invalid, or if the address is out of
+    /// range.
+    pub fn ram_write(
+        &mut self,
+        ram: &mut RamVar,
+        addr: Variable,
+        value: Variable,
+    ) -> Result<(), PlonkError> {
+        self.check_var_bound(value)?;
+        let index = self.ram_index(ram, addr)?;
+        ram.memory[index] = value;
+        self.record_ram_access(ram, addr, value, true)
+    }
+
+    /// Constrain the accesses to the memory to be consistent, i.e. every read
+    /// to return the value of the last write to the same address. This is to
+    /// be called once all the accesses are done, and the circuit is not
+    /// satisfied if an address is out of range.
+    /// Return error if the variables are invalid.
+    pub fn ram_consistency_gate(&mut self, ram: &RamVar) -> Result<(), PlonkError> {
+        // the accesses sorted by address and time
+        let mut keys = ram
+            .accesses
+            .iter()
+            .enumerate()
+            .map(|(i, access)| {
+                let addr = self.witness(access[0])?.into_repr();
+                let time = self.witness(access[1])?.into_repr();
+                Ok((addr, time, i))
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        keys.sort();
+        let mut sorted = vec![];
+        for &(.., i) in keys.iter() {
+            let access = ram.accesses[i]
+                .iter()
+                .map(|&var| self.create_variable(self.witness(var)?))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            sorted.push(access);
+        }
+        let accesses: Vec<Vec<Variable>> = ram.accesses.iter().map(|a| a.to_vec()).collect();
+        self.multiset_equality_gate(&accesses, &sorted)?;
+
+        // the addresses go from 0 to size - 1 by steps of 0 or 1, and the first
+        // access to each address is the initial write
+        let (first, last) = (&sorted[0], &sorted[sorted.len() - 1]);
+        self.constant_gate(first[0], F::zero())?;
+        self.constant_gate(last[0], F::from((ram.size() - 1) as u64))?;
+        self.enforce_true(first[3])?;
+        // the times are distinct and smaller than the number of accesses
+        let time_bits = (usize::BITS - ram.num_accesses().leading_zeros()) as usize;
+        let zero = self.zero();
+        for pair in sorted.windows(2) {
+            let (prev, cur) = (&pair[0], &pair[1]);
+            let step = self.sub(cur[0], prev[0])?;
+            self.bool_gate(step)?;
+            // step * (1 - is_write) = 0
+            self.quad_poly_gate(
+                &[step, cur[3], zero, zero, zero],
+                &[F::one(), F::zero(), F::zero(), F::zero()],
+                &[-F::one(), F::zero()],
+                F::zero(),
+                F::zero(),
+            )?;
+            // (1 - step) * (time - prev_time - 1) is in range, i.e. the times
+            // increase for a given address
+            let time_gap = self.gen_quad_poly(
+                &[cur[1], prev[1], zero, zero],
+                &[F::one(), -F::one(), F::zero(), F::zero()],
+                &[F::zero(), F::zero()],
+                -F::one(),
+            )?;
+            let time_gap = self.gen_quad_poly(
+                &[time_gap, step, zero, zero],
+                &[F::one(), F::zero(), F::zero(), F::zero()],
+                &[-F::one(), F::zero()],
+                F::zero(),
+            )?;
+            self.range_gate(time_gap, time_bits)?;
+            // (1 - is_write) * (value - prev_value) = 0, where a read follows
+            // an access to the same address as the step is then zero
+            self.quad_poly_gate(
+                &[cur[3], cur[2], cur[3], prev[2], zero],
+                &[F::zero(), F::one(), F::zero(), -F::one()],
+                &[-F::one(), F::one()],
+                F::zero(),
+                F::zero(),
+            )?;
+        }
+        Ok(())
+    }
+
+    // The address of an access, which is in range for the witness.
+    fn ram_index(&self, ram: &RamVar, addr: Variable) -> Result<usize, PlonkError> {
+        let addr_val = self.witness(addr)?.into_repr();
+        let index = addr_val.as_ref()[0] as usize;
+        if addr_val.num_bits() > 64 || index >= ram.size() {
+            return Err(ParameterError(format!(
+                "address out of range for a memory of size {}",
+                ram.size()
+            ))
+            .into());
+        }
+        Ok(index)
+    }
+
+    fn record_ram_access(
+        &mut self,
+        ram: &mut RamVar,
+        addr: Variable,
+        value: Variable,
+        is_write: bool,
+    ) -> Result<(), PlonkError> {
+        self.check_var_bound(addr)?;
+        let time = self.create_constant_variable(F::from(ram.num_accesses() as u64))?;
+        let is_write = if is_write { self.one() } else { self.zero() };
+        ram.accesses.push([addr, time, value, is_write]);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::Rng, test_rng};
+
+    const SIZE: usize = 8;
+
+    #[test]
+    fn test_ram() -> Result<(), PlonkError> {
+        test_ram_helper::<FqEd254>()?;
+        test_ram_helper::<FqEd377>()?;
+        test_ram_helper::<FqEd381>()?;
+        test_ram_helper::<Fq377>()
+    }
+
+    fn test_ram_helper<F: RescueParameter>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(4),
+        ]
+        .iter()
+        {
+            let mut circuit = circuit.clone();
+            let mut memory: Vec<F> = (0..SIZE).map(|_| F::rand(&mut rng)).collect();
+            let init = memory
+                .iter()
+                .map(|&v| circuit.create_variable(v))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let mut ram = circuit.create_ram(&init)?;
+            assert_eq!(ram.size(), SIZE);
+            let mut reads = vec![];
+            for _ in 0..20 {
+                let addr: usize = rng.gen_range(0..SIZE);
+                let addr_var = circuit.create_variable(F::from(addr as u64))?;
+                if rng.gen_bool(0.5) {
+                    let value = F::rand(&mut rng);
+                    let value_var = circuit.create_variable(value)?;
+                    circuit.ram_write(&mut ram, addr_var, value_var)?;
+                    memory[addr] = value;
+                } else {
+                    let value_var = circuit.ram_read(&mut ram, addr_var)?;
+                    assert_eq!(circuit.witness(value_var)?, memory[addr]);
+                    reads.push(value_var);
+                }
+            }
+            assert_eq!(ram.num_accesses(), SIZE + 20);
+            let mut good_circuit = circuit.clone();
+            good_circuit.ram_consistency_gate(&ram)?;
+            assert!(good_circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a read returning a wrong value
+            for &read in reads.iter() {
+                let mut bad_circuit = circuit.clone();
+                *bad_circuit.witness_mut(read) += F::one();
+                bad_circuit.ram_consistency_gate(&ram)?;
+                assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+
+            // an address out of range
+            let mut bad_circuit = circuit.clone();
+            let addr_var = bad_circuit.create_variable(F::from(SIZE as u64))?;
+            assert!(bad_circuit.ram_read(&mut ram, addr_var).is_err());
+            let mut bad_ram = ram.clone();
+            let addr_var = bad_circuit.create_variable(F::zero())?;
+            bad_circuit.ram_read(&mut bad_ram, addr_var)?;
+            *bad_circuit.witness_mut(addr_var) = F::from(SIZE as u64);
+            bad_circuit.ram_consistency_gate(&bad_ram)?;
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // invalid memory
+            assert!(circuit.create_ram(&[]).is_err());
+        }
+        Ok(())
+    }
+}
@@ -8,7 +8,7 @@
 //! related, rescue-based transcript and lookup table etc.
 
 use self::gates::*;
-use super::{Circuit, PlonkCircuit, PlonkError, Variable};
+use super::{BoolVar, Circuit, PlonkCircuit, PlonkError, Variable};
 use crate::{
     circuit::gates::{ConstantAdditionGate, ConstantMultiplicationGate, FifthRootGate},
     constants::{GATE_WIDTH, N_MUL_SELECTORS},
@@ -18,9 +18,24 @@ use ark_ff::{BigInteger, PrimeField};
 use ark_std::{borrow::ToOwned, boxed::Box, cmp::Ordering, format, string::ToString, vec::Vec};
 use num_bigint::BigUint;
 
+mod bit_ops;
+pub mod blake2s;
+pub mod bytes;
+mod comparison;
+mod division;
 pub mod ecc;
 mod gates;
+pub mod hmac;
+pub mod keccak;
+pub mod memory;
+pub mod mpt;
+mod mux;
+pub mod poseidon;
 pub mod rescue;
+pub mod rlp;
+pub mod sha2;
+mod sort;
+mod sqrt;
 pub mod transcript;
 pub mod ultraplonk;
 
@@ -235,10 +250,11 @@ where
     /// one. Return error if variables are invalid.
     pub fn conditional_select(
         &mut self,
-        b: Variable,
+        b: BoolVar,
         x_0: Variable,
         x_1: Variable,
     ) -> Result<Variable, PlonkError> {
+        let b: Variable = b.into();
         self.check_var_bound(b)?;
         self.check_var_bound(x_0)?;
         self.check_var_bound(x_1)?;
@@ -313,17 +329,17 @@ where
 
     /// Constrain that `a` is true or `b` is true.
     /// Return error if variables are invalid.
-    pub fn logic_or_gate(&mut self, a: Variable, b: Variable) -> Result<(), PlonkError> {
-        self.check_var_bound(a)?;
-        self.check_var_bound(b)?;
-        let wire_vars = &[a, b, 0, 0, 0];
+    pub fn logic_or_gate(&mut self, a: BoolVar, b: BoolVar) -> Result<(), PlonkError> {
+        self.check_var_bound(a.into())?;
+        self.check_var_bound(b.into())?;
+        let wire_vars = &[a.0, b.0, 0, 0, 0];
         self.insert_gate(wire_vars, Box::new(LogicOrGate))?;
         Ok(())
     }
 
     /// Obtain a bool variable representing whether two input variables are
     /// equal. Return error if variables are invalid.
-    pub fn is_equal(&mut self, a: Variable, b: Variable) -> Result<Variable, PlonkError> {
+    pub fn is_equal(&mut self, a: Variable, b: Variable) -> Result<BoolVar, PlonkError> {
         self.check_var_bound(a)?;
         self.check_var_bound(b)?;
         let delta = self.sub(a, b)?;
@@ -332,7 +348,7 @@ where
 
     /// Obtain a bool variable representing whether input variable is zero.
     /// Return error if the input variable is invalid.
-    pub fn is_zero(&mut self, a: Variable) -> Result<Variable, PlonkError> {
+    pub fn is_zero(&mut self, a: Variable) -> Result<BoolVar, PlonkError> {
         self.check_var_bound(a)?;
 
         // y is the bit indicating if a == zero
@@ -358,7 +374,7 @@ where
         )?;
         // constraint 2: multiplication y * a = 0
         self.mul_gate(y, a, self.zero())?;
-        Ok(y)
+        Ok(BoolVar(y))
     }
 
     /// Constrain a variable to be non-zero.
@@ -370,26 +386,22 @@ where
         self.mul_gate(var, inv_var, one_var)
     }
 
-    /// Assuming value represented by `a` is boolean, obtain a
-    /// variable representing the result of a logic negation gate. Return the
-    /// index of the variable. Return error if the input variable is invalid.
-    pub fn logic_neg(&mut self, a: Variable) -> Result<Variable, PlonkError> {
-        self.is_zero(a)
+    /// Obtain a bool variable representing the result of a logic negation
+    /// gate. Return error if the input variable is invalid.
+    pub fn logic_neg(&mut self, a: BoolVar) -> Result<BoolVar, PlonkError> {
+        self.is_zero(a.into())
     }
 
-    /// Assuming values represented by `a` and `b` are boolean, obtain a
-    /// variable representing the result of a logic AND gate. Return the
-    /// index of the variable. Return error if the input variables are
-    /// invalid.
-    pub fn logic_and(&mut self, a: Variable, b: Variable) -> Result<Variable, PlonkError> {
-        self.mul(a, b)
+    /// Obtain a bool variable representing the result of a logic AND gate.
+    /// Return error if the input variables are invalid.
+    pub fn logic_and(&mut self, a: BoolVar, b: BoolVar) -> Result<BoolVar, PlonkError> {
+        Ok(BoolVar(self.mul(a.into(), b.into())?))
     }
 
-    /// Given a list of boolean variables, obtain a
-    /// variable representing the result of a logic AND gate. Return the
-    /// index of the variable. Return error if the input variables are
-    /// invalid.
-    pub fn logic_and_all(&mut self, vars: &[Variable]) -> Result<Variable, PlonkError> {
+    /// Given a list of bool variables, obtain a bool variable representing
+    /// the result of a logic AND gate. Return error if the input variables
+    /// are invalid.
+    pub fn logic_and_all(&mut self, vars: &[BoolVar]) -> Result<BoolVar, PlonkError> {
         if vars.is_empty() {
             return Err(PlonkError::InvalidParameters(
                 "logic_and_all: empty variable list".to_string(),
@@ -402,11 +414,10 @@ where
         Ok(res)
     }
 
-    /// Assuming values represented by `a` and `b` are boolean, obtain a
-    /// variable representing the result of a logic OR gate. Return the
-    /// index of the variable. Return error if the input variables are
-    /// invalid.
-    pub fn logic_or(&mut self, a: Variable, b: Variable) -> Result<Variable, PlonkError> {
+    /// Obtain a bool variable representing the result of a logic OR gate.
+    /// Return error if the input variables are invalid.
+    pub fn logic_or(&mut self, a: BoolVar, b: BoolVar) -> Result<BoolVar, PlonkError> {
+        let (a, b): (Variable, Variable) = (a.into(), b.into());
         self.check_var_bound(a)?;
         self.check_var_bound(b)?;
         let a_val = self.witness(a)?;
@@ -415,7 +426,7 @@ where
         let c = self.create_variable(c_val)?;
         let wire_vars = &[a, b, 0, 0, c];
         self.insert_gate(wire_vars, Box::new(LogicOrValueGate))?;
-        Ok(c)
+        Ok(BoolVar(c))
     }
 
     /// Assuming values represented by `a` is boolean.
@@ -430,6 +441,14 @@ where
         self.constant_gate(a, F::zero())
     }
 
+    /// Constrain a variable to be boolean, and return it as a bool variable,
+    /// e.g. to pass a selector created by `create_variable` to the boolean
+    /// gadgets. Return error if the variable is invalid.
+    pub fn enforce_bool(&mut self, a: Variable) -> Result<BoolVar, PlonkError> {
+        self.bool_gate(a)?;
+        Ok(BoolVar(a))
+    }
+
     /// Return a variable to be the 11th power of the input variable.
     /// Cost: 3 constraints.
     pub fn power_11_gen(&mut self, x: Variable) -> Result<Variable, PlonkError> {
@@ -514,7 +533,8 @@ where
             ));
         }
 
-        let bit_length_non_lookup_range = bit_length % self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
+        let bit_length_non_lookup_range = bit_length % range_bit_len;
         let bit_length_lookup_component = bit_length - bit_length_non_lookup_range;
 
         // we need to show that a and b satisfy the following
@@ -532,7 +552,7 @@ where
         // So we set delta_length = native_field_bit_length - bit_length
 
         let delta_length = native_field_bit_length - bit_length;
-        let delta_length_non_lookup_range = delta_length % self.range_bit_len()?;
+        let delta_length_non_lookup_range = delta_length % range_bit_len;
         let delta_length_lookup_component = delta_length - delta_length_non_lookup_range;
 
         // Now (2) becomes
@@ -643,6 +663,9 @@ impl<F: PrimeField> PlonkCircuit<F> {
     /// Constrain a variable to be within the [0, 2^`bit_len`) range
     /// Return error if the variable is invalid.
     pub fn range_gate(&mut self, a: Variable, bit_len: usize) -> Result<(), PlonkError> {
+        if self.is_range_bit_len_deferred() {
+            return self.enforce_in_range(a, bit_len);
+        }
         if self.support_lookup() && bit_len % self.range_bit_len()? == 0 {
             self.range_gate_with_lookup(a, bit_len)?;
         } else {
@@ -654,7 +677,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
     /// Return a boolean variable indicating whether variable `a` is in the
     /// range [0, 2^`bit_len`). Return error if the variable is invalid.
     /// TODO: optimize the gate for UltraPlonk.
-    pub fn is_in_range(&mut self, a: Variable, bit_len: usize) -> Result<Variable, PlonkError> {
+    pub fn is_in_range(&mut self, a: Variable, bit_len: usize) -> Result<BoolVar, PlonkError> {
         let a_bit_le = self.unpack(a, F::size_in_bits())?;
         // a is in range if and only if the bits in `a_bit_le[bit_len..]` are all
         // zeroes.
@@ -667,6 +690,17 @@ impl<F: PrimeField> PlonkCircuit<F> {
     /// representation of `a`.
     /// Return error if the `a` is not the range of [0, 2^`bit_len`).
     pub fn unpack(&mut self, a: Variable, bit_len: usize) -> Result<Vec<Variable>, PlonkError> {
+        Ok(self
+            .unpack_bool(a, bit_len)?
+            .into_iter()
+            .map(Variable::from)
+            .collect())
+    }
+
+    /// Same as `unpack`, with the bits as boolean variables, which they are
+    /// constrained to be.
+    /// Return error if the `a` is not the range of [0, 2^`bit_len`).
+    pub fn unpack_bool(&mut self, a: Variable, bit_len: usize) -> Result<Vec<BoolVar>, PlonkError> {
         if bit_len < F::size_in_bits() && self.witness(a)? >= F::from(2u32).pow([bit_len as u64]) {
             return Err(CircuitError::ParameterError(
                 "Failed to unpack variable to a range of smaller than 2^bit_len".to_string(),
@@ -676,12 +710,69 @@ impl<F: PrimeField> PlonkCircuit<F> {
         self.range_gate_internal(a, bit_len)
     }
 
+    /// Constrain the little-endian bits `bits_le` of an integer to be the
+    /// canonical binary representation of a field element, i.e. the integer
+    /// to be smaller than the modulus, so that no other element is mapped to
+    /// the same one by packing the bits, e.g. for scalars given by the bits
+    /// of a field element of `F::size_in_bits()` bits.
+    /// Return error if there are not `F::size_in_bits()` bits, or if the
+    /// variables are invalid.
+    pub fn enforce_canonical_bits(&mut self, bits_le: &[BoolVar]) -> Result<(), PlonkError> {
+        let num_bits = F::size_in_bits();
+        if bits_le.len() != num_bits {
+            return Err(CircuitError::ParameterError(format!(
+                "a canonical representation has {} bits, got {}",
+                num_bits,
+                bits_le.len()
+            ))
+            .into());
+        }
+        // the limbs packed from boolean variables are in range
+        let lo_bits = num_bits / 2;
+        let lo_var = self.pack_bits(&bits_le[..lo_bits])?;
+        let hi_var = self.pack_bits(&bits_le[lo_bits..])?;
+        self.leq_constant_limbs_gate(hi_var, lo_var, lo_bits, &-F::one())
+    }
+
+    // Constrain the integer `hi * 2^lo_bits + lo` to be at most `bound`, where
+    // `hi` and `lo` are expected to be in `[0, 2^{size_in_bits - lo_bits})` and
+    // `[0, 2^lo_bits)`, by comparing the limbs lexicographically with those of
+    // the bound: `bound_hi - hi` is in `[0, 2^{size_in_bits - lo_bits})`, and
+    // so is `bound_lo - lo` in `[0, 2^lo_bits)` if the high limbs are equal.
+    pub(crate) fn leq_constant_limbs_gate(
+        &mut self,
+        hi_var: Variable,
+        lo_var: Variable,
+        lo_bits: usize,
+        bound: &F,
+    ) -> Result<(), PlonkError> {
+        let hi_bits = F::size_in_bits() - lo_bits;
+        let bound_hi = F::from(BigUint::from(*bound) >> lo_bits);
+        let bound_lo = *bound - bound_hi * F::from(2u8).pow(&[lo_bits as u64]);
+        let zero = self.zero();
+        let hi_gap = self.gen_quad_poly(
+            &[hi_var, zero, zero, zero],
+            &[-F::one(), F::zero(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            bound_hi,
+        )?;
+        self.range_gate(hi_gap, hi_bits)?;
+        let hi_eq = self.is_zero(hi_gap)?;
+        let lo_gap = self.gen_quad_poly(
+            &[hi_eq.into(), lo_var, zero, zero],
+            &[bound_lo, F::zero(), F::zero(), F::zero()],
+            &[-F::one(), F::zero()],
+            F::zero(),
+        )?;
+        self.range_gate(lo_gap, lo_bits)
+    }
+
     // internal of a range check gate
     fn range_gate_internal(
         &mut self,
         a: Variable,
         bit_len: usize,
-    ) -> Result<Vec<Variable>, PlonkError> {
+    ) -> Result<Vec<BoolVar>, PlonkError> {
         self.check_var_bound(a)?;
         if bit_len == 0 {
             return Err(CircuitError::ParameterError(
@@ -701,15 +792,17 @@ impl<F: PrimeField> PlonkCircuit<F> {
         }
         // convert to variable in the circuit from the vector of boolean as binary
         // representation
-        let a_bits_le: Vec<Variable> = a_bits_le
+        let a_bits_le: Vec<BoolVar> = a_bits_le
             .iter()
             .take(bit_len) // since little-endian, truncate would remove MSBs
-            .map(|&b| {
-                self.create_bool_variable(b)
-            })
+            .map(|&b| self.create_bool_variable(b))
             .collect::<Result<Vec<_>, PlonkError>>()?;
 
-        self.decompose_vars_gate(a_bits_le.clone(), a, F::from(2u8))?;
+        self.decompose_vars_gate(
+            a_bits_le.iter().map(|&b| b.into()).collect(),
+            a,
+            F::from(2u8),
+        )?;
 
         Ok(a_bits_le)
     }
@@ -772,6 +865,7 @@ pub(crate) mod test {
     use ark_ed_on_bls12_377::Fq as FqEd377;
     use ark_ed_on_bls12_381::Fq as FqEd381;
     use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::FpParameters;
     use ark_std::{convert::TryInto, test_rng, vec};
 
     // two circuit with the same statement should have the same extended permutation
@@ -815,8 +909,8 @@ pub(crate) mod test {
 
     fn test_logic_or_helper<F: PrimeField>() -> Result<(), PlonkError> {
         let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
-        let zero_var = circuit.zero();
-        let one_var = circuit.one();
+        let zero_var = circuit.false_var();
+        let one_var = circuit.true_var();
         // Good path
         circuit.logic_or_gate(zero_var, one_var)?;
         circuit.logic_or_gate(one_var, zero_var)?;
@@ -836,7 +930,9 @@ pub(crate) mod test {
     fn build_logic_or_circuit<F: PrimeField>(a: F, b: F) -> Result<PlonkCircuit<F>, PlonkError> {
         let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
         let a = circuit.create_variable(a)?;
+        let a = circuit.enforce_bool(a)?;
         let b = circuit.create_variable(b)?;
+        let b = circuit.enforce_bool(b)?;
         circuit.logic_or_gate(a, b)?;
         circuit.finalize_for_arithmetization()?;
         Ok(circuit)
@@ -852,24 +948,24 @@ pub(crate) mod test {
 
     fn test_logic_and_helper<F: PrimeField>() -> Result<(), PlonkError> {
         let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
-        let zero_var = circuit.zero();
-        let one_var = circuit.one();
+        let zero_var = circuit.false_var();
+        let one_var = circuit.true_var();
         // Good path
         let a = circuit.logic_and(zero_var, one_var)?;
-        assert_eq!(F::zero(), circuit.witness(a)?);
+        assert_eq!(F::zero(), circuit.witness(a.into())?);
         let b = circuit.logic_and(one_var, zero_var)?;
-        assert_eq!(F::zero(), circuit.witness(b)?);
+        assert_eq!(F::zero(), circuit.witness(b.into())?);
         let c = circuit.logic_and(one_var, one_var)?;
-        assert_eq!(F::one(), circuit.witness(c)?);
+        assert_eq!(F::one(), circuit.witness(c.into())?);
         let d = circuit.logic_and_all(&[zero_var, one_var, one_var])?;
-        assert_eq!(F::zero(), circuit.witness(d)?);
+        assert_eq!(F::zero(), circuit.witness(d.into())?);
         let e = circuit.logic_and_all(&[one_var, one_var, one_var])?;
-        assert_eq!(F::one(), circuit.witness(e)?);
+        assert_eq!(F::one(), circuit.witness(e.into())?);
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
         // Error path
-        *circuit.witness_mut(e) = F::zero();
+        *circuit.witness_mut(e.into()) = F::zero();
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
-        *circuit.witness_mut(e) = F::one();
+        *circuit.witness_mut(e.into()) = F::one();
         assert!(circuit.logic_and_all(&[]).is_err());
 
         let circuit_1 = build_logic_and_circuit(F::one(), F::one())?;
@@ -882,7 +978,9 @@ pub(crate) mod test {
     fn build_logic_and_circuit<F: PrimeField>(a: F, b: F) -> Result<PlonkCircuit<F>, PlonkError> {
         let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
         let a = circuit.create_variable(a)?;
+        let a = circuit.enforce_bool(a)?;
         let b = circuit.create_variable(b)?;
+        let b = circuit.enforce_bool(b)?;
         circuit.logic_and(a, b)?;
         circuit.finalize_for_arithmetization()?;
         Ok(circuit)
@@ -904,8 +1002,8 @@ pub(crate) mod test {
         let a_zero_eq = circuit.is_equal(a, circuit.zero())?;
 
         // check circuit
-        assert_eq!(circuit.witness(a_b_eq)?, F::one());
-        assert_eq!(circuit.witness(a_zero_eq)?, F::zero());
+        assert_eq!(circuit.witness(a_b_eq.into())?, F::one());
+        assert_eq!(circuit.witness(a_zero_eq.into())?, F::zero());
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
         *circuit.witness_mut(b) = val + F::one();
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
@@ -943,12 +1041,12 @@ pub(crate) mod test {
         let zero_zero_eq = circuit.is_zero(circuit.zero())?;
 
         // check circuit
-        assert_eq!(circuit.witness(a_zero_eq)?, F::zero());
-        assert_eq!(circuit.witness(zero_zero_eq)?, F::one());
+        assert_eq!(circuit.witness(a_zero_eq.into())?, F::zero());
+        assert_eq!(circuit.witness(zero_zero_eq.into())?, F::one());
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
-        *circuit.witness_mut(zero_zero_eq) = F::zero();
+        *circuit.witness_mut(zero_zero_eq.into()) = F::zero();
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
-        *circuit.witness_mut(zero_zero_eq) = F::one();
+        *circuit.witness_mut(zero_zero_eq.into()) = F::one();
         *circuit.witness_mut(a) = F::zero();
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
         // Check variable out of bound error.
@@ -1179,8 +1277,8 @@ pub(crate) mod test {
 
     fn test_conditional_select_helper<F: PrimeField>() -> Result<(), PlonkError> {
         let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
-        let bit_true = circuit.create_variable(F::one())?;
-        let bit_false = circuit.create_variable(F::zero())?;
+        let bit_true = circuit.create_bool_variable(true)?;
+        let bit_false = circuit.create_bool_variable(false)?;
         let x_0 = circuit.create_variable(F::from(23u32))?;
         let x_1 = circuit.create_variable(F::from(24u32))?;
         let select_true = circuit.conditional_select(bit_true, x_0, x_1)?;
@@ -1192,9 +1290,10 @@ pub(crate) mod test {
 
         // if bit is NOT a boolean variable, should fail
         let non_bool = circuit.create_variable(F::from(2u32))?;
+        let non_bool = BoolVar::new_unchecked(non_bool);
         assert!(circuit.conditional_select(non_bool, x_0, x_1).is_err());
         // if mess up the wire value, should fail
-        *circuit.witness_mut(bit_false) = F::one();
+        *circuit.witness_mut(bit_false.into()) = F::one();
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
         // Check variable out of bound error.
         assert!(circuit
@@ -1218,6 +1317,7 @@ pub(crate) mod test {
     ) -> Result<PlonkCircuit<F>, PlonkError> {
         let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_turbo_plonk();
         let bit_var = circuit.create_variable(bit)?;
+        let bit_var = circuit.enforce_bool(bit_var)?;
         let x_0_var = circuit.create_variable(x_0)?;
         let x_1_var = circuit.create_variable(x_1)?;
         circuit.conditional_select(bit_var, x_0_var, x_1_var)?;
@@ -1305,6 +1405,20 @@ pub(crate) mod test {
         assert_eq!(b_le.len(), 10);
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
         assert!(circuit.unpack(b, 9).is_err());
+
+        // the bits of p - 1 are canonical, but those of p are not
+        let num_bits = F::size_in_bits();
+        let c = circuit.create_variable(-F::one())?;
+        let c_le = circuit.unpack_bool(c, num_bits)?;
+        circuit.enforce_canonical_bits(&c_le)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        let modulus = F::Params::MODULUS.to_bits_le();
+        let p_le = (0..num_bits)
+            .map(|i| circuit.create_bool_variable(modulus[i]))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        circuit.enforce_canonical_bits(&p_le)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        assert!(circuit.enforce_canonical_bits(&c_le[1..]).is_err());
         Ok(())
     }
 
@@ -1370,9 +1484,9 @@ pub(crate) mod test {
         let b1 = circuit.is_in_range(a, 5)?;
         let b2 = circuit.is_in_range(a, 10)?;
         let b3 = circuit.is_in_range(a, 0)?;
-        assert_eq!(circuit.witness(b1)?, F::zero());
-        assert_eq!(circuit.witness(b2)?, F::one());
-        assert_eq!(circuit.witness(b3)?, F::zero());
+        assert_eq!(circuit.witness(b1.into())?, F::zero());
+        assert_eq!(circuit.witness(b2.into())?, F::one());
+        assert_eq!(circuit.witness(b3.into())?, F::zero());
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
 
         // if mess up the wire value, should fail
@@ -1430,7 +1544,7 @@ pub(crate) mod test {
         circuit.lc(&wire_in.try_into().unwrap(), &coeffs)?;
 
         // conditional select gate
-        let bit_true = circuit.create_variable(F::one())?;
+        let bit_true = circuit.create_bool_variable(true)?;
         let x_0 = circuit.create_variable(F::from(23u32))?;
         let x_1 = circuit.create_variable(F::from(24u32))?;
         circuit.conditional_select(bit_true, x_0, x_1)?;
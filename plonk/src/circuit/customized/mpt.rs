@@ -0,0 +1,585 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the verification of inclusion proofs in the
+//! Merkle-Patricia tries of Ethereum, e.g. for account or storage proofs
+//! against a state root, as returned by `eth_getProof`.
+//!
+//! A proof is the list of the RLP encoded nodes on the path from the root to
+//! a leaf, each of them being hashed with `keccak256` into the reference
+//! stored by its parent. The nodes are given as a fixed number of byte
+//! variables, and the proofs are padded to a fixed depth, so that the circuit
+//! only depends on these bounds. The keys are 32 bytes, i.e., the hashed keys
+//! of the secure tries of the state and of the storages, so that the value
+//! is always stored in a leaf.
+//!
+//! Every node has to be referred to by its hash, which is not the case of
+//! the nodes whose encoding is shorter than 32 bytes, e.g. some leaves of
+//! storage tries, which are inlined in their parent; such proofs are not
+//! supported.
+
+use super::{
+    keccak::KeccakGadget,
+    rlp::{RlpGadget, RlpItemVar},
+};
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec, vec::Vec};
+
+/// The length in bytes of the keys and of the node hashes.
+const MPT_KEY_LEN: usize = 32;
+
+/// The number of nibbles of a key.
+const MPT_KEY_NIBBLES: usize = 2 * MPT_KEY_LEN;
+
+/// The number of items of a branch node.
+const MPT_BRANCH_LEN: usize = 17;
+
+/// The maximal length of the hex-prefix encoded path of a leaf or an
+/// extension node.
+const MPT_MAX_PATH_LEN: usize = MPT_KEY_LEN + 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Variables of a Merkle-Patricia-Trie proof, padded to a maximal depth and
+/// to a maximal node length.
+pub struct MptProofVar {
+    // the bytes of the nodes from the root, the levels after the proof
+    // repeating its last node
+    nodes: Vec<Vec<Variable>>,
+    // the lengths of the encodings of the nodes
+    node_lens: Vec<Variable>,
+    // the number of nodes of the proof
+    depth: Variable,
+}
+
+impl MptProofVar {
+    /// The maximal number of nodes of the proof.
+    pub fn max_depth(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// The maximal length of the encoding of a node.
+    pub fn max_node_len(&self) -> usize {
+        self.nodes[0].len()
+    }
+
+    /// The variable of the number of nodes of the proof.
+    pub fn depth(&self) -> Variable {
+        self.depth
+    }
+}
+
+/// Trait for the Merkle-Patricia-Trie gadgets.
+pub trait MptGadget<F: PrimeField> {
+    /// Create the variables of a proof.
+    /// * `nodes` - the RLP encodings of the nodes from the root to the leaf
+    /// * `max_depth` - the maximal number of nodes of the proofs
+    /// * `max_node_len` - the maximal length of the encodings of the nodes,
+    ///   which is 532 bytes for a branch node
+    /// * `returns` - the variables of the proof
+    fn create_mpt_proof_variable(
+        &mut self,
+        nodes: &[Vec<u8>],
+        max_depth: usize,
+        max_node_len: usize,
+    ) -> Result<MptProofVar, PlonkError>;
+
+    /// Constrain the key-value pair to be stored in the trie of root `root`,
+    /// as proven by `proof`.
+    /// * `root` - the 32 bytes of the root hash, which are expected to be
+    ///   constrained to be in `[0, 256)`
+    /// * `key` - the 32 bytes of the key; each of them is constrained to be in
+    ///   `[0, 256)`
+    /// * `value` - the bytes of the value padded with zeros, which are
+    ///   expected to be constrained to be in `[0, 256)`
+    /// * `value_len` - the length of the value
+    /// * `proof` - the variables of the proof
+    fn mpt_verify_inclusion(
+        &mut self,
+        root: &[Variable],
+        key: &[Variable],
+        value: &[Variable],
+        value_len: Variable,
+        proof: &MptProofVar,
+    ) -> Result<(), PlonkError>;
+}
+
+impl<F> MptGadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn create_mpt_proof_variable(
+        &mut self,
+        nodes: &[Vec<u8>],
+        max_depth: usize,
+        max_node_len: usize,
+    ) -> Result<MptProofVar, PlonkError> {
+        if nodes.is_empty() || nodes.len() > max_depth {
+            return Err(ParameterError(format!(
+                "a proof has between 1 and {} nodes, got {}",
+                max_depth,
+                nodes.len()
+            ))
+            .into());
+        }
+        if nodes.iter().any(|node| node.len() > max_node_len) {
+            return Err(ParameterError(format!(
+                "the nodes of a proof have at most {} bytes",
+                max_node_len
+            ))
+            .into());
+        }
+
+        let zero = self.zero();
+        let mut node_vars = Vec::with_capacity(max_depth);
+        let mut node_lens = Vec::with_capacity(max_depth);
+        for node in nodes.iter() {
+            let mut vars = node
+                .iter()
+                .map(|&b| self.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            vars.resize(max_node_len, zero);
+            node_vars.push(vars);
+            node_lens.push(self.create_variable(F::from(node.len() as u64))?);
+        }
+        // the levels after the proof repeat its last node, so that they are
+        // decoded as well
+        let last = (
+            node_vars[nodes.len() - 1].clone(),
+            node_lens[nodes.len() - 1],
+        );
+        node_vars.resize(max_depth, last.0);
+        node_lens.resize(max_depth, last.1);
+        Ok(MptProofVar {
+            nodes: node_vars,
+            node_lens,
+            depth: self.create_variable(F::from(nodes.len() as u64))?,
+        })
+    }
+
+    fn mpt_verify_inclusion(
+        &mut self,
+        root: &[Variable],
+        key: &[Variable],
+        value: &[Variable],
+        value_len: Variable,
+        proof: &MptProofVar,
+    ) -> Result<(), PlonkError> {
+        if root.len() != MPT_KEY_LEN || key.len() != MPT_KEY_LEN {
+            return Err(ParameterError(format!(
+                "roots and keys have {} bytes, got {} and {}",
+                MPT_KEY_LEN,
+                root.len(),
+                key.len()
+            ))
+            .into());
+        }
+        self.check_vars_bound(root)?;
+        self.check_vars_bound(value)?;
+        self.check_var_bound(value_len)?;
+        self.check_var_bound(proof.depth)?;
+
+        // the nibbles of the key, the high nibble of a byte first
+        let mut key_nibbles = Vec::with_capacity(MPT_KEY_NIBBLES);
+        for &byte in key.iter() {
+            let bits = self.unpack_bool(byte, 8)?;
+            key_nibbles.push(self.pack_bits(&bits[4..])?);
+            key_nibbles.push(self.pack_bits(&bits[..4])?);
+        }
+
+        // active[d] = [d < depth] and is_last[d] = [d + 1 == depth] for a
+        // depth in [1, max_depth]
+        let max_depth = proof.max_depth();
+        let depth_selector = self.one_hot(proof.depth, max_depth + 1)?;
+        let active = self.prefix_mask(&depth_selector)?;
+        self.enforce_false(depth_selector[0])?;
+
+        let zero = self.zero();
+        let one = self.one();
+        let hash_len = self.create_constant_variable(F::from(MPT_KEY_LEN as u64))?;
+        let mut expected = root.to_vec();
+        // the number of nibbles of the key consumed by the previous nodes
+        let mut pos = zero;
+        for d in 0..max_depth {
+            let node = &proof.nodes[d];
+            let (active, is_last) = (active[d], depth_selector[d + 1]);
+            // the node is hashed into the expected reference
+            let hash = self.keccak256_var_len(node, proof.node_lens[d])?;
+            for (&h, &e) in hash.iter().zip(expected.iter()) {
+                self.mpt_gated_equal(active, h, e)?;
+            }
+
+            // the node is a list of 17 items for a branch, and of 2 items for a
+            // leaf or an extension, whose encoding is the hashed bytes
+            let (list, items) = self.rlp_decode_list(node, MPT_BRANCH_LEN)?;
+            let list_end = self.add(list.payload_offset(), list.payload_len())?;
+            self.equal_gate(list_end, proof.node_lens[d])?;
+            let is_branch = items[MPT_BRANCH_LEN - 1].present();
+            let is_pair = self.sub(items[1].present(), items[2].present())?;
+            let is_node = self.add(is_branch, is_pair)?;
+            self.mpt_gated_equal(active, is_node, one)?;
+            // a branch is not the last node, as all the keys have 64 nibbles
+            self.mpt_gated_equal(is_last, is_branch, zero)?;
+
+            // the nibbles of the key from the current position
+            let pos_selector = self.one_hot(pos, MPT_KEY_NIBBLES)?;
+            let key_rest = (0..MPT_KEY_NIBBLES)
+                .map(|t| self.one_hot_select(&pos_selector, &key_nibbles, t))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+
+            // a branch refers to its child at the next nibble of the key
+            let child_selector = self.one_hot(key_rest[0], 16)?;
+            let child = self.mpt_select_item(&child_selector, &items[..16])?;
+
+            // a leaf or an extension starts with its hex-prefix encoded path,
+            // whose first byte has the flags 0x20 for a leaf and 0x10 for an
+            // odd number of nibbles, and the first nibble if odd
+            let num_path_bytes = self.mul(is_pair, items[0].payload_len())?;
+            self.mpt_gated_equal(is_pair, items[0].is_list(), zero)?;
+            let path = self.rlp_slice(
+                node,
+                items[0].payload_offset(),
+                num_path_bytes,
+                MPT_MAX_PATH_LEN,
+            )?;
+            let no_path: Variable = self.is_zero(num_path_bytes)?.into();
+            self.mpt_gated_equal(is_pair, no_path, zero)?;
+            let flags = self.unpack_bool(path[0], 8)?;
+            let (odd, leaf): (Variable, Variable) = (flags[4].into(), flags[5].into());
+            self.mpt_gated_equal(is_pair, flags[6].into(), zero)?;
+            self.mpt_gated_equal(is_pair, flags[7].into(), zero)?;
+            let first_nibble = self.pack_bits(&flags[..4])?;
+            let even: Variable = self.logic_neg(flags[4])?.into();
+            let is_even_pair = self.mul(is_pair, even)?;
+            self.mpt_gated_equal(is_even_pair, first_nibble, zero)?;
+            // a leaf is the last node
+            let is_active_pair = self.mul(active, is_pair)?;
+            self.mpt_gated_equal(is_active_pair, leaf, is_last)?;
+
+            // the path has 2 * num_path_bytes - 2 + odd nibbles for a leaf or
+            // an extension, and none for a branch
+            let num_nibbles = self.gen_quad_poly(
+                &[is_pair, odd, num_path_bytes, zero],
+                &[-F::from(2u8), F::zero(), F::from(2u8), F::zero()],
+                &[F::one(), F::zero()],
+                F::zero(),
+            )?;
+            let mut path_nibbles = vec![first_nibble];
+            for &byte in path[1..].iter() {
+                let bits = self.unpack_bool(byte, 8)?;
+                path_nibbles.push(self.pack_bits(&bits[4..])?);
+                path_nibbles.push(self.pack_bits(&bits[..4])?);
+            }
+            // the t-th nibble of the path, i.e. path_nibbles[t + 1 - odd], is
+            // the t-th nibble of the rest of the key if t < num_nibbles
+            let nibble_selector = self.one_hot(num_nibbles, MPT_KEY_NIBBLES + 1)?;
+            let in_path = self.prefix_mask(&nibble_selector)?;
+            for (t, &in_path) in in_path.iter().enumerate() {
+                let nibble = self.gen_quad_poly(
+                    &[odd, path_nibbles[t], odd, path_nibbles[t + 1]],
+                    &[F::zero(), F::zero(), F::zero(), F::one()],
+                    &[F::one(), -F::one()],
+                    F::zero(),
+                )?;
+                let gate = self.mul(is_active_pair, in_path)?;
+                self.mpt_gated_equal(gate, nibble, key_rest[t])?;
+            }
+
+            // the next node is referred to by the hash stored as the child of a
+            // branch, or as the second item of an extension
+            let next = self.mpt_select_item(&[is_branch, is_pair], &[child, items[1]])?;
+            let has_next = self.sub(active, is_last)?;
+            self.mpt_gated_equal(has_next, next.is_list(), zero)?;
+            self.mpt_gated_equal(has_next, next.payload_len(), hash_len)?;
+            let next_len = self.mul(has_next, next.payload_len())?;
+            expected = self.rlp_slice(node, next.payload_offset(), next_len, MPT_KEY_LEN)?;
+
+            // the second item of the leaf is the value
+            self.mpt_gated_equal(is_last, items[1].is_list(), zero)?;
+            self.mpt_gated_equal(is_last, items[1].payload_len(), value_len)?;
+            let leaf_value_len = self.mul(is_last, items[1].payload_len())?;
+            let leaf_value =
+                self.rlp_slice(node, items[1].payload_offset(), leaf_value_len, value.len())?;
+            for (&a, &b) in leaf_value.iter().zip(value.iter()) {
+                self.mpt_gated_equal(is_last, a, b)?;
+            }
+
+            // pos += active * (is_branch + num_nibbles)
+            let step = self.add(is_branch, num_nibbles)?;
+            let step = self.mul(active, step)?;
+            pos = self.add(pos, step)?;
+        }
+        // the whole key is consumed
+        self.constant_gate(pos, F::from(MPT_KEY_NIBBLES as u64))
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    // Constrain gate * (a - b) = 0, i.e. a = b if the boolean `gate` is set.
+    fn mpt_gated_equal(
+        &mut self,
+        gate: Variable,
+        a: Variable,
+        b: Variable,
+    ) -> Result<(), PlonkError> {
+        let zero = self.zero();
+        self.quad_poly_gate(
+            &[gate, a, gate, b, zero],
+            &[F::zero(); 4],
+            &[F::one(), -F::one()],
+            F::zero(),
+            F::zero(),
+        )
+    }
+
+    // The fields of the item selected by the one-hot `selector`, or zeros if
+    // the selector is zero.
+    fn mpt_select_item(
+        &mut self,
+        selector: &[Variable],
+        items: &[RlpItemVar],
+    ) -> Result<RlpItemVar, PlonkError> {
+        let mut fields = vec![Vec::with_capacity(items.len()); 5];
+        for item in items.iter() {
+            fields[0].push(item.present);
+            fields[1].push(item.start);
+            fields[2].push(item.is_list);
+            fields[3].push(item.offset);
+            fields[4].push(item.len);
+        }
+        let mut selected = Vec::with_capacity(fields.len());
+        for field in fields.iter() {
+            selected.push(self.one_hot_select(selector, field, 0)?);
+        }
+        Ok(RlpItemVar {
+            present: selected[0],
+            start: selected[1],
+            is_list: selected[2],
+            offset: selected[3],
+            len: selected[4],
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng};
+    use sha3::{Digest, Keccak256};
+
+    const MAX_DEPTH: usize = 4;
+    const MAX_NODE_LEN: usize = 100;
+    const MAX_VALUE_LEN: usize = 48;
+
+    fn encode_string(s: &[u8]) -> Vec<u8> {
+        if s.len() == 1 && s[0] < 0x80 {
+            return s.to_vec();
+        }
+        let mut out = encode_header(s.len(), 0x80);
+        out.extend_from_slice(s);
+        out
+    }
+
+    fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload: Vec<u8> = items.concat();
+        let mut out = encode_header(payload.len(), 0xc0);
+        out.extend_from_slice(&payload);
+        out
+    }
+
+    fn encode_header(len: usize, base: u8) -> Vec<u8> {
+        if len < 56 {
+            vec![base + len as u8]
+        } else {
+            vec![base + 55 + 1, len as u8]
+        }
+    }
+
+    fn nibbles(key: &[u8]) -> Vec<u8> {
+        key.iter().flat_map(|b| vec![b >> 4, b & 0xf]).collect()
+    }
+
+    // the hex-prefix encoding of a path
+    fn encode_path(path: &[u8], leaf: bool) -> Vec<u8> {
+        let flags = if leaf { 0x20 } else { 0 };
+        let (mut out, rest) = if path.len() % 2 == 1 {
+            (vec![flags | 0x10 | path[0]], &path[1..])
+        } else {
+            (vec![flags], path)
+        };
+        out.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+        out
+    }
+
+    fn leaf(path: &[u8], value: &[u8]) -> Vec<u8> {
+        encode_list(&[
+            encode_string(&encode_path(path, true)),
+            encode_string(value),
+        ])
+    }
+
+    fn branch(children: &[(u8, &[u8])]) -> Vec<u8> {
+        let mut items = vec![encode_string(&[]); MPT_BRANCH_LEN];
+        for &(nibble, child) in children.iter() {
+            items[nibble as usize] = encode_string(&Keccak256::digest(child));
+        }
+        encode_list(&items)
+    }
+
+    #[test]
+    fn test_mpt_verify_inclusion() -> Result<(), PlonkError> {
+        test_mpt_verify_inclusion_helper::<FqEd254>()?;
+        test_mpt_verify_inclusion_helper::<Fq377>()
+    }
+
+    fn test_mpt_verify_inclusion_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        // keys[0] and keys[1] share their first 2 nibbles, and keys[2] differs
+        // from them at the first nibble
+        let mut keys = vec![[0u8; MPT_KEY_LEN]; 3];
+        rng.fill_bytes(&mut keys[0]);
+        keys[1] = keys[0];
+        keys[1][1] ^= 0x10;
+        rng.fill_bytes(&mut keys[2]);
+        keys[2][0] = keys[0][0] ^ 0x80;
+        let values: Vec<Vec<u8>> = (0..3)
+            .map(|i| {
+                let mut value = vec![0u8; 40 - i];
+                rng.fill_bytes(&mut value);
+                value
+            })
+            .collect();
+        let paths: Vec<Vec<u8>> = keys.iter().map(|key| nibbles(key)).collect();
+
+        // root -> extension -> branch -> leaves 0 and 1, and root -> leaf 2
+        let leaves: Vec<Vec<u8>> = (0..3)
+            .map(|i| {
+                let from = if i < 2 { 3 } else { 1 };
+                leaf(&paths[i][from..], &values[i])
+            })
+            .collect();
+        let inner = branch(&[(paths[0][2], &leaves[0]), (paths[1][2], &leaves[1])]);
+        let extension = encode_list(&[
+            encode_string(&encode_path(&paths[0][1..2], false)),
+            encode_string(&Keccak256::digest(&inner)),
+        ]);
+        let root = branch(&[(paths[0][0], &extension), (paths[2][0], &leaves[2])]);
+        let root_hash = Keccak256::digest(&root);
+        let proofs = vec![
+            vec![
+                root.clone(),
+                extension.clone(),
+                inner.clone(),
+                leaves[0].clone(),
+            ],
+            vec![root.clone(), extension, inner, leaves[1].clone()],
+            vec![root, leaves[2].clone()],
+        ];
+
+        for (i, proof) in proofs.iter().enumerate() {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let root_vars = root_hash
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let key_vars = keys[i]
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let mut value = values[i].clone();
+            value.resize(MAX_VALUE_LEN, 0);
+            let value_vars = value
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let value_len_var = circuit.create_variable(F::from(values[i].len() as u64))?;
+            let proof_var = circuit.create_mpt_proof_variable(proof, MAX_DEPTH, MAX_NODE_LEN)?;
+            assert_eq!(proof_var.max_depth(), MAX_DEPTH);
+            assert_eq!(proof_var.max_node_len(), MAX_NODE_LEN);
+            circuit.mpt_verify_inclusion(
+                &root_vars,
+                &key_vars,
+                &value_vars,
+                value_len_var,
+                &proof_var,
+            )?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong root, key, value, value length or depth is rejected
+            for &var in [
+                root_vars[31],
+                key_vars[0],
+                key_vars[31],
+                value_vars[0],
+                value_vars[MAX_VALUE_LEN - 1],
+                value_len_var,
+                proof_var.depth(),
+            ]
+            .iter()
+            {
+                *circuit.witness_mut(var) += F::one();
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                *circuit.witness_mut(var) -= F::one();
+            }
+        }
+
+        // the proof of a key is not a proof of another key
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let root_vars = root_hash
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let key_vars = keys[1]
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let mut value = values[0].clone();
+        value.resize(MAX_VALUE_LEN, 0);
+        let value_vars = value
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let value_len_var = circuit.create_variable(F::from(values[0].len() as u64))?;
+        let proof_var = circuit.create_mpt_proof_variable(&proofs[0], MAX_DEPTH, MAX_NODE_LEN)?;
+        circuit.mpt_verify_inclusion(
+            &root_vars,
+            &key_vars,
+            &value_vars,
+            value_len_var,
+            &proof_var,
+        )?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid proofs and inputs
+        assert!(circuit
+            .create_mpt_proof_variable(&[], MAX_DEPTH, MAX_NODE_LEN)
+            .is_err());
+        assert!(circuit
+            .create_mpt_proof_variable(&proofs[0], 3, MAX_NODE_LEN)
+            .is_err());
+        assert!(circuit
+            .create_mpt_proof_variable(&proofs[0], MAX_DEPTH, 80)
+            .is_err());
+        assert!(circuit
+            .mpt_verify_inclusion(
+                &root_vars[1..],
+                &key_vars,
+                &value_vars,
+                value_len_var,
+                &proof_var
+            )
+            .is_err());
+        Ok(())
+    }
+}
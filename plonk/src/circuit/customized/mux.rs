@@ -0,0 +1,167 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Multiplexers selecting one of `k` variables, or one of `k` vectors of
+//! variables, by a variable index.
+//!
+//! The index is decomposed into `ceil(log2(k))` bits, and the inputs are
+//! reduced by a binary tree of conditional selections, the `l`-th level being
+//! selected by the `l`-th least significant bit. When `k` is not a power of
+//! two, the last node of a level with an odd number of nodes is passed to the
+//! next level as is, and the index is constrained to be smaller than `k`, so
+//! that the bit which would select the missing node is zero. Selecting among
+//! `k` vectors of `m` variables takes `(k - 1) * m` gates in addition to the
+//! decomposition of the index.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec, vec::Vec};
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Obtain a variable equal to `inputs[index]`, the index being
+    /// constrained to be smaller than `inputs.len()`.
+    /// Return error if `inputs` is empty, if the variables are invalid, or if
+    /// the value of the index is out of range.
+    pub fn mux(&mut self, index: Variable, inputs: &[Variable]) -> Result<Variable, PlonkError> {
+        let vectors: Vec<Vec<Variable>> = inputs.iter().map(|&x| vec![x]).collect();
+        Ok(self.vector_mux(index, &vectors)?[0])
+    }
+
+    /// Obtain the variables equal to `vectors[index]`, the index being
+    /// constrained to be smaller than `vectors.len()`.
+    /// Return error if `vectors` is empty or if its vectors have different
+    /// lengths, if the variables are invalid, or if the value of the index is
+    /// out of range.
+    pub fn vector_mux(
+        &mut self,
+        index: Variable,
+        vectors: &[Vec<Variable>],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        if vectors.is_empty() {
+            return Err(ParameterError("no input of the multiplexer".to_string()).into());
+        }
+        let len = vectors[0].len();
+        if vectors.iter().any(|v| v.len() != len) {
+            return Err(ParameterError(
+                "the inputs of the multiplexer have different lengths".to_string(),
+            )
+            .into());
+        }
+        for v in vectors.iter() {
+            self.check_vars_bound(v)?;
+        }
+        self.check_var_bound(index)?;
+        let k = vectors.len();
+        if self.witness(index)? >= F::from(k as u64) {
+            return Err(ParameterError(format!(
+                "the index of the multiplexer is not smaller than {}",
+                k
+            ))
+            .into());
+        }
+
+        // index and k - 1 - index are in [0, 2^num_bits), i.e. index is in
+        // [0, k - 1]
+        let max_index = k - 1;
+        let num_bits = (usize::BITS - max_index.leading_zeros()) as usize;
+        if num_bits == 0 {
+            self.constant_gate(index, F::zero())?;
+            return Ok(vectors[0].clone());
+        }
+        let bits = self.unpack_bool(index, num_bits)?;
+        if !k.is_power_of_two() {
+            let zero = self.zero();
+            let rev_index = self.gen_quad_poly(
+                &[index, zero, zero, zero],
+                &[-F::one(), F::zero(), F::zero(), F::zero()],
+                &[F::zero(), F::zero()],
+                F::from(max_index as u64),
+            )?;
+            self.range_gate(rev_index, num_bits)?;
+        }
+
+        let mut level = vectors.to_vec();
+        for bit in bits {
+            level = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [x_0, x_1] => x_0
+                        .iter()
+                        .zip(x_1.iter())
+                        .map(|(&a, &b)| self.conditional_select(bit, a, b))
+                        .collect(),
+                    _ => Ok(pair[0].clone()),
+                })
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+        }
+        Ok(level.remove(0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_mux() -> Result<(), PlonkError> {
+        test_mux_helper::<FqEd254>()?;
+        test_mux_helper::<FqEd377>()?;
+        test_mux_helper::<FqEd381>()?;
+        test_mux_helper::<Fq377>()
+    }
+
+    fn test_mux_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        // powers of two or not, up to a single input
+        for k in [1usize, 2, 5, 8] {
+            for index in 0..k {
+                let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+                let vectors = (0..k)
+                    .map(|i| {
+                        (0..3)
+                            .map(|j| circuit.create_variable(F::from((10 * i + j) as u64)))
+                            .collect::<Result<Vec<_>, PlonkError>>()
+                    })
+                    .collect::<Result<Vec<_>, PlonkError>>()?;
+                let index_var = circuit.create_variable(F::from(index as u64))?;
+                let output = circuit.vector_mux(index_var, &vectors)?;
+                for (j, &var) in output.iter().enumerate() {
+                    assert_eq!(circuit.witness(var)?, F::from((10 * index + j) as u64));
+                }
+                let inputs: Vec<Variable> = vectors.iter().map(|v| v[1]).collect();
+                let output = circuit.mux(index_var, &inputs)?;
+                assert_eq!(circuit.witness(output)?, F::from((10 * index + 1) as u64));
+                assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+                // a wrong output or index
+                *circuit.witness_mut(output) += F::one();
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                *circuit.witness_mut(output) -= F::one();
+                *circuit.witness_mut(index_var) = F::from(k as u64);
+                assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            }
+        }
+
+        // invalid inputs
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let zero = circuit.zero();
+        let index_var = circuit.create_variable(F::from(2u32))?;
+        assert!(circuit.mux(zero, &[]).is_err());
+        assert!(circuit.mux(index_var, &[zero, zero]).is_err());
+        assert!(circuit
+            .vector_mux(zero, &[vec![zero], vec![zero, zero]])
+            .is_err());
+        assert!(circuit.mux(circuit.num_vars(), &[zero]).is_err());
+        assert!(circuit.mux(zero, &[circuit.num_vars()]).is_err());
+        Ok(())
+    }
+}
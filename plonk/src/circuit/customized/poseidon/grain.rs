@@ -0,0 +1,184 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Generation of Poseidon parameters with the Grain LFSR of the reference
+//! script `generate_parameters_grain.sage` of the Poseidon authors.
+
+use super::PoseidonParams;
+use crate::errors::{CircuitError::ParameterError, PlonkError};
+use ark_ff::{BigInteger, Field, FpParameters, PrimeField};
+use ark_std::{format, string::ToString, vec, vec::Vec};
+
+/// Size in bits of the state of the Grain LFSR.
+const GRAIN_STATE_SIZE: usize = 80;
+
+/// The Grain LFSR in self-shrinking mode, initialized with the description
+/// of a Poseidon instance over a prime field with the `x^5` S-box. The i-th
+/// bit of the state is the i-th oldest bit of the sequence.
+struct GrainLfsr {
+    state: u128,
+}
+
+impl GrainLfsr {
+    fn new(field_bits: usize, width: usize, full_rounds: usize, partial_rounds: usize) -> Self {
+        // 2 bits for the prime field type (1), 4 bits for the `x^alpha` S-box
+        // type (0), 12 bits for the field size, 12 bits for the width, 10 bits
+        // for each number of rounds, all big-endian, and 30 bits set to one
+        let fields = [
+            (1, 2),
+            (0, 4),
+            (field_bits, 12),
+            (width, 12),
+            (full_rounds, 10),
+            (partial_rounds, 10),
+        ];
+        let mut state = (1u128 << GRAIN_STATE_SIZE) - 1;
+        let mut pos = 0;
+        for (value, len) in fields.iter() {
+            for i in (0..*len).rev() {
+                if (value >> i) & 1 == 0 {
+                    state &= !(1u128 << pos);
+                }
+                pos += 1;
+            }
+        }
+        let mut lfsr = Self { state };
+        // discard the first 160 bits
+        for _ in 0..160 {
+            lfsr.update();
+        }
+        lfsr
+    }
+
+    fn update(&mut self) -> bool {
+        let s = self.state;
+        let new_bit = ((s >> 62) ^ (s >> 51) ^ (s >> 38) ^ (s >> 23) ^ (s >> 13) ^ s) & 1;
+        self.state = (s >> 1) | (new_bit << (GRAIN_STATE_SIZE - 1));
+        new_bit == 1
+    }
+
+    // Output the second bit of the first pair of bits starting with a one.
+    fn next_bit(&mut self) -> bool {
+        while !self.update() {
+            self.update();
+        }
+        self.update()
+    }
+
+    // The next `num_bits` output bits, most significant bit first.
+    fn next_bits(&mut self, num_bits: usize) -> Vec<bool> {
+        (0..num_bits).map(|_| self.next_bit()).collect()
+    }
+}
+
+// Interpret `bits`, most significant bit first, as a big integer with the
+// representation of `F`, or return `None` if it is not smaller than the
+// modulus.
+fn bits_to_field<F: PrimeField>(bits: &[bool]) -> Option<F> {
+    F::from_repr(<F::BigInt as BigInteger>::from_bits_be(bits))
+}
+
+impl<F: PrimeField> PoseidonParams<F> {
+    /// Generate the parameters of a Poseidon permutation of width `width`
+    /// with the `x^5` S-box as the reference script of the Poseidon authors,
+    /// `generate_parameters_grain.sage 1 0 n width full_rounds partial_rounds`
+    /// where `n` is the bit length of the modulus of `F`: the round constants
+    /// are sampled with rejection from the Grain LFSR, and the Cauchy MDS
+    /// matrix from elements of `F` sampled from the Grain LFSR without
+    /// rejection. These are, e.g., the parameters of circomlib.
+    ///
+    /// The numbers of rounds are not checked against a security level.
+    /// Return error if `x^5` is not a permutation of `F` or if the parameters
+    /// are invalid, c.f. `PoseidonParams::new`.
+    pub fn from_grain_lfsr(
+        width: usize,
+        full_rounds: usize,
+        partial_rounds: usize,
+    ) -> Result<Self, PlonkError> {
+        // `x^5` is a permutation iff 5 does not divide p - 1; since 256 = 1
+        // mod 5, p mod 5 is the sum of the bytes of p mod 5
+        let modulus = <F::Params as FpParameters>::MODULUS.to_bytes_le();
+        if modulus.iter().map(|b| *b as u64).sum::<u64>() % 5 == 1 {
+            return Err(ParameterError(format!(
+                "x^5 is not a permutation of a field of {} bits",
+                F::size_in_bits()
+            ))
+            .into());
+        }
+        let field_bits = F::size_in_bits();
+        let mut lfsr = GrainLfsr::new(field_bits, width, full_rounds, partial_rounds);
+
+        let mut round_constants = Vec::with_capacity(full_rounds + partial_rounds);
+        for _ in 0..full_rounds + partial_rounds {
+            let mut rc = Vec::with_capacity(width);
+            while rc.len() < width {
+                if let Some(c) = bits_to_field::<F>(&lfsr.next_bits(field_bits)) {
+                    rc.push(c);
+                }
+            }
+            round_constants.push(rc);
+        }
+
+        // the Cauchy matrix 1 / (x_i + y_j) of 2 * width distinct elements
+        let mds_elems = loop {
+            let elems: Vec<F> = (0..2 * width)
+                .map(|_| {
+                    let bits = lfsr.next_bits(field_bits);
+                    // pad to whole bytes for the reduction mod p
+                    let mut padded = vec![false; (8 - field_bits % 8) % 8];
+                    padded.extend(bits);
+                    let bytes: Vec<u8> = padded
+                        .chunks(8)
+                        .map(|byte| byte.iter().fold(0u8, |acc, b| (acc << 1) | *b as u8))
+                        .collect();
+                    F::from_be_bytes_mod_order(&bytes)
+                })
+                .collect();
+            let distinct = elems
+                .iter()
+                .enumerate()
+                .all(|(i, x)| elems[..i].iter().all(|y| y != x));
+            if distinct {
+                break elems;
+            }
+        };
+        let (xs, ys) = mds_elems.split_at(width);
+        let mds = xs
+            .iter()
+            .map(|x| {
+                ys.iter()
+                    .map(|y| {
+                        (*x + y).inverse().ok_or_else(|| {
+                            PlonkError::from(ParameterError(
+                                "singular Poseidon Cauchy matrix".to_string(),
+                            ))
+                        })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(full_rounds, partial_rounds, round_constants, mds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::{Fq as Fq377, Fr as Fr377};
+
+    #[test]
+    fn test_from_grain_lfsr() -> Result<(), PlonkError> {
+        let params = PoseidonParams::<Fq377>::from_grain_lfsr(4, 8, 56)?;
+        assert_eq!(params.width(), 4);
+        // x^5 is not a permutation of the scalar field of BLS12-377
+        assert!(PoseidonParams::<Fr377>::from_grain_lfsr(4, 8, 56).is_err());
+        // invalid parameters
+        assert!(PoseidonParams::<Fq377>::from_grain_lfsr(4, 7, 56).is_err());
+        assert!(PoseidonParams::<Fq377>::from_grain_lfsr(1, 8, 56).is_err());
+        Ok(())
+    }
+}
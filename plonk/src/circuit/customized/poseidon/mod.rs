@@ -0,0 +1,596 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Poseidon hash related gates and gadgets.
+//!
+//! The permutation uses the `x^5` S-box and is instantiated with caller
+//! provided round constants and MDS matrix, so that it can match the
+//! parameters of an external system, e.g., those of circomlib over BN254.
+//! A round costs one gate per state element when the state width is at most
+//! `GATE_WIDTH`; wider states, such as those of circomlib's EdDSA, chain
+//! several gates per element.
+
+use crate::{
+    circuit::{gates::Gate, Circuit, PlonkCircuit, Variable},
+    constants::GATE_WIDTH,
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{boxed::Box, format, string::ToString, vec, vec::Vec};
+use jf_utils::compute_len_to_next_multiple;
+
+mod circomlib;
+mod grain;
+pub use circomlib::*;
+
+/// Parameters of a Poseidon permutation with the `x^5` S-box.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PoseidonParams<F: PrimeField> {
+    full_rounds: usize,
+    partial_rounds: usize,
+    round_constants: Vec<Vec<F>>,
+    mds: Vec<Vec<F>>,
+}
+
+impl<F: PrimeField> PoseidonParams<F> {
+    /// Build the parameters of a Poseidon permutation.
+    /// * `full_rounds` - number of full rounds, half of them before and half
+    ///   of them after the partial rounds
+    /// * `partial_rounds` - number of partial rounds
+    /// * `round_constants` - one vector of `width` constants per round
+    /// * `mds` - the `width x width` MDS matrix, given row by row
+    ///
+    /// Return error if the number of full rounds is odd, if the width is
+    /// smaller than 2 or if the dimensions are inconsistent.
+    pub fn new(
+        full_rounds: usize,
+        partial_rounds: usize,
+        round_constants: Vec<Vec<F>>,
+        mds: Vec<Vec<F>>,
+    ) -> Result<Self, PlonkError> {
+        let width = mds.len();
+        if width < 2 {
+            return Err(ParameterError(format!(
+                "Poseidon width should be at least 2, got {}",
+                width
+            ))
+            .into());
+        }
+        if full_rounds % 2 != 0 {
+            return Err(ParameterError(format!(
+                "Poseidon number of full rounds should be even, got {}",
+                full_rounds
+            ))
+            .into());
+        }
+        if mds.iter().any(|row| row.len() != width) {
+            return Err(ParameterError("Poseidon MDS matrix should be square".to_string()).into());
+        }
+        if round_constants.len() != full_rounds + partial_rounds
+            || round_constants.iter().any(|rc| rc.len() != width)
+        {
+            return Err(ParameterError(format!(
+                "Poseidon needs {} vectors of {} round constants",
+                full_rounds + partial_rounds,
+                width
+            ))
+            .into());
+        }
+        Ok(Self {
+            full_rounds,
+            partial_rounds,
+            round_constants,
+            mds,
+        })
+    }
+
+    /// The width of the permutation state.
+    pub fn width(&self) -> usize {
+        self.mds.len()
+    }
+
+    /// Evaluate the permutation on `input`.
+    /// Return error if `input` does not have `width` elements.
+    pub fn eval(&self, input: &[F]) -> Result<Vec<F>, PlonkError> {
+        self.check_width(input.len())?;
+        // the state holds the input of the S-boxes, i.e., the round constants
+        // are added at the end of the previous round
+        let mut state: Vec<F> = input
+            .iter()
+            .zip(self.round_constants[0].iter())
+            .map(|(x, rc)| *x + rc)
+            .collect();
+        for round in 0..self.num_rounds() {
+            let sboxed: Vec<F> = state
+                .iter()
+                .enumerate()
+                .map(|(i, x)| {
+                    if i == 0 || self.is_full_round(round) {
+                        x.pow(&[5])
+                    } else {
+                        *x
+                    }
+                })
+                .collect();
+            state = self
+                .mds
+                .iter()
+                .zip(self.next_round_constants(round).iter())
+                .map(|(row, rc)| {
+                    row.iter()
+                        .zip(sboxed.iter())
+                        .fold(*rc, |acc, (m, x)| acc + *m * x)
+                })
+                .collect();
+        }
+        Ok(state)
+    }
+
+    fn num_rounds(&self) -> usize {
+        self.full_rounds + self.partial_rounds
+    }
+
+    fn is_full_round(&self, round: usize) -> bool {
+        round < self.full_rounds / 2 || round >= self.full_rounds / 2 + self.partial_rounds
+    }
+
+    // The constants added at the end of `round`, i.e., those of the next round
+    // or zeros after the last round.
+    fn next_round_constants(&self, round: usize) -> Vec<F> {
+        match self.round_constants.get(round + 1) {
+            Some(rc) => rc.clone(),
+            None => vec![F::zero(); self.width()],
+        }
+    }
+
+    fn check_width(&self, len: usize) -> Result<(), PlonkError> {
+        if len != self.width() {
+            return Err(ParameterError(format!(
+                "Poseidon state has {} elements, got {}",
+                self.width(),
+                len
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+////////////////////////////////////////////////////////////
+// Poseidon related gates //////////////////////////////////
+////////////////////////////////////////////////////////////
+
+/// Gate for one output element of a Poseidon round:
+/// `var_output = <q_hash, x^5> + <q_lc, x> + constant`, where the S-box inputs
+/// use `q_hash` and the other state elements use `q_lc`.
+#[derive(Debug, Clone)]
+pub(crate) struct PoseidonRoundGate<F> {
+    pub(crate) q_hash: [F; GATE_WIDTH],
+    pub(crate) q_lc: [F; GATE_WIDTH],
+    pub(crate) constant: F,
+}
+
+impl<F: PrimeField> Gate<F> for PoseidonRoundGate<F> {
+    fn name(&self) -> &'static str {
+        "Poseidon round gate"
+    }
+
+    fn q_lc(&self) -> [F; GATE_WIDTH] {
+        self.q_lc
+    }
+
+    fn q_hash(&self) -> [F; GATE_WIDTH] {
+        self.q_hash
+    }
+
+    fn q_c(&self) -> F {
+        self.constant
+    }
+
+    fn q_o(&self) -> F {
+        F::one()
+    }
+}
+
+/// Trait for Poseidon circuit over native field.
+pub trait PoseidonGadget<F: PrimeField> {
+    /// Compute the Poseidon permutation of the input state variables.
+    /// * `params` - the parameters of the permutation
+    /// * `input_vars` - variables of the input state, there should be
+    ///   `params.width()` of them
+    /// * `returns` - variables of the output state
+    fn poseidon_permutation(
+        &mut self,
+        params: &PoseidonParams<F>,
+        input_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Sponge-based hashes from Poseidon permutations, with the capacity
+    /// element at index 0 of the state and the rate elements after it.
+    /// * `params` - the parameters of the permutation
+    /// * `data_vars` - sponge input variables, `data_vars.len()` should be a
+    ///   positive integer that is a multiple of the sponge rate (i.e.
+    ///   `params.width() - 1`)
+    /// * `num_output` - number of output variables
+    /// * `returns` - a vector of variables that refers to the sponge hash
+    ///   output
+    fn poseidon_sponge_no_padding(
+        &mut self,
+        params: &PoseidonParams<F>,
+        data_vars: &[Variable],
+        num_output: usize,
+    ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Sponge-based hashes from Poseidon permutations, where the input is
+    /// padded with a one followed by zeros to a multiple of the rate.
+    /// * `params` - the parameters of the permutation
+    /// * `data_vars` - sponge input variables
+    /// * `num_output` - number of output variables
+    /// * `returns` - a vector of variables that refers to the sponge hash
+    ///   output
+    fn poseidon_sponge_with_padding(
+        &mut self,
+        params: &PoseidonParams<F>,
+        data_vars: &[Variable],
+        num_output: usize,
+    ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Fixed-length Poseidon hash as computed by circomlib: the state is the
+    /// zero capacity element followed by the inputs, and the digest is the
+    /// first element of the permuted state.
+    /// * `params` - the parameters of the permutation, of width
+    ///   `data_vars.len() + 1`
+    /// * `data_vars` - the inputs of the hash
+    /// * `returns` - the variable of the digest
+    fn poseidon_hash(
+        &mut self,
+        params: &PoseidonParams<F>,
+        data_vars: &[Variable],
+    ) -> Result<Variable, PlonkError>;
+}
+
+impl<F> PoseidonGadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn poseidon_permutation(
+        &mut self,
+        params: &PoseidonParams<F>,
+        input_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        params.check_width(input_vars.len())?;
+        self.check_vars_bound(input_vars)?;
+
+        let state_vars = input_vars
+            .iter()
+            .zip(params.round_constants[0].iter())
+            .map(|(&var, rc)| self.add_constant(var, rc))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.poseidon_rounds(params, state_vars)
+    }
+
+    fn poseidon_sponge_no_padding(
+        &mut self,
+        params: &PoseidonParams<F>,
+        data_vars: &[Variable],
+        num_output: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let rate = params.width() - 1;
+        if data_vars.is_empty() || data_vars.len() % rate != 0 {
+            return Err(ParameterError(format!(
+                "Bad input length for Poseidon sponge: {}, it must be a positive multiple of {}",
+                data_vars.len(),
+                rate
+            ))
+            .into());
+        }
+
+        // ABSORB PHASE
+        let mut state_vars = vec![self.zero(); params.width()];
+        for (i, block) in data_vars.chunks_exact(rate).enumerate() {
+            for (state_var, &data_var) in state_vars[1..].iter_mut().zip(block.iter()) {
+                // the initial state is zero
+                *state_var = if i == 0 {
+                    data_var
+                } else {
+                    self.add(*state_var, data_var)?
+                };
+            }
+            state_vars = self.poseidon_permutation(params, &state_vars)?;
+        }
+
+        // SQUEEZE PHASE
+        let mut result = vec![];
+        let mut remaining = num_output;
+        loop {
+            let extract = remaining.min(rate);
+            result.extend_from_slice(&state_vars[1..1 + extract]);
+            remaining -= extract;
+            if remaining == 0 {
+                break;
+            }
+            state_vars = self.poseidon_permutation(params, &state_vars)?;
+        }
+        Ok(result)
+    }
+
+    fn poseidon_sponge_with_padding(
+        &mut self,
+        params: &PoseidonParams<F>,
+        data_vars: &[Variable],
+        num_output: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let rate = params.width() - 1;
+        let data_len = compute_len_to_next_multiple(data_vars.len() + 1, rate);
+        let data_vars = [
+            data_vars,
+            &[self.one()],
+            vec![self.zero(); data_len - data_vars.len() - 1].as_ref(),
+        ]
+        .concat();
+        self.poseidon_sponge_no_padding(params, &data_vars, num_output)
+    }
+
+    fn poseidon_hash(
+        &mut self,
+        params: &PoseidonParams<F>,
+        data_vars: &[Variable],
+    ) -> Result<Variable, PlonkError> {
+        let state_vars = [&[self.zero()], data_vars].concat();
+        Ok(self.poseidon_permutation(params, &state_vars)?[0])
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Add `block_vars` to the rate part of the state `state_vars`, i.e., to
+    /// the elements after the capacity element at index 0, and apply the
+    /// Poseidon permutation, as in a duplex sponge absorption. The addition of
+    /// the block is merged with the addition of the first round constants,
+    /// hence costs no constraint on top of the permutation.
+    pub(crate) fn poseidon_absorb_block(
+        &mut self,
+        params: &PoseidonParams<F>,
+        state_vars: &[Variable],
+        block_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        params.check_width(state_vars.len())?;
+        params.check_width(block_vars.len() + 1)?;
+        self.check_vars_bound(state_vars)?;
+        self.check_vars_bound(block_vars)?;
+
+        let zero = self.zero();
+        let rc = &params.round_constants[0];
+        let mut keyed_vars = vec![self.add_constant(state_vars[0], &rc[0])?];
+        for ((&var, &block_var), rc) in state_vars[1..]
+            .iter()
+            .zip(block_vars.iter())
+            .zip(rc[1..].iter())
+        {
+            // state[i] + block[i] + rc[i] in a single gate
+            keyed_vars.push(self.gen_quad_poly(
+                &[var, block_var, zero, zero],
+                &[F::one(), F::one(), F::zero(), F::zero()],
+                &[F::zero(), F::zero()],
+                *rc,
+            )?);
+        }
+        self.poseidon_rounds(params, keyed_vars)
+    }
+
+    // The rounds of the Poseidon permutation on the state variables
+    // `state_vars`, to which the first round constants have been added.
+    fn poseidon_rounds(
+        &mut self,
+        params: &PoseidonParams<F>,
+        mut state_vars: Vec<Variable>,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let width = params.width();
+        for round in 0..params.num_rounds() {
+            let is_full_round = params.is_full_round(round);
+            let input_vals = state_vars
+                .iter()
+                .map(|&var| self.witness(var))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+
+            let mut next_state_vars = Vec::with_capacity(width);
+            for (row, rc) in params
+                .mds
+                .iter()
+                .zip(params.next_round_constants(round).into_iter())
+            {
+                // the first gate takes up to `GATE_WIDTH` state elements, and
+                // each next gate adds up to `GATE_WIDTH - 1` of them to the
+                // partial sum output by the previous gate
+                let mut partial_sum: Option<(Variable, F)> = None;
+                let mut start = 0;
+                while start < width {
+                    let mut wires = [self.zero(); GATE_WIDTH];
+                    let mut q_hash = [F::zero(); GATE_WIDTH];
+                    let mut q_lc = [F::zero(); GATE_WIDTH];
+                    let (mut output_val, constant, offset) = match partial_sum {
+                        None => (rc, rc, 0),
+                        Some((var, val)) => {
+                            wires[0] = var;
+                            q_lc[0] = F::one();
+                            (val, F::zero(), 1)
+                        },
+                    };
+                    let end = width.min(start + GATE_WIDTH - offset);
+                    for i in start..end {
+                        let k = i - start + offset;
+                        wires[k] = state_vars[i];
+                        if i == 0 || is_full_round {
+                            q_hash[k] = row[i];
+                            output_val += row[i] * input_vals[i].pow(&[5]);
+                        } else {
+                            q_lc[k] = row[i];
+                            output_val += row[i] * input_vals[i];
+                        }
+                    }
+                    let output_var = self.create_variable(output_val)?;
+                    let wire_vars = [wires[0], wires[1], wires[2], wires[3], output_var];
+                    self.insert_gate(
+                        &wire_vars,
+                        Box::new(PoseidonRoundGate {
+                            q_hash,
+                            q_lc,
+                            constant,
+                        }),
+                    )?;
+                    partial_sum = Some((output_var, output_val));
+                    start = end;
+                }
+                // safe unwrap: the width is at least 2
+                next_state_vars.push(partial_sum.unwrap().0);
+            }
+            state_vars = next_state_vars;
+        }
+        Ok(state_vars)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{test_rng, UniformRand};
+
+    fn random_params<F: PrimeField>(width: usize) -> PoseidonParams<F> {
+        let mut rng = test_rng();
+        let (full_rounds, partial_rounds) = (8, 57);
+        let round_constants = (0..full_rounds + partial_rounds)
+            .map(|_| (0..width).map(|_| F::rand(&mut rng)).collect())
+            .collect();
+        let mds = (0..width)
+            .map(|_| (0..width).map(|_| F::rand(&mut rng)).collect())
+            .collect();
+        PoseidonParams::new(full_rounds, partial_rounds, round_constants, mds).unwrap()
+    }
+
+    #[test]
+    fn test_poseidon_params() {
+        test_poseidon_params_helper::<FqEd254>();
+        test_poseidon_params_helper::<FqEd381>();
+    }
+
+    fn test_poseidon_params_helper<F: PrimeField>() {
+        let params = random_params::<F>(3);
+        let rc = params.round_constants.clone();
+        let mds = params.mds.clone();
+        assert!(PoseidonParams::new(8, 57, rc.clone(), mds.clone()).is_ok());
+        // odd number of full rounds
+        assert!(PoseidonParams::new(7, 58, rc.clone(), mds.clone()).is_err());
+        // wrong number of rounds
+        assert!(PoseidonParams::new(8, 56, rc.clone(), mds.clone()).is_err());
+        // non square matrix
+        let mut bad_mds = mds.clone();
+        bad_mds[1].pop();
+        assert!(PoseidonParams::new(8, 57, rc.clone(), bad_mds).is_err());
+        // width too small
+        assert!(PoseidonParams::new(8, 57, rc, vec![vec![F::one()]]).is_err());
+        assert!(params.eval(&[F::one(); 4]).is_err());
+    }
+
+    #[test]
+    fn test_poseidon_permutation() -> Result<(), PlonkError> {
+        test_poseidon_permutation_helper::<FqEd254>()?;
+        test_poseidon_permutation_helper::<FqEd377>()?;
+        test_poseidon_permutation_helper::<FqEd381>()?;
+        test_poseidon_permutation_helper::<Fq377>()
+    }
+
+    fn test_poseidon_permutation_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        for width in 2..=GATE_WIDTH + 3 {
+            let params = random_params::<F>(width);
+            let input: Vec<F> = (0..width).map(|_| F::rand(&mut rng)).collect();
+            let expected = params.eval(&input)?;
+
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let input_vars = input
+                .iter()
+                .map(|x| circuit.create_variable(*x))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let num_gates = circuit.num_gates();
+            let output_vars = circuit.poseidon_permutation(&params, &input_vars)?;
+            for (var, val) in output_vars.iter().zip(expected.iter()) {
+                assert_eq!(circuit.witness(*var)?, *val);
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            // one gate per element for adding the first round constants, and
+            // one per element and round for narrow states
+            let gates_per_element = 1 + (width - 2) / (GATE_WIDTH - 1);
+            assert_eq!(
+                circuit.num_gates() - num_gates,
+                width * (1 + gates_per_element * (8 + 57))
+            );
+            if width <= GATE_WIDTH {
+                assert_eq!(gates_per_element, 1);
+            }
+
+            *circuit.witness_mut(output_vars[0]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // wrong number of inputs
+            assert!(circuit
+                .poseidon_permutation(&params, &input_vars[1..])
+                .is_err());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_poseidon_sponge_and_hash() -> Result<(), PlonkError> {
+        test_poseidon_sponge_and_hash_helper::<FqEd254>()?;
+        test_poseidon_sponge_and_hash_helper::<FqEd377>()?;
+        test_poseidon_sponge_and_hash_helper::<FqEd381>()
+    }
+
+    fn test_poseidon_sponge_and_hash_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let params = random_params::<F>(3);
+        let data: Vec<F> = (0..5).map(|_| F::rand(&mut rng)).collect();
+
+        // native sponge with padding, capacity at index 0
+        let mut padded = data.clone();
+        padded.push(F::one());
+        let mut state = vec![F::zero(); 3];
+        for block in padded.chunks(2) {
+            state[1] += block[0];
+            state[2] += block[1];
+            state = params.eval(&state)?;
+        }
+        let mut expected = vec![state[1], state[2]];
+        state = params.eval(&state)?;
+        expected.push(state[1]);
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let data_vars = data
+            .iter()
+            .map(|x| circuit.create_variable(*x))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let output_vars = circuit.poseidon_sponge_with_padding(&params, &data_vars, 3)?;
+        assert_eq!(output_vars.len(), 3);
+        for (var, val) in output_vars.iter().zip(expected.iter()) {
+            assert_eq!(circuit.witness(*var)?, *val);
+        }
+        assert!(circuit
+            .poseidon_sponge_no_padding(&params, &data_vars, 1)
+            .is_err());
+        assert!(circuit.poseidon_sponge_no_padding(&params, &[], 1).is_err());
+
+        // circomlib style hash of two elements
+        let digest_var = circuit.poseidon_hash(&params, &data_vars[..2])?;
+        let expected = params.eval(&[F::zero(), data[0], data[1]])?[0];
+        assert_eq!(circuit.witness(digest_var)?, expected);
+        assert!(circuit.poseidon_hash(&params, &data_vars[..3]).is_err());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+}
@@ -10,5 +10,6 @@
 mod native;
 mod non_native;
 
-pub use native::{RescueGadget, RescueStateVar};
+pub(crate) use native::RescueHelperGadget;
+pub use native::{RescueGadget, RescuePermutationGadget, RescueSpongeGadget, RescueStateVar};
 pub use non_native::{RescueNonNativeGadget, RescueNonNativeStateVar};
@@ -7,16 +7,15 @@
 use crate::{
     circuit::{
         gates::{FifthRootGate, Gate},
-        Circuit, PlonkCircuit, Variable,
+        Circuit, CircuitGadget, PlonkCircuit, Variable,
     },
     constants::GATE_WIDTH,
     errors::{CircuitError::ParameterError, PlonkError},
 };
 use ark_ff::PrimeField;
 use ark_std::{boxed::Box, format, string::ToString, vec, vec::Vec};
-use itertools::Itertools;
 use jf_rescue::{
-    Permutation, RescueMatrix, RescueParameter, RescueVector, PRP, ROUNDS, STATE_SIZE,
+    Permutation, RescueMatrix, RescueParameter, RescueVector, PRP, RATE, ROUNDS, STATE_SIZE,
 };
 use jf_utils::compute_len_to_next_multiple;
 
@@ -196,14 +195,7 @@ where
         input_var: RescueStateVar,
     ) -> Result<RescueStateVar, PlonkError> {
         let permutation = Permutation::default();
-        let keys = permutation.round_keys_ref();
-        let keys = keys
-            .iter()
-            .map(|key| RescueVector::from(key.elems().as_slice()))
-            .collect_vec();
-        let mds_matrix = permutation.mds_matrix_ref();
-
-        self.permutation_with_const_round_keys(input_var, mds_matrix, keys.as_slice())
+        self.permutation_with_instance(&permutation, input_var)
     }
 
     fn prp(
@@ -227,18 +219,20 @@ where
         }
         let zero_var = self.zero();
         let rate = STATE_SIZE - 1;
+        // materialize the round keys once for all the permutations of this sponge
+        let permutation = Permutation::default();
 
         // ABSORB PHASE
         let mut state_var =
             RescueStateVar::from([data_vars[0], data_vars[1], data_vars[2], zero_var]);
-        state_var = self.rescue_permutation(state_var)?;
+        state_var = self.permutation_with_instance(&permutation, state_var)?;
 
         for block in data_vars[rate..].chunks_exact(rate) {
             state_var = self.add_state(
                 &state_var,
                 &RescueStateVar::from([block[0], block[1], block[2], zero_var]),
             )?;
-            state_var = self.rescue_permutation(state_var)?;
+            state_var = self.permutation_with_instance(&permutation, state_var)?;
         }
 
         // SQUEEZE PHASE
@@ -252,7 +246,7 @@ where
             if remaining == 0 {
                 break;
             }
-            state_var = self.rescue_permutation(state_var)?;
+            state_var = self.permutation_with_instance(&permutation, state_var)?;
         }
 
         Ok(result)
@@ -292,6 +286,7 @@ where
             ))
             .into());
         }
+        let permutation = Permutation::default();
         // set key
         let mut state = RescueStateVar::from([self.zero(), self.zero(), self.zero(), key]);
         // absorb phase
@@ -299,7 +294,7 @@ where
         for chunk in chunks {
             let chunk_var = RescueStateVar::from([chunk[0], chunk[1], chunk[2], chunk[3]]);
             state = self.add_state(&state, &chunk_var)?;
-            state = self.rescue_permutation(state)?;
+            state = self.permutation_with_instance(&permutation, state)?;
         }
         // squeeze phase, but only a single output, can return directly from state
         Ok(state.0[0])
@@ -376,6 +371,76 @@ where
     }
 }
 
+#[derive(Debug, Clone, Copy, Default)]
+/// The Rescue permutation as a [`CircuitGadget`], c.f.
+/// `RescueGadget::rescue_permutation`.
+pub struct RescuePermutationGadget;
+
+impl<F> CircuitGadget<F> for RescuePermutationGadget
+where
+    F: RescueParameter,
+{
+    type Input = RescueStateVar;
+    type Output = RescueStateVar;
+
+    fn name(&self) -> &'static str {
+        "rescue_permutation"
+    }
+
+    fn synthesize(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        input: RescueStateVar,
+    ) -> Result<RescueStateVar, PlonkError> {
+        circuit.rescue_permutation(input)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The Rescue sponge hash as a [`CircuitGadget`] over the data variables,
+/// c.f. `RescueGadget::rescue_sponge_with_padding` and
+/// `RescueGadget::rescue_sponge_no_padding`.
+pub struct RescueSpongeGadget {
+    num_output: usize,
+    padding: bool,
+}
+
+impl RescueSpongeGadget {
+    /// A sponge with `num_output` outputs, which pads the data if `padding`
+    /// is set, or expects a positive multiple of the rate of data variables
+    /// otherwise.
+    pub fn new(num_output: usize, padding: bool) -> Self {
+        Self {
+            num_output,
+            padding,
+        }
+    }
+}
+
+impl<F> CircuitGadget<F> for RescueSpongeGadget
+where
+    F: RescueParameter,
+{
+    type Input = Vec<Variable>;
+    type Output = Vec<Variable>;
+
+    fn name(&self) -> &'static str {
+        "rescue_sponge"
+    }
+
+    fn synthesize(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        data_vars: Vec<Variable>,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        if self.padding {
+            circuit.rescue_sponge_with_padding(&data_vars, self.num_output)
+        } else {
+            circuit.rescue_sponge_no_padding(&data_vars, self.num_output)
+        }
+    }
+}
+
 pub(crate) trait RescueHelperGadget<F: PrimeField>: Circuit<F> {
     fn check_var_bound_rescue_state(&self, rescue_state: &RescueStateVar)
         -> Result<(), PlonkError>;
@@ -436,6 +501,36 @@ pub(crate) trait RescueHelperGadget<F: PrimeField>: Circuit<F> {
     /// * `returns` - the variable id corresponding to y
     fn pow_alpha_inv(&mut self, input_var: Variable) -> Result<Variable, PlonkError>;
 
+    /// Same as `RescueGadget::rescue_permutation`, but reuses the round keys
+    /// and the MDS matrix of an existing `permutation` instance instead of
+    /// re-materializing them from their byte encoding. Note that the round
+    /// keys are embedded as gate selectors, hence no constant variable is
+    /// created for them.
+    /// * `permutation` - rescue permutation instance
+    /// * `input_var` - variables corresponding to the input state
+    /// * `returns` - variables corresponding to the output state
+    fn permutation_with_instance(
+        &mut self,
+        permutation: &Permutation<F>,
+        input_var: RescueStateVar,
+    ) -> Result<RescueStateVar, PlonkError>;
+
+    /// Add the `block` variables to the rate part of the state `state_var` and
+    /// apply the rescue permutation with the round keys and the MDS matrix of
+    /// an existing `permutation` instance, as in a duplex sponge absorption.
+    /// The addition of the block is merged with the addition of the first
+    /// round key, hence costs no constraint on top of the permutation.
+    /// * `permutation` - rescue permutation instance
+    /// * `state_var` - variables corresponding to the state before absorption
+    /// * `block` - variables added to the rate part of the state
+    /// * `returns` - variables corresponding to the output state
+    fn absorb_block_with_instance(
+        &mut self,
+        permutation: &Permutation<F>,
+        state_var: &RescueStateVar,
+        block: &[Variable; RATE],
+    ) -> Result<RescueStateVar, PlonkError>;
+
     /// Given an input state st_0 and an output state st_1, ensure that st_1 is
     /// obtained by applying the rescue permutation with a specific  list of
     /// round keys (i.e. the keys are constants) and a matrix
@@ -455,8 +550,24 @@ pub(crate) trait RescueHelperGadget<F: PrimeField>: Circuit<F> {
             )));
         }
 
-        let mut state_var = self.add_constant_state(&input_var, &round_keys[0])?;
-        for (r, key) in round_keys.iter().skip(1).enumerate() {
+        let state_var = self.add_constant_state(&input_var, &round_keys[0])?;
+        self.rounds_with_const_round_keys(state_var, mds, &round_keys[1..])
+    }
+
+    /// Apply the rounds of the rescue permutation that follow the addition of
+    /// the first round key, c.f. `permutation_with_const_round_keys`.
+    /// * `state_var` - variables corresponding to the input state, to which
+    ///   the first round key has been added
+    /// * `mds` - Rescue matrix
+    /// * `round_keys` - list of round keys but the first one
+    /// * `returns` - variables corresponding to the output state
+    fn rounds_with_const_round_keys(
+        &mut self,
+        mut state_var: RescueStateVar,
+        mds: &RescueMatrix<F>,
+        round_keys: &[RescueVector<F>],
+    ) -> Result<RescueStateVar, PlonkError> {
+        for (r, key) in round_keys.iter().enumerate() {
             if r % 2 == 0 {
                 state_var = self.pow_alpha_inv_state(&state_var)?;
                 state_var = self.affine_transform(&state_var, mds, key)?;
@@ -482,6 +593,51 @@ where
         Ok(())
     }
 
+    fn permutation_with_instance(
+        &mut self,
+        permutation: &Permutation<F>,
+        input_var: RescueStateVar,
+    ) -> Result<RescueStateVar, PlonkError> {
+        self.permutation_with_const_round_keys(
+            input_var,
+            permutation.mds_matrix_ref(),
+            permutation.round_keys_ref(),
+        )
+    }
+
+    fn absorb_block_with_instance(
+        &mut self,
+        permutation: &Permutation<F>,
+        state_var: &RescueStateVar,
+        block: &[Variable; RATE],
+    ) -> Result<RescueStateVar, PlonkError> {
+        self.check_var_bound_rescue_state(state_var)?;
+        self.check_vars_bound(block)?;
+
+        let round_keys = permutation.round_keys_ref();
+        let first_key = round_keys[0].elems();
+        let zero = self.zero();
+        let mut keyed_vars = [Variable::default(); STATE_SIZE];
+        for (i, keyed_var) in keyed_vars.iter_mut().enumerate() {
+            *keyed_var = if i < RATE {
+                // state[i] + block[i] + key[i] in a single gate
+                self.gen_quad_poly(
+                    &[state_var.0[i], block[i], zero, zero],
+                    &[F::one(), F::one(), F::zero(), F::zero()],
+                    &[F::zero(), F::zero()],
+                    first_key[i],
+                )?
+            } else {
+                self.add_constant(state_var.0[i], &first_key[i])?
+            };
+        }
+        self.rounds_with_const_round_keys(
+            RescueStateVar::from(keyed_vars),
+            permutation.mds_matrix_ref(),
+            &round_keys[1..],
+        )
+    }
+
     fn add_constant_state(
         &mut self,
         input_var: &RescueStateVar,
@@ -852,6 +1008,54 @@ mod tests {
         check_circuit_satisfiability(&mut circuit, state_out.elems(), out_var);
     }
 
+    #[test]
+    fn test_absorb_block() {
+        test_absorb_block_helper::<FqEd254>();
+        test_absorb_block_helper::<FqEd377>();
+        test_absorb_block_helper::<FqEd381>();
+    }
+    fn test_absorb_block_helper<F: RescueParameter>() {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+
+        let state_in =
+            RescueVector::from(&[F::from(1u32), F::from(2u32), F::from(3u32), F::from(4u32)]);
+        let block = [F::from(5u32), F::from(6u32), F::from(7u32)];
+
+        let state_in_var = circuit.create_rescue_state_variable(&state_in).unwrap();
+        let block_var = [
+            circuit.create_variable(block[0]).unwrap(),
+            circuit.create_variable(block[1]).unwrap(),
+            circuit.create_variable(block[2]).unwrap(),
+        ];
+
+        let perm = Permutation::default();
+        let mut absorbed = state_in.elems();
+        for (s, b) in absorbed.iter_mut().zip(block.iter()) {
+            *s += b;
+        }
+        let state_out = perm.eval(&RescueVector::from(absorbed.as_slice()));
+
+        // the block is absorbed at no cost on top of the permutation
+        let num_gates = circuit.num_gates();
+        let out_var = circuit
+            .absorb_block_with_instance(&perm, &state_in_var, &block_var)
+            .unwrap();
+        let absorb_gates = circuit.num_gates() - num_gates;
+        let mut perm_circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let perm_in_var = perm_circuit
+            .create_rescue_state_variable(&state_in)
+            .unwrap();
+        perm_circuit.rescue_permutation(perm_in_var).unwrap();
+        assert_eq!(absorb_gates, perm_circuit.num_gates());
+
+        check_state(&circuit, &out_var, &state_out);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // Alter the block
+        *circuit.witness_mut(block_var[1]) = F::from(0_u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+    }
+
     #[test]
     fn test_add_state() {
         test_add_state_helper::<FqEd254>();
@@ -655,7 +655,8 @@ where
         state: &RescueVector<T>,
     ) -> Result<RescueNonNativeStateVar<F>, PlonkError> {
         // parameter m
-        let m = (T::size_in_bits() / 2 / self.range_bit_len()? + 1) * self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
+        let m = (T::size_in_bits() / 2 / range_bit_len + 1) * range_bit_len;
 
         // move the modulus to the right field
         let t_modulus = F::from_le_bytes_mod_order(T::Params::MODULUS.to_bytes_le().as_ref());
@@ -0,0 +1,547 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the decoding of RLP, the serialization of
+//! Ethereum, e.g. for proofs about transactions, receipts or state.
+//!
+//! An encoding is given as a fixed number of byte variables, i.e., variables
+//! whose values are in `[0, 256)`, of which it occupies a prefix, so that the
+//! lengths of the items are only known to the prover. The header of an item
+//! is read at a secret position with a one-hot selection of the bytes, which
+//! takes a number of gates linear in the number of bytes. The items of a list
+//! are decoded one after the other, and have to exactly fill the payload of
+//! the list; nested items are decoded from their extracted encodings.
+//!
+//! The encodings are limited to `2^16 - 1` bytes, i.e., the lengths of the
+//! long items are encoded with one or two bytes. The gadgets reject long
+//! lengths smaller than 56, but do not check the other canonicity rules,
+//! e.g. a single byte smaller than `0x80` may be encoded as a string.
+
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+
+/// The bit length of the lengths and positions of the RLP gadgets.
+const RLP_LEN_BIT_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Variables of a decoded RLP item.
+pub struct RlpItemVar {
+    // whether the item exists, e.g. for the slots of a list after its items
+    pub(crate) present: Variable,
+    // the position of the header of the item
+    pub(crate) start: Variable,
+    // whether the item is a list
+    pub(crate) is_list: Variable,
+    // the position of the payload of the item
+    pub(crate) offset: Variable,
+    // the length of the payload of the item
+    pub(crate) len: Variable,
+}
+
+impl RlpItemVar {
+    /// The bool variable indicating whether the item exists. The other
+    /// variables are meaningless if it does not.
+    pub fn present(&self) -> Variable {
+        self.present
+    }
+
+    /// The variable of the position of the header of the item in the
+    /// encoding.
+    pub fn start(&self) -> Variable {
+        self.start
+    }
+
+    /// The bool variable indicating whether the item is a list.
+    pub fn is_list(&self) -> Variable {
+        self.is_list
+    }
+
+    /// The variable of the position of the payload of the item in the
+    /// encoding.
+    pub fn payload_offset(&self) -> Variable {
+        self.offset
+    }
+
+    /// The variable of the length of the payload of the item.
+    pub fn payload_len(&self) -> Variable {
+        self.len
+    }
+}
+
+/// Trait for the RLP decoding gadgets.
+pub trait RlpGadget<F: PrimeField> {
+    /// Decode the header of the RLP item encoded at the start of `bytes`,
+    /// which has to fit in `bytes`.
+    /// * `bytes` - the bytes of the encoding followed by arbitrary bytes,
+    ///   which are expected to be constrained to be in `[0, 256)`
+    /// * `returns` - the decoded item
+    fn rlp_decode(&mut self, bytes: &[Variable]) -> Result<RlpItemVar, PlonkError>;
+
+    /// Decode the RLP list encoded at the start of `bytes`, which has to fit
+    /// in `bytes`, and the headers of its items.
+    /// * `bytes` - the bytes of the encoding followed by arbitrary bytes,
+    ///   which are expected to be constrained to be in `[0, 256)`
+    /// * `max_items` - the maximal number of items of the list; the circuit
+    ///   is not satisfied if the list has more items
+    /// * `returns` - the decoded list, and `max_items` slots for its items,
+    ///   the first ones of which are present
+    fn rlp_decode_list(
+        &mut self,
+        bytes: &[Variable],
+        max_items: usize,
+    ) -> Result<(RlpItemVar, Vec<RlpItemVar>), PlonkError>;
+
+    /// Extract the payload of a decoded item, e.g. the bytes of a string.
+    /// * `bytes` - the bytes from which the item is decoded
+    /// * `item` - the decoded item
+    /// * `max_len` - the maximal length of the payload; the circuit is not
+    ///   satisfied if the payload is longer
+    /// * `returns` - the `max_len` bytes of the payload padded with zeros
+    fn rlp_payload(
+        &mut self,
+        bytes: &[Variable],
+        item: &RlpItemVar,
+        max_len: usize,
+    ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Extract the encoding of a decoded item, header included, e.g. to
+    /// decode the items of a nested list.
+    /// * `bytes` - the bytes from which the item is decoded
+    /// * `item` - the decoded item
+    /// * `max_len` - the maximal length of the encoding; the circuit is not
+    ///   satisfied if the encoding is longer
+    /// * `returns` - the `max_len` bytes of the encoding padded with zeros
+    fn rlp_encoding(
+        &mut self,
+        bytes: &[Variable],
+        item: &RlpItemVar,
+        max_len: usize,
+    ) -> Result<Vec<Variable>, PlonkError>;
+}
+
+impl<F> RlpGadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn rlp_decode(&mut self, bytes: &[Variable]) -> Result<RlpItemVar, PlonkError> {
+        self.check_rlp_bytes(bytes)?;
+        let (zero, one) = (self.zero(), self.one());
+        let item = self.rlp_decode_header(bytes, zero, one)?;
+        // offset + len <= bytes.len()
+        let end = self.add(item.offset, item.len)?;
+        let gap = self.gen_quad_poly(
+            &[end, zero, zero, zero],
+            &[-F::one(), F::zero(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            F::from(bytes.len() as u64),
+        )?;
+        self.range_gate(gap, RLP_LEN_BIT_LEN)?;
+        Ok(item)
+    }
+
+    fn rlp_decode_list(
+        &mut self,
+        bytes: &[Variable],
+        max_items: usize,
+    ) -> Result<(RlpItemVar, Vec<RlpItemVar>), PlonkError> {
+        let list = self.rlp_decode(bytes)?;
+        self.enforce_true(list.is_list)?;
+        let end = self.add(list.offset, list.len)?;
+
+        let zero = self.zero();
+        let n = F::from(bytes.len() as u64);
+        let mut cursor = list.offset;
+        let mut items = Vec::with_capacity(max_items);
+        for _ in 0..max_items {
+            let present: Variable = self.is_lt(cursor, end, RLP_LEN_BIT_LEN)?.into();
+            // the header of a missing item is read out of range, i.e. at
+            // position present * (cursor - n) + n
+            let start = self.gen_quad_poly(
+                &[present, cursor, zero, zero],
+                &[-n, F::zero(), F::zero(), F::zero()],
+                &[F::one(), F::zero()],
+                n,
+            )?;
+            let item = self.rlp_decode_header(bytes, start, present)?;
+            // the item ends within the list if present
+            let item_end = self.add(item.offset, item.len)?;
+            let gap = self.gen_quad_poly(
+                &[present, end, present, item_end],
+                &[F::zero(); 4],
+                &[F::one(), -F::one()],
+                F::zero(),
+            )?;
+            self.range_gate(gap, RLP_LEN_BIT_LEN)?;
+            // the next item starts at the end of the item if present
+            cursor = self.gen_quad_poly(
+                &[present, item_end, present, cursor],
+                &[F::zero(), F::zero(), F::zero(), F::one()],
+                &[F::one(), -F::one()],
+                F::zero(),
+            )?;
+            items.push(item);
+        }
+        // all the items are decoded
+        self.equal_gate(cursor, end)?;
+        Ok((list, items))
+    }
+
+    fn rlp_payload(
+        &mut self,
+        bytes: &[Variable],
+        item: &RlpItemVar,
+        max_len: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        self.check_rlp_bytes(bytes)?;
+        self.rlp_slice(bytes, item.offset, item.len, max_len)
+    }
+
+    fn rlp_encoding(
+        &mut self,
+        bytes: &[Variable],
+        item: &RlpItemVar,
+        max_len: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        self.check_rlp_bytes(bytes)?;
+        // offset + len - start
+        let len = self.gen_quad_poly(
+            &[item.offset, item.len, item.start, self.zero()],
+            &[F::one(), F::one(), -F::one(), F::zero()],
+            &[F::zero(), F::zero()],
+            F::zero(),
+        )?;
+        self.rlp_slice(bytes, item.start, len, max_len)
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn check_rlp_bytes(&self, bytes: &[Variable]) -> Result<(), PlonkError> {
+        if bytes.is_empty() || bytes.len() >= 1 << RLP_LEN_BIT_LEN {
+            return Err(ParameterError(format!(
+                "RLP encodings take between 1 and {} bytes, got {}",
+                (1 << RLP_LEN_BIT_LEN) - 1,
+                bytes.len()
+            ))
+            .into());
+        }
+        self.check_vars_bound(bytes)
+    }
+
+    // Decode the header of the item at position `start` of `bytes`, which has
+    // to be in range iff `present` is set.
+    fn rlp_decode_header(
+        &mut self,
+        bytes: &[Variable],
+        start: Variable,
+        present: Variable,
+    ) -> Result<RlpItemVar, PlonkError> {
+        let zero = self.zero();
+        let selector = self.one_hot(start, bytes.len())?;
+        let selector_sum = self.sum(&selector)?;
+        self.equal_gate(selector_sum, present)?;
+        let prefix = self.one_hot_select(&selector, bytes, 0)?;
+        // a prefix is in [0, 0x80) for a single byte, in [0x80, 0xc0) for a
+        // string and in [0xc0, 0x100) for a list, whose low 6 bits are at least
+        // 0x38 for a long item
+        let bool_bits = self.unpack_bool(prefix, 8)?;
+        let bits: Vec<Variable> = bool_bits.iter().map(|&bit| bit.into()).collect();
+        let single: Variable = self.logic_neg(bool_bits[7])?.into();
+        let is_list = self.mul(bits[7], bits[6])?;
+        let long = self.mul(bits[5], bits[4])?;
+        let long = self.mul(long, bits[3])?;
+        let long = self.mul(long, bits[7])?;
+        let short_len = self.pack_bits(&bool_bits[..6])?;
+        // the length of a long item takes 1 + bits[0] bytes
+        self.mul_gate(long, bits[1], zero)?;
+        self.mul_gate(long, bits[2], zero)?;
+        let len_byte0 = self.one_hot_select(&selector, bytes, 1)?;
+        let len_byte1 = self.one_hot_select(&selector, bytes, 2)?;
+        self.range_gate(len_byte0, 8)?;
+        self.range_gate(len_byte1, 8)?;
+        // long_len = len_byte0 + bits[0] * (255 * len_byte0 + len_byte1)
+        let two_bytes = self.lc(
+            &[len_byte0, len_byte1, zero, zero],
+            &[F::from(255u8), F::one(), F::zero(), F::zero()],
+        )?;
+        let long_len = self.gen_quad_poly(
+            &[bits[0], two_bytes, len_byte0, zero],
+            &[F::zero(), F::zero(), F::one(), F::zero()],
+            &[F::one(), F::zero()],
+            F::zero(),
+        )?;
+        // long * (long_len - 56) is in range, i.e. long lengths are at least 56
+        let long_gap = self.gen_quad_poly(
+            &[long, long_len, zero, zero],
+            &[-F::from(56u8), F::zero(), F::zero(), F::zero()],
+            &[F::one(), F::zero()],
+            F::zero(),
+        )?;
+        self.range_gate(long_gap, RLP_LEN_BIT_LEN)?;
+
+        // len = single + (bits[7] - long) * short_len + long * long_len
+        let short_part = self.gen_quad_poly(
+            &[bits[7], short_len, long, short_len],
+            &[F::zero(); 4],
+            &[F::one(), -F::one()],
+            F::zero(),
+        )?;
+        let len = self.gen_quad_poly(
+            &[long, long_len, short_part, single],
+            &[F::zero(), F::zero(), F::one(), F::one()],
+            &[F::one(), F::zero()],
+            F::zero(),
+        )?;
+        // offset = start + bits[7] + long * (1 + bits[0])
+        let offset = self.gen_quad_poly(
+            &[start, bits[7], long, bits[0]],
+            &[F::one(), F::one(), F::one(), F::zero()],
+            &[F::zero(), F::one()],
+            F::zero(),
+        )?;
+        Ok(RlpItemVar {
+            present,
+            start,
+            is_list,
+            offset,
+            len,
+        })
+    }
+
+    // Obtain the `max_len` bytes of `bytes` from position `from`, the bytes
+    // from position `from + len` being replaced by zeros; the circuit is not
+    // satisfied if `len > max_len`.
+    pub(crate) fn rlp_slice(
+        &mut self,
+        bytes: &[Variable],
+        from: Variable,
+        len: Variable,
+        max_len: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        self.check_var_bound(from)?;
+        self.check_var_bound(len)?;
+        let selector = self.one_hot(from, bytes.len())?;
+        let len_selector = self.one_hot(len, max_len + 1)?;
+        let mask = self.prefix_mask(&len_selector)?;
+        mask.into_iter()
+            .enumerate()
+            .map(|(i, in_len)| {
+                let byte = self.one_hot_select(&selector, bytes, i)?;
+                self.mul(in_len, byte)
+            })
+            .collect()
+    }
+
+    // The bool variables [a == j] for j in [0, n).
+    pub(crate) fn one_hot(&mut self, a: Variable, n: usize) -> Result<Vec<Variable>, PlonkError> {
+        (0..n)
+            .map(|j| {
+                let diff = self.add_constant(a, &-F::from(j as u64))?;
+                self.is_zero(diff).map(Variable::from)
+            })
+            .collect()
+    }
+
+    // The bool variables [i < a] for i in [0, n), given the one-hot selector
+    // of `a` over [0, n], i.e. `a` is constrained to be at most n.
+    pub(crate) fn prefix_mask(
+        &mut self,
+        selector: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let selector_sum = self.sum(selector)?;
+        self.enforce_true(selector_sum)?;
+        // [i < a] = 1 - sum_{k <= i} [a == k]
+        let n = selector.len().saturating_sub(1);
+        let mut reached = self.zero();
+        let mut mask = Vec::with_capacity(n);
+        for &is_a in selector.iter().take(n) {
+            reached = self.add(reached, is_a)?;
+            mask.push(self.logic_neg(BoolVar::new_unchecked(reached))?.into());
+        }
+        Ok(mask)
+    }
+
+    // sum_j selector[j] * vars[j + shift], i.e. the variable at position
+    // `a + shift` for the one-hot selector of `a`, or zero out of range.
+    pub(crate) fn one_hot_select(
+        &mut self,
+        selector: &[Variable],
+        vars: &[Variable],
+        shift: usize,
+    ) -> Result<Variable, PlonkError> {
+        let zero = self.zero();
+        let terms: Vec<(Variable, Variable)> = selector
+            .iter()
+            .copied()
+            .zip(vars.iter().skip(shift).copied())
+            .collect();
+        if terms.is_empty() {
+            return Ok(zero);
+        }
+        let partials = terms
+            .chunks(2)
+            .map(|pair| {
+                let (a, b) = pair[0];
+                let (c, d) = pair.get(1).copied().unwrap_or((zero, zero));
+                self.gen_quad_poly(
+                    &[a, b, c, d],
+                    &[F::zero(); 4],
+                    &[F::one(), F::one()],
+                    F::zero(),
+                )
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.sum(&partials)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::vec;
+
+    fn encode_header(len: usize, base: u8) -> Vec<u8> {
+        if len < 56 {
+            vec![base + len as u8]
+        } else if len < 256 {
+            vec![base + 56, len as u8]
+        } else {
+            vec![base + 57, (len >> 8) as u8, len as u8]
+        }
+    }
+
+    fn encode_string(s: &[u8]) -> Vec<u8> {
+        if s.len() == 1 && s[0] < 0x80 {
+            return s.to_vec();
+        }
+        let mut res = encode_header(s.len(), 0x80);
+        res.extend_from_slice(s);
+        res
+    }
+
+    fn encode_list(items: &[Vec<u8>]) -> Vec<u8> {
+        let payload = items.concat();
+        let mut res = encode_header(payload.len(), 0xc0);
+        res.extend(payload);
+        res
+    }
+
+    #[test]
+    fn test_rlp_decode() -> Result<(), PlonkError> {
+        test_rlp_decode_helper::<FqEd254>()?;
+        test_rlp_decode_helper::<FqEd377>()?;
+        test_rlp_decode_helper::<FqEd381>()?;
+        test_rlp_decode_helper::<Fq377>()
+    }
+
+    fn test_rlp_decode_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let strings: Vec<Vec<u8>> = vec![
+            b"cat".to_vec(),
+            vec![],
+            vec![0x0f],
+            vec![0x42; 56],
+            vec![0x81; 300],
+        ];
+        let nested = encode_list(&[encode_string(b"dog"), encode_list(&[])]);
+        let mut items: Vec<Vec<u8>> = strings.iter().map(|s| encode_string(s)).collect();
+        items.push(nested.clone());
+        let encoding = encode_list(&items);
+        let mut padded = encoding.clone();
+        padded.extend_from_slice(&[0xc1, 0x80, 0xff]);
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let bytes = padded
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let (list, decoded) = circuit.rlp_decode_list(&bytes, 8)?;
+        assert_eq!(circuit.witness(list.is_list())?, F::one());
+        assert_eq!(
+            circuit.witness(list.payload_offset())?,
+            F::from((encoding.len() - items.concat().len()) as u64)
+        );
+        assert_eq!(
+            circuit.witness(list.payload_len())?,
+            F::from(items.concat().len() as u64)
+        );
+        let mut position = encoding.len() - items.concat().len();
+        for (i, item) in decoded.iter().enumerate() {
+            if i >= items.len() {
+                assert_eq!(circuit.witness(item.present())?, F::zero());
+                continue;
+            }
+            assert_eq!(circuit.witness(item.present())?, F::one());
+            assert_eq!(circuit.witness(item.start())?, F::from(position as u64));
+            position += items[i].len();
+        }
+        for (item, s) in decoded.iter().zip(strings.iter()) {
+            assert_eq!(circuit.witness(item.is_list())?, F::zero());
+            assert_eq!(
+                circuit.witness(item.payload_len())?,
+                F::from(s.len() as u64)
+            );
+            let payload = circuit.rlp_payload(&bytes, item, s.len() + 1)?;
+            for (i, &var) in payload.iter().enumerate() {
+                let expected = s.get(i).copied().unwrap_or(0);
+                assert_eq!(circuit.witness(var)?, F::from(expected));
+            }
+        }
+
+        // the nested list
+        let nested_item = decoded[5];
+        assert_eq!(circuit.witness(nested_item.is_list())?, F::one());
+        let nested_bytes = circuit.rlp_encoding(&bytes, &nested_item, 10)?;
+        let (_, nested_items) = circuit.rlp_decode_list(&nested_bytes, 2)?;
+        let dog = circuit.rlp_payload(&nested_bytes, &nested_items[0], 3)?;
+        for (&var, &b) in dog.iter().zip(b"dog".iter()) {
+            assert_eq!(circuit.witness(var)?, F::from(b));
+        }
+        assert_eq!(circuit.witness(nested_items[1].is_list())?, F::one());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // too many items, or a payload longer than expected
+        let mut bad_circuit = circuit.clone();
+        bad_circuit.rlp_decode_list(&bytes, 5)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut bad_circuit = circuit.clone();
+        bad_circuit.rlp_payload(&bytes, &decoded[0], 2)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // a truncated encoding
+        let mut bad_circuit = circuit.clone();
+        bad_circuit.rlp_decode_list(&bytes[..encoding.len() - 1], 8)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // a wrong length
+        let mut bad_circuit = circuit.clone();
+        *bad_circuit.witness_mut(decoded[0].payload_len()) += F::one();
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // a non-canonical long length
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let bytes = [0xb8u8, 0x01, 0x61]
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        circuit.rlp_decode(&bytes)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid encodings
+        assert!(circuit.rlp_decode(&[]).is_err());
+        assert!(circuit.rlp_decode(&[circuit.num_vars()]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,939 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the SHA-256 and SHA-512 hash functions.
+//!
+//! Messages and digests are represented by byte variables, i.e., variables
+//! whose values are in `[0, 256)`, and the compression functions take words
+//! given by their little-endian boolean variables, c.f. `bit_ops`. On
+//! UltraPlonk circuits whose range bit length divides 32, words are computed
+//! on as their little-endian 32-bit limbs instead, i.e. one `U32Var` for
+//! SHA-256 and a pair of them for SHA-512, so that the additions are range
+//! checked with the range table and the bitwise operations are looked up in
+//! the logic table. The message length is fixed at circuit generation time,
+//! so that the padding is made of constants.
+
+use super::{
+    bit_ops::{rotr, shr},
+    bytes::BytesVar,
+    ultraplonk::uint::{U32Var, UintVar},
+};
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+
+/// SHA-256 initial hash value.
+const SHA256_IV: [u64; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// SHA-256 round constants.
+const SHA256_K: [u64; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+/// SHA-512 initial hash value.
+const SHA512_IV: [u64; 8] = [
+    0x6a09e667f3bcc908,
+    0xbb67ae8584caa73b,
+    0x3c6ef372fe94f82b,
+    0xa54ff53a5f1d36f1,
+    0x510e527fade682d1,
+    0x9b05688c2b3e6c1f,
+    0x1f83d9abfb41bd6b,
+    0x5be0cd19137e2179,
+];
+
+/// SHA-512 round constants.
+const SHA512_K: [u64; 80] = [
+    0x428a2f98d728ae22,
+    0x7137449123ef65cd,
+    0xb5c0fbcfec4d3b2f,
+    0xe9b5dba58189dbbc,
+    0x3956c25bf348b538,
+    0x59f111f1b605d019,
+    0x923f82a4af194f9b,
+    0xab1c5ed5da6d8118,
+    0xd807aa98a3030242,
+    0x12835b0145706fbe,
+    0x243185be4ee4b28c,
+    0x550c7dc3d5ffb4e2,
+    0x72be5d74f27b896f,
+    0x80deb1fe3b1696b1,
+    0x9bdc06a725c71235,
+    0xc19bf174cf692694,
+    0xe49b69c19ef14ad2,
+    0xefbe4786384f25e3,
+    0x0fc19dc68b8cd5b5,
+    0x240ca1cc77ac9c65,
+    0x2de92c6f592b0275,
+    0x4a7484aa6ea6e483,
+    0x5cb0a9dcbd41fbd4,
+    0x76f988da831153b5,
+    0x983e5152ee66dfab,
+    0xa831c66d2db43210,
+    0xb00327c898fb213f,
+    0xbf597fc7beef0ee4,
+    0xc6e00bf33da88fc2,
+    0xd5a79147930aa725,
+    0x06ca6351e003826f,
+    0x142929670a0e6e70,
+    0x27b70a8546d22ffc,
+    0x2e1b21385c26c926,
+    0x4d2c6dfc5ac42aed,
+    0x53380d139d95b3df,
+    0x650a73548baf63de,
+    0x766a0abb3c77b2a8,
+    0x81c2c92e47edaee6,
+    0x92722c851482353b,
+    0xa2bfe8a14cf10364,
+    0xa81a664bbc423001,
+    0xc24b8b70d0f89791,
+    0xc76c51a30654be30,
+    0xd192e819d6ef5218,
+    0xd69906245565a910,
+    0xf40e35855771202a,
+    0x106aa07032bbd1b8,
+    0x19a4c116b8d2d0c8,
+    0x1e376c085141ab53,
+    0x2748774cdf8eeb99,
+    0x34b0bcb5e19b48a8,
+    0x391c0cb3c5c95a63,
+    0x4ed8aa4ae3418acb,
+    0x5b9cca4f7763e373,
+    0x682e6ff3d6b2b8a3,
+    0x748f82ee5defb2fc,
+    0x78a5636f43172f60,
+    0x84c87814a1f0ab72,
+    0x8cc702081a6439ec,
+    0x90befffa23631e28,
+    0xa4506cebde82bde9,
+    0xbef9a3f7b2c67915,
+    0xc67178f2e372532b,
+    0xca273eceea26619c,
+    0xd186b8c721c0c207,
+    0xeada7dd6cde0eb1e,
+    0xf57d4f7fee6ed178,
+    0x06f067aa72176fba,
+    0x0a637dc5a2c898a6,
+    0x113f9804bef90dae,
+    0x1b710b35131c471b,
+    0x28db77f523047d84,
+    0x32caab7b40c72493,
+    0x3c9ebe0a15c9bebc,
+    0x431d67c49c100d4c,
+    0x4cc5d4becb3e42b6,
+    0x597f299cfc657e2a,
+    0x5fcb6fab3ad6faec,
+    0x6c44198c4a475817,
+];
+
+// The parameters of a function of the SHA-2 family.
+struct Sha2Variant {
+    // number of bits of a word
+    word_len: usize,
+    // number of bytes of a block
+    block_len: usize,
+    // number of bytes of the encoded message bit length
+    length_len: usize,
+    iv: &'static [u64; 8],
+    k: &'static [u64],
+    // rotations of `Sigma_0` and `Sigma_1`
+    big_sigma0: [usize; 3],
+    big_sigma1: [usize; 3],
+    // two rotations and a shift for `sigma_0` and `sigma_1`
+    small_sigma0: [usize; 3],
+    small_sigma1: [usize; 3],
+}
+
+const SHA256: Sha2Variant = Sha2Variant {
+    word_len: 32,
+    block_len: 64,
+    length_len: 8,
+    iv: &SHA256_IV,
+    k: &SHA256_K,
+    big_sigma0: [2, 13, 22],
+    big_sigma1: [6, 11, 25],
+    small_sigma0: [7, 18, 3],
+    small_sigma1: [17, 19, 10],
+};
+
+const SHA512: Sha2Variant = Sha2Variant {
+    word_len: 64,
+    block_len: 128,
+    length_len: 16,
+    iv: &SHA512_IV,
+    k: &SHA512_K,
+    big_sigma0: [28, 34, 39],
+    big_sigma1: [14, 18, 41],
+    small_sigma0: [1, 8, 7],
+    small_sigma1: [19, 61, 6],
+};
+
+// A word given by its little-endian 32-bit limbs.
+type Limbs = Vec<U32Var>;
+
+/// Trait for the SHA-256 gadgets.
+pub trait Sha256Gadget<F: PrimeField> {
+    /// Apply the SHA-256 compression function to a message block.
+    /// * `state` - the 8 words of the chaining state, each given by its 32
+    ///   little-endian boolean variables
+    /// * `block` - the 16 words of the message block, in the same form
+    /// * `returns` - the 8 words of the new chaining state
+    fn sha256_compress(
+        &mut self,
+        state: &[Vec<BoolVar>],
+        block: &[Vec<BoolVar>],
+    ) -> Result<Vec<Vec<BoolVar>>, PlonkError>;
+
+    /// SHA-256 hash of a message.
+    /// * `msg_vars` - the bytes of the message; each of them is constrained
+    ///   to be in `[0, 256)`
+    /// * `returns` - the 32 bytes of the digest
+    fn sha256(&mut self, msg_vars: &[Variable]) -> Result<Vec<Variable>, PlonkError>;
+
+    /// SHA-256 hash of a byte string, whose digest carries the range
+    /// guarantee of its bytes, as it is packed from bits or split into range
+    /// checked bytes.
+    fn sha256_bytes(&mut self, msg: &BytesVar) -> Result<BytesVar, PlonkError> {
+        Ok(BytesVar::new_unchecked(self.sha256(&msg.vars())?))
+    }
+}
+
+/// Trait for the SHA-512 gadgets.
+pub trait Sha512Gadget<F: PrimeField> {
+    /// Apply the SHA-512 compression function to a message block.
+    /// * `state` - the 8 words of the chaining state, each given by its 64
+    ///   little-endian boolean variables
+    /// * `block` - the 16 words of the message block, in the same form
+    /// * `returns` - the 8 words of the new chaining state
+    fn sha512_compress(
+        &mut self,
+        state: &[Vec<BoolVar>],
+        block: &[Vec<BoolVar>],
+    ) -> Result<Vec<Vec<BoolVar>>, PlonkError>;
+
+    /// SHA-512 hash of a message.
+    /// * `msg_vars` - the bytes of the message; each of them is constrained
+    ///   to be in `[0, 256)`
+    /// * `returns` - the 64 bytes of the digest
+    fn sha512(&mut self, msg_vars: &[Variable]) -> Result<Vec<Variable>, PlonkError>;
+
+    /// SHA-512 hash of a byte string, whose digest carries the range
+    /// guarantee of its bytes, as it is packed from bits or split into range
+    /// checked bytes.
+    fn sha512_bytes(&mut self, msg: &BytesVar) -> Result<BytesVar, PlonkError> {
+        Ok(BytesVar::new_unchecked(self.sha512(&msg.vars())?))
+    }
+}
+
+impl<F> Sha256Gadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn sha256_compress(
+        &mut self,
+        state: &[Vec<BoolVar>],
+        block: &[Vec<BoolVar>],
+    ) -> Result<Vec<Vec<BoolVar>>, PlonkError> {
+        self.sha2_compress(&SHA256, state, block)
+    }
+
+    fn sha256(&mut self, msg_vars: &[Variable]) -> Result<Vec<Variable>, PlonkError> {
+        self.sha2_hash(&SHA256, msg_vars)
+    }
+}
+
+impl<F> Sha512Gadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn sha512_compress(
+        &mut self,
+        state: &[Vec<BoolVar>],
+        block: &[Vec<BoolVar>],
+    ) -> Result<Vec<Vec<BoolVar>>, PlonkError> {
+        self.sha2_compress(&SHA512, state, block)
+    }
+
+    fn sha512(&mut self, msg_vars: &[Variable]) -> Result<Vec<Variable>, PlonkError> {
+        self.sha2_hash(&SHA512, msg_vars)
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn sha2_compress(
+        &mut self,
+        variant: &Sha2Variant,
+        state: &[Vec<BoolVar>],
+        block: &[Vec<BoolVar>],
+    ) -> Result<Vec<Vec<BoolVar>>, PlonkError> {
+        let word_len = variant.word_len;
+        if state.len() != 8 || block.len() != 16 {
+            return Err(ParameterError(format!(
+                "SHA-2 compression takes 8 state words and 16 block words, got {} and {}",
+                state.len(),
+                block.len()
+            ))
+            .into());
+        }
+        if let Some(word) = state
+            .iter()
+            .chain(block.iter())
+            .find(|word| word.len() != word_len)
+        {
+            return Err(ParameterError(format!(
+                "SHA-2 words have {} bits, got {}",
+                word_len,
+                word.len()
+            ))
+            .into());
+        }
+        if self.support_uint() {
+            // the limbs packed from boolean variables are in range
+            let to_limbs = |circuit: &mut Self, words: &[Vec<BoolVar>]| {
+                words
+                    .iter()
+                    .map(|word| {
+                        word.chunks(32)
+                            .map(|bits| Ok(U32Var(circuit.pack_bits(bits)?)))
+                            .collect::<Result<Limbs, PlonkError>>()
+                    })
+                    .collect::<Result<Vec<_>, PlonkError>>()
+            };
+            let state = to_limbs(self, state)?;
+            let block = to_limbs(self, block)?;
+            let new_state = self.sha2_compress_limbs(variant, &state, &block)?;
+            return new_state
+                .iter()
+                .map(|word| {
+                    let mut bits = Vec::with_capacity(word_len);
+                    for limb in word.iter() {
+                        bits.extend(self.unpack_bool(limb.var(), 32)?);
+                    }
+                    Ok(bits)
+                })
+                .collect();
+        }
+        let zero = self.false_var();
+        let [r0, r1, r2] = variant.big_sigma0;
+        let [r3, r4, r5] = variant.big_sigma1;
+        let [r6, r7, s0_shift] = variant.small_sigma0;
+        let [r8, r9, s1_shift] = variant.small_sigma1;
+
+        // message schedule
+        let mut w: Vec<Vec<BoolVar>> = block.to_vec();
+        for t in 16..variant.k.len() {
+            let s0 = self.xor3_words(
+                &rotr(&w[t - 15], r6),
+                &rotr(&w[t - 15], r7),
+                &shr(&w[t - 15], s0_shift, zero),
+            )?;
+            let s1 = self.xor3_words(
+                &rotr(&w[t - 2], r8),
+                &rotr(&w[t - 2], r9),
+                &shr(&w[t - 2], s1_shift, zero),
+            )?;
+            let w_t = self.add_words_mod(&[&w[t - 16], &s0, &w[t - 7], &s1], 0)?;
+            w.push(w_t);
+        }
+
+        // compression
+        let mut v: Vec<Vec<BoolVar>> = state.to_vec();
+        for (w_t, k_t) in w.iter().zip(variant.k.iter()) {
+            let (a, b, c, d) = (&v[0], &v[1], &v[2], &v[3]);
+            let (e, f, g, h) = (&v[4], &v[5], &v[6], &v[7]);
+
+            let s1 = self.xor3_words(&rotr(e, r3), &rotr(e, r4), &rotr(e, r5))?;
+            let ch = self.map_bits3(e, f, g, Self::ch_bit)?;
+            let s0 = self.xor3_words(&rotr(a, r0), &rotr(a, r1), &rotr(a, r2))?;
+            let maj = self.map_bits3(a, b, c, Self::maj_bit)?;
+
+            // temp1 = h + S1 + ch + K[t] + w[t] is shared by the new `a` and
+            // `e`, so it is only packed once
+            let packed = [h, &s1, &ch, w_t]
+                .iter()
+                .map(|word| self.pack_bits(word))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let temp1 = self.sum(&packed)?;
+            let temp1 = self.add_constant(temp1, &F::from(*k_t))?;
+            let packed_d = self.pack_bits(d)?;
+            let packed_s0 = self.pack_bits(&s0)?;
+            let packed_maj = self.pack_bits(&maj)?;
+            // temp1 < 5 * 2^word_len, hence both sums are smaller than
+            // 2^(word_len + 3)
+            let sum_e = self.sum(&[temp1, packed_d])?;
+            let new_e = self.unpack_mod(sum_e, word_len, 3)?;
+            let sum_a = self.sum(&[temp1, packed_s0, packed_maj])?;
+            let new_a = self.unpack_mod(sum_a, word_len, 3)?;
+
+            v = ark_std::vec![
+                new_a,
+                v[0].clone(),
+                v[1].clone(),
+                v[2].clone(),
+                new_e,
+                v[4].clone(),
+                v[5].clone(),
+                v[6].clone(),
+            ];
+        }
+
+        state
+            .iter()
+            .zip(v.iter())
+            .map(|(s, x)| self.add_words_mod(&[s, x], 0))
+            .collect()
+    }
+
+    fn sha2_hash(
+        &mut self,
+        variant: &Sha2Variant,
+        msg_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        if self.support_uint() {
+            return self.sha2_hash_limbs(variant, msg_vars);
+        }
+        let word_bytes = variant.word_len / 8;
+        // the bits of each byte of the padded message, little-endian within
+        // the byte
+        let mut bytes: Vec<Vec<BoolVar>> = msg_vars
+            .iter()
+            .map(|&byte| self.unpack_bool(byte, 8))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let bit_len = (msg_vars.len() as u128) * 8;
+        bytes.push(self.constant_bits(0x80, 8));
+        while bytes.len() % variant.block_len != variant.block_len - variant.length_len {
+            bytes.push(self.constant_bits(0, 8));
+        }
+        let bit_len_bytes = bit_len.to_be_bytes();
+        for byte in bit_len_bytes[16 - variant.length_len..].iter() {
+            bytes.push(self.constant_bits(*byte as u64, 8));
+        }
+
+        let mut state: Vec<Vec<BoolVar>> = variant
+            .iv
+            .iter()
+            .map(|&x| self.constant_bits(x, variant.word_len))
+            .collect();
+        for block in bytes.chunks(variant.block_len) {
+            // words are big-endian
+            let block: Vec<Vec<BoolVar>> = block
+                .chunks(word_bytes)
+                .map(|word| word.iter().rev().flatten().copied().collect())
+                .collect();
+            state = self.sha2_compress(variant, &state, &block)?;
+        }
+
+        // the digest is the big-endian encoding of the state
+        let mut digest = Vec::with_capacity(8 * word_bytes);
+        for word in state.iter() {
+            for byte in word.chunks(8).rev() {
+                digest.push(self.pack_bits(byte)?);
+            }
+        }
+        Ok(digest)
+    }
+
+    // `sha2_compress` with words of 32-bit limbs.
+    fn sha2_compress_limbs(
+        &mut self,
+        variant: &Sha2Variant,
+        state: &[Limbs],
+        block: &[Limbs],
+    ) -> Result<Vec<Limbs>, PlonkError> {
+        let [r0, r1, r2] = variant.big_sigma0;
+        let [r3, r4, r5] = variant.big_sigma1;
+        let [r6, r7, s0_shift] = variant.small_sigma0;
+        let [r8, r9, s1_shift] = variant.small_sigma1;
+
+        // message schedule
+        let mut w: Vec<Limbs> = block.to_vec();
+        for t in 16..variant.k.len() {
+            let s0 = self.sha2_limbs_sigma(&w[t - 15], [r6, r7], s0_shift)?;
+            let s1 = self.sha2_limbs_sigma(&w[t - 2], [r8, r9], s1_shift)?;
+            let w_t = self.sha2_limbs_sum(&[&w[t - 16], &s0, &w[t - 7], &s1], 0)?;
+            w.push(w_t);
+        }
+
+        // compression
+        let mut v: Vec<Limbs> = state.to_vec();
+        for (w_t, k_t) in w.iter().zip(variant.k.iter()) {
+            let (a, b, c, d) = (&v[0], &v[1], &v[2], &v[3]);
+            let (e, f, g, h) = (&v[4], &v[5], &v[6], &v[7]);
+
+            let s1 = self.sha2_limbs_big_sigma(e, [r3, r4, r5])?;
+            let s0 = self.sha2_limbs_big_sigma(a, [r0, r1, r2])?;
+            // the bits of `e AND f` and `NOT e AND g` are disjoint, and so are
+            // those of `a AND b` and `c AND (a XOR b)`, hence they are added
+            let mut ch = Vec::with_capacity(e.len());
+            let mut maj = Vec::with_capacity(a.len());
+            for i in 0..e.len() {
+                let ef = self.uint_and(e[i], f[i])?;
+                let not_e = self.uint_not(e[i])?;
+                let not_e_g = self.uint_and(not_e, g[i])?;
+                ch.push(U32Var(self.add(ef.var(), not_e_g.var())?));
+                let (ab, a_xor_b) = self.uint_logic(a[i], b[i])?;
+                let c_a_xor_b = self.uint_and(c[i], U32Var(a_xor_b))?;
+                maj.push(U32Var(self.add(ab, c_a_xor_b.var())?));
+            }
+
+            let temp1 = self.sha2_limbs_sum(&[h, &s1, &ch, w_t], *k_t)?;
+            let new_e = self.sha2_limbs_sum(&[d, &temp1], 0)?;
+            let new_a = self.sha2_limbs_sum(&[&temp1, &s0, &maj], 0)?;
+
+            v = ark_std::vec![
+                new_a,
+                v[0].clone(),
+                v[1].clone(),
+                v[2].clone(),
+                new_e,
+                v[4].clone(),
+                v[5].clone(),
+                v[6].clone(),
+            ];
+        }
+
+        state
+            .iter()
+            .zip(v.iter())
+            .map(|(s, x)| self.sha2_limbs_sum(&[s, x], 0))
+            .collect()
+    }
+
+    // `sha2_hash` with words of 32-bit limbs, whose bytes are range checked
+    // with lookups.
+    fn sha2_hash_limbs(
+        &mut self,
+        variant: &Sha2Variant,
+        msg_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let num_limbs = variant.word_len / 32;
+        let mut bytes = msg_vars.to_vec();
+        let bit_len = (msg_vars.len() as u128) * 8;
+        let mut padding = ark_std::vec![0x80u8];
+        while (bytes.len() + padding.len()) % variant.block_len
+            != variant.block_len - variant.length_len
+        {
+            padding.push(0);
+        }
+        padding.extend_from_slice(&bit_len.to_be_bytes()[16 - variant.length_len..]);
+        for byte in padding {
+            bytes.push(self.create_constant_variable(F::from(byte))?);
+        }
+
+        let mut state = variant
+            .iv
+            .iter()
+            .map(|&x| {
+                (0..num_limbs)
+                    .map(|i| self.create_constant_u32_variable((x >> (32 * i)) as u32))
+                    .collect::<Result<Limbs, PlonkError>>()
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        for block in bytes.chunks(variant.block_len) {
+            // words and their limbs are big-endian
+            let block = block
+                .chunks(4 * num_limbs)
+                .map(|word| {
+                    word.chunks(4)
+                        .rev()
+                        .map(|limb| {
+                            let limb_le: Vec<Variable> = limb.iter().rev().copied().collect();
+                            self.uint_from_bytes_le(&limb_le)
+                        })
+                        .collect::<Result<Limbs, PlonkError>>()
+                })
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            state = self.sha2_compress_limbs(variant, &state, &block)?;
+        }
+
+        // the digest is the big-endian encoding of the state
+        let mut digest = Vec::with_capacity(32 * num_limbs);
+        for word in state.iter() {
+            for &limb in word.iter().rev() {
+                let limb_bytes = self.uint_to_bytes_le(limb)?;
+                digest.extend(limb_bytes.into_iter().rev());
+            }
+        }
+        Ok(digest)
+    }
+
+    // `Sigma_0` or `Sigma_1` of a word of limbs, the XOR of 3 rotations.
+    fn sha2_limbs_big_sigma(
+        &mut self,
+        a: &[U32Var],
+        rots: [usize; 3],
+    ) -> Result<Limbs, PlonkError> {
+        let x = self.sha2_limbs_shift(a, rots[0], true)?;
+        let y = self.sha2_limbs_shift(a, rots[1], true)?;
+        let z = self.sha2_limbs_shift(a, rots[2], true)?;
+        let t = self.sha2_limbs_xor(&x, &y)?;
+        self.sha2_limbs_xor(&t, &z)
+    }
+
+    // `sigma_0` or `sigma_1` of a word of limbs, the XOR of 2 rotations and a
+    // shift.
+    fn sha2_limbs_sigma(
+        &mut self,
+        a: &[U32Var],
+        rots: [usize; 2],
+        shift: usize,
+    ) -> Result<Limbs, PlonkError> {
+        let x = self.sha2_limbs_shift(a, rots[0], true)?;
+        let y = self.sha2_limbs_shift(a, rots[1], true)?;
+        let z = self.sha2_limbs_shift(a, shift, false)?;
+        let t = self.sha2_limbs_xor(&x, &y)?;
+        self.sha2_limbs_xor(&t, &z)
+    }
+
+    // `a XOR b` for words of limbs.
+    fn sha2_limbs_xor(&mut self, a: &[U32Var], b: &[U32Var]) -> Result<Limbs, PlonkError> {
+        a.iter()
+            .zip(b.iter())
+            .map(|(&x, &y)| self.uint_xor(x, y))
+            .collect()
+    }
+
+    // The right rotation of a word of limbs by `0 < n < 32 * a.len()` bits,
+    // or its logical right shift if `!rotate`.
+    fn sha2_limbs_shift(
+        &mut self,
+        a: &[U32Var],
+        n: usize,
+        rotate: bool,
+    ) -> Result<Limbs, PlonkError> {
+        let num_limbs = a.len();
+        let (q, r) = (n / 32, n % 32);
+        // the limbs shifted by whole limbs, where `None` is a zero limb
+        let limbs: Vec<Option<U32Var>> = (0..num_limbs)
+            .map(|i| {
+                if rotate {
+                    Some(a[(i + q) % num_limbs])
+                } else {
+                    a.get(i + q).copied()
+                }
+            })
+            .collect();
+        let zero = self.zero();
+        if r == 0 {
+            return Ok(limbs
+                .into_iter()
+                .map(|limb| limb.unwrap_or(U32Var(zero)))
+                .collect());
+        }
+
+        // each limb is split into `high * 2^r + low`, and its low part moves to
+        // the top of the next lower limb
+        let mut splits = Vec::with_capacity(num_limbs);
+        for limb in limbs.iter() {
+            splits.push(match limb {
+                Some(limb) => Some(self.uint_split(*limb, r)?),
+                None => None,
+            });
+        }
+        let shift = F::from(1u64 << (32 - r));
+        (0..num_limbs)
+            .map(|i| {
+                let next = if rotate {
+                    splits[(i + 1) % num_limbs]
+                } else {
+                    splits.get(i + 1).copied().flatten()
+                };
+                let high = splits[i].map_or(zero, |(high, _)| high);
+                let low = next.map_or(zero, |(_, low)| low);
+                let res = self.lc(
+                    &[high, low, zero, zero],
+                    &[F::one(), shift, F::zero(), F::zero()],
+                )?;
+                Ok(U32Var(res))
+            })
+            .collect()
+    }
+
+    // `(sum_i terms[i] + constant) mod 2^word_len` for words of limbs, where
+    // the carry of each limb is added to the next one.
+    fn sha2_limbs_sum(&mut self, terms: &[&[U32Var]], constant: u64) -> Result<Limbs, PlonkError> {
+        let num_limbs = terms[0].len();
+        let mut res = Vec::with_capacity(num_limbs);
+        let mut carry = None;
+        for i in 0..num_limbs {
+            let mut limbs: Vec<U32Var> = terms.iter().map(|term| term[i]).collect();
+            // the carry is small, hence a 32-bit integer
+            limbs.extend(carry.map(U32Var));
+            let limb_constant = (constant >> (32 * i)) & u32::MAX as u64;
+            let (limb, limb_carry) = self.uint_sum_with_carry(&limbs, limb_constant)?;
+            res.push(limb);
+            carry = Some(limb_carry);
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng, vec};
+    use sha2::{Digest, Sha256, Sha512};
+
+    fn bool_words<F: PrimeField>(
+        circuit: &mut PlonkCircuit<F>,
+        values: &[u64],
+        word_len: usize,
+    ) -> Vec<Vec<BoolVar>> {
+        values
+            .iter()
+            .map(|&x| {
+                (0..word_len)
+                    .map(|i| circuit.create_bool_variable((x >> i) & 1 == 1).unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn byte_vars<F: PrimeField>(circuit: &mut PlonkCircuit<F>, msg: &[u8]) -> Vec<Variable> {
+        msg.iter()
+            .map(|&b| circuit.create_variable(F::from(b)).unwrap())
+            .collect()
+    }
+
+    fn check_digest<F: PrimeField>(
+        circuit: &PlonkCircuit<F>,
+        digest_vars: &[Variable],
+        expected: &[u8],
+    ) -> Result<(), PlonkError> {
+        assert_eq!(digest_vars.len(), expected.len());
+        for (var, byte) in digest_vars.iter().zip(expected.iter()) {
+            assert_eq!(circuit.witness(*var)?, F::from(*byte));
+        }
+        Ok(())
+    }
+
+    fn word_value<F: PrimeField>(circuit: &PlonkCircuit<F>, word: &[BoolVar]) -> u64 {
+        word.iter().enumerate().fold(0, |acc, (i, &bit)| {
+            if circuit.witness(bit.into()).unwrap() == F::one() {
+                acc | (1 << i)
+            } else {
+                acc
+            }
+        })
+    }
+
+    #[test]
+    fn test_sha256() -> Result<(), PlonkError> {
+        test_sha256_helper::<FqEd254>()?;
+        test_sha256_helper::<FqEd377>()?;
+        test_sha256_helper::<FqEd381>()?;
+        test_sha256_helper::<Fq377>()
+    }
+
+    fn test_sha256_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        // the padding takes one or two blocks
+        for len in [0, 3, 55, 56, 64] {
+            let mut msg = vec![0u8; len];
+            rng.fill_bytes(&mut msg);
+            let expected = Sha256::digest(&msg);
+
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let msg_vars = msg
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let digest_vars = circuit.sha256(&msg_vars)?;
+            assert_eq!(digest_vars.len(), 32);
+            for (var, byte) in digest_vars.iter().zip(expected.iter()) {
+                assert_eq!(circuit.witness(*var)?, F::from(*byte));
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong digest byte is rejected
+            *circuit.witness_mut(digest_vars[0]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // a message byte out of range is rejected
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let byte_var = circuit.create_variable(F::from(256u32))?;
+        assert!(circuit.sha256(&[byte_var]).is_err());
+
+        // with 32-bit limbs on UltraPlonk, in fewer gates
+        for len in [0, 3, 56] {
+            let mut msg = vec![0u8; len];
+            rng.fill_bytes(&mut msg);
+            let expected = Sha256::digest(&msg);
+
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+            let msg_vars = byte_vars(&mut circuit, &msg);
+            let digest_vars = circuit.sha256(&msg_vars)?;
+            check_digest(&circuit, &digest_vars, &expected)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            let mut turbo_circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let msg_vars = byte_vars(&mut turbo_circuit, &msg);
+            turbo_circuit.sha256(&msg_vars)?;
+            assert!(circuit.num_gates() < turbo_circuit.num_gates());
+
+            *circuit.witness_mut(digest_vars[31]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        // a message byte out of range is range checked with a lookup
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let byte_var = circuit.create_variable(F::from(256u32))?;
+        circuit.sha256(&[byte_var])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // the compression of the single padded block of "abc" from the
+        // initial hash value is the digest
+        test_sha256_compress_helper(PlonkCircuit::<F>::new_turbo_plonk())?;
+        test_sha256_compress_helper(PlonkCircuit::<F>::new_ultra_plonk(8))
+    }
+
+    fn test_sha256_compress_helper<F: PrimeField>(
+        mut circuit: PlonkCircuit<F>,
+    ) -> Result<(), PlonkError> {
+        let mut padded = [0u8; 64];
+        padded[..3].copy_from_slice(b"abc");
+        padded[3] = 0x80;
+        padded[63] = 24;
+        let block: Vec<u64> = padded
+            .chunks(4)
+            .map(|w| u32::from_be_bytes([w[0], w[1], w[2], w[3]]) as u64)
+            .collect();
+        let state = bool_words(&mut circuit, &SHA256_IV, 32);
+        let block = bool_words(&mut circuit, &block, 32);
+        let new_state = circuit.sha256_compress(&state, &block)?;
+        let expected = Sha256::digest(b"abc");
+        for (word, w) in new_state.iter().zip(expected.chunks(4)) {
+            assert_eq!(
+                word_value(&circuit, word),
+                u32::from_be_bytes([w[0], w[1], w[2], w[3]]) as u64
+            );
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // a non-boolean output bit is rejected
+        let bit = Variable::from(new_state[3][5]);
+        let value = circuit.witness(bit)?;
+        *circuit.witness_mut(bit) = F::from(2u8);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(bit) = value;
+        // a non-boolean input bit is rejected
+        *circuit.witness_mut(block[0][0].into()) = F::from(2u8);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // malformed compression inputs
+        let word = bool_words(&mut circuit, &[0], 32).remove(0);
+        assert!(circuit
+            .sha256_compress(&vec![word.clone(); 7], &vec![word.clone(); 16])
+            .is_err());
+        assert!(circuit
+            .sha256_compress(&vec![word.clone(); 8], &vec![word[..31].to_vec(); 16])
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sha512() -> Result<(), PlonkError> {
+        test_sha512_helper::<FqEd254>()?;
+        test_sha512_helper::<Fq377>()
+    }
+
+    fn test_sha512_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        // the padding takes one or two blocks
+        for len in [0, 111, 112] {
+            let mut msg = vec![0u8; len];
+            rng.fill_bytes(&mut msg);
+            let expected = Sha512::digest(&msg);
+
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let msg_vars = msg
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let digest_vars = circuit.sha512(&msg_vars)?;
+            assert_eq!(digest_vars.len(), 64);
+            for (var, byte) in digest_vars.iter().zip(expected.iter()) {
+                assert_eq!(circuit.witness(*var)?, F::from(*byte));
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong digest byte is rejected
+            *circuit.witness_mut(digest_vars[63]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // with pairs of 32-bit limbs on UltraPlonk, in fewer gates
+        for len in [0, 112] {
+            let mut msg = vec![0u8; len];
+            rng.fill_bytes(&mut msg);
+            let expected = Sha512::digest(&msg);
+
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+            let msg_vars = byte_vars(&mut circuit, &msg);
+            let digest_vars = circuit.sha512(&msg_vars)?;
+            check_digest(&circuit, &digest_vars, &expected)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            let mut turbo_circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let msg_vars = byte_vars(&mut turbo_circuit, &msg);
+            turbo_circuit.sha512(&msg_vars)?;
+            assert!(circuit.num_gates() < turbo_circuit.num_gates());
+
+            *circuit.witness_mut(digest_vars[0]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // the compression of the single padded block of "abc" on UltraPlonk
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let mut padded = [0u8; 128];
+        padded[..3].copy_from_slice(b"abc");
+        padded[3] = 0x80;
+        padded[127] = 24;
+        let block: Vec<u64> = padded
+            .chunks(8)
+            .map(|w| u64::from_be_bytes([w[0], w[1], w[2], w[3], w[4], w[5], w[6], w[7]]))
+            .collect();
+        let state = bool_words(&mut circuit, &SHA512_IV, 64);
+        let block = bool_words(&mut circuit, &block, 64);
+        let new_state = circuit.sha512_compress(&state, &block)?;
+        let expected = Sha512::digest(b"abc");
+        for (word, w) in new_state.iter().zip(expected.chunks(8)) {
+            assert_eq!(
+                word_value(&circuit, word),
+                u64::from_be_bytes([w[0], w[1], w[2], w[3], w[4], w[5], w[6], w[7]])
+            );
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // a wrong output bit is rejected
+        let bit = Variable::from(new_state[7][63]);
+        *circuit.witness_mut(bit) = F::one() - circuit.witness(bit)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // SHA-256 words are rejected by the SHA-512 compression
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let word = bool_words(&mut circuit, &[0], 32).remove(0);
+        assert!(circuit
+            .sha512_compress(&vec![word.clone(); 8], &vec![word; 16])
+            .is_err());
+        Ok(())
+    }
+}
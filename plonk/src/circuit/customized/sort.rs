@@ -0,0 +1,263 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Multiset equality and sorting gadgets.
+//!
+//! Two lists of tuples are equal as multisets iff `prod_i (e_i + gamma)` is
+//! the same for both lists with overwhelming probability, where `e_i` is the
+//! tuple `t_i` compressed as `sum_j alpha^j * t_i[j]`. As the permutation
+//! between the lists depends on the witness, it cannot be enforced with copy
+//! constraints; instead the challenges `alpha` and `gamma` are derived in the
+//! circuit by hashing all the tuples with Rescue.
+
+use super::rescue::RescueGadget;
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_std::{format, string::ToString, vec, vec::Vec};
+use jf_rescue::RescueParameter;
+
+impl<F: RescueParameter> PlonkCircuit<F> {
+    /// Constrain two lists of tuples of variables to be equal as multisets,
+    /// i.e. `rhs` to be a permutation of `lhs`. The grand products of the
+    /// argument are computed with challenges derived from all the tuples.
+    /// Return error if the lists have different lengths, if the tuples are
+    /// empty or have different lengths, or if a variable is invalid.
+    pub fn multiset_equality_gate(
+        &mut self,
+        lhs: &[Vec<Variable>],
+        rhs: &[Vec<Variable>],
+    ) -> Result<(), PlonkError> {
+        if lhs.len() != rhs.len() {
+            return Err(ParameterError(format!(
+                "multisets of different sizes {} and {}",
+                lhs.len(),
+                rhs.len()
+            ))
+            .into());
+        }
+        if lhs.is_empty() {
+            return Ok(());
+        }
+        let arity = lhs[0].len();
+        if arity == 0 || lhs.iter().chain(rhs.iter()).any(|t| t.len() != arity) {
+            return Err(ParameterError("tuples of inconsistent lengths".to_string()).into());
+        }
+        let data: Vec<Variable> = lhs.iter().chain(rhs.iter()).flatten().copied().collect();
+        self.check_vars_bound(&data)?;
+        let challenges = self.rescue_sponge_with_padding(&data, 2)?;
+        let (alpha, gamma) = (challenges[0], challenges[1]);
+
+        let lhs_prod = self.multiset_grand_product(lhs, alpha, gamma)?;
+        let rhs_prod = self.multiset_grand_product(rhs, alpha, gamma)?;
+        self.equal_gate(lhs_prod, rhs_prod)
+    }
+
+    /// Obtain the variables of `inputs` sorted in ascending order, where the
+    /// inputs are expected to be constrained to be in `[0, 2^bit_len)`.
+    /// Return error if `bit_len` is zero or too large for the native field,
+    /// or if a variable is invalid.
+    pub fn sort(
+        &mut self,
+        inputs: &[Variable],
+        bit_len: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let mut values = inputs
+            .iter()
+            .map(|&var| self.witness(var))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        values.sort_by_key(|value| value.into_repr());
+        let outputs = values
+            .into_iter()
+            .map(|value| self.create_variable(value))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.sorted_permutation_gate(inputs, &outputs, bit_len)?;
+        Ok(outputs)
+    }
+
+    /// Constrain `outputs` to be a permutation of `inputs` sorted in
+    /// ascending order, where the inputs are expected to be constrained to be
+    /// in `[0, 2^bit_len)`. The sortedness is enforced with range checks of
+    /// the consecutive differences, which use the lookup range table when
+    /// possible, as for `range_gate`.
+    /// Return error if the lists have different lengths, if `bit_len` is zero
+    /// or too large for the native field, or if a variable is invalid.
+    pub fn sorted_permutation_gate(
+        &mut self,
+        inputs: &[Variable],
+        outputs: &[Variable],
+        bit_len: usize,
+    ) -> Result<(), PlonkError> {
+        if bit_len == 0 || bit_len + 1 >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "unsupported bit length {} of sorted values",
+                bit_len
+            ))
+            .into());
+        }
+        let lhs: Vec<Vec<Variable>> = inputs.iter().map(|&var| vec![var]).collect();
+        let rhs: Vec<Vec<Variable>> = outputs.iter().map(|&var| vec![var]).collect();
+        self.multiset_equality_gate(&lhs, &rhs)?;
+        // outputs[i + 1] - outputs[i] is in [0, 2^bit_len), which is only
+        // possible without wrapping around the native modulus
+        for pair in outputs.windows(2) {
+            let diff = self.sub(pair[1], pair[0])?;
+            self.range_gate(diff, bit_len)?;
+        }
+        Ok(())
+    }
+
+    // prod_i (sum_j alpha^j * tuples[i][j] + gamma)
+    fn multiset_grand_product(
+        &mut self,
+        tuples: &[Vec<Variable>],
+        alpha: Variable,
+        gamma: Variable,
+    ) -> Result<Variable, PlonkError> {
+        let zero = self.zero();
+        let mut prod = self.one();
+        for tuple in tuples.iter() {
+            // Horner evaluation of the compressed tuple
+            let mut elem = tuple[tuple.len() - 1];
+            for &var in tuple.iter().rev().skip(1) {
+                elem = self.gen_quad_poly(
+                    &[elem, alpha, var, zero],
+                    &[F::zero(), F::zero(), F::one(), F::zero()],
+                    &[F::one(), F::zero()],
+                    F::zero(),
+                )?;
+            }
+            // prod * elem + prod * gamma
+            prod = self.gen_quad_poly(
+                &[prod, elem, prod, gamma],
+                &[F::zero(); 4],
+                &[F::one(), F::one()],
+                F::zero(),
+            )?;
+        }
+        Ok(prod)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::Rng, test_rng};
+
+    #[test]
+    fn test_multiset_equality() -> Result<(), PlonkError> {
+        test_multiset_equality_helper::<FqEd254>()?;
+        test_multiset_equality_helper::<FqEd377>()?;
+        test_multiset_equality_helper::<FqEd381>()?;
+        test_multiset_equality_helper::<Fq377>()
+    }
+
+    fn test_multiset_equality_helper<F: RescueParameter>() -> Result<(), PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let tuples: Vec<Vec<Variable>> = [(1u64, 2u64), (3, 4), (1, 2), (5, 6)]
+            .iter()
+            .map(|&(a, b)| {
+                Ok(vec![
+                    circuit.create_variable(F::from(a))?,
+                    circuit.create_variable(F::from(b))?,
+                ])
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let permuted = vec![
+            tuples[3].clone(),
+            tuples[0].clone(),
+            tuples[1].clone(),
+            tuples[2].clone(),
+        ];
+        circuit.multiset_equality_gate(&tuples, &permuted)?;
+        circuit.multiset_equality_gate(&[], &[])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // the multiplicities matter, and so does the order within a tuple
+        let mut bad_circuit = circuit.clone();
+        let duplicated = vec![
+            tuples[3].clone(),
+            tuples[0].clone(),
+            tuples[1].clone(),
+            tuples[3].clone(),
+        ];
+        bad_circuit.multiset_equality_gate(&tuples, &duplicated)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut bad_circuit = circuit.clone();
+        let swapped: Vec<Vec<Variable>> = tuples.iter().map(|t| vec![t[1], t[0]]).collect();
+        bad_circuit.multiset_equality_gate(&tuples, &swapped)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid parameters
+        assert!(circuit
+            .multiset_equality_gate(&tuples, &tuples[1..])
+            .is_err());
+        assert!(circuit
+            .multiset_equality_gate(&tuples[..1], &[vec![tuples[0][0]]])
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_sort() -> Result<(), PlonkError> {
+        test_sort_helper::<FqEd254>()?;
+        test_sort_helper::<FqEd377>()?;
+        test_sort_helper::<FqEd381>()?;
+        test_sort_helper::<Fq377>()
+    }
+
+    fn test_sort_helper<F: RescueParameter>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        for circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ]
+        .iter()
+        {
+            let mut circuit = circuit.clone();
+            let mut values: Vec<u64> = (0..10).map(|_| rng.gen_range(0..1 << 16)).collect();
+            values.extend_from_slice(&[0, 65535, values[3]]);
+            let inputs = values
+                .iter()
+                .map(|&v| circuit.create_variable(F::from(v)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let outputs = circuit.sort(&inputs, 16)?;
+            values.sort_unstable();
+            for (&output, &value) in outputs.iter().zip(values.iter()) {
+                assert_eq!(circuit.witness(output)?, F::from(value));
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // not sorted
+            let mut bad_circuit = circuit.clone();
+            let mut unsorted = outputs.clone();
+            unsorted.swap(0, 12);
+            bad_circuit.sorted_permutation_gate(&inputs, &unsorted, 16)?;
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+            // not a permutation
+            let mut bad_circuit = circuit.clone();
+            let vars = [3u8, 1, 2, 1, 1, 3]
+                .iter()
+                .map(|&v| bad_circuit.create_variable(F::from(v)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            bad_circuit.sorted_permutation_gate(&vars[..3], &vars[3..], 16)?;
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+            // invalid parameters
+            assert!(circuit.sort(&inputs, 0).is_err());
+            assert!(circuit.sort(&inputs, F::size_in_bits()).is_err());
+            assert!(circuit
+                .sorted_permutation_gate(&inputs, &outputs[1..], 16)
+                .is_err());
+        }
+        Ok(())
+    }
+}
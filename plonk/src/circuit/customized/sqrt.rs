@@ -0,0 +1,165 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Square roots and quadratic residuosity in the native field, e.g. for point
+//! decompression or hash-to-curve circuits.
+//!
+//! A non-zero element `a` is not a square iff `g * a` is a square, where `g`
+//! is the multiplicative generator of the field, which is not a square.
+//! Hence both cases are proven with a witness square root.
+
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use ark_ff::{PrimeField, SquareRootField};
+
+impl<F: PrimeField + SquareRootField> PlonkCircuit<F> {
+    /// Obtain a square root of `a`, i.e. a variable `s` such that `s * s =
+    /// a`. Either of the two square roots may be returned, and the circuit is
+    /// not satisfied if `a` is not a square.
+    /// Return error if the variable is invalid.
+    pub fn sqrt(&mut self, a: Variable) -> Result<Variable, PlonkError> {
+        self.check_var_bound(a)?;
+        let root = self.witness(a)?.sqrt().unwrap_or_else(F::zero);
+        let root_var = self.create_variable(root)?;
+        self.mul_gate(root_var, root_var, a)?;
+        Ok(root_var)
+    }
+
+    /// Constrain `a` not to be a square.
+    /// Return error if the variable is invalid.
+    pub fn non_square_gate(&mut self, a: Variable) -> Result<(), PlonkError> {
+        self.check_var_bound(a)?;
+        // a != 0 and g * a = s * s
+        self.non_zero_gate(a)?;
+        let g = F::multiplicative_generator();
+        let root = (self.witness(a)? * g).sqrt().unwrap_or_else(F::zero);
+        let root_var = self.create_variable(root)?;
+        let wires = [root_var, root_var, a, self.zero(), self.zero()];
+        self.quad_poly_gate(
+            &wires,
+            &[F::zero(), F::zero(), -g, F::zero()],
+            &[F::one(), F::zero()],
+            F::zero(),
+            F::zero(),
+        )
+    }
+
+    /// Obtain a bool variable representing whether `a` is a square, zero
+    /// being a square.
+    /// Return error if the variable is invalid.
+    pub fn is_square(&mut self, a: Variable) -> Result<BoolVar, PlonkError> {
+        self.check_var_bound(a)?;
+        let g = F::multiplicative_generator();
+        let a_val = self.witness(a)?;
+        let (b, root) = match a_val.sqrt() {
+            Some(root) => (true, root),
+            None => (false, (a_val * g).sqrt().unwrap_or_else(F::zero)),
+        };
+        let b_var = self.create_bool_variable(b)?;
+        let root_var = self.create_variable(root)?;
+
+        // s * s = a * (g + b * (1 - g)), i.e. a if b = 1 and g * a otherwise
+        let factor = self.gen_quad_poly(
+            &[b_var.into(), self.zero(), self.zero(), self.zero()],
+            &[F::one() - g, F::zero(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            g,
+        )?;
+        let wires = [root_var, root_var, a, factor, self.zero()];
+        self.quad_poly_gate(
+            &wires,
+            &[F::zero(); 4],
+            &[F::one(), -F::one()],
+            F::zero(),
+            F::zero(),
+        )?;
+        // zero is only proven to be a square: (1 - b) * is_zero(a) = 0
+        let a_is_zero = self.is_zero(a)?;
+        let wires = [
+            b_var.into(),
+            a_is_zero.into(),
+            self.zero(),
+            self.zero(),
+            self.zero(),
+        ];
+        self.quad_poly_gate(
+            &wires,
+            &[F::zero(), F::one(), F::zero(), F::zero()],
+            &[-F::one(), F::zero()],
+            F::zero(),
+            F::zero(),
+        )?;
+        Ok(b_var)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{test_rng, UniformRand};
+
+    #[test]
+    fn test_sqrt() -> Result<(), PlonkError> {
+        test_sqrt_helper::<FqEd254>()?;
+        test_sqrt_helper::<FqEd377>()?;
+        test_sqrt_helper::<FqEd381>()?;
+        test_sqrt_helper::<Fq377>()
+    }
+
+    fn test_sqrt_helper<F: PrimeField + SquareRootField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let g = F::multiplicative_generator();
+        let x = F::rand(&mut rng);
+        let square = x.square();
+        let non_square = g * square;
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let square_var = circuit.create_variable(square)?;
+        let non_square_var = circuit.create_variable(non_square)?;
+        let zero_var = circuit.zero();
+        let root = circuit.sqrt(square_var)?;
+        assert_eq!(circuit.witness(root)?.square(), square);
+        circuit.non_square_gate(non_square_var)?;
+        let b = circuit.is_square(square_var)?;
+        assert_eq!(circuit.witness(b.into())?, F::one());
+        let b = circuit.is_square(non_square_var)?;
+        assert_eq!(circuit.witness(b.into())?, F::zero());
+        let b_zero = circuit.is_square(zero_var)?;
+        assert_eq!(circuit.witness(b_zero.into())?, F::one());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong outputs
+        *circuit.witness_mut(b.into()) = F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(b.into()) = F::zero();
+        *circuit.witness_mut(b_zero.into()) = F::zero();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(b_zero.into()) = F::one();
+        *circuit.witness_mut(root) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // non-squares have no square roots, and squares are not non-squares
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let non_square_var = circuit.create_variable(non_square)?;
+        circuit.sqrt(non_square_var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let square_var = circuit.create_variable(square)?;
+        circuit.non_square_gate(square_var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let zero_var = circuit.zero();
+        circuit.non_square_gate(zero_var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        Ok(())
+    }
+}
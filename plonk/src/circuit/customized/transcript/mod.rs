@@ -6,23 +6,32 @@
 
 //! Implementing *native* circuit for rescue transcript
 
+mod poseidon;
+
+pub use poseidon::PoseidonTranscriptVar;
+
 use super::ultraplonk::mod_arith::FpElemVar;
 use crate::{
     circuit::{
         customized::{
             ecc::{PointVariable, SWToTEConParam},
-            rescue::RescueGadget,
+            rescue::{RescueHelperGadget, RescueStateVar},
             ultraplonk::plonk_verifier::*,
         },
-        Circuit, PlonkCircuit, Variable,
+        Circuit, CircuitGadget, PlonkCircuit, Variable,
     },
     errors::{PlonkError, SnarkError::ParameterError},
+    transcript::rescue::{
+        checked_challenge_bit_len, domain_separator_elems, fork_separator_elems, label_tag,
+        RescueTranscriptState,
+    },
 };
 use ark_ec::{short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters};
 use ark_ff::PrimeField;
-use ark_std::{string::ToString, vec::Vec};
+use ark_std::{format, string::ToString, vec::Vec};
 use core::marker::PhantomData;
-use jf_rescue::{RescueParameter, STATE_SIZE};
+use jf_rescue::{Permutation, RescueParameter, RATE, STATE_SIZE};
+use jf_utils::field_switching;
 
 /// Struct of variables representing a Rescue transcript type, including
 /// `STATE_SIZE` variables for the state, and a vector of variables for
@@ -30,24 +39,578 @@ use jf_rescue::{RescueParameter, STATE_SIZE};
 pub struct RescueTranscriptVar<F: RescueParameter> {
     transcript_var: Vec<Variable>,
     state_var: [Variable; STATE_SIZE],
+    permutation_count: usize,
+    strict_infinity: bool,
+    merlin_compat: bool,
+    absorb_labels: bool,
+    labels: Vec<Vec<u8>>,
+    challenge_bit_len: Option<usize>,
     _phantom: PhantomData<F>,
 }
 
+/// Defines in-circuit transcript APIs, mirroring the native
+/// `PlonkTranscript` trait.
+///
+/// It has an associated type `F` which defines the native field of the
+/// verifier circuit. An implementation derives the same challenges as its
+/// native counterpart, so that the Plonk verifier gadgets can be instantiated
+/// with the transcript that was used to produce the proof.
+pub trait PlonkTranscriptVar<F: PrimeField>: Sized {
+    /// Create a new transcript variable for a given circuit.
+    fn new(circuit: &mut PlonkCircuit<F>) -> Self;
+
+    /// Append the verification key and the public input to the transcript.
+    fn append_vk_and_pub_input_vars<E: PairingEngine<Fq = F>>(
+        &mut self,
+        circuit: &mut PlonkCircuit<F>,
+        vk_var: &VerifyingKeyVar<E>,
+        pub_input: &[FpElemVar<F>],
+    ) -> Result<(), PlonkError>;
+
+    /// Append a variable to the transcript.
+    fn append_variable(&mut self, label: &'static [u8], var: &Variable) -> Result<(), PlonkError>;
+
+    /// Append the message variables to the transcript.
+    fn append_message_vars(
+        &mut self,
+        label: &'static [u8],
+        msg_vars: &[Variable],
+    ) -> Result<(), PlonkError> {
+        for e in msg_vars.iter() {
+            self.append_variable(label, e)?;
+        }
+        Ok(())
+    }
+
+    /// Append a commitment variable (in TE form) to the transcript.
+    fn append_commitment_var<E, P>(
+        &mut self,
+        label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+        poly_comm_var: &PointVariable,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<G1Affine = GroupAffine<P>>,
+        P: SWModelParameters<BaseField = F>;
+
+    /// Append a slice of commitment variables (in TE form) to the transcript.
+    fn append_commitments_vars<E, P>(
+        &mut self,
+        label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+        poly_comm_vars: &[PointVariable],
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<G1Affine = GroupAffine<P>>,
+        P: SWModelParameters<BaseField = F>,
+    {
+        for poly_comm_var in poly_comm_vars.iter() {
+            self.append_commitment_var::<E, P>(label, circuit, poly_comm_var)?;
+        }
+        Ok(())
+    }
+
+    /// Append a challenge variable to the transcript.
+    fn append_challenge_var(
+        &mut self,
+        label: &'static [u8],
+        challenge_var: &Variable,
+    ) -> Result<(), PlonkError> {
+        self.append_variable(label, challenge_var)
+    }
+
+    /// Append the proof evaluations to the transcript.
+    fn append_proof_evaluations_vars<E: PairingEngine>(
+        &mut self,
+        circuit: &mut PlonkCircuit<F>,
+        evals: &ProofEvaluationsVar<F>,
+    ) -> Result<(), PlonkError> {
+        for e in &evals.wires_evals {
+            let tmp = e.convert_to_var(circuit)?;
+            self.append_variable(b"wire_evals", &tmp)?;
+        }
+        for e in &evals.wire_sigma_evals {
+            let tmp = e.convert_to_var(circuit)?;
+            self.append_variable(b"wire_sigma_evals", &tmp)?;
+        }
+        let tmp = evals.perm_next_eval.convert_to_var(circuit)?;
+        self.append_variable(b"perm_next_eval", &tmp)
+    }
+
+    /// Append the plookup evaluations to the transcript: the evaluations at
+    /// point `zeta`, then the ones at point `zeta * g`.
+    fn append_plookup_evaluations_vars<E: PairingEngine>(
+        &mut self,
+        circuit: &mut PlonkCircuit<F>,
+        evals: &PlookupEvaluationsVar<F>,
+    ) -> Result<(), PlonkError> {
+        for e in evals.evals_vec().iter() {
+            let tmp = e.convert_to_var(circuit)?;
+            self.append_variable(b"plookup_evals", &tmp)?;
+        }
+        for e in evals.next_evals_vec().iter() {
+            let tmp = e.convert_to_var(circuit)?;
+            self.append_variable(b"plookup_next_evals", &tmp)?;
+        }
+        Ok(())
+    }
+
+    /// Generate the challenge for the current transcript, and then append it
+    /// to the transcript. The challenge is returned as a variable in the
+    /// circuit field whose value is an element of `E::Fr`.
+    fn get_and_append_challenge_var<E>(
+        &mut self,
+        label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+    ) -> Result<Variable, PlonkError>
+    where
+        E: PairingEngine;
+
+    /// Generate one challenge per label in `labels` for the current
+    /// transcript, with no variable appended in between, and then append
+    /// them, in order, to the transcript, c.f.
+    /// `PlonkTranscript::get_and_append_challenges`. By default this is the
+    /// same as calling `get_and_append_challenge_var` for each label.
+    /// Return error if `labels` is empty.
+    fn get_and_append_challenges_var<E>(
+        &mut self,
+        labels: &[&'static [u8]],
+        circuit: &mut PlonkCircuit<F>,
+    ) -> Result<Vec<Variable>, PlonkError>
+    where
+        E: PairingEngine,
+    {
+        if labels.is_empty() {
+            return Err(ParameterError("the number of challenges is zero".to_string()).into());
+        }
+        labels
+            .iter()
+            .map(|label| self.get_and_append_challenge_var::<E>(label, circuit))
+            .collect()
+    }
+
+    /// Same as `get_and_append_challenge_var`, but also return the challenge
+    /// in its `FpElemVar` form, split with the parameter `m` that the verifier
+    /// circuit uses for `E::Fr` elements. The raw variable can be absorbed
+    /// further while the `FpElemVar` is used for non-native scalar
+    /// arithmetic. The challenge is already bounded by the truncation in
+    /// `get_and_append_challenge_var`, so no extra range gates are added.
+    fn get_and_append_challenge_with_fp<E>(
+        &mut self,
+        label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+    ) -> Result<(Variable, FpElemVar<F>), PlonkError>
+    where
+        E: PairingEngine,
+    {
+        let challenge_var = self.get_and_append_challenge_var::<E>(label, circuit)?;
+        let m = fr_split_param::<E>(circuit.resolve_range_bit_len()?);
+        let challenge_fp_elem_var = FpElemVar::new_unchecked(circuit, challenge_var, m, None)?;
+        Ok((challenge_var, challenge_fp_elem_var))
+    }
+}
+
 impl<F> RescueTranscriptVar<F>
+where
+    F: RescueParameter + SWToTEConParam,
+{
+    /// Number of variables that are absorbed but not yet hashed into the
+    /// state.
+    pub fn absorbed_len(&self) -> usize {
+        self.transcript_var.len()
+    }
+
+    /// Enable or disable the strict infinity mode (disabled by default).
+    ///
+    /// When enabled, appending a commitment variable constrains it not to be
+    /// the all-zero point (0, 0). Such a point is the raw SW encoding of the
+    /// point at infinity that was not converted to its TE form: the native
+    /// transcript absorbs the infinity point as the TE neutral point (0, 1),
+    /// so absorbing (0, 0) would silently diverge from it.
+    pub fn set_strict_infinity(&mut self, strict: bool) {
+        self.strict_infinity = strict;
+    }
+
+    /// Enable or disable the Merlin compatible mode (disabled by default).
+    ///
+    /// When enabled, `append_vk_and_pub_input_vars` additionally absorbs the
+    /// messages that are otherwise omitted for a cheaper verifier circuit,
+    /// in the same order as the Merlin transcript, as constant variables.
+    /// The native `RescueTranscript` must use the same mode.
+    pub fn set_merlin_compat(&mut self, enabled: bool) {
+        self.merlin_compat = enabled;
+    }
+
+    /// Enable or disable the absorption of labels (disabled by default).
+    ///
+    /// When enabled, the labels used since the last challenge, including the
+    /// label of the challenge itself, are hashed into a single tag that is
+    /// absorbed as a constant variable right before the challenge is
+    /// squeezed, as in `RescueTranscript::set_absorb_labels`. The native
+    /// `RescueTranscript` must use the same mode.
+    pub fn set_absorb_labels(&mut self, enabled: bool) {
+        self.absorb_labels = enabled;
+    }
+
+    /// Set the bit length of the challenges, or reset it to the default with
+    /// `None`.
+    ///
+    /// A custom bit length `bit_len` trades the soundness of the challenges,
+    /// which are uniform in `[0, 2^bit_len)`, against the number of range
+    /// gates of their truncation. It must be within
+    /// `[1, E::Fr::size_in_bits())`, which is checked when a challenge is
+    /// squeezed. The native `RescueTranscript` must use the same bit length.
+    pub fn set_challenge_bit_len(&mut self, bit_len: Option<usize>) {
+        self.challenge_bit_len = bit_len;
+    }
+
+    /// Run `f` within the domain `domain`: the domain-separation elements of
+    /// `RescueTranscript::with_domain` are absorbed as constant variables
+    /// before running `f`, and the closing ones after it succeeds. Nested calls
+    /// compose, so that the same appends under different domain stacks
+    /// produce different challenges.
+    pub fn with_domain<R>(
+        &mut self,
+        domain: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+        f: impl FnOnce(&mut Self, &mut PlonkCircuit<F>) -> Result<R, PlonkError>,
+    ) -> Result<R, PlonkError> {
+        for e in domain_separator_elems::<F>(domain, true) {
+            let var = circuit.create_constant_variable(e)?;
+            self.transcript_var.push(var);
+        }
+        let res = f(self, circuit)?;
+        for e in domain_separator_elems::<F>(domain, false) {
+            let var = circuit.create_constant_variable(e)?;
+            self.transcript_var.push(var);
+        }
+        Ok(res)
+    }
+
+    /// Derive an independent child transcript for the sub-protocol `label`.
+    ///
+    /// The child starts from the state and pending variables of this
+    /// transcript, followed by the fork separator of `RescueTranscript::fork`
+    /// for `label` as constant variables, so that it derives the same
+    /// challenges as the native child. This transcript is left unchanged.
+    pub fn fork(
+        &self,
+        label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+    ) -> Result<Self, PlonkError> {
+        let mut transcript_var = self.transcript_var.clone();
+        for e in fork_separator_elems::<F>(label) {
+            transcript_var.push(circuit.create_constant_variable(e)?);
+        }
+        Ok(Self {
+            transcript_var,
+            state_var: self.state_var,
+            permutation_count: self.permutation_count,
+            strict_infinity: self.strict_infinity,
+            merlin_compat: self.merlin_compat,
+            absorb_labels: self.absorb_labels,
+            labels: self.labels.clone(),
+            challenge_bit_len: self.challenge_bit_len,
+            _phantom: PhantomData::default(),
+        })
+    }
+
+    /// Iterate, in order, over the variables absorbed since the last
+    /// challenge was squeezed (the last challenge included).
+    pub fn iter_pending(&self) -> impl Iterator<Item = Variable> + '_ {
+        self.transcript_var.iter().copied()
+    }
+
+    /// Number of sponge invocations performed so far to squeeze challenges:
+    /// one per squeezed challenge, or, for `get_and_append_n_challenges_var`,
+    /// one per `RATE` challenges squeezed at once.
+    pub fn permutation_count(&self) -> usize {
+        self.permutation_count
+    }
+
+    /// The variables of the sponge state.
+    pub fn state_vars(&self) -> &[Variable; STATE_SIZE] {
+        &self.state_var
+    }
+
+    /// Export the Fiat-Shamir state of the transcript from the witnesses of
+    /// its variables, including the pending elements, the permutation count
+    /// and the Merlin compatible mode, as `RescueTranscript::export_state`
+    /// does.
+    pub fn export_state(
+        &self,
+        circuit: &PlonkCircuit<F>,
+    ) -> Result<RescueTranscriptState<F>, PlonkError> {
+        Ok(RescueTranscriptState {
+            state: self
+                .state_var
+                .iter()
+                .map(|&var| circuit.witness(var))
+                .collect::<Result<Vec<_>, _>>()?,
+            pending: self
+                .transcript_var
+                .iter()
+                .map(|&var| circuit.witness(var))
+                .collect::<Result<Vec<_>, _>>()?,
+            permutation_count: self.permutation_count,
+            merlin_compat: self.merlin_compat,
+            absorb_labels: self.absorb_labels,
+            labels: self.labels.clone(),
+            challenge_bit_len: self.challenge_bit_len,
+        })
+    }
+
+    /// Restore a transcript from an exported state, e.g., one produced by the
+    /// native `RescueTranscript::export_state`. The restored transcript
+    /// derives the same challenges as the original one would from that point
+    /// on.
+    ///
+    /// Fresh variables are created for the state and the pending elements:
+    /// they are not constrained, so the caller is responsible for binding
+    /// them, e.g., to public inputs or to the variables of the transcript that
+    /// produced the state, via `state_vars` and `iter_pending`.
+    pub fn from_state(
+        circuit: &mut PlonkCircuit<F>,
+        state: &RescueTranscriptState<F>,
+    ) -> Result<Self, PlonkError> {
+        state.check()?;
+        let mut state_var = [circuit.zero(); STATE_SIZE];
+        for (var, &value) in state_var.iter_mut().zip(state.state.iter()) {
+            *var = circuit.create_variable(value)?;
+        }
+        let transcript_var = state
+            .pending
+            .iter()
+            .map(|&value| circuit.create_variable(value))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            transcript_var,
+            state_var,
+            permutation_count: state.permutation_count,
+            strict_infinity: false,
+            merlin_compat: state.merlin_compat,
+            absorb_labels: state.absorb_labels,
+            labels: state.labels.clone(),
+            challenge_bit_len: state.challenge_bit_len,
+            _phantom: PhantomData::default(),
+        })
+    }
+
+    /// Append a variable representing a 64-bit integer to the transcript as
+    /// a single field element, matching `RescueTranscript::append_u64`. The
+    /// variable is constrained to be within `[0, 2^64)` via the range table.
+    /// `label` is omitted for efficiency, c.f. `set_absorb_labels`.
+    pub fn append_u64_var(
+        &mut self,
+        label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+        value: Variable,
+    ) -> Result<(), PlonkError> {
+        circuit.range_gate_with_lookup(value, 64)?;
+        self.append_variable(label, &value)
+    }
+
+    /// Append a non-native field element variable to the transcript as its
+    /// two limbs, without recomposing it with `convert_to_var`, matching
+    /// `RescueTranscript::append_fp_elem`. `label` is omitted for efficiency,
+    /// c.f. `set_absorb_labels`.
+    ///
+    /// The limbs must be range-checked, e.g., created with
+    /// `FpElemVar::new_from_field_element`: the decomposition is otherwise not
+    /// unique, and different limbs of the same element would derive different
+    /// challenges.
+    pub fn append_fp_elem_var(
+        &mut self,
+        label: &'static [u8],
+        fp_elem_var: &FpElemVar<F>,
+    ) -> Result<(), PlonkError> {
+        let (var0, var1) = fp_elem_var.components();
+        self.append_variable(label, &var0)?;
+        self.append_variable(label, &var1)
+    }
+
+    // Under `strict_infinity`, constrain a commitment variable not to be the
+    // unconverted SW point at infinity (0, 0).
+    // Cost: 6 constraints.
+    fn enforce_not_raw_infinity(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        poly_comm_var: &PointVariable,
+    ) -> Result<(), PlonkError> {
+        if self.strict_infinity {
+            let x_is_zero = circuit.is_zero(poly_comm_var.get_x())?;
+            let y_is_zero = circuit.is_zero(poly_comm_var.get_y())?;
+            let is_raw_infinity = circuit.logic_and(x_is_zero, y_is_zero)?;
+            circuit.enforce_false(is_raw_infinity.into())?;
+        }
+        Ok(())
+    }
+
+    /// Same as `get_and_append_challenge_var`, but instead of returning the
+    /// challenge, constrain it to be equal to `expected`, e.g., a public input
+    /// that an outer circuit references. The state is updated and the
+    /// challenge is appended to the transcript as usual.
+    pub fn get_and_enforce_challenge_var<E>(
+        &mut self,
+        label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+        expected: Variable,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine,
+    {
+        circuit.check_var_bound(expected)?;
+        let challenge_var = self.get_and_append_challenge_var::<E>(label, circuit)?;
+        circuit.equal_gate(challenge_var, expected)
+    }
+
+    /// Generate `n` challenge variables from a single sponge invocation, and
+    /// then append them, in order, to the transcript, matching
+    /// `RescueTranscript::get_and_append_n_challenges`. `label` is omitted
+    /// for efficiency, c.f. `set_absorb_labels`.
+    ///
+    /// The transcript is absorbed as in `get_and_append_challenge_var`, and
+    /// the i-th challenge is the i-th rate variable of the resulting state
+    /// truncated to the challenge bit length. If more than `RATE` challenges
+    /// are requested, the state is permuted again to squeeze the next `RATE`
+    /// ones. For `n = 1` this is the same as `get_and_append_challenge_var`.
+    pub fn get_and_append_n_challenges_var<E>(
+        &mut self,
+        label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+        n: usize,
+    ) -> Result<Vec<Variable>, PlonkError>
+    where
+        E: PairingEngine,
+    {
+        if n == 0 {
+            return Err(ParameterError("the number of challenges is zero".to_string()).into());
+        }
+        let challenge_bit_len = self.challenge_bit_len::<E>(circuit)?;
+
+        self.append_label_tag(label, circuit)?;
+        let permutation = Permutation::default();
+        let mut state_var = self.absorb_var(circuit)?;
+        let mut challenge_vars = Vec::with_capacity(n);
+        loop {
+            for out_var in state_var[..RATE].iter().take(n - challenge_vars.len()) {
+                challenge_vars.push(circuit.truncate(*out_var, challenge_bit_len)?);
+            }
+            if challenge_vars.len() == n {
+                break;
+            }
+            state_var = *circuit
+                .permutation_with_instance(&permutation, RescueStateVar::from(state_var))?
+                .array();
+        }
+
+        self.state_var = state_var;
+        self.transcript_var = Vec::new();
+        for challenge_var in challenge_vars.iter() {
+            self.append_challenge_var(label, challenge_var)?;
+        }
+        self.permutation_count += (n + RATE - 1) / RATE;
+
+        Ok(challenge_vars)
+    }
+
+    // Record `label` for the label tag of the current phase, if labels are
+    // absorbed and `label` is not a repetition of the previous label.
+    fn record_label(&mut self, label: &[u8]) {
+        if self.absorb_labels && self.labels.last().map(|l| l.as_slice()) != Some(label) {
+            self.labels.push(label.to_vec());
+        }
+    }
+
+    // If labels are absorbed, record the challenge label `label` and append
+    // the tag of the labels of the current phase as a constant variable.
+    fn append_label_tag(
+        &mut self,
+        label: &[u8],
+        circuit: &mut PlonkCircuit<F>,
+    ) -> Result<(), PlonkError> {
+        if self.absorb_labels {
+            self.record_label(label);
+            let tag_var = circuit.create_constant_variable(label_tag(&self.labels))?;
+            self.transcript_var.push(tag_var);
+            self.labels.clear();
+        }
+        Ok(())
+    }
+
+    // The bit length of the challenges, by default the lowest
+    // `E::Fr::size_in_bits() / 8` bytes of a squeezed element, c.f.
+    // `fq_to_fr_with_mask`, or the bit length set with `set_challenge_bit_len`.
+    // This requires the range table, and the scalar field of `E` to be smaller
+    // than the circuit field. In particular, BN254 is not supported: its scalar
+    // and base fields have the same bit length, and the Rescue parameters of
+    // its base field are mock parameters for testing only.
+    fn challenge_bit_len<E: PairingEngine>(
+        &self,
+        circuit: &PlonkCircuit<F>,
+    ) -> Result<usize, PlonkError> {
+        if !circuit.support_lookup() {
+            return Err(ParameterError("does not support range table".to_string()).into());
+        }
+
+        if E::Fr::size_in_bits() >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "scalar field ({} bits) is not smaller than the circuit field ({} bits)",
+                E::Fr::size_in_bits(),
+                F::size_in_bits()
+            ))
+            .into());
+        }
+        checked_challenge_bit_len::<E::Fr>(self.challenge_bit_len)
+    }
+
+    // Absorb the pending transcript variables into the state variables and
+    // return the resulting state, without updating `self`: the transcript is
+    // padded with a one, then with zeros to a multiple of RATE, and the blocks
+    // are added into the rate part of the state, each followed by a
+    // permutation. The capacity variable is threaded through as is, and the
+    // additions are merged into the first key addition of each permutation.
+    fn absorb_var(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+    ) -> Result<[Variable; STATE_SIZE], PlonkError> {
+        let mut padded_var = self.transcript_var.clone();
+        padded_var.push(circuit.one());
+        padded_var.resize((padded_var.len() + RATE - 1) / RATE * RATE, circuit.zero());
+        let permutation = Permutation::default();
+        let mut state_var = RescueStateVar::from(self.state_var);
+        for block in padded_var.chunks_exact(RATE) {
+            state_var = circuit.absorb_block_with_instance(
+                &permutation,
+                &state_var,
+                &[block[0], block[1], block[2]],
+            )?;
+        }
+        Ok(*state_var.array())
+    }
+}
+
+impl<F> PlonkTranscriptVar<F> for RescueTranscriptVar<F>
 where
     F: RescueParameter + SWToTEConParam,
 {
     /// create a new RescueTranscriptVar for a given circuit.
-    pub(crate) fn new(circuit: &mut PlonkCircuit<F>) -> Self {
+    fn new(circuit: &mut PlonkCircuit<F>) -> Self {
         Self {
             transcript_var: Vec::new(),
             state_var: [circuit.zero(); STATE_SIZE],
+            permutation_count: 0,
+            strict_infinity: false,
+            merlin_compat: false,
+            absorb_labels: false,
+            labels: Vec::new(),
+            challenge_bit_len: None,
             _phantom: PhantomData::default(),
         }
     }
 
     // append the verification key and the public input
-    pub(crate) fn append_vk_and_pub_input_vars<E: PairingEngine<Fq = F>>(
+    fn append_vk_and_pub_input_vars<E: PairingEngine<Fq = F>>(
         &mut self,
         circuit: &mut PlonkCircuit<F>,
         vk_var: &VerifyingKeyVar<E>,
@@ -59,6 +622,40 @@ where
         //  - domain size
         //  - number of inputs
         //  - wire subsets separators
+        // unless the Merlin compatible mode is enabled
+        if self.merlin_compat {
+            let field_size_var =
+                circuit.create_constant_variable(F::from(E::Fr::size_in_bits() as u64))?;
+            self.transcript_var.push(field_size_var);
+            let domain_size_var =
+                circuit.create_constant_variable(F::from(vk_var.domain_size as u64))?;
+            self.transcript_var.push(domain_size_var);
+            let num_inputs_var =
+                circuit.create_constant_variable(F::from(vk_var.num_inputs as u64))?;
+            self.transcript_var.push(num_inputs_var);
+            for ki in vk_var.k.iter() {
+                let ki_var = circuit.create_constant_variable(field_switching(ki))?;
+                self.transcript_var.push(ki_var);
+            }
+        }
+
+        // all public inputs must share the splitting parameter `m` that the
+        // verifier circuit uses for `E::Fr` elements, so that the absorbed
+        // values agree with the native transcript
+        if !pub_input.is_empty() {
+            let expected_m = fr_split_param::<E>(circuit.resolve_range_bit_len()?);
+            for (i, e) in pub_input.iter().enumerate() {
+                if e.param_m() != expected_m {
+                    return Err(ParameterError(format!(
+                        "the {}-th public input has splitting parameter {}, expected {}",
+                        i,
+                        e.param_m(),
+                        expected_m
+                    ))
+                    .into());
+                }
+            }
+        }
 
         // selector commitments
         for com in vk_var.selector_comms.iter() {
@@ -81,44 +678,34 @@ where
     }
 
     // Append the variable to the transcript.
-    // For efficiency purpose, label is not used for rescue FS.
-    pub(crate) fn append_variable(
-        &mut self,
-        _label: &'static [u8],
-        var: &Variable,
-    ) -> Result<(), PlonkError> {
+    // For efficiency purpose, label is not used for rescue FS, unless labels
+    // are absorbed.
+    fn append_variable(&mut self, label: &'static [u8], var: &Variable) -> Result<(), PlonkError> {
+        self.record_label(label);
         self.transcript_var.push(*var);
 
         Ok(())
     }
 
-    // Append the message variables to the transcript.
-    // For efficiency purpose, label is not used for rescue FS.
-    pub(crate) fn append_message_vars(
-        &mut self,
-        _label: &'static [u8],
-        msg_vars: &[Variable],
-    ) -> Result<(), PlonkError> {
-        for e in msg_vars.iter() {
-            self.append_variable(_label, e)?;
-        }
-
-        Ok(())
-    }
-
     // Append a commitment variable (in the form of PointVariable) to the
     // transcript. The caller needs to make sure that the commitment is
-    // already converted to TE form before generating the variables.
-    // For efficiency purpose, label is not used for rescue FS.
-    pub(crate) fn append_commitment_var<E, P>(
+    // already converted to TE form before generating the variables; in
+    // particular, the point at infinity is expected to be the TE neutral point
+    // (0, 1), consistently with the native transcript.
+    // For efficiency purpose, label is not used for rescue FS, unless labels
+    // are absorbed.
+    fn append_commitment_var<E, P>(
         &mut self,
-        _label: &'static [u8],
+        label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
         poly_comm_var: &PointVariable,
     ) -> Result<(), PlonkError>
     where
         E: PairingEngine<G1Affine = GroupAffine<P>>,
         P: SWModelParameters<BaseField = F>,
     {
+        self.enforce_not_raw_infinity(circuit, poly_comm_var)?;
+        self.record_label(label);
         // push the x and y coordinate of comm to the transcript
         self.transcript_var.push(poly_comm_var.get_x());
         self.transcript_var.push(poly_comm_var.get_y());
@@ -126,127 +713,866 @@ where
         Ok(())
     }
 
-    // Append  a slice of commitment variables (in the form of PointVariable) to the
-    // The caller needs to make sure that the commitment is
-    // already converted to TE form before generating the variables.
-    // transcript For efficiency purpose, label is not used for rescue FS.
-    pub(crate) fn append_commitments_vars<E, P>(
-        &mut self,
-        _label: &'static [u8],
-        poly_comm_vars: &[PointVariable],
-    ) -> Result<(), PlonkError>
-    where
-        E: PairingEngine<G1Affine = GroupAffine<P>>,
-        P: SWModelParameters<BaseField = F>,
-    {
-        for poly_comm_var in poly_comm_vars.iter() {
-            // push the x and y coordinate of comm to the transcript
-            self.transcript_var.push(poly_comm_var.get_x());
-            self.transcript_var.push(poly_comm_var.get_y());
-        }
-        Ok(())
-    }
-
-    // Append a challenge variable to the transcript.
-    // For efficiency purpose, label is not used for rescue FS.
-    pub(crate) fn append_challenge_var(
-        &mut self,
-        _label: &'static [u8],
-        challenge_var: &Variable,
-    ) -> Result<(), PlonkError> {
-        self.append_variable(_label, challenge_var)
-    }
-
-    // Append the proof evaluation to the transcript
-    pub(crate) fn append_proof_evaluations_vars<E: PairingEngine>(
+    // Append the plookup evaluations to the transcript. As the native
+    // `RescueTranscript::append_plookup_evaluations`, no label is recorded,
+    // even if labels are absorbed.
+    fn append_plookup_evaluations_vars<E: PairingEngine>(
         &mut self,
         circuit: &mut PlonkCircuit<F>,
-        evals: &ProofEvaluationsVar<F>,
+        evals: &PlookupEvaluationsVar<F>,
     ) -> Result<(), PlonkError> {
-        for e in &evals.wires_evals {
-            let tmp = e.convert_to_var(circuit)?;
-            self.transcript_var.push(tmp);
-        }
-        for e in &evals.wire_sigma_evals {
+        for e in evals
+            .evals_vec()
+            .iter()
+            .chain(evals.next_evals_vec().iter())
+        {
             let tmp = e.convert_to_var(circuit)?;
             self.transcript_var.push(tmp);
         }
-        let tmp = evals.perm_next_eval.convert_to_var(circuit)?;
-        self.transcript_var.push(tmp);
         Ok(())
     }
 
     // generate the challenge for the current transcript
     // and append it to the transcript
-    // For efficiency purpose, label is not used for rescue FS.
-    // Note that this function currently only supports bls12-377
-    // curve due to its decomposition method.
-    pub(crate) fn get_and_append_challenge_var<E>(
+    // For efficiency purpose, label is not used for rescue FS, unless labels
+    // are absorbed.
+    // The challenge is the lowest `E::Fr::size_in_bits() / 8` bytes of the
+    // squeezed element, c.f. `fq_to_fr_with_mask`, which requires the scalar
+    // field of `E` to be smaller than the circuit field.
+    fn get_and_append_challenge_var<E>(
         &mut self,
-        _label: &'static [u8],
+        label: &'static [u8],
         circuit: &mut PlonkCircuit<F>,
     ) -> Result<Variable, PlonkError>
     where
         E: PairingEngine,
     {
-        if !circuit.support_lookup() {
-            return Err(ParameterError("does not support range table".to_string()).into());
-        }
-
-        if E::Fr::size_in_bits() != 253 || E::Fq::size_in_bits() != 377 {
-            return Err(ParameterError(
-                "Curve Parameter does not support for rescue transcript circuit".to_string(),
-            )
-            .into());
-        }
+        let challenge_bit_len = self.challenge_bit_len::<E>(circuit)?;
+        self.append_label_tag(label, circuit)?;
 
         // ==================================
         // This algorithm takes in 3 steps
-        // 1. state: [F: STATE_SIZE] = hash(state|transcript)
+        // 1. state: [F: STATE_SIZE] = absorb(state, transcript)
         // 2. challenge = state[0] in Fr
         // 3. transcript = vec![challenge]
+        // c.f. `RescueTranscript` for the definition of `absorb`
         // ==================================
 
-        // step 1. state: [F: STATE_SIZE] = hash(state|transcript)
-        let input_var = [self.state_var.as_ref(), self.transcript_var.as_ref()].concat();
-        let res_var = circuit
-            .rescue_sponge_with_padding(&input_var, STATE_SIZE)
-            .unwrap();
+        // step 1. state: [F: STATE_SIZE] = absorb(state, transcript)
+        let res_var = self.absorb_var(circuit)?;
         let out_var = res_var[0];
 
         // step 2. challenge = state[0] in Fr
-        let challenge_var = circuit.truncate(out_var, 248)?;
+        let challenge_var = circuit.truncate(out_var, challenge_bit_len)?;
 
         // 3. transcript = vec![challenge]
         // finish and update the states
         self.state_var.copy_from_slice(&res_var[0..STATE_SIZE]);
         self.transcript_var = Vec::new();
-        self.append_challenge_var(_label, &challenge_var)?;
+        self.append_challenge_var(label, &challenge_var)?;
+        self.permutation_count += 1;
 
         Ok(challenge_var)
     }
+
+    // Generate one challenge per label in `labels` from a single sponge
+    // invocation, c.f. `RescueTranscript::get_and_append_challenges`.
+    fn get_and_append_challenges_var<E>(
+        &mut self,
+        labels: &[&'static [u8]],
+        circuit: &mut PlonkCircuit<F>,
+    ) -> Result<Vec<Variable>, PlonkError>
+    where
+        E: PairingEngine,
+    {
+        let (last, rest) = labels
+            .split_last()
+            .ok_or_else(|| ParameterError("the number of challenges is zero".to_string()))?;
+        for label in rest {
+            self.record_label(label);
+        }
+        self.get_and_append_n_challenges_var::<E>(last, circuit, labels.len())
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::{
-        circuit::customized::ecc::Point,
-        proof_system::structs::VerifyingKey,
-        transcript::{PlonkTranscript, RescueTranscript},
-    };
-    use ark_bls12_377::Bls12_377;
-    use ark_ec::{AffineCurve, ProjectiveCurve};
-    use ark_poly_commit::kzg10::{Commitment, VerifierKey};
-    use ark_std::{format, test_rng, UniformRand};
-    use jf_utils::{bytes_to_field_elements, field_switching};
+#[derive(Derivative)]
+#[derivative(Debug(bound = ""), Clone(bound = ""), Copy(bound = ""))]
+/// The challenge of a Rescue transcript absorbing the message variables, as a
+/// [`CircuitGadget`]. It matches the first challenge of a native
+/// `RescueTranscript` to which the same messages are appended.
+pub struct RescueTranscriptChallengeGadget<E> {
+    label: &'static [u8],
+    _phantom: PhantomData<E>,
+}
+
+impl<E: PairingEngine> RescueTranscriptChallengeGadget<E> {
+    /// A challenge with label `label`, whose value is an element of `E::Fr`.
+    pub fn new(label: &'static [u8]) -> Self {
+        Self {
+            label,
+            _phantom: PhantomData,
+        }
+    }
+}
+
+impl<F, E> CircuitGadget<F> for RescueTranscriptChallengeGadget<E>
+where
+    F: RescueParameter + SWToTEConParam,
+    E: PairingEngine,
+{
+    type Input = Vec<Variable>;
+    type Output = Variable;
+
+    fn name(&self) -> &'static str {
+        "rescue_transcript_challenge"
+    }
+
+    fn synthesize(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        msg_vars: Vec<Variable>,
+    ) -> Result<Variable, PlonkError> {
+        circuit.check_vars_bound(&msg_vars)?;
+        let mut transcript_var = RescueTranscriptVar::new(circuit);
+        transcript_var.append_message_vars(self.label, &msg_vars)?;
+        transcript_var.get_and_append_challenge_var::<E>(self.label, circuit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::customized::{ecc::Point, ultraplonk::mod_arith::FpElem},
+        proof_system::structs::VerifyingKey,
+        transcript::{PlonkTranscript, RescueTranscript},
+    };
+    use ark_bls12_377::{Bls12_377, Fr as Fr377};
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_ff::{field_new, BigInteger, Zero};
+    use ark_poly_commit::kzg10::{Commitment, VerifierKey};
+    use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+    use ark_std::{format, test_rng, vec, UniformRand};
+    use jf_utils::{bytes_to_field_elements, field_switching};
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+    // Number of gates of a Rescue permutation over a field with `A = 5`, e.g.
+    // the base field of BLS12-377: 4 gates for the first key addition, then 12
+    // rounds of 4 fifth-root gates and 4 affine gates, and 12 rounds of 4
+    // power-5 non-linear gates.
+    const RESCUE_PERMUTATION_GATES: usize = 4 + 12 * (4 + 4) + 12 * 4;
+    #[test]
+    fn test_rescue_transcript_challenge_circuit() {
+        test_rescue_transcript_challenge_circuit_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_challenge_circuit_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+
+        let label = "testing".as_ref();
+
+        let mut transcipt_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+
+        for _ in 0..10 {
+            for i in 0..10 {
+                let msg = format!("message {}", i);
+                let vals = bytes_to_field_elements(&msg);
+                let message_vars: Vec<Variable> = vals
+                    .iter()
+                    .map(|x| circuit.create_variable(*x).unwrap())
+                    .collect();
+
+                transcript.append_message(label, msg.as_bytes()).unwrap();
+
+                transcipt_var
+                    .append_message_vars(label, &message_vars)
+                    .unwrap();
+            }
+
+            let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+
+            let challenge_var = transcipt_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .unwrap();
+
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap().into_repr(),
+                field_switching::<_, F>(&challenge).into_repr()
+            );
+        }
+    }
+
+    #[test]
+    fn test_rescue_transcript_challenge_gadget() {
+        test_rescue_transcript_challenge_gadget_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_challenge_gadget_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+        let msg = "message";
+        let message_vars: Vec<Variable> = bytes_to_field_elements(msg)
+            .iter()
+            .map(|x| circuit.create_variable(*x).unwrap())
+            .collect();
+        let mut transcript = RescueTranscript::<F>::new(label);
+        transcript.append_message(label, msg.as_bytes()).unwrap();
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+
+        let gadget = RescueTranscriptChallengeGadget::<E>::new(label);
+        let (challenge_var, cost) = gadget
+            .synthesize_with_cost(&mut circuit, message_vars)
+            .unwrap();
+        assert_eq!(
+            circuit.witness(challenge_var).unwrap().into_repr(),
+            field_switching::<_, F>(&challenge).into_repr()
+        );
+        assert!(cost.num_gates > 0);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        assert!(gadget
+            .synthesize(&mut circuit, vec![circuit.num_vars()])
+            .is_err());
+    }
+
+    #[test]
+    fn test_rescue_transcript_challenge_with_fp() {
+        test_rescue_transcript_challenge_with_fp_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_challenge_with_fp_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+
+        let mut transcipt_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+        let m = fr_split_param::<E>(RANGE_BIT_LEN_FOR_TEST);
+
+        for i in 0..10 {
+            let msg = format!("message {}", i);
+            let vals = bytes_to_field_elements(&msg);
+            let message_vars: Vec<Variable> = vals
+                .iter()
+                .map(|x| circuit.create_variable(*x).unwrap())
+                .collect();
+            transcript.append_message(label, msg.as_bytes()).unwrap();
+            transcipt_var
+                .append_message_vars(label, &message_vars)
+                .unwrap();
+
+            let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+            let (challenge_var, challenge_fp_elem_var) = transcipt_var
+                .get_and_append_challenge_with_fp::<E>(label, &mut circuit)
+                .unwrap();
+
+            // the challenge still matches the native side
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap(),
+                field_switching::<_, F>(&challenge)
+            );
+            // both representations are consistent
+            assert_eq!(challenge_fp_elem_var.param_m(), m);
+            let converted_var = challenge_fp_elem_var.convert_to_var(&mut circuit).unwrap();
+            assert_eq!(
+                circuit.witness(converted_var).unwrap(),
+                circuit.witness(challenge_var).unwrap()
+            );
+            circuit.equal_gate(converted_var, challenge_var).unwrap();
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_rescue_transcript_enforce_challenge() {
+        test_rescue_transcript_enforce_challenge_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_enforce_challenge_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let label = "testing".as_ref();
+        let msg = "message".as_bytes();
+        let vals: Vec<F> = bytes_to_field_elements(msg);
+
+        let mut transcript = RescueTranscript::<F>::new(label);
+        transcript.append_message(label, msg).unwrap();
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+        transcript.append_message(label, msg).unwrap();
+        let next_challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+
+        for (expected, satisfiable) in [
+            (field_switching::<_, F>(&challenge), true),
+            (field_switching::<_, F>(&challenge) + F::one(), false),
+        ] {
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let mut transcipt_var = RescueTranscriptVar::new(&mut circuit);
+            let expected_var = circuit.create_public_variable(expected).unwrap();
+
+            let message_vars: Vec<Variable> = vals
+                .iter()
+                .map(|x| circuit.create_variable(*x).unwrap())
+                .collect();
+            transcipt_var
+                .append_message_vars(label, &message_vars)
+                .unwrap();
+            transcipt_var
+                .get_and_enforce_challenge_var::<E>(label, &mut circuit, expected_var)
+                .unwrap();
+
+            // the state is updated and the challenge re-absorbed as usual
+            let message_vars: Vec<Variable> = vals
+                .iter()
+                .map(|x| circuit.create_variable(*x).unwrap())
+                .collect();
+            transcipt_var
+                .append_message_vars(label, &message_vars)
+                .unwrap();
+            let next_challenge_var = transcipt_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .unwrap();
+            assert_eq!(
+                circuit.witness(next_challenge_var).unwrap(),
+                field_switching::<_, F>(&next_challenge)
+            );
+
+            assert_eq!(
+                circuit.check_circuit_satisfiability(&[expected]).is_ok(),
+                satisfiable
+            );
+        }
+
+        // out of bound variable
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut transcipt_var = RescueTranscriptVar::new(&mut circuit);
+        let num_vars = circuit.num_vars();
+        assert!(transcipt_var
+            .get_and_enforce_challenge_var::<E>(label, &mut circuit, num_vars + 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_rescue_transcript_challenge_regression() {
+        let expected = [
+            field_new!(
+                Fr377,
+                "134955339424686660453138282938376606741852176574351400236372035496381193125"
+            ),
+            field_new!(
+                Fr377,
+                "323111571822494663718610584521025353202310298387677850696285175505398671685"
+            ),
+            field_new!(
+                Fr377,
+                "238098183541728492089115076038706526096187378911493840873536559509079768867"
+            ),
+            field_new!(
+                Fr377,
+                "363177939764882998534194785954216104978198984563241259081124232344669097375"
+            ),
+            field_new!(
+                Fr377,
+                "254733366877913591273176031100488138173634930747936244052298804963892206546"
+            ),
+            field_new!(
+                Fr377,
+                "352798745257584630209671999956860949537716339026196731286516131655880700615"
+            ),
+            field_new!(
+                Fr377,
+                "172195043450922025406567111704212285708331080583638686840785496026811207724"
+            ),
+        ];
+        test_rescue_transcript_challenge_regression_helper::<Bls12_377, _, _>(&expected)
+    }
+    // Pin the challenges derived after absorbing 0, 1, 2, 3, 4, 5 and 11
+    // elements, which covers every padding length, to literal values.
+    //
+    // The values changed deliberately when the capacity element of the state
+    // stopped being re-absorbed as data (the transcript used to hash
+    // `state|transcript` with a fresh sponge), c.f. the CHANGELOG. Any other
+    // change of these values breaks the compatibility of the proofs.
+    fn test_rescue_transcript_challenge_regression_helper<E, F, P>(expected: &[E::Fr])
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+
+        for (num_elems, expected) in [0usize, 1, 2, 3, 4, 5, 11].iter().zip(expected.iter()) {
+            for i in 0..*num_elems {
+                let value = (num_elems * 100 + i) as u64;
+                let var = circuit.create_variable(F::from(value)).unwrap();
+                transcript.append_u64(label, value).unwrap();
+                transcript_var.append_variable(label, &var).unwrap();
+            }
+
+            let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+            let challenge_var = transcript_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .unwrap();
+            assert_eq!(challenge, *expected);
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap(),
+                field_switching(expected)
+            );
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_rescue_transcript_n_challenges() {
+        test_rescue_transcript_n_challenges_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_n_challenges_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+        // a transcript squeezing one challenge at a time
+        let mut single_transcript = RescueTranscript::<F>::new(label);
+
+        for (i, n) in [1usize, 2, 3, 4, 7].iter().enumerate() {
+            let value = i as u64 + 1;
+            let var = circuit.create_variable(F::from(value)).unwrap();
+            transcript.append_u64(label, value).unwrap();
+            single_transcript.append_u64(label, value).unwrap();
+            transcript_var.append_variable(label, &var).unwrap();
+
+            let challenges = transcript
+                .get_and_append_n_challenges::<E>(label, *n)
+                .unwrap();
+            let challenge_vars = transcript_var
+                .get_and_append_n_challenges_var::<E>(label, &mut circuit, *n)
+                .unwrap();
+            assert_eq!(challenges.len(), *n);
+            assert_eq!(challenge_vars.len(), *n);
+            for (challenge, challenge_var) in challenges.iter().zip(challenge_vars.iter()) {
+                assert_eq!(
+                    circuit.witness(*challenge_var).unwrap(),
+                    field_switching(challenge)
+                );
+            }
+            // the challenges are distinct
+            for j in 1..*n {
+                assert!(!challenges[..j].contains(&challenges[j]));
+            }
+            // a single challenge is the same as `get_and_append_challenge`
+            if *n == 1 {
+                let challenge = single_transcript
+                    .get_and_append_challenge::<E>(label)
+                    .unwrap();
+                assert_eq!(challenges[0], challenge);
+            }
+        }
+        // one sponge invocation per `RATE` challenges of a batch
+        assert_eq!(transcript.permutation_count(), 1 + 1 + 1 + 2 + 3);
+        assert_eq!(transcript_var.permutation_count(), 1 + 1 + 1 + 2 + 3);
+
+        // the transcripts stay in sync afterwards
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+        let challenge_var = transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        assert_eq!(
+            circuit.witness(challenge_var).unwrap(),
+            field_switching(&challenge)
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // zero challenges are rejected
+        assert!(transcript
+            .get_and_append_n_challenges::<E>(label, 0)
+            .is_err());
+        assert!(transcript_var
+            .get_and_append_n_challenges_var::<E>(label, &mut circuit, 0)
+            .is_err());
+    }
+
+    #[test]
+    fn test_rescue_transcript_challenges_with_labels() {
+        test_rescue_transcript_challenges_with_labels_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_challenges_with_labels_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let labels = [b"beta".as_ref(), b"gamma".as_ref()];
+        for absorb_labels in [false, true] {
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+            let mut transcript = RescueTranscript::<F>::new(b"testing");
+            // a transcript squeezing the same number of challenges at once
+            let mut n_transcript = RescueTranscript::<F>::new(b"testing");
+            transcript_var.set_absorb_labels(absorb_labels);
+            transcript.set_absorb_labels(absorb_labels);
+            n_transcript.set_absorb_labels(absorb_labels);
+
+            let var = circuit.create_variable(F::from(7u64)).unwrap();
+            transcript.append_u64(b"msg", 7).unwrap();
+            n_transcript.append_u64(b"msg", 7).unwrap();
+            transcript_var.append_variable(b"msg", &var).unwrap();
+
+            let challenges = transcript.get_and_append_challenges::<E>(&labels).unwrap();
+            let challenge_vars = transcript_var
+                .get_and_append_challenges_var::<E>(&labels, &mut circuit)
+                .unwrap();
+            for (challenge, challenge_var) in challenges.iter().zip(challenge_vars.iter()) {
+                assert_eq!(
+                    circuit.witness(*challenge_var).unwrap(),
+                    field_switching(challenge)
+                );
+            }
+            assert_eq!(transcript.permutation_count(), 1);
+            assert_eq!(transcript_var.permutation_count(), 1);
+            // without labels, this is `get_and_append_n_challenges`
+            let n_challenges = n_transcript
+                .get_and_append_n_challenges::<E>(b"gamma", 2)
+                .unwrap();
+            assert_eq!(challenges == n_challenges, !absorb_labels);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+
+        // no label is rejected
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(b"testing");
+        assert!(transcript.get_and_append_challenges::<E>(&[]).is_err());
+        assert!(transcript_var
+            .get_and_append_challenges_var::<E>(&[], &mut circuit)
+            .is_err());
+    }
+
+    #[test]
+    fn test_rescue_transcript_state_resume() {
+        test_rescue_transcript_state_resume_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_state_resume_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+        transcript.set_merlin_compat(true);
+        transcript_var.set_merlin_compat(true);
+
+        for value in 1..5u64 {
+            let var = circuit.create_variable(F::from(value)).unwrap();
+            transcript.append_u64(label, value).unwrap();
+            transcript_var.append_variable(label, &var).unwrap();
+        }
+        transcript.get_and_append_challenge::<E>(label).unwrap();
+        transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        transcript.append_u64(label, 42).unwrap();
+        let var = circuit.create_variable(F::from(42u64)).unwrap();
+        transcript_var.append_variable(label, &var).unwrap();
+
+        // the native state and the state of the circuit agree
+        let state = transcript.export_state();
+        assert_eq!(transcript_var.export_state(&circuit).unwrap(), state);
+        assert_eq!(state.pending.len(), transcript.absorbed_len());
+
+        // round trip through serialization
+        let mut bytes = Vec::new();
+        state.serialize(&mut bytes).unwrap();
+        let state = RescueTranscriptState::<F>::deserialize(&bytes[..]).unwrap();
+
+        // the resumed transcripts derive the same challenges as the original
+        let mut resumed = RescueTranscript::from_state(&state).unwrap();
+        let mut resumed_var = RescueTranscriptVar::from_state(&mut circuit, &state).unwrap();
+        assert_eq!(resumed.permutation_count(), 1);
+        assert_eq!(resumed_var.permutation_count(), 1);
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+        let challenge_var = transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        assert_eq!(
+            resumed.get_and_append_challenge::<E>(label).unwrap(),
+            challenge
+        );
+        let resumed_challenge_var = resumed_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        assert_eq!(
+            circuit.witness(resumed_challenge_var).unwrap(),
+            circuit.witness(challenge_var).unwrap()
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a malformed state is rejected
+        let mut bad_state = state;
+        bad_state.state.pop();
+        assert!(RescueTranscript::from_state(&bad_state).is_err());
+        assert!(RescueTranscriptVar::from_state(&mut circuit, &bad_state).is_err());
+    }
+
+    #[test]
+    fn test_rescue_transcript_absorb_labels() {
+        test_rescue_transcript_absorb_labels_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_absorb_labels_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let msg = b"message";
+        let native_challenges = |absorb_labels: bool, msg_label: &'static [u8]| {
+            let mut transcript = RescueTranscript::<F>::new(b"testing");
+            transcript.set_absorb_labels(absorb_labels);
+            let mut challenges = vec![];
+            for _ in 0..3 {
+                transcript.append_message(msg_label, msg).unwrap();
+                transcript.append_u64(b"counter", 7).unwrap();
+                challenges.push(transcript.get_and_append_challenge::<E>(b"chal").unwrap());
+            }
+            challenges
+        };
+
+        // the labels are bound to the challenges only when they are absorbed
+        let challenges = native_challenges(true, b"msg");
+        assert_ne!(challenges, native_challenges(false, b"msg"));
+        assert_ne!(challenges, native_challenges(true, b"other msg"));
+        assert_eq!(
+            native_challenges(false, b"msg"),
+            native_challenges(false, b"other msg")
+        );
+
+        // the circuit is in sync with the native transcript
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        transcript_var.set_absorb_labels(true);
+        for challenge in challenges.iter() {
+            let message_vars: Vec<Variable> = bytes_to_field_elements::<_, F>(msg)
+                .iter()
+                .map(|x| circuit.create_variable(*x).unwrap())
+                .collect();
+            transcript_var
+                .append_message_vars(b"msg", &message_vars)
+                .unwrap();
+            let counter_var = circuit.create_variable(F::from(7u64)).unwrap();
+            transcript_var
+                .append_u64_var(b"counter", &mut circuit, counter_var)
+                .unwrap();
+            let challenge_var = transcript_var
+                .get_and_append_challenge_var::<E>(b"chal", &mut circuit)
+                .unwrap();
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap(),
+                field_switching(challenge)
+            );
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_rescue_transcript_challenge_bit_len() {
+        test_rescue_transcript_challenge_bit_len_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_challenge_bit_len_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let label = "testing".as_ref();
+        let default_bit_len = (E::Fr::size_in_bits() >> 3) << 3;
+        for bit_len in [
+            None,
+            Some(1),
+            Some(64),
+            Some(128),
+            Some(131),
+            Some(default_bit_len),
+            Some(E::Fr::size_in_bits() - 1),
+        ] {
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+            let mut transcript = RescueTranscript::<F>::new(label);
+            let mut default_transcript = RescueTranscript::<F>::new(label);
+            transcript.set_challenge_bit_len(bit_len);
+            transcript_var.set_challenge_bit_len(bit_len);
+
+            for value in 0..3u64 {
+                let var = circuit.create_variable(F::from(value)).unwrap();
+                transcript.append_u64(label, value).unwrap();
+                default_transcript.append_u64(label, value).unwrap();
+                transcript_var.append_variable(label, &var).unwrap();
+
+                let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+                let challenge_var = transcript_var
+                    .get_and_append_challenge_var::<E>(label, &mut circuit)
+                    .unwrap();
+                assert_eq!(
+                    circuit.witness(challenge_var).unwrap(),
+                    field_switching(&challenge)
+                );
+                let expected_bit_len = bit_len.unwrap_or(default_bit_len);
+                assert!(challenge.into_repr().num_bits() as usize <= expected_bit_len);
+                if expected_bit_len == default_bit_len {
+                    let default_challenge = default_transcript
+                        .get_and_append_challenge::<E>(label)
+                        .unwrap();
+                    assert_eq!(challenge, default_challenge);
+                }
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+
+        // out of range bit lengths are rejected
+        for bit_len in [0, E::Fr::size_in_bits()] {
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+            let mut transcript = RescueTranscript::<F>::new(label);
+            transcript.set_challenge_bit_len(Some(bit_len));
+            transcript_var.set_challenge_bit_len(Some(bit_len));
+            assert!(transcript.get_and_append_challenge::<E>(label).is_err());
+            assert!(transcript_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn test_rescue_transcript_fork() {
+        test_rescue_transcript_fork_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_fork_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+        transcript.append_u64(label, 1).unwrap();
+        let var = circuit.create_variable(F::one()).unwrap();
+        transcript_var.append_variable(label, &var).unwrap();
+
+        // forking leaves the parent unchanged
+        let mut reference = RescueTranscript::<F>::new(label);
+        reference.append_u64(label, 1).unwrap();
+
+        let mut challenges = vec![];
+        let fork_labels = [&b"instance"[..], &b"instance"[..], &b"other"[..]];
+        for (i, fork_label) in fork_labels.iter().enumerate() {
+            let mut child = transcript.fork(*fork_label);
+            let mut child_var = transcript_var.fork(*fork_label, &mut circuit).unwrap();
+            child.append_u64(label, i as u64).unwrap();
+            let index_var = circuit.create_variable(F::from(i as u64)).unwrap();
+            child_var.append_variable(label, &index_var).unwrap();
+
+            let challenge = child.get_and_append_challenge::<E>(label).unwrap();
+            let challenge_var = child_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .unwrap();
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap(),
+                field_switching(&challenge)
+            );
+            challenges.push(challenge);
+        }
+
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+        let challenge_var = transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        assert_eq!(
+            circuit.witness(challenge_var).unwrap(),
+            field_switching(&challenge)
+        );
+        assert_eq!(
+            challenge,
+            reference.get_and_append_challenge::<E>(label).unwrap()
+        );
+        challenges.push(challenge);
+
+        // the children and the parent derive different challenges
+        for i in 1..challenges.len() {
+            assert!(!challenges[..i].contains(&challenges[i]));
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_rescue_transcript_append_fp_elem() {
+        test_rescue_transcript_append_fp_elem_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_append_fp_elem_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut rng = test_rng();
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+        let m = fr_split_param::<E>(RANGE_BIT_LEN_FOR_TEST);
+
+        for _ in 0..3 {
+            let x = field_switching::<_, F>(&E::Fr::rand(&mut rng));
+            let fp_elem = FpElem::new(&x, m, None).unwrap();
+            let fp_elem_var = FpElemVar::new_from_fp_elem(&mut circuit, &fp_elem, m, None).unwrap();
+            let num_gates = circuit.num_gates();
+            transcript.append_fp_elem(label, &fp_elem).unwrap();
+            transcript_var
+                .append_fp_elem_var(label, &fp_elem_var)
+                .unwrap();
+            // no recomposition gate
+            assert_eq!(circuit.num_gates(), num_gates);
+            assert_eq!(transcript_var.absorbed_len(), transcript.absorbed_len());
+
+            let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+            let challenge_var = transcript_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .unwrap();
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap(),
+                field_switching(&challenge)
+            );
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+    }
 
-    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
     #[test]
-    fn test_rescue_transcript_challenge_circuit() {
-        test_rescue_transcript_challenge_circuit_helper::<Bls12_377, _, _>()
+    fn test_rescue_transcript_metering() {
+        test_rescue_transcript_metering_helper::<Bls12_377, _, _>()
     }
-    fn test_rescue_transcript_challenge_circuit_helper<E, F, P>()
+    fn test_rescue_transcript_metering_helper<E, F, P>()
     where
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         F: RescueParameter + SWToTEConParam,
@@ -256,35 +1582,277 @@ mod tests {
 
         let label = "testing".as_ref();
 
-        let mut transcipt_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
         let mut transcript = RescueTranscript::<F>::new(label);
+        assert_eq!(transcript.absorbed_len(), 0);
+        assert_eq!(transcript_var.absorbed_len(), 0);
+        assert_eq!(transcript.permutation_count(), 0);
+        assert_eq!(transcript_var.permutation_count(), 0);
 
-        for _ in 0..10 {
-            for i in 0..10 {
+        let num_msgs = 7;
+        let num_challenges = 5;
+        for j in 0..num_challenges {
+            let mut expected_len = transcript.absorbed_len();
+            for i in 0..num_msgs {
                 let msg = format!("message {}", i);
                 let vals = bytes_to_field_elements(&msg);
                 let message_vars: Vec<Variable> = vals
                     .iter()
                     .map(|x| circuit.create_variable(*x).unwrap())
                     .collect();
+                expected_len += vals.len();
 
                 transcript.append_message(label, msg.as_bytes()).unwrap();
-
-                transcipt_var
+                transcript_var
                     .append_message_vars(label, &message_vars)
                     .unwrap();
             }
+            assert_eq!(transcript.absorbed_len(), expected_len);
+            assert_eq!(transcript_var.absorbed_len(), expected_len);
 
-            let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+            transcript.get_and_append_challenge::<E>(label).unwrap();
+            transcript_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .unwrap();
 
-            let challenge_var = transcipt_var
+            // only the fresh challenge remains pending
+            assert_eq!(transcript.absorbed_len(), 1);
+            assert_eq!(transcript_var.absorbed_len(), 1);
+            assert_eq!(transcript.permutation_count(), j + 1);
+            assert_eq!(transcript_var.permutation_count(), j + 1);
+        }
+        assert_eq!(transcript.permutation_count(), num_challenges);
+        assert_eq!(transcript_var.permutation_count(), num_challenges);
+    }
+
+    #[test]
+    fn test_rescue_transcript_iter_pending() {
+        test_rescue_transcript_iter_pending_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_iter_pending_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+        assert_eq!(transcript.iter_pending().count(), 0);
+        assert_eq!(transcript_var.iter_pending().count(), 0);
+
+        let mut expected_vals = Vec::new();
+        let mut expected_vars = Vec::new();
+        for i in 0..5 {
+            let msg = format!("message {}", i);
+            let vals = bytes_to_field_elements(&msg);
+            let message_vars: Vec<Variable> = vals
+                .iter()
+                .map(|x| circuit.create_variable(*x).unwrap())
+                .collect();
+            transcript.append_message(label, msg.as_bytes()).unwrap();
+            transcript_var
+                .append_message_vars(label, &message_vars)
+                .unwrap();
+            expected_vals.extend_from_slice(&vals);
+            expected_vars.extend_from_slice(&message_vars);
+
+            assert_eq!(transcript.iter_pending().collect::<Vec<_>>(), expected_vals);
+            assert_eq!(
+                transcript_var.iter_pending().collect::<Vec<_>>(),
+                expected_vars
+            );
+        }
+
+        // after squeezing, only the challenge remains pending
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+        let challenge_var = transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        assert_eq!(
+            transcript.iter_pending().collect::<Vec<_>>(),
+            vec![field_switching::<_, F>(&challenge)]
+        );
+        assert_eq!(
+            transcript_var.iter_pending().collect::<Vec<_>>(),
+            vec![challenge_var]
+        );
+    }
+
+    #[test]
+    fn test_rescue_transcript_challenge_overhead() {
+        test_rescue_transcript_challenge_overhead_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_challenge_overhead_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+
+        // squeeze a first challenge so that every following squeeze
+        // absorbs the same number of elements
+        transcript.get_and_append_challenge::<E>(label).unwrap();
+        transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+
+        // `RESCUE_PERMUTATION_GATES` is pinned for `A = 5`
+        assert_eq!(F::A, 5);
+        // the truncation of the squeezed element to a challenge, measured on
+        // its own
+        let (truncate_gates, truncate_vars) = {
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let var = circuit.create_variable(F::from(42u64)).unwrap();
+            let (num_gates, num_vars) = (circuit.num_gates(), circuit.num_vars());
+            circuit
+                .truncate(var, checked_challenge_bit_len::<E::Fr>(None).unwrap())
+                .unwrap();
+            (
+                circuit.num_gates() - num_gates,
+                circuit.num_vars() - num_vars,
+            )
+        };
+
+        let (num_gates, num_vars) = (circuit.num_gates(), circuit.num_vars());
+        for i in 0..10 {
+            let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+            let challenge_var = transcript_var
                 .get_and_append_challenge_var::<E>(label, &mut circuit)
                 .unwrap();
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap(),
+                field_switching(&challenge)
+            );
+            assert_eq!(transcript_var.permutation_count(), i + 2);
+        }
+        // Each squeeze absorbs the previous challenge, padded to a single
+        // block, with a single permutation whose first key addition also adds
+        // the block into the rate, and the truncation. The round keys are
+        // embedded as selectors, so no constant variable is created and each
+        // gate has a single fresh output variable.
+        let per_challenge_gates = RESCUE_PERMUTATION_GATES + truncate_gates;
+        let per_challenge_vars = RESCUE_PERMUTATION_GATES + truncate_vars;
+        assert_eq!(circuit.num_gates() - num_gates, 10 * per_challenge_gates);
+        assert_eq!(circuit.num_vars() - num_vars, 10 * per_challenge_vars);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+    }
+
+    #[test]
+    fn test_rescue_transcript_append_u64_circuit() {
+        test_rescue_transcript_append_u64_circuit_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_append_u64_circuit_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F>,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let label = "testing".as_ref();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript = RescueTranscript::<F>::new(label);
+
+        for &value in [0u64, 1, 42, 1 << 32, u64::MAX].iter() {
+            let var = circuit.create_variable(F::from(value)).unwrap();
+            transcript.append_u64(label, value).unwrap();
+            transcript_var
+                .append_u64_var(label, &mut circuit, var)
+                .unwrap();
+        }
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+        let challenge_var = transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        assert_eq!(
+            circuit.witness(challenge_var).unwrap(),
+            field_switching(&challenge)
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a value exceeding 2^64
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        let var = circuit
+            .create_variable(F::from(u64::MAX) + F::one())
+            .unwrap();
+        transcript_var
+            .append_u64_var(label, &mut circuit, var)
+            .unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+    }
+
+    #[test]
+    fn test_rescue_transcript_infinity_commitment() {
+        test_rescue_transcript_infinity_commitment_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_infinity_commitment_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let mut rng = test_rng();
+        let label = "testing".as_ref();
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        transcript_var.set_strict_infinity(true);
+        let mut transcript = RescueTranscript::<F>::new(label);
+
+        let infinity = Commitment::<E>(GroupAffine::<P>::zero());
+        let comm = Commitment::<E>(E::G1Projective::rand(&mut rng).into_affine());
+        let infinity_var = circuit.create_point_variable((&infinity.0).into()).unwrap();
+        let comm_var = circuit.create_point_variable((&comm.0).into()).unwrap();
+        assert_eq!(
+            circuit.point_witness(&infinity_var).unwrap(),
+            Point::from(&infinity.0)
+        );
+
+        transcript.append_commitment(label, &infinity).unwrap();
+        transcript
+            .append_commitments(label, &[comm, infinity])
+            .unwrap();
+        transcript_var
+            .append_commitment_var::<E, P>(label, &mut circuit, &infinity_var)
+            .unwrap();
+        transcript_var
+            .append_commitments_vars::<E, P>(label, &mut circuit, &[comm_var, infinity_var])
+            .unwrap();
+
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+        let challenge_var = transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        assert_eq!(
+            circuit.witness(challenge_var).unwrap(),
+            field_switching(&challenge)
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
 
+        // an unconverted point at infinity, i.e. the SW point (0, 0), violates
+        // the constraints under strict mode only
+        let raw_infinity = GroupAffine::<P>::new(F::zero(), F::zero(), false);
+        for strict in [true, false] {
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+            transcript_var.set_strict_infinity(strict);
+            let comm_var = circuit.create_point_variable((&comm.0).into()).unwrap();
+            let raw_infinity_var = circuit
+                .create_point_variable(Point::from(raw_infinity))
+                .unwrap();
             assert_eq!(
-                circuit.witness(challenge_var).unwrap().into_repr(),
-                field_switching::<_, F>(&challenge).into_repr()
+                circuit.point_witness(&raw_infinity_var).unwrap(),
+                Point::from(raw_infinity)
             );
+            transcript_var
+                .append_commitments_vars::<E, P>(label, &mut circuit, &[comm_var, raw_infinity_var])
+                .unwrap();
+            assert_eq!(circuit.check_circuit_satisfiability(&[]).is_ok(), !strict);
         }
     }
 
@@ -325,6 +1893,7 @@ mod tests {
             open_key: open_key.clone(),
             is_merged: false,
             plookup_vk: None,
+            pub_input_names: vec![],
         };
 
         let dummy_vk_var = VerifyingKeyVar::new(&mut circuit, &dummy_vk).unwrap();
@@ -384,6 +1953,7 @@ mod tests {
                 open_key: open_key.clone(),
                 is_merged: false,
                 plookup_vk: None,
+                pub_input_names: vec![],
             };
             let vk_var = VerifyingKeyVar::new(&mut circuit, &vk).unwrap();
 
@@ -415,4 +1985,243 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_rescue_transcript_with_domain() {
+        test_rescue_transcript_with_domain_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_with_domain_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let label = "testing".as_ref();
+        let msg = "the same message".as_bytes();
+
+        // the challenge of absorbing `msg` under the domain stack `domains`,
+        // after checking that the native and circuit transcripts agree
+        let challenge_under = |domains: &[&'static [u8]]| -> E::Fr {
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+            let mut transcript = RescueTranscript::<F>::new(label);
+
+            fn absorb<F: RescueParameter + SWToTEConParam>(
+                transcript: &mut RescueTranscript<F>,
+                domains: &[&'static [u8]],
+                msg: &[u8],
+            ) -> Result<(), PlonkError> {
+                match domains.split_first() {
+                    None => transcript.append_message(b"msg", msg),
+                    Some((domain, rest)) => {
+                        transcript.with_domain(*domain, |t| absorb(t, rest, msg))
+                    },
+                }
+            }
+            fn absorb_var<F: RescueParameter + SWToTEConParam>(
+                transcript_var: &mut RescueTranscriptVar<F>,
+                circuit: &mut PlonkCircuit<F>,
+                domains: &[&'static [u8]],
+                msg: &[u8],
+            ) -> Result<(), PlonkError> {
+                match domains.split_first() {
+                    None => {
+                        let msg_vars = bytes_to_field_elements::<_, F>(msg)
+                            .iter()
+                            .map(|x| circuit.create_variable(*x))
+                            .collect::<Result<Vec<_>, _>>()?;
+                        transcript_var.append_message_vars(b"msg", &msg_vars)
+                    },
+                    Some((domain, rest)) => transcript_var
+                        .with_domain(*domain, circuit, |t, c| absorb_var(t, c, rest, msg)),
+                }
+            }
+
+            absorb(&mut transcript, domains, msg).unwrap();
+            absorb_var(&mut transcript_var, &mut circuit, domains, msg).unwrap();
+
+            let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+            let challenge_var = transcript_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .unwrap();
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap(),
+                field_switching(&challenge)
+            );
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            challenge
+        };
+
+        let stacks: [&[&'static [u8]]; 6] = [
+            &[],
+            &[&b"outer"[..]],
+            &[&b"inner"[..]],
+            &[&b"outer"[..], &b"inner"[..]],
+            &[&b"inner"[..], &b"outer"[..]],
+            &[&b"outer\0"[..]],
+        ];
+        let challenges: Vec<E::Fr> = stacks.iter().map(|s| challenge_under(*s)).collect();
+
+        // different domain stacks produce different challenges
+        for i in 0..challenges.len() {
+            for j in i + 1..challenges.len() {
+                assert_ne!(challenges[i], challenges[j]);
+            }
+        }
+        // identical domain stacks produce identical challenges
+        for (stack, challenge) in stacks.iter().zip(challenges.iter()) {
+            assert_eq!(challenge_under(*stack), *challenge);
+        }
+    }
+
+    #[test]
+    fn test_rescue_transcript_merlin_compat() {
+        test_rescue_transcript_merlin_compat_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_merlin_compat_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let mut rng = test_rng();
+        let label = "testing".as_ref();
+        let m = 128;
+
+        let open_key: VerifierKey<E> = VerifierKey {
+            g: E::G1Affine::prime_subgroup_generator(),
+            gamma_g: E::G1Projective::rand(&mut rng).into_affine(),
+            h: E::G2Affine::prime_subgroup_generator(),
+            beta_h: E::G2Projective::rand(&mut rng).into_affine(),
+            prepared_h: E::G2Affine::prime_subgroup_generator().into(),
+            prepared_beta_h: E::G2Projective::rand(&mut rng).into_affine().into(),
+        };
+        let input: Vec<E::Fr> = (0..4).map(|_| E::Fr::rand(&mut rng)).collect();
+        let vk = VerifyingKey {
+            domain_size: 512,
+            num_inputs: input.len(),
+            sigma_comms: (0..5)
+                .map(|_| Commitment(E::G1Projective::rand(&mut rng).into_affine()))
+                .collect(),
+            selector_comms: (0..13)
+                .map(|_| Commitment(E::G1Projective::rand(&mut rng).into_affine()))
+                .collect(),
+            k: (0..5).map(|_| E::Fr::rand(&mut rng)).collect(),
+            open_key,
+            is_merged: false,
+            plookup_vk: None,
+            pub_input_names: vec![],
+        };
+
+        let mut challenges = Vec::new();
+        for merlin_compat in [false, true] {
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+            let mut transcript = RescueTranscript::<F>::new(label);
+            transcript_var.set_merlin_compat(merlin_compat);
+            transcript.set_merlin_compat(merlin_compat);
+
+            let vk_var = VerifyingKeyVar::new(&mut circuit, &vk).unwrap();
+            let input_fp_elem_vars: Vec<FpElemVar<F>> = input
+                .iter()
+                .map(|x| {
+                    let var = circuit.create_public_variable(field_switching(x)).unwrap();
+                    FpElemVar::new_unchecked(&mut circuit, var, m, None).unwrap()
+                })
+                .collect();
+
+            transcript.append_vk_and_pub_input(&vk, &input).unwrap();
+            transcript_var
+                .append_vk_and_pub_input_vars::<E>(&mut circuit, &vk_var, &input_fp_elem_vars)
+                .unwrap();
+
+            let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+            let challenge_var = transcript_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .unwrap();
+
+            // both sides agree under either mode
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap(),
+                field_switching(&challenge)
+            );
+            let pub_input: Vec<F> = input.iter().map(field_switching).collect();
+            assert!(circuit.check_circuit_satisfiability(&pub_input).is_ok());
+            challenges.push(challenge);
+        }
+
+        // the extra messages change the challenge
+        assert_ne!(challenges[0], challenges[1]);
+    }
+
+    #[test]
+    fn test_rescue_transcript_append_pub_input_limb_sizes() {
+        test_rescue_transcript_append_pub_input_limb_sizes_helper::<Bls12_377, _, _>()
+    }
+    fn test_rescue_transcript_append_pub_input_limb_sizes_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut rng = test_rng();
+
+        let open_key: VerifierKey<E> = VerifierKey {
+            g: E::G1Affine::prime_subgroup_generator(),
+            gamma_g: E::G1Projective::rand(&mut rng).into_affine(),
+            h: E::G2Affine::prime_subgroup_generator(),
+            beta_h: E::G2Projective::rand(&mut rng).into_affine(),
+            prepared_h: E::G2Affine::prime_subgroup_generator().into(),
+            prepared_beta_h: E::G2Projective::rand(&mut rng).into_affine().into(),
+        };
+        let vk = VerifyingKey {
+            domain_size: 512,
+            num_inputs: 2,
+            sigma_comms: Vec::new(),
+            selector_comms: Vec::new(),
+            k: Vec::new(),
+            open_key,
+            is_merged: false,
+            plookup_vk: None,
+            pub_input_names: vec![],
+        };
+        let vk_var = VerifyingKeyVar::new(&mut circuit, &vk).unwrap();
+
+        let input: Vec<E::Fr> = (0..2).map(|_| E::Fr::rand(&mut rng)).collect();
+        let input_vars: Vec<Variable> = input
+            .iter()
+            .map(|&x| circuit.create_public_variable(field_switching(&x)).unwrap())
+            .collect();
+
+        // consistent limb sizes
+        let consistent: Vec<FpElemVar<F>> = input_vars
+            .iter()
+            .map(|&x| FpElemVar::new_unchecked(&mut circuit, x, 128, None).unwrap())
+            .collect();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        assert!(transcript_var
+            .append_vk_and_pub_input_vars::<E>(&mut circuit, &vk_var, &consistent)
+            .is_ok());
+
+        // mixed limb sizes
+        let mixed = vec![
+            FpElemVar::new_unchecked(&mut circuit, input_vars[0], 128, None).unwrap(),
+            FpElemVar::new_unchecked(&mut circuit, input_vars[1], 112, None).unwrap(),
+        ];
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        assert!(transcript_var
+            .append_vk_and_pub_input_vars::<E>(&mut circuit, &vk_var, &mixed)
+            .is_err());
+
+        // consistent, but not the limb size expected for the curve
+        let unexpected: Vec<FpElemVar<F>> = input_vars
+            .iter()
+            .map(|&x| FpElemVar::new_unchecked(&mut circuit, x, 112, None).unwrap())
+            .collect();
+        let mut transcript_var = RescueTranscriptVar::new(&mut circuit);
+        assert!(transcript_var
+            .append_vk_and_pub_input_vars::<E>(&mut circuit, &vk_var, &unexpected)
+            .is_err());
+    }
 }
@@ -0,0 +1,328 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Implementing *native* circuit for Poseidon transcript
+
+use super::PlonkTranscriptVar;
+use crate::{
+    circuit::{
+        customized::{
+            ecc::PointVariable,
+            poseidon::PoseidonParams,
+            ultraplonk::{mod_arith::FpElemVar, plonk_verifier::*},
+        },
+        Circuit, PlonkCircuit, Variable,
+    },
+    errors::{PlonkError, SnarkError::ParameterError},
+    transcript::{
+        poseidon::{poseidon_transcript_params, POSEIDON_TRANSCRIPT_WIDTH},
+        rescue::checked_challenge_bit_len,
+    },
+};
+use ark_ec::{short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec, vec::Vec};
+
+/// Struct of variables representing a Poseidon transcript type, including
+/// `POSEIDON_TRANSCRIPT_WIDTH` variables for the state, and a vector of
+/// variables for the transcript. It derives the same challenges as the native
+/// `PoseidonTranscript`.
+pub struct PoseidonTranscriptVar<F: PrimeField> {
+    params: PoseidonParams<F>,
+    transcript_var: Vec<Variable>,
+    state_var: Vec<Variable>,
+}
+
+impl<F: PrimeField> PoseidonTranscriptVar<F> {
+    // Absorb the pending transcript variables into the state variables and
+    // return the resulting state, without updating `self`, c.f.
+    // `PoseidonTranscript` for the padding. The capacity variable is threaded
+    // through as is, and the additions are merged into the first round
+    // constants of each permutation.
+    fn absorb_var(&self, circuit: &mut PlonkCircuit<F>) -> Result<Vec<Variable>, PlonkError> {
+        let rate = POSEIDON_TRANSCRIPT_WIDTH - 1;
+        let mut padded_var = self.transcript_var.clone();
+        padded_var.push(circuit.one());
+        padded_var.resize((padded_var.len() + rate - 1) / rate * rate, circuit.zero());
+        let mut state_var = self.state_var.clone();
+        for block in padded_var.chunks_exact(rate) {
+            state_var = circuit.poseidon_absorb_block(&self.params, &state_var, block)?;
+        }
+        Ok(state_var)
+    }
+}
+
+impl<F: PrimeField> PlonkTranscriptVar<F> for PoseidonTranscriptVar<F> {
+    /// Create a new PoseidonTranscriptVar for a given circuit.
+    /// Panic if `x^5` is not a permutation of `F`, c.f. `PoseidonTranscript`.
+    fn new(circuit: &mut PlonkCircuit<F>) -> Self {
+        Self {
+            params: poseidon_transcript_params()
+                .expect("x^5 is not a permutation of the transcript field"),
+            transcript_var: Vec::new(),
+            state_var: vec![circuit.zero(); POSEIDON_TRANSCRIPT_WIDTH],
+        }
+    }
+
+    // Append the verification key and the public input, omitting the same
+    // messages as `PoseidonTranscript::append_vk_and_pub_input`.
+    fn append_vk_and_pub_input_vars<E: PairingEngine<Fq = F>>(
+        &mut self,
+        circuit: &mut PlonkCircuit<F>,
+        vk_var: &VerifyingKeyVar<E>,
+        pub_input: &[FpElemVar<F>],
+    ) -> Result<(), PlonkError> {
+        // all public inputs must share the splitting parameter `m` that the
+        // verifier circuit uses for `E::Fr` elements
+        if !pub_input.is_empty() {
+            let expected_m = fr_split_param::<E>(circuit.resolve_range_bit_len()?);
+            for (i, e) in pub_input.iter().enumerate() {
+                if e.param_m() != expected_m {
+                    return Err(ParameterError(format!(
+                        "the {}-th public input has splitting parameter {}, expected {}",
+                        i,
+                        e.param_m(),
+                        expected_m
+                    ))
+                    .into());
+                }
+            }
+        }
+
+        // the commitment vars are already in TE form
+        for com in vk_var
+            .selector_comms
+            .iter()
+            .chain(vk_var.sigma_comms.iter())
+        {
+            self.transcript_var.push(com.get_x());
+            self.transcript_var.push(com.get_y());
+        }
+        for e in pub_input {
+            let pub_var = e.convert_to_var(circuit)?;
+            self.transcript_var.push(pub_var)
+        }
+        Ok(())
+    }
+
+    // Append the variable to the transcript. The label is not used.
+    fn append_variable(&mut self, _label: &'static [u8], var: &Variable) -> Result<(), PlonkError> {
+        self.transcript_var.push(*var);
+        Ok(())
+    }
+
+    // Append a commitment variable (in the form of PointVariable) to the
+    // transcript. The caller needs to make sure that the commitment is
+    // already converted to TE form before generating the variables. The label
+    // is not used.
+    fn append_commitment_var<E, P>(
+        &mut self,
+        _label: &'static [u8],
+        _circuit: &mut PlonkCircuit<F>,
+        poly_comm_var: &PointVariable,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<G1Affine = GroupAffine<P>>,
+        P: SWModelParameters<BaseField = F>,
+    {
+        self.transcript_var.push(poly_comm_var.get_x());
+        self.transcript_var.push(poly_comm_var.get_y());
+        Ok(())
+    }
+
+    // Generate the challenge for the current transcript and append it to the
+    // transcript. The label is not used. The challenge is the lowest
+    // `E::Fr::size_in_bits() / 8` bytes of the squeezed element, c.f.
+    // `fq_to_fr_with_mask`, which requires the range table and the scalar
+    // field of `E` to be smaller than the circuit field.
+    fn get_and_append_challenge_var<E>(
+        &mut self,
+        _label: &'static [u8],
+        circuit: &mut PlonkCircuit<F>,
+    ) -> Result<Variable, PlonkError>
+    where
+        E: PairingEngine,
+    {
+        if !circuit.support_lookup() {
+            return Err(ParameterError("does not support range table".to_string()).into());
+        }
+        if E::Fr::size_in_bits() >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "scalar field ({} bits) is not smaller than the circuit field ({} bits)",
+                E::Fr::size_in_bits(),
+                F::size_in_bits()
+            ))
+            .into());
+        }
+        let challenge_bit_len = checked_challenge_bit_len::<E::Fr>(None)?;
+
+        // 1. state = absorb(state, transcript)
+        let state_var = self.absorb_var(circuit)?;
+        // 2. challenge = state[1] in Fr
+        let challenge_var = circuit.truncate(state_var[1], challenge_bit_len)?;
+        // 3. transcript = vec![challenge]
+        self.state_var = state_var;
+        self.transcript_var = vec![challenge_var];
+
+        Ok(challenge_var)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        circuit::customized::ecc::{Point, SWToTEConParam},
+        proof_system::structs::VerifyingKey,
+        transcript::{PlonkTranscript, PoseidonTranscript},
+    };
+    use ark_bls12_377::Bls12_377;
+    use ark_bls12_381::Bls12_381;
+    use ark_ec::{AffineCurve, ProjectiveCurve};
+    use ark_poly_commit::kzg10::{Commitment, VerifierKey};
+    use ark_std::{format, test_rng, vec, UniformRand};
+    use jf_utils::{bytes_to_field_elements, field_switching};
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+
+    #[test]
+    fn test_poseidon_transcript_challenge_circuit() {
+        test_poseidon_transcript_challenge_circuit_helper::<Bls12_377, _, _>();
+        test_poseidon_transcript_challenge_circuit_helper::<Bls12_381, _, _>()
+    }
+    fn test_poseidon_transcript_challenge_circuit_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: PrimeField + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut rng = test_rng();
+        let label = "testing".as_ref();
+
+        let mut transcript_var = PoseidonTranscriptVar::new(&mut circuit);
+        let mut transcript = PoseidonTranscript::<F>::new(label);
+
+        // 0 to 5 absorbed elements cover a partial block, a full block, and
+        // several blocks per challenge
+        for num_msgs in 0..6 {
+            for i in 0..num_msgs {
+                let msg = format!("message {}", i);
+                let vals = bytes_to_field_elements(&msg);
+                let message_vars: Vec<Variable> = vals
+                    .iter()
+                    .map(|x| circuit.create_variable(*x).unwrap())
+                    .collect();
+                transcript.append_message(label, msg.as_bytes()).unwrap();
+                transcript_var
+                    .append_message_vars(label, &message_vars)
+                    .unwrap();
+            }
+
+            let comm = Commitment::<E>(E::G1Projective::rand(&mut rng).into_affine());
+            let point: Point<F> = (&comm.0).into();
+            let comm_var = circuit.create_point_variable(point).unwrap();
+            transcript.append_commitment(label, &comm).unwrap();
+            transcript_var
+                .append_commitment_var::<E, P>(label, &mut circuit, &comm_var)
+                .unwrap();
+
+            let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+            let challenge_var = transcript_var
+                .get_and_append_challenge_var::<E>(label, &mut circuit)
+                .unwrap();
+            assert_eq!(
+                circuit.witness(challenge_var).unwrap(),
+                field_switching(&challenge)
+            );
+        }
+        assert!(circuit
+            .check_circuit_satisfiability(&circuit.public_input().unwrap())
+            .is_ok());
+
+        // a tampered challenge is rejected
+        let challenge_var = transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        *circuit.witness_mut(challenge_var) = F::from(2u64);
+        assert!(circuit
+            .check_circuit_satisfiability(&circuit.public_input().unwrap())
+            .is_err());
+    }
+
+    #[test]
+    fn test_poseidon_transcript_append_vk_and_input_circuit() {
+        test_poseidon_transcript_append_vk_and_input_circuit_helper::<Bls12_377, _, _>()
+    }
+    fn test_poseidon_transcript_append_vk_and_input_circuit_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: PrimeField + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut rng = test_rng();
+        let label = "testing".as_ref();
+
+        let mut transcript_var = PoseidonTranscriptVar::new(&mut circuit);
+        let mut transcript = PoseidonTranscript::<F>::new(label);
+
+        let open_key: VerifierKey<E> = VerifierKey {
+            g: E::G1Affine::prime_subgroup_generator(),
+            gamma_g: E::G1Projective::rand(&mut rng).into_affine(),
+            h: E::G2Affine::prime_subgroup_generator(),
+            beta_h: E::G2Projective::rand(&mut rng).into_affine(),
+            prepared_h: E::G2Affine::prime_subgroup_generator().into(),
+            prepared_beta_h: E::G2Projective::rand(&mut rng).into_affine().into(),
+        };
+        let input: Vec<E::Fr> = (0..4).map(|_| E::Fr::rand(&mut rng)).collect();
+        let sigma_comms: Vec<Commitment<E>> = (0..5)
+            .map(|_| Commitment(E::G1Projective::rand(&mut rng).into_affine()))
+            .collect();
+        let selector_comms: Vec<Commitment<E>> = (0..13)
+            .map(|_| Commitment(E::G1Projective::rand(&mut rng).into_affine()))
+            .collect();
+        let k: Vec<E::Fr> = (0..5).map(|_| E::Fr::rand(&mut rng)).collect();
+        let vk = VerifyingKey {
+            domain_size: 512,
+            num_inputs: input.len(),
+            sigma_comms,
+            selector_comms,
+            k,
+            open_key,
+            is_merged: false,
+            plookup_vk: None,
+            pub_input_names: vec![],
+        };
+        let vk_var = VerifyingKeyVar::new(&mut circuit, &vk).unwrap();
+
+        let m = fr_split_param::<E>(RANGE_BIT_LEN_FOR_TEST);
+        let input_fp_elem_vars: Vec<FpElemVar<F>> = input
+            .iter()
+            .map(|x| {
+                let var = circuit.create_public_variable(field_switching(x)).unwrap();
+                FpElemVar::new_unchecked(&mut circuit, var, m, None).unwrap()
+            })
+            .collect();
+
+        transcript.append_vk_and_pub_input(&vk, &input).unwrap();
+        transcript_var
+            .append_vk_and_pub_input_vars::<E>(&mut circuit, &vk_var, &input_fp_elem_vars)
+            .unwrap();
+
+        let challenge = transcript.get_and_append_challenge::<E>(label).unwrap();
+        let challenge_var = transcript_var
+            .get_and_append_challenge_var::<E>(label, &mut circuit)
+            .unwrap();
+        assert_eq!(
+            circuit.witness(challenge_var).unwrap(),
+            field_switching(&challenge)
+        );
+        assert!(circuit
+            .check_circuit_satisfiability(&circuit.public_input().unwrap())
+            .is_ok());
+    }
+}
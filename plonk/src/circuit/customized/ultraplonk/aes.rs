@@ -0,0 +1,426 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the AES-128 block cipher, e.g. for statements
+//! about data encrypted with AES.
+//!
+//! Blocks and keys are represented by byte variables, i.e., variables whose
+//! values are in `[0, 256)`, in the byte order of FIPS-197. SubBytes looks up
+//! every byte in a table of 256 elements mapping `x` to `(S(x), 2 * S(x))`,
+//! where the product is in `GF(2^8)`, so that MixColumns only takes XORs,
+//! which are looked up in the bitwise logic table of the circuit. The key
+//! schedule is computed in the circuit as well.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+    PlonkType,
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec::Vec};
+
+/// The AES S-box.
+const AES_SBOX: [u8; 256] = [
+    0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
+    0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
+    0xb7, 0xfd, 0x93, 0x26, 0x36, 0x3f, 0xf7, 0xcc, 0x34, 0xa5, 0xe5, 0xf1, 0x71, 0xd8, 0x31, 0x15,
+    0x04, 0xc7, 0x23, 0xc3, 0x18, 0x96, 0x05, 0x9a, 0x07, 0x12, 0x80, 0xe2, 0xeb, 0x27, 0xb2, 0x75,
+    0x09, 0x83, 0x2c, 0x1a, 0x1b, 0x6e, 0x5a, 0xa0, 0x52, 0x3b, 0xd6, 0xb3, 0x29, 0xe3, 0x2f, 0x84,
+    0x53, 0xd1, 0x00, 0xed, 0x20, 0xfc, 0xb1, 0x5b, 0x6a, 0xcb, 0xbe, 0x39, 0x4a, 0x4c, 0x58, 0xcf,
+    0xd0, 0xef, 0xaa, 0xfb, 0x43, 0x4d, 0x33, 0x85, 0x45, 0xf9, 0x02, 0x7f, 0x50, 0x3c, 0x9f, 0xa8,
+    0x51, 0xa3, 0x40, 0x8f, 0x92, 0x9d, 0x38, 0xf5, 0xbc, 0xb6, 0xda, 0x21, 0x10, 0xff, 0xf3, 0xd2,
+    0xcd, 0x0c, 0x13, 0xec, 0x5f, 0x97, 0x44, 0x17, 0xc4, 0xa7, 0x7e, 0x3d, 0x64, 0x5d, 0x19, 0x73,
+    0x60, 0x81, 0x4f, 0xdc, 0x22, 0x2a, 0x90, 0x88, 0x46, 0xee, 0xb8, 0x14, 0xde, 0x5e, 0x0b, 0xdb,
+    0xe0, 0x32, 0x3a, 0x0a, 0x49, 0x06, 0x24, 0x5c, 0xc2, 0xd3, 0xac, 0x62, 0x91, 0x95, 0xe4, 0x79,
+    0xe7, 0xc8, 0x37, 0x6d, 0x8d, 0xd5, 0x4e, 0xa9, 0x6c, 0x56, 0xf4, 0xea, 0x65, 0x7a, 0xae, 0x08,
+    0xba, 0x78, 0x25, 0x2e, 0x1c, 0xa6, 0xb4, 0xc6, 0xe8, 0xdd, 0x74, 0x1f, 0x4b, 0xbd, 0x8b, 0x8a,
+    0x70, 0x3e, 0xb5, 0x66, 0x48, 0x03, 0xf6, 0x0e, 0x61, 0x35, 0x57, 0xb9, 0x86, 0xc1, 0x1d, 0x9e,
+    0xe1, 0xf8, 0x98, 0x11, 0x69, 0xd9, 0x8e, 0x94, 0x9b, 0x1e, 0x87, 0xe9, 0xce, 0x55, 0x28, 0xdf,
+    0x8c, 0xa1, 0x89, 0x0d, 0xbf, 0xe6, 0x42, 0x68, 0x41, 0x99, 0x2d, 0x0f, 0xb0, 0x54, 0xbb, 0x16,
+];
+
+/// The round constants of the AES-128 key schedule.
+const AES_RCON: [u8; 10] = [0x01, 0x02, 0x04, 0x08, 0x10, 0x20, 0x40, 0x80, 0x1b, 0x36];
+
+/// The number of bytes of an AES block and of an AES-128 key.
+const AES_BLOCK_LEN: usize = 16;
+
+/// The number of rounds of AES-128.
+const AES128_NUM_ROUNDS: usize = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The lookup table of the AES S-box inserted in the circuit.
+pub struct AesSboxTable {
+    // the position of the table among all the table elements
+    offset: usize,
+}
+
+/// Trait for the AES-128 gadgets.
+pub trait Aes128Gadget<F: PrimeField> {
+    /// Insert the lookup table of the AES S-box, which has 256 elements and
+    /// is shared by all the AES gadgets of the circuit.
+    /// Return error if the circuit does not support lookup.
+    fn create_aes_sbox_table(&mut self) -> Result<AesSboxTable, PlonkError>;
+
+    /// Expand an AES-128 key into the round keys.
+    /// * `table` - the S-box table of the circuit
+    /// * `key` - the 16 bytes of the key; each of them is constrained to be in
+    ///   `[0, 256)`
+    /// * `returns` - the 11 round keys of 16 bytes
+    fn aes128_key_expansion(
+        &mut self,
+        table: &AesSboxTable,
+        key: &[Variable],
+    ) -> Result<Vec<Vec<Variable>>, PlonkError>;
+
+    /// Encrypt a block with AES-128.
+    /// * `table` - the S-box table of the circuit
+    /// * `round_keys` - the 11 round keys of 16 bytes, which are expected to
+    ///   be constrained to be in `[0, 256)`, e.g. as output by
+    ///   `aes128_key_expansion`
+    /// * `block` - the 16 bytes of the plaintext; each of them is constrained
+    ///   to be in `[0, 256)`
+    /// * `returns` - the 16 bytes of the ciphertext
+    fn aes128_encrypt_block(
+        &mut self,
+        table: &AesSboxTable,
+        round_keys: &[Vec<Variable>],
+        block: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Encrypt a block with AES-128, expanding the key in the circuit.
+    /// * `table` - the S-box table of the circuit
+    /// * `key` - the 16 bytes of the key; each of them is constrained to be in
+    ///   `[0, 256)`
+    /// * `block` - the 16 bytes of the plaintext; each of them is constrained
+    ///   to be in `[0, 256)`
+    /// * `returns` - the 16 bytes of the ciphertext
+    fn aes128_encrypt(
+        &mut self,
+        table: &AesSboxTable,
+        key: &[Variable],
+        block: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError>;
+}
+
+impl<F> Aes128Gadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn create_aes_sbox_table(&mut self) -> Result<AesSboxTable, PlonkError> {
+        self.check_plonk_type(PlonkType::UltraPlonk)?;
+        // the table elements are constant, and are shared between the rows
+        let values = (0..256u32)
+            .map(|v| self.create_constant_variable(F::from(v)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let table_vars: Vec<(Variable, Variable)> = AES_SBOX
+            .iter()
+            .map(|&s| (values[s as usize], values[xtime(s) as usize]))
+            .collect();
+        let offset = self.num_table_elems();
+        self.create_table_and_lookup_variables(&[], &table_vars)?;
+        Ok(AesSboxTable { offset })
+    }
+
+    fn aes128_key_expansion(
+        &mut self,
+        table: &AesSboxTable,
+        key: &[Variable],
+    ) -> Result<Vec<Vec<Variable>>, PlonkError> {
+        self.check_aes_inputs(table, key)?;
+        let mut words: Vec<Vec<Variable>> = key.chunks(4).map(|word| word.to_vec()).collect();
+        for (i, &rcon) in AES_RCON.iter().enumerate() {
+            // SubWord(RotWord(w)) ^ (rcon, 0, 0, 0)
+            let last = &words[words.len() - 1];
+            let rotated = [last[1], last[2], last[3], last[0]];
+            let mut temp: Vec<Variable> = self
+                .aes_sub_bytes(table, &rotated)?
+                .into_iter()
+                .map(|(s, _)| s)
+                .collect();
+            let rcon_var = self.create_constant_variable(F::from(rcon))?;
+            temp[0] = self.aes_xor(&[(temp[0], rcon_var)])?[0];
+            for j in 0..4 {
+                let prev = &words[4 * i + j];
+                let pairs: Vec<(Variable, Variable)> =
+                    prev.iter().copied().zip(temp.into_iter()).collect();
+                temp = self.aes_xor(&pairs)?;
+                words.push(temp.clone());
+            }
+        }
+        Ok(words
+            .chunks(4)
+            .map(|round_key| round_key.concat())
+            .collect())
+    }
+
+    fn aes128_encrypt_block(
+        &mut self,
+        table: &AesSboxTable,
+        round_keys: &[Vec<Variable>],
+        block: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        self.check_aes_inputs(table, block)?;
+        if round_keys.len() != AES128_NUM_ROUNDS + 1
+            || round_keys.iter().any(|key| key.len() != AES_BLOCK_LEN)
+        {
+            return Err(ParameterError(format!(
+                "expecting {} round keys of {} bytes",
+                AES128_NUM_ROUNDS + 1,
+                AES_BLOCK_LEN
+            ))
+            .into());
+        }
+        for round_key in round_keys.iter() {
+            self.check_vars_bound(round_key)?;
+        }
+
+        let mut state = self.aes_add_round_key(block, &round_keys[0])?;
+        for (round, round_key) in round_keys.iter().enumerate().skip(1) {
+            // SubBytes and ShiftRows, the state being column-major
+            let sub = self.aes_sub_bytes(table, &state)?;
+            let shifted: Vec<(Variable, Variable)> = (0..AES_BLOCK_LEN)
+                .map(|i| {
+                    let (col, row) = (i / 4, i % 4);
+                    sub[((col + row) % 4) * 4 + row]
+                })
+                .collect();
+            state = if round < AES128_NUM_ROUNDS {
+                self.aes_mix_columns(&shifted)?
+            } else {
+                shifted.into_iter().map(|(s, _)| s).collect()
+            };
+            state = self.aes_add_round_key(&state, round_key)?;
+        }
+        Ok(state)
+    }
+
+    fn aes128_encrypt(
+        &mut self,
+        table: &AesSboxTable,
+        key: &[Variable],
+        block: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let round_keys = self.aes128_key_expansion(table, key)?;
+        self.aes128_encrypt_block(table, &round_keys, block)
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    // Check that the input is made of 16 variables, which are constrained to
+    // be bytes, and that the tables are inserted.
+    fn check_aes_inputs(
+        &mut self,
+        table: &AesSboxTable,
+        input: &[Variable],
+    ) -> Result<(), PlonkError> {
+        if input.len() != AES_BLOCK_LEN {
+            return Err(ParameterError(format!(
+                "expecting {} bytes, got {}",
+                AES_BLOCK_LEN,
+                input.len()
+            ))
+            .into());
+        }
+        if table.offset + AES_SBOX.len() > self.num_table_elems() {
+            return Err(ParameterError("the AES S-box table is not inserted".to_string()).into());
+        }
+        // the XORs look up pairs of bytes
+        if self.logic_table_bit_len()? != 8 {
+            return Err(ParameterError(
+                "the AES gadgets need a logic table for operands of 8 bits".to_string(),
+            )
+            .into());
+        }
+        self.check_vars_bound(input)?;
+        for &byte in input.iter() {
+            self.range_gate(byte, 8)?;
+        }
+        Ok(())
+    }
+
+    // Obtain (S(x), 2 * S(x)) for the bytes x, by looking them up in the S-box
+    // table.
+    fn aes_sub_bytes(
+        &mut self,
+        table: &AesSboxTable,
+        bytes: &[Variable],
+    ) -> Result<Vec<(Variable, Variable)>, PlonkError> {
+        let mut lookup_vars = Vec::with_capacity(bytes.len());
+        let mut outputs = Vec::with_capacity(bytes.len());
+        for &byte in bytes.iter() {
+            // a byte out of range makes the circuit unsatisfiable
+            let index = self.witness(byte)?.into_repr().as_ref()[0] as usize & 0xff;
+            let s = self.create_variable(F::from(AES_SBOX[index]))?;
+            let s2 = self.create_variable(F::from(xtime(AES_SBOX[index])))?;
+            // the key is the position of the element in the table
+            let key = self.add_constant(byte, &F::from(table.offset as u64))?;
+            lookup_vars.push((key, s, s2));
+            outputs.push((s, s2));
+        }
+        self.lookup_variables(&lookup_vars)?;
+        Ok(outputs)
+    }
+
+    // Obtain a ^ b for pairs of bytes.
+    fn aes_xor(&mut self, pairs: &[(Variable, Variable)]) -> Result<Vec<Variable>, PlonkError> {
+        Ok(self
+            .logic_lookup(pairs)?
+            .into_iter()
+            .map(|(_, xor)| xor)
+            .collect())
+    }
+
+    fn aes_add_round_key(
+        &mut self,
+        state: &[Variable],
+        round_key: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let pairs: Vec<(Variable, Variable)> = state
+            .iter()
+            .copied()
+            .zip(round_key.iter().copied())
+            .collect();
+        self.aes_xor(&pairs)
+    }
+
+    // MixColumns of the substituted bytes (s, 2 * s): for every column,
+    // 2 * s_r ^ 3 * s_{r + 1} ^ s_{r + 2} ^ s_{r + 3}
+    //      = 2 * s_r ^ 2 * s_{r + 1} ^ s_r ^ (s_0 ^ s_1 ^ s_2 ^ s_3)
+    fn aes_mix_columns(
+        &mut self,
+        state: &[(Variable, Variable)],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let mut pairs = Vec::with_capacity(AES_BLOCK_LEN + AES_BLOCK_LEN / 2);
+        for i in 0..AES_BLOCK_LEN {
+            let next = (i / 4) * 4 + (i + 1) % 4;
+            pairs.push((state[i].1, state[next].1));
+        }
+        for i in (0..AES_BLOCK_LEN).step_by(2) {
+            pairs.push((state[i].0, state[i + 1].0));
+        }
+        let xors = self.aes_xor(&pairs)?;
+        let (doubles, halves) = xors.split_at(AES_BLOCK_LEN);
+
+        let mut pairs: Vec<(Variable, Variable)> = (0..AES_BLOCK_LEN)
+            .map(|i| (doubles[i], state[i].0))
+            .collect();
+        for col in 0..4 {
+            pairs.push((halves[2 * col], halves[2 * col + 1]));
+        }
+        let xors = self.aes_xor(&pairs)?;
+        let (partials, sums) = xors.split_at(AES_BLOCK_LEN);
+
+        let pairs: Vec<(Variable, Variable)> = (0..AES_BLOCK_LEN)
+            .map(|i| (partials[i], sums[i / 4]))
+            .collect();
+        self.aes_xor(&pairs)
+    }
+}
+
+// Multiplication by 2 in GF(2^8) with the AES polynomial.
+fn xtime(b: u8) -> u8 {
+    (b << 1) ^ if b & 0x80 != 0 { 0x1b } else { 0 }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    // the test vectors of FIPS-197, appendices B and C.1
+    const TEST_VECTORS: [(&str, &str, &str); 2] = [
+        (
+            "2b7e151628aed2a6abf7158809cf4f3c",
+            "3243f6a8885a308d313198a2e0370734",
+            "3925841d02dc09fbdc118597196a0b32",
+        ),
+        (
+            "000102030405060708090a0b0c0d0e0f",
+            "00112233445566778899aabbccddeeff",
+            "69c4e0d86a7b0430d8cdb78070b4c55a",
+        ),
+    ];
+
+    fn from_hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_aes128() -> Result<(), PlonkError> {
+        test_aes128_helper::<FqEd254>()?;
+        test_aes128_helper::<FqEd377>()?;
+        test_aes128_helper::<FqEd381>()?;
+        test_aes128_helper::<Fq377>()
+    }
+
+    fn test_aes128_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let table = circuit.create_aes_sbox_table()?;
+        let mut outputs = Vec::new();
+        for (key, plaintext, ciphertext) in TEST_VECTORS.iter() {
+            let key_vars = from_hex(key)
+                .into_iter()
+                .map(|b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let block_vars = from_hex(plaintext)
+                .into_iter()
+                .map(|b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let output_vars = circuit.aes128_encrypt(&table, &key_vars, &block_vars)?;
+            for (&var, byte) in output_vars.iter().zip(from_hex(ciphertext)) {
+                assert_eq!(circuit.witness(var)?, F::from(byte));
+            }
+            outputs.extend(output_vars);
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a wrong ciphertext byte
+        for &var in outputs.iter().step_by(5) {
+            *circuit.witness_mut(var) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(var) -= F::one();
+        }
+
+        // invalid inputs
+        let byte = circuit.create_variable(F::one())?;
+        assert!(circuit
+            .aes128_encrypt(&table, &[byte; 15], &[byte; 16])
+            .is_err());
+        assert!(circuit
+            .aes128_encrypt(&table, &[byte; 16], &[byte; 17])
+            .is_err());
+        let round_keys = circuit.aes128_key_expansion(&table, &[byte; 16])?;
+        assert!(circuit
+            .aes128_encrypt_block(&table, &round_keys[1..], &[byte; 16])
+            .is_err());
+        let mut other_circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let byte = other_circuit.create_variable(F::one())?;
+        assert!(other_circuit
+            .aes128_encrypt(&table, &[byte; 16], &[byte; 16])
+            .is_err());
+        let mut other_circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        other_circuit.create_logic_table(4)?;
+        let table = other_circuit.create_aes_sbox_table()?;
+        let byte = other_circuit.create_variable(F::one())?;
+        assert!(other_circuit
+            .aes128_encrypt(&table, &[byte; 16], &[byte; 16])
+            .is_err());
+        assert!(PlonkCircuit::<F>::new_turbo_plonk()
+            .create_aes_sbox_table()
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,364 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the decoding of base64url and of ASCII decimal
+//! numbers, e.g. for JWT or email circuits which hash or parse encoded
+//! payloads.
+//!
+//! Characters and bytes are represented by byte variables, i.e., variables
+//! whose values are in `[0, 256)`. Every character is looked up in a table
+//! of 256 elements mapping `c` to its base64url value and to its decimal
+//! digit value, invalid characters being mapped to 255. The base64url
+//! decoding follows RFC 4648 without padding, as used by JWT, and rejects
+//! the encodings whose unused trailing bits are not zero.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+    PlonkType,
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec::Vec};
+
+/// The value of the characters which are not in the alphabet.
+const ASCII_INVALID: u8 = 255;
+
+/// The number of elements of the ASCII table.
+const ASCII_TABLE_LEN: usize = 256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The lookup table of the ASCII characters inserted in the circuit.
+pub struct AsciiTable {
+    // the position of the table among all the table elements
+    offset: usize,
+}
+
+/// Trait for the base64url and ASCII gadgets.
+pub trait Base64Gadget<F: PrimeField> {
+    /// Insert the lookup table of the ASCII characters, which has 256
+    /// elements and is shared by all the base64url and ASCII gadgets of the
+    /// circuit.
+    /// Return error if the circuit does not support lookup.
+    fn create_ascii_table(&mut self) -> Result<AsciiTable, PlonkError>;
+
+    /// Decode a base64url encoding without padding.
+    /// * `table` - the ASCII table of the circuit
+    /// * `chars` - the characters of the encoding, whose number is not 1
+    ///   modulo 4; each of them is constrained to be in `[0, 256)`, and the
+    ///   circuit is not satisfied if one of them is not in the alphabet
+    /// * `returns` - the `3 * chars.len() / 4` decoded bytes
+    fn base64url_decode(
+        &mut self,
+        table: &AsciiTable,
+        chars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Obtain the values of decimal digits.
+    /// * `table` - the ASCII table of the circuit
+    /// * `chars` - the characters of the digits; each of them is constrained
+    ///   to be in `[0, 256)`, and the circuit is not satisfied if one of them
+    ///   is not in `'0'..='9'`
+    /// * `returns` - the values of the digits, in `[0, 10)`
+    fn ascii_digits(
+        &mut self,
+        table: &AsciiTable,
+        chars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Parse a decimal number, most significant digit first.
+    /// * `table` - the ASCII table of the circuit
+    /// * `chars` - the characters of the digits, which are constrained as for
+    ///   `ascii_digits`; there are at most `(F::size_in_bits() - 1) / 4` of
+    ///   them, so that the number is smaller than the modulus
+    /// * `returns` - the parsed number
+    fn parse_ascii_decimal(
+        &mut self,
+        table: &AsciiTable,
+        chars: &[Variable],
+    ) -> Result<Variable, PlonkError>;
+}
+
+impl<F> Base64Gadget<F> for PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    fn create_ascii_table(&mut self) -> Result<AsciiTable, PlonkError> {
+        self.check_plonk_type(PlonkType::UltraPlonk)?;
+        // the table elements are constant, and are shared between the rows
+        let invalid = self.create_constant_variable(F::from(ASCII_INVALID))?;
+        let values = (0..64u32)
+            .map(|v| self.create_constant_variable(F::from(v)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let value_var = |v: u8| {
+            if v == ASCII_INVALID {
+                invalid
+            } else {
+                values[v as usize]
+            }
+        };
+        let table_vars: Vec<(Variable, Variable)> = (0..ASCII_TABLE_LEN)
+            .map(|c| {
+                (
+                    value_var(base64url_value(c as u8)),
+                    value_var(digit_value(c as u8)),
+                )
+            })
+            .collect();
+        let offset = self.num_table_elems();
+        self.create_table_and_lookup_variables(&[], &table_vars)?;
+        Ok(AsciiTable { offset })
+    }
+
+    fn base64url_decode(
+        &mut self,
+        table: &AsciiTable,
+        chars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        if chars.len() % 4 == 1 {
+            return Err(ParameterError(format!(
+                "invalid length {} of a base64url encoding",
+                chars.len()
+            ))
+            .into());
+        }
+        let values = self.ascii_lookup(table, chars)?;
+
+        // the big-endian bits of the sextets, which are constrained to be in
+        // [0, 64), i.e. the characters to be in the alphabet
+        let mut bits = Vec::with_capacity(6 * chars.len());
+        for &(value, _) in values.iter() {
+            let mut sextet = self.unpack_bool(value, 6)?;
+            sextet.reverse();
+            bits.extend(sextet);
+        }
+        let num_bytes = bits.len() / 8;
+        // the unused trailing bits are zero
+        for &bit in bits[8 * num_bytes..].iter() {
+            self.enforce_false(bit.into())?;
+        }
+        bits.chunks_exact(8)
+            .map(|byte| {
+                let mut byte = byte.to_vec();
+                byte.reverse();
+                self.pack_bits(&byte)
+            })
+            .collect()
+    }
+
+    fn ascii_digits(
+        &mut self,
+        table: &AsciiTable,
+        chars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let values = self.ascii_lookup(table, chars)?;
+        values
+            .into_iter()
+            .map(|(_, digit)| {
+                // digit + 6 is in [0, 16), i.e. the digit is in [0, 10)
+                let shifted = self.add_constant(digit, &F::from(6u8))?;
+                self.range_gate(shifted, 4)?;
+                Ok(digit)
+            })
+            .collect()
+    }
+
+    fn parse_ascii_decimal(
+        &mut self,
+        table: &AsciiTable,
+        chars: &[Variable],
+    ) -> Result<Variable, PlonkError> {
+        // 10^n < 2^(4 * n) <= 2^(size_in_bits - 1) < p
+        if 4 * chars.len() + 1 > F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "a number of {} digits may overflow the field",
+                chars.len()
+            ))
+            .into());
+        }
+        let digits = self.ascii_digits(table, chars)?;
+        // Horner's rule, with 3 digits per gate
+        let zero = self.zero();
+        let ten = F::from(10u8);
+        let mut acc = zero;
+        for chunk in digits.chunks(3) {
+            let mut wires = [zero; 4];
+            let mut coeffs = [F::zero(); 4];
+            wires[0] = acc;
+            coeffs[0] = ten.pow(&[chunk.len() as u64]);
+            for (i, &digit) in chunk.iter().enumerate() {
+                wires[i + 1] = digit;
+                coeffs[i + 1] = ten.pow(&[(chunk.len() - 1 - i) as u64]);
+            }
+            acc = self.lc(&wires, &coeffs)?;
+        }
+        Ok(acc)
+    }
+}
+
+impl<F> PlonkCircuit<F>
+where
+    F: PrimeField,
+{
+    // Obtain the base64url and digit values of the characters, by looking
+    // them up in the ASCII table.
+    fn ascii_lookup(
+        &mut self,
+        table: &AsciiTable,
+        chars: &[Variable],
+    ) -> Result<Vec<(Variable, Variable)>, PlonkError> {
+        if table.offset + ASCII_TABLE_LEN > self.num_table_elems() {
+            return Err(ParameterError("the ASCII table is not inserted".to_string()).into());
+        }
+        self.check_vars_bound(chars)?;
+        let mut lookup_vars = Vec::with_capacity(chars.len());
+        let mut outputs = Vec::with_capacity(chars.len());
+        for &c in chars.iter() {
+            // the keys of other tables may hold arbitrary elements
+            self.range_gate(c, 8)?;
+            // a character out of range makes the circuit unsatisfiable
+            let index = self.witness(c)?.into_repr().as_ref()[0] as u8;
+            let value = self.create_variable(F::from(base64url_value(index)))?;
+            let digit = self.create_variable(F::from(digit_value(index)))?;
+            // the key is the position of the element in the table
+            let key = self.add_constant(c, &F::from(table.offset as u64))?;
+            lookup_vars.push((key, value, digit));
+            outputs.push((value, digit));
+        }
+        self.lookup_variables(&lookup_vars)?;
+        Ok(outputs)
+    }
+}
+
+// The value of a character in the base64url alphabet.
+fn base64url_value(c: u8) -> u8 {
+    match c {
+        b'A'..=b'Z' => c - b'A',
+        b'a'..=b'z' => c - b'a' + 26,
+        b'0'..=b'9' => c - b'0' + 52,
+        b'-' => 62,
+        b'_' => 63,
+        _ => ASCII_INVALID,
+    }
+}
+
+// The value of a decimal digit.
+fn digit_value(c: u8) -> u8 {
+    match c {
+        b'0'..=b'9' => c - b'0',
+        _ => ASCII_INVALID,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng, vec};
+
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+    fn encode(bytes: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        for chunk in bytes.chunks(3) {
+            let mut group = [0u8; 3];
+            group[..chunk.len()].copy_from_slice(chunk);
+            let n = (group[0] as usize) << 16 | (group[1] as usize) << 8 | group[2] as usize;
+            for i in 0..chunk.len() + 1 {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) & 63]);
+            }
+        }
+        out
+    }
+
+    fn create_chars<F: PrimeField>(
+        circuit: &mut PlonkCircuit<F>,
+        chars: &[u8],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        chars
+            .iter()
+            .map(|&c| circuit.create_variable(F::from(c)))
+            .collect()
+    }
+
+    #[test]
+    fn test_base64() -> Result<(), PlonkError> {
+        test_base64_helper::<FqEd254>()?;
+        test_base64_helper::<FqEd377>()?;
+        test_base64_helper::<FqEd381>()?;
+        test_base64_helper::<Fq377>()
+    }
+
+    fn test_base64_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let table = circuit.create_ascii_table()?;
+        assert_eq!(encode(b"\xfb\xff"), b"-_8");
+
+        // all the lengths modulo 3
+        let mut outputs = vec![];
+        for len in [0, 1, 2, 3, 47] {
+            let mut bytes = vec![0u8; len];
+            rng.fill_bytes(&mut bytes);
+            let chars = create_chars(&mut circuit, &encode(&bytes))?;
+            let byte_vars = circuit.base64url_decode(&table, &chars)?;
+            assert_eq!(byte_vars.len(), len);
+            for (&var, &byte) in byte_vars.iter().zip(bytes.iter()) {
+                assert_eq!(circuit.witness(var)?, F::from(byte));
+            }
+            outputs.extend(byte_vars);
+        }
+        let chars = create_chars(&mut circuit, b"20261016")?;
+        let number = circuit.parse_ascii_decimal(&table, &chars)?;
+        assert_eq!(circuit.witness(number)?, F::from(20261016u64));
+        let digits = circuit.ascii_digits(&table, &chars)?;
+        assert_eq!(circuit.witness(digits[1])?, F::zero());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a wrong output
+        *circuit.witness_mut(outputs[0]) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(outputs[0]) -= F::one();
+        *circuit.witness_mut(number) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(number) -= F::one();
+
+        // characters out of the alphabets, and non-zero trailing bits
+        for encoding in [&b"ab+d"[..], b"ab=", b"Q\x80", b"QR", b"QUR"] {
+            let mut bad_circuit = circuit.clone();
+            let chars = create_chars(&mut bad_circuit, encoding)?;
+            bad_circuit.base64url_decode(&table, &chars)?;
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        for digits in [&b"12a"[..], b"/", b":", b"\xb0"] {
+            let mut bad_circuit = circuit.clone();
+            let chars = create_chars(&mut bad_circuit, digits)?;
+            bad_circuit.parse_ascii_decimal(&table, &chars)?;
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        // a character out of range which would be a valid key of the table
+        let mut bad_circuit = circuit.clone();
+        let chars = create_chars(&mut bad_circuit, b"0")?;
+        *bad_circuit.witness_mut(chars[0]) += F::from(256u32);
+        bad_circuit.ascii_digits(&table, &chars)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid inputs
+        let chars = create_chars(&mut circuit, b"abcde")?;
+        assert!(circuit.base64url_decode(&table, &chars).is_err());
+        let chars = vec![chars[0]; F::size_in_bits() / 4 + 1];
+        assert!(circuit.parse_ascii_decimal(&table, &chars).is_err());
+        let mut other_circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let chars = create_chars(&mut other_circuit, b"0")?;
+        assert!(other_circuit.ascii_digits(&table, &chars).is_err());
+        assert!(PlonkCircuit::<F>::new_turbo_plonk()
+            .create_ascii_table()
+            .is_err());
+        Ok(())
+    }
+}
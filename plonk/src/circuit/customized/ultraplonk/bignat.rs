@@ -0,0 +1,472 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuits for the modular arithmetic of natural numbers larger than the
+//! native field, e.g. 2048-bit or 4096-bit RSA moduli.
+//!
+//! A number is represented by its little-endian limbs of `BIG_NAT_LIMB_BITS`
+//! bits, which are range checked with the lookup-based range gates. As for
+//! `mod_arith`, a modular multiplication `x * y = z mod n` is enforced over
+//! the integers as `x * y = q * n + z`, where the products are computed
+//! column by column and the columns are tied together by range-checked
+//! carries. Prime moduli of at most 256 bits are rather handled by the
+//! two-limb `EmulatedFieldVar`s of `emulated_field`.
+
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec, vec::Vec};
+use num_bigint::{BigInt, BigUint, Sign};
+
+/// Number of bits of the limbs of big natural numbers.
+pub const BIG_NAT_LIMB_BITS: usize = 64;
+
+/// Number of bits of the range-checked carries between columns, which are
+/// offset by `2^{CARRY_BITS - 1}` to be non-negative.
+const CARRY_BITS: usize = 80;
+
+/// Maximal number of limbs of a big natural number, so that the carries fit
+/// in `CARRY_BITS` bits.
+const MAX_NUM_LIMBS: usize = 1 << 12;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Variable of a big natural number, given by its little-endian limbs of
+/// `BIG_NAT_LIMB_BITS` bits.
+pub struct BigNatVar {
+    limbs: Vec<Variable>,
+}
+
+impl BigNatVar {
+    /// The little-endian limbs of the number.
+    pub fn limbs(&self) -> &[Variable] {
+        &self.limbs
+    }
+
+    /// The number of limbs of the number.
+    pub fn num_limbs(&self) -> usize {
+        self.limbs.len()
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a variable for the big natural number `value` with `num_limbs`
+    /// range-checked limbs.
+    /// Return error if `value` does not fit in `num_limbs` limbs or if the
+    /// circuit does not support lookup.
+    pub fn create_big_nat_variable(
+        &mut self,
+        value: &BigUint,
+        num_limbs: usize,
+    ) -> Result<BigNatVar, PlonkError> {
+        let limbs = big_nat_limbs(value, num_limbs)?
+            .into_iter()
+            .map(|limb| self.create_variable(limb))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.big_nat_from_limbs(&limbs)
+    }
+
+    /// Create a constant big natural number with `num_limbs` limbs.
+    /// Return error if `value` does not fit in `num_limbs` limbs.
+    pub fn create_constant_big_nat_variable(
+        &mut self,
+        value: &BigUint,
+        num_limbs: usize,
+    ) -> Result<BigNatVar, PlonkError> {
+        let limbs = big_nat_limbs(value, num_limbs)?
+            .into_iter()
+            .map(|limb| self.create_constant_variable(limb))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        Ok(BigNatVar { limbs })
+    }
+
+    /// Obtain a big natural number from its little-endian limbs, which are
+    /// range checked.
+    /// Return error if a limb is invalid or if the circuit does not support
+    /// lookup.
+    pub fn big_nat_from_limbs(&mut self, limbs: &[Variable]) -> Result<BigNatVar, PlonkError> {
+        self.check_big_nat_params(limbs.len())?;
+        for &limb in limbs.iter() {
+            self.range_gate_with_lookup(limb, BIG_NAT_LIMB_BITS)?;
+        }
+        Ok(BigNatVar {
+            limbs: limbs.to_vec(),
+        })
+    }
+
+    /// The value of a big natural number.
+    pub fn big_nat_witness(&self, x: &BigNatVar) -> Result<BigUint, PlonkError> {
+        let mut value = BigUint::from(0u8);
+        for &limb in x.limbs.iter().rev() {
+            let limb: BigUint = self.witness(limb)?.into_repr().into();
+            value = (value << BIG_NAT_LIMB_BITS) + limb;
+        }
+        Ok(value)
+    }
+
+    /// Modular multiplication of big natural numbers: obtain `z = x * y mod
+    /// n`, with as many limbs as `n`. The most significant limb of `n` is
+    /// expected to be non-zero. The output is guaranteed to be congruent to
+    /// `x * y` modulo `n` and to fit in its limbs, but not to be reduced.
+    /// Return error if `x` or `y` have more limbs than `n`, if `n` is zero,
+    /// or if the circuit does not support lookup.
+    pub fn big_nat_mod_mul(
+        &mut self,
+        x: &BigNatVar,
+        y: &BigNatVar,
+        n: &BigNatVar,
+    ) -> Result<BigNatVar, PlonkError> {
+        let n_int = self.big_nat_witness(n)?;
+        if n_int == BigUint::from(0u8) {
+            return Err(ParameterError("zero modulus".to_string()).into());
+        }
+        let xy_int = self.big_nat_witness(x)? * self.big_nat_witness(y)?;
+        let z = self.create_big_nat_variable(&(xy_int % n_int), n.num_limbs())?;
+        self.big_nat_enforce_mod_mul(x, y, n, &z)?;
+        Ok(z)
+    }
+
+    /// Enforce `x * y = z mod n` for big natural numbers, where `z` has at
+    /// most as many limbs as `n`, and the most significant limb of `n` is
+    /// expected to be non-zero. The circuit is not satisfied if the relation
+    /// does not hold.
+    /// Return error if `x`, `y` or `z` have more limbs than `n`, if `n` is
+    /// zero, or if the circuit does not support lookup.
+    pub fn big_nat_enforce_mod_mul(
+        &mut self,
+        x: &BigNatVar,
+        y: &BigNatVar,
+        n: &BigNatVar,
+        z: &BigNatVar,
+    ) -> Result<(), PlonkError> {
+        let num_limbs = n.num_limbs();
+        if x.num_limbs() > num_limbs || y.num_limbs() > num_limbs || z.num_limbs() > num_limbs {
+            return Err(ParameterError(format!(
+                "operands have more limbs than the modulus ({})",
+                num_limbs
+            ))
+            .into());
+        }
+        self.check_big_nat_params(x.num_limbs() + y.num_limbs())?;
+        let n_int = self.big_nat_witness(n)?;
+        if n_int == BigUint::from(0u8) {
+            return Err(ParameterError("zero modulus".to_string()).into());
+        }
+
+        // q = x * y / n, which fits in the given number of limbs if the most
+        // significant limb of n is non-zero
+        let num_q_limbs = (x.num_limbs() + y.num_limbs() + 1).saturating_sub(num_limbs);
+        let xy_int = self.big_nat_witness(x)? * self.big_nat_witness(y)?;
+        let q = self.create_big_nat_variable(&(xy_int / n_int), num_q_limbs)?;
+
+        // column t of x * y - q * n - z
+        let num_columns = (x.num_limbs() + y.num_limbs())
+            .saturating_sub(1)
+            .max(num_q_limbs + num_limbs - 1)
+            .max(z.num_limbs());
+        let zero = self.zero();
+        let mut columns = vec![];
+        for t in 0..num_columns {
+            let mut terms = vec![];
+            for (i, &x_i) in x.limbs.iter().enumerate() {
+                if t >= i && t - i < y.num_limbs() {
+                    terms.push((x_i, y.limbs[t - i], F::one()));
+                }
+            }
+            for (i, &q_i) in q.limbs.iter().enumerate() {
+                if t >= i && t - i < num_limbs {
+                    terms.push((q_i, n.limbs[t - i], -F::one()));
+                }
+            }
+            if terms.len() % 2 == 1 {
+                terms.push((zero, zero, F::zero()));
+            }
+            let partials = terms
+                .chunks(2)
+                .map(|pair| {
+                    self.mul_add(
+                        &[pair[0].0, pair[0].1, pair[1].0, pair[1].1],
+                        &[pair[0].2, pair[1].2],
+                    )
+                })
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let column = if partials.is_empty() {
+                zero
+            } else {
+                self.sum(&partials)?
+            };
+            let z_t = if t < z.num_limbs() { z.limbs[t] } else { zero };
+            columns.push((column, z_t));
+        }
+
+        // carries: column_t - z_t + c_{t-1} = 2^LIMB_BITS * c_t, with c_{-1} =
+        // 0 and no carry out of the last column
+        let two_power_limb = F::from(2u8).pow(&[BIG_NAT_LIMB_BITS as u64]);
+        let offset_int = BigInt::from(1u8) << (CARRY_BITS - 1);
+        let offset = F::from(2u8).pow(&[(CARRY_BITS - 1) as u64]);
+        let mut carry_int = BigInt::from(0u8);
+        let mut carry_var = zero;
+        for (t, &(column, z_t)) in columns.iter().enumerate() {
+            let value = field_to_signed(self.witness(column)?)
+                - field_to_signed(self.witness(z_t)?)
+                + &carry_int;
+            let mut q_c = if t == 0 { F::zero() } else { -offset };
+            let (next_carry_var, next_carry_coeff) = if t + 1 < num_columns {
+                carry_int = value >> BIG_NAT_LIMB_BITS;
+                let var = self.create_variable(signed_to_field(&(&carry_int + &offset_int)))?;
+                self.range_gate_with_lookup(var, CARRY_BITS)?;
+                q_c += offset * two_power_limb;
+                (var, -two_power_limb)
+            } else {
+                (zero, F::zero())
+            };
+            let wires = [column, z_t, carry_var, next_carry_var, zero];
+            let q_lc = [F::one(), -F::one(), F::one(), next_carry_coeff];
+            self.quad_poly_gate(&wires, &q_lc, &[F::zero(), F::zero()], F::zero(), q_c)?;
+            carry_var = next_carry_var;
+        }
+        Ok(())
+    }
+
+    /// Modular exponentiation of a big natural number by a constant exponent
+    /// `e`, with the square-and-multiply algorithm.
+    /// Return error if `x` has more limbs than `n`, if `n` is zero, or if the
+    /// circuit does not support lookup.
+    pub fn big_nat_mod_pow(
+        &mut self,
+        x: &BigNatVar,
+        e: &BigUint,
+        n: &BigNatVar,
+    ) -> Result<BigNatVar, PlonkError> {
+        let mut res = self.create_constant_big_nat_variable(&BigUint::from(1u8), n.num_limbs())?;
+        for i in (0..e.bits()).rev() {
+            res = self.big_nat_mod_mul(&res, &res, n)?;
+            if e.bit(i) {
+                res = self.big_nat_mod_mul(&res, x, n)?;
+            }
+        }
+        Ok(res)
+    }
+
+    /// Modular exponentiation of a big natural number by a variable exponent
+    /// given by its little-endian bits, with the square-and-multiply
+    /// algorithm, e.g. for Diffie-Hellman relations with a secret exponent.
+    /// The output is not guaranteed to be reduced, as for `big_nat_mod_mul`.
+    /// Return error if `x` has more limbs than `n`, if `n` is zero, if a bit
+    /// is invalid, or if the circuit does not support lookup.
+    pub fn big_nat_mod_pow_var(
+        &mut self,
+        x: &BigNatVar,
+        e_bits_le: &[BoolVar],
+        n: &BigNatVar,
+    ) -> Result<BigNatVar, PlonkError> {
+        let mut res = self.create_constant_big_nat_variable(&BigUint::from(1u8), n.num_limbs())?;
+        for &bit in e_bits_le.iter().rev() {
+            res = self.big_nat_mod_mul(&res, &res, n)?;
+            let res_x = self.big_nat_mod_mul(&res, x, n)?;
+            let limbs = res
+                .limbs
+                .iter()
+                .zip(res_x.limbs.iter())
+                .map(|(&limb, &limb_x)| self.conditional_select(bit, limb, limb_x))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            // the selected limbs are range checked as the inputs are
+            res = BigNatVar { limbs };
+        }
+        Ok(res)
+    }
+
+    // Check that a number of limbs is supported and that the native field
+    // is large enough.
+    fn check_big_nat_params(&mut self, num_limbs: usize) -> Result<(), PlonkError> {
+        let range_bit_len = self.resolve_range_bit_len()?;
+        if BIG_NAT_LIMB_BITS % range_bit_len != 0 || CARRY_BITS % range_bit_len != 0 {
+            return Err(ParameterError(format!(
+                "range_bit_len = {} does not divide the limb length {} and carry length {}",
+                range_bit_len, BIG_NAT_LIMB_BITS, CARRY_BITS
+            ))
+            .into());
+        }
+        if num_limbs > MAX_NUM_LIMBS {
+            return Err(ParameterError(format!(
+                "big natural numbers have at most {} limbs, got {}",
+                MAX_NUM_LIMBS, num_limbs
+            ))
+            .into());
+        }
+        // the columns must not wrap around the native modulus
+        if F::size_in_bits() < BIG_NAT_LIMB_BITS + CARRY_BITS + 16 {
+            return Err(ParameterError(format!(
+                "native field of {} bits is too small for {}-bit limbs",
+                F::size_in_bits(),
+                BIG_NAT_LIMB_BITS
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+// The little-endian limbs of `value`, or an error if it does not fit in
+// `num_limbs` limbs.
+fn big_nat_limbs<F: PrimeField>(value: &BigUint, num_limbs: usize) -> Result<Vec<F>, PlonkError> {
+    if value.bits() as usize > num_limbs * BIG_NAT_LIMB_BITS {
+        return Err(ParameterError(format!(
+            "value of {} bits does not fit in {} limbs",
+            value.bits(),
+            num_limbs
+        ))
+        .into());
+    }
+    let mut digits = value.to_u64_digits();
+    digits.resize(num_limbs, 0);
+    Ok(digits.into_iter().map(F::from).collect())
+}
+
+// The integer in (-p/2, p/2] represented by a field element.
+fn field_to_signed<F: PrimeField>(x: F) -> BigInt {
+    let x_int: BigUint = x.into_repr().into();
+    let neg_x_int: BigUint = (-x).into_repr().into();
+    if x_int <= neg_x_int {
+        BigInt::from(x_int)
+    } else {
+        -BigInt::from(neg_x_int)
+    }
+}
+
+fn signed_to_field<F: PrimeField>(x: &BigInt) -> F {
+    let abs = F::from(x.magnitude().clone());
+    if x.sign() == Sign::Minus {
+        -abs
+    } else {
+        abs
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::{RangeTableSize, MAX_AUTO_RANGE_BIT_LEN};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng};
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+
+    fn random_big_nat<R: RngCore>(rng: &mut R, num_limbs: usize) -> BigUint {
+        let mut bytes = vec![0u8; num_limbs * BIG_NAT_LIMB_BITS / 8];
+        rng.fill_bytes(&mut bytes);
+        BigUint::from_bytes_le(&bytes)
+    }
+
+    #[test]
+    fn test_big_nat_mod_mul() -> Result<(), PlonkError> {
+        test_big_nat_mod_mul_helper::<FqEd254>()?;
+        test_big_nat_mod_mul_helper::<Fq377>()
+    }
+
+    fn test_big_nat_mod_mul_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        for &num_limbs in [1, 4, 32].iter() {
+            // a modulus with a non-zero most significant limb
+            let n_int = random_big_nat(&mut rng, num_limbs)
+                | (BigUint::from(1u8) << (num_limbs * BIG_NAT_LIMB_BITS - 1));
+            let x_int = random_big_nat(&mut rng, num_limbs) % &n_int;
+            let y_int = random_big_nat(&mut rng, num_limbs);
+
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let n = circuit.create_big_nat_variable(&n_int, num_limbs)?;
+            let x = circuit.create_big_nat_variable(&x_int, num_limbs)?;
+            let y = circuit.create_big_nat_variable(&y_int, num_limbs)?;
+            let z = circuit.big_nat_mod_mul(&x, &y, &n)?;
+            assert_eq!(circuit.big_nat_witness(&z)?, (&x_int * &y_int) % &n_int);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            let e = BigUint::from(5u8);
+            let x_pow = circuit.big_nat_mod_pow(&x, &e, &n)?;
+            assert_eq!(circuit.big_nat_witness(&x_pow)?, x_int.modpow(&e, &n_int));
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // wrong product
+            *circuit.witness_mut(z.limbs()[0]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(z.limbs()[0]) -= F::one();
+
+            // a wrong residue
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let n = circuit.create_big_nat_variable(&n_int, num_limbs)?;
+            let x = circuit.create_big_nat_variable(&x_int, num_limbs)?;
+            let y = circuit.create_big_nat_variable(&y_int, num_limbs)?;
+            let wrong_z_int = ((&x_int * &y_int) + BigUint::from(1u8)) % &n_int;
+            let wrong_z = circuit.create_big_nat_variable(&wrong_z_int, num_limbs)?;
+            circuit.big_nat_enforce_mod_mul(&x, &y, &n, &wrong_z)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // error paths
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let two_power_64 = BigUint::from(1u8) << BIG_NAT_LIMB_BITS;
+        assert!(circuit.create_big_nat_variable(&two_power_64, 1).is_err());
+        let n = circuit.create_big_nat_variable(&two_power_64, 2)?;
+        let x = circuit.create_big_nat_variable(&two_power_64, 3)?;
+        assert!(circuit.big_nat_mod_mul(&x, &n, &n).is_err());
+        let zero = circuit.create_big_nat_variable(&BigUint::from(0u8), 2)?;
+        assert!(circuit.big_nat_mod_mul(&n, &n, &zero).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        assert!(circuit.create_big_nat_variable(&two_power_64, 2).is_err());
+
+        // the range table size is chosen if it is automatic
+        let n_int =
+            random_big_nat(&mut rng, 4) | (BigUint::from(1u8) << (4 * BIG_NAT_LIMB_BITS - 1));
+        let x_int = random_big_nat(&mut rng, 4) % &n_int;
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RangeTableSize::Auto);
+        let n = circuit.create_big_nat_variable(&n_int, 4)?;
+        let x = circuit.create_big_nat_variable(&x_int, 4)?;
+        let z = circuit.big_nat_mod_mul(&x, &x, &n)?;
+        assert_eq!(circuit.big_nat_witness(&z)?, (&x_int * &x_int) % &n_int);
+        assert_eq!(circuit.range_bit_len()?, MAX_AUTO_RANGE_BIT_LEN);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_nat_mod_pow_var() -> Result<(), PlonkError> {
+        test_big_nat_mod_pow_var_helper::<FqEd254>()?;
+        test_big_nat_mod_pow_var_helper::<Fq377>()
+    }
+
+    fn test_big_nat_mod_pow_var_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        for &num_limbs in [1, 4].iter() {
+            let n_int = random_big_nat(&mut rng, num_limbs)
+                | (BigUint::from(1u8) << (num_limbs * BIG_NAT_LIMB_BITS - 1));
+            let x_int = random_big_nat(&mut rng, num_limbs) % &n_int;
+            let e = rng.next_u32() as u64 | (1 << 31);
+
+            let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            let n = circuit.create_big_nat_variable(&n_int, num_limbs)?;
+            let x = circuit.create_big_nat_variable(&x_int, num_limbs)?;
+            let e_bits_le = (0..32)
+                .map(|i| circuit.create_bool_variable((e >> i) & 1 == 1))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let y = circuit.big_nat_mod_pow_var(&x, &e_bits_le, &n)?;
+            assert_eq!(
+                circuit.big_nat_witness(&y)? % &n_int,
+                x_int.modpow(&BigUint::from(e), &n_int)
+            );
+            // the empty exponent
+            let one = circuit.big_nat_mod_pow_var(&x, &[], &n)?;
+            assert_eq!(circuit.big_nat_witness(&one)?, BigUint::from(1u8));
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // a wrong exponent bit
+            *circuit.witness_mut(e_bits_le[5]) = F::from((e >> 5) & 1 == 0);
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,611 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the ECDSA signature verification over short
+//! Weierstrass curves of prime order, such as secp256k1 or secp256r1, whose
+//! base and scalar fields are emulated with `EmulatedFieldVar`s.
+//!
+//! Coordinates and scalars are split into two 128-bit limbs, so that
+//! the native field has to be larger than about 280 bits, e.g. the base field
+//! of BLS12-377 or of BLS12-381. Points are the affine
+//! `EmulatedPointVariable`s of `emulated_ecc`; the scalar multiplications
+//! start from a fixed offset point so that the incomplete addition formulas
+//! never meet the point at infinity.
+
+use super::{
+    emulated::{mod_inverse, parse_hex, NUM_BITS},
+    emulated_ecc::EmulatedPointVariable,
+    emulated_field::EmulatedFieldVar,
+    mod_arith::FpElemVar,
+};
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec, vec::Vec};
+use num_bigint::BigUint;
+
+/// Affine point given by its integer coordinates.
+type NativePoint = (BigUint, BigUint);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// Parameters of a short Weierstrass curve `y^2 = x^3 + a * x + b` of prime
+/// order over which ECDSA signatures are verified.
+pub struct EcdsaCurveParams {
+    base_modulus: BigUint,
+    scalar_modulus: BigUint,
+    coeff_a: BigUint,
+    coeff_b: BigUint,
+    generator: NativePoint,
+    // starting point of the scalar multiplications
+    offset: NativePoint,
+    // -(2^NUM_BITS * offset), which is added at the end of the scalar
+    // multiplications
+    offset_correction: NativePoint,
+}
+
+impl EcdsaCurveParams {
+    /// Create the parameters of a curve.
+    /// * `base_modulus` - the prime `p` over which the curve is defined
+    /// * `scalar_modulus` - the prime order `n` of the curve
+    /// * `coeff_a`, `coeff_b` - the coefficients of the curve equation, in
+    ///   `[0, p)`
+    /// * `generator` - the affine coordinates of the generator
+    /// * `offset` - the affine coordinates of a point whose discrete logarithm
+    ///   is unknown, used as the starting point of the scalar multiplications
+    /// * `returns` - error if the moduli have more than 256 bits, if `p >= 2n`,
+    ///   or if the points are not on the curve
+    pub fn new(
+        base_modulus: BigUint,
+        scalar_modulus: BigUint,
+        coeff_a: BigUint,
+        coeff_b: BigUint,
+        generator: (BigUint, BigUint),
+        offset: (BigUint, BigUint),
+    ) -> Result<Self, PlonkError> {
+        if base_modulus.bits() as usize > NUM_BITS || scalar_modulus.bits() as usize > NUM_BITS {
+            return Err(PlonkError::InvalidParameters(format!(
+                "curve moduli should have at most {} bits",
+                NUM_BITS
+            )));
+        }
+        // the x-coordinate of a point is reduced modulo n with a single
+        // subtraction
+        if base_modulus >= BigUint::from(2u8) * &scalar_modulus {
+            return Err(PlonkError::InvalidParameters(
+                "the base modulus should be smaller than twice the scalar modulus".to_string(),
+            ));
+        }
+        if coeff_a >= base_modulus || coeff_b >= base_modulus {
+            return Err(PlonkError::InvalidParameters(
+                "curve coefficients should be reduced".to_string(),
+            ));
+        }
+        let mut params = Self {
+            base_modulus,
+            scalar_modulus,
+            coeff_a,
+            coeff_b,
+            generator,
+            offset_correction: offset.clone(),
+            offset,
+        };
+        if !params.is_on_curve(&params.generator) || !params.is_on_curve(&params.offset) {
+            return Err(PlonkError::InvalidParameters(
+                "point is not on the curve".to_string(),
+            ));
+        }
+        let mut correction = Some(params.offset.clone());
+        for _ in 0..NUM_BITS {
+            correction = params.add_points(&correction, &correction);
+        }
+        let (x, y) = correction.ok_or_else(|| {
+            PlonkError::InvalidParameters("offset point of small order".to_string())
+        })?;
+        params.offset_correction = (x, (&params.base_modulus - y) % &params.base_modulus);
+        Ok(params)
+    }
+
+    /// Parameters of the secp256k1 curve, c.f. SEC 2, section 2.4.1.
+    pub fn secp256k1() -> Self {
+        Self::new(
+            parse_hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEFFFFFC2F"),
+            parse_hex("FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFEBAAEDCE6AF48A03BBFD25E8CD0364141"),
+            BigUint::from(0u8),
+            BigUint::from(7u8),
+            (
+                parse_hex("79BE667EF9DCBBAC55A06295CE870B07029BFCDB2DCE28D959F2815B16F81798"),
+                parse_hex("483ADA7726A3C4655DA4FBFC0E1108A8FD17B448A68554199C47D08FFB10D4B8"),
+            ),
+            // sha256("jellyfish ecdsa offset") * G
+            (
+                parse_hex("c6b3b5354331312ff43f85db17813b67886930680b1e19d912ebfae947a31086"),
+                parse_hex("613e180edb1d14288eccd9297bb95051069c919be22e01ea30f4a36a229e2d87"),
+            ),
+        )
+        .unwrap() // safe unwrap: the parameters are valid
+    }
+
+    /// Parameters of the secp256r1 curve, a.k.a. P-256, c.f. SEC 2, section
+    /// 2.4.2. This is the curve of the ES256 signatures of WebAuthn.
+    pub fn secp256r1() -> Self {
+        let p = parse_hex("FFFFFFFF00000001000000000000000000000000FFFFFFFFFFFFFFFFFFFFFFFF");
+        // a = -3
+        let coeff_a = &p - BigUint::from(3u8);
+        Self::new(
+            p,
+            parse_hex("FFFFFFFF00000000FFFFFFFFFFFFFFFFBCE6FAADA7179E84F3B9CAC2FC632551"),
+            coeff_a,
+            parse_hex("5AC635D8AA3A93E7B3EBBD55769886BC651D06B0CC53B0F63BCE3C3E27D2604B"),
+            (
+                parse_hex("6B17D1F2E12C4247F8BCE6E563A440F277037D812DEB33A0F4A13945D898C296"),
+                parse_hex("4FE342E2FE1A7F9B8EE7EB4A7C0F9E162BCE33576B315ECECBB6406837BF51F5"),
+            ),
+            // sha256("jellyfish ecdsa offset") * G
+            (
+                parse_hex("8d9003d662a88d13066b2104861eed0e0561e15015c711273c3285bc9ae39ad6"),
+                parse_hex("20c19f6e89c45010b72ac306dce0e55d320f64fc990359eee5bb1e2a75383e17"),
+            ),
+        )
+        .unwrap() // safe unwrap: the parameters are valid
+    }
+
+    /// The prime over which the curve is defined.
+    pub fn base_modulus(&self) -> &BigUint {
+        &self.base_modulus
+    }
+
+    /// The prime order of the curve.
+    pub fn scalar_modulus(&self) -> &BigUint {
+        &self.scalar_modulus
+    }
+
+    /// The affine coordinates of the generator.
+    pub fn generator(&self) -> &(BigUint, BigUint) {
+        &self.generator
+    }
+
+    fn is_on_curve(&self, point: &NativePoint) -> bool {
+        let p = &self.base_modulus;
+        let (x, y) = point;
+        x < p && y < p && (y * y) % p == (x * x * x + &self.coeff_a * x + &self.coeff_b) % p
+    }
+
+    // Add two points, where `None` is the point at infinity.
+    fn add_points(
+        &self,
+        point1: &Option<NativePoint>,
+        point2: &Option<NativePoint>,
+    ) -> Option<NativePoint> {
+        let p = &self.base_modulus;
+        let ((x1, y1), (x2, y2)) = match (point1, point2) {
+            (None, _) => return point2.clone(),
+            (_, None) => return point1.clone(),
+            (Some(point1), Some(point2)) => (point1, point2),
+        };
+        let lambda = if x1 == x2 {
+            if (y1 + y2) % p == BigUint::from(0u8) {
+                return None;
+            }
+            self.double_slope(x1, y1)
+        } else {
+            ((y2 + p - y1) * mod_inverse(&((x2 + p - x1) % p), p)) % p
+        };
+        Some(self.point_from_slope(&lambda, x1, y1, x2))
+    }
+
+    // The slope of the tangent at (x, y).
+    fn double_slope(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        let p = &self.base_modulus;
+        ((BigUint::from(3u8) * x * x + &self.coeff_a)
+            * mod_inverse(&((BigUint::from(2u8) * y) % p), p))
+            % p
+    }
+
+    // The sum of (x1, y1) and (x2, _) given the slope of the line through them.
+    fn point_from_slope(
+        &self,
+        lambda: &BigUint,
+        x1: &BigUint,
+        y1: &BigUint,
+        x2: &BigUint,
+    ) -> NativePoint {
+        let p = &self.base_modulus;
+        let x3 = (lambda * lambda + BigUint::from(2u8) * p - x1 - x2) % p;
+        let y3 = (lambda * (x1 + p - &x3) + p - y1) % p;
+        (x3, y3)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Variable of an ECDSA public key.
+pub struct EcdsaPublicKeyVar<F: PrimeField>(EmulatedPointVariable<F>);
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Variable of an ECDSA signature.
+pub struct EcdsaSignatureVar<F: PrimeField> {
+    r: EmulatedFieldVar<F>,
+    s: EmulatedFieldVar<F>,
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a variable for the public key `(x, y)` of `curve` and enforce
+    /// that it lies on the curve.
+    /// Return error if the coordinates are not reduced or if the circuit does
+    /// not support lookup.
+    pub fn create_ecdsa_public_key_variable(
+        &mut self,
+        curve: &EcdsaCurveParams,
+        x: &BigUint,
+        y: &BigUint,
+    ) -> Result<EcdsaPublicKeyVar<F>, PlonkError> {
+        let x = self.create_emulated_field_variable_with_modulus(x, &curve.base_modulus)?;
+        let y = self.create_emulated_field_variable_with_modulus(y, &curve.base_modulus)?;
+        let point = EmulatedPointVariable { x, y };
+        self.emulated_on_curve_gate_with_coeffs(&point, &curve.coeff_a, &curve.coeff_b)?;
+        Ok(EcdsaPublicKeyVar(point))
+    }
+
+    /// Create a variable for the signature `(r, s)` and enforce that both `r`
+    /// and `s` are non-zero.
+    /// Return error if `r` or `s` are not reduced modulo the order of `curve`
+    /// or if the circuit does not support lookup.
+    pub fn create_ecdsa_signature_variable(
+        &mut self,
+        curve: &EcdsaCurveParams,
+        r: &BigUint,
+        s: &BigUint,
+    ) -> Result<EcdsaSignatureVar<F>, PlonkError> {
+        let r = self.create_emulated_field_variable_with_modulus(r, &curve.scalar_modulus)?;
+        let s = self.create_emulated_field_variable_with_modulus(s, &curve.scalar_modulus)?;
+        r.elem.enforce_nonzero(self, &r.modulus)?;
+        s.elem.enforce_nonzero(self, &s.modulus)?;
+        Ok(EcdsaSignatureVar { r, s })
+    }
+
+    /// Obtain the message hash to be used by `ecdsa_verify` from the 32
+    /// big-endian bytes of a digest, e.g. the output of the SHA-256 gadget.
+    /// The byte variables are expected to be constrained to `[0, 256)`.
+    pub fn ecdsa_msg_hash_from_bytes(
+        &mut self,
+        bytes: &[Variable],
+    ) -> Result<FpElemVar<F>, PlonkError> {
+        if bytes.len() != NUM_BITS / 8 {
+            return Err(ParameterError(format!(
+                "message hash should have {} bytes, got {}",
+                NUM_BITS / 8,
+                bytes.len()
+            ))
+            .into());
+        }
+        let bytes_le: Vec<Variable> = bytes.iter().rev().copied().collect();
+        self.emulated_from_bytes_le(&bytes_le)
+    }
+
+    /// Verify an ECDSA signature over `curve`: the circuit is satisfied iff
+    /// `sig` is a valid signature of the message hash `msg_hash` under the
+    /// public key `pk`.
+    /// * `msg_hash` - the message hash, with 128-bit limbs; it is not
+    ///   required to be reduced modulo the order of the curve
+    /// * `returns` - error if the circuit does not support lookup, if the
+    ///   native field is too small, or if `pk` or `sig` are not over `curve`
+    pub fn ecdsa_verify(
+        &mut self,
+        curve: &EcdsaCurveParams,
+        pk: &EcdsaPublicKeyVar<F>,
+        msg_hash: &FpElemVar<F>,
+        sig: &EcdsaSignatureVar<F>,
+    ) -> Result<(), PlonkError> {
+        if pk.0.x.modulus() != curve.base_modulus || sig.s.modulus() != curve.scalar_modulus {
+            return Err(ParameterError(
+                "the public key or the signature is not over the curve".to_string(),
+            )
+            .into());
+        }
+        let n = sig.s.modulus;
+
+        // w = s^{-1} mod n
+        let w = self.emulated_field_inverse(&sig.s)?;
+
+        // u1 = msg_hash * w and u2 = r * w, whose limbs are range checked by
+        // mod_mul
+        let u1 = self.mod_mul(msg_hash, &w.elem, &n)?;
+        let u2 = self.mod_mul(&sig.r.elem, &w.elem, &n)?;
+        let u1_bits = self.emulated_bits(&u1)?;
+        let u2_bits = self.emulated_bits(&u2)?;
+
+        // offset + u1 * G + u2 * Q with Shamir's trick
+        let g = self.create_constant_emulated_point_variable_with_modulus(
+            &curve.generator,
+            &curve.base_modulus,
+        )?;
+        let q = pk.0;
+        let g_plus_q = self.emulated_ecc_add(&g, &q)?;
+        let mut acc = self.create_constant_emulated_point_variable_with_modulus(
+            &curve.offset,
+            &curve.base_modulus,
+        )?;
+        for i in (0..NUM_BITS).rev() {
+            acc = self.emulated_ecc_double_with_coeff(&acc, &curve.coeff_a)?;
+            let q_or_g_plus_q = self.emulated_point_select(u1_bits[i], &q, &g_plus_q)?;
+            let point = self.emulated_point_select(u2_bits[i], &g, &q_or_g_plus_q)?;
+            let sum = self.emulated_ecc_add(&acc, &point)?;
+            let any_bit = self.logic_or(u1_bits[i], u2_bits[i])?;
+            acc = self.emulated_point_select(any_bit, &acc, &sum)?;
+        }
+        let correction = self.create_constant_emulated_point_variable_with_modulus(
+            &curve.offset_correction,
+            &curve.base_modulus,
+        )?;
+        let res = self.emulated_ecc_add(&acc, &correction)?;
+
+        // x = r mod n; since x < p < 2n and r < n, x = r + k * n for a bit k
+        let x_int = self.emulated_field_int_witness(&res.x)?;
+        let k = if x_int >= curve.scalar_modulus {
+            F::one()
+        } else {
+            F::zero()
+        };
+        let k_var = self.create_variable(k)?;
+        self.bool_gate(k_var)?;
+        let (x0, x1) = res.x.limbs();
+        let (r0, r1) = sig.r.limbs();
+        let two_power_m = n.two_power_m();
+        self.enforce_lc_with_constant(
+            vec![
+                (x0, F::one()),
+                (x1, two_power_m),
+                (r0, -F::one()),
+                (r1, -two_power_m),
+                (k_var, -n.field_elem()),
+            ],
+            F::zero(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_bls12_381::Fq as Fq381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng};
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+
+    struct Signature {
+        pk: NativePoint,
+        msg_hash: BigUint,
+        r: BigUint,
+        s: BigUint,
+    }
+
+    // Multiply a point by a scalar, where `None` is the point at infinity.
+    fn mul_point(
+        curve: &EcdsaCurveParams,
+        point: &NativePoint,
+        scalar: &BigUint,
+    ) -> Option<NativePoint> {
+        let point = Some(point.clone());
+        let mut res = None;
+        for i in (0..scalar.bits()).rev() {
+            res = curve.add_points(&res, &res);
+            if scalar.bit(i) {
+                res = curve.add_points(&res, &point);
+            }
+        }
+        res
+    }
+
+    fn random_scalar<R: RngCore>(rng: &mut R, curve: &EcdsaCurveParams) -> BigUint {
+        let mut bytes = [0u8; 64];
+        rng.fill_bytes(&mut bytes);
+        BigUint::from_bytes_be(&bytes) % curve.scalar_modulus()
+    }
+
+    fn sign<R: RngCore>(rng: &mut R, curve: &EcdsaCurveParams) -> Signature {
+        let n = curve.scalar_modulus();
+        let sk = random_scalar(rng, curve);
+        let nonce = random_scalar(rng, curve);
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        let msg_hash = BigUint::from_bytes_be(&bytes);
+        let pk = mul_point(curve, curve.generator(), &sk).unwrap();
+        let r = mul_point(curve, curve.generator(), &nonce).unwrap().0 % n;
+        let s = (mod_inverse(&nonce, n) * (&msg_hash + &r * &sk)) % n;
+        Signature { pk, msg_hash, r, s }
+    }
+
+    fn build_ecdsa_circuit<F: PrimeField>(
+        curve: &EcdsaCurveParams,
+        sig: &Signature,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let pk_var = circuit.create_ecdsa_public_key_variable(curve, &sig.pk.0, &sig.pk.1)?;
+        let mut bytes = sig.msg_hash.to_bytes_be();
+        while bytes.len() < 32 {
+            bytes.insert(0, 0);
+        }
+        let byte_vars = bytes
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let msg_hash_var = circuit.ecdsa_msg_hash_from_bytes(&byte_vars)?;
+        let sig_var = circuit.create_ecdsa_signature_variable(curve, &sig.r, &sig.s)?;
+        circuit.ecdsa_verify(curve, &pk_var, &msg_hash_var, &sig_var)?;
+        Ok(circuit)
+    }
+
+    #[test]
+    fn test_curve_params() {
+        let curve = EcdsaCurveParams::secp256k1();
+        assert_eq!(
+            curve.offset_correction,
+            (
+                parse_hex("f63ae480030d8a975272c973310629157aabcb58669f47a110517e9405a52051"),
+                parse_hex("7e7e8233c478b1df1cf1d29fc606287656591183342b596b066150425b786aa4"),
+            )
+        );
+        assert_eq!(
+            mul_point(&curve, curve.generator(), curve.scalar_modulus()),
+            None
+        );
+        let curve = EcdsaCurveParams::secp256r1();
+        assert_eq!(
+            curve.offset_correction,
+            (
+                parse_hex("5838d4af982ca20a64c00598955698004c84b08d3d781da296e73f98bd9eebed"),
+                parse_hex("1b757afa7b6f1734fe8edca1187f59625294c20e42bcf3fae0da43b72f970792"),
+            )
+        );
+        assert_eq!(
+            mul_point(&curve, curve.generator(), curve.scalar_modulus()),
+            None
+        );
+
+        // invalid parameters
+        let mut generator = curve.generator.clone();
+        generator.1 += 1u8;
+        assert!(EcdsaCurveParams::new(
+            curve.base_modulus.clone(),
+            curve.scalar_modulus.clone(),
+            curve.coeff_a.clone(),
+            curve.coeff_b.clone(),
+            generator,
+            curve.offset.clone(),
+        )
+        .is_err());
+        assert!(EcdsaCurveParams::new(
+            curve.base_modulus.clone(),
+            curve.scalar_modulus.clone() / 2u8,
+            curve.coeff_a.clone(),
+            curve.coeff_b.clone(),
+            curve.generator.clone(),
+            curve.offset.clone(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_ecdsa_secp256k1() -> Result<(), PlonkError> {
+        let curve = EcdsaCurveParams::secp256k1();
+        let sig = Signature {
+            pk: (
+                parse_hex("6eda464e47ec2cf94af9871a4dbc6ad0d6876eb3f1d1db912542b832b7aaebc1"),
+                parse_hex("7b6825486981955bb176daf5322f9a0f57c0453353594c864d220fe33f2cc75d"),
+            ),
+            msg_hash: parse_hex("a0ea5328f032f1557fbc5d6516c59cc85e7c0fa270c43085f9c994ef2915449b"),
+            r: parse_hex("2f704c01351369095652e9a1d6adead29e29f4c516018ae01718936358305cb7"),
+            s: parse_hex("6a858e5796e15e7fcaf60dcc9d43bba7f1a19fec2b487f91f0aac7bcfd478e8a"),
+        };
+        test_ecdsa_helper::<Fq377>(&curve, &sig)?;
+        let circuit = build_ecdsa_circuit::<Fq381>(&curve, &sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        let mut rng = test_rng();
+        let circuit = build_ecdsa_circuit::<Fq377>(&curve, &sign(&mut rng, &curve))?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ecdsa_secp256r1() -> Result<(), PlonkError> {
+        let curve = EcdsaCurveParams::secp256r1();
+        let sig = Signature {
+            pk: (
+                parse_hex("f8cdd9744c65a6ecd7d7d6ee1836da15700f2e918b006f7b99d9099315706144"),
+                parse_hex("0d9220961962daa684764a8abe28575d92e37b9f89ba02f78ac5427b53e485de"),
+            ),
+            msg_hash: parse_hex("a0ea5328f032f1557fbc5d6516c59cc85e7c0fa270c43085f9c994ef2915449b"),
+            r: parse_hex("f06456f5d87a006ce09515d5f5ebe11b5c54eeaee563cbb402cfb273d6efee60"),
+            s: parse_hex("fc5000ea509372dd6aa69a8b4b58f007e4a608b87dffb81dc94cbfd404b2b2ce"),
+        };
+        test_ecdsa_helper::<Fq377>(&curve, &sig)?;
+        let circuit = build_ecdsa_circuit::<Fq381>(&curve, &sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        let mut rng = test_rng();
+        let circuit = build_ecdsa_circuit::<Fq381>(&curve, &sign(&mut rng, &curve))?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+
+    fn test_ecdsa_helper<F: PrimeField>(
+        curve: &EcdsaCurveParams,
+        sig: &Signature,
+    ) -> Result<(), PlonkError> {
+        let circuit = build_ecdsa_circuit::<F>(curve, sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong message hash
+        let mut bad_sig = Signature {
+            pk: sig.pk.clone(),
+            msg_hash: &sig.msg_hash + 1u8,
+            r: sig.r.clone(),
+            s: sig.s.clone(),
+        };
+        let circuit = build_ecdsa_circuit::<F>(curve, &bad_sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong signature
+        bad_sig.msg_hash = sig.msg_hash.clone();
+        bad_sig.s = (&sig.s + 1u8) % curve.scalar_modulus();
+        let circuit = build_ecdsa_circuit::<F>(curve, &bad_sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong public key
+        bad_sig.s = sig.s.clone();
+        bad_sig.pk = curve
+            .add_points(&Some(sig.pk.clone()), &Some(curve.generator().clone()))
+            .unwrap();
+        let circuit = build_ecdsa_circuit::<F>(curve, &bad_sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ecdsa_invalid_inputs() -> Result<(), PlonkError> {
+        let curve = EcdsaCurveParams::secp256k1();
+        let (x, y) = curve.generator().clone();
+
+        // the native field is too small
+        let mut circuit = PlonkCircuit::<FqEd254>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        assert!(circuit
+            .create_ecdsa_public_key_variable(&curve, &x, &y)
+            .is_err());
+        // the circuit does not support lookup
+        let mut circuit = PlonkCircuit::<Fq377>::new_turbo_plonk();
+        assert!(circuit
+            .create_ecdsa_public_key_variable(&curve, &x, &y)
+            .is_err());
+
+        let mut circuit = PlonkCircuit::<Fq377>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        // unreduced inputs
+        let p = curve.base_modulus().clone();
+        let n = curve.scalar_modulus().clone();
+        assert!(circuit
+            .create_ecdsa_public_key_variable(&curve, &(&x + &p), &y)
+            .is_err());
+        assert!(circuit
+            .create_ecdsa_signature_variable(&curve, &n, &y)
+            .is_err());
+        // wrong number of bytes
+        let zero = circuit.zero();
+        assert!(circuit.ecdsa_msg_hash_from_bytes(&[zero; 31]).is_err());
+
+        // a point that is not on the curve
+        circuit.create_ecdsa_public_key_variable(&curve, &x, &(&y + 1u8))?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // zero signature components
+        let mut circuit = PlonkCircuit::<Fq377>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        circuit.create_ecdsa_signature_variable(&curve, &BigUint::from(0u8), &y)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,415 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the Ed25519 signature verification of RFC 8032.
+//!
+//! The base field and the scalar field of edwards25519 are emulated with
+//! `EmulatedFieldVar`s, and the challenge is computed with the SHA-512
+//! gadget. The verification is the cofactorless one, i.e. `[S]B = R + [k]A`
+//! where `k = SHA-512(R || A || M) mod L`. The twisted Edwards addition law
+//! is complete, so that no exceptional case has to be handled.
+
+use super::{
+    emulated::{mod_inverse, parse_hex, LIMB_BITS, NUM_BITS},
+    emulated_field::EmulatedFieldVar,
+};
+use crate::{
+    circuit::{customized::sha2::Sha512Gadget, BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+use num_bigint::BigUint;
+
+/// 2^255 - 19
+const BASE_MODULUS: &str = "7fffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffed";
+
+/// The order L of the prime order subgroup.
+const SCALAR_MODULUS: &str = "1000000000000000000000000000000014def9dea2f79cd65812631a5cf5d3ed";
+
+/// -121665 / 121666
+const COEFF_D: &str = "52036cee2b6ffe738cc740797779e89800700a4d4141d8ab75eb4dca135978a3";
+
+/// Coordinates of the base point B.
+const GENERATOR: (&str, &str) = (
+    "216936d3cd6e53fec0a4e231fdd6dc5c692cc7609525a7b2c9562d608f25d51a",
+    "6666666666666666666666666666666666666666666666666666666666666658",
+);
+
+/// Number of bits of the scalars, which are smaller than L.
+const SCALAR_BITS: usize = 253;
+
+/// Number of bytes of encoded points and scalars.
+const ENCODING_LEN: usize = NUM_BITS / 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+// Affine point of edwards25519.
+struct EdwardsPointVar<F: PrimeField> {
+    x: EmulatedFieldVar<F>,
+    y: EmulatedFieldVar<F>,
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Verify an Ed25519 signature: the circuit is satisfied iff `sig_bytes`
+    /// is a valid signature of the message `msg_bytes` under the public key
+    /// `pk_bytes`, as specified in RFC 8032, section 5.1.7, without the
+    /// multiplication by the cofactor.
+    /// * `pk_bytes` - the 32 bytes of the encoded public key
+    /// * `msg_bytes` - the bytes of the message
+    /// * `sig_bytes` - the 64 bytes of the signature
+    /// * `returns` - error if the lengths are wrong, if a byte is not in `[0,
+    ///   256)`, if the circuit does not support lookup or if the native field
+    ///   is too small
+    pub fn ed25519_verify(
+        &mut self,
+        pk_bytes: &[Variable],
+        msg_bytes: &[Variable],
+        sig_bytes: &[Variable],
+    ) -> Result<(), PlonkError> {
+        if pk_bytes.len() != ENCODING_LEN || sig_bytes.len() != 2 * ENCODING_LEN {
+            return Err(ParameterError(format!(
+                "Ed25519 public keys and signatures have {} and {} bytes, got {} and {}",
+                ENCODING_LEN,
+                2 * ENCODING_LEN,
+                pk_bytes.len(),
+                sig_bytes.len()
+            ))
+            .into());
+        }
+        let p_int = parse_hex(BASE_MODULUS);
+        let l_int = parse_hex(SCALAR_MODULUS);
+        let l = self.emulated_modulus(&l_int)?;
+        let d =
+            self.create_constant_emulated_field_variable_with_modulus(&parse_hex(COEFF_D), &p_int)?;
+
+        let a = self.ed25519_decompress(pk_bytes, &d)?;
+        let (r_bytes, s_bytes) = sig_bytes.split_at(ENCODING_LEN);
+        let r = self.ed25519_decompress(r_bytes, &d)?;
+
+        // S < L
+        for byte in s_bytes.iter() {
+            self.unpack(*byte, 8)?;
+        }
+        let s = self.emulated_field_from_bytes_le(s_bytes, &l)?;
+
+        // k = SHA-512(R || A || M) mod L, where the digest is little-endian
+        let mut hash_input = r_bytes.to_vec();
+        hash_input.extend_from_slice(pk_bytes);
+        hash_input.extend_from_slice(msg_bytes);
+        let digest = self.sha512(&hash_input)?;
+        let digest_lo = self.emulated_from_bytes_le(&digest[..ENCODING_LEN])?;
+        let digest_hi = self.emulated_from_bytes_le(&digest[ENCODING_LEN..])?;
+        let digest_lo_int: BigUint = digest_lo.witness(self)?.into_repr().into();
+        let digest_hi_int: BigUint = digest_hi.witness(self)?.into_repr().into();
+        let shift_int = (BigUint::from(1u8) << NUM_BITS) % &l_int;
+        let k_int = (digest_lo_int + digest_hi_int * &shift_int) % &l_int;
+        let k = self.create_emulated_field_variable_with_modulus(&k_int, &l_int)?;
+        let shift = self.emulated_constant(&shift_int)?;
+        let shifted_hi = self.mod_mul(&digest_hi, &shift, &l)?;
+        let quotient_bits = self.emulated_quotient_bits(&l)?;
+        self.enforce_emulated_relation(
+            &[(1, &digest_lo), (1, &shifted_hi), (-1, &k.elem)],
+            &BigUint::from(0u8),
+            &l,
+            quotient_bits,
+        )?;
+
+        // [S]B + [k](-A) with Shamir's trick
+        let s_bits = self.emulated_field_bits(&s)?;
+        let k_bits = self.emulated_field_bits(&k)?;
+        let mut constant = |value: &BigUint| {
+            self.create_constant_emulated_field_variable_with_modulus(value, &p_int)
+        };
+        let zero = constant(&BigUint::from(0u8))?;
+        let identity = EdwardsPointVar {
+            x: zero,
+            y: constant(&BigUint::from(1u8))?,
+        };
+        let b = EdwardsPointVar {
+            x: constant(&parse_hex(GENERATOR.0))?,
+            y: constant(&parse_hex(GENERATOR.1))?,
+        };
+        let neg_a = EdwardsPointVar {
+            x: self.emulated_field_sub(&zero, &a.x)?,
+            y: a.y,
+        };
+        let b_minus_a = self.ed25519_point_add(&b, &neg_a, &d)?;
+        let mut acc = identity;
+        for i in (0..SCALAR_BITS).rev() {
+            acc = self.ed25519_point_add(&acc, &acc, &d)?;
+            let point0 = self.ed25519_point_select(s_bits[i], &identity, &b)?;
+            let point1 = self.ed25519_point_select(s_bits[i], &neg_a, &b_minus_a)?;
+            let point = self.ed25519_point_select(k_bits[i], &point0, &point1)?;
+            acc = self.ed25519_point_add(&acc, &point, &d)?;
+        }
+
+        // both sides are canonical
+        self.emulated_field_equal_gate(&acc.x, &r.x)?;
+        self.emulated_field_equal_gate(&acc.y, &r.y)
+    }
+
+    // Decode a point as specified in RFC 8032, section 5.1.3: the encoding is
+    // the little-endian y-coordinate, whose most significant bit is replaced
+    // by the least significant bit of the x-coordinate. The bytes other than
+    // the last one are expected to be constrained to [0, 256).
+    fn ed25519_decompress(
+        &mut self,
+        bytes: &[Variable],
+        d: &EmulatedFieldVar<F>,
+    ) -> Result<EdwardsPointVar<F>, PlonkError> {
+        let p = d.modulus;
+        let quotient_bits = self.emulated_quotient_bits(&p)?;
+        let last_bits = self.unpack(bytes[ENCODING_LEN - 1], 8)?;
+        let sign = last_bits[7];
+        let last = self.lc(
+            &[bytes[ENCODING_LEN - 1], sign, self.zero(), self.zero()],
+            &[F::one(), -F::from(128u32), F::zero(), F::zero()],
+        )?;
+        let mut y_bytes = bytes[..ENCODING_LEN - 1].to_vec();
+        y_bytes.push(last);
+        let y = self.emulated_field_from_bytes_le(&y_bytes, &p)?;
+
+        // x = ±sqrt((y^2 - 1) / (d * y^2 + 1)), or 0 if there is no square
+        // root, in which case the circuit is not satisfied
+        let p_int = d.modulus();
+        let y_int = self.emulated_field_int_witness(&y)?;
+        let d_int = self.emulated_field_int_witness(d)?;
+        let yy_int = (&y_int * &y_int) % &p_int;
+        let u = (&yy_int + &p_int - BigUint::from(1u8)) % &p_int;
+        let v = (d_int * yy_int + BigUint::from(1u8)) % &p_int;
+        let x_sq = (u * mod_inverse(&v, &p_int)) % &p_int;
+        let mut x_int = x_sq.modpow(&((&p_int + BigUint::from(3u8)) >> 3), &p_int);
+        if (&x_int * &x_int) % &p_int != x_sq {
+            // multiply by sqrt(-1)
+            let sqrt_minus_one = BigUint::from(2u8).modpow(&((&p_int - 1u8) >> 2), &p_int);
+            x_int = (x_int * sqrt_minus_one) % &p_int;
+        }
+        if (&x_int * &x_int) % &p_int != x_sq {
+            x_int = BigUint::from(0u8);
+        }
+        if x_int.bit(0) != (self.witness(sign)? == F::one()) {
+            x_int = (&p_int - x_int) % &p_int;
+        }
+        let x = self.create_emulated_field_variable_with_modulus(&x_int, &p_int)?;
+
+        // x0 = 2 * h + sign, with h in [0, 2^LIMB_BITS)
+        let (x0, _) = x.limbs();
+        let h = (self.witness(x0)? - self.witness(sign)?) * F::from(2u8).inverse().unwrap();
+        let h_var = self.create_variable(h)?;
+        self.range_gate_with_lookup(h_var, LIMB_BITS)?;
+        self.lc_gate(
+            &[h_var, sign, self.zero(), self.zero(), x0],
+            &[F::from(2u8), F::one(), F::zero(), F::zero()],
+        )?;
+
+        // -x^2 + y^2 = 1 + d * x^2 * y^2 mod p
+        let xx = self.emulated_field_mul(&x, &x)?;
+        let yy = self.mod_mul(&y.elem, &y.elem, &p)?;
+        let xxyy = self.mod_mul(&xx.elem, &yy, &p)?;
+        let dxxyy = self.mod_mul(&d.elem, &xxyy, &p)?;
+        self.enforce_emulated_relation(
+            &[(-1, &xx.elem), (1, &yy), (-1, &dxxyy)],
+            &(&p_int - 1u8),
+            &p,
+            quotient_bits,
+        )?;
+
+        Ok(EdwardsPointVar { x, y })
+    }
+
+    // Compute point1 + point2 with the complete addition law
+    //   x3 = (x1 * y2 + y1 * x2) / (1 + d * x1 * x2 * y1 * y2),
+    //   y3 = (y1 * y2 + x1 * x2) / (1 - d * x1 * x2 * y1 * y2).
+    fn ed25519_point_add(
+        &mut self,
+        point1: &EdwardsPointVar<F>,
+        point2: &EdwardsPointVar<F>,
+        d: &EmulatedFieldVar<F>,
+    ) -> Result<EdwardsPointVar<F>, PlonkError> {
+        let p = d.modulus;
+        let quotient_bits = self.emulated_quotient_bits(&p)?;
+        let x1x2 = self.emulated_field_mul(&point1.x, &point2.x)?;
+        let y1y2 = self.mod_mul(&point1.y.elem, &point2.y.elem, &p)?;
+        let x1x2y1y2 = self.mod_mul(&x1x2.elem, &y1y2, &p)?;
+        let t = self.mod_mul(&d.elem, &x1x2y1y2, &p)?;
+        let x1y2 = self.mod_mul(&point1.x.elem, &point2.y.elem, &p)?;
+        let y1x2 = self.mod_mul(&point1.y.elem, &point2.x.elem, &p)?;
+
+        let p_int = d.modulus();
+        let x1x2_int = self.emulated_field_int_witness(&x1x2)?;
+        let y1y2_int: BigUint = y1y2.witness(self)?.into_repr().into();
+        let t_int: BigUint = t.witness(self)?.into_repr().into();
+        let x1y2_int: BigUint = x1y2.witness(self)?.into_repr().into();
+        let y1x2_int: BigUint = y1x2.witness(self)?.into_repr().into();
+        let x3_int = ((x1y2_int + y1x2_int)
+            * mod_inverse(&((BigUint::from(1u8) + &t_int) % &p_int), &p_int))
+            % &p_int;
+        let y3_int = ((y1y2_int + &x1x2_int)
+            * mod_inverse(
+                &((BigUint::from(1u8) + &p_int - &t_int % &p_int) % &p_int),
+                &p_int,
+            ))
+            % &p_int;
+        let x3 = self.create_emulated_field_variable_with_modulus(&x3_int, &p_int)?;
+        let y3 = self.create_emulated_field_variable_with_modulus(&y3_int, &p_int)?;
+
+        // x3 * (1 + t) = x1 * y2 + y1 * x2 mod p
+        let x3t = self.mod_mul(&x3.elem, &t, &p)?;
+        self.enforce_emulated_relation(
+            &[(1, &x3.elem), (1, &x3t), (-1, &x1y2), (-1, &y1x2)],
+            &BigUint::from(0u8),
+            &p,
+            quotient_bits,
+        )?;
+        // y3 * (1 - t) = y1 * y2 + x1 * x2 mod p
+        let y3t = self.mod_mul(&y3.elem, &t, &p)?;
+        self.enforce_emulated_relation(
+            &[(1, &y3.elem), (-1, &y3t), (-1, &y1y2), (-1, &x1x2.elem)],
+            &BigUint::from(0u8),
+            &p,
+            quotient_bits,
+        )?;
+
+        Ok(EdwardsPointVar { x: x3, y: y3 })
+    }
+
+    // Obtain point0 if b is zero, or point1 if b is one.
+    fn ed25519_point_select(
+        &mut self,
+        b: BoolVar,
+        point0: &EdwardsPointVar<F>,
+        point1: &EdwardsPointVar<F>,
+    ) -> Result<EdwardsPointVar<F>, PlonkError> {
+        Ok(EdwardsPointVar {
+            x: self.emulated_field_select(b, &point0.x, &point1.x)?,
+            y: self.emulated_field_select(b, &point0.y, &point1.y)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_bls12_381::Fq as Fq381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+
+    // RFC 8032, section 7.1, tests 1 and 2: (public key, message, signature)
+    const RFC8032_VECTORS: [(&str, &str, &str); 2] = [
+        (
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e065224901555fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+    ];
+
+    fn build_ed25519_circuit<F: PrimeField>(
+        pk: &[u8],
+        msg: &[u8],
+        sig: &[u8],
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut byte_vars = |bytes: &[u8]| {
+            bytes
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()
+        };
+        let pk_vars = byte_vars(pk)?;
+        let msg_vars = byte_vars(msg)?;
+        let sig_vars = byte_vars(sig)?;
+        circuit.ed25519_verify(&pk_vars, &msg_vars, &sig_vars)?;
+        Ok(circuit)
+    }
+
+    #[test]
+    fn test_ed25519() -> Result<(), PlonkError> {
+        for (pk, msg, sig) in RFC8032_VECTORS.iter() {
+            let pk = hex::decode(pk).unwrap();
+            let msg = hex::decode(msg).unwrap();
+            let sig = hex::decode(sig).unwrap();
+            test_ed25519_helper::<Fq377>(&pk, &msg, &sig)?;
+        }
+        let (pk, msg, sig) = RFC8032_VECTORS[1];
+        let circuit = build_ed25519_circuit::<Fq381>(
+            &hex::decode(pk).unwrap(),
+            &hex::decode(msg).unwrap(),
+            &hex::decode(sig).unwrap(),
+        )?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+
+    fn test_ed25519_helper<F: PrimeField>(
+        pk: &[u8],
+        msg: &[u8],
+        sig: &[u8],
+    ) -> Result<(), PlonkError> {
+        let circuit = build_ed25519_circuit::<F>(pk, msg, sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong message
+        let mut bad_msg = msg.to_vec();
+        bad_msg.push(0);
+        let circuit = build_ed25519_circuit::<F>(pk, &bad_msg, sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong R and wrong S
+        for i in [0, ENCODING_LEN] {
+            let mut bad_sig = sig.to_vec();
+            bad_sig[i] ^= 1;
+            let circuit = build_ed25519_circuit::<F>(pk, msg, &bad_sig)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // S + L is rejected
+        let s = BigUint::from_bytes_le(&sig[ENCODING_LEN..]) + parse_hex(SCALAR_MODULUS);
+        let mut bad_sig = sig[..ENCODING_LEN].to_vec();
+        bad_sig.extend(s.to_bytes_le());
+        let circuit = build_ed25519_circuit::<F>(pk, msg, &bad_sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong public key
+        let mut bad_pk = pk.to_vec();
+        bad_pk[0] ^= 1;
+        let circuit = build_ed25519_circuit::<F>(&bad_pk, msg, sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_ed25519_invalid_inputs() -> Result<(), PlonkError> {
+        let (pk, _, sig) = RFC8032_VECTORS[0];
+        let pk = hex::decode(pk).unwrap();
+        let sig = hex::decode(sig).unwrap();
+
+        // wrong lengths
+        assert!(build_ed25519_circuit::<Fq377>(&pk[1..], &[], &sig).is_err());
+        assert!(build_ed25519_circuit::<Fq377>(&pk, &[], &sig[1..]).is_err());
+        // the native field is too small
+        assert!(build_ed25519_circuit::<FqEd254>(&pk, &[], &sig).is_err());
+        // bytes out of range
+        let mut circuit = PlonkCircuit::<Fq377>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let mut sig_vars = sig
+            .iter()
+            .map(|&b| circuit.create_variable(Fq377::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let pk_vars = pk
+            .iter()
+            .map(|&b| circuit.create_variable(Fq377::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        sig_vars[2 * ENCODING_LEN - 1] = circuit.create_variable(Fq377::from(256u32))?;
+        assert!(circuit.ed25519_verify(&pk_vars, &[], &sig_vars).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,299 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Helpers shared by the gadgets over emulated fields of at most 256 bits,
+//! whose elements are `FpElemVar`s with two `LIMB_BITS`-bit limbs.
+//!
+//! An emulated field element is *canonical* if it is in `[0, p)`; the outputs
+//! of `mod_mul` are only guaranteed to be in `[0, 2^256)`. `mod_mul` requires
+//! at least one of its inputs to be canonical.
+
+use super::mod_arith::{FpElem, FpElemVar};
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    constants::GATE_WIDTH,
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec, vec::Vec};
+use num_bigint::BigUint;
+
+/// Number of bits of the limbs of the emulated field elements.
+pub(crate) const LIMB_BITS: usize = 128;
+
+/// Maximal number of bits of the emulated moduli.
+pub(crate) const NUM_BITS: usize = 2 * LIMB_BITS;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    // The range bit length of the circuit, after checking that the native
+    // field is large enough to emulate moduli of `NUM_BITS` bits.
+    pub(crate) fn emulated_range_bit_len(&mut self) -> Result<usize, PlonkError> {
+        let range_bit_len = self.resolve_range_bit_len()?;
+        if LIMB_BITS % range_bit_len != 0 {
+            return Err(ParameterError(format!(
+                "range_bit_len = {} does not divide the limb length {}",
+                range_bit_len, LIMB_BITS
+            ))
+            .into());
+        }
+        // linear relations over the emulated field elements must not wrap
+        // around the native modulus
+        if F::size_in_bits() < NUM_BITS + range_bit_len + 8 {
+            return Err(ParameterError(format!(
+                "native field of {} bits is too small to emulate {}-bit moduli",
+                F::size_in_bits(),
+                NUM_BITS
+            ))
+            .into());
+        }
+        Ok(range_bit_len)
+    }
+
+    // Create a variable for an element of [0, modulus) with range-checked
+    // limbs.
+    pub(crate) fn emulated_canonical_var(
+        &mut self,
+        value: &BigUint,
+        modulus: &FpElem<F>,
+    ) -> Result<FpElemVar<F>, PlonkError> {
+        let modulus_int: BigUint = modulus.field_elem().into_repr().into();
+        if value >= &modulus_int {
+            return Err(PlonkError::NonNativeFieldOverflow);
+        }
+        let var = FpElemVar::new_from_field_element(
+            self,
+            &F::from(value.clone()),
+            LIMB_BITS,
+            Some(modulus.two_power_m()),
+        )?;
+        self.enforce_emulated_lt(&var, modulus)?;
+        Ok(var)
+    }
+
+    // Enforce var < modulus, given that the limbs of var are range checked.
+    pub(crate) fn enforce_emulated_lt(
+        &mut self,
+        var: &FpElemVar<F>,
+        modulus: &FpElem<F>,
+    ) -> Result<(), PlonkError> {
+        // d = modulus - 1 - var, so that var is in [0, modulus) once d is
+        // range checked
+        let d = modulus.field_elem() - F::one() - var.witness(self)?;
+        let d_var = self.create_variable(d)?;
+        self.range_gate_with_lookup(d_var, NUM_BITS)?;
+        let (var0, var1) = var.components();
+        let wires = [var0, var1, d_var, self.one(), self.zero()];
+        let coeffs = [
+            F::one(),
+            modulus.two_power_m(),
+            F::one(),
+            F::one() - modulus.field_elem(),
+        ];
+        self.lc_gate(&wires, &coeffs)
+    }
+
+    // Create a constant emulated field element.
+    pub(crate) fn emulated_constant(
+        &mut self,
+        value: &BigUint,
+    ) -> Result<FpElemVar<F>, PlonkError> {
+        let elem = FpElem::new(&F::from(value.clone()), LIMB_BITS, None)?;
+        let (value0, value1) = elem.components();
+        Ok(FpElemVar::new_from_components(
+            (
+                self.create_constant_variable(value0)?,
+                self.create_constant_variable(value1)?,
+            ),
+            LIMB_BITS,
+            elem.two_power_m(),
+        ))
+    }
+
+    // The little-endian bits of an emulated field element whose limbs are
+    // range checked.
+    pub(crate) fn emulated_bits(
+        &mut self,
+        elem: &FpElemVar<F>,
+    ) -> Result<Vec<BoolVar>, PlonkError> {
+        let (var0, var1) = elem.components();
+        let mut bits = self.unpack(var0, LIMB_BITS)?;
+        bits.extend(self.unpack(var1, LIMB_BITS)?);
+        Ok(bits.into_iter().map(BoolVar::new_unchecked).collect())
+    }
+
+    // Enforce sum_i c_i * x_i + constant = 0 mod `modulus`, where the x_i have
+    // range-checked limbs and the coefficients c_i are small.
+    //
+    // The relation is enforced over the integers as
+    //   sum_i c_i * x_i + constant = k * modulus,
+    // where k + 2^{range_bit_len - 1} is range checked to range_bit_len bits,
+    // which does not wrap around the native modulus. `range_bit_len` may be any
+    // multiple of the range bit length of the circuit, e.g. a larger one for
+    // small moduli, whose quotients k are larger.
+    pub(crate) fn enforce_emulated_relation(
+        &mut self,
+        terms: &[(i64, &FpElemVar<F>)],
+        constant: &BigUint,
+        modulus: &FpElem<F>,
+        range_bit_len: usize,
+    ) -> Result<(), PlonkError> {
+        let modulus_f = modulus.field_elem();
+        let constant = F::from(constant.clone());
+        let mut value = constant;
+        let mut pairs = vec![];
+        for (coeff, elem) in terms.iter() {
+            let coeff = if *coeff >= 0 {
+                F::from(*coeff as u64)
+            } else {
+                -F::from(coeff.unsigned_abs())
+            };
+            value += coeff * elem.witness(self)?;
+            let (var0, var1) = elem.components();
+            pairs.push((var0, coeff));
+            pairs.push((var1, coeff * elem.two_power_m()));
+        }
+        // safe unwrap: the modulus is non-zero
+        let k = value * modulus_f.inverse().unwrap();
+        let bias = F::from(2u8).pow(&[(range_bit_len - 1) as u64]);
+        let k_var = self.create_variable(k + bias)?;
+        self.range_gate_with_lookup(k_var, range_bit_len)?;
+        pairs.push((k_var, -modulus_f));
+        self.enforce_lc_with_constant(pairs, constant + bias * modulus_f)
+    }
+
+    // Enforce sum_i c_i * x_i + constant = 0 over the native field, given the
+    // pairs (x_i, c_i).
+    pub(crate) fn enforce_lc_with_constant(
+        &mut self,
+        mut pairs: Vec<(Variable, F)>,
+        constant: F,
+    ) -> Result<(), PlonkError> {
+        while pairs.len() > GATE_WIDTH {
+            let rest = pairs.split_off(GATE_WIDTH);
+            let wires = [pairs[0].0, pairs[1].0, pairs[2].0, pairs[3].0];
+            let coeffs = [pairs[0].1, pairs[1].1, pairs[2].1, pairs[3].1];
+            let acc = self.lc(&wires, &coeffs)?;
+            pairs = vec![(acc, F::one())];
+            pairs.extend(rest);
+        }
+        pairs.resize(GATE_WIDTH, (self.zero(), F::zero()));
+        let wires = [pairs[0].0, pairs[1].0, pairs[2].0, pairs[3].0, self.zero()];
+        let coeffs = [pairs[0].1, pairs[1].1, pairs[2].1, pairs[3].1];
+        self.quad_poly_gate(
+            &wires,
+            &coeffs,
+            &[F::zero(), F::zero()],
+            F::zero(),
+            constant,
+        )
+    }
+
+    // Obtain a canonical variable for x - y mod p, given canonical x and y.
+    pub(crate) fn emulated_sub(
+        &mut self,
+        x: &FpElemVar<F>,
+        y: &FpElemVar<F>,
+        p: &FpElem<F>,
+        range_bit_len: usize,
+    ) -> Result<FpElemVar<F>, PlonkError> {
+        let p_int: BigUint = p.field_elem().into_repr().into();
+        let x_int: BigUint = x.witness(self)?.into_repr().into();
+        let y_int: BigUint = y.witness(self)?.into_repr().into();
+        let z = self.emulated_canonical_var(&((x_int + &p_int - y_int) % &p_int), p)?;
+        self.enforce_emulated_relation(
+            &[(1, &z), (-1, x), (1, y)],
+            &BigUint::from(0u8),
+            p,
+            range_bit_len,
+        )?;
+        Ok(z)
+    }
+
+    // Obtain a canonical variable for x * y mod p, given that at least one of
+    // x and y is canonical.
+    pub(crate) fn emulated_mul_canonical(
+        &mut self,
+        x: &FpElemVar<F>,
+        y: &FpElemVar<F>,
+        p: &FpElem<F>,
+        range_bit_len: usize,
+    ) -> Result<FpElemVar<F>, PlonkError> {
+        let p_int: BigUint = p.field_elem().into_repr().into();
+        let x_int: BigUint = x.witness(self)?.into_repr().into();
+        let y_int: BigUint = y.witness(self)?.into_repr().into();
+        let z = self.emulated_canonical_var(&((x_int * y_int) % &p_int), p)?;
+        let xy = self.mod_mul(x, y, p)?;
+        self.enforce_emulated_relation(
+            &[(1, &xy), (-1, &z)],
+            &BigUint::from(0u8),
+            p,
+            range_bit_len,
+        )?;
+        Ok(z)
+    }
+
+    // Obtain x0 if b is zero, or x1 if b is one.
+    pub(crate) fn emulated_select(
+        &mut self,
+        b: BoolVar,
+        x0: &FpElemVar<F>,
+        x1: &FpElemVar<F>,
+    ) -> Result<FpElemVar<F>, PlonkError> {
+        let (x00, x01) = x0.components();
+        let (x10, x11) = x1.components();
+        Ok(FpElemVar::new_from_components(
+            (
+                self.conditional_select(b, x00, x10)?,
+                self.conditional_select(b, x01, x11)?,
+            ),
+            x0.param_m(),
+            x0.two_power_m(),
+        ))
+    }
+
+    // Obtain the emulated field element whose little-endian bytes are
+    // `bytes`, which are expected to be constrained to [0, 256).
+    pub(crate) fn emulated_from_bytes_le(
+        &mut self,
+        bytes: &[Variable],
+    ) -> Result<FpElemVar<F>, PlonkError> {
+        if bytes.len() != NUM_BITS / 8 {
+            return Err(ParameterError(format!(
+                "emulated field elements have {} bytes, got {}",
+                NUM_BITS / 8,
+                bytes.len()
+            ))
+            .into());
+        }
+        self.check_vars_bound(bytes)?;
+        let base = F::from(256u32);
+        let mut limbs = vec![];
+        for limb_bytes in bytes.chunks(LIMB_BITS / 8) {
+            let mut limb = F::zero();
+            for byte in limb_bytes.iter().rev() {
+                limb = limb * base + self.witness(*byte)?;
+            }
+            let limb_var = self.create_variable(limb)?;
+            self.decompose_vars_gate(limb_bytes.to_vec(), limb_var, base)?;
+            limbs.push(limb_var);
+        }
+        Ok(FpElemVar::new_from_components(
+            (limbs[0], limbs[1]),
+            LIMB_BITS,
+            F::from(2u8).pow(&[LIMB_BITS as u64]),
+        ))
+    }
+}
+
+// The inverse of x modulo the prime `modulus`, or zero if x is zero.
+pub(crate) fn mod_inverse(x: &BigUint, modulus: &BigUint) -> BigUint {
+    x.modpow(&(modulus - BigUint::from(2u8)), modulus)
+}
+
+pub(crate) fn parse_hex(hex: &str) -> BigUint {
+    // safe unwrap: only called on valid hexadecimal constants
+    BigUint::parse_bytes(hex.as_bytes(), 16).unwrap()
+}
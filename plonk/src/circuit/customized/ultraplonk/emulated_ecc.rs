@@ -0,0 +1,474 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Group operations over short Weierstrass curves whose base field differs
+//! from the native field of the circuit, e.g. for the verification of
+//! signatures over a foreign curve. The coordinates are emulated with
+//! `EmulatedFieldVar`s.
+//!
+//! Points are represented in affine coordinates and cannot be the point at
+//! infinity. The addition and doubling formulas are incomplete: the circuit
+//! is not satisfied if their exceptional cases are met. The scalar
+//! multiplication starts from a fixed offset point so that these cases are
+//! only met with negligible probability. The slopes of the formulas are
+//! witnessed and constrained by a single relation each, instead of being
+//! computed with an emulated inversion.
+
+use super::{
+    emulated::mod_inverse,
+    emulated_field::{check_same_field, EmulatedFieldVar},
+};
+use crate::{
+    circuit::{BoolVar, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, AffineCurve, ProjectiveCurve, SWModelParameters,
+};
+use ark_ff::{One, PrimeField, Zero};
+use ark_std::string::ToString;
+use num_bigint::BigUint;
+
+/// Seed of the offset point of the scalar multiplications.
+const OFFSET_SEED: &[u8] = b"jellyfish emulated ecc offset";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Variable of an affine point, other than the point at infinity, of a short
+/// Weierstrass curve over an emulated field.
+pub struct EmulatedPointVariable<F: PrimeField> {
+    pub(crate) x: EmulatedFieldVar<F>,
+    pub(crate) y: EmulatedFieldVar<F>,
+}
+
+impl<F: PrimeField> EmulatedPointVariable<F> {
+    /// Get the variable of the x-coordinate.
+    pub fn get_x(&self) -> EmulatedFieldVar<F> {
+        self.x
+    }
+
+    /// Get the variable of the y-coordinate.
+    pub fn get_y(&self) -> EmulatedFieldVar<F> {
+        self.y
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a variable for a point of the curve `P` and enforce that it lies
+    /// on the curve.
+    /// Return error if the point is the point at infinity, or if the circuit
+    /// does not support emulated fields.
+    pub fn create_emulated_point_variable<P>(
+        &mut self,
+        point: &GroupAffine<P>,
+    ) -> Result<EmulatedPointVariable<F>, PlonkError>
+    where
+        P: SWModelParameters,
+        P::BaseField: PrimeField,
+    {
+        check_not_infinity(point)?;
+        let point_var = EmulatedPointVariable {
+            x: self.create_emulated_field_variable(point.x)?,
+            y: self.create_emulated_field_variable(point.y)?,
+        };
+        self.emulated_on_curve_gate::<P>(&point_var)?;
+        Ok(point_var)
+    }
+
+    /// Create a constant variable for a point of the curve `P`.
+    /// Return error if the point is the point at infinity, or if the circuit
+    /// does not support emulated fields.
+    pub fn create_constant_emulated_point_variable<P>(
+        &mut self,
+        point: &GroupAffine<P>,
+    ) -> Result<EmulatedPointVariable<F>, PlonkError>
+    where
+        P: SWModelParameters,
+        P::BaseField: PrimeField,
+    {
+        check_not_infinity(point)?;
+        Ok(EmulatedPointVariable {
+            x: self.create_constant_emulated_field_variable(point.x)?,
+            y: self.create_constant_emulated_field_variable(point.y)?,
+        })
+    }
+
+    /// The value of a point variable.
+    /// Return error if the variables are invalid.
+    pub fn emulated_point_witness<P>(
+        &self,
+        point: &EmulatedPointVariable<F>,
+    ) -> Result<GroupAffine<P>, PlonkError>
+    where
+        P: SWModelParameters,
+        P::BaseField: PrimeField,
+    {
+        Ok(GroupAffine::new(
+            self.emulated_field_witness(&point.x)?,
+            self.emulated_field_witness(&point.y)?,
+            false,
+        ))
+    }
+
+    /// Enforce that a point lies on the curve `P`, i.e. that `y^2 = x^3 + a *
+    /// x + b`.
+    /// Return error if the variables are invalid.
+    pub fn emulated_on_curve_gate<P>(
+        &mut self,
+        point: &EmulatedPointVariable<F>,
+    ) -> Result<(), PlonkError>
+    where
+        P: SWModelParameters,
+        P::BaseField: PrimeField,
+    {
+        self.emulated_on_curve_gate_with_coeffs(point, &P::COEFF_A.into(), &P::COEFF_B.into())
+    }
+
+    /// Obtain a variable for `point1 + point2`. The circuit is not satisfied
+    /// if `point1 = ±point2`, see `emulated_ecc_double` for the doubling.
+    /// Return error if the variables are invalid or if the points are not
+    /// over the same field.
+    pub fn emulated_ecc_add(
+        &mut self,
+        point1: &EmulatedPointVariable<F>,
+        point2: &EmulatedPointVariable<F>,
+    ) -> Result<EmulatedPointVariable<F>, PlonkError> {
+        check_same_field(&point1.x, &point2.x)?;
+        let p = point1.x.modulus;
+        let quotient_bits = self.emulated_quotient_bits(&p)?;
+
+        // x1 != x2, as both are canonical
+        let (x10, x11) = point1.x.limbs();
+        let (x20, x21) = point2.x.limbs();
+        let two_power_m = p.two_power_m();
+        let x_diff = self.lc(
+            &[x20, x21, x10, x11],
+            &[F::one(), two_power_m, -F::one(), -two_power_m],
+        )?;
+        self.non_zero_gate(x_diff)?;
+
+        // lambda * (x2 - x1) = y2 - y1 mod p
+        let p_int = point1.x.modulus();
+        let x1_int = self.emulated_field_int_witness(&point1.x)?;
+        let y1_int = self.emulated_field_int_witness(&point1.y)?;
+        let x2_int = self.emulated_field_int_witness(&point2.x)?;
+        let y2_int = self.emulated_field_int_witness(&point2.y)?;
+        let lambda_int = ((y2_int + &p_int - y1_int)
+            * mod_inverse(&((x2_int + &p_int - x1_int) % &p_int), &p_int))
+            % &p_int;
+        let lambda = self.create_emulated_field_variable_with_modulus(&lambda_int, &p_int)?;
+        let dx = self.emulated_field_sub(&point2.x, &point1.x)?;
+        let lambda_dx = self.mod_mul(&lambda.elem, &dx.elem, &p)?;
+        self.enforce_emulated_relation(
+            &[(1, &lambda_dx), (-1, &point2.y.elem), (1, &point1.y.elem)],
+            &BigUint::from(0u8),
+            &p,
+            quotient_bits,
+        )?;
+
+        self.emulated_point_from_slope(&lambda, point1, point2)
+    }
+
+    /// Obtain a variable for `2 * point` over the curve `P`. The circuit is
+    /// not satisfied if the y-coordinate of `point` is zero.
+    /// Return error if the variables are invalid.
+    pub fn emulated_ecc_double<P>(
+        &mut self,
+        point: &EmulatedPointVariable<F>,
+    ) -> Result<EmulatedPointVariable<F>, PlonkError>
+    where
+        P: SWModelParameters,
+        P::BaseField: PrimeField,
+    {
+        self.emulated_ecc_double_with_coeff(point, &P::COEFF_A.into())
+    }
+
+    /// Obtain a variable for `scalar * base` over the curve `P`, where
+    /// `scalar_bits_le` are the little-endian bits of the scalar, which are
+    /// expected to be constrained to be boolean, e.g. by `unpack`. The circuit
+    /// is not satisfied if the result is the point at infinity.
+    /// Return error if the variables are invalid or if a bit is not boolean.
+    pub fn emulated_scalar_mul<P>(
+        &mut self,
+        scalar_bits_le: &[Variable],
+        base: &EmulatedPointVariable<F>,
+    ) -> Result<EmulatedPointVariable<F>, PlonkError>
+    where
+        P: SWModelParameters,
+        P::BaseField: PrimeField,
+    {
+        for &bit in scalar_bits_le {
+            self.check_var_bound(bit)?;
+            self.check_bool(bit)?;
+        }
+
+        // acc = 2^n * offset + scalar * base at the end of the loop, and the
+        // offset is removed with a last addition
+        let offset = offset_point::<P>()?;
+        let mut correction = offset.into_projective();
+        for _ in 0..scalar_bits_le.len() {
+            correction.double_in_place();
+        }
+        let correction = -correction.into_affine();
+        check_not_infinity(&correction)?;
+
+        let mut acc = self.create_constant_emulated_point_variable(&offset)?;
+        for &bit in scalar_bits_le.iter().rev() {
+            acc = self.emulated_ecc_double::<P>(&acc)?;
+            let sum = self.emulated_ecc_add(&acc, base)?;
+            acc = self.emulated_point_select(BoolVar::new_unchecked(bit), &acc, &sum)?;
+        }
+        let correction = self.create_constant_emulated_point_variable(&correction)?;
+        self.emulated_ecc_add(&acc, &correction)
+    }
+
+    /// Obtain `point0` if `b` is zero, or `point1` if `b` is one.
+    /// Return error if the variables are invalid.
+    pub fn emulated_point_select(
+        &mut self,
+        b: BoolVar,
+        point0: &EmulatedPointVariable<F>,
+        point1: &EmulatedPointVariable<F>,
+    ) -> Result<EmulatedPointVariable<F>, PlonkError> {
+        Ok(EmulatedPointVariable {
+            x: self.emulated_field_select(b, &point0.x, &point1.x)?,
+            y: self.emulated_field_select(b, &point0.y, &point1.y)?,
+        })
+    }
+
+    /// Constrain two points to be equal.
+    /// Return error if the variables are invalid.
+    pub fn emulated_point_equal_gate(
+        &mut self,
+        point0: &EmulatedPointVariable<F>,
+        point1: &EmulatedPointVariable<F>,
+    ) -> Result<(), PlonkError> {
+        self.emulated_field_equal_gate(&point0.x, &point1.x)?;
+        self.emulated_field_equal_gate(&point0.y, &point1.y)
+    }
+
+    // Create a constant variable for the affine point `(x, y)` of a curve over
+    // the field of prime order `modulus`.
+    pub(crate) fn create_constant_emulated_point_variable_with_modulus(
+        &mut self,
+        point: &(BigUint, BigUint),
+        modulus: &BigUint,
+    ) -> Result<EmulatedPointVariable<F>, PlonkError> {
+        Ok(EmulatedPointVariable {
+            x: self.create_constant_emulated_field_variable_with_modulus(&point.0, modulus)?,
+            y: self.create_constant_emulated_field_variable_with_modulus(&point.1, modulus)?,
+        })
+    }
+
+    // Enforce that a point lies on the curve y^2 = x^3 + a * x + b, where the
+    // coefficients are reduced.
+    pub(crate) fn emulated_on_curve_gate_with_coeffs(
+        &mut self,
+        point: &EmulatedPointVariable<F>,
+        coeff_a: &BigUint,
+        coeff_b: &BigUint,
+    ) -> Result<(), PlonkError> {
+        check_same_field(&point.x, &point.y)?;
+        let p = point.x.modulus;
+        let quotient_bits = self.emulated_quotient_bits(&p)?;
+        let p_int = point.x.modulus();
+
+        // y^2 - x^3 - a * x - b = 0 mod p
+        let yy = self.mod_mul(&point.y.elem, &point.y.elem, &p)?;
+        let xx = self.mod_mul(&point.x.elem, &point.x.elem, &p)?;
+        let xxx = self.mod_mul(&xx, &point.x.elem, &p)?;
+        let a = self.emulated_constant(coeff_a)?;
+        let ax = self.mod_mul(&a, &point.x.elem, &p)?;
+        let neg_b = (&p_int - coeff_b) % &p_int;
+        self.enforce_emulated_relation(
+            &[(1, &yy), (-1, &xxx), (-1, &ax)],
+            &neg_b,
+            &p,
+            quotient_bits,
+        )
+    }
+
+    // Compute 2 * point over a curve whose coefficient a is the reduced
+    // `coeff_a`. The circuit is not satisfied if the y-coordinate of point is
+    // zero.
+    pub(crate) fn emulated_ecc_double_with_coeff(
+        &mut self,
+        point: &EmulatedPointVariable<F>,
+        coeff_a: &BigUint,
+    ) -> Result<EmulatedPointVariable<F>, PlonkError> {
+        check_same_field(&point.x, &point.y)?;
+        let p = point.x.modulus;
+        let quotient_bits = self.emulated_quotient_bits(&p)?;
+
+        // 2 * lambda * y = 3 * x^2 + a mod p
+        let p_int = point.x.modulus();
+        let x_int = self.emulated_field_int_witness(&point.x)?;
+        let y_int = self.emulated_field_int_witness(&point.y)?;
+        let lambda_int = ((BigUint::from(3u8) * &x_int * &x_int + coeff_a)
+            * mod_inverse(&((BigUint::from(2u8) * y_int) % &p_int), &p_int))
+            % &p_int;
+        let lambda = self.create_emulated_field_variable_with_modulus(&lambda_int, &p_int)?;
+        let lambda_y = self.mod_mul(&lambda.elem, &point.y.elem, &p)?;
+        let xx = self.mod_mul(&point.x.elem, &point.x.elem, &p)?;
+        let neg_a = (&p_int - coeff_a) % &p_int;
+        self.enforce_emulated_relation(&[(2, &lambda_y), (-3, &xx)], &neg_a, &p, quotient_bits)?;
+
+        self.emulated_point_from_slope(&lambda, point, point)
+    }
+
+    // Compute the sum of point1 and point2 given the slope lambda of the line
+    // through them:
+    //   x3 = lambda^2 - x1 - x2,
+    //   y3 = lambda * (x1 - x3) - y1.
+    fn emulated_point_from_slope(
+        &mut self,
+        lambda: &EmulatedFieldVar<F>,
+        point1: &EmulatedPointVariable<F>,
+        point2: &EmulatedPointVariable<F>,
+    ) -> Result<EmulatedPointVariable<F>, PlonkError> {
+        let p = lambda.modulus;
+        let quotient_bits = self.emulated_quotient_bits(&p)?;
+        let p_int = lambda.modulus();
+        let lambda_int = self.emulated_field_int_witness(lambda)?;
+        let x1_int = self.emulated_field_int_witness(&point1.x)?;
+        let y1_int = self.emulated_field_int_witness(&point1.y)?;
+        let x2_int = self.emulated_field_int_witness(&point2.x)?;
+        let x3_int =
+            (&lambda_int * &lambda_int + BigUint::from(2u8) * &p_int - &x1_int - x2_int) % &p_int;
+        let y3_int = (&lambda_int * (&x1_int + &p_int - &x3_int) + &p_int - y1_int) % &p_int;
+
+        let x3 = self.create_emulated_field_variable_with_modulus(&x3_int, &p_int)?;
+        let lambda_sq = self.mod_mul(&lambda.elem, &lambda.elem, &p)?;
+        self.enforce_emulated_relation(
+            &[
+                (1, &lambda_sq),
+                (-1, &point1.x.elem),
+                (-1, &point2.x.elem),
+                (-1, &x3.elem),
+            ],
+            &BigUint::from(0u8),
+            &p,
+            quotient_bits,
+        )?;
+
+        let y3 = self.create_emulated_field_variable_with_modulus(&y3_int, &p_int)?;
+        let dx = self.emulated_field_sub(&point1.x, &x3)?;
+        let lambda_dx = self.mod_mul(&lambda.elem, &dx.elem, &p)?;
+        self.enforce_emulated_relation(
+            &[(1, &lambda_dx), (-1, &point1.y.elem), (-1, &y3.elem)],
+            &BigUint::from(0u8),
+            &p,
+            quotient_bits,
+        )?;
+
+        Ok(EmulatedPointVariable { x: x3, y: y3 })
+    }
+}
+
+fn check_not_infinity<P: SWModelParameters>(point: &GroupAffine<P>) -> Result<(), PlonkError> {
+    if point.is_zero() {
+        return Err(ParameterError("the point at infinity is not supported".to_string()).into());
+    }
+    Ok(())
+}
+
+// The first point of the curve whose x-coordinate is at least the integer
+// encoding of `OFFSET_SEED`, whose discrete logarithm is unknown.
+fn offset_point<P: SWModelParameters>() -> Result<GroupAffine<P>, PlonkError> {
+    let mut x = P::BaseField::from_le_bytes_mod_order(OFFSET_SEED);
+    for _ in 0..1000 {
+        if let Some(point) = GroupAffine::<P>::get_point_from_x(x, false) {
+            return Ok(point);
+        }
+        x += P::BaseField::one();
+    }
+    Err(ParameterError("failed to find an offset point".to_string()).into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::Circuit;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_bls12_381::Fq as Fq381;
+    use ark_bn254::{g1::Parameters as Bn254G1Parameters, Fr as Fr254, G1Affine};
+    use ark_std::{test_rng, vec::Vec, UniformRand};
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+
+    #[test]
+    fn test_emulated_ecc() -> Result<(), PlonkError> {
+        test_emulated_ecc_helper::<Fq377>()?;
+        test_emulated_ecc_helper::<Fq381>()
+    }
+
+    fn test_emulated_ecc_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let g = G1Affine::prime_subgroup_generator();
+        let p1 = g.mul(Fr254::rand(rng)).into_affine();
+        let p2 = g.mul(Fr254::rand(rng)).into_affine();
+
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let p1_var = circuit.create_emulated_point_variable(&p1)?;
+        let p2_var = circuit.create_emulated_point_variable(&p2)?;
+        let g_var = circuit.create_constant_emulated_point_variable(&g)?;
+        let sum = circuit.emulated_ecc_add(&p1_var, &p2_var)?;
+        assert_eq!(circuit.emulated_point_witness(&sum)?, p1 + p2);
+        let double = circuit.emulated_ecc_double::<Bn254G1Parameters>(&p1_var)?;
+        assert_eq!(circuit.emulated_point_witness(&double)?, p1 + p1);
+
+        // 64-bit scalar multiplication
+        let scalar = u64::rand(rng);
+        let bits = (0..64)
+            .map(|i| circuit.create_variable(F::from((scalar >> i) & 1)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        for &bit in bits.iter() {
+            circuit.bool_gate(bit)?;
+        }
+        let product = circuit.emulated_scalar_mul::<Bn254G1Parameters>(&bits, &p1_var)?;
+        assert_eq!(
+            circuit.emulated_point_witness(&product)?,
+            p1.mul(Fr254::from(scalar)).into_affine()
+        );
+        let five_bits = [circuit.one(), circuit.zero(), circuit.one()];
+        let product = circuit.emulated_scalar_mul::<Bn254G1Parameters>(&five_bits, &g_var)?;
+        assert_eq!(
+            circuit.emulated_point_witness(&product)?,
+            g.mul(Fr254::from(5u64)).into_affine()
+        );
+        let bit = circuit.enforce_bool(bits[0])?;
+        let selected = circuit.emulated_point_select(bit, &sum, &double)?;
+        circuit
+            .emulated_point_equal_gate(&selected, if scalar & 1 == 1 { &double } else { &sum })?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong result
+        *circuit.witness_mut(sum.get_x().limbs().0) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // the addition of a point to itself is not supported
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let p1_var = circuit.create_emulated_point_variable(&p1)?;
+        circuit.emulated_ecc_add(&p1_var, &p1_var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // point not on the curve
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        circuit.create_emulated_point_variable(&G1Affine::new(p1.x, p2.y, false))?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid inputs
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        assert!(circuit
+            .create_emulated_point_variable(&G1Affine::zero())
+            .is_err());
+        let p1_var = circuit.create_emulated_point_variable(&p1)?;
+        let two = circuit.create_variable(F::from(2u8))?;
+        assert!(circuit
+            .emulated_scalar_mul::<Bn254G1Parameters>(&[two], &p1_var)
+            .is_err());
+        Ok(())
+    }
+}
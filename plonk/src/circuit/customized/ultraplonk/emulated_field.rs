@@ -0,0 +1,436 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Arithmetic over an emulated prime field of at most 256 bits, i.e. a field
+//! which differs from the native field `F` of the circuit, on top of the
+//! modular arithmetic gates of `mod_arith`. This is the representation of
+//! the coordinates and scalars of the gadgets over foreign curves, such as
+//! `emulated_ecc`, `ecdsa` and `ed25519`.
+//!
+//! The elements are `EmulatedFieldVar`s, which are split into two 128-bit
+//! limbs, carry the modulus of their field and are always canonical, i.e. in
+//! `[0, p)`. The modulus is either the one of an arkworks field `T`, or any
+//! prime given at runtime, e.g. by the parameters of an ECDSA curve. As for
+//! the other gadgets over emulated fields, the circuit has to support lookup,
+//! and the native field has to be larger than about 280 bits, e.g. the base
+//! field of BLS12-377 or of BLS12-381.
+
+use super::{
+    emulated::{mod_inverse, LIMB_BITS, NUM_BITS},
+    mod_arith::{FpElem, FpElemVar},
+};
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ff::{FpParameters, PrimeField};
+use ark_std::{format, string::ToString, vec::Vec};
+use num_bigint::BigUint;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+/// Variable of a canonical element of an emulated prime field.
+pub struct EmulatedFieldVar<F: PrimeField> {
+    pub(crate) elem: FpElemVar<F>,
+    pub(crate) modulus: FpElem<F>,
+}
+
+impl<F: PrimeField> EmulatedFieldVar<F> {
+    /// The variables of the low and high 128-bit limbs of the element.
+    pub fn limbs(&self) -> (Variable, Variable) {
+        self.elem.components()
+    }
+
+    /// The modulus of the field of the element.
+    pub fn modulus(&self) -> BigUint {
+        self.modulus.field_elem().into_repr().into()
+    }
+
+    // Wrap an element whose limbs are range checked and which is constrained
+    // to be smaller than `modulus`.
+    pub(crate) fn new(elem: FpElemVar<F>, modulus: FpElem<F>) -> Self {
+        Self { elem, modulus }
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a variable for an element of the emulated field `T`.
+    /// Return error if `T` has more than 256 bits, or if the circuit does not
+    /// support emulated fields.
+    pub fn create_emulated_field_variable<T: PrimeField>(
+        &mut self,
+        value: T,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        self.create_emulated_field_variable_with_modulus(&value.into(), &T::Params::MODULUS.into())
+    }
+
+    /// Create a constant variable for an element of the emulated field `T`.
+    /// Return error if `T` has more than 256 bits, or if the circuit does not
+    /// support emulated fields.
+    pub fn create_constant_emulated_field_variable<T: PrimeField>(
+        &mut self,
+        value: T,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        self.create_constant_emulated_field_variable_with_modulus(
+            &value.into(),
+            &T::Params::MODULUS.into(),
+        )
+    }
+
+    /// Create a variable for `value` in the emulated field of prime order
+    /// `modulus`.
+    /// Return error if `value` is not smaller than `modulus`, if `modulus`
+    /// has more than 256 bits, or if the circuit does not support emulated
+    /// fields.
+    pub fn create_emulated_field_variable_with_modulus(
+        &mut self,
+        value: &BigUint,
+        modulus: &BigUint,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        let p = self.emulated_modulus(modulus)?;
+        let elem = self.emulated_canonical_var(value, &p)?;
+        Ok(EmulatedFieldVar::new(elem, p))
+    }
+
+    /// Create a constant variable for `value` in the emulated field of prime
+    /// order `modulus`.
+    /// Return error if `value` is not smaller than `modulus`, if `modulus`
+    /// has more than 256 bits, or if the circuit does not support emulated
+    /// fields.
+    pub fn create_constant_emulated_field_variable_with_modulus(
+        &mut self,
+        value: &BigUint,
+        modulus: &BigUint,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        let p = self.emulated_modulus(modulus)?;
+        if value >= modulus {
+            return Err(PlonkError::NonNativeFieldOverflow);
+        }
+        let elem = self.emulated_constant(value)?;
+        Ok(EmulatedFieldVar::new(elem, p))
+    }
+
+    /// The value of an element of the emulated field `T`.
+    /// Return error if the variables are invalid or if the element is not in
+    /// `T`.
+    pub fn emulated_field_witness<T: PrimeField>(
+        &self,
+        x: &EmulatedFieldVar<F>,
+    ) -> Result<T, PlonkError> {
+        let modulus: BigUint = T::Params::MODULUS.into();
+        if x.modulus() != modulus {
+            return Err(
+                ParameterError("the element is not in the emulated field".to_string()).into(),
+            );
+        }
+        Ok(T::from(self.emulated_field_int_witness(x)?))
+    }
+
+    /// The value of an emulated field element, as an integer in `[0, p)`.
+    /// Return error if the variables are invalid.
+    pub fn emulated_field_int_witness(
+        &self,
+        x: &EmulatedFieldVar<F>,
+    ) -> Result<BigUint, PlonkError> {
+        Ok(x.elem.witness(self)?.into_repr().into())
+    }
+
+    /// Obtain a variable for `x + y` over the emulated field.
+    /// Return error if the variables are invalid or if `x` and `y` are not in
+    /// the same field.
+    pub fn emulated_field_add(
+        &mut self,
+        x: &EmulatedFieldVar<F>,
+        y: &EmulatedFieldVar<F>,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        check_same_field(x, y)?;
+        let quotient_bits = self.emulated_quotient_bits(&x.modulus)?;
+        let p_int = x.modulus();
+        let value =
+            (self.emulated_field_int_witness(x)? + self.emulated_field_int_witness(y)?) % &p_int;
+        let z = self.create_emulated_field_variable_with_modulus(&value, &p_int)?;
+        self.enforce_emulated_relation(
+            &[(1, &x.elem), (1, &y.elem), (-1, &z.elem)],
+            &BigUint::from(0u8),
+            &x.modulus,
+            quotient_bits,
+        )?;
+        Ok(z)
+    }
+
+    /// Obtain a variable for `x - y` over the emulated field.
+    /// Return error if the variables are invalid or if `x` and `y` are not in
+    /// the same field.
+    pub fn emulated_field_sub(
+        &mut self,
+        x: &EmulatedFieldVar<F>,
+        y: &EmulatedFieldVar<F>,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        check_same_field(x, y)?;
+        let quotient_bits = self.emulated_quotient_bits(&x.modulus)?;
+        let z = self.emulated_sub(&x.elem, &y.elem, &x.modulus, quotient_bits)?;
+        Ok(EmulatedFieldVar::new(z, x.modulus))
+    }
+
+    /// Obtain a variable for `x * y` over the emulated field.
+    /// Return error if the variables are invalid or if `x` and `y` are not in
+    /// the same field.
+    pub fn emulated_field_mul(
+        &mut self,
+        x: &EmulatedFieldVar<F>,
+        y: &EmulatedFieldVar<F>,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        check_same_field(x, y)?;
+        let quotient_bits = self.emulated_quotient_bits(&x.modulus)?;
+        let z = self.emulated_mul_canonical(&x.elem, &y.elem, &x.modulus, quotient_bits)?;
+        Ok(EmulatedFieldVar::new(z, x.modulus))
+    }
+
+    /// Obtain a variable for the inverse of `x` over the emulated field. The
+    /// circuit is not satisfied if `x` is zero.
+    /// Return error if the variables are invalid.
+    pub fn emulated_field_inverse(
+        &mut self,
+        x: &EmulatedFieldVar<F>,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        let quotient_bits = self.emulated_quotient_bits(&x.modulus)?;
+        let p_int = x.modulus();
+        let x_int = self.emulated_field_int_witness(x)?;
+        let z = self.emulated_canonical_var(&mod_inverse(&x_int, &p_int), &x.modulus)?;
+        // x * z - 1 = 0 mod p
+        let xz = self.mod_mul(&x.elem, &z, &x.modulus)?;
+        self.enforce_emulated_relation(&[(1, &xz)], &(p_int - 1u8), &x.modulus, quotient_bits)?;
+        Ok(EmulatedFieldVar::new(z, x.modulus))
+    }
+
+    /// Obtain `x0` if `b` is zero, or `x1` if `b` is one.
+    /// Return error if the variables are invalid or if `x0` and `x1` are not
+    /// in the same field.
+    pub fn emulated_field_select(
+        &mut self,
+        b: BoolVar,
+        x0: &EmulatedFieldVar<F>,
+        x1: &EmulatedFieldVar<F>,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        check_same_field(x0, x1)?;
+        let z = self.emulated_select(b, &x0.elem, &x1.elem)?;
+        Ok(EmulatedFieldVar::new(z, x0.modulus))
+    }
+
+    /// Constrain two emulated field elements to be equal.
+    /// Return error if the variables are invalid or if `x` and `y` are not in
+    /// the same field.
+    pub fn emulated_field_equal_gate(
+        &mut self,
+        x: &EmulatedFieldVar<F>,
+        y: &EmulatedFieldVar<F>,
+    ) -> Result<(), PlonkError> {
+        check_same_field(x, y)?;
+        // the elements are canonical, hence equal iff their limbs are
+        let (x0, x1) = x.limbs();
+        let (y0, y1) = y.limbs();
+        self.equal_gate(x0, y0)?;
+        self.equal_gate(x1, y1)
+    }
+
+    // Obtain the element of the field of prime order `modulus` whose
+    // little-endian bytes are `bytes`, which are expected to be constrained to
+    // [0, 256), and enforce that it is canonical.
+    pub(crate) fn emulated_field_from_bytes_le(
+        &mut self,
+        bytes: &[Variable],
+        modulus: &FpElem<F>,
+    ) -> Result<EmulatedFieldVar<F>, PlonkError> {
+        let elem = self.emulated_from_bytes_le(bytes)?;
+        self.enforce_emulated_lt(&elem, modulus)?;
+        Ok(EmulatedFieldVar::new(elem, *modulus))
+    }
+
+    // The little-endian bits of an emulated field element.
+    pub(crate) fn emulated_field_bits(
+        &mut self,
+        x: &EmulatedFieldVar<F>,
+    ) -> Result<Vec<BoolVar>, PlonkError> {
+        self.emulated_bits(&x.elem)
+    }
+
+    // The modulus in the native field, after checking that it has at most
+    // `NUM_BITS` bits and that the circuit supports emulated fields.
+    pub(crate) fn emulated_modulus(&mut self, modulus: &BigUint) -> Result<FpElem<F>, PlonkError> {
+        self.emulated_range_bit_len()?;
+        if modulus.bits() as usize > NUM_BITS || modulus < &BigUint::from(2u8) {
+            return Err(ParameterError(format!(
+                "emulated moduli should be in [2, 2^{}), got {}",
+                NUM_BITS, modulus
+            ))
+            .into());
+        }
+        FpElem::new(&F::from(modulus.clone()), LIMB_BITS, None)
+    }
+
+    // The bit length of the quotients of the relations modulo `modulus`, which
+    // increases as the modulus gets smaller. The relations of the emulated
+    // gadgets have terms smaller than 2^NUM_BITS whose coefficients sum to at
+    // most 5 in absolute value, and a canonical constant, so that their
+    // quotients are smaller than 2^{NUM_BITS + 4 - num_bits} in absolute
+    // value.
+    pub(crate) fn emulated_quotient_bits(
+        &mut self,
+        modulus: &FpElem<F>,
+    ) -> Result<usize, PlonkError> {
+        let range_bit_len = self.emulated_range_bit_len()?;
+        let modulus: BigUint = modulus.field_elem().into_repr().into();
+        let quotient_bits = NUM_BITS + 5 - modulus.bits() as usize;
+        Ok((quotient_bits + range_bit_len - 1) / range_bit_len * range_bit_len)
+    }
+}
+
+// Check that two emulated field elements are in the same field.
+pub(crate) fn check_same_field<F: PrimeField>(
+    x: &EmulatedFieldVar<F>,
+    y: &EmulatedFieldVar<F>,
+) -> Result<(), PlonkError> {
+    if x.modulus != y.modulus {
+        return Err(ParameterError(format!(
+            "emulated field elements have different moduli {} and {}",
+            x.modulus(),
+            y.modulus()
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::{RangeTableSize, MAX_AUTO_RANGE_BIT_LEN};
+    use ark_bls12_377::{Fq as Fq377, Fr as Fr377};
+    use ark_bls12_381::{Fq as Fq381, Fr as Fr381};
+    use ark_bn254::{Fq as Fq254, Fr as Fr254};
+    use ark_ed_on_bn254::Fr as FrEd254;
+    use ark_std::{test_rng, UniformRand};
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+
+    #[test]
+    fn test_emulated_field_arithmetic() -> Result<(), PlonkError> {
+        test_emulated_field_arithmetic_helper::<Fq377, Fq254>()?;
+        test_emulated_field_arithmetic_helper::<Fq377, Fr381>()?;
+        test_emulated_field_arithmetic_helper::<Fq377, FrEd254>()?;
+        test_emulated_field_arithmetic_helper::<Fq381, Fr254>()?;
+        test_emulated_field_arithmetic_helper::<Fq381, Fr377>()
+    }
+
+    fn test_emulated_field_arithmetic_helper<F: PrimeField, T: PrimeField>(
+    ) -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let x = T::rand(rng);
+        let y = T::rand(rng);
+        let x_var = circuit.create_emulated_field_variable(x)?;
+        let y_var = circuit.create_emulated_field_variable(y)?;
+        let max_var = circuit.create_emulated_field_variable(-T::one())?;
+        let c_var = circuit.create_constant_emulated_field_variable(-T::from(2u8))?;
+        assert_eq!(circuit.emulated_field_witness::<T>(&x_var)?, x);
+
+        let sum = circuit.emulated_field_add(&x_var, &y_var)?;
+        assert_eq!(circuit.emulated_field_witness::<T>(&sum)?, x + y);
+        let sum = circuit.emulated_field_add(&max_var, &c_var)?;
+        assert_eq!(circuit.emulated_field_witness::<T>(&sum)?, -T::from(3u8));
+        let diff = circuit.emulated_field_sub(&x_var, &y_var)?;
+        assert_eq!(circuit.emulated_field_witness::<T>(&diff)?, x - y);
+        let prod = circuit.emulated_field_mul(&x_var, &y_var)?;
+        assert_eq!(circuit.emulated_field_witness::<T>(&prod)?, x * y);
+        let prod = circuit.emulated_field_mul(&max_var, &c_var)?;
+        assert_eq!(circuit.emulated_field_witness::<T>(&prod)?, T::from(2u8));
+        let inv = circuit.emulated_field_inverse(&x_var)?;
+        assert_eq!(
+            circuit.emulated_field_witness::<T>(&inv)?,
+            x.inverse().unwrap()
+        );
+        let b = circuit.create_bool_variable(true)?;
+        let selected = circuit.emulated_field_select(b, &x_var, &y_var)?;
+        assert_eq!(circuit.emulated_field_witness::<T>(&selected)?, y);
+        circuit.emulated_field_equal_gate(&selected, &y_var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong result
+        *circuit.witness_mut(prod.limbs().0) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(prod.limbs().0) -= F::one();
+        *circuit.witness_mut(selected.limbs().1) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // zero has no inverse
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let zero_var = circuit.create_emulated_field_variable(T::zero())?;
+        circuit.emulated_field_inverse(&zero_var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // the native field is too small, or the circuit does not support lookup
+        let mut circuit = PlonkCircuit::<Fq254>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        assert!(circuit.create_emulated_field_variable(x).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        assert!(circuit.create_emulated_field_variable(x).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_emulated_field_with_modulus() -> Result<(), PlonkError> {
+        let mut circuit = PlonkCircuit::<Fq377>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        // the field of order 2^127 - 1
+        let p = (BigUint::from(1u8) << 127) - 1u8;
+        let x = &p - 2u8;
+        let x_var = circuit.create_emulated_field_variable_with_modulus(&x, &p)?;
+        let c_var = circuit
+            .create_constant_emulated_field_variable_with_modulus(&BigUint::from(5u8), &p)?;
+        assert_eq!(x_var.modulus(), p);
+        let sum = circuit.emulated_field_add(&x_var, &c_var)?;
+        assert_eq!(
+            circuit.emulated_field_int_witness(&sum)?,
+            BigUint::from(3u8)
+        );
+        let prod = circuit.emulated_field_mul(&x_var, &x_var)?;
+        assert_eq!(
+            circuit.emulated_field_int_witness(&prod)?,
+            BigUint::from(4u8)
+        );
+        let inv = circuit.emulated_field_inverse(&c_var)?;
+        let one = circuit.emulated_field_mul(&inv, &c_var)?;
+        assert_eq!(
+            circuit.emulated_field_int_witness(&one)?,
+            BigUint::from(1u8)
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // elements of different fields
+        let y_var = circuit.create_emulated_field_variable(Fr254::from(2u8))?;
+        assert!(circuit.emulated_field_add(&x_var, &y_var).is_err());
+        assert!(circuit.emulated_field_witness::<Fr254>(&x_var).is_err());
+        // unreduced values and invalid moduli
+        assert!(circuit
+            .create_emulated_field_variable_with_modulus(&p, &p)
+            .is_err());
+        assert!(circuit
+            .create_constant_emulated_field_variable_with_modulus(&p, &p)
+            .is_err());
+        assert!(circuit
+            .create_emulated_field_variable_with_modulus(&x, &(BigUint::from(1u8) << 256))
+            .is_err());
+
+        // the range table size is chosen if it is automatic
+        let mut circuit = PlonkCircuit::<Fq377>::new_ultra_plonk(RangeTableSize::Auto);
+        let x_var = circuit.create_emulated_field_variable_with_modulus(&x, &p)?;
+        let prod = circuit.emulated_field_mul(&x_var, &x_var)?;
+        assert_eq!(
+            circuit.emulated_field_int_witness(&prod)?,
+            BigUint::from(4u8)
+        );
+        assert_eq!(circuit.range_bit_len()?, MAX_AUTO_RANGE_BIT_LEN);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+}
@@ -0,0 +1,268 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! User-defined key-value lookup tables, e.g. S-boxes, tables of precomputed
+//! curve points or application dictionaries.
+//!
+//! A table of `n` elements maps the keys `0, ..., n - 1` to pairs of values,
+//! which are either constants or variables of the circuit. It is inserted
+//! after the other tables of the circuit, so a looked up key is offset by the
+//! position of the table among all the table elements, and it is constrained
+//! to be smaller than `n` with range checks so that the elements of the
+//! other tables cannot be looked up.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+    PlonkType,
+};
+use ark_ff::PrimeField;
+use ark_std::{collections::HashMap, format, string::ToString, vec::Vec};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A key-value table inserted in the circuit, whose `i`-th element is looked
+/// up with the key `i`.
+pub struct LookupTable {
+    // the variables of the values of the elements
+    entries: Vec<(Variable, Variable)>,
+    // the position of the table among all the table elements
+    offset: usize,
+}
+
+impl LookupTable {
+    /// The number of elements of the table.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the table is empty, which is never the case of an inserted
+    /// table.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The variables of the values of the elements of the table.
+    pub fn entries(&self) -> &[(Variable, Variable)] {
+        &self.entries
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Insert a table whose `i`-th element is the pair of constants
+    /// `values[i]`.
+    /// Return error if `values` is empty, or if the circuit does not support
+    /// lookup.
+    pub fn create_lookup_table(&mut self, values: &[(F, F)]) -> Result<LookupTable, PlonkError> {
+        self.check_plonk_type(PlonkType::UltraPlonk)?;
+        if values.is_empty() {
+            return Err(ParameterError("empty lookup table".to_string()).into());
+        }
+        // the constants are shared between the elements
+        let mut constants = HashMap::new();
+        let mut constant_var = |circuit: &mut Self, value: F| -> Result<Variable, PlonkError> {
+            match constants.get(&value) {
+                Some(&var) => Ok(var),
+                None => {
+                    let var = circuit.create_constant_variable(value)?;
+                    constants.insert(value, var);
+                    Ok(var)
+                },
+            }
+        };
+        let entries = values
+            .iter()
+            .map(|&(val0, val1)| Ok((constant_var(self, val0)?, constant_var(self, val1)?)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.create_variable_lookup_table(&entries)
+    }
+
+    /// Insert a table whose `i`-th element is the pair of the values of the
+    /// variables `entries[i]`, e.g. a dictionary which is part of the
+    /// witness.
+    /// Return error if `entries` is empty, if the variables are invalid, or
+    /// if the circuit does not support lookup.
+    pub fn create_variable_lookup_table(
+        &mut self,
+        entries: &[(Variable, Variable)],
+    ) -> Result<LookupTable, PlonkError> {
+        self.check_plonk_type(PlonkType::UltraPlonk)?;
+        if entries.is_empty() {
+            return Err(ParameterError("empty lookup table".to_string()).into());
+        }
+        let offset = self.num_table_elems();
+        self.create_table_and_lookup_variables(&[], entries)?;
+        Ok(LookupTable {
+            entries: entries.to_vec(),
+            offset,
+        })
+    }
+
+    /// Obtain the variables of the values of the elements of `table` at the
+    /// keys `keys`, which are constrained to be smaller than the length of
+    /// the table.
+    /// Return error if the variables are invalid, if the value of a key is
+    /// out of range, or if the table is not inserted in the circuit.
+    pub fn lookup(
+        &mut self,
+        table: &LookupTable,
+        keys: &[Variable],
+    ) -> Result<Vec<(Variable, Variable)>, PlonkError> {
+        self.check_vars_bound(keys)?;
+        let mut lookup_vars = Vec::with_capacity(keys.len());
+        let mut outputs = Vec::with_capacity(keys.len());
+        for &key in keys.iter() {
+            let key_val = self.witness(key)?;
+            if key_val >= F::from(table.len() as u64) {
+                return Err(ParameterError(format!(
+                    "the key {} is not smaller than the length {} of the table",
+                    key_val,
+                    table.len()
+                ))
+                .into());
+            }
+            let (var0, var1) = table.entries[key_val.into_repr().as_ref()[0] as usize];
+            let val0 = self.create_variable(self.witness(var0)?)?;
+            let val1 = self.create_variable(self.witness(var1)?)?;
+            lookup_vars.push((self.lookup_table_key(table, key)?, val0, val1));
+            outputs.push((val0, val1));
+        }
+        self.lookup_variables(&lookup_vars)?;
+        Ok(outputs)
+    }
+
+    /// Constrain each triple `(key, val0, val1)` of `lookups` to be an element
+    /// of `table`, the key being constrained to be smaller than the length of
+    /// the table.
+    /// Return error if the variables are invalid, or if the table is not
+    /// inserted in the circuit.
+    pub fn enforce_lookup(
+        &mut self,
+        table: &LookupTable,
+        lookups: &[(Variable, Variable, Variable)],
+    ) -> Result<(), PlonkError> {
+        let lookup_vars = lookups
+            .iter()
+            .map(|&(key, val0, val1)| {
+                self.check_vars_bound(&[key, val0, val1])?;
+                Ok((self.lookup_table_key(table, key)?, val0, val1))
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.lookup_variables(&lookup_vars)
+    }
+
+    // The variable of the position of the element at `key` among all the
+    // table elements, `key` being constrained to be in the table.
+    fn lookup_table_key(
+        &mut self,
+        table: &LookupTable,
+        key: Variable,
+    ) -> Result<Variable, PlonkError> {
+        let len = table.len();
+        if table.offset + len > self.num_table_elems() {
+            return Err(ParameterError("the lookup table is not inserted".to_string()).into());
+        }
+        // key and len - 1 - key are in [0, 2^bits), i.e. key is in [0, len - 1]
+        let max_key = len - 1;
+        let bits = (usize::BITS - max_key.leading_zeros()) as usize;
+        if bits == 0 {
+            self.constant_gate(key, F::zero())?;
+        } else {
+            self.range_gate(key, bits)?;
+            if !len.is_power_of_two() {
+                let zero = self.zero();
+                let rev_key = self.gen_quad_poly(
+                    &[key, zero, zero, zero],
+                    &[-F::one(), F::zero(), F::zero(), F::zero()],
+                    &[F::zero(), F::zero()],
+                    F::from(max_key as u64),
+                )?;
+                self.range_gate(rev_key, bits)?;
+            }
+        }
+        match table.offset {
+            0 => Ok(key),
+            offset => self.add_constant(key, &F::from(offset as u64)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_key_value_table() -> Result<(), PlonkError> {
+        test_key_value_table_helper::<FqEd254>()?;
+        test_key_value_table_helper::<FqEd377>()?;
+        test_key_value_table_helper::<FqEd381>()?;
+        test_key_value_table_helper::<Fq377>()
+    }
+
+    fn test_key_value_table_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        // a dictionary of the witness, inserted first
+        let words = (0..3u32)
+            .map(|i| {
+                Ok((
+                    circuit.create_variable(F::from(100 + i))?,
+                    circuit.create_variable(F::from(200 + i))?,
+                ))
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let dictionary = circuit.create_variable_lookup_table(&words)?;
+        // a constant table of squares and cubes, whose keys are offset
+        let values: Vec<(F, F)> = (0..5u64)
+            .map(|i| (F::from(i * i), F::from(i * i * i)))
+            .collect();
+        let table = circuit.create_lookup_table(&values)?;
+        assert_eq!(table.len(), 5);
+
+        let keys = (0..5u32)
+            .map(|i| circuit.create_variable(F::from(i)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let outputs = circuit.lookup(&table, &keys)?;
+        for (&(val0, val1), &(expected0, expected1)) in outputs.iter().zip(values.iter()) {
+            assert_eq!(circuit.witness(val0)?, expected0);
+            assert_eq!(circuit.witness(val1)?, expected1);
+        }
+        let entry = circuit.lookup(&dictionary, &keys[2..3])?[0];
+        assert_eq!(circuit.witness(entry.0)?, F::from(102u32));
+        let (val0, val1) = words[1];
+        circuit.enforce_lookup(&dictionary, &[(keys[1], val0, val1)])?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong values, or a key out of the table, e.g. the key of an element
+        // of the dictionary in the table of squares
+        let mut bad_circuit = circuit.clone();
+        *bad_circuit.witness_mut(outputs[3].1) += F::one();
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut bad_circuit = circuit.clone();
+        bad_circuit.enforce_lookup(&dictionary, &[(keys[1], val1, val0)])?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut bad_circuit = circuit.clone();
+        let key = bad_circuit.create_variable(-F::from(3u32))?;
+        let (val0, val1) = words[0];
+        bad_circuit.enforce_lookup(&table, &[(key, val0, val1)])?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid inputs
+        let key = circuit.create_variable(F::from(5u32))?;
+        assert!(circuit.lookup(&table, &[key]).is_err());
+        assert!(circuit.lookup(&table, &[circuit.num_vars()]).is_err());
+        assert!(circuit.create_lookup_table(&[]).is_err());
+        assert!(circuit.create_variable_lookup_table(&[]).is_err());
+        let mut other = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let key = other.create_variable(F::zero())?;
+        assert!(other.lookup(&table, &[key]).is_err());
+        let mut turbo = PlonkCircuit::<F>::new_turbo_plonk();
+        assert!(turbo.create_lookup_table(&values).is_err());
+        Ok(())
+    }
+}
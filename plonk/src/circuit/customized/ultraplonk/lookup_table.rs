@@ -6,12 +6,14 @@
 
 //! Lookup gates over variable tables.
 
+use super::uint::LOGIC_TABLE_BIT_LEN;
 use crate::{
     circuit::{customized::gates::LookupGate, Circuit, PlonkCircuit, Variable},
-    errors::PlonkError,
+    errors::{PlonkError, SnarkError::ParameterError},
+    PlonkType,
 };
 use ark_ff::PrimeField;
-use ark_std::{boxed::Box, cmp::max, vec::Vec};
+use ark_std::{boxed::Box, cmp::max, format, string::ToString, vec::Vec};
 
 impl<F: PrimeField> PlonkCircuit<F> {
     /// Create a table with keys/values
@@ -58,6 +60,115 @@ impl<F: PrimeField> PlonkCircuit<F> {
         *self.num_table_elems_mut() += n;
         Ok(())
     }
+
+    /// Insert the bitwise logic table for operands of `bit_len` bits, which
+    /// maps the key `a * 2^bit_len + b` to `(a & b, a ^ b)`. The table has
+    /// `2^{2 * bit_len}` elements and is shared by all the bitwise operations
+    /// of the circuit; if it is not inserted explicitly, the first bitwise
+    /// operation inserts it with `LOGIC_TABLE_BIT_LEN`.
+    /// Return error if the table is already inserted, if `bit_len` is not
+    /// one of 1, 2, 4 or 8, or if the circuit does not support lookup.
+    pub fn create_logic_table(&mut self, bit_len: usize) -> Result<(), PlonkError> {
+        self.check_plonk_type(PlonkType::UltraPlonk)?;
+        if self.logic_table_mut().is_some() {
+            return Err(ParameterError("the logic table is already inserted".to_string()).into());
+        }
+        if bit_len == 0 || bit_len > LOGIC_TABLE_BIT_LEN || 32 % bit_len != 0 {
+            return Err(ParameterError(format!(
+                "unsupported bit length {} of the logic table",
+                bit_len
+            ))
+            .into());
+        }
+        // the table elements are constant, and are shared between the rows
+        let size = 1usize << bit_len;
+        let values = (0..size)
+            .map(|v| self.create_constant_variable(F::from(v as u64)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let table_vars: Vec<(Variable, Variable)> = (0..size * size)
+            .map(|i| {
+                let (a, b) = (i >> bit_len, i & (size - 1));
+                (values[a & b], values[a ^ b])
+            })
+            .collect();
+        let offset = self.num_table_elems();
+        self.create_table_and_lookup_variables(&[], &table_vars)?;
+        *self.logic_table_mut() = Some((bit_len, offset));
+        Ok(())
+    }
+
+    /// The bit length of the operands of the bitwise logic table, which is
+    /// inserted if needed.
+    /// Return error if the circuit does not support lookup.
+    pub(crate) fn logic_table_bit_len(&mut self) -> Result<usize, PlonkError> {
+        if self.logic_table_mut().is_none() {
+            self.create_logic_table(LOGIC_TABLE_BIT_LEN)?;
+        }
+        // safe unwrap: the table is inserted
+        Ok(self.logic_table_mut().unwrap().0)
+    }
+
+    /// Obtain the variables of `(a & b, a ^ b)` for pairs `(a, b)` of
+    /// operands by looking them up in the bitwise logic table, which is
+    /// inserted if needed.
+    /// The operands are expected to be constrained to be smaller than
+    /// `2^{logic_table_bit_len()}`, otherwise the lookups are unsound.
+    /// Return error if the variables are invalid, or if the circuit does not
+    /// support lookup.
+    pub(crate) fn logic_lookup(
+        &mut self,
+        pairs: &[(Variable, Variable)],
+    ) -> Result<Vec<(Variable, Variable)>, PlonkError> {
+        let bit_len = self.logic_table_bit_len()?;
+        // safe unwrap: the table is inserted
+        let offset = self.logic_table_mut().unwrap().1;
+        let zero = self.zero();
+        let mut lookup_vars = Vec::with_capacity(pairs.len());
+        let mut outputs = Vec::with_capacity(pairs.len());
+        for &(a, b) in pairs.iter() {
+            let a_val = self.witness(a)?.into_repr().as_ref()[0];
+            let b_val = self.witness(b)?.into_repr().as_ref()[0];
+            let and = self.create_variable(F::from(a_val & b_val))?;
+            let xor = self.create_variable(F::from(a_val ^ b_val))?;
+            // the key is the position of the element in the table
+            let key = self.gen_quad_poly(
+                &[a, b, zero, zero],
+                &[F::from(1u64 << bit_len), F::one(), F::zero(), F::zero()],
+                &[F::zero(), F::zero()],
+                F::from(offset as u64),
+            )?;
+            lookup_vars.push((key, and, xor));
+            outputs.push((and, xor));
+        }
+
+        self.lookup_variables(&lookup_vars)?;
+        Ok(outputs)
+    }
+
+    /// Create a list of variable tuples to be looked up in the tables already
+    /// inserted in the circuit. Unlike `create_table_and_lookup_variables`,
+    /// the keys are not offset to a new table, i.e. they are the positions of
+    /// the elements among all the table elements, and the rows only contain
+    /// dummy table elements.
+    /// Return error if the variables are invalid.
+    pub(crate) fn lookup_variables(
+        &mut self,
+        lookup_vars: &[(Variable, Variable, Variable)],
+    ) -> Result<(), PlonkError> {
+        for lookup_var in lookup_vars.iter() {
+            self.check_var_bound(lookup_var.0)?;
+            self.check_var_bound(lookup_var.1)?;
+            self.check_var_bound(lookup_var.2)?;
+        }
+        let zero = self.zero();
+        let n_gate = self.num_gates();
+        (*self.table_gate_ids_mut()).push((n_gate, lookup_vars.len()));
+        for &(key, val0, val1) in lookup_vars.iter() {
+            self.insert_gate(&[key, val0, val1, zero, zero], Box::new(LookupGate))?;
+        }
+        *self.num_table_elems_mut() += lookup_vars.len();
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -6,8 +6,22 @@
 
 //! Implements ultra-plonk related circuits.
 
+pub mod aes;
+pub mod base64;
+pub mod bignat;
+pub mod ecdsa;
+pub mod ed25519;
+mod emulated;
+pub mod emulated_ecc;
+pub mod emulated_field;
+pub mod key_value_table;
 mod lookup_table;
 pub mod mod_arith;
 mod non_native_gates;
 pub mod plonk_verifier;
 mod range;
+pub mod rsa;
+pub mod set_membership;
+pub mod substring;
+pub mod u256;
+pub mod uint;
@@ -120,6 +120,20 @@ impl<F: PrimeField> FpElemVar<F> {
         })
     }
 
+    /// Create an FpElemVar from the variables of its two components. Does not
+    /// perform range checks on the variables.
+    pub(crate) fn new_from_components(
+        vars: (Variable, Variable),
+        m: usize,
+        two_power_m: F,
+    ) -> Self {
+        Self {
+            vars,
+            m,
+            two_power_m,
+        }
+    }
+
     /// Convert into a single variable with value `witness[vars.0] + 2^m *
     /// witness[vars.1]`
     pub fn convert_to_var(&self, cs: &mut PlonkCircuit<F>) -> Result<Variable, PlonkError> {
@@ -220,6 +234,26 @@ impl<F: PrimeField> FpElemVar<F> {
         }
     }
 
+    /// Enforce self != 0 mod p where p is the `modulus`, so that `self` can
+    /// be safely inverted modulo p. `self` is expected to be in [0, 2p), as
+    /// the outputs of the modular arithmetic gates are: both self != 0 and
+    /// self != p are enforced.
+    pub fn enforce_nonzero(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        modulus: &FpElem<F>,
+    ) -> Result<(), PlonkError> {
+        if self.m != modulus.m || self.two_power_m != modulus.two_power_m {
+            return Err(PlonkError::InvalidParameters(
+                "m or two_power_m do not match".to_string(),
+            ));
+        }
+        let var = self.convert_to_var(circuit)?;
+        circuit.non_zero_gate(var)?;
+        let var_minus_p = circuit.add_constant(var, &-modulus.field_elem())?;
+        circuit.non_zero_gate(var_minus_p)
+    }
+
     /// Enforce self == other.
     pub fn enforce_equal(
         &self,
@@ -250,7 +284,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         p: F,
         l_p: usize,
     ) -> Result<Variable, PlonkError> {
-        let range_bit_len = self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
         let range_size = self.range_size()?;
         let mut sum_x = F::zero();
         for &var in vars.iter() {
@@ -311,7 +345,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         y: &FpElemVar<F>,
         p: &FpElem<F>,
     ) -> Result<FpElemVar<F>, PlonkError> {
-        let range_bit_len = self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
         self.check_var_bound(x.vars.0)?;
         self.check_var_bound(x.vars.1)?;
         self.check_var_bound(y.vars.0)?;
@@ -361,7 +395,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         y: &FpElem<F>,
         p: &FpElem<F>,
     ) -> Result<FpElemVar<F>, PlonkError> {
-        let range_bit_len = self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
         let range_size = self.range_size()?;
         self.check_var_bound(x.vars.0)?;
         self.check_var_bound(x.vars.1)?;
@@ -448,7 +482,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         x: &[FpElemVar<F>],
         p: &FpElem<F>,
     ) -> Result<FpElemVar<F>, PlonkError> {
-        let range_bit_len = self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
         for e in x {
             if e.m != p.m {
                 return Err(ParameterError(format!(
@@ -495,7 +529,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         y: &FpElemVar<F>,
         p: &FpElem<F>,
     ) -> Result<FpElemVar<F>, PlonkError> {
-        let range_bit_len = self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
         if x.m != p.m || y.m != p.m {
             return Err(ParameterError(format!(
                 "field elements splitting parameters do not match: x.m = {}, y.m = {}, p.m = {}",
@@ -680,7 +714,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         y: &FpElem<F>,
         p: &FpElem<F>,
     ) -> Result<FpElemVar<F>, PlonkError> {
-        let range_bit_len = self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
         if x.m != p.m || y.m != p.m {
             return Err(ParameterError(format!(
                 "field elements splitting parameters do not match: x.m = {}, y.m = {}, p.m = {}",
@@ -847,10 +881,88 @@ impl<F: PrimeField> PlonkCircuit<F> {
         })
     }
 
+    /// Modular subtraction gate:
+    /// Given Fp elements x, y and modulus p, compute z = x - y mod p.
+    /// Both x and y are expected to be reduced, i.e., smaller than p.
+    /// The output is guaranteed to be in [0, p), given that 2^{2m+1} is
+    /// smaller than the native field modulus.
+    pub fn mod_sub(
+        &mut self,
+        x: &FpElemVar<F>,
+        y: &FpElemVar<F>,
+        p: &FpElem<F>,
+    ) -> Result<FpElemVar<F>, PlonkError> {
+        let range_bit_len = self.resolve_range_bit_len()?;
+        self.check_var_bound(x.vars.0)?;
+        self.check_var_bound(x.vars.1)?;
+        self.check_var_bound(y.vars.0)?;
+        self.check_var_bound(y.vars.1)?;
+
+        if x.m != p.m || y.m != p.m {
+            return Err(ParameterError(format!(
+                "field elements splitting parameters do not match: x.m = {}, y.m = {}, p.m = {}",
+                x.m, y.m, p.m
+            ))
+            .into());
+        }
+        if x.two_power_m != p.two_power_m || y.two_power_m != p.two_power_m {
+            return Err(ParameterError(format!(
+                "field elements splitting parameters do not match: x.2^m = {}, y.2^m = {}, p.2^m = {}",
+                x.two_power_m, y.two_power_m, p.two_power_m
+            ))
+            .into());
+        }
+        if p.m % range_bit_len != 0 {
+            return Err(ParameterError(format!(
+                "splitting parameter m = {} is not a multiple of range_bit_len",
+                p.m
+            ))
+            .into());
+        }
+
+        // Witness computation
+        let x_int: BigUint = to_big_int!(x.witness(self)?);
+        let y_int: BigUint = to_big_int!(y.witness(self)?);
+        let p_int: BigUint = to_big_int!(p.field_elem());
+        if x_int >= p_int || y_int >= p_int {
+            return Err(PlonkError::NonNativeFieldOverflow);
+        }
+        // borrow = 1 iff x < y, in which case the modulus is added back
+        let (borrow, z_int) = if x_int >= y_int {
+            (F::zero(), x_int - y_int)
+        } else {
+            (F::one(), x_int + &p_int - y_int)
+        };
+        let z = F::from(z_int);
+        let p_f = p.field_elem();
+
+        // variables
+        let x_var = x.convert_to_var(self)?;
+        let y_var = y.convert_to_var(self)?;
+        let borrow_var = self.create_variable(borrow)?;
+        self.bool_gate(borrow_var)?;
+        let z_var = self.create_variable(z)?;
+        // d = p - 1 - z, so that z is in [0, p) once both z and d are range checked
+        let d_var = self.create_variable(p_f - F::one() - z)?;
+        self.range_gate_with_lookup(z_var, 2 * p.m)?;
+        self.range_gate_with_lookup(d_var, 2 * p.m)?;
+
+        // add constraint: x - y + p * borrow = z
+        let wires = [x_var, y_var, borrow_var, self.zero(), z_var];
+        let coeffs = [F::one(), -F::one(), p_f, F::zero()];
+        self.lc_gate(&wires, &coeffs)?;
+        // add constraint: z + d = p - 1
+        let wires = [z_var, d_var, self.one(), self.zero(), self.zero()];
+        let coeffs = [F::one(), F::one(), F::one() - p_f, F::zero()];
+        self.lc_gate(&wires, &coeffs)?;
+
+        FpElemVar::new_unchecked(self, z_var, p.m, Some(p.two_power_m))
+    }
+
     /// Negate an FpElemVar mod p where p is a public variable which is
     /// also the modulus for the FpElem element.
     pub fn mod_negate(&mut self, x: &FpElemVar<F>, p: &F) -> Result<FpElemVar<F>, PlonkError> {
-        let range_bit_len = self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
         if x.m % range_bit_len != 0 {
             return Err(ParameterError(format!(
                 "splitting parameter m = {} is not a multiple of range_bit_len",
@@ -912,6 +1024,63 @@ mod test {
     const RANGE_BIT_LEN_FOR_TEST: usize = 16;
     const RANGE_SIZE_FOR_TEST: usize = 65536;
 
+    #[test]
+    fn test_fp_elem_constants() -> Result<(), PlonkError> {
+        test_fp_elem_constants_helper::<FqEd254>()?;
+        test_fp_elem_constants_helper::<FqEd377>()?;
+        test_fp_elem_constants_helper::<FqEd381>()?;
+        test_fp_elem_constants_helper::<Fq377>()
+    }
+    // Test zero/one constants and enforce_nonzero
+    fn test_fp_elem_constants_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let p = F::from(RANGE_SIZE_FOR_TEST as u32).pow(&[10u64]);
+        let m = 80;
+        let modulus = FpElem::new(&p, m, None)?;
+
+        // constants convert back to 0 and 1
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let zero = FpElemVar::zero(&circuit, m, None);
+        let one = FpElemVar::one(&circuit, m, None);
+        let zero_var = zero.convert_to_var(&mut circuit)?;
+        let one_var = one.convert_to_var(&mut circuit)?;
+        assert_eq!(circuit.witness(zero_var)?, F::zero());
+        assert_eq!(circuit.witness(one_var)?, F::one());
+        assert_eq!(zero.witness(&circuit)?, F::zero());
+        assert_eq!(one.witness(&circuit)?, F::one());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // non-zero elements pass
+        let x = p - F::from(rng.gen_range(1..u128::MAX));
+        let x = FpElemVar::new_from_field_element(&mut circuit, &x, m, None)?;
+        x.enforce_nonzero(&mut circuit, &modulus)?;
+        one.enforce_nonzero(&mut circuit, &modulus)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // zero makes the circuit unsatisfiable
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        FpElemVar::zero(&circuit, m, None).enforce_nonzero(&mut circuit, &modulus)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        FpElemVar::new_from_field_element(&mut circuit, &F::zero(), m, None)?
+            .enforce_nonzero(&mut circuit, &modulus)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // so does p, which is 0 mod p
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        FpElemVar::new_from_field_element(&mut circuit, &p, m, None)?
+            .enforce_nonzero(&mut circuit, &modulus)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // mismatched parameters
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        assert!(FpElemVar::one(&circuit, m + RANGE_BIT_LEN_FOR_TEST, None)
+            .enforce_nonzero(&mut circuit, &modulus)
+            .is_err());
+
+        Ok(())
+    }
+
     #[test]
     fn test_fp_elem() -> Result<(), PlonkError> {
         test_fp_elem_helper::<FqEd254>()?;
@@ -1546,6 +1715,97 @@ mod test {
         Ok(circuit)
     }
 
+    // ========================================
+    //  mod sub
+    // ========================================
+    #[test]
+    fn test_mod_sub() -> Result<(), PlonkError> {
+        test_mod_sub_helper::<FqEd254, FrEd254>(126, 9)?;
+        test_mod_sub_helper::<FqEd377, FrEd377>(126, 9)?;
+        test_mod_sub_helper::<Fq377, Fr377>(128, 16)
+    }
+    fn test_mod_sub_helper<F: PrimeField, T: PrimeField>(
+        m: usize,
+        range_bit_len: usize,
+    ) -> Result<(), PlonkError> {
+        let p = F::from_le_bytes_mod_order(T::Params::MODULUS.to_bytes_le().as_ref());
+        let p_split = FpElem::new(&p, m, None)?;
+        let mut rng = test_rng();
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(range_bit_len);
+
+        let mut pairs: Vec<(T, T)> = (0..10)
+            .map(|_| (T::rand(&mut rng), T::rand(&mut rng)))
+            .collect();
+        // make sure that both x < y and x >= y are covered
+        let (small, large) = (T::from(3u8), -T::one());
+        pairs.push((small, large));
+        pairs.push((large, small));
+        pairs.push((small, small));
+
+        for (x, y) in pairs {
+            let x_var = circuit.create_variable(field_switching(&x))?;
+            let x_split_vars =
+                FpElemVar::new_unchecked(&mut circuit, x_var, m, Some(p_split.two_power_m))?;
+            let y_var = circuit.create_variable(field_switching(&y))?;
+            let y_split_vars =
+                FpElemVar::new_unchecked(&mut circuit, y_var, m, Some(p_split.two_power_m))?;
+
+            let z_split_vars = circuit.mod_sub(&x_split_vars, &y_split_vars, &p_split)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+            // bad witnesses
+            *circuit.witness_mut(z_split_vars.vars.1) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(z_split_vars.vars.1) -= F::one();
+
+            let z_var = z_split_vars.convert_to_var(&mut circuit)?;
+            let z = circuit.witness(z_var)?;
+            assert_eq!(x - y, field_switching::<_, T>(&z));
+        }
+
+        // inputs larger than the modulus
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(range_bit_len);
+        let x_var = circuit.create_variable(p)?;
+        let x_split_vars =
+            FpElemVar::new_unchecked(&mut circuit, x_var, m, Some(p_split.two_power_m))?;
+        let y_split_vars = FpElemVar::zero(&circuit, m, Some(p_split.two_power_m));
+        assert!(circuit
+            .mod_sub(&x_split_vars, &y_split_vars, &p_split)
+            .is_err());
+
+        // check that circuit config is independent of witness values
+        let circuit_1 = build_mod_sub_circuit(
+            &field_switching(&T::rand(&mut rng)),
+            &field_switching(&T::rand(&mut rng)),
+            &p_split,
+            range_bit_len,
+        )?;
+        let circuit_2 = build_mod_sub_circuit(
+            &field_switching(&T::rand(&mut rng)),
+            &field_switching(&T::rand(&mut rng)),
+            &p_split,
+            range_bit_len,
+        )?;
+        test_variable_independence_for_circuit::<F>(circuit_1, circuit_2)?;
+
+        Ok(())
+    }
+    fn build_mod_sub_circuit<F: PrimeField>(
+        x: &F,
+        y: &F,
+        p: &FpElem<F>,
+        range_bit_len: usize,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(range_bit_len);
+        let x_var = circuit.create_variable(*x)?;
+        let x_split_vars = FpElemVar::new_unchecked(&mut circuit, x_var, p.m, Some(p.two_power_m))?;
+        let y_var = circuit.create_variable(*y)?;
+        let y_split_vars = FpElemVar::new_unchecked(&mut circuit, y_var, p.m, Some(p.two_power_m))?;
+        circuit.mod_sub(&x_split_vars, &y_split_vars, p)?;
+        circuit.finalize_for_arithmetization()?;
+        Ok(circuit)
+    }
+
     // ========================================
     //  mod negation
     // ========================================
@@ -9,7 +9,7 @@ use crate::{
     circuit::{
         customized::{
             ecc::{PointVariable, SWToTEConParam},
-            transcript::RescueTranscriptVar,
+            transcript::PlonkTranscriptVar,
             ultraplonk::{
                 mod_arith::{FpElem, FpElemVar},
                 plonk_verifier::*,
@@ -36,7 +36,7 @@ use jf_utils::{bytes_to_field_elements, field_switching};
 /// input
 /// - vks: verification key variable
 /// - challenges: challenge variable in FpElemVar form
-/// - poly_evals: zeta^n, zeta^n-1 and Lagrange evaluated at 1
+/// - poly_evals: zeta^n, zeta^n-1 and Lagrange evaluated at 1 and n
 /// - batch_proof: batched proof inputs
 /// - non_native_field_info: aux information for non-native field
 /// Output
@@ -46,7 +46,7 @@ pub(super) fn aggregate_poly_commitments_circuit<E, F>(
     circuit: &mut PlonkCircuit<F>,
     vks: &[&VerifyingKeyVar<E>],
     challenges: &ChallengesFpElemVar<F>,
-    poly_evals: &[FpElemVar<F>; 3],
+    poly_evals: &[FpElemVar<F>; 4],
     batch_proof: &BatchProofVar<F>,
     alpha_bases: &[FpElemVar<F>],
     non_native_field_info: NonNativeFieldInfo<F>,
@@ -120,6 +120,58 @@ where
             &challenges.v,
             &non_native_field_info.modulus_fp_elem,
         )?;
+
+        // Add Plookup polynomial commitments
+        if let Some(proof_lkup) = batch_proof.plookup_proofs_vec[i].as_ref() {
+            let plookup_vk = vk.plookup_vk.as_ref().ok_or_else(|| {
+                ParameterError(format!(
+                    "the {}-th verification key does not support lookup",
+                    i
+                ))
+            })?;
+            let q_lookup_comm = vk.selector_comms.last().ok_or(PlonkError::IndexError)?;
+
+            // add commitments to be evaluated at point `zeta`, in the order of
+            // the prover
+            for poly_comm in [
+                &plookup_vk.range_table_comm,
+                &plookup_vk.key_table_comm,
+                &proof_lkup.h_poly_comms[0],
+                q_lookup_comm,
+            ] {
+                v_and_uv_basis.push(v_base);
+                add_poly_comm_circuit(
+                    circuit,
+                    &mut scalars_and_bases,
+                    &mut v_base,
+                    poly_comm,
+                    &challenges.v,
+                    &non_native_field_info.modulus_fp_elem,
+                )?;
+            }
+
+            // add commitments to be evaluated at point `zeta * g`
+            for poly_comm in [
+                &proof_lkup.prod_lookup_poly_comm,
+                &plookup_vk.range_table_comm,
+                &plookup_vk.key_table_comm,
+                &proof_lkup.h_poly_comms[0],
+                &proof_lkup.h_poly_comms[1],
+                q_lookup_comm,
+                &batch_proof.wires_poly_comms_vec[i][3],
+                &batch_proof.wires_poly_comms_vec[i][4],
+            ] {
+                v_and_uv_basis.push(uv_base);
+                add_poly_comm_circuit(
+                    circuit,
+                    &mut scalars_and_bases,
+                    &mut uv_base,
+                    poly_comm,
+                    &challenges.v,
+                    &non_native_field_info.modulus_fp_elem,
+                )?;
+            }
+        }
     }
 
     Ok((scalars_and_bases, v_and_uv_basis))
@@ -132,6 +184,7 @@ pub(super) fn aggregate_evaluations_circuit<E, F>(
     circuit: &mut PlonkCircuit<F>,
     lin_poly_constant: &FpElemVar<F>,
     poly_evals_vec: &[ProofEvaluationsVar<F>],
+    plookup_proofs_vec: &[Option<PlookupProofVar<F>>],
     non_native_field_info: NonNativeFieldInfo<F>,
     buffer_v_and_uv_basis: &[FpElemVar<F>],
 ) -> Result<FpElemVar<F>, PlonkError>
@@ -139,10 +192,19 @@ where
     E: PairingEngine<Fq = F>,
     F: PrimeField,
 {
+    if poly_evals_vec.len() != plookup_proofs_vec.len() {
+        return Err(ParameterError(format!(
+            "the number of proof evaluations {} != the number of Plookup proofs {}",
+            poly_evals_vec.len(),
+            plookup_proofs_vec.len()
+        ))
+        .into());
+    }
+
     let mut result = circuit.mod_negate(lin_poly_constant, &non_native_field_info.modulus_in_f)?;
     let mut v_and_uv_basis = buffer_v_and_uv_basis.iter();
 
-    for poly_evals in poly_evals_vec.iter() {
+    for (poly_evals, plookup_proof) in poly_evals_vec.iter().zip(plookup_proofs_vec.iter()) {
         // evaluations at point `zeta`
         for wire_eval in poly_evals.wires_evals.iter() {
             add_pcs_eval_circuit(
@@ -179,6 +241,28 @@ where
             &poly_evals.perm_next_eval,
             &non_native_field_info.modulus_fp_elem,
         )?;
+
+        // add Plookup related polynomial evaluations, at point `zeta` and then
+        // at point `zeta * g`
+        if let Some(proof_lk) = plookup_proof {
+            let evals = &proof_lk.poly_evals;
+            for eval in evals
+                .evals_vec()
+                .iter()
+                .chain(evals.next_evals_vec().iter())
+            {
+                add_pcs_eval_circuit(
+                    circuit,
+                    &mut result,
+                    match v_and_uv_basis.next() {
+                        Some(p) => p,
+                        None => return Err(PlonkError::IteratorOutOfRange),
+                    },
+                    eval,
+                    &non_native_field_info.modulus_fp_elem,
+                )?;
+            }
+        }
     }
     // ensure all the buffer has been consumed
     if v_and_uv_basis.next().is_some() {
@@ -187,9 +271,11 @@ where
     Ok(result)
 }
 
-/// Compute verifier challenges `beta`, `gamma`, `alpha`, `zeta`, 'v', 'u'.
-/// also compute the `alpha^2` and `alpha^3`.
-pub(super) fn compute_challenges_vars<E, F, P>(
+/// Compute verifier challenges `tau`, `beta`, `gamma`, `alpha`, `zeta`, 'v',
+/// 'u'. also compute the powers `alpha^2, ..., alpha^7`.
+/// The challenges are derived with the in-circuit transcript `T`, which must
+/// match the native transcript used by the prover.
+pub(super) fn compute_challenges_vars<E, F, P, T>(
     circuit: &mut PlonkCircuit<F>,
     verify_keys: &[&VerifyingKeyVar<E>],
     public_inputs: &[&[FpElemVar<F>]],
@@ -201,6 +287,7 @@ where
     E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
     F: RescueParameter + SWToTEConParam,
     P: SWParam<BaseField = F>,
+    T: PlonkTranscriptVar<F>,
 {
     if verify_keys.len() != batch_proof.len() || verify_keys.len() != public_inputs.len() {
         return Err(ParameterError(format!(
@@ -211,7 +298,7 @@ where
             ))
             .into());
     }
-    let mut transcript_var = RescueTranscriptVar::new(circuit);
+    let mut transcript_var = T::new(circuit);
     if let Some(msg) = extra_transcript_init_msg {
         let msg_fs = bytes_to_field_elements::<_, F>(msg);
         let msg_vars = msg_fs
@@ -224,28 +311,66 @@ where
         transcript_var.append_vk_and_pub_input_vars::<E>(circuit, vk, pi)?;
     }
     for wires_poly_comms in batch_proof.wires_poly_comms_vec.iter() {
-        transcript_var.append_commitments_vars::<E, P>(b"witness_poly_comms", wires_poly_comms)?;
+        transcript_var.append_commitments_vars::<E, P>(
+            b"witness_poly_comms",
+            circuit,
+            wires_poly_comms,
+        )?;
     }
     let tau = transcript_var.get_and_append_challenge_var::<E>(b"tau", circuit)?;
 
-    let beta = transcript_var.get_and_append_challenge_var::<E>(b"beta", circuit)?;
-    let gamma = transcript_var.get_and_append_challenge_var::<E>(b"gamma", circuit)?;
+    for plookup_proof in batch_proof.plookup_proofs_vec.iter().flatten() {
+        transcript_var.append_commitments_vars::<E, P>(
+            b"h_poly_comms",
+            circuit,
+            &plookup_proof.h_poly_comms,
+        )?;
+    }
+
+    // as the native prover and verifier, derive beta and gamma together
+    let beta_gamma = transcript_var
+        .get_and_append_challenges_var::<E>(&[b"beta".as_ref(), b"gamma".as_ref()], circuit)?;
+    let (beta, gamma) = (beta_gamma[0], beta_gamma[1]);
     for prod_perm_poly_comm in batch_proof.prod_perm_poly_comms_vec.iter() {
-        transcript_var.append_commitment_var::<E, P>(b"perm_poly_comms", prod_perm_poly_comm)?;
+        transcript_var.append_commitment_var::<E, P>(
+            b"perm_poly_comms",
+            circuit,
+            prod_perm_poly_comm,
+        )?;
+    }
+    for plookup_proof in batch_proof.plookup_proofs_vec.iter().flatten() {
+        transcript_var.append_commitment_var::<E, P>(
+            b"plookup_poly_comms",
+            circuit,
+            &plookup_proof.prod_lookup_poly_comm,
+        )?;
     }
 
     let alpha = transcript_var.get_and_append_challenge_var::<E>(b"alpha", circuit)?;
-    transcript_var
-        .append_commitments_vars::<E, P>(b"quot_poly_comms", &batch_proof.split_quot_poly_comms)?;
+    transcript_var.append_commitments_vars::<E, P>(
+        b"quot_poly_comms",
+        circuit,
+        &batch_proof.split_quot_poly_comms,
+    )?;
     let zeta = transcript_var.get_and_append_challenge_var::<E>(b"zeta", circuit)?;
     for poly_evals in batch_proof.poly_evals_vec.iter() {
         transcript_var.append_proof_evaluations_vars::<E>(circuit, poly_evals)?;
     }
+    for plookup_proof in batch_proof.plookup_proofs_vec.iter().flatten() {
+        transcript_var.append_plookup_evaluations_vars::<E>(circuit, &plookup_proof.poly_evals)?;
+    }
 
     let v = transcript_var.get_and_append_challenge_var::<E>(b"v", circuit)?;
-    transcript_var.append_commitment_var::<E, P>(b"open_proof", &batch_proof.opening_proof)?;
-    transcript_var
-        .append_commitment_var::<E, P>(b"shifted_open_proof", &batch_proof.shifted_opening_proof)?;
+    transcript_var.append_commitment_var::<E, P>(
+        b"open_proof",
+        circuit,
+        &batch_proof.opening_proof,
+    )?;
+    transcript_var.append_commitment_var::<E, P>(
+        b"shifted_open_proof",
+        circuit,
+        &batch_proof.shifted_opening_proof,
+    )?;
     let u = transcript_var.get_and_append_challenge_var::<E>(b"u", circuit)?;
 
     // convert challenge vars into FpElemVars
@@ -265,8 +390,9 @@ where
 }
 
 /// Prepare the (aggregated) polynomial commitment evaluation information.
+/// The challenges are derived with the in-circuit transcript `T`.
 #[allow(clippy::too_many_arguments)]
-pub(super) fn prepare_pcs_info_var<E, F, P>(
+pub(super) fn prepare_pcs_info_var<E, F, P, T>(
     circuit: &mut PlonkCircuit<F>,
     verify_keys: &[&VerifyingKeyVar<E>],
     public_inputs: &[&[FpElemVar<F>]],
@@ -280,6 +406,7 @@ where
     E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
     F: RescueParameter + SWToTEConParam,
     P: SWParam<BaseField = F>,
+    T: PlonkTranscriptVar<F>,
 {
     if verify_keys.len() != batch_proof.len() || verify_keys.len() != public_inputs.len() {
         return Err(ParameterError(format!(
@@ -302,6 +429,14 @@ where
                 .into());
         }
 
+        if vk.plookup_vk.is_some() != batch_proof.plookup_proofs_vec[i].is_some() {
+            return Err(ParameterError(format!(
+                "Mismatched proof type and verification key type for the {}-th instance",
+                i
+            ))
+            .into());
+        }
+
         if vk.domain_size != domain.size() {
             return Err(ParameterError(format!(
                 "the domain size {} of the {}-th verification key is different from {}",
@@ -314,7 +449,7 @@ where
     }
 
     // compute challenges and evaluations
-    let challenges_fp_elem_var = compute_challenges_vars::<E, F, P>(
+    let challenges_fp_elem_var = compute_challenges_vars::<E, F, P, T>(
         circuit,
         verify_keys,
         public_inputs,
@@ -323,10 +458,16 @@ where
         non_native_field_info,
     )?;
 
-    // pre-compute alpha_bases: [1, alpha^3, alpha^6, alpha^(3* (vks.len()-1))]
+    // pre-compute alpha_bases: [1, alpha^k, alpha^2k, alpha^(k* (vks.len()-1))]
+    // where k = 7 for proofs with lookups and k = 3 otherwise
+    let alpha_base = if verify_keys[0].plookup_vk.is_some() {
+        challenges_fp_elem_var.alphas[6]
+    } else {
+        challenges_fp_elem_var.alphas[2]
+    };
     let alpha_bases = compute_alpha_basis(
         circuit,
-        challenges_fp_elem_var.alphas[2],
+        alpha_base,
         verify_keys.len(),
         non_native_field_info,
     )?;
@@ -366,6 +507,7 @@ where
         circuit,
         &lin_poly_constant,
         &batch_proof.poly_evals_vec,
+        &batch_proof.plookup_proofs_vec,
         non_native_field_info,
         &v_and_uv_basis,
     )?;
@@ -432,11 +574,12 @@ where
     Ok(())
 }
 
-// pre-compute alpha_bases: [1, alpha^3, alpha^6, alpha^(3*(len-1))]
+// pre-compute alpha_bases: [1, alpha^k, alpha^2k, alpha^(k*(len-1))] from
+// `alpha_to_k`
 #[inline]
 fn compute_alpha_basis<F: PrimeField>(
     circuit: &mut PlonkCircuit<F>,
-    alpha_to_3: FpElemVar<F>,
+    alpha_to_k: FpElemVar<F>,
     len: usize,
     non_native_field_info: NonNativeFieldInfo<F>,
 ) -> Result<Vec<FpElemVar<F>>, PlonkError> {
@@ -450,7 +593,7 @@ fn compute_alpha_basis<F: PrimeField>(
     for _ in 0..len - 1 {
         alpha_base_elem_var = circuit.mod_mul(
             &alpha_base_elem_var,
-            &alpha_to_3,
+            &alpha_to_k,
             &non_native_field_info.modulus_fp_elem,
         )?;
         res.push(alpha_base_elem_var);
@@ -462,12 +605,15 @@ fn compute_alpha_basis<F: PrimeField>(
 mod test {
     use super::*;
     use crate::{
-        circuit::Circuit,
+        circuit::{
+            customized::transcript::{PoseidonTranscriptVar, RescueTranscriptVar},
+            Circuit,
+        },
         proof_system::{
             batch_arg::{new_mergeable_circuit_for_test, BatchArgument},
             PlonkKzgSnark,
         },
-        transcript::{PlonkTranscript, RescueTranscript},
+        transcript::{PlonkTranscript, PoseidonTranscript, RescueTranscript},
     };
     use ark_bls12_377::{g1::Parameters as Param377, Bls12_377};
     use ark_ec::{SWModelParameters, TEModelParameters};
@@ -478,17 +624,32 @@ mod test {
     const RANGE_BIT_LEN_FOR_TEST: usize = 16;
     #[test]
     fn test_compute_challenges_vars_circuit() -> Result<(), PlonkError> {
-        test_compute_challenges_vars_circuit_helper::<Bls12_377, _, _, Param377, RescueTranscript<_>>(
-        )
+        test_compute_challenges_vars_circuit_helper::<
+            Bls12_377,
+            _,
+            _,
+            Param377,
+            RescueTranscript<_>,
+            RescueTranscriptVar<_>,
+        >()?;
+        test_compute_challenges_vars_circuit_helper::<
+            Bls12_377,
+            _,
+            _,
+            Param377,
+            PoseidonTranscript<_>,
+            PoseidonTranscriptVar<_>,
+        >()
     }
 
-    fn test_compute_challenges_vars_circuit_helper<E, F, P, Q, T>() -> Result<(), PlonkError>
+    fn test_compute_challenges_vars_circuit_helper<E, F, P, Q, T, V>() -> Result<(), PlonkError>
     where
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         F: RescueParameter + SWToTEConParam,
         P: SWModelParameters<BaseField = F> + Clone + TEModelParameters,
         Q: TEParam<BaseField = F> + Clone,
         T: PlonkTranscript<F>,
+        V: PlonkTranscriptVar<F>,
     {
         // 1. Simulate universal setup
         let rng = &mut test_rng();
@@ -585,7 +746,7 @@ mod test {
         // proof
         let batch_proof_vars = batch_proof.create_variables(&mut circuit, m, two_power_m)?;
 
-        let _challenges_fp_elem_var = compute_challenges_vars::<E, F, P>(
+        let _challenges_fp_elem_var = compute_challenges_vars::<E, F, P, V>(
             &mut circuit,
             &merged_vks_ref,
             &[shared_public_input_fp_elem_var_ref; 18],
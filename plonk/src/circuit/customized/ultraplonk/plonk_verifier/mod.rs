@@ -9,6 +9,7 @@ use crate::{
     circuit::{
         customized::{
             ecc::{MultiScalarMultiplicationCircuit, Point, PointVariable, SWToTEConParam},
+            transcript::PlonkTranscriptVar,
             ultraplonk::mod_arith::{FpElem, FpElemVar},
         },
         Circuit, PlonkCircuit, Variable,
@@ -24,6 +25,7 @@ use ark_ec::{
 use ark_ff::{BigInteger, FpParameters, PrimeField};
 use ark_std::{format, string::ToString, vec, vec::Vec};
 use jf_rescue::RescueParameter;
+use jf_utils::field_switching;
 
 mod gadgets;
 mod poly;
@@ -43,14 +45,27 @@ pub struct VerifyingKeyVar<E: PairingEngine> {
     is_merged: bool,
 
     /// The size of the evaluation domain. Should be a power of two.
-    domain_size: usize,
+    pub(crate) domain_size: usize,
 
     /// The number of public inputs.
-    num_inputs: usize,
+    pub(crate) num_inputs: usize,
 
     /// The constants K0, ..., K_num_wire_types that ensure wire subsets are
     /// disjoint.
-    k: Vec<E::Fr>,
+    pub(crate) k: Vec<E::Fr>,
+
+    /// The variables for the Plookup verifying key, if the circuit supports
+    /// lookup.
+    pub(crate) plookup_vk: Option<PlookupVerifyingKeyVar>,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+/// Represent variable of a Plookup verifying key.
+pub(crate) struct PlookupVerifyingKeyVar {
+    /// The variable for the range table polynomial commitment.
+    pub(crate) range_table_comm: PointVariable,
+    /// The variable for the key table polynomial commitment.
+    pub(crate) key_table_comm: PointVariable,
 }
 
 impl<E: PairingEngine> VerifyingKeyVar<E> {
@@ -74,6 +89,15 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
             .iter()
             .map(|comm| circuit.create_point_variable(Point::from(&comm.0)))
             .collect::<Result<Vec<_>, PlonkError>>()?;
+        let plookup_vk = match verify_key.plookup_vk.as_ref() {
+            Some(plookup_vk) => Some(PlookupVerifyingKeyVar {
+                range_table_comm: circuit
+                    .create_point_variable(Point::from(&plookup_vk.range_table_comm.0))?,
+                key_table_comm: circuit
+                    .create_point_variable(Point::from(&plookup_vk.key_table_comm.0))?,
+            }),
+            None => None,
+        };
         Ok(Self {
             sigma_comms,
             selector_comms,
@@ -81,6 +105,49 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
             domain_size: verify_key.domain_size,
             num_inputs: verify_key.num_inputs,
             k: verify_key.k.clone(),
+            plookup_vk,
+        })
+    }
+
+    /// Create a constant variable for a Plonk verifying key, so that a
+    /// verifier circuit is bound to this key, e.g. when the key is not a
+    /// public input of the circuit.
+    pub fn new_constant<F, P>(
+        circuit: &mut PlonkCircuit<F>,
+        verify_key: &VerifyingKey<E>,
+    ) -> Result<Self, PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: PrimeField + SWToTEConParam,
+        P: SWParam<BaseField = F> + Clone,
+    {
+        let sigma_comms = verify_key
+            .sigma_comms
+            .iter()
+            .map(|comm| circuit.create_constant_point_variable(Point::from(&comm.0)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let selector_comms = verify_key
+            .selector_comms
+            .iter()
+            .map(|comm| circuit.create_constant_point_variable(Point::from(&comm.0)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let plookup_vk = match verify_key.plookup_vk.as_ref() {
+            Some(plookup_vk) => Some(PlookupVerifyingKeyVar {
+                range_table_comm: circuit
+                    .create_constant_point_variable(Point::from(&plookup_vk.range_table_comm.0))?,
+                key_table_comm: circuit
+                    .create_constant_point_variable(Point::from(&plookup_vk.key_table_comm.0))?,
+            }),
+            None => None,
+        };
+        Ok(Self {
+            sigma_comms,
+            selector_comms,
+            is_merged: verify_key.is_merged,
+            domain_size: verify_key.domain_size,
+            num_inputs: verify_key.num_inputs,
+            k: verify_key.k.clone(),
+            plookup_vk,
         })
     }
 
@@ -95,6 +162,12 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
             res.push(selector_comm.get_x());
             res.push(selector_comm.get_y());
         }
+        if let Some(plookup_vk) = self.plookup_vk.as_ref() {
+            res.push(plookup_vk.range_table_comm.get_x());
+            res.push(plookup_vk.range_table_comm.get_y());
+            res.push(plookup_vk.key_table_comm.get_x());
+            res.push(plookup_vk.key_table_comm.get_y());
+        }
         res
     }
 
@@ -111,6 +184,11 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
         if self.is_merged || other.is_merged {
             return Err(ParameterError("cannot merge a merged key again".to_string()).into());
         }
+        if self.plookup_vk.is_some() || other.plookup_vk.is_some() {
+            return Err(
+                ParameterError("cannot merge UltraPlonk verifying keys".to_string()).into(),
+            );
+        }
         if self.domain_size != other.domain_size {
             return Err(ParameterError(
                 "cannot merge a verifying key with different domain size".to_string(),
@@ -142,6 +220,7 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
             domain_size: self.domain_size,
             num_inputs: self.num_inputs + other.num_inputs,
             k: self.k.clone(),
+            plookup_vk: None,
         })
     }
 
@@ -149,7 +228,10 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
     /// pairing. Return the variables for the two group elements used in the
     /// final pairing.
     /// The public inputs are already in the form of FpElemVars.
-    pub fn partial_verify_circuit<F, P>(
+    /// The challenges are derived with the in-circuit transcript `T`, which
+    /// must match the native transcript used by the prover, e.g.
+    /// `RescueTranscriptVar` for proofs computed with `RescueTranscript`.
+    pub fn partial_verify_circuit<F, P, T>(
         circuit: &mut PlonkCircuit<F>,
         beta_g: &Point<F>,
         generator_g: &Point<F>,
@@ -162,6 +244,7 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         F: RescueParameter + SWToTEConParam,
         P: SWParam<BaseField = F> + Clone + TEParam,
+        T: PlonkTranscriptVar<F>,
     {
         if merged_vks.is_empty() {
             return Err(ParameterError("empty merged verification keys".to_string()).into());
@@ -186,24 +269,8 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
             }
         }
 
-        let range_bit_len = circuit.range_bit_len()?;
-        let m2 = (<E::Fr as PrimeField>::size_in_bits() + 1) >> 1;
-        // m should be a multiple of `range_bit_len`
-        let m = (m2 - 1) / range_bit_len * range_bit_len + range_bit_len;
-
-        // constants
-        let two_power_m = Some(E::Fq::from(2u8).pow(&[m as u64]));
-
-        let fr_modulus_bits = <E::Fr as PrimeField>::Params::MODULUS.to_bytes_le();
-        let modulus_in_f = F::from_le_bytes_mod_order(&fr_modulus_bits);
-        let modulus_fp_elem = FpElem::new(&modulus_in_f, m, two_power_m)?;
-
-        let non_native_field_info = NonNativeFieldInfo::<F> {
-            m,
-            two_power_m,
-            modulus_in_f,
-            modulus_fp_elem,
-        };
+        let non_native_field_info = non_native_field_info::<E, F>(circuit)?;
+        let modulus_fp_elem = non_native_field_info.modulus_fp_elem;
 
         let verifier = Verifier::<E>::new(domain_size)?;
         let domain = verifier.domain;
@@ -215,7 +282,7 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
         let merged_vks_ref: Vec<&VerifyingKeyVar<E>> = merged_vks.iter().collect();
 
         // generate the PCS info
-        let pcs_info_var = prepare_pcs_info_var(
+        let pcs_info_var = prepare_pcs_info_var::<E, F, P, T>(
             circuit,
             &merged_vks_ref,
             &public_inputs,
@@ -278,6 +345,264 @@ impl<E: PairingEngine> VerifyingKeyVar<E> {
 
         Ok((inner1, inner2))
     }
+
+    /// Circuit for partially verifying independent Plonk proofs, each with its
+    /// own verifying key and public input, without performing the pairing.
+    /// The pairing inputs of the proofs are combined with the powers of a
+    /// challenge derived from all the proofs, so that a single pairing checks
+    /// them all. Return the variables for the two group elements used in the
+    /// final pairing.
+    /// The proofs are batch proofs of a single instance, e.g. converted from a
+    /// `Proof`, with or without lookups, computed with the native counterpart
+    /// of the in-circuit transcript `T`, which also derives the combiner.
+    pub fn aggregate_partial_verify_circuit<F, P, T>(
+        circuit: &mut PlonkCircuit<F>,
+        beta_g: &Point<F>,
+        generator_g: &Point<F>,
+        vks: &[Self],
+        public_input_vars: &[&[FpElemVar<F>]],
+        proofs: &[BatchProofVar<F>],
+        blinding_factor: Variable,
+    ) -> Result<(PointVariable, PointVariable), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWParam<BaseField = F> + Clone + TEParam,
+        T: PlonkTranscriptVar<F>,
+    {
+        let acc = Self::fold_partial_verify_circuit::<F, P, T>(
+            circuit,
+            beta_g,
+            generator_g,
+            None,
+            vks,
+            public_input_vars,
+            proofs,
+            blinding_factor,
+        )?;
+        Ok((acc.inner1, acc.inner2))
+    }
+
+    /// Circuit for folding the KZG opening claims of independent Plonk proofs
+    /// into the running accumulator `acc`, without performing any pairing.
+    /// The claims are combined with the accumulator as in
+    /// `aggregate_partial_verify_circuit`, with a challenge that also depends
+    /// on the accumulator, so that a single pairing on the final accumulator
+    /// checks the claims of all the folding steps. Return the variable of the
+    /// new accumulator. The challenges are derived with the in-circuit
+    /// transcript `T`, as in `aggregate_partial_verify_circuit`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn accumulate_partial_verify_circuit<F, P, T>(
+        circuit: &mut PlonkCircuit<F>,
+        beta_g: &Point<F>,
+        generator_g: &Point<F>,
+        acc: &AccumulatorVar,
+        vks: &[Self],
+        public_input_vars: &[&[FpElemVar<F>]],
+        proofs: &[BatchProofVar<F>],
+        blinding_factor: Variable,
+    ) -> Result<AccumulatorVar, PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWParam<BaseField = F> + Clone + TEParam,
+        T: PlonkTranscriptVar<F>,
+    {
+        Self::fold_partial_verify_circuit::<F, P, T>(
+            circuit,
+            beta_g,
+            generator_g,
+            Some(acc),
+            vks,
+            public_input_vars,
+            proofs,
+            blinding_factor,
+        )
+    }
+
+    // Combine the pairing inputs of the proofs, and of the accumulator `acc`
+    // if any, with the powers of a challenge derived from all of them. The
+    // accumulator is combined with the power 0, and the proofs with the next
+    // powers.
+    #[allow(clippy::too_many_arguments)]
+    fn fold_partial_verify_circuit<F, P, T>(
+        circuit: &mut PlonkCircuit<F>,
+        beta_g: &Point<F>,
+        generator_g: &Point<F>,
+        acc: Option<&AccumulatorVar>,
+        vks: &[Self],
+        public_input_vars: &[&[FpElemVar<F>]],
+        proofs: &[BatchProofVar<F>],
+        blinding_factor: Variable,
+    ) -> Result<AccumulatorVar, PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWParam<BaseField = F> + Clone + TEParam,
+        T: PlonkTranscriptVar<F>,
+    {
+        if vks.is_empty() {
+            return Err(ParameterError("empty verification keys".to_string()).into());
+        }
+        if vks.len() != proofs.len() || vks.len() != public_input_vars.len() {
+            return Err(ParameterError(format!(
+                "the number of verification keys = {}; the number of proofs = {}; the number of public inputs = {}",
+                vks.len(),
+                proofs.len(),
+                public_input_vars.len(),
+            ))
+            .into());
+        }
+
+        let non_native_field_info = non_native_field_info::<E, F>(circuit)?;
+        let modulus_fp_elem = non_native_field_info.modulus_fp_elem;
+
+        // generate the PCS info of each proof with its own transcript; the last
+        // challenge `u` of a proof depends on all of its messages, and the
+        // combiner `r` depends on the ones of all the proofs
+        let mut pcs_info_vars = Vec::with_capacity(vks.len());
+        let mut transcript_var = T::new(circuit);
+        if let Some(acc) = acc {
+            transcript_var.append_commitment_var::<E, P>(b"acc", circuit, &acc.inner1)?;
+            transcript_var.append_commitment_var::<E, P>(b"acc", circuit, &acc.inner2)?;
+        }
+        for ((vk, &public_input), proof) in vks.iter().zip(public_input_vars).zip(proofs) {
+            let domain = Verifier::<E>::new(vk.domain_size)?.domain;
+            let pcs_info_var = prepare_pcs_info_var::<E, F, P, T>(
+                circuit,
+                &[vk],
+                &[public_input],
+                proof,
+                &None,
+                domain,
+                non_native_field_info,
+            )?;
+            let u_var = pcs_info_var.u.convert_to_var(circuit)?;
+            transcript_var.append_challenge_var(b"u", &u_var)?;
+            pcs_info_vars.push(pcs_info_var);
+        }
+        let (_, r) = transcript_var.get_and_append_challenge_with_fp::<E>(b"r", circuit)?;
+
+        // inner1
+        //  = [acc_inner1]
+        //  + \sum_i r^i * ([open_proof_i] + u_i * [shifted_open_proof_i])
+        //  + blinding_factor * [1]1
+        //
+        // inner2
+        //  = [acc_inner2]
+        //  + \sum_i r^i * (eval_point_i * [open_proof_i]
+        //  + next_eval_point_i * u_i * [shifted_open_proof_i]
+        //  + [aggregated_comm_i] - aggregated_eval_i * [1]1)
+        //  + blinding_factor * [beta]1
+        //
+        // where i starts from 1 with an accumulator, and from 0 otherwise
+        let mut scalars_and_bases1 = ScalarsAndBasesVar::new();
+        let mut scalars_and_bases2 = ScalarsAndBasesVar::new();
+        let mut evals = Vec::with_capacity(pcs_info_vars.len());
+        let mut r_power = FpElemVar::one(
+            circuit,
+            non_native_field_info.m,
+            non_native_field_info.two_power_m,
+        );
+        if let Some(acc) = acc {
+            scalars_and_bases1.scalars.push(r_power);
+            scalars_and_bases1.bases.push(acc.inner1);
+            scalars_and_bases2.scalars.push(r_power);
+            scalars_and_bases2.bases.push(acc.inner2);
+            r_power = r;
+        }
+        for pcs_info_var in pcs_info_vars.iter() {
+            let u = circuit.mod_mul(&pcs_info_var.u, &r_power, &modulus_fp_elem)?;
+            scalars_and_bases1.scalars.push(r_power);
+            scalars_and_bases1.bases.push(pcs_info_var.opening_proof);
+            scalars_and_bases1.scalars.push(u);
+            scalars_and_bases1
+                .bases
+                .push(pcs_info_var.shifted_opening_proof);
+
+            for (scalar, base) in pcs_info_var
+                .comm_scalars_and_bases
+                .scalars
+                .iter()
+                .zip(pcs_info_var.comm_scalars_and_bases.bases.iter())
+            {
+                let scalar = circuit.mod_mul(scalar, &r_power, &modulus_fp_elem)?;
+                scalars_and_bases2.scalars.push(scalar);
+                scalars_and_bases2.bases.push(*base);
+            }
+            let scalar = circuit.mod_mul(&pcs_info_var.eval_point, &r_power, &modulus_fp_elem)?;
+            scalars_and_bases2.scalars.push(scalar);
+            scalars_and_bases2.bases.push(pcs_info_var.opening_proof);
+            let scalar = circuit.mod_mul(&pcs_info_var.next_eval_point, &u, &modulus_fp_elem)?;
+            scalars_and_bases2.scalars.push(scalar);
+            scalars_and_bases2
+                .bases
+                .push(pcs_info_var.shifted_opening_proof);
+            evals.push(circuit.mod_mul(&pcs_info_var.eval, &r_power, &modulus_fp_elem)?);
+
+            r_power = circuit.mod_mul(&r_power, &r, &modulus_fp_elem)?;
+        }
+        let generator_g_inv_var = circuit.create_constant_point_variable(generator_g.inverse())?;
+        scalars_and_bases2
+            .scalars
+            .push(circuit.mod_add_vec(&evals, &modulus_fp_elem)?);
+        scalars_and_bases2.bases.push(generator_g_inv_var);
+
+        let generator_g_var = circuit.create_constant_point_variable(*generator_g)?;
+        let beta_g_var = circuit.create_constant_point_variable(*beta_g)?;
+        let mut inner = Vec::with_capacity(2);
+        for (scalars_and_bases, base) in [
+            (scalars_and_bases1, generator_g_var),
+            (scalars_and_bases2, beta_g_var),
+        ] {
+            let mut scalars = scalars_and_bases
+                .scalars
+                .iter()
+                .map(|x| x.convert_to_var(circuit))
+                .collect::<Result<Vec<_>, _>>()?;
+            scalars.push(blinding_factor);
+            let mut bases = scalars_and_bases.bases;
+            bases.push(base);
+            inner.push(MultiScalarMultiplicationCircuit::<_, P>::msm(
+                circuit, &bases, &scalars,
+            )?);
+        }
+
+        Ok(AccumulatorVar::new(inner[0], inner[1]))
+    }
+}
+
+// The information of the scalar field of `E` as a non-native field of the
+// verifier circuit, whose elements are split with `fr_split_param`.
+fn non_native_field_info<E, F>(
+    circuit: &mut PlonkCircuit<F>,
+) -> Result<NonNativeFieldInfo<F>, PlonkError>
+where
+    E: PairingEngine<Fq = F>,
+    F: PrimeField,
+{
+    let m = fr_split_param::<E>(circuit.resolve_range_bit_len()?);
+    let two_power_m = Some(F::from(2u8).pow(&[m as u64]));
+
+    let fr_modulus_bits = <E::Fr as PrimeField>::Params::MODULUS.to_bytes_le();
+    let modulus_in_f = F::from_le_bytes_mod_order(&fr_modulus_bits);
+    let modulus_fp_elem = FpElem::new(&modulus_in_f, m, two_power_m)?;
+
+    Ok(NonNativeFieldInfo {
+        m,
+        two_power_m,
+        modulus_in_f,
+        modulus_fp_elem,
+    })
+}
+
+/// The splitting parameter `m` used by the verifier circuit to represent an
+/// `E::Fr` element as an `FpElemVar`: the smallest multiple of `range_bit_len`
+/// that is no less than half of the bit length of `E::Fr`.
+pub(crate) fn fr_split_param<E: PairingEngine>(range_bit_len: usize) -> usize {
+    let m2 = (<E::Fr as PrimeField>::size_in_bits() + 1) >> 1;
+    // m should be a multiple of `range_bit_len`
+    (m2 - 1) / range_bit_len * range_bit_len + range_bit_len
 }
 
 // Instances batching scheme related gates
@@ -285,6 +610,40 @@ impl<F> PlonkCircuit<F>
 where
     F: PrimeField,
 {
+    /// Create public input variables for the scalar field elements `inputs`
+    /// of the curve `E`, and split each of them into an `FpElemVar` whose
+    /// components are range checked. Each input is also constrained to be
+    /// smaller than the modulus of `E::Fr`, so that it is the canonical
+    /// representation of a scalar field element. The splitting parameter is
+    /// the one expected by the verifier circuit and the Rescue transcript
+    /// circuit.
+    pub fn create_public_fp_elem_inputs<E>(
+        &mut self,
+        inputs: &[E::Fr],
+    ) -> Result<Vec<FpElemVar<F>>, PlonkError>
+    where
+        E: PairingEngine<Fq = F>,
+    {
+        let m = fr_split_param::<E>(self.resolve_range_bit_len()?);
+        let fr_modulus_bits = <E::Fr as PrimeField>::Params::MODULUS.to_bytes_le();
+        let fr_modulus = F::from_le_bytes_mod_order(&fr_modulus_bits);
+        let fr_modulus_var = self.create_constant_variable(fr_modulus)?;
+        inputs
+            .iter()
+            .map(|x| {
+                let var = self.create_public_variable(field_switching(x))?;
+                let fp_elem_var = FpElemVar::new_unchecked(self, var, m, None)?;
+                self.range_gate_with_lookup(fp_elem_var.components().0, m)?;
+                self.range_gate_with_lookup(fp_elem_var.components().1, m)?;
+                // the range checks bound the input by 2^{2m}, which may exceed
+                // the modulus of `E::Fr`
+                let is_canonical = self.is_lt(var, fr_modulus_var, 2 * m)?;
+                self.enforce_true(is_canonical.into())?;
+                Ok(fp_elem_var)
+            })
+            .collect()
+    }
+
     /// Aggregate verification keys
     pub fn aggregate_verify_keys<E, P>(
         &mut self,
@@ -315,15 +674,19 @@ mod test {
     use super::*;
     use crate::{
         circuit::{
-            customized::{ecc::Point, test::test_variable_independence_for_circuit},
-            Circuit,
+            customized::{
+                ecc::Point,
+                test::test_variable_independence_for_circuit,
+                transcript::{PoseidonTranscriptVar, RescueTranscriptVar},
+            },
+            Circuit, RangeTableSize, MAX_AUTO_RANGE_BIT_LEN,
         },
         proof_system::{
             batch_arg::{new_mergeable_circuit_for_test, BatchArgument},
             structs::BatchProof,
             PlonkKzgSnark,
         },
-        transcript::{PlonkTranscript, RescueTranscript},
+        transcript::{PlonkTranscript, PoseidonTranscript, RescueTranscript},
     };
     use ark_bls12_377::{g1::Parameters as Param377, Bls12_377, Fq as Fq377};
     use ark_ec::{ProjectiveCurve, SWModelParameters, TEModelParameters};
@@ -449,16 +812,32 @@ mod test {
 
     #[test]
     fn test_partial_verification_circuit() -> Result<(), PlonkError> {
-        test_partial_verification_circuit_helper::<Bls12_377, _, _, Param377, RescueTranscript<_>>()
+        test_partial_verification_circuit_helper::<
+            Bls12_377,
+            _,
+            _,
+            Param377,
+            RescueTranscript<_>,
+            RescueTranscriptVar<_>,
+        >()?;
+        test_partial_verification_circuit_helper::<
+            Bls12_377,
+            _,
+            _,
+            Param377,
+            PoseidonTranscript<_>,
+            PoseidonTranscriptVar<_>,
+        >()
     }
 
-    fn test_partial_verification_circuit_helper<E, F, P, Q, T>() -> Result<(), PlonkError>
+    fn test_partial_verification_circuit_helper<E, F, P, Q, T, V>() -> Result<(), PlonkError>
     where
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         F: RescueParameter + SWToTEConParam,
         P: SWModelParameters<BaseField = F> + Clone + TEModelParameters,
         Q: TEParam<BaseField = F> + Clone,
         T: PlonkTranscript<F>,
+        V: PlonkTranscriptVar<F>,
     {
         let rng = &mut test_rng();
 
@@ -538,7 +917,7 @@ mod test {
                 // =======================================
                 let public_inputs = [[field_switching(&shared_public_input)].as_ref()].concat();
 
-                let (mut circuit, partial_verify_points) = build_circuit::<E, F, P>(
+                let (mut circuit, partial_verify_points) = build_circuit::<E, F, P, V>(
                     &shared_public_input,
                     &merged_vks,
                     &batch_proof,
@@ -598,7 +977,7 @@ mod test {
                 // should not be able to generate circuit
                 let mut wrong_merge_vks = merged_vks.clone();
                 let tmp = wrong_merge_vks.pop().unwrap();
-                assert!(build_circuit::<E, F, P>(
+                assert!(build_circuit::<E, F, P, V>(
                     &shared_public_input,
                     &wrong_merge_vks,
                     &batch_proof,
@@ -612,7 +991,7 @@ mod test {
                 // should not be able to generate circuit
                 let mut wrong_merge_vks = merged_vks.clone();
                 wrong_merge_vks.push(tmp);
-                assert!(build_circuit::<E, F, P>(
+                assert!(build_circuit::<E, F, P, V>(
                     &shared_public_input,
                     &wrong_merge_vks,
                     &batch_proof,
@@ -631,7 +1010,7 @@ mod test {
                 // instance inputs = satisfiability inputs != partial verify inputs
                 let public_inputs = [[field_switching(&shared_public_input)].as_ref()].concat();
                 let wrong_shared_public_input = E::Fr::rand(rng);
-                let (circuit, partial_verify_points) = build_circuit::<E, F, P>(
+                let (circuit, partial_verify_points) = build_circuit::<E, F, P, V>(
                     &wrong_shared_public_input,
                     &merged_vks,
                     &batch_proof,
@@ -684,7 +1063,7 @@ mod test {
         Ok(())
     }
 
-    fn build_circuit<E, F, P>(
+    fn build_circuit<E, F, P, V>(
         shared_public_input: &E::Fr,
         merged_vks: &[VerifyingKey<E>],
         batch_proof: &BatchProof<E>,
@@ -696,6 +1075,7 @@ mod test {
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         F: RescueParameter + SWToTEConParam,
         P: SWModelParameters<BaseField = F> + Clone + TEModelParameters,
+        V: PlonkTranscriptVar<F>,
     {
         let mut circuit = PlonkCircuit::<E::Fq>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
 
@@ -722,7 +1102,7 @@ mod test {
         let generator_g = &generator_g.into();
         let blinding_factor_var = circuit.create_variable(field_switching(blinding_factor))?;
 
-        let partial_verify_points = VerifyingKeyVar::partial_verify_circuit(
+        let partial_verify_points = VerifyingKeyVar::partial_verify_circuit::<_, _, V>(
             &mut circuit,
             &beta_g,
             &generator_g,
@@ -743,10 +1123,11 @@ mod test {
             _,
             Param377,
             RescueTranscript<_>,
+            RescueTranscriptVar<_>,
         >()
     }
 
-    fn test_variable_independence_for_partial_verification_circuit_helper<E, F, P, Q, T>(
+    fn test_variable_independence_for_partial_verification_circuit_helper<E, F, P, Q, T, V>(
     ) -> Result<(), PlonkError>
     where
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
@@ -754,6 +1135,7 @@ mod test {
         P: SWModelParameters<BaseField = F> + Clone + TEModelParameters,
         Q: TEParam<BaseField = F> + Clone,
         T: PlonkTranscript<F>,
+        V: PlonkTranscriptVar<F>,
     {
         let rng = &mut test_rng();
         let i = 8;
@@ -812,7 +1194,7 @@ mod test {
             let beta_g_ref = &srs.0.powers_of_g[1];
             let blinding_factor = E::Fr::rand(rng);
 
-            let (mut circuit, _partial_verify_points) = build_circuit::<E, F, P>(
+            let (mut circuit, _partial_verify_points) = build_circuit::<E, F, P, V>(
                 &shared_public_input,
                 &merged_vks,
                 &batch_proof,
@@ -829,4 +1211,101 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_create_public_fp_elem_inputs() -> Result<(), PlonkError> {
+        test_create_public_fp_elem_inputs_helper::<Bls12_377, Fq377, _>()
+    }
+
+    fn test_create_public_fp_elem_inputs_helper<E, F, P>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: PrimeField + RescueParameter + SWToTEConParam,
+        P: SWParam<BaseField = F> + Clone,
+    {
+        let rng = &mut test_rng();
+        let label = "testing".as_ref();
+        let inputs: Vec<E::Fr> = (0..8).map(|_| E::Fr::rand(rng)).collect();
+
+        // manual path with a correctly chosen `m`
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let m = 128;
+        let manual_vars: Vec<FpElemVar<F>> = inputs
+            .iter()
+            .map(|x| {
+                let var = circuit.create_public_variable(field_switching(x))?;
+                FpElemVar::new_unchecked(&mut circuit, var, m, None)
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let fp_elem_vars = circuit.create_public_fp_elem_inputs::<E>(&inputs)?;
+        assert!(fp_elem_vars.iter().all(|x| x.param_m() == m));
+        for (x, y) in manual_vars.iter().zip(fp_elem_vars.iter()) {
+            assert_eq!(x.witness(&circuit)?, y.witness(&circuit)?);
+        }
+
+        let mut transcript_var_1 = RescueTranscriptVar::new(&mut circuit);
+        let mut transcript_var_2 = RescueTranscriptVar::new(&mut circuit);
+        let vk_var = VerifyingKeyVar::<E> {
+            sigma_comms: vec![],
+            selector_comms: vec![],
+            is_merged: false,
+            domain_size: 512,
+            num_inputs: inputs.len(),
+            k: vec![],
+            plookup_vk: None,
+        };
+        transcript_var_1.append_vk_and_pub_input_vars::<E>(&mut circuit, &vk_var, &manual_vars)?;
+        transcript_var_2.append_vk_and_pub_input_vars::<E>(&mut circuit, &vk_var, &fp_elem_vars)?;
+        let challenge_1 =
+            transcript_var_1.get_and_append_challenge_var::<E>(label, &mut circuit)?;
+        let challenge_2 =
+            transcript_var_2.get_and_append_challenge_var::<E>(label, &mut circuit)?;
+        assert_eq!(circuit.witness(challenge_1)?, circuit.witness(challenge_2)?);
+
+        let pub_input: Vec<F> = [inputs.as_slice(), inputs.as_slice()]
+            .concat()
+            .iter()
+            .map(field_switching)
+            .collect();
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_ok());
+
+        // a non-canonical split of an input is rejected by the range checks
+        let (var0, var1) = fp_elem_vars[0].components();
+        *circuit.witness_mut(var0) -= fp_elem_vars[0].two_power_m();
+        *circuit.witness_mut(var1) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_err());
+
+        // an input lifted by the modulus of `E::Fr` is rejected although both
+        // of its components pass the range checks
+        let fr_modulus_bits = <E::Fr as PrimeField>::Params::MODULUS.to_bytes_le();
+        let fr_modulus = F::from_le_bytes_mod_order(&fr_modulus_bits);
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let fp_elem_vars = circuit.create_public_fp_elem_inputs::<E>(&inputs[..1])?;
+        let input: F = field_switching(&inputs[0]);
+        assert!(circuit.check_circuit_satisfiability(&[input]).is_ok());
+        let (var0, var1) = fp_elem_vars[0].components();
+        // the public variable is created right before its components
+        let input_var = var0 - 1;
+        let lifted_input = input + fr_modulus;
+        let lifted_split = FpElem::new(&lifted_input, m, None)?.components();
+        assert!(lifted_split.1 < fp_elem_vars[0].two_power_m());
+        *circuit.witness_mut(input_var) = lifted_input;
+        *circuit.witness_mut(var0) = lifted_split.0;
+        *circuit.witness_mut(var1) = lifted_split.1;
+        assert!(circuit
+            .check_circuit_satisfiability(&[lifted_input])
+            .is_err());
+
+        // the range table size is chosen if it is automatic
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RangeTableSize::Auto);
+        let fp_elem_vars = circuit.create_public_fp_elem_inputs::<E>(&inputs)?;
+        assert_eq!(circuit.range_bit_len()?, MAX_AUTO_RANGE_BIT_LEN);
+        assert!(fp_elem_vars
+            .iter()
+            .all(|x| x.param_m() == fr_split_param::<E>(MAX_AUTO_RANGE_BIT_LEN)));
+        let pub_input: Vec<F> = inputs.iter().map(field_switching).collect();
+        assert!(circuit.check_circuit_satisfiability(&pub_input).is_ok());
+
+        Ok(())
+    }
 }
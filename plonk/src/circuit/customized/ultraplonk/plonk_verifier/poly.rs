@@ -16,7 +16,7 @@ use crate::{
     errors::{PlonkError, SnarkError::ParameterError},
 };
 use ark_ec::PairingEngine;
-use ark_ff::PrimeField;
+use ark_ff::{Field, PrimeField};
 use ark_poly::{EvaluationDomain, Radix2EvaluationDomain};
 use ark_std::{format, string::ToString, vec, vec::Vec, One};
 use jf_utils::field_switching;
@@ -34,15 +34,13 @@ use jf_utils::field_switching;
 /// - zeta^n
 /// - zeta^n - 1
 /// - lagrange evaluation at 1
-///
-/// Note that evaluation at n is commented out as we don't need it for
-/// partial verification circuit.
+/// - lagrange evaluation at n, which is only used for Plookup proofs
 pub(super) fn evaluate_poly_helper<E, F>(
     circuit: &mut PlonkCircuit<F>,
     zeta_fp_elem_var: &FpElemVar<F>,
     domain_size: usize,
     non_native_field_info: NonNativeFieldInfo<F>,
-) -> Result<([FpElemVar<F>; 3]), PlonkError>
+) -> Result<([FpElemVar<F>; 4]), PlonkError>
 where
     E: PairingEngine<Fq = F>,
     F: PrimeField,
@@ -162,13 +160,208 @@ where
     )?;
     left.enforce_equal(circuit, &zeta_n_minus_one_fp_elem_var)?;
 
+    // ================================
+    // evaluate lagrange at n
+    //  lagrange_n_eval = (zeta^n - 1) * g^{-1} / (zeta - g^{-1}) / domain_size
+    //
+    // which is proven via
+    //  domain_size * lagrange_n_eval * (zeta - g^{-1})
+    //      = (zeta^n - 1) * g^{-1} mod Fr::modulus
+    // ================================
+    let domain =
+        Radix2EvaluationDomain::<E::Fr>::new(domain_size).ok_or(PlonkError::DomainCreationError)?;
+    let group_gen_inv_fp_elem = FpElem::new(
+        &field_switching(&domain.group_gen_inv),
+        non_native_field_info.m,
+        non_native_field_info.two_power_m,
+    )?;
+
+    // lagrange_n_eval
+    let zeta_minus_g_inv_fr = zeta_fr - domain.group_gen_inv;
+    let divisor = E::Fr::from(domain_size as u64) * zeta_minus_g_inv_fr;
+    let lagrange_n_eval = zeta_n_minus_one * domain.group_gen_inv / divisor;
+    let lagrange_n_eval_fp_elem_var = FpElemVar::new_from_field_element(
+        circuit,
+        &field_switching(&lagrange_n_eval),
+        non_native_field_info.m,
+        non_native_field_info.two_power_m,
+    )?;
+
+    // zeta - g^{-1}
+    let zeta_minus_g_inv_fp_elem_var = FpElemVar::new_from_field_element(
+        circuit,
+        &field_switching(&zeta_minus_g_inv_fr),
+        non_native_field_info.m,
+        non_native_field_info.two_power_m,
+    )?;
+    let zeta_fp_elem_var_rec = circuit.mod_add_constant(
+        &zeta_minus_g_inv_fp_elem_var,
+        &group_gen_inv_fp_elem,
+        &non_native_field_info.modulus_fp_elem,
+    )?;
+    zeta_fp_elem_var.enforce_equal(circuit, &zeta_fp_elem_var_rec)?;
+
+    // left
+    let mut left = circuit.mod_mul_constant(
+        &lagrange_n_eval_fp_elem_var,
+        &domain_size_fp_elem,
+        &non_native_field_info.modulus_fp_elem,
+    )?;
+    left = circuit.mod_mul(
+        &left,
+        &zeta_minus_g_inv_fp_elem_var,
+        &non_native_field_info.modulus_fp_elem,
+    )?;
+    // right
+    let right = circuit.mod_mul_constant(
+        &zeta_n_minus_one_fp_elem_var,
+        &group_gen_inv_fp_elem,
+        &non_native_field_info.modulus_fp_elem,
+    )?;
+    left.enforce_equal(circuit, &right)?;
+
     Ok([
         zeta_n_fp_elem_var,
         zeta_n_minus_one_fp_elem_var,
         lagrange_1_eval_fp_elem_var,
+        lagrange_n_eval_fp_elem_var,
     ])
 }
 
+/// Evaluate the vanishing polynomial `Z_H(zeta) = zeta^n - 1 mod p` where `n`
+/// is `domain_size` and `p` is `modulus`, over a non-native circuit.
+/// When `domain_size` is a power of two (as Plonk domains are), zeta^n is
+/// computed with repeated squaring; otherwise with square-and-multiply.
+/// The output is reduced modulo `p`.
+pub(super) fn eval_vanishing_poly<F>(
+    circuit: &mut PlonkCircuit<F>,
+    zeta_fp_elem_var: &FpElemVar<F>,
+    domain_size: usize,
+    modulus: &FpElem<F>,
+) -> Result<FpElemVar<F>, PlonkError>
+where
+    F: PrimeField,
+{
+    if domain_size == 0 {
+        return Err(ParameterError("domain size cannot be zero".to_string()).into());
+    }
+
+    // compute zeta^n
+    let mut zeta_n_fp_elem_var = *zeta_fp_elem_var;
+    if domain_size.is_power_of_two() {
+        let mut ctr = 1;
+        while ctr < domain_size {
+            ctr <<= 1;
+            zeta_n_fp_elem_var =
+                circuit.mod_mul(&zeta_n_fp_elem_var, &zeta_n_fp_elem_var, modulus)?;
+        }
+    } else {
+        // square-and-multiply, from the most significant bit
+        let num_bits = usize::BITS - domain_size.leading_zeros();
+        for i in (0..num_bits - 1).rev() {
+            zeta_n_fp_elem_var =
+                circuit.mod_mul(&zeta_n_fp_elem_var, &zeta_n_fp_elem_var, modulus)?;
+            if (domain_size >> i) & 1 == 1 {
+                zeta_n_fp_elem_var =
+                    circuit.mod_mul(&zeta_n_fp_elem_var, zeta_fp_elem_var, modulus)?;
+            }
+        }
+    }
+
+    // zeta^n - 1 mod p
+    let one_fp_elem_var = FpElemVar::one(
+        circuit,
+        zeta_fp_elem_var.param_m(),
+        Some(zeta_fp_elem_var.two_power_m()),
+    );
+    circuit.mod_sub(&zeta_n_fp_elem_var, &one_fp_elem_var, modulus)
+}
+
+/// Evaluate the public input polynomial of a single (non-merged) instance at
+/// point `zeta`, i.e.
+///
+/// PI(zeta) = \sum_{i=0..l} L_{i,H}(zeta) * pub_input[i]
+///
+/// where L_{i,H}(zeta) = Z_H(zeta) * g^i / (n * (zeta - g^i)).
+///
+/// Each lagrange evaluation is computed in the clear and proven via
+/// L_{i,H}(zeta) * (zeta - g^i) = Z_H(zeta) * g^i / n mod p.
+/// Returns zero if `pub_inputs_fp_elem_var` is empty.
+pub(super) fn compute_pi_poly_eval<E, F>(
+    circuit: &mut PlonkCircuit<F>,
+    pub_inputs_fp_elem_var: &[FpElemVar<F>],
+    zeta_fp_elem_var: &FpElemVar<F>,
+    domain_size: usize,
+    modulus: &FpElem<F>,
+) -> Result<FpElemVar<F>, PlonkError>
+where
+    E: PairingEngine<Fq = F>,
+    F: PrimeField,
+{
+    let m = modulus.param_m();
+    let two_power_m = Some(modulus.two_power_m());
+    if pub_inputs_fp_elem_var.is_empty() {
+        return Ok(FpElemVar::zero(circuit, m, two_power_m));
+    }
+    if pub_inputs_fp_elem_var.len() > domain_size {
+        return Err(ParameterError(format!(
+            "number of public inputs {} exceeds the domain size {}",
+            pub_inputs_fp_elem_var.len(),
+            domain_size
+        ))
+        .into());
+    }
+    let domain =
+        Radix2EvaluationDomain::<E::Fr>::new(domain_size).ok_or(PlonkError::DomainCreationError)?;
+
+    let vanish_eval_fp_elem_var =
+        eval_vanishing_poly(circuit, zeta_fp_elem_var, domain_size, modulus)?;
+
+    // constants
+    let zeta = field_switching::<_, E::Fr>(&zeta_fp_elem_var.witness(circuit)?);
+    let vanish_eval = field_switching::<_, E::Fr>(&vanish_eval_fp_elem_var.witness(circuit)?);
+    let domain_size_inv = E::Fr::from(domain_size as u64)
+        .inverse()
+        .ok_or(PlonkError::DivisionError)?;
+
+    let mut res_i_fp_elem_var = Vec::new();
+    for (i, pub_input_fp_elem_var) in pub_inputs_fp_elem_var.iter().enumerate() {
+        // compute v_i = g^i / n and L_{i,H}(zeta) in the clear
+        let v_i = domain.element(i) * domain_size_inv;
+        let v_i_fp_elem = FpElem::<F>::new(&field_switching(&v_i), m, two_power_m)?;
+        let g_i_fp_elem = FpElem::<F>::new(&field_switching(&domain.element(i)), m, two_power_m)?;
+        let zeta_minus_gi = zeta - domain.element(i);
+        let eval_i =
+            vanish_eval * v_i * zeta_minus_gi.inverse().ok_or(PlonkError::DivisionError)?;
+
+        // prove zeta_minus_gi = zeta - g^i
+        let zeta_minus_gi_elem_var = FpElemVar::new_from_field_element(
+            circuit,
+            &field_switching(&zeta_minus_gi),
+            m,
+            two_power_m,
+        )?;
+        let zeta_fp_elem_var_rec =
+            circuit.mod_add_constant(&zeta_minus_gi_elem_var, &g_i_fp_elem, modulus)?;
+        zeta_fp_elem_var.enforce_equal(circuit, &zeta_fp_elem_var_rec)?;
+
+        // prove L_{i,H}(zeta) * zeta_minus_gi = Z_H(zeta) * v_i
+        let eval_i_fp_elem_var =
+            FpElemVar::new_from_field_element(circuit, &field_switching(&eval_i), m, two_power_m)?;
+        let left = circuit.mod_mul(&eval_i_fp_elem_var, &zeta_minus_gi_elem_var, modulus)?;
+        let right = circuit.mod_mul_constant(&vanish_eval_fp_elem_var, &v_i_fp_elem, modulus)?;
+        left.enforce_equal(circuit, &right)?;
+
+        // L_{i,H}(zeta) * pub_input[i]
+        res_i_fp_elem_var.push(circuit.mod_mul(
+            &eval_i_fp_elem_var,
+            pub_input_fp_elem_var,
+            modulus,
+        )?);
+    }
+    circuit.mod_add_vec(&res_i_fp_elem_var, modulus)
+}
+
 /// Evaluate public input polynomial at point `z`.
 /// Define the following as
 /// - H: The domain with generator g
@@ -302,13 +495,17 @@ where
 ///  \prod_i=1..m-1 (w_{j,i} + beta * sigma_{j,i} + gamma)
 ///  * (w_{j,m} + gamma) * z_j(xw)
 ///
-/// return r_0 = \sum_{j=1..m} alpha^{k_j} * r_plonk_j
+/// r_lookup_j = alpha^3 * Ln(x) * (h1_x_j - h2_wx_j) -
+///              alpha^4 * L1(x) * alpha -
+///              alpha^5 * Ln(x) -
+///              alpha^6 * (x - g^{n-1}) * prod_poly_wx_j * [gamma(1+beta) +
+/// h1_x_j + beta * h1_wx_j] * [gamma(1+beta) + beta * h2_wx_j]
+///
+/// return r_0 = \sum_{j=1..m} alpha^{k_j} * (r_plonk_j + (r_lookup_j))
 /// where m is the number of instances, and k_j is the number of alpha power
 /// terms added to the first j-1 instances.
 ///
-/// - input evals: zeta^n, zeta^n-1 and Lagrange evaluated at 1
-///
-/// Note that this function cannot evaluate plookup verification circuits.
+/// - input evals: zeta^n, zeta^n-1 and Lagrange evaluated at 1 and n
 #[allow(clippy::too_many_arguments)]
 pub(super) fn compute_lin_poly_constant_term_circuit<E, F>(
     circuit: &mut PlonkCircuit<F>,
@@ -317,7 +514,7 @@ pub(super) fn compute_lin_poly_constant_term_circuit<E, F>(
     verify_keys: &[&VerifyingKeyVar<E>],
     public_inputs: &[&[FpElemVar<F>]],
     batch_proof: &BatchProofVar<F>,
-    evals: &[FpElemVar<F>; 3],
+    evals: &[FpElemVar<F>; 4],
     alpha_bases: &[FpElemVar<F>],
     non_native_field_info: NonNativeFieldInfo<F>,
 ) -> Result<FpElemVar<F>, PlonkError>
@@ -348,16 +545,26 @@ where
         }
     }
 
-    // compute public inputs
-    let pi_fp_elem_var = evaluate_pi_poly_circuit::<E, F>(
-        circuit,
-        domain_size,
-        pi,
-        &zeta_fp_elem_var,
-        &evals[1],
-        true,
-        non_native_field_info,
-    )?;
+    // compute public inputs, which are copied once in merged circuits
+    let pi_fp_elem_var = if verify_keys[0].is_merged {
+        evaluate_pi_poly_circuit::<E, F>(
+            circuit,
+            domain_size,
+            pi,
+            &zeta_fp_elem_var,
+            &evals[1],
+            true,
+            non_native_field_info,
+        )?
+    } else {
+        compute_pi_poly_eval::<E, F>(
+            circuit,
+            pi,
+            &zeta_fp_elem_var,
+            domain_size,
+            &non_native_field_info.modulus_fp_elem,
+        )?
+    };
     let pi_fr = field_switching::<_, E::Fr>(&pi_fp_elem_var.witness(circuit)?);
 
     // L1(x)*alpha_2
@@ -379,10 +586,14 @@ where
     //  \prod_i=1..m-1 (w_{j,i} + beta * sigma_{j,i} + gamma)
     //  * (w_{j,m} + gamma) * z_j(xw)
     //
-    // r_0[j] = alpha^{k_j} * r_plonk_j
+    // r_0[j] = alpha^{k_j} * (r_plonk_j + (r_lookup_j))
     // where m is the number of instances, and k_j is the number of alpha power
     // terms added to the first j-1 instances.
-    for poly_evals in batch_proof.poly_evals_vec.iter() {
+    for (poly_evals, plookup_proof) in batch_proof
+        .poly_evals_vec
+        .iter()
+        .zip(batch_proof.plookup_proofs_vec.iter())
+    {
         // =====================================================
         // r_plonk_j
         //  = PI - L1(x) * alpha^2 - alpha *
@@ -396,7 +607,8 @@ where
             non_native_field_info.m,
             non_native_field_info.two_power_m,
         );
-        for (w_j_i_var, sigma_j_i_var) in poly_evals.wires_evals[..GATE_WIDTH]
+        let num_wire_types = poly_evals.wires_evals.len();
+        for (w_j_i_var, sigma_j_i_var) in poly_evals.wires_evals[..num_wire_types - 1]
             .iter()
             .zip(poly_evals.wire_sigma_evals.iter())
         {
@@ -414,7 +626,7 @@ where
 
         // tmp = (w_{j,m} + gamma) * z_j(xw)
         let mut tmp = circuit.mod_add(
-            &poly_evals.wires_evals[GATE_WIDTH],
+            &poly_evals.wires_evals[num_wire_types - 1],
             &challenges.gamma,
             &non_native_field_info.modulus_fp_elem,
         )?;
@@ -453,13 +665,38 @@ where
         left = circuit.mod_add(&left, &tmp, &non_native_field_info.modulus_fp_elem)?;
         left.enforce_equal(circuit, &pi_fp_elem_var)?;
 
+        // r_plonk_j + alpha^3 * r_lookup_j
+        let r_j_fp_elem_var = match plookup_proof {
+            Some(proof_lk) => {
+                let r_lookup_j = plookup_constant_term_circuit::<E, F>(
+                    circuit,
+                    domain_size,
+                    challenges,
+                    &proof_lk.poly_evals,
+                    evals,
+                    non_native_field_info,
+                )?;
+                let tmp = circuit.mod_mul(
+                    &challenges.alphas[2],
+                    &r_lookup_j,
+                    &non_native_field_info.modulus_fp_elem,
+                )?;
+                circuit.mod_add(
+                    &r_plonk_j_fp_elem_var,
+                    &tmp,
+                    &non_native_field_info.modulus_fp_elem,
+                )?
+            },
+            None => r_plonk_j_fp_elem_var,
+        };
+
         // preparing data for second statement
         let r_0_component = circuit.mod_mul(
             match alpha_bases_elem_var.next() {
                 Some(p) => p,
                 None => return Err(PlonkError::IteratorOutOfRange),
             },
-            &r_plonk_j_fp_elem_var,
+            &r_j_fp_elem_var,
             &non_native_field_info.modulus_fp_elem,
         )?;
 
@@ -471,7 +708,7 @@ where
     }
     // =====================================================
     // second statement
-    // r_0 = \sum_{j=1..m} alpha^{k_j} * r_plonk_j
+    // r_0 = \sum_{j=1..m} alpha^{k_j} * (r_plonk_j + (r_lookup_j))
     // =====================================================
     let res_elem_var =
         circuit.mod_add_vec(&r_0_components, &non_native_field_info.modulus_fp_elem)?;
@@ -479,18 +716,135 @@ where
     Ok(res_elem_var)
 }
 
+/// Compute the constant term of the linearization polynomial of the Plookup
+/// argument of an instance, i.e.
+///
+/// Ln(x) * (h1_x - h2_wx - alpha^2) - alpha * L1(x) - alpha^3 * (x - g^{n-1})
+/// * prod_poly_wx * [gamma(1+beta) + h1_x + beta * h1_wx]
+/// * [gamma(1+beta) + beta * h2_wx]
+///
+/// which is scaled by alpha^3 in r_lookup_j.
+///
+/// - input evals: zeta^n, zeta^n-1 and Lagrange evaluated at 1 and n
+fn plookup_constant_term_circuit<E, F>(
+    circuit: &mut PlonkCircuit<F>,
+    domain_size: usize,
+    challenges: &ChallengesFpElemVar<F>,
+    plookup_evals: &PlookupEvaluationsVar<F>,
+    evals: &[FpElemVar<F>; 4],
+    non_native_field_info: NonNativeFieldInfo<F>,
+) -> Result<FpElemVar<F>, PlonkError>
+where
+    E: PairingEngine<Fq = F>,
+    F: PrimeField,
+{
+    let modulus = &non_native_field_info.modulus_fp_elem;
+
+    // Ln(x) * (h1_x - h2_wx - alpha^2)
+    let mut first_term = circuit.mod_sub(
+        &plookup_evals.h_1_eval,
+        &plookup_evals.h_2_next_eval,
+        modulus,
+    )?;
+    first_term = circuit.mod_sub(&first_term, &challenges.alphas[1], modulus)?;
+    first_term = circuit.mod_mul(&evals[3], &first_term, modulus)?;
+
+    // alpha * L1(x)
+    let second_term = circuit.mod_mul(&challenges.alphas[0], &evals[2], modulus)?;
+
+    // alpha^3 * (x - g^{n-1}) * prod_poly_wx * [gamma(1+beta) + h1_x + beta *
+    // h1_wx] * [gamma(1+beta) + beta * h2_wx]
+    let gamma_mul_beta_plus_one = gamma_mul_beta_plus_one_circuit(circuit, challenges, modulus)?;
+    let zeta_minus_g_inv = zeta_minus_g_inv_circuit::<E, F>(
+        circuit,
+        domain_size,
+        &challenges.zeta,
+        non_native_field_info,
+    )?;
+    let beta_h_1_next = circuit.mod_mul(&challenges.beta, &plookup_evals.h_1_next_eval, modulus)?;
+    let h_1_factor = circuit.mod_add_vec(
+        &[
+            gamma_mul_beta_plus_one,
+            plookup_evals.h_1_eval,
+            beta_h_1_next,
+        ],
+        modulus,
+    )?;
+    let beta_h_2_next = circuit.mod_mul(&challenges.beta, &plookup_evals.h_2_next_eval, modulus)?;
+    let h_2_factor = circuit.mod_add(&gamma_mul_beta_plus_one, &beta_h_2_next, modulus)?;
+    let mut third_term = circuit.mod_mul(&challenges.alphas[2], &zeta_minus_g_inv, modulus)?;
+    third_term = circuit.mod_mul(&third_term, &plookup_evals.prod_next_eval, modulus)?;
+    third_term = circuit.mod_mul(&third_term, &h_1_factor, modulus)?;
+    third_term = circuit.mod_mul(&third_term, &h_2_factor, modulus)?;
+
+    let res = circuit.mod_sub(&first_term, &second_term, modulus)?;
+    circuit.mod_sub(&res, &third_term, modulus)
+}
+
+// gamma * (1 + beta)
+#[inline]
+fn gamma_mul_beta_plus_one_circuit<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    challenges: &ChallengesFpElemVar<F>,
+    modulus: &FpElem<F>,
+) -> Result<FpElemVar<F>, PlonkError> {
+    let gamma_mul_beta = circuit.mod_mul(&challenges.gamma, &challenges.beta, modulus)?;
+    circuit.mod_add(&challenges.gamma, &gamma_mul_beta, modulus)
+}
+
+// zeta - g^{n-1}, where g^{n-1} = g^{-1} for the generator g of the domain
+#[inline]
+fn zeta_minus_g_inv_circuit<E, F>(
+    circuit: &mut PlonkCircuit<F>,
+    domain_size: usize,
+    zeta: &FpElemVar<F>,
+    non_native_field_info: NonNativeFieldInfo<F>,
+) -> Result<FpElemVar<F>, PlonkError>
+where
+    E: PairingEngine<Fq = F>,
+    F: PrimeField,
+{
+    let domain =
+        Radix2EvaluationDomain::<E::Fr>::new(domain_size).ok_or(PlonkError::DomainCreationError)?;
+    let minus_g_inv = FpElem::new(
+        &field_switching(&-domain.group_gen_inv),
+        non_native_field_info.m,
+        non_native_field_info.two_power_m,
+    )?;
+    circuit.mod_add_constant(zeta, &minus_g_inv, &non_native_field_info.modulus_fp_elem)
+}
+
+// The evaluation `first + q * tau * (x[0] + tau * (x[1] + tau * x[2]))` of a
+// merged lookup witness or table, c.f. `eval_merged_lookup_witness` and
+// `eval_merged_table`.
+fn eval_merged_circuit<F: PrimeField>(
+    circuit: &mut PlonkCircuit<F>,
+    tau: &FpElemVar<F>,
+    first: &FpElemVar<F>,
+    q: &FpElemVar<F>,
+    x: &[FpElemVar<F>; 3],
+    modulus: &FpElem<F>,
+) -> Result<FpElemVar<F>, PlonkError> {
+    let mut res = x[2];
+    for x_i in x[..2].iter().rev() {
+        res = circuit.mod_mul(&res, tau, modulus)?;
+        res = circuit.mod_add(x_i, &res, modulus)?;
+    }
+    res = circuit.mod_mul(&res, tau, modulus)?;
+    res = circuit.mod_mul(&res, q, modulus)?;
+    circuit.mod_add(first, &res, modulus)
+}
+
 /// Compute the bases and scalars in the batched polynomial commitment,
 /// which is a generalization of `[D]1` specified in Sec 8.3, Verifier
 /// algorithm step 9 of https://eprint.iacr.org/2019/953.pdf.
 ///
-/// - input evals: zeta^n, zeta^n-1 and Lagrange evaluated at 1
-///
-/// Do not compute plookup related variables.
+/// - input evals: zeta^n, zeta^n-1 and Lagrange evaluated at 1 and n
 pub(super) fn linearization_scalars_and_bases_circuit<E, F>(
     circuit: &mut PlonkCircuit<F>,
     vks: &[&VerifyingKeyVar<E>],
     challenges: &ChallengesFpElemVar<F>,
-    poly_evals: &[FpElemVar<F>; 3],
+    poly_evals: &[FpElemVar<F>; 4],
     batch_proof: &BatchProofVar<F>,
     alpha_bases: &[FpElemVar<F>],
     non_native_field_info: NonNativeFieldInfo<F>,
@@ -692,6 +1046,105 @@ where
             scalars_and_bases.scalars.push(tmp);
             scalars_and_bases.bases.push(bases);
         }
+
+        // ============================================
+        // Add Plookup related commitments
+        // ============================================
+        if let Some(lookup_proof) = batch_proof.plookup_proofs_vec[i].as_ref() {
+            let modulus = &non_native_field_info.modulus_fp_elem;
+            let lookup_evals = &lookup_proof.poly_evals;
+            let merged_lookup_x = eval_merged_circuit(
+                circuit,
+                &challenges.tau,
+                &w_evals[5],
+                &lookup_evals.q_lookup_eval,
+                &[w_evals[0], w_evals[1], w_evals[2]],
+                modulus,
+            )?;
+            let merged_table_x = eval_merged_circuit(
+                circuit,
+                &challenges.tau,
+                &lookup_evals.range_table_eval,
+                &lookup_evals.q_lookup_eval,
+                &[lookup_evals.key_table_eval, w_evals[3], w_evals[4]],
+                modulus,
+            )?;
+            let merged_table_xw = eval_merged_circuit(
+                circuit,
+                &challenges.tau,
+                &lookup_evals.range_table_next_eval,
+                &lookup_evals.q_lookup_next_eval,
+                &[
+                    lookup_evals.key_table_next_eval,
+                    lookup_evals.w_3_next_eval,
+                    lookup_evals.w_4_next_eval,
+                ],
+                modulus,
+            )?;
+            let gamma_mul_beta_plus_one =
+                gamma_mul_beta_plus_one_circuit(circuit, challenges, modulus)?;
+            let zeta_minus_g_inv = zeta_minus_g_inv_circuit::<E, F>(
+                circuit,
+                vk.domain_size,
+                &challenges.zeta,
+                non_native_field_info,
+            )?;
+
+            // coefficient for prod_lookup_poly(X):
+            // coeff_lin_poly = alpha^4 * L1(x) +
+            //                  alpha^5 * Ln(x) +
+            //                  alpha^6 * (x - w^{n-1}) * (1+beta) * (gamma + lookup_w_eval)
+            //                  * (gamma(1+beta) + table_x + beta * table_xw),
+            let one = FpElemVar::one(
+                circuit,
+                non_native_field_info.m,
+                non_native_field_info.two_power_m,
+            );
+            let beta_plus_one = circuit.mod_add(&one, &challenges.beta, modulus)?;
+            let gamma_plus_lookup_x =
+                circuit.mod_add(&challenges.gamma, &merged_lookup_x, modulus)?;
+            let beta_table_xw = circuit.mod_mul(&challenges.beta, &merged_table_xw, modulus)?;
+            let table_factor = circuit.mod_add_vec(
+                &[gamma_mul_beta_plus_one, merged_table_x, beta_table_xw],
+                modulus,
+            )?;
+            let mut tmp = circuit.mod_mul(&challenges.alphas[5], &zeta_minus_g_inv, modulus)?;
+            tmp = circuit.mod_mul(&tmp, &beta_plus_one, modulus)?;
+            tmp = circuit.mod_mul(&tmp, &gamma_plus_lookup_x, modulus)?;
+            tmp = circuit.mod_mul(&tmp, &table_factor, modulus)?;
+            let alpha_4_l1 = circuit.mod_mul(&challenges.alphas[3], &poly_evals[2], modulus)?;
+            let alpha_5_ln = circuit.mod_mul(&challenges.alphas[4], &poly_evals[3], modulus)?;
+            let mut coeff = circuit.mod_add_vec(&[alpha_4_l1, alpha_5_ln, tmp], modulus)?;
+            coeff = circuit.mod_mul(&coeff, current_alpha_bases, modulus)?;
+            scalars_and_bases.scalars.push(coeff);
+            scalars_and_bases
+                .bases
+                .push(lookup_proof.prod_lookup_poly_comm);
+
+            // coefficient for h2(X):
+            // coeff_lin_poly = alpha_base * alpha^6 * (w^{n-1} - x)
+            //                  * prod_lookup_poly_xw
+            //                  * [gamma(1+beta) + h1_x + beta * h1_xw]
+            // note that we compute it with (x - w^{n-1}), so we will need to
+            // inverse the basis
+            let beta_h_1_next =
+                circuit.mod_mul(&challenges.beta, &lookup_evals.h_1_next_eval, modulus)?;
+            let h_1_factor = circuit.mod_add_vec(
+                &[
+                    gamma_mul_beta_plus_one,
+                    lookup_evals.h_1_eval,
+                    beta_h_1_next,
+                ],
+                modulus,
+            )?;
+            let mut coeff = circuit.mod_mul(current_alpha_bases, &challenges.alphas[5], modulus)?;
+            coeff = circuit.mod_mul(&coeff, &zeta_minus_g_inv, modulus)?;
+            coeff = circuit.mod_mul(&coeff, &lookup_evals.prod_next_eval, modulus)?;
+            coeff = circuit.mod_mul(&coeff, &h_1_factor, modulus)?;
+            scalars_and_bases.scalars.push(coeff);
+            let tmp = circuit.inverse_point(&lookup_proof.h_poly_comms[1])?;
+            scalars_and_bases.bases.push(tmp);
+        }
     }
 
     // ensure all the buffer has been consumed
@@ -739,11 +1192,16 @@ where
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::circuit::Circuit;
+    use crate::{
+        circuit::{customized::ecc::SWToTEConParam, Circuit},
+        proof_system::verifier::Verifier,
+    };
     use ark_bls12_377::Bls12_377;
+    use ark_ec::{short_weierstrass_jacobian::GroupAffine, SWModelParameters};
     use ark_ff::Field;
     use ark_poly::Radix2EvaluationDomain;
     use ark_std::{test_rng, One, UniformRand};
+    use jf_rescue::RescueParameter;
     use jf_utils::field_switching;
 
     const RANGE_BIT_LEN_FOR_TEST: usize = 16;
@@ -767,6 +1225,8 @@ mod test {
             let zeta_n = vanish_eval + E::Fr::one();
             let divisor = E::Fr::from(domain_size as u32) * (zeta - E::Fr::one());
             let lagrange_1_eval = vanish_eval / divisor;
+            let divisor = E::Fr::from(domain_size as u32) * (zeta - domain.group_gen_inv);
+            let lagrange_n_eval = vanish_eval * domain.group_gen_inv / divisor;
 
             // compute the variables
             let m = 128;
@@ -812,6 +1272,131 @@ mod test {
                 field_switching::<_, E::Fq>(&lagrange_1_eval),
                 circuit.witness(tmp).unwrap(),
             );
+
+            let tmp = eval_results[3].convert_to_var(&mut circuit).unwrap();
+            assert_eq!(
+                field_switching::<_, E::Fq>(&lagrange_n_eval),
+                circuit.witness(tmp).unwrap(),
+            );
         }
     }
+
+    #[test]
+    fn test_compute_pi_poly_eval() {
+        test_compute_pi_poly_eval_helper::<Bls12_377, _, _>();
+    }
+
+    fn test_compute_pi_poly_eval_helper<E, F, P>()
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let mut rng = test_rng();
+        let m = 128;
+        let fr_modulus_bits = <E::Fr as PrimeField>::Params::MODULUS.to_bytes_le();
+        let modulus_in_f = F::from_le_bytes_mod_order(&fr_modulus_bits);
+        let modulus_fp_elem = FpElem::new(&modulus_in_f, m, None).unwrap();
+
+        for domain_size in [16, 64, 256] {
+            let verifier = Verifier::<E>::new(domain_size).unwrap();
+            for num_inputs in [0, 1, 5, 16] {
+                let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+                let zeta = E::Fr::rand(&mut rng);
+                let pub_inputs: Vec<E::Fr> =
+                    (0..num_inputs).map(|_| E::Fr::rand(&mut rng)).collect();
+
+                // compute the result in the clear, as the native verifier does
+                let vanish_eval = verifier.domain.evaluate_vanishing_polynomial(zeta);
+                let expected = verifier
+                    .evaluate_pi_poly(&pub_inputs, &zeta, &vanish_eval, false)
+                    .unwrap();
+
+                // compute the result in the circuit
+                let zeta_var = circuit.create_variable(field_switching(&zeta)).unwrap();
+                let zeta_fp_elem_var =
+                    FpElemVar::new_unchecked(&mut circuit, zeta_var, m, None).unwrap();
+                let pub_inputs_fp_elem_var: Vec<FpElemVar<F>> = pub_inputs
+                    .iter()
+                    .map(|x| {
+                        FpElemVar::new_from_field_element(
+                            &mut circuit,
+                            &field_switching(x),
+                            m,
+                            None,
+                        )
+                        .unwrap()
+                    })
+                    .collect();
+                let res = compute_pi_poly_eval::<E, F>(
+                    &mut circuit,
+                    &pub_inputs_fp_elem_var,
+                    &zeta_fp_elem_var,
+                    domain_size,
+                    &modulus_fp_elem,
+                )
+                .unwrap();
+
+                assert_eq!(
+                    field_switching::<_, F>(&expected),
+                    res.witness(&circuit).unwrap()
+                );
+                assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            }
+        }
+
+        // more public inputs than the domain size
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let zeta_fp_elem_var = FpElemVar::one(&circuit, m, None);
+        let pub_inputs_fp_elem_var = vec![FpElemVar::one(&circuit, m, None); 5];
+        assert!(compute_pi_poly_eval::<E, F>(
+            &mut circuit,
+            &pub_inputs_fp_elem_var,
+            &zeta_fp_elem_var,
+            4,
+            &modulus_fp_elem,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_eval_vanishing_poly() {
+        test_eval_vanishing_poly_helper::<Bls12_377>();
+    }
+
+    fn test_eval_vanishing_poly_helper<E: PairingEngine>() {
+        let mut rng = test_rng();
+        let m = 128;
+        let fr_modulus_bits = <E::Fr as PrimeField>::Params::MODULUS.to_bytes_le();
+        let modulus_in_f = E::Fq::from_le_bytes_mod_order(&fr_modulus_bits);
+        let modulus_fp_elem = FpElem::new(&modulus_in_f, m, None).unwrap();
+
+        for domain_size in [1, 2, 3, 13, 64, 100, 1024] {
+            let mut circuit = PlonkCircuit::<E::Fq>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+            for zeta in [E::Fr::rand(&mut rng), E::Fr::rand(&mut rng), E::Fr::one()] {
+                let expected = zeta.pow(&[domain_size as u64]) - E::Fr::one();
+
+                let zeta_var = circuit.create_variable(field_switching(&zeta)).unwrap();
+                let zeta_fp_elem_var =
+                    FpElemVar::new_unchecked(&mut circuit, zeta_var, m, None).unwrap();
+                let res = eval_vanishing_poly(
+                    &mut circuit,
+                    &zeta_fp_elem_var,
+                    domain_size,
+                    &modulus_fp_elem,
+                )
+                .unwrap();
+                let res_var = res.convert_to_var(&mut circuit).unwrap();
+                assert_eq!(
+                    field_switching::<_, E::Fq>(&expected),
+                    circuit.witness(res_var).unwrap(),
+                );
+            }
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        }
+
+        let mut circuit = PlonkCircuit::<E::Fq>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let zeta_fp_elem_var = FpElemVar::one(&circuit, m, None);
+        assert!(eval_vanishing_poly(&mut circuit, &zeta_fp_elem_var, 0, &modulus_fp_elem).is_err());
+    }
 }
@@ -15,7 +15,7 @@ use crate::{
     errors::PlonkError,
 };
 use ark_ff::PrimeField;
-use ark_std::vec::Vec;
+use ark_std::{vec, vec::Vec};
 
 /// Plonk IOP verifier challenges.
 #[derive(Debug, Default)]
@@ -32,8 +32,9 @@ pub(crate) struct ChallengesVar {
 /// Plonk IOP verifier challenges.
 #[derive(Debug, Default)]
 pub(crate) struct ChallengesFpElemVar<F: PrimeField> {
-    pub(crate) _tau: FpElemVar<F>,
-    pub(crate) alphas: [FpElemVar<F>; 3],
+    pub(crate) tau: FpElemVar<F>,
+    /// `alpha, alpha^2, ..., alpha^7`
+    pub(crate) alphas: [FpElemVar<F>; 7],
     pub(crate) beta: FpElemVar<F>,
     pub(crate) gamma: FpElemVar<F>,
     pub(crate) zeta: FpElemVar<F>,
@@ -57,20 +58,26 @@ pub(crate) fn challenge_var_to_fp_elem_var<F: PrimeField>(
         &alpha_fp_elem_var,
         &non_native_field_info.modulus_fp_elem,
     )?;
-    let alpha_3_fp_elem_var = circuit.mod_mul(
-        &alpha_2_fp_elem_var,
-        &alpha_fp_elem_var,
-        &non_native_field_info.modulus_fp_elem,
-    )?;
+    // alpha^3, ..., alpha^7, the higher powers being used by the Plookup
+    // argument
+    let mut alphas = [alpha_fp_elem_var; 7];
+    alphas[1] = alpha_2_fp_elem_var;
+    for i in 2..alphas.len() {
+        alphas[i] = circuit.mod_mul(
+            &alphas[i - 1],
+            &alpha_fp_elem_var,
+            &non_native_field_info.modulus_fp_elem,
+        )?;
+    }
 
     Ok(ChallengesFpElemVar {
-        _tau: FpElemVar::new_unchecked(
+        tau: FpElemVar::new_unchecked(
             circuit,
             challenge_var.tau,
             non_native_field_info.m,
             non_native_field_info.two_power_m,
         )?,
-        alphas: [alpha_fp_elem_var, alpha_2_fp_elem_var, alpha_3_fp_elem_var],
+        alphas,
         beta: FpElemVar::new_unchecked(
             circuit,
             challenge_var.beta,
@@ -155,9 +162,9 @@ pub struct BatchProofVar<F: PrimeField> {
     /// The list of polynomial evaluations.
     pub(crate) poly_evals_vec: Vec<ProofEvaluationsVar<F>>,
 
-    // /// The list of partial proofs for Plookup argument
-    // not used for plonk verification circuit
-    // pub(crate) plookup_proofs_vec: Vec<Option<PlookupProofVar>>,
+    /// The list of partial proofs for Plookup argument
+    pub(crate) plookup_proofs_vec: Vec<Option<PlookupProofVar<F>>>,
+
     /// Splitted quotient polynomial commitments.
     pub(crate) split_quot_poly_comms: Vec<PointVariable>,
 
@@ -176,10 +183,38 @@ impl<F: PrimeField> BatchProofVar<F> {
     }
 }
 
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+/// Represent variables of a KZG opening accumulator: the two group elements
+/// `inner1` and `inner2` of a deferred pairing check
+/// `e(inner1, [beta]2) = e(inner2, [1]2)`.
+pub struct AccumulatorVar {
+    /// The variable for the first group element of the pairing.
+    pub(crate) inner1: PointVariable,
+    /// The variable for the second group element of the pairing.
+    pub(crate) inner2: PointVariable,
+}
+
+impl AccumulatorVar {
+    /// Create an accumulator from the variables of its two group elements.
+    pub fn new(inner1: PointVariable, inner2: PointVariable) -> Self {
+        Self { inner1, inner2 }
+    }
+
+    /// Get the variable for the first group element of the pairing.
+    pub fn inner1(&self) -> PointVariable {
+        self.inner1
+    }
+
+    /// Get the variable for the second group element of the pairing.
+    pub fn inner2(&self) -> PointVariable {
+        self.inner2
+    }
+}
+
 /// Represent variables for a struct that stores the polynomial evaluations in a
 /// Plonk proof.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub(crate) struct ProofEvaluationsVar<F: PrimeField> {
+pub struct ProofEvaluationsVar<F: PrimeField> {
     /// Wire witness polynomials evaluations at point `zeta`.
     pub(crate) wires_evals: Vec<FpElemVar<F>>,
 
@@ -191,6 +226,89 @@ pub(crate) struct ProofEvaluationsVar<F: PrimeField> {
     pub(crate) perm_next_eval: FpElemVar<F>,
 }
 
+/// Represent variables of a Plookup argument proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct PlookupProofVar<F: PrimeField> {
+    /// The commitments for the polynomials that interpolate the sorted
+    /// concatenation of the lookup table and the witnesses in the lookup gates.
+    pub(crate) h_poly_comms: Vec<PointVariable>,
+
+    /// The product accumulation polynomial commitment for the Plookup argument
+    pub(crate) prod_lookup_poly_comm: PointVariable,
+
+    /// Polynomial evaluations.
+    pub(crate) poly_evals: PlookupEvaluationsVar<F>,
+}
+
+/// Represent variables for a struct that stores the polynomial evaluations in a
+/// Plookup argument proof.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlookupEvaluationsVar<F: PrimeField> {
+    /// Range table polynomial evaluation at point `zeta`.
+    pub(crate) range_table_eval: FpElemVar<F>,
+
+    /// Key table polynomial evaluation at point `zeta`.
+    pub(crate) key_table_eval: FpElemVar<F>,
+
+    /// The first sorted vector polynomial evaluation at point `zeta`.
+    pub(crate) h_1_eval: FpElemVar<F>,
+
+    /// The lookup selector polynomial evaluation at point `zeta`.
+    pub(crate) q_lookup_eval: FpElemVar<F>,
+
+    /// Lookup product polynomial evaluation at point `zeta * g`.
+    pub(crate) prod_next_eval: FpElemVar<F>,
+
+    /// Range table polynomial evaluation at point `zeta * g`.
+    pub(crate) range_table_next_eval: FpElemVar<F>,
+
+    /// Key table polynomial evaluation at point `zeta * g`.
+    pub(crate) key_table_next_eval: FpElemVar<F>,
+
+    /// The first sorted vector polynomial evaluation at point `zeta * g`.
+    pub(crate) h_1_next_eval: FpElemVar<F>,
+
+    /// The second sorted vector polynomial evaluation at point `zeta * g`.
+    pub(crate) h_2_next_eval: FpElemVar<F>,
+
+    /// The lookup selector polynomial evaluation at point `zeta * g`.
+    pub(crate) q_lookup_next_eval: FpElemVar<F>,
+
+    /// The 4th witness polynomial evaluation at point `zeta * g`.
+    pub(crate) w_3_next_eval: FpElemVar<F>,
+
+    /// The 5th witness polynomial evaluation at point `zeta * g`.
+    pub(crate) w_4_next_eval: FpElemVar<F>,
+}
+
+impl<F: PrimeField> PlookupEvaluationsVar<F> {
+    /// Return the list of evaluations at point `zeta`, in the order of
+    /// `PlookupEvaluations::evals_vec`.
+    pub(crate) fn evals_vec(&self) -> Vec<FpElemVar<F>> {
+        vec![
+            self.range_table_eval,
+            self.key_table_eval,
+            self.h_1_eval,
+            self.q_lookup_eval,
+        ]
+    }
+
+    /// Return the list of evaluations at point `zeta * g`, in the order of
+    /// `PlookupEvaluations::next_evals_vec`.
+    pub(crate) fn next_evals_vec(&self) -> Vec<FpElemVar<F>> {
+        vec![
+            self.prod_next_eval,
+            self.range_table_next_eval,
+            self.key_table_next_eval,
+            self.h_1_next_eval,
+            self.h_2_next_eval,
+            self.q_lookup_next_eval,
+            self.w_3_next_eval,
+            self.w_4_next_eval,
+        ]
+    }
+}
+
 /// Information related to non-native field
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub(crate) struct NonNativeFieldInfo<F: PrimeField> {
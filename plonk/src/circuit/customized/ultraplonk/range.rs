@@ -10,7 +10,7 @@ use crate::{
     errors::{PlonkError, SnarkError::ParameterError},
 };
 use ark_ff::{BigInteger, PrimeField};
-use ark_std::{string::ToString, vec::Vec};
+use ark_std::{format, string::ToString, vec, vec::Vec};
 
 impl<F: PrimeField> PlonkCircuit<F> {
     /// Constrain a variable to be within the [0, 2^{bit_len}) range
@@ -23,7 +23,7 @@ impl<F: PrimeField> PlonkCircuit<F> {
         a: Variable,
         bit_len: usize,
     ) -> Result<(), PlonkError> {
-        let range_bit_len = self.range_bit_len()?;
+        let range_bit_len = self.resolve_range_bit_len()?;
         let range_size = self.range_size()?;
         if bit_len == 0 {
             return Err(ParameterError("bit_len cannot be zero".to_string()).into());
@@ -36,11 +36,15 @@ impl<F: PrimeField> PlonkCircuit<F> {
         }
         self.check_var_bound(a)?;
         let len = bit_len / range_bit_len;
-        let reprs_le = decompose_le(self.witness(a)?, len, range_bit_len);
-        let reprs_le_vars: Vec<Variable> = reprs_le
-            .iter()
-            .map(|&val| self.create_variable(val))
-            .collect::<Result<Vec<_>, PlonkError>>()?;
+        // a single limb is the variable itself
+        let reprs_le_vars: Vec<Variable> = if len == 1 {
+            vec![a]
+        } else {
+            decompose_le(self.witness(a)?, len, range_bit_len)
+                .into_iter()
+                .map(|val| self.create_variable(val))
+                .collect::<Result<Vec<_>, PlonkError>>()?
+        };
 
         // add range gates for decomposed variables
         for &var in reprs_le_vars.iter() {
@@ -55,6 +59,64 @@ impl<F: PrimeField> PlonkCircuit<F> {
         Ok(())
     }
 
+    /// Constrain a variable to be within the [0, 2^{bit_len}) range for any
+    /// `bit_len`, e.g. 13 or 48 bits, without padding it to a multiple of
+    /// `range_bit_len`.
+    /// The variable is decomposed into `bit_len / range_bit_len` limbs looked
+    /// up in the range table, plus a partial limb of the remaining bits
+    /// `r`, which is looked up along with its product by
+    /// `2^{range_bit_len - r}`. A circuit which does not support lookup falls
+    /// back to a bitwise decomposition, and the check is deferred until the
+    /// range table size is chosen if it is automatic.
+    /// Return error if the variable is invalid, or if `bit_len` is zero or
+    /// not smaller than the bit length of the field.
+    pub fn enforce_in_range(&mut self, a: Variable, bit_len: usize) -> Result<(), PlonkError> {
+        self.check_var_bound(a)?;
+        if bit_len == 0 || bit_len >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "the bit length of a range is between 1 and {}, got {}",
+                F::size_in_bits() - 1,
+                bit_len
+            ))
+            .into());
+        }
+        if !self.support_lookup() {
+            self.range_gate_internal(a, bit_len)?;
+            return Ok(());
+        }
+        if self.is_range_bit_len_deferred() {
+            return self.defer_range_check(a, bit_len);
+        }
+        let range_bit_len = self.range_bit_len()?;
+        let range_size = self.range_size()?;
+        let partial_bit_len = bit_len % range_bit_len;
+        let len = (bit_len + range_bit_len - 1) / range_bit_len;
+        let limbs = if len == 1 {
+            vec![a]
+        } else {
+            decompose_le(self.witness(a)?, len, range_bit_len)
+                .into_iter()
+                .map(|val| self.create_variable(val))
+                .collect::<Result<Vec<_>, PlonkError>>()?
+        };
+        for &var in limbs.iter() {
+            self.add_range_check_variable(var)?;
+        }
+
+        // the partial limb p and p * 2^{range_bit_len - r} are in
+        // [0, 2^range_bit_len), i.e. p is in [0, 2^r)
+        if partial_bit_len != 0 {
+            let shift = F::from(1u64 << (range_bit_len - partial_bit_len));
+            let shifted = self.mul_constant(limbs[len - 1], &shift)?;
+            self.add_range_check_variable(shifted)?;
+        }
+
+        if len > 1 {
+            self.decompose_vars_gate(limbs, a, F::from(range_size as u64))?;
+        }
+        Ok(())
+    }
+
     /// The number of range blocks, i.e., the minimal integer such that
     /// RANGE_SIZE^NUM_RANGES >= p,
     #[inline]
@@ -144,6 +206,8 @@ mod test {
             circuit.range_gate_with_lookup(var, bit_len)?;
         }
         circuit.range_gate_with_lookup(circuit.zero(), RANGE_BIT_LEN_FOR_TEST)?;
+        let b = circuit.create_variable(F::from(RANGE_SIZE_FOR_TEST as u64 - 1))?;
+        circuit.range_gate_with_lookup(b, RANGE_BIT_LEN_FOR_TEST)?;
         assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
 
         // Error paths
@@ -153,6 +217,9 @@ mod test {
         *circuit.witness_mut(a[0]) = F::from(u32::MAX as u64 + 1);
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
         *circuit.witness_mut(a[0]) = tmp;
+        // a single limb is the variable itself
+        *circuit.witness_mut(b) = F::from(RANGE_SIZE_FOR_TEST as u64);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
 
         let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
         let zero_var = circuit.zero();
@@ -172,4 +239,56 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn test_enforce_in_range() -> Result<(), PlonkError> {
+        test_enforce_in_range_helper::<FqEd254>()?;
+        test_enforce_in_range_helper::<FqEd377>()?;
+        test_enforce_in_range_helper::<FqEd381>()?;
+        test_enforce_in_range_helper::<Fq377>()
+    }
+    fn test_enforce_in_range_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        for bit_len in [1, 5, 8, 13, 16, 48, 61] {
+            let max = F::from(2u32).pow([bit_len as u64]) - F::one();
+            let values = [
+                F::zero(),
+                max,
+                F::from(rng.gen_range(0..u64::MAX) >> (64 - bit_len)),
+            ];
+            for &value in values.iter() {
+                for mut circuit in [
+                    PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST),
+                    PlonkCircuit::<F>::new_turbo_plonk(),
+                ] {
+                    let a = circuit.create_variable(value)?;
+                    circuit.enforce_in_range(a, bit_len)?;
+                    assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+                    // the value is out of range
+                    *circuit.witness_mut(a) = max + F::one();
+                    assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+                }
+            }
+        }
+
+        // the lookup decomposition is cheaper than the bitwise one
+        let mut circuit: PlonkCircuit<F> = PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let a = circuit.create_variable(F::from(5000u32))?;
+        let num_gates = circuit.num_gates();
+        circuit.enforce_in_range(a, 13)?;
+        let cost = circuit.num_gates() - num_gates;
+        circuit.range_gate(a, 13)?;
+        assert!(cost < circuit.num_gates() - num_gates - cost);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // Error paths
+        let zero_var = circuit.zero();
+        assert!(circuit.enforce_in_range(zero_var, 0).is_err());
+        assert!(circuit
+            .enforce_in_range(zero_var, F::size_in_bits())
+            .is_err());
+        assert!(circuit.enforce_in_range(circuit.num_vars(), 13).is_err());
+        Ok(())
+    }
 }
@@ -0,0 +1,172 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the RSASSA-PKCS1-v1_5 signature verification
+//! with SHA-256 and public exponent 65537, as specified in RFC 8017, on top of
+//! the big natural number arithmetic of `bignat`.
+
+use super::{
+    bignat::{BigNatVar, BIG_NAT_LIMB_BITS},
+    emulated::parse_hex,
+};
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec::Vec};
+use num_bigint::BigUint;
+
+/// DER encoding of the `DigestInfo` prefix of SHA-256 digests.
+const SHA256_DIGEST_INFO: &str = "3031300d060960864801650304020105000420";
+
+/// Number of bytes of a SHA-256 digest.
+const SHA256_DIGEST_BYTES: usize = 32;
+
+/// Minimal number of `0xff` padding bytes in the encoded message.
+const MIN_PADDING_BYTES: usize = 8;
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Verify an RSASSA-PKCS1-v1_5 signature with SHA-256 and public exponent
+    /// 65537: the circuit is satisfied iff `sig^65537 = EM mod modulus`,
+    /// where `EM = 0x00 || 0x01 || 0xff...0xff || 0x00 || DigestInfo ||
+    /// digest` is the encoded message, of the byte length of the modulus.
+    /// * `modulus` - the RSA modulus, whose most significant byte is expected
+    ///   to be non-zero, e.g. a 2048-bit modulus with 32 limbs
+    /// * `sig` - the signature, with at most as many limbs as the modulus; it
+    ///   is not required to be reduced modulo `modulus`
+    /// * `digest` - the 32 big-endian bytes of the SHA-256 digest of the
+    ///   message, e.g. the output of the SHA-256 gadget; the byte variables are
+    ///   expected to be constrained to `[0, 256)`
+    /// * `returns` - error if the modulus is too small for the encoded
+    ///   message, or if the circuit does not support lookup
+    pub fn rsa_pkcs1v15_sha256_verify(
+        &mut self,
+        modulus: &BigNatVar,
+        sig: &BigNatVar,
+        digest: &[Variable],
+    ) -> Result<(), PlonkError> {
+        if digest.len() != SHA256_DIGEST_BYTES {
+            return Err(ParameterError(format!(
+                "SHA-256 digest should have {} bytes, got {}",
+                SHA256_DIGEST_BYTES,
+                digest.len()
+            ))
+            .into());
+        }
+        let num_limbs = modulus.num_limbs();
+        let em_bytes = num_limbs * BIG_NAT_LIMB_BITS / 8;
+        let digest_info = parse_hex(SHA256_DIGEST_INFO);
+        let digest_info_bytes = SHA256_DIGEST_INFO.len() / 2;
+        if em_bytes < 3 + MIN_PADDING_BYTES + digest_info_bytes + SHA256_DIGEST_BYTES {
+            return Err(ParameterError(format!(
+                "modulus of {} bytes is too small for the encoded message",
+                em_bytes
+            ))
+            .into());
+        }
+        self.check_vars_bound(digest)?;
+
+        // the digest fills the least significant limbs of EM
+        let limb_bytes = BIG_NAT_LIMB_BITS / 8;
+        let base = F::from(256u32);
+        let digest_le: Vec<Variable> = digest.iter().rev().copied().collect();
+        let mut limbs = Vec::with_capacity(num_limbs);
+        for bytes in digest_le.chunks(limb_bytes) {
+            let mut limb = F::zero();
+            for byte in bytes.iter().rev() {
+                limb = limb * base + self.witness(*byte)?;
+            }
+            let limb_var = self.create_variable(limb)?;
+            self.decompose_vars_gate(bytes.to_vec(), limb_var, base)?;
+            limbs.push(limb_var);
+        }
+        // the remaining limbs are constants: 0x00 || 0x01 || 0xff...0xff ||
+        // 0x00 || DigestInfo
+        let padding_bytes = em_bytes - 3 - digest_info_bytes - SHA256_DIGEST_BYTES;
+        let one = BigUint::from(1u8);
+        let padding = ((&one << (8 * padding_bytes)) - &one) << (8 * (1 + digest_info_bytes));
+        let prefix = (one << (8 * (padding_bytes + 1 + digest_info_bytes))) + padding + digest_info;
+        let mut prefix_digits = prefix.to_u64_digits();
+        prefix_digits.resize(num_limbs - limbs.len(), 0);
+        for digit in prefix_digits {
+            limbs.push(self.create_constant_variable(F::from(digit))?);
+        }
+        let em = self.big_nat_from_limbs(&limbs)?;
+
+        // sig^65537 = (sig^(2^16)) * sig
+        let mut acc = sig.clone();
+        for _ in 0..16 {
+            acc = self.big_nat_mod_mul(&acc, &acc, modulus)?;
+        }
+        self.big_nat_enforce_mod_mul(&acc, sig, modulus, &em)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+
+    // 2048-bit PKCS#1 v1.5 signature of "jellyfish rsa test" with SHA-256
+    const N: &str = "abf2e5f9463c8f9f2ef7beed98b35910fccbee520d1e73c76d35dd631f59914addfaadaa62c8fabd5fe8f037de290fd7c3d2d9b46ee9204443f3433fa1d7f7fe9e7f5a1a4d7eba710bf29a5c87110d32821ecca9f86a325087b1911a04892174a7db49b12784801a84028e444e6d601c2dd739ec59245cf0da43dc1ade026033b7a24d37038e6c535e47b4a46d070896ec8324bc57cf65b7c99b93924b5933fa52c7d462213f1c85410b699bdf31b521cc0e2688a7c7317d593982253cc1ae41c85d76c6af290397a2b177c3412dd135ae809601bab3f6efe4e938fdd35fc74f1682fcb015523d970447d0640120b07093b248d94e118415bc8ae7bd1c3c2d9f";
+    const SIG: &str = "4e8cc1dc966ec9b364baf673ae2f64e8bf4afdc0f67c5d249cbbaac48a76dd7a3c4d321028761ad0d64affecb92d3a54dcf9c9dec94bf219df0332c6933def24adaaafd0fe99ba1f10737b4f8bade23535b7f710387827f3d8aeccf87939cd9f043745d421bfc7229a85017724a2fd064cdb25932266ae558371a6c5f41b4c68f7f09455e6cde4508b6a0635023627db9dd02e3cc9484d93001b14e8e4b9fd9e7a3358d2fb95627d87dfe57dabd836970c30b3113dd00e248b1efc0150f52b4792db5a1bd9dffe07f8080b1a380afc82544013d9871504e203c96d7b82dda78a655ad81eea7eb5ff05fca30dd79aa95ad3a418b614577ead8b497035f4d12153";
+    const DIGEST: &str = "bb44649e24377bb34eb5d40cbc2f4fbfba5fe2d642c5dd41940b5052059dbea8";
+    const NUM_LIMBS: usize = 2048 / BIG_NAT_LIMB_BITS;
+
+    fn build_rsa_circuit<F: PrimeField>(
+        sig_int: &BigUint,
+        digest_bytes: &[u8],
+    ) -> Result<(PlonkCircuit<F>, Vec<Variable>), PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let modulus = circuit.create_big_nat_variable(&parse_hex(N), NUM_LIMBS)?;
+        let sig = circuit.create_big_nat_variable(sig_int, NUM_LIMBS)?;
+        let digest = digest_bytes
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        circuit.rsa_pkcs1v15_sha256_verify(&modulus, &sig, &digest)?;
+        Ok((circuit, digest))
+    }
+
+    #[test]
+    fn test_rsa_pkcs1v15_sha256_verify() -> Result<(), PlonkError> {
+        test_rsa_pkcs1v15_sha256_verify_helper::<FqEd254>()?;
+        test_rsa_pkcs1v15_sha256_verify_helper::<Fq377>()
+    }
+
+    fn test_rsa_pkcs1v15_sha256_verify_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let sig_int = parse_hex(SIG);
+        let digest_bytes = parse_hex(DIGEST).to_bytes_be();
+
+        let (mut circuit, digest) = build_rsa_circuit::<F>(&sig_int, &digest_bytes)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong digest
+        *circuit.witness_mut(digest[31]) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // wrong signature
+        let (circuit, _) = build_rsa_circuit::<F>(&(sig_int + 1u8), &digest_bytes)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid inputs
+        assert!(build_rsa_circuit::<F>(&parse_hex(SIG), &digest_bytes[1..]).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let modulus = circuit.create_big_nat_variable(&parse_hex(DIGEST), 4)?;
+        let digest = digest_bytes
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        assert!(circuit
+            .rsa_pkcs1v15_sha256_verify(&modulus, &modulus, &digest)
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,272 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Membership and non-membership proofs for public sets of bounded integers.
+//!
+//! A set `s_0 < ... < s_{n - 1}` of integers in `[0, 2^bit_len)` is inserted
+//! as a lookup table of `n + 1` elements, the `i`-th element being the
+//! adjacent pair `(s_{i - 1}, s_i)` with the sentinels `s_{-1} = -1` and
+//! `s_n = 2^bit_len`. An integer `x` in `[0, 2^bit_len)` is in the set iff it
+//! is the first entry of an element of the table, and it is not in the set
+//! iff `s_{i - 1} < x < s_i` for an element of the table, which is proven
+//! with range checks. The looked up keys are constrained to be in the table,
+//! as other tables of the circuit may contain arbitrary elements.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+    PlonkType,
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec::Vec};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A public set of integers inserted as a lookup table of the circuit.
+pub struct SetTable<F: PrimeField> {
+    // the elements of the set in ascending order
+    elems: Vec<F>,
+    // the number of bits of the elements
+    bit_len: usize,
+    // the position of the table among all the table elements
+    offset: usize,
+}
+
+impl<F: PrimeField> SetTable<F> {
+    /// The elements of the set in ascending order.
+    pub fn elems(&self) -> &[F] {
+        &self.elems
+    }
+
+    /// The number of bits of the elements of the set.
+    pub fn bit_len(&self) -> usize {
+        self.bit_len
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Insert a lookup table committing to the set of integers `elems`, which
+    /// are in `[0, 2^bit_len)`. The table has `elems.len() + 1` elements.
+    /// Return error if `elems` is empty or has duplicates, if an element is
+    /// out of range, if `bit_len` is zero or too large for the native field,
+    /// or if the circuit does not support lookup.
+    pub fn create_set_table(
+        &mut self,
+        elems: &[F],
+        bit_len: usize,
+    ) -> Result<SetTable<F>, PlonkError> {
+        self.check_plonk_type(PlonkType::UltraPlonk)?;
+        if bit_len == 0 || bit_len + 1 >= F::size_in_bits() {
+            return Err(ParameterError(format!(
+                "unsupported bit length {} of set elements",
+                bit_len
+            ))
+            .into());
+        }
+        if elems.is_empty() {
+            return Err(ParameterError("empty set".to_string()).into());
+        }
+        let mut elems = elems.to_vec();
+        elems.sort_by_key(|elem| elem.into_repr());
+        let bound = F::from(2u8).pow(&[bit_len as u64]);
+        if elems.windows(2).any(|pair| pair[0] == pair[1])
+            || elems[elems.len() - 1].into_repr() >= bound.into_repr()
+        {
+            return Err(ParameterError(format!(
+                "set elements are not distinct integers of {} bits",
+                bit_len
+            ))
+            .into());
+        }
+
+        // the elements are constant, and are shared between adjacent pairs
+        let mut bounds = Vec::with_capacity(elems.len() + 2);
+        bounds.push(self.create_constant_variable(-F::one())?);
+        for &elem in elems.iter() {
+            bounds.push(self.create_constant_variable(elem)?);
+        }
+        bounds.push(self.create_constant_variable(bound)?);
+        let table_vars: Vec<(Variable, Variable)> =
+            bounds.windows(2).map(|pair| (pair[0], pair[1])).collect();
+        let offset = self.num_table_elems();
+        self.create_table_and_lookup_variables(&[], &table_vars)?;
+        Ok(SetTable {
+            elems,
+            bit_len,
+            offset,
+        })
+    }
+
+    /// Constrain `x` to be in the set of `table`, where `x` is expected to be
+    /// constrained to be in `[0, 2^{table.bit_len()})`.
+    /// Return error if the variable is invalid, or if the table is not
+    /// inserted in the circuit.
+    pub fn set_membership_gate(
+        &mut self,
+        table: &SetTable<F>,
+        x: Variable,
+    ) -> Result<(), PlonkError> {
+        self.check_var_bound(x)?;
+        let x_val = self.witness(x)?;
+        // x = s_i is the first entry of the (i + 1)-th element; a wrong witness
+        // makes the circuit unsatisfiable
+        let index = match table
+            .elems
+            .binary_search_by_key(&x_val.into_repr(), |elem| elem.into_repr())
+        {
+            Ok(i) => i + 1,
+            Err(_) => 0,
+        };
+        let next = table
+            .elems
+            .get(index)
+            .copied()
+            .unwrap_or_else(|| F::from(2u8).pow(&[table.bit_len as u64]));
+        let next_var = self.create_variable(next)?;
+        let key = self.set_table_key(table, index)?;
+        self.lookup_variables(&[(key, x, next_var)])
+    }
+
+    /// Constrain `x` not to be in the set of `table`, where `x` is expected to
+    /// be constrained to be in `[0, 2^{table.bit_len()})`. The range checks
+    /// use the lookup range table when possible, as for `range_gate`.
+    /// Return error if the variable is invalid, or if the table is not
+    /// inserted in the circuit.
+    pub fn set_non_membership_gate(
+        &mut self,
+        table: &SetTable<F>,
+        x: Variable,
+    ) -> Result<(), PlonkError> {
+        self.check_var_bound(x)?;
+        let x_val = self.witness(x)?;
+        // s_{index - 1} < x < s_index, unless x is in the set
+        let index = table
+            .elems
+            .partition_point(|elem| elem.into_repr() < x_val.into_repr());
+        let prev = match index {
+            0 => -F::one(),
+            _ => table.elems[index - 1],
+        };
+        let next = table
+            .elems
+            .get(index)
+            .copied()
+            .unwrap_or_else(|| F::from(2u8).pow(&[table.bit_len as u64]));
+        let prev_var = self.create_variable(prev)?;
+        let next_var = self.create_variable(next)?;
+        let key = self.set_table_key(table, index)?;
+        self.lookup_variables(&[(key, prev_var, next_var)])?;
+
+        // x - prev - 1 and next - x - 1 are in [0, 2^bit_len)
+        let zero = self.zero();
+        let lower_gap = self.gen_quad_poly(
+            &[x, prev_var, zero, zero],
+            &[F::one(), -F::one(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            -F::one(),
+        )?;
+        self.range_gate(lower_gap, table.bit_len)?;
+        let upper_gap = self.gen_quad_poly(
+            &[next_var, x, zero, zero],
+            &[F::one(), -F::one(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            -F::one(),
+        )?;
+        self.range_gate(upper_gap, table.bit_len)
+    }
+
+    // The variable of the key of the `index`-th element of the table, which is
+    // constrained to be in the table.
+    fn set_table_key(&mut self, table: &SetTable<F>, index: usize) -> Result<Variable, PlonkError> {
+        let size = table.elems.len();
+        if table.offset + size >= self.num_table_elems() {
+            return Err(ParameterError("the set table is not inserted".to_string()).into());
+        }
+        // index and size - index are in [0, 2^bits), i.e. index is in [0, size]
+        let bits = (usize::BITS - size.leading_zeros()) as usize;
+        let index_var = self.create_variable(F::from(index as u64))?;
+        self.range_gate(index_var, bits)?;
+        let zero = self.zero();
+        let rev_index = self.gen_quad_poly(
+            &[index_var, zero, zero, zero],
+            &[-F::one(), F::zero(), F::zero(), F::zero()],
+            &[F::zero(), F::zero()],
+            F::from(size as u64),
+        )?;
+        self.range_gate(rev_index, bits)?;
+        self.add_constant(index_var, &F::from(table.offset as u64))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_set_membership() -> Result<(), PlonkError> {
+        test_set_membership_helper::<FqEd254>()?;
+        test_set_membership_helper::<FqEd377>()?;
+        test_set_membership_helper::<FqEd381>()?;
+        test_set_membership_helper::<Fq377>()
+    }
+
+    fn test_set_membership_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        // another table whose elements must not be used for the set
+        let zero = circuit.zero();
+        let five = circuit.create_variable(F::from(5u8))?;
+        circuit.create_table_and_lookup_variables(&[], &[(five, zero)])?;
+        let elems: Vec<F> = [40u64, 3, 65535, 17, 1000]
+            .iter()
+            .map(|&v| F::from(v))
+            .collect();
+        let table = circuit.create_set_table(&elems, 16)?;
+        assert_eq!(table.elems()[0], F::from(3u8));
+        assert_eq!(table.bit_len(), 16);
+
+        for &v in [3u64, 17, 40, 1000, 65535].iter() {
+            let x = circuit.create_variable(F::from(v))?;
+            circuit.set_membership_gate(&table, x)?;
+        }
+        for &v in [0u64, 2, 4, 39, 41, 65534].iter() {
+            let x = circuit.create_variable(F::from(v))?;
+            circuit.set_non_membership_gate(&table, x)?;
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // members are not non-members, and vice versa, even if another table
+        // contains them
+        for &(v, member) in [(5u64, true), (0, true), (4, true), (17, false), (3, false)].iter() {
+            let mut bad_circuit = circuit.clone();
+            let x = bad_circuit.create_variable(F::from(v))?;
+            if member {
+                bad_circuit.set_membership_gate(&table, x)?;
+            } else {
+                bad_circuit.set_non_membership_gate(&table, x)?;
+            }
+            assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+        // invalid sets
+        assert!(circuit.create_set_table(&[], 16).is_err());
+        assert!(circuit
+            .create_set_table(&[F::one(), F::from(2u8), F::one()], 16)
+            .is_err());
+        assert!(circuit
+            .create_set_table(&[F::from(1u64 << 16)], 16)
+            .is_err());
+        assert!(circuit.create_set_table(&[F::one()], 0).is_err());
+        assert!(PlonkCircuit::<F>::new_turbo_plonk()
+            .create_set_table(&[F::one()], 16)
+            .is_err());
+        let mut other_circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let x = other_circuit.create_variable(F::one())?;
+        assert!(other_circuit.set_membership_gate(&table, x).is_err());
+        Ok(())
+    }
+}
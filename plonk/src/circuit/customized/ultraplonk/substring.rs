@@ -0,0 +1,211 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Substring matching within byte strings, e.g. for proofs about the
+//! disclosed parts of a redacted document.
+//!
+//! A string `s_0, ..., s_{n - 1}` is inserted as a lookup table of `n`
+//! elements, the `i`-th element being `(s_i, 0)`. Its elements are variables,
+//! so that the string may be private, e.g. committed to by a hash computed in
+//! the circuit. A pattern `p_0, ..., p_{m - 1}` occurs at offset `k` iff
+//! `(p_t, 0)` is the `(k + t)`-th element of the table for every `t`, which
+//! takes `m` lookups whatever the length of the string, and a range check of
+//! `k`, as other tables of the circuit may contain arbitrary elements. The
+//! offset may be public or private.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+    PlonkType,
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec::Vec};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A byte string inserted as a lookup table of the circuit.
+pub struct ByteStringTable {
+    // the length of the string
+    len: usize,
+    // the position of the table among all the table elements
+    offset: usize,
+}
+
+impl ByteStringTable {
+    /// The length of the string.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the string is empty, which is never the case.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Insert a lookup table holding the string `bytes`, whose variables are
+    /// not constrained, i.e. they may be any field elements. The table has
+    /// `bytes.len()` elements.
+    /// Return error if `bytes` is empty, if a variable is invalid, or if the
+    /// circuit does not support lookup.
+    pub fn create_byte_string_table(
+        &mut self,
+        bytes: &[Variable],
+    ) -> Result<ByteStringTable, PlonkError> {
+        self.check_plonk_type(PlonkType::UltraPlonk)?;
+        if bytes.is_empty() {
+            return Err(ParameterError("empty string".to_string()).into());
+        }
+        let zero = self.zero();
+        let table_vars: Vec<(Variable, Variable)> = bytes.iter().map(|&b| (b, zero)).collect();
+        let offset = self.num_table_elems();
+        self.create_table_and_lookup_variables(&[], &table_vars)?;
+        Ok(ByteStringTable {
+            len: bytes.len(),
+            offset,
+        })
+    }
+
+    /// Constrain `pattern` to occur in the string of `table` at position
+    /// `offset`, i.e. `pattern[t]` to be the `(offset + t)`-th byte of the
+    /// string. The offset is constrained to be at most
+    /// `table.len() - pattern.len()`.
+    /// Return error if the variables are invalid, if the pattern is empty or
+    /// longer than the string, or if the table is not inserted in the
+    /// circuit.
+    pub fn substring_gate(
+        &mut self,
+        table: &ByteStringTable,
+        pattern: &[Variable],
+        offset: Variable,
+    ) -> Result<(), PlonkError> {
+        if pattern.is_empty() || pattern.len() > table.len {
+            return Err(ParameterError(format!(
+                "a pattern has between 1 and {} bytes, got {}",
+                table.len,
+                pattern.len()
+            ))
+            .into());
+        }
+        if table.offset + table.len > self.num_table_elems() {
+            return Err(ParameterError("the string table is not inserted".to_string()).into());
+        }
+        self.check_vars_bound(pattern)?;
+        self.check_var_bound(offset)?;
+
+        // offset and max_offset - offset are in [0, 2^bits), i.e. offset is in
+        // [0, max_offset]
+        let max_offset = table.len - pattern.len();
+        let bits = (usize::BITS - max_offset.leading_zeros()) as usize;
+        if bits == 0 {
+            self.constant_gate(offset, F::zero())?;
+        } else {
+            self.range_gate(offset, bits)?;
+            let zero = self.zero();
+            let rev_offset = self.gen_quad_poly(
+                &[offset, zero, zero, zero],
+                &[-F::one(), F::zero(), F::zero(), F::zero()],
+                &[F::zero(), F::zero()],
+                F::from(max_offset as u64),
+            )?;
+            self.range_gate(rev_offset, bits)?;
+        }
+
+        // the key of the t-th byte is the position of the (offset + t)-th
+        // element of the table
+        let zero = self.zero();
+        let lookup_vars = pattern
+            .iter()
+            .enumerate()
+            .map(|(t, &byte)| {
+                let key = self.add_constant(offset, &F::from((table.offset + t) as u64))?;
+                Ok((key, byte, zero))
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        self.lookup_variables(&lookup_vars)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng, vec};
+
+    #[test]
+    fn test_substring() -> Result<(), PlonkError> {
+        test_substring_helper::<FqEd254>()?;
+        test_substring_helper::<FqEd377>()?;
+        test_substring_helper::<FqEd381>()?;
+        test_substring_helper::<Fq377>()
+    }
+
+    fn test_substring_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let mut bytes = vec![0u8; 40];
+        rng.fill_bytes(&mut bytes);
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let byte_vars = bytes
+            .iter()
+            .map(|&b| circuit.create_variable(F::from(b)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let table = circuit.create_byte_string_table(&byte_vars)?;
+        assert_eq!(table.len(), 40);
+        // another table whose elements must not be matched
+        let zero = circuit.zero();
+        let other = circuit.create_variable(F::from(bytes[0]))?;
+        circuit.create_table_and_lookup_variables(&[], &[(other, zero)])?;
+
+        // private and public offsets, up to the end of the string
+        let mut patterns = vec![];
+        for &(offset, len) in [(7usize, 5usize), (0, 40), (39, 1), (35, 5)].iter() {
+            let pattern = bytes[offset..offset + len]
+                .iter()
+                .map(|&b| circuit.create_variable(F::from(b)))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let offset_var = circuit.create_variable(F::from(offset as u64))?;
+            circuit.substring_gate(&table, &pattern, offset_var)?;
+            patterns.push((pattern, offset_var));
+        }
+        let pattern = vec![byte_vars[12], byte_vars[13]];
+        let offset_var = circuit.create_constant_variable(F::from(12u8))?;
+        circuit.substring_gate(&table, &pattern, offset_var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a wrong pattern byte or offset
+        for (pattern, offset_var) in patterns.iter() {
+            *circuit.witness_mut(pattern[0]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(pattern[0]) -= F::one();
+            *circuit.witness_mut(*offset_var) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(*offset_var) -= F::one();
+        }
+        // a pattern overflowing the string into the other table
+        let mut bad_circuit = circuit.clone();
+        let pattern = vec![bad_circuit.create_variable(F::from(bytes[0]))?];
+        let offset_var = bad_circuit.create_variable(F::from(40u8))?;
+        bad_circuit.substring_gate(&table, &pattern, offset_var)?;
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid inputs
+        assert!(circuit.create_byte_string_table(&[]).is_err());
+        assert!(circuit.substring_gate(&table, &[], offset_var).is_err());
+        assert!(circuit
+            .substring_gate(&table, &vec![zero; 41], zero)
+            .is_err());
+        let mut other_circuit = PlonkCircuit::<F>::new_ultra_plonk(8);
+        let zero = other_circuit.zero();
+        assert!(other_circuit.substring_gate(&table, &[zero], zero).is_err());
+        assert!(PlonkCircuit::<F>::new_turbo_plonk()
+            .create_byte_string_table(&[zero])
+            .is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,421 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! 256-bit unsigned integers with the semantics of EVM words, e.g. for
+//! circuits manipulating hash outputs as integers.
+//!
+//! An integer is given by four little-endian `U64Var` limbs of `uint`, so that
+//! the circuit has to support lookup with a range bit length dividing 32. The
+//! additions, subtractions and multiplications wrap around modulo `2^256`,
+//! and a division by zero yields a zero quotient and a zero remainder.
+
+use super::uint::{U64Var, UintVar};
+use crate::{
+    circuit::{BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, vec, vec::Vec};
+use num_bigint::BigUint;
+
+/// Number of limbs of a 256-bit integer.
+const NUM_LIMBS: usize = 4;
+
+/// Number of bits of the range-checked carries of the multiplications.
+const CARRY_BITS: usize = 96;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Variable of a 256-bit unsigned integer, given by its little-endian 64-bit
+/// limbs.
+pub struct U256Var {
+    limbs: [U64Var; NUM_LIMBS],
+}
+
+impl U256Var {
+    /// The little-endian 64-bit limbs of the integer.
+    pub fn limbs(&self) -> [U64Var; NUM_LIMBS] {
+        self.limbs
+    }
+
+    fn limb_vars(&self) -> [Variable; NUM_LIMBS] {
+        [
+            self.limbs[0].var(),
+            self.limbs[1].var(),
+            self.limbs[2].var(),
+            self.limbs[3].var(),
+        ]
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a variable for a 256-bit unsigned integer.
+    /// Return error if `value` does not fit in 256 bits, or if the circuit
+    /// does not support the range checks of the limbs.
+    pub fn create_u256_variable(&mut self, value: &BigUint) -> Result<U256Var, PlonkError> {
+        let digits = u256_digits(value)?;
+        let mut limbs = [U64Var(self.zero()); NUM_LIMBS];
+        for (limb, &digit) in limbs.iter_mut().zip(digits.iter()) {
+            *limb = self.create_u64_variable(digit)?;
+        }
+        Ok(U256Var { limbs })
+    }
+
+    /// Create a constant variable for a 256-bit unsigned integer.
+    /// Return error if `value` does not fit in 256 bits.
+    pub fn create_constant_u256_variable(
+        &mut self,
+        value: &BigUint,
+    ) -> Result<U256Var, PlonkError> {
+        let digits = u256_digits(value)?;
+        let mut limbs = [U64Var(self.zero()); NUM_LIMBS];
+        for (limb, &digit) in limbs.iter_mut().zip(digits.iter()) {
+            *limb = self.create_constant_u64_variable(digit)?;
+        }
+        Ok(U256Var { limbs })
+    }
+
+    /// Obtain a 256-bit integer from its little-endian 64-bit limbs.
+    pub fn u256_from_limbs(&self, limbs: [U64Var; NUM_LIMBS]) -> U256Var {
+        U256Var { limbs }
+    }
+
+    /// The value of a 256-bit integer.
+    /// Return error if the variables are invalid.
+    pub fn u256_witness(&self, a: &U256Var) -> Result<BigUint, PlonkError> {
+        let mut value = BigUint::from(0u8);
+        for &limb in a.limbs.iter().rev() {
+            value = (value << 64) + self.uint_witness(limb)?;
+        }
+        Ok(value)
+    }
+
+    /// Obtain `a + b mod 2^256`.
+    /// Return error if the variables are invalid.
+    pub fn u256_add(&mut self, a: &U256Var, b: &U256Var) -> Result<U256Var, PlonkError> {
+        let (res, _) = self.u256_add_with_carry(&a.limb_vars(), &b.limb_vars(), false)?;
+        Ok(res)
+    }
+
+    /// Obtain `a - b mod 2^256`.
+    /// Return error if the variables are invalid.
+    pub fn u256_sub(&mut self, a: &U256Var, b: &U256Var) -> Result<U256Var, PlonkError> {
+        let (res, _) = self.u256_add_with_carry(&a.limb_vars(), &b.limb_vars(), true)?;
+        Ok(res)
+    }
+
+    /// Obtain `a * b mod 2^256`.
+    /// Return error if the variables are invalid, or if the native field is
+    /// too small.
+    pub fn u256_mul(&mut self, a: &U256Var, b: &U256Var) -> Result<U256Var, PlonkError> {
+        let prod = self.u256_witness(a)? * self.u256_witness(b)?;
+        let low = self.create_u256_variable(&(&prod % (BigUint::from(1u8) << 256)))?;
+        let high = self.create_u256_variable(&(prod >> 256))?;
+        let zero = self.zero();
+        self.u256_enforce_mul_add(
+            &a.limb_vars(),
+            &b.limb_vars(),
+            &[zero; NUM_LIMBS],
+            &low.limb_vars(),
+            &high.limb_vars(),
+        )?;
+        Ok(low)
+    }
+
+    /// Obtain the quotient and the remainder of the division of `a` by `m`,
+    /// which are both zero if `m` is zero.
+    /// Return error if the variables are invalid, or if the native field is
+    /// too small.
+    pub fn u256_div_rem(
+        &mut self,
+        a: &U256Var,
+        m: &U256Var,
+    ) -> Result<(U256Var, U256Var), PlonkError> {
+        let m_vars = m.limb_vars();
+        // m is zero iff the sum of its limbs is, and a division by zero is
+        // replaced by a division by one
+        let m_sum = self.sum(&m_vars)?;
+        let m_is_zero = self.is_zero(m_sum)?;
+        let m_eff = [
+            self.add(m_vars[0], m_is_zero.into())?,
+            m_vars[1],
+            m_vars[2],
+            m_vars[3],
+        ];
+
+        let a_int = self.u256_witness(a)?;
+        let m_int = self.u256_witness(m)?;
+        let (q_int, r_int) = if m_int == BigUint::from(0u8) {
+            (a_int, BigUint::from(0u8))
+        } else {
+            (&a_int / &m_int, &a_int % &m_int)
+        };
+        let q = self.create_u256_variable(&q_int)?;
+        let r = self.create_u256_variable(&r_int)?;
+
+        // q * m + r = a and r < m
+        let zero = self.zero();
+        self.u256_enforce_mul_add(
+            &q.limb_vars(),
+            &m_eff,
+            &r.limb_vars(),
+            &a.limb_vars(),
+            &[zero; NUM_LIMBS],
+        )?;
+        let (_, borrow) = self.u256_add_with_carry(&r.limb_vars(), &m_eff, true)?;
+        self.enforce_true(borrow.into())?;
+
+        let mut q_limbs = q.limbs;
+        for limb in q_limbs.iter_mut() {
+            *limb = U64Var(self.conditional_select(m_is_zero, limb.var(), zero)?);
+        }
+        Ok((U256Var { limbs: q_limbs }, r))
+    }
+
+    /// Obtain a bool variable representing whether `a < b`.
+    /// Return error if the variables are invalid.
+    pub fn u256_is_lt(&mut self, a: &U256Var, b: &U256Var) -> Result<BoolVar, PlonkError> {
+        // a < b iff the subtraction a - b borrows
+        let (_, borrow) = self.u256_add_with_carry(&a.limb_vars(), &b.limb_vars(), true)?;
+        Ok(borrow)
+    }
+
+    /// Obtain a bool variable representing whether `a <= b`.
+    /// Return error if the variables are invalid.
+    pub fn u256_is_leq(&mut self, a: &U256Var, b: &U256Var) -> Result<BoolVar, PlonkError> {
+        let b_lt_a = self.u256_is_lt(b, a)?;
+        self.logic_neg(b_lt_a)
+    }
+
+    /// Constrain two 256-bit integers to be equal.
+    /// Return error if the variables are invalid.
+    pub fn u256_equal_gate(&mut self, a: &U256Var, b: &U256Var) -> Result<(), PlonkError> {
+        for (&a_i, &b_i) in a.limb_vars().iter().zip(b.limb_vars().iter()) {
+            self.equal_gate(a_i, b_i)?;
+        }
+        Ok(())
+    }
+
+    // Obtain a + b mod 2^256 and the final carry, or a - b mod 2^256 and the
+    // final borrow if `sub` is true.
+    fn u256_add_with_carry(
+        &mut self,
+        a: &[Variable; NUM_LIMBS],
+        b: &[Variable; NUM_LIMBS],
+        sub: bool,
+    ) -> Result<(U256Var, BoolVar), PlonkError> {
+        let sign = if sub { -F::one() } else { F::one() };
+        let two_power_64 = F::from(u64::MAX) + F::one();
+        let mut limbs = [U64Var(self.zero()); NUM_LIMBS];
+        let mut carry = self.false_var();
+        let mut carry_val = false;
+        for i in 0..NUM_LIMBS {
+            let a_val = self.uint_witness(U64Var(a[i]))? as u128;
+            let b_val = self.uint_witness(U64Var(b[i]))? as u128 + carry_val as u128;
+            let (res_val, next_carry_val) = if sub {
+                (a_val.wrapping_sub(b_val) as u64, a_val < b_val)
+            } else {
+                ((a_val + b_val) as u64, (a_val + b_val) >> 64 == 1)
+            };
+            limbs[i] = self.create_u64_variable(res_val)?;
+            let next_carry = self.create_bool_variable(next_carry_val)?;
+            // a_i + sign * (b_i + c_{i-1} - 2^64 * c_i) = res_i
+            let wires = [a[i], b[i], carry.into(), next_carry.into(), limbs[i].var()];
+            let coeffs = [F::one(), sign, sign, -sign * two_power_64];
+            self.lc_gate(&wires, &coeffs)?;
+            carry = next_carry;
+            carry_val = next_carry_val;
+        }
+        Ok((U256Var { limbs }, carry))
+    }
+
+    // Enforce x * y + z = low + 2^256 * high over the integers, with the
+    // products computed column by column and range-checked carries.
+    fn u256_enforce_mul_add(
+        &mut self,
+        x: &[Variable; NUM_LIMBS],
+        y: &[Variable; NUM_LIMBS],
+        z: &[Variable; NUM_LIMBS],
+        low: &[Variable; NUM_LIMBS],
+        high: &[Variable; NUM_LIMBS],
+    ) -> Result<(), PlonkError> {
+        // the columns and carries have at most 132 and 68 bits, and must not
+        // wrap around the native modulus
+        if F::size_in_bits() <= 64 + CARRY_BITS + 2 {
+            return Err(ParameterError(format!(
+                "native field of {} bits is too small for 256-bit multiplications",
+                F::size_in_bits()
+            ))
+            .into());
+        }
+        let zero = self.zero();
+        let res: Vec<Variable> = low.iter().chain(high.iter()).copied().collect();
+        let two_power_64 = F::from(u64::MAX) + F::one();
+        let mut carry = zero;
+        let mut carry_int = BigUint::from(0u8);
+        for t in 0..2 * NUM_LIMBS - 1 {
+            let mut terms = vec![];
+            for i in 0..NUM_LIMBS {
+                if t >= i && t - i < NUM_LIMBS {
+                    terms.push((x[i], y[t - i]));
+                }
+            }
+            if terms.len() % 2 == 1 {
+                terms.push((zero, zero));
+            }
+            let partials = terms
+                .chunks(2)
+                .map(|pair| {
+                    self.mul_add(
+                        &[pair[0].0, pair[0].1, pair[1].0, pair[1].1],
+                        &[F::one(), F::one()],
+                    )
+                })
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let column = self.sum(&partials)?;
+            let z_t = if t < NUM_LIMBS { z[t] } else { zero };
+
+            // column_t + z_t + c_{t-1} = res_t + 2^64 * c_t, where the last
+            // carry is the most significant limb of the result
+            let value: BigUint = self.witness(column)?.into_repr().into();
+            let z_t_int: BigUint = self.witness(z_t)?.into_repr().into();
+            let res_t_int: BigUint = self.witness(res[t])?.into_repr().into();
+            let total = value + z_t_int + &carry_int;
+            if total < res_t_int {
+                return Err(ParameterError(
+                    "the relation of the 256-bit multiplication does not hold".into(),
+                )
+                .into());
+            }
+            carry_int = (total - res_t_int) >> 64;
+            let next_carry = if t + 1 < 2 * NUM_LIMBS - 1 {
+                let var = self.create_variable(F::from(carry_int.clone()))?;
+                self.range_gate_with_lookup(var, CARRY_BITS)?;
+                var
+            } else {
+                res[2 * NUM_LIMBS - 1]
+            };
+            let wires = [column, z_t, carry, next_carry, res[t]];
+            self.lc_gate(&wires, &[F::one(), F::one(), F::one(), -two_power_64])?;
+            carry = next_carry;
+        }
+        Ok(())
+    }
+}
+
+// The little-endian 64-bit digits of `value`, or an error if it does not fit
+// in 256 bits.
+fn u256_digits(value: &BigUint) -> Result<Vec<u64>, PlonkError> {
+    if value.bits() > 256 {
+        return Err(ParameterError(format!(
+            "value of {} bits does not fit in 256 bits",
+            value.bits()
+        ))
+        .into());
+    }
+    let mut digits = value.to_u64_digits();
+    digits.resize(NUM_LIMBS, 0);
+    Ok(digits)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng};
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 16;
+
+    fn random_u256<R: RngCore>(rng: &mut R) -> BigUint {
+        let mut bytes = [0u8; 32];
+        rng.fill_bytes(&mut bytes);
+        BigUint::from_bytes_le(&bytes)
+    }
+
+    #[test]
+    fn test_u256() -> Result<(), PlonkError> {
+        test_u256_helper::<FqEd254>()?;
+        test_u256_helper::<FqEd377>()?;
+        test_u256_helper::<FqEd381>()?;
+        test_u256_helper::<Fq377>()
+    }
+
+    fn test_u256_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let modulus = BigUint::from(1u8) << 256;
+        let max = &modulus - 1u8;
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        for _ in 0..4 {
+            let x = random_u256(&mut rng);
+            let y = random_u256(&mut rng) >> 128;
+            let a = circuit.create_u256_variable(&x)?;
+            let b = circuit.create_u256_variable(&y)?;
+            assert_eq!(circuit.u256_witness(&a)?, x);
+
+            let res = circuit.u256_add(&a, &b)?;
+            assert_eq!(circuit.u256_witness(&res)?, (&x + &y) % &modulus);
+            let res = circuit.u256_sub(&b, &a)?;
+            assert_eq!(circuit.u256_witness(&res)?, (&modulus + &y - &x) % &modulus);
+            let res = circuit.u256_mul(&a, &b)?;
+            assert_eq!(circuit.u256_witness(&res)?, (&x * &y) % &modulus);
+            let (q, r) = circuit.u256_div_rem(&a, &b)?;
+            assert_eq!(circuit.u256_witness(&q)?, &x / &y);
+            assert_eq!(circuit.u256_witness(&r)?, &x % &y);
+            let lt = circuit.u256_is_lt(&a, &b)?;
+            assert_eq!(circuit.witness(lt.into())?, F::from(x < y));
+            let leq = circuit.u256_is_leq(&b, &a)?;
+            assert_eq!(circuit.witness(leq.into())?, F::from(y <= x));
+        }
+
+        // wrapping and edge cases
+        let a = circuit.create_u256_variable(&max)?;
+        let one = circuit.create_constant_u256_variable(&BigUint::from(1u8))?;
+        let zero = circuit.create_constant_u256_variable(&BigUint::from(0u8))?;
+        let res = circuit.u256_add(&a, &one)?;
+        assert_eq!(circuit.u256_witness(&res)?, BigUint::from(0u8));
+        let res = circuit.u256_sub(&zero, &one)?;
+        assert_eq!(circuit.u256_witness(&res)?, max);
+        let res = circuit.u256_mul(&a, &a)?;
+        assert_eq!(circuit.u256_witness(&res)?, BigUint::from(1u8));
+        let (q, r) = circuit.u256_div_rem(&a, &zero)?;
+        assert_eq!(circuit.u256_witness(&q)?, BigUint::from(0u8));
+        assert_eq!(circuit.u256_witness(&r)?, BigUint::from(0u8));
+        let (q, r) = circuit.u256_div_rem(&a, &a)?;
+        assert_eq!(circuit.u256_witness(&q)?, BigUint::from(1u8));
+        assert_eq!(circuit.u256_witness(&r)?, BigUint::from(0u8));
+        let leq = circuit.u256_is_leq(&a, &a)?;
+        assert_eq!(circuit.witness(leq.into())?, F::one());
+        let lt = circuit.u256_is_lt(&a, &a)?;
+        assert_eq!(circuit.witness(lt.into())?, F::zero());
+        circuit.u256_equal_gate(&res, &zero)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong results
+        let mut bad_circuit = circuit.clone();
+        *bad_circuit.witness_mut(q.limbs()[0].var()) += F::one();
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(lt) = F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // a remainder which is not reduced
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let a = circuit.create_u256_variable(&BigUint::from(10u8))?;
+        let m = circuit.create_u256_variable(&BigUint::from(3u8))?;
+        let (q, r) = circuit.u256_div_rem(&a, &m)?;
+        *circuit.witness_mut(q.limbs()[0].var()) -= F::one();
+        *circuit.witness_mut(r.limbs()[0].var()) += F::from(3u8);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // invalid values
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        assert!(circuit.create_u256_variable(&modulus).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        assert!(circuit.create_u256_variable(&max).is_err());
+        Ok(())
+    }
+}
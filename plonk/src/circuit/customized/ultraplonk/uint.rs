@@ -0,0 +1,622 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Unsigned integer variables of 32 and 64 bits with wrapping arithmetic, e.g.
+//! for hash functions or virtual machines.
+//!
+//! An integer is a single variable whose value is range checked with the
+//! lookup range table, so that the range bit length of the circuit has to
+//! divide 32. The arithmetic operations compute their result modulo `2^BITS`
+//! as the `wrapping_*` operations of Rust, with an explicit carry, borrow or
+//! high part which is range checked as well. The bitwise operations split
+//! their operands into chunks which are looked up in the bitwise logic table
+//! of the circuit.
+
+use crate::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::{PlonkError, SnarkError::ParameterError},
+};
+use ark_ff::PrimeField;
+use ark_std::{format, string::ToString, vec, vec::Vec};
+
+/// Default bit length of the operands of the bitwise logic table of the
+/// circuit, see `PlonkCircuit::create_logic_table`.
+pub const LOGIC_TABLE_BIT_LEN: usize = 8;
+
+mod sealed {
+    use crate::circuit::Variable;
+
+    pub trait Sealed {
+        fn from_var(var: Variable) -> Self;
+    }
+}
+
+/// Variable of an unsigned integer of `BITS` bits.
+pub trait UintVar: sealed::Sealed + Copy {
+    /// Number of bits of the integer.
+    const BITS: usize;
+
+    /// The variable of the value of the integer.
+    fn var(&self) -> Variable;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Variable of a 32-bit unsigned integer.
+pub struct U32Var(pub(crate) Variable);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// Variable of a 64-bit unsigned integer.
+pub struct U64Var(pub(crate) Variable);
+
+impl sealed::Sealed for U32Var {
+    fn from_var(var: Variable) -> Self {
+        Self(var)
+    }
+}
+
+impl UintVar for U32Var {
+    const BITS: usize = 32;
+
+    fn var(&self) -> Variable {
+        self.0
+    }
+}
+
+impl sealed::Sealed for U64Var {
+    fn from_var(var: Variable) -> Self {
+        Self(var)
+    }
+}
+
+impl UintVar for U64Var {
+    const BITS: usize = 64;
+
+    fn var(&self) -> Variable {
+        self.0
+    }
+}
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Create a variable for a 32-bit unsigned integer.
+    /// Return error if the circuit does not support lookup, or if its range
+    /// bit length does not divide 32.
+    pub fn create_u32_variable(&mut self, value: u32) -> Result<U32Var, PlonkError> {
+        self.create_uint_variable(value as u64)
+    }
+
+    /// Create a variable for a 64-bit unsigned integer.
+    /// Return error if the circuit does not support lookup, or if its range
+    /// bit length does not divide 32.
+    pub fn create_u64_variable(&mut self, value: u64) -> Result<U64Var, PlonkError> {
+        self.create_uint_variable(value)
+    }
+
+    /// Create a constant variable for a 32-bit unsigned integer.
+    /// Return error if the circuit does not support lookup.
+    pub fn create_constant_u32_variable(&mut self, value: u32) -> Result<U32Var, PlonkError> {
+        Ok(U32Var(self.create_constant_variable(F::from(value))?))
+    }
+
+    /// Create a constant variable for a 64-bit unsigned integer.
+    /// Return error if the circuit does not support lookup.
+    pub fn create_constant_u64_variable(&mut self, value: u64) -> Result<U64Var, PlonkError> {
+        Ok(U64Var(self.create_constant_variable(F::from(value))?))
+    }
+
+    /// Obtain an integer variable from a variable whose value is range checked
+    /// to `U::BITS` bits by this function.
+    /// Return error if the value is too large, or if the circuit does not
+    /// support the range checks.
+    pub fn uint_from_variable<U: UintVar>(&mut self, var: Variable) -> Result<U, PlonkError> {
+        self.uint_bounded_range_gate(var, U::BITS, U::BITS)?;
+        Ok(U::from_var(var))
+    }
+
+    /// The value of an integer variable.
+    /// Return error if the variable is invalid.
+    pub fn uint_witness<U: UintVar>(&self, a: U) -> Result<u64, PlonkError> {
+        Ok(self.witness(a.var())?.into_repr().as_ref()[0])
+    }
+
+    /// Obtain `a + b mod 2^BITS`.
+    /// Return error if the variables are invalid.
+    pub fn uint_add<U: UintVar>(&mut self, a: U, b: U) -> Result<U, PlonkError> {
+        let sum = self.uint_witness(a)? as u128 + self.uint_witness(b)? as u128;
+        let carry = self.create_bool_variable(sum >> U::BITS == 1)?;
+        let res = self.create_uint_variable::<U>(sum as u64 & uint_mask::<U>())?;
+        // a + b - 2^BITS * carry = res
+        let wires = [a.var(), b.var(), carry.into(), self.zero(), res.var()];
+        let coeffs = [F::one(), F::one(), -two_power::<F>(U::BITS), F::zero()];
+        self.lc_gate(&wires, &coeffs)?;
+        Ok(res)
+    }
+
+    /// Obtain `terms[0] + ... + terms[n-1] + constant mod 2^BITS`, with a
+    /// single range checked carry for all the additions.
+    /// Return error if `terms` is empty or if the variables are invalid.
+    pub fn uint_sum<U: UintVar>(&mut self, terms: &[U], constant: u64) -> Result<U, PlonkError> {
+        let (res, _) = self.uint_sum_with_carry(terms, constant)?;
+        Ok(res)
+    }
+
+    // `uint_sum` along with the carry, which is range checked to the bit length
+    // of the largest possible carry.
+    pub(crate) fn uint_sum_with_carry<U: UintVar>(
+        &mut self,
+        terms: &[U],
+        constant: u64,
+    ) -> Result<(U, Variable), PlonkError> {
+        if terms.is_empty() {
+            return Err(ParameterError("cannot sum an empty slice of integers".to_string()).into());
+        }
+        if terms.len() == 1 && constant == 0 {
+            return Ok((terms[0], self.zero()));
+        }
+        let mut sum = constant as u128;
+        for &term in terms.iter() {
+            sum += self.uint_witness(term)? as u128;
+        }
+        // the carry is at most the one of the largest possible sum
+        let max_sum = terms.len() as u128 * uint_mask::<U>() as u128 + constant as u128;
+        let carry_bit_len = (128 - (max_sum >> U::BITS).leading_zeros() as usize).max(1);
+        let carry = self.create_variable(F::from((sum >> U::BITS) as u64))?;
+        self.enforce_in_range(carry, carry_bit_len)?;
+        let res = self.create_uint_variable::<U>(sum as u64 & uint_mask::<U>())?;
+
+        // at most 3 terms are added along with the carry in the last gate
+        let mut vars: Vec<Variable> = terms.iter().map(|term| term.var()).collect();
+        if vars.len() > 3 {
+            let head = self.sum(&vars[..vars.len() - 2])?;
+            vars = vec![head, vars[vars.len() - 2], vars[vars.len() - 1]];
+        }
+        vars.resize(3, self.zero());
+        // terms + constant - 2^BITS * carry = res
+        let wires = [vars[0], vars[1], vars[2], carry, res.var()];
+        let q_lc = [F::one(), F::one(), F::one(), -two_power::<F>(U::BITS)];
+        self.quad_poly_gate(&wires, &q_lc, &[F::zero(); 2], F::one(), F::from(constant))?;
+        Ok((res, carry))
+    }
+
+    /// Obtain an integer variable from its little-endian bytes, each of which
+    /// is range checked to be in `[0, 256)` by this function.
+    /// Return error if there are not `U::BITS / 8` bytes, or if the variables
+    /// are invalid.
+    pub fn uint_from_bytes_le<U: UintVar>(&mut self, bytes: &[Variable]) -> Result<U, PlonkError> {
+        if bytes.len() != U::BITS / 8 {
+            return Err(ParameterError(format!(
+                "a {}-bit integer has {} bytes, got {}",
+                U::BITS,
+                U::BITS / 8,
+                bytes.len()
+            ))
+            .into());
+        }
+        let range_bit_len = self.resolve_range_bit_len()?;
+        let byte_range = (8 + range_bit_len - 1) / range_bit_len * range_bit_len;
+        let mut value = F::zero();
+        for &byte in bytes.iter().rev() {
+            self.uint_bounded_range_gate(byte, byte_range, 8)?;
+            value = value * F::from(256u32) + self.witness(byte)?;
+        }
+        let var = self.create_variable(value)?;
+        self.decompose_vars_gate(bytes.to_vec(), var, F::from(256u32))?;
+        Ok(U::from_var(var))
+    }
+
+    /// The little-endian bytes of an integer variable, which are range checked
+    /// to be in `[0, 256)`.
+    /// Return error if the variable is invalid.
+    pub fn uint_to_bytes_le<U: UintVar>(&mut self, a: U) -> Result<Vec<Variable>, PlonkError> {
+        self.uint_chunks(a, 8)
+    }
+
+    /// Obtain `a - b mod 2^BITS`.
+    /// Return error if the variables are invalid.
+    pub fn uint_sub<U: UintVar>(&mut self, a: U, b: U) -> Result<U, PlonkError> {
+        let a_val = self.uint_witness(a)?;
+        let b_val = self.uint_witness(b)?;
+        let borrow = self.create_bool_variable(a_val < b_val)?;
+        let res = self.create_uint_variable::<U>(a_val.wrapping_sub(b_val) & uint_mask::<U>())?;
+        // a - b + 2^BITS * borrow = res
+        let wires = [a.var(), b.var(), borrow.into(), self.zero(), res.var()];
+        let coeffs = [F::one(), -F::one(), two_power::<F>(U::BITS), F::zero()];
+        self.lc_gate(&wires, &coeffs)?;
+        Ok(res)
+    }
+
+    /// Obtain `a * b mod 2^BITS`.
+    /// Return error if the variables are invalid.
+    pub fn uint_mul<U: UintVar>(&mut self, a: U, b: U) -> Result<U, PlonkError> {
+        let prod = self.uint_witness(a)? as u128 * self.uint_witness(b)? as u128;
+        let high = self.create_uint_variable::<U>((prod >> U::BITS) as u64)?;
+        let res = self.create_uint_variable::<U>(prod as u64 & uint_mask::<U>())?;
+        // a * b - 2^BITS * high - res = 0
+        let wires = [a.var(), b.var(), high.var(), res.var(), self.zero()];
+        let q_lc = [F::zero(), F::zero(), -two_power::<F>(U::BITS), -F::one()];
+        self.quad_poly_gate(&wires, &q_lc, &[F::one(), F::zero()], F::zero(), F::zero())?;
+        Ok(res)
+    }
+
+    /// Obtain the left rotation of `a` by `n` bits.
+    /// Return error if the variable is invalid.
+    pub fn uint_rotl<U: UintVar>(&mut self, a: U, n: usize) -> Result<U, PlonkError> {
+        let n = n % U::BITS;
+        if n == 0 {
+            return Ok(a);
+        }
+        // a = high * 2^{BITS - n} + low, and the result is low * 2^n + high
+        let (high, low) = self.uint_split(a, U::BITS - n)?;
+        let res = self.lc(
+            &[low, high, self.zero(), self.zero()],
+            &[two_power::<F>(n), F::one(), F::zero(), F::zero()],
+        )?;
+        Ok(U::from_var(res))
+    }
+
+    /// Obtain the logical right shift of `a` by `n` bits.
+    /// Return error if the variable is invalid or if `n >= BITS`.
+    pub fn uint_shr<U: UintVar>(&mut self, a: U, n: usize) -> Result<U, PlonkError> {
+        if n >= U::BITS {
+            return Err(ParameterError(format!(
+                "cannot shift a {}-bit integer by {} bits",
+                U::BITS,
+                n
+            ))
+            .into());
+        }
+        if n == 0 {
+            return Ok(a);
+        }
+        let (high, _) = self.uint_split(a, n)?;
+        Ok(U::from_var(high))
+    }
+
+    /// Obtain the bitwise NOT of `a`, i.e. `2^BITS - 1 - a`.
+    /// Return error if the variable is invalid.
+    pub fn uint_not<U: UintVar>(&mut self, a: U) -> Result<U, PlonkError> {
+        let res = self.create_variable(F::from(!self.uint_witness(a)? & uint_mask::<U>()))?;
+        // 2^BITS - 1 - a = res
+        let wires = [a.var(), self.zero(), self.zero(), self.zero(), res];
+        let q_lc = [-F::one(), F::zero(), F::zero(), F::zero()];
+        let q_c = F::from(uint_mask::<U>());
+        self.quad_poly_gate(&wires, &q_lc, &[F::zero(); 2], F::one(), q_c)?;
+        Ok(U::from_var(res))
+    }
+
+    /// Obtain the bitwise AND of `a` and `b`, with lookups in the bitwise
+    /// logic table of the circuit.
+    /// Return error if the variables are invalid.
+    pub fn uint_and<U: UintVar>(&mut self, a: U, b: U) -> Result<U, PlonkError> {
+        let (and, _) = self.uint_logic(a, b)?;
+        Ok(U::from_var(and))
+    }
+
+    /// Obtain the bitwise OR of `a` and `b`, with lookups in the bitwise
+    /// logic table of the circuit.
+    /// Return error if the variables are invalid.
+    pub fn uint_or<U: UintVar>(&mut self, a: U, b: U) -> Result<U, PlonkError> {
+        // a | b = (a & b) + (a ^ b)
+        let (and, xor) = self.uint_logic(a, b)?;
+        Ok(U::from_var(self.add(and, xor)?))
+    }
+
+    /// Obtain the bitwise XOR of `a` and `b`, with lookups in the bitwise
+    /// logic table of the circuit.
+    /// Return error if the variables are invalid.
+    pub fn uint_xor<U: UintVar>(&mut self, a: U, b: U) -> Result<U, PlonkError> {
+        let (_, xor) = self.uint_logic(a, b)?;
+        Ok(U::from_var(xor))
+    }
+
+    /// Whether the integer variables are supported, i.e. whether the circuit
+    /// supports lookup and its range bit length, which is chosen if it is
+    /// automatic, divides 32.
+    pub(crate) fn support_uint(&mut self) -> bool {
+        self.support_lookup() && matches!(self.resolve_range_bit_len(), Ok(len) if 32 % len == 0)
+    }
+
+    fn create_uint_variable<U: UintVar>(&mut self, value: u64) -> Result<U, PlonkError> {
+        let var = self.create_variable(F::from(value))?;
+        self.uint_from_variable(var)
+    }
+
+    // Obtain (high, low) such that a = high * 2^k + low, where low < 2^k and
+    // high < 2^{BITS - k}, for 0 < k < BITS.
+    pub(crate) fn uint_split<U: UintVar>(
+        &mut self,
+        a: U,
+        k: usize,
+    ) -> Result<(Variable, Variable), PlonkError> {
+        let value = self.uint_witness(a)?;
+        let high = self.create_variable(F::from(value >> k))?;
+        let low = self.create_variable(F::from(value & ((1u64 << k) - 1)))?;
+        self.enforce_in_range(high, U::BITS - k)?;
+        self.enforce_in_range(low, k)?;
+        let wires = [high, low, self.zero(), self.zero(), a.var()];
+        let coeffs = [two_power::<F>(k), F::one(), F::zero(), F::zero()];
+        self.lc_gate(&wires, &coeffs)?;
+        Ok((high, low))
+    }
+
+    // Obtain (a & b, a ^ b), by splitting the operands into chunks of the bit
+    // length of the logic table, which are looked up in the table.
+    pub(crate) fn uint_logic<U: UintVar>(
+        &mut self,
+        a: U,
+        b: U,
+    ) -> Result<(Variable, Variable), PlonkError> {
+        let bit_len = self.logic_table_bit_len()?;
+        let a_chunks = self.uint_chunks(a, bit_len)?;
+        let b_chunks = self.uint_chunks(b, bit_len)?;
+        let pairs: Vec<(Variable, Variable)> =
+            a_chunks.into_iter().zip(b_chunks.into_iter()).collect();
+        let outputs = self.logic_lookup(&pairs)?;
+
+        // the chunks of the outputs are in range as they are table elements
+        let base = two_power::<F>(bit_len);
+        let mut res = [self.zero(); 2];
+        for (k, res_k) in res.iter_mut().enumerate() {
+            let chunks: Vec<Variable> = outputs
+                .iter()
+                .map(|&(and, xor)| if k == 0 { and } else { xor })
+                .collect();
+            let mut value = 0u64;
+            for &chunk in chunks.iter().rev() {
+                value = (value << bit_len) + self.witness(chunk)?.into_repr().as_ref()[0];
+            }
+            *res_k = self.create_variable(F::from(value))?;
+            self.decompose_vars_gate(chunks, *res_k, base)?;
+        }
+        Ok((res[0], res[1]))
+    }
+
+    // The little-endian chunks of `bit_len` bits of `a`, which are range
+    // checked.
+    fn uint_chunks<U: UintVar>(
+        &mut self,
+        a: U,
+        bit_len: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let range_bit_len = self.resolve_range_bit_len()?;
+        let chunk_range = (bit_len + range_bit_len - 1) / range_bit_len * range_bit_len;
+        let value = self.uint_witness(a)?;
+        let chunks = (0..U::BITS / bit_len)
+            .map(|i| self.create_variable(F::from((value >> (i * bit_len)) & ((1 << bit_len) - 1))))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        for &chunk in chunks.iter() {
+            self.uint_bounded_range_gate(chunk, chunk_range, bit_len)?;
+        }
+        self.decompose_vars_gate(chunks.clone(), a.var(), two_power::<F>(bit_len))?;
+        Ok(chunks)
+    }
+
+    // Enforce a < 2^bits for bits <= bit_len, with range checks of bit_len
+    // bits: a < 2^bits iff a < 2^bit_len and a * 2^{bit_len - bits} <
+    // 2^bit_len, as the latter does not wrap around the native modulus.
+    fn uint_bounded_range_gate(
+        &mut self,
+        a: Variable,
+        bit_len: usize,
+        bits: usize,
+    ) -> Result<(), PlonkError> {
+        self.range_gate_with_lookup(a, bit_len)?;
+        if bits < bit_len {
+            let shifted = self.mul_constant(a, &two_power::<F>(bit_len - bits))?;
+            self.range_gate_with_lookup(shifted, bit_len)?;
+        }
+        Ok(())
+    }
+}
+
+fn uint_mask<U: UintVar>() -> u64 {
+    u64::MAX >> (64 - U::BITS)
+}
+
+fn two_power<F: PrimeField>(n: usize) -> F {
+    F::from(2u8).pow(&[n as u64])
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::{RangeTableSize, MAX_AUTO_RANGE_BIT_LEN};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{rand::RngCore, test_rng, vec};
+
+    const RANGE_BIT_LEN_FOR_TEST: usize = 8;
+
+    #[test]
+    fn test_uint() -> Result<(), PlonkError> {
+        test_uint_helper::<FqEd254>()?;
+        test_uint_helper::<FqEd377>()?;
+        test_uint_helper::<FqEd381>()?;
+        test_uint_helper::<Fq377>()
+    }
+
+    fn test_uint_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        for _ in 0..4 {
+            let (x, y) = (rng.next_u32(), rng.next_u32());
+            let (a, b) = (
+                circuit.create_u32_variable(x)?,
+                circuit.create_u32_variable(y)?,
+            );
+            let res = circuit.uint_add(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, x.wrapping_add(y) as u64);
+            let res = circuit.uint_sub(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, x.wrapping_sub(y) as u64);
+            let res = circuit.uint_mul(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, x.wrapping_mul(y) as u64);
+            let res = circuit.uint_sum(&[a, b, a, b, a], 0x9e3779b9)?;
+            let expected = [x, y, x, y, x, 0x9e3779b9]
+                .iter()
+                .fold(0u32, |acc, v| acc.wrapping_add(*v));
+            assert_eq!(circuit.uint_witness(res)?, expected as u64);
+            let bytes = circuit.uint_to_bytes_le(a)?;
+            for (&byte, v) in bytes.iter().zip(x.to_le_bytes().iter()) {
+                assert_eq!(circuit.witness(byte)?, F::from(*v));
+            }
+            let res = circuit.uint_from_bytes_le::<U32Var>(&bytes)?;
+            assert_eq!(circuit.uint_witness(res)?, x as u64);
+            for &n in [0, 1, 7, 31, 32, 45].iter() {
+                let res = circuit.uint_rotl(a, n)?;
+                assert_eq!(circuit.uint_witness(res)?, x.rotate_left(n as u32) as u64);
+            }
+            for &n in [0, 1, 10, 31].iter() {
+                let res = circuit.uint_shr(a, n)?;
+                assert_eq!(circuit.uint_witness(res)?, (x >> n) as u64);
+            }
+
+            let (x, y) = (rng.next_u64(), rng.next_u64());
+            let (a, b) = (
+                circuit.create_u64_variable(x)?,
+                circuit.create_u64_variable(y)?,
+            );
+            let c = circuit.create_constant_u64_variable(u64::MAX)?;
+            let res = circuit.uint_add(a, c)?;
+            assert_eq!(circuit.uint_witness(res)?, x.wrapping_add(u64::MAX));
+            let res = circuit.uint_sub(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, x.wrapping_sub(y));
+            let res = circuit.uint_mul(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, x.wrapping_mul(y));
+            let res = circuit.uint_rotl(a, 19)?;
+            assert_eq!(circuit.uint_witness(res)?, x.rotate_left(19));
+            let res = circuit.uint_shr(a, 61)?;
+            assert_eq!(circuit.uint_witness(res)?, x >> 61);
+        }
+        let zero = circuit.create_constant_u32_variable(0)?;
+        let max = circuit.create_constant_u32_variable(u32::MAX)?;
+        let res = circuit.uint_sub(zero, max)?;
+        assert_eq!(circuit.uint_witness(res)?, 1);
+        let sum = circuit.uint_sum(&[max, max, max], u32::MAX as u64)?;
+        assert_eq!(circuit.uint_witness(sum)?, u32::MAX as u64 - 3);
+        let res = circuit.uint_add(max, max)?;
+        assert_eq!(circuit.uint_witness(res)?, u32::MAX as u64 - 1);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong results
+        let mut bad_circuit = circuit.clone();
+        *bad_circuit.witness_mut(res.var()) += F::one();
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut bad_circuit = circuit.clone();
+        *bad_circuit.witness_mut(sum.var()) += F::one();
+        assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
+        let a = circuit.create_u32_variable(0xdeadbeef)?;
+        let res = circuit.uint_shr(a, 4)?;
+        *circuit.witness_mut(res.var()) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // out of range values and invalid parameters
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let var = circuit.create_variable(F::from(u32::MAX as u64 + 1))?;
+        circuit.uint_from_variable::<U32Var>(var)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let bytes = [
+            circuit.create_variable(F::from(256u32))?,
+            circuit.zero(),
+            circuit.zero(),
+            circuit.zero(),
+        ];
+        circuit.uint_from_bytes_le::<U32Var>(&bytes)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RANGE_BIT_LEN_FOR_TEST);
+        let a = circuit.create_u32_variable(1)?;
+        assert!(circuit.uint_shr(a, 32).is_err());
+        assert!(circuit.uint_sum::<U32Var>(&[], 1).is_err());
+        assert!(circuit
+            .uint_from_bytes_le::<U64Var>(&[circuit.zero(); 4])
+            .is_err());
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        assert!(circuit.create_u32_variable(1).is_err());
+
+        // the range table size is chosen if it is automatic
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(RangeTableSize::Auto);
+        let a = circuit.create_u32_variable(0xdeadbeef)?;
+        let b = circuit.create_u32_variable(0x01234567)?;
+        let res = circuit.uint_xor(a, b)?;
+        assert_eq!(circuit.uint_witness(res)?, 0xdeadbeef ^ 0x01234567);
+        assert_eq!(circuit.range_bit_len()?, MAX_AUTO_RANGE_BIT_LEN);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn test_uint_logic() -> Result<(), PlonkError> {
+        test_uint_logic_helper::<FqEd254>(4, 16)?;
+        test_uint_logic_helper::<FqEd377>(2, 8)?;
+        test_uint_logic_helper::<FqEd381>(4, 8)?;
+        test_uint_logic_helper::<Fq377>(1, 4)?;
+        test_uint_logic_helper::<FqEd254>(LOGIC_TABLE_BIT_LEN, 8)
+    }
+
+    fn test_uint_logic_helper<F: PrimeField>(
+        bit_len: usize,
+        range_bit_len: usize,
+    ) -> Result<(), PlonkError> {
+        let mut rng = test_rng();
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(range_bit_len);
+        if bit_len != LOGIC_TABLE_BIT_LEN {
+            circuit.create_logic_table(bit_len)?;
+        }
+        let mut results = vec![];
+        for _ in 0..4 {
+            let (x, y) = (rng.next_u32(), rng.next_u32());
+            let (a, b) = (
+                circuit.create_u32_variable(x)?,
+                circuit.create_u32_variable(y)?,
+            );
+            let res = circuit.uint_and(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, (x & y) as u64);
+            let res = circuit.uint_or(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, (x | y) as u64);
+            let res = circuit.uint_xor(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, (x ^ y) as u64);
+            results.push(res.var());
+            let res = circuit.uint_not(a)?;
+            assert_eq!(circuit.uint_witness(res)?, !x as u64);
+            results.push(res.var());
+
+            let (x, y) = (rng.next_u64(), u64::MAX);
+            let (a, b) = (
+                circuit.create_u64_variable(x)?,
+                circuit.create_u64_variable(y)?,
+            );
+            let res = circuit.uint_and(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, x);
+            let res = circuit.uint_or(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, u64::MAX);
+            let res = circuit.uint_xor(a, b)?;
+            assert_eq!(circuit.uint_witness(res)?, !x);
+            results.push(res.var());
+            let res = circuit.uint_not(res)?;
+            assert_eq!(circuit.uint_witness(res)?, x);
+            results.push(res.var());
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // the table is inserted once
+        assert!(circuit.create_logic_table(bit_len).is_err());
+
+        // wrong results
+        for &res in results.iter() {
+            *circuit.witness_mut(res) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            *circuit.witness_mut(res) -= F::one();
+        }
+
+        // invalid tables
+        let mut circuit = PlonkCircuit::<F>::new_ultra_plonk(range_bit_len);
+        assert!(circuit.create_logic_table(3).is_err());
+        assert!(circuit.create_logic_table(16).is_err());
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        assert!(circuit.create_logic_table(bit_len).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,167 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! A uniform interface for the gadgets of Plonk circuits.
+//!
+//! A gadget is a value holding the parameters of a computation, e.g. the
+//! number of outputs of a sponge or the base point of a scalar
+//! multiplication, which synthesizes the constraints of the computation over
+//! input variables. Gadgets of this crate and of other libraries implementing
+//! [`CircuitGadget`] can be composed generically, and their cost in the
+//! circuit can be reported in the same way.
+
+use super::{Circuit, PlonkCircuit};
+use crate::errors::PlonkError;
+use ark_ff::PrimeField;
+
+/// The constraints added to a circuit by a gadget.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GadgetCost {
+    /// The number of gates.
+    pub num_gates: usize,
+    /// The number of variables.
+    pub num_vars: usize,
+}
+
+/// A gadget synthesizing its constraints in a Plonk circuit.
+pub trait CircuitGadget<F: PrimeField> {
+    /// The variables the gadget is applied to.
+    type Input;
+    /// The variables output by the gadget.
+    type Output;
+
+    /// The name of the gadget, e.g. for cost reports.
+    fn name(&self) -> &'static str;
+
+    /// Add the constraints of the gadget applied to `input` to `circuit`, and
+    /// return the output variables.
+    /// Return error if the input variables are invalid, or if the gadget is
+    /// not supported by the circuit.
+    fn synthesize(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        input: Self::Input,
+    ) -> Result<Self::Output, PlonkError>;
+
+    /// Synthesize the gadget as `synthesize` does, and also return the number
+    /// of gates and variables it added to `circuit`.
+    fn synthesize_with_cost(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        input: Self::Input,
+    ) -> Result<(Self::Output, GadgetCost), PlonkError> {
+        let (num_gates, num_vars) = (circuit.num_gates(), circuit.num_vars());
+        let output = self.synthesize(circuit, input)?;
+        let cost = GadgetCost {
+            num_gates: circuit.num_gates() - num_gates,
+            num_vars: circuit.num_vars() - num_vars,
+        };
+        Ok((output, cost))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::circuit::customized::{
+        ecc::{EccAddGadget, FixedBaseScalarMulGadget, Point, VariableBaseScalarMulGadget},
+        rescue::{RescueGadget, RescuePermutationGadget, RescueSpongeGadget},
+    };
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ec::{twisted_edwards_extended::GroupAffine, TEModelParameters as Parameters};
+    use ark_ed_on_bls12_377::{EdwardsParameters as Param377, Fq as FqEd377};
+    use ark_ed_on_bls12_381::{EdwardsParameters as Param381, Fq as FqEd381};
+    use ark_ed_on_bn254::{EdwardsParameters as Param254, Fq as FqEd254};
+    use ark_std::{test_rng, UniformRand};
+    use jf_rescue::{Permutation, RescueParameter, RescueVector};
+    use jf_utils::fr_to_fq;
+
+    #[test]
+    fn test_rescue_gadgets() -> Result<(), PlonkError> {
+        test_rescue_gadgets_helper::<FqEd254>()?;
+        test_rescue_gadgets_helper::<FqEd377>()?;
+        test_rescue_gadgets_helper::<FqEd381>()?;
+        test_rescue_gadgets_helper::<Fq377>()
+    }
+
+    fn test_rescue_gadgets_helper<F: RescueParameter>() -> Result<(), PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let state = RescueVector::from(&[F::from(1u32), F::from(2u32), F::from(3u32), F::zero()]);
+        let state_var = circuit.create_rescue_state_variable(&state)?;
+        let gadget = RescuePermutationGadget;
+        let (output, cost) = gadget.synthesize_with_cost(&mut circuit, state_var.clone())?;
+        let expected = Permutation::default().eval(&state).elems();
+        for (&var, &value) in output.array().iter().zip(expected.iter()) {
+            assert_eq!(circuit.witness(var)?, value);
+        }
+        // the cost is that of the direct call
+        let num_gates = circuit.num_gates();
+        circuit.rescue_permutation(state_var)?;
+        assert_eq!(cost.num_gates, circuit.num_gates() - num_gates);
+        assert!(cost.num_vars > 0);
+
+        let data_vars = (0..5u32)
+            .map(|i| circuit.create_variable(F::from(i)))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let sponge = RescueSpongeGadget::new(2, true);
+        assert_eq!(sponge.name(), "rescue_sponge");
+        let output = sponge.synthesize(&mut circuit, data_vars.clone())?;
+        let expected = circuit.rescue_sponge_with_padding(&data_vars, 2)?;
+        assert_eq!(output.len(), 2);
+        for (&a, &b) in output.iter().zip(expected.iter()) {
+            assert_eq!(circuit.witness(a)?, circuit.witness(b)?);
+        }
+        assert!(RescueSpongeGadget::new(1, false)
+            .synthesize(&mut circuit, data_vars)
+            .is_err());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        *circuit.witness_mut(output[0]) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ecc_gadgets() -> Result<(), PlonkError> {
+        test_ecc_gadgets_helper::<FqEd254, Param254>()?;
+        test_ecc_gadgets_helper::<FqEd377, Param377>()?;
+        test_ecc_gadgets_helper::<FqEd381, Param381>()
+    }
+
+    fn test_ecc_gadgets_helper<F, P>() -> Result<(), PlonkError>
+    where
+        F: PrimeField,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut rng = test_rng();
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let p = GroupAffine::<P>::rand(&mut rng);
+        let q = GroupAffine::<P>::rand(&mut rng);
+        let p_var = circuit.create_point_variable(Point::from(p))?;
+        let q_var = circuit.create_point_variable(Point::from(q))?;
+
+        let sum = EccAddGadget::<P>::new().synthesize(&mut circuit, (p_var, q_var))?;
+        assert_eq!(circuit.point_witness(&sum)?, Point::from(p + q));
+
+        let s = P::ScalarField::rand(&mut rng);
+        let scalar_var = circuit.create_variable(fr_to_fq::<F, P>(&s))?;
+        let mut product = p;
+        product *= s;
+        let expected = Point::from(product);
+        let (var_mul, cost) = VariableBaseScalarMulGadget::<P>::new()
+            .synthesize_with_cost(&mut circuit, (scalar_var, p_var))?;
+        assert_eq!(circuit.point_witness(&var_mul)?, expected);
+        assert!(cost.num_gates > 0);
+        let fixed_mul = FixedBaseScalarMulGadget::new(p).synthesize(&mut circuit, scalar_var)?;
+        assert_eq!(circuit.point_witness(&fixed_mul)?, expected);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        *circuit.witness_mut(scalar_var) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        Ok(())
+    }
+}
@@ -12,8 +12,10 @@ use downcast_rs::Downcast;
 
 use crate::constants::{GATE_WIDTH, N_MUL_SELECTORS};
 
-/// Describes a gate with getter for all selectors configuration
-pub trait Gate<F: Field>: Downcast + GateClone<F> {
+/// Describes a gate with getter for all selectors configuration. Gates are
+/// `Send` and `Sync` so that circuits can be moved between threads, e.g. by
+/// `PlonkCircuit::par_synthesize`.
+pub trait Gate<F: Field>: Downcast + GateClone<F> + Send + Sync {
     /// Get the name of a gate.
     fn name(&self) -> &'static str;
     /// Selectors for linear combination.
@@ -279,3 +281,75 @@ impl<F: Field> Gate<F> for FifthRootGate {
         F::one()
     }
 }
+
+/// A gate whose constant input wires are folded into its selectors by
+/// `PlonkCircuit::optimize`.
+#[derive(Debug, Clone)]
+pub struct FoldedGate<F: Field> {
+    pub(crate) q_lc: [F; GATE_WIDTH],
+    pub(crate) q_mul: [F; N_MUL_SELECTORS],
+    pub(crate) q_hash: [F; GATE_WIDTH],
+    pub(crate) q_o: F,
+    pub(crate) q_c: F,
+}
+
+impl<F: Field> Gate<F> for FoldedGate<F> {
+    fn name(&self) -> &'static str {
+        "Folded Gate"
+    }
+    fn q_lc(&self) -> [F; GATE_WIDTH] {
+        self.q_lc
+    }
+    fn q_mul(&self) -> [F; N_MUL_SELECTORS] {
+        self.q_mul
+    }
+    fn q_hash(&self) -> [F; GATE_WIDTH] {
+        self.q_hash
+    }
+    fn q_o(&self) -> F {
+        self.q_o
+    }
+    fn q_c(&self) -> F {
+        self.q_c
+    }
+}
+
+/// A gate of a circuit reloaded from its compiled form, c.f.
+/// `CompiledCircuit`, which has the selectors of the compiled gate.
+#[derive(Debug, Clone)]
+pub struct CompiledGate<F: Field> {
+    pub(crate) q_lc: [F; GATE_WIDTH],
+    pub(crate) q_mul: [F; N_MUL_SELECTORS],
+    pub(crate) q_hash: [F; GATE_WIDTH],
+    pub(crate) q_o: F,
+    pub(crate) q_c: F,
+    pub(crate) q_ecc: F,
+    pub(crate) q_lookup: F,
+}
+
+impl<F: Field> Gate<F> for CompiledGate<F> {
+    fn name(&self) -> &'static str {
+        "Compiled Gate"
+    }
+    fn q_lc(&self) -> [F; GATE_WIDTH] {
+        self.q_lc
+    }
+    fn q_mul(&self) -> [F; N_MUL_SELECTORS] {
+        self.q_mul
+    }
+    fn q_hash(&self) -> [F; GATE_WIDTH] {
+        self.q_hash
+    }
+    fn q_o(&self) -> F {
+        self.q_o
+    }
+    fn q_c(&self) -> F {
+        self.q_c
+    }
+    fn q_ecc(&self) -> F {
+        self.q_ecc
+    }
+    fn q_lookup(&self) -> F {
+        self.q_lookup
+    }
+}
@@ -8,16 +8,48 @@
 use crate::errors::{CircuitError::LookupUnsupported, PlonkError};
 use ark_ff::{FftField, Field};
 use ark_poly::univariate::DensePolynomial;
-use ark_std::vec::Vec;
+use ark_std::{string::String, vec::Vec};
 
+pub mod acir;
 pub mod basic;
+pub mod circom;
 pub mod customized;
+pub mod gadget;
 pub mod gates;
+pub mod profile;
+pub mod r1cs;
 
-pub use basic::PlonkCircuit;
+pub use basic::{
+    generate_witness, CompiledCircuit, GateDiagnostics, GateViolation, OptimizationReport,
+    PlonkCircuit, RangeTableSize, SatisfiabilityReport, ViolationKind, WitnessVector,
+    MAX_AUTO_RANGE_BIT_LEN,
+};
+pub use gadget::{CircuitGadget, GadgetCost};
+pub use profile::{CircuitSize, ConstraintProfile, NamespaceCost};
 
 /// An index to one of the witness values.
 pub type Variable = usize;
+
+/// A variable whose value is constrained to be boolean, i.e. zero or one.
+/// Boolean gadgets return it and the gadgets assuming boolean inputs take
+/// it, so that an arbitrary variable cannot be passed as a boolean one; it
+/// converts into a `Variable` for arithmetic.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoolVar(pub(crate) Variable);
+
+impl BoolVar {
+    /// Wrap a variable which is already constrained to be boolean, e.g. a
+    /// bit output by `unpack`, without adding a constraint.
+    pub(crate) fn new_unchecked(var: Variable) -> Self {
+        BoolVar(var)
+    }
+}
+
+impl From<BoolVar> for Variable {
+    fn from(b: BoolVar) -> Self {
+        b.0
+    }
+}
 /// An index to a gate in circuit.
 pub type GateId = usize;
 /// An index to the type of gate wires.
@@ -55,12 +87,12 @@ pub trait Circuit<F: Field> {
     /// Add a variable to the circuit; return the index of the variable.
     fn create_variable(&mut self, val: F) -> Result<Variable, PlonkError>;
 
-    /// Add a bool variable to the circuit; return the index of the variable.
-    fn create_bool_variable(&mut self, val: bool) -> Result<Variable, PlonkError> {
+    /// Add a bool variable to the circuit; return the bool variable.
+    fn create_bool_variable(&mut self, val: bool) -> Result<BoolVar, PlonkError> {
         let val_scalar = if val { F::one() } else { F::zero() };
         let var = self.create_variable(val_scalar)?;
         self.bool_gate(var)?;
-        Ok(var)
+        Ok(BoolVar(var))
     }
 
     /// Add a public input variable; return the index of the variable.
@@ -69,12 +101,28 @@ pub trait Circuit<F: Field> {
     /// Set a variable to a public variable
     fn set_variable_public(&mut self, var: Variable) -> Result<(), PlonkError>;
 
+    /// The names of the public inputs which have one, with their positions
+    /// among the public inputs, in increasing order of positions.
+    fn public_input_names(&self) -> Vec<(String, usize)> {
+        Vec::new()
+    }
+
     /// Return a default variable with value zero.
     fn zero(&self) -> Variable;
 
     /// Return a default variable with value one.
     fn one(&self) -> Variable;
 
+    /// Return the default variable with value zero as a bool variable.
+    fn false_var(&self) -> BoolVar {
+        BoolVar(self.zero())
+    }
+
+    /// Return the default variable with value one as a bool variable.
+    fn true_var(&self) -> BoolVar {
+        BoolVar(self.one())
+    }
+
     /// Return the witness value of variable `idx`.
     /// Return error if the input variable is invalid.
     fn witness(&self, idx: Variable) -> Result<F, PlonkError>;
@@ -0,0 +1,109 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! The size of a circuit, c.f. `PlonkCircuit::size`, and its breakdown by the
+//! namespaces of its gates, c.f. `PlonkCircuit::push_namespace`, to find the
+//! gadgets which dominate it.
+//!
+//! A profile is serializable, e.g. to JSON with `serde_json`, and can be
+//! exported as folded stacks, the input format of flamegraph tools such as
+//! `inferno` or `flamegraph.pl`.
+
+use super::basic::NAMESPACE_SEPARATOR;
+use ark_std::{format, string::String, vec::Vec};
+use serde::{Deserialize, Serialize};
+
+/// The separator of the frames of a folded stack.
+const FOLDED_STACK_SEPARATOR: char = ';';
+
+/// The size of a circuit and of the evaluation domain and SRS it requires.
+/// The padding gates of a finalized circuit are ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct CircuitSize {
+    /// The number of variables.
+    pub num_vars: usize,
+    /// The number of public inputs.
+    pub num_inputs: usize,
+    /// The number of algebraic gates, including the lookup gates.
+    pub num_gates: usize,
+    /// The number of lookup gates.
+    pub num_lookup_gates: usize,
+    /// The number of range gates.
+    pub num_range_gates: usize,
+    /// The number of elements of the inserted lookup tables.
+    pub num_table_elems: usize,
+    /// The size of the evaluation domain of the finalized circuit.
+    pub eval_domain_size: usize,
+    /// The minimum degree of the SRS to preprocess the circuit.
+    pub srs_size: usize,
+}
+
+/// The gates inserted directly in a namespace, i.e. not in one of its
+/// children.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct NamespaceCost {
+    /// The path of the namespace, e.g. `"ecdsa/msm"`, or the empty string
+    /// for the root namespace.
+    pub path: String,
+    /// The number of algebraic gates, including the lookup gates.
+    pub num_gates: usize,
+    /// The number of lookup gates.
+    pub num_lookup_gates: usize,
+    /// The number of range gates.
+    pub num_range_gates: usize,
+}
+
+/// The costs of the namespaces of a circuit in which gates are inserted,
+/// ordered by path. The padding gates of a finalized circuit are ignored.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ConstraintProfile {
+    /// The cost of each namespace.
+    pub namespaces: Vec<NamespaceCost>,
+}
+
+impl ConstraintProfile {
+    /// The cost of the namespace `path` including all its descendants, e.g.
+    /// the whole circuit for the empty path.
+    pub fn total(&self, path: &str) -> NamespaceCost {
+        let mut total = NamespaceCost {
+            path: path.into(),
+            ..NamespaceCost::default()
+        };
+        for cost in self.namespaces.iter().filter(|c| is_within(&c.path, path)) {
+            total.num_gates += cost.num_gates;
+            total.num_lookup_gates += cost.num_lookup_gates;
+            total.num_range_gates += cost.num_range_gates;
+        }
+        total
+    }
+
+    /// Export the numbers of algebraic gates as folded stacks, one line
+    /// `root;label_1;...;label_k count` per namespace, where `root` names the
+    /// root namespace.
+    pub fn to_folded_stacks(&self, root: &str) -> String {
+        let mut folded = String::new();
+        for cost in self.namespaces.iter().filter(|c| c.num_gates > 0) {
+            let mut stack = String::from(root);
+            for label in cost
+                .path
+                .split(NAMESPACE_SEPARATOR)
+                .filter(|l| !l.is_empty())
+            {
+                stack.push(FOLDED_STACK_SEPARATOR);
+                stack.push_str(label);
+            }
+            folded.push_str(&format!("{} {}\n", stack, cost.num_gates));
+        }
+        folded
+    }
+}
+
+// Whether the namespace `path` is `ancestor` or one of its descendants.
+fn is_within(path: &str, ancestor: &str) -> bool {
+    ancestor.is_empty()
+        || path == ancestor
+        || (path.starts_with(ancestor) && path[ancestor.len()..].starts_with(NAMESPACE_SEPARATOR))
+}
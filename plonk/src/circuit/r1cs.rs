@@ -0,0 +1,249 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Import of arkworks R1CS constraint systems, so that the gadgets written
+//! for `ark-relations` can be proven with the Plonk provers of this crate.
+//!
+//! Each R1CS constraint `<A, z> * <B, z> = <C, z>` becomes a quadratic
+//! polynomial gate. A linear combination with a single variable term and a
+//! constant is a wire of this gate and its selectors, and one with several
+//! variable terms is first reduced to a variable with linear combination
+//! gates of 4 terms each, the first term of the following gates being the
+//! previous partial sum.
+
+use super::{Circuit, PlonkCircuit, Variable};
+use crate::{
+    constants::GATE_WIDTH,
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use ark_ff::PrimeField;
+use ark_relations::r1cs::ConstraintSystemRef;
+use ark_std::{cmp::min, format, string::ToString, vec::Vec};
+
+impl<F: PrimeField> PlonkCircuit<F> {
+    /// Append the constraints of the R1CS constraint system `cs`, whose
+    /// instance variables, other than the constant one, become public inputs
+    /// of this circuit in the same order. `cs` is finalized by this function,
+    /// i.e. its symbolic linear combinations are inlined.
+    /// Return the variable of this circuit of each R1CS variable, indexed as
+    /// in the matrices of `cs`: the constant one, then the instance
+    /// variables, then the witness variables.
+    ///
+    /// The values of the variables are those assigned in `cs`, or zero for
+    /// a constraint system in setup mode, whose circuit can be imported in
+    /// a shape-only circuit, c.f. `is_shape_only`, to preprocess it.
+    /// Return error if the constraint system is not available or does not
+    /// construct its matrices.
+    pub fn import_r1cs(&mut self, cs: ConstraintSystemRef<F>) -> Result<Vec<Variable>, PlonkError> {
+        cs.finalize();
+        let matrices = cs
+            .to_matrices()
+            .ok_or_else(|| ParameterError("the R1CS matrices are not available".to_string()))?;
+        let (instance, witness) = {
+            let cs = cs.borrow().ok_or_else(|| {
+                ParameterError("the R1CS constraint system is not available".to_string())
+            })?;
+            (
+                cs.instance_assignment.clone(),
+                cs.witness_assignment.clone(),
+            )
+        };
+        let value = |assignment: &[F], i: usize| assignment.get(i).copied().unwrap_or_else(F::zero);
+
+        let mut vars =
+            Vec::with_capacity(matrices.num_instance_variables + matrices.num_witness_variables);
+        vars.push(self.one());
+        for i in 1..matrices.num_instance_variables {
+            vars.push(self.create_public_variable(value(&instance, i))?);
+        }
+        for i in 0..matrices.num_witness_variables {
+            vars.push(self.create_variable(value(&witness, i))?);
+        }
+
+        for ((a, b), c) in matrices
+            .a
+            .iter()
+            .zip(matrices.b.iter())
+            .zip(matrices.c.iter())
+        {
+            self.r1cs_constraint_gate(a, b, c, &vars)?;
+        }
+        Ok(vars)
+    }
+
+    /// Constrain the R1CS constraint `<a, z> * <b, z> = <c, z>`, whose linear
+    /// combinations are lists of terms `(coeff, index)` of the R1CS
+    /// variables `vars`, the variable `0` being the constant one.
+    /// Return error if an index or a variable is invalid.
+    pub(crate) fn r1cs_constraint_gate(
+        &mut self,
+        a: &[(F, usize)],
+        b: &[(F, usize)],
+        c: &[(F, usize)],
+        vars: &[Variable],
+    ) -> Result<(), PlonkError> {
+        // (alpha_a * a + kappa_a) * (alpha_b * b + kappa_b) = alpha_c * c + kappa_c
+        let (alpha_a, var_a, kappa_a) = self.reduce_r1cs_lc(a, vars)?;
+        let (alpha_b, var_b, kappa_b) = self.reduce_r1cs_lc(b, vars)?;
+        let (alpha_c, var_c, kappa_c) = self.reduce_r1cs_lc(c, vars)?;
+        let zero = self.zero();
+        self.quad_poly_gate(
+            &[var_a, var_b, zero, zero, var_c],
+            &[alpha_a * kappa_b, kappa_a * alpha_b, F::zero(), F::zero()],
+            &[alpha_a * alpha_b, F::zero()],
+            alpha_c,
+            kappa_a * kappa_b - kappa_c,
+        )
+    }
+
+    // Reduce the linear combination `lc` of R1CS variables to
+    // `alpha * var + kappa`, adding linear combination gates if it has
+    // several variable terms.
+    fn reduce_r1cs_lc(
+        &mut self,
+        lc: &[(F, usize)],
+        vars: &[Variable],
+    ) -> Result<(F, Variable, F), PlonkError> {
+        let mut kappa = F::zero();
+        let mut terms = Vec::with_capacity(lc.len());
+        for &(coeff, index) in lc.iter() {
+            match index {
+                0 => kappa += coeff,
+                _ => terms.push((
+                    coeff,
+                    *vars.get(index).ok_or_else(|| {
+                        ParameterError(format!("invalid R1CS variable index {}", index))
+                    })?,
+                )),
+            }
+        }
+        match terms.len() {
+            0 => Ok((F::zero(), self.zero(), kappa)),
+            1 => Ok((terms[0].0, terms[0].1, kappa)),
+            _ => {
+                let zero = self.zero();
+                let mut sum = None;
+                let mut rest = &terms[..];
+                while !rest.is_empty() {
+                    let mut wires = [zero; GATE_WIDTH];
+                    let mut coeffs = [F::zero(); GATE_WIDTH];
+                    let start = match sum {
+                        Some(sum) => {
+                            wires[0] = sum;
+                            coeffs[0] = F::one();
+                            1
+                        },
+                        None => 0,
+                    };
+                    let len = min(GATE_WIDTH - start, rest.len());
+                    for (i, &(coeff, var)) in rest[..len].iter().enumerate() {
+                        wires[start + i] = var;
+                        coeffs[start + i] = coeff;
+                    }
+                    rest = &rest[len..];
+                    sum = Some(self.lc(&wires, &coeffs)?);
+                }
+                Ok((F::one(), sum.unwrap(), kappa)) // safe unwrap
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_relations::{
+        lc,
+        r1cs::{ConstraintSystem, SynthesisError, SynthesisMode, Variable as R1csVariable},
+    };
+
+    // x^3 + x + 5 = y for the public input y, and
+    // (x + 2 * x^2 + 3 * x^3 + y + 1) * (x + 1) = z.
+    fn synthesize<F: PrimeField>(
+        cs: ConstraintSystemRef<F>,
+        x: F,
+    ) -> Result<(R1csVariable, R1csVariable), SynthesisError> {
+        let x2_val = x * x;
+        let x3_val = x2_val * x;
+        let y_val = x3_val + x + F::from(5u32);
+        let z_val =
+            (x + x2_val.double() + F::from(3u32) * x3_val + y_val + F::one()) * (x + F::one());
+        let y = cs.new_input_variable(|| Ok(y_val))?;
+        let x = cs.new_witness_variable(|| Ok(x))?;
+        let x2 = cs.new_witness_variable(|| Ok(x2_val))?;
+        let x3 = cs.new_witness_variable(|| Ok(x3_val))?;
+        let z = cs.new_witness_variable(|| Ok(z_val))?;
+        let one = R1csVariable::One;
+        cs.enforce_constraint(lc!() + x, lc!() + x, lc!() + x2)?;
+        cs.enforce_constraint(lc!() + x2, lc!() + x, lc!() + x3)?;
+        cs.enforce_constraint(
+            lc!() + x3 + x + (F::from(5u32), one),
+            lc!() + one,
+            lc!() + y,
+        )?;
+        cs.enforce_constraint(
+            lc!() + x + (F::from(2u32), x2) + (F::from(3u32), x3) + y + one,
+            lc!() + x + one,
+            lc!() + z,
+        )?;
+        Ok((y, z))
+    }
+
+    #[test]
+    fn test_import_r1cs() -> Result<(), PlonkError> {
+        test_import_r1cs_helper::<FqEd254>()?;
+        test_import_r1cs_helper::<FqEd377>()?;
+        test_import_r1cs_helper::<FqEd381>()?;
+        test_import_r1cs_helper::<Fq377>()
+    }
+
+    fn test_import_r1cs_helper<F: PrimeField>() -> Result<(), PlonkError> {
+        let x = F::from(3u32);
+        let y = F::from(35u32);
+        let cs = ConstraintSystem::<F>::new_ref();
+        let (_, z) = synthesize(cs.clone(), x).unwrap();
+        assert!(cs.is_satisfied().unwrap());
+        let z = z.get_index_unchecked(cs.num_instance_variables()).unwrap();
+
+        for mut circuit in [
+            PlonkCircuit::<F>::new_turbo_plonk(),
+            PlonkCircuit::<F>::new_ultra_plonk(8),
+        ] {
+            let vars = circuit.import_r1cs(cs.clone())?;
+            assert_eq!(vars.len(), 6);
+            assert_eq!(vars[0], circuit.one());
+            assert_eq!(circuit.num_inputs(), 1);
+            assert_eq!(circuit.witness(vars[1])?, y);
+            assert_eq!(circuit.witness(vars[z])?, F::from(552u32));
+            assert!(circuit.check_circuit_satisfiability(&[y]).is_ok());
+
+            // a wrong public input or witness
+            assert!(circuit
+                .check_circuit_satisfiability(&[y + F::one()])
+                .is_err());
+            *circuit.witness_mut(vars[z]) += F::one();
+            assert!(circuit.check_circuit_satisfiability(&[y]).is_err());
+        }
+
+        // a constraint system in setup mode has the same shape
+        let setup_cs = ConstraintSystem::<F>::new_ref();
+        setup_cs.set_mode(SynthesisMode::Setup);
+        synthesize(setup_cs.clone(), x).unwrap();
+        let mut shape = PlonkCircuit::<F>::new_turbo_plonk_shape_only();
+        shape.import_r1cs(setup_cs)?;
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        circuit.import_r1cs(cs)?;
+        assert_eq!(shape.size()?, circuit.size()?);
+
+        // no constraint system
+        assert!(circuit.import_r1cs(ConstraintSystemRef::None).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,553 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! An accumulation scheme deferring the pairings of KZG openings.
+//!
+//! Each step folds the opening claims of Plonk proofs over a curve `E` into a
+//! running accumulator, in a circuit over the base field of `E` proven with a
+//! curve `E2` whose scalar field is this base field, e.g. BLS12-377 and
+//! BW6-761. The accumulator consists of the two group elements of a pairing
+//! check, and is input and output by every step circuit, so that no pairing is
+//! computed until a single pairing on the final accumulator decides the claims
+//! of all the steps.
+use crate::{
+    circuit::{
+        customized::{
+            ecc::{Point, SWToTEConParam},
+            transcript::RescueTranscriptVar,
+            ultraplonk::{
+                mod_arith::FpElemVar,
+                plonk_verifier::{fr_split_param, AccumulatorVar, VerifyingKeyVar},
+            },
+        },
+        Circuit, PlonkCircuit,
+    },
+    errors::PlonkError,
+    proof_system::{
+        aggregation::{extend_sw_point_public_input, RANGE_BIT_LEN},
+        batch_arg::BatchArgument,
+        structs::{BatchProof, OpenKey, Proof, ProvingKey, UniversalSrs, VerifyingKey},
+        PlonkKzgSnark, Snark,
+    },
+    transcript::PlonkTranscript,
+};
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, AffineCurve, PairingEngine, SWModelParameters,
+    TEModelParameters,
+};
+use ark_ff::{One, PrimeField, Zero};
+use ark_serialize::*;
+use ark_std::{
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    vec::Vec,
+    UniformRand,
+};
+use jf_rescue::RescueParameter;
+use jf_utils::field_switching;
+
+/// A KZG opening accumulator over the curve `E`: the two group elements
+/// `inner1` and `inner2` of the deferred pairing check
+/// `e(inner1, [beta]2) = e(inner2, [1]2)`.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct KzgAccumulator<E: PairingEngine> {
+    /// The first group element of the pairing.
+    pub inner1: E::G1Affine,
+    /// The second group element of the pairing.
+    pub inner2: E::G1Affine,
+}
+
+impl<E: PairingEngine> Default for KzgAccumulator<E> {
+    /// The initial accumulator, whose group elements are the identity, and
+    /// which holds no claim.
+    fn default() -> Self {
+        Self {
+            inner1: E::G1Affine::zero(),
+            inner2: E::G1Affine::zero(),
+        }
+    }
+}
+
+impl<E: PairingEngine> KzgAccumulator<E> {
+    /// Decide the accumulator with a single pairing under the open key
+    /// `open_key`. Return true if all the accumulated claims are valid.
+    pub fn decide(&self, open_key: &OpenKey<E>) -> Result<bool, PlonkError> {
+        BatchArgument::decide(
+            open_key,
+            self.inner1.into_projective(),
+            self.inner2.into_projective(),
+        )
+    }
+}
+
+/// A proof of an accumulation step, attesting that the new accumulator folds
+/// the claims of the step's Plonk proofs into the previous accumulator.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AccumulationStepProof<E: PairingEngine, E2: PairingEngine> {
+    /// The proof of the step circuit.
+    pub proof: Proof<E2>,
+    /// The new accumulator.
+    pub acc: KzgAccumulator<E>,
+}
+
+/// An accumulation scheme of the KZG openings of Plonk proofs over the curve
+/// `E`, whose steps are proven with the curve `E2`.
+pub struct AccumulationArgument<E: PairingEngine, E2: PairingEngine>(PhantomData<(E, E2)>);
+
+impl<E, F, P, E2, F2, P2> AccumulationArgument<E, E2>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + TEModelParameters + Clone,
+    E2: PairingEngine<Fr = F, Fq = F2, G1Affine = GroupAffine<P2>>,
+    F2: RescueParameter + SWToTEConParam,
+    P2: SWModelParameters<BaseField = F2> + Clone,
+{
+    /// Build the finalized step circuit folding the proofs `proofs` of the
+    /// verifying keys `vks` with the public inputs `public_inputs` into the
+    /// accumulator `acc`, c.f. `AggregationArgument::build_circuit`. The
+    /// public inputs of the circuit are the public inputs of the proofs,
+    /// followed by the coordinates and the infinity flag of each group
+    /// element of the previous accumulator, then of the new accumulator.
+    /// Return the circuit and the new accumulator, which is blinded by
+    /// `blinding_factor`.
+    /// Return error if the numbers of keys, public inputs and proofs differ,
+    /// or if they are inconsistent.
+    #[allow(clippy::too_many_arguments)]
+    pub fn build_circuit(
+        beta_g: &E::G1Affine,
+        open_key: &OpenKey<E>,
+        acc: &KzgAccumulator<E>,
+        vks: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        proofs: &[&Proof<E>],
+        blinding_factor: E::Fr,
+    ) -> Result<(PlonkCircuit<F>, KzgAccumulator<E>), PlonkError> {
+        let mut circuit = PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN);
+        let public_input_vars = public_inputs
+            .iter()
+            .map(|public_input| circuit.create_public_fp_elem_inputs::<E>(public_input))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let mut acc_point_vars = Vec::with_capacity(2);
+        for point in [&acc.inner1, &acc.inner2] {
+            let sw_point_var = circuit.create_sw_point_variable(point)?;
+            circuit.set_variable_public(sw_point_var.get_x())?;
+            circuit.set_variable_public(sw_point_var.get_y())?;
+            circuit.set_variable_public(sw_point_var.get_infinity().into())?;
+            acc_point_vars.push(circuit.sw_to_te_point(&sw_point_var)?);
+        }
+        let acc_var = AccumulatorVar::new(acc_point_vars[0], acc_point_vars[1]);
+        let vk_vars = vks
+            .iter()
+            .map(|vk| VerifyingKeyVar::new_constant(&mut circuit, vk))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let m = fr_split_param::<E>(RANGE_BIT_LEN);
+        let two_power_m = Some(F::from(2u8).pow(&[m as u64]));
+        let proof_vars = proofs
+            .iter()
+            .map(|&proof| {
+                BatchProof::from(proof.clone()).create_variables(&mut circuit, m, two_power_m)
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let blinding_factor_var = circuit.create_variable(field_switching(&blinding_factor))?;
+
+        let public_input_vars_ref: Vec<&[FpElemVar<F>]> =
+            public_input_vars.iter().map(|x| x.as_slice()).collect();
+        let new_acc_var =
+            VerifyingKeyVar::accumulate_partial_verify_circuit::<_, _, RescueTranscriptVar<F>>(
+                &mut circuit,
+                &Point::from(beta_g),
+                &Point::from(&open_key.g),
+                &acc_var,
+                &vk_vars,
+                &public_input_vars_ref,
+                &proof_vars,
+                blinding_factor_var,
+            )?;
+
+        let mut new_acc = Vec::with_capacity(2);
+        for point_var in [new_acc_var.inner1(), new_acc_var.inner2()] {
+            let sw_point_var = circuit.te_to_sw_point(&point_var)?;
+            circuit.set_variable_public(sw_point_var.get_x())?;
+            circuit.set_variable_public(sw_point_var.get_y())?;
+            circuit.set_variable_public(sw_point_var.get_infinity().into())?;
+            new_acc.push(circuit.sw_point_witness::<P>(&sw_point_var)?);
+        }
+        circuit.finalize_for_arithmetization()?;
+        Ok((
+            circuit,
+            KzgAccumulator {
+                inner1: new_acc[0],
+                inner2: new_acc[1],
+            },
+        ))
+    }
+
+    /// Compute the proving key and the verifying key of the step circuit,
+    /// given sample proofs of the verifying keys `vks`, c.f. `build_circuit`.
+    /// The circuit only depends on the keys and on the numbers of public
+    /// inputs, thus the proving key folds any proofs of the keys into any
+    /// accumulator.
+    pub fn preprocess<'a>(
+        srs: &'a UniversalSrs<E2>,
+        beta_g: &E::G1Affine,
+        open_key: &OpenKey<E>,
+        vks: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        proofs: &[&Proof<E>],
+    ) -> Result<(ProvingKey<'a, E2>, VerifyingKey<E2>), PlonkError> {
+        let (circuit, _) = Self::build_circuit(
+            beta_g,
+            open_key,
+            &KzgAccumulator::default(),
+            vks,
+            public_inputs,
+            proofs,
+            E::Fr::one(),
+        )?;
+        PlonkKzgSnark::preprocess(srs, &circuit)
+    }
+
+    /// Fold the proofs `proofs` of the verifying keys `vks` with the public
+    /// inputs `public_inputs` into the accumulator `acc`, using the proving
+    /// key `prove_key` of the step circuit, c.f. `build_circuit`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn accumulate<R, T>(
+        prng: &mut R,
+        prove_key: &ProvingKey<E2>,
+        beta_g: &E::G1Affine,
+        open_key: &OpenKey<E>,
+        acc: &KzgAccumulator<E>,
+        vks: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        proofs: &[&Proof<E>],
+    ) -> Result<AccumulationStepProof<E, E2>, PlonkError>
+    where
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F2>,
+    {
+        let blinding_factor = E::Fr::rand(prng);
+        let (circuit, new_acc) = Self::build_circuit(
+            beta_g,
+            open_key,
+            acc,
+            vks,
+            public_inputs,
+            proofs,
+            blinding_factor,
+        )?;
+        let proof = PlonkKzgSnark::<E2>::prove::<_, _, T>(prng, &circuit, prove_key, None)?;
+        Ok(AccumulationStepProof {
+            proof,
+            acc: new_acc,
+        })
+    }
+
+    /// Verify an accumulation step from the accumulator `acc`, with the
+    /// public inputs `public_inputs` of the folded proofs, using the
+    /// verifying key `verify_key` of the step circuit. No pairing over `E`
+    /// is computed: the claims of the step are only checked when the final
+    /// accumulator is decided, c.f. `KzgAccumulator::decide`.
+    /// Return error if the step proof is invalid.
+    pub fn verify_step<T>(
+        verify_key: &VerifyingKey<E2>,
+        acc: &KzgAccumulator<E>,
+        public_inputs: &[&[E::Fr]],
+        step_proof: &AccumulationStepProof<E, E2>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F2>,
+    {
+        let public_input = accumulation_public_input(public_inputs, acc, &step_proof.acc);
+        PlonkKzgSnark::<E2>::verify::<T>(verify_key, &public_input, &step_proof.proof, None)
+    }
+}
+
+// The public input of the step circuit: the public inputs of the folded
+// proofs, then the coordinates and the infinity flag of the two group
+// elements of the previous accumulator, and of the new accumulator.
+fn accumulation_public_input<F, E, P>(
+    public_inputs: &[&[E::Fr]],
+    acc: &KzgAccumulator<E>,
+    new_acc: &KzgAccumulator<E>,
+) -> Vec<F>
+where
+    F: PrimeField,
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    P: SWModelParameters<BaseField = F>,
+{
+    let mut public_input: Vec<F> = public_inputs
+        .iter()
+        .flat_map(|x| x.iter())
+        .map(field_switching)
+        .collect();
+    for point in [&acc.inner1, &acc.inner2, &new_acc.inner1, &new_acc.inner2] {
+        extend_sw_point_public_input(&mut public_input, point);
+    }
+    public_input
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::Arithmetization,
+        proof_system::{
+            aggregation::AggregationArgument, batch_arg::new_mergeable_circuit_for_test,
+        },
+        transcript::{RescueTranscript, StandardTranscript},
+        MergeableCircuitType,
+    };
+    use ark_bls12_377::{g1::Parameters as Param377, Bls12_377};
+    use ark_bw6_761::BW6_761;
+    use ark_std::{test_rng, vec};
+
+    // Proofs of circuits of `i` additions with random public inputs, one per
+    // accumulation step.
+    #[allow(clippy::type_complexity)]
+    fn build_proofs<E, F, P, R>(
+        rng: &mut R,
+        srs: &UniversalSrs<E>,
+        num_steps: usize,
+    ) -> Result<(VerifyingKey<E>, Vec<Vec<E::Fr>>, Vec<Proof<E>>), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        R: CryptoRng + RngCore,
+    {
+        let mut vk = None;
+        let mut public_inputs = vec![];
+        let mut proofs = vec![];
+        for _ in 0..num_steps {
+            let public_input = E::Fr::rand(rng);
+            let mut circuit =
+                new_mergeable_circuit_for_test::<E>(public_input, 5, MergeableCircuitType::TypeA)?;
+            circuit.finalize_for_arithmetization()?;
+            let (pk, step_vk) = PlonkKzgSnark::preprocess(srs, &circuit)?;
+            let proof =
+                PlonkKzgSnark::<E>::prove::<_, _, RescueTranscript<F>>(rng, &circuit, &pk, None)?;
+            vk = Some(step_vk);
+            public_inputs.push(vec![public_input]);
+            proofs.push(proof);
+        }
+        Ok((vk.unwrap(), public_inputs, proofs))
+    }
+
+    #[test]
+    fn test_accumulation_circuit() -> Result<(), PlonkError> {
+        test_accumulation_circuit_helper::<Bls12_377, _, Param377, BW6_761, _, _>()
+    }
+
+    fn test_accumulation_circuit_helper<E, F, P, E2, F2, P2>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + TEModelParameters + Clone,
+        E2: PairingEngine<Fr = F, Fq = F2, G1Affine = GroupAffine<P2>>,
+        F2: RescueParameter + SWToTEConParam,
+        P2: SWModelParameters<BaseField = F2> + Clone,
+    {
+        let rng = &mut test_rng();
+        let srs = PlonkKzgSnark::<E>::universal_setup(64 + 2, rng)?;
+        let (vk, public_inputs, proofs) = build_proofs(rng, &srs, 3)?;
+        let open_key = &vk.open_key;
+        let beta_g = &srs.0.powers_of_g[1];
+        assert!(KzgAccumulator::<E>::default().decide(open_key)?);
+
+        // fold one proof per step, deciding the accumulator only at the end
+        let mut acc = KzgAccumulator::default();
+        for (public_input, proof) in public_inputs.iter().zip(proofs.iter()) {
+            let public_inputs_ref = [public_input.as_slice()];
+            let (circuit, new_acc) = AccumulationArgument::<E, E2>::build_circuit(
+                beta_g,
+                open_key,
+                &acc,
+                &[&vk],
+                &public_inputs_ref,
+                &[proof],
+                E::Fr::rand(rng),
+            )?;
+            let circuit_public_input =
+                accumulation_public_input::<F, E, P>(&public_inputs_ref, &acc, &new_acc);
+            assert_eq!(circuit.num_inputs(), circuit_public_input.len());
+            assert!(circuit
+                .check_circuit_satisfiability(&circuit_public_input)
+                .is_ok());
+
+            // wrong public input of the proof or wrong previous accumulator
+            let mut bad_public_input = circuit_public_input.clone();
+            bad_public_input[0] += F::one();
+            assert!(circuit
+                .check_circuit_satisfiability(&bad_public_input)
+                .is_err());
+            let bad_public_input = accumulation_public_input::<F, E, P>(
+                &public_inputs_ref,
+                &KzgAccumulator {
+                    inner1: acc.inner2,
+                    inner2: acc.inner1,
+                },
+                &new_acc,
+            );
+            if acc.inner1 != acc.inner2 {
+                assert!(circuit
+                    .check_circuit_satisfiability(&bad_public_input)
+                    .is_err());
+            }
+            acc = new_acc;
+        }
+        assert!(acc.decide(open_key)?);
+
+        // a wrong proof folded at any step makes the final accumulator fail
+        let mut bad_proof = proofs[1].clone();
+        bad_proof.poly_evals.perm_next_eval += E::Fr::one();
+        let mut acc = KzgAccumulator::default();
+        for (public_input, proof) in public_inputs
+            .iter()
+            .zip([&proofs[0], &bad_proof, &proofs[2]])
+        {
+            let (_, new_acc) = AccumulationArgument::<E, E2>::build_circuit(
+                beta_g,
+                open_key,
+                &acc,
+                &[&vk],
+                &[public_input.as_slice()],
+                &[proof],
+                E::Fr::rand(rng),
+            )?;
+            acc = new_acc;
+        }
+        assert!(!acc.decide(open_key)?);
+
+        // folding all the proofs in a single step is as sound as aggregating
+        // them
+        let public_inputs_ref: Vec<&[E::Fr]> = public_inputs.iter().map(|x| x.as_slice()).collect();
+        let proofs_ref: Vec<&Proof<E>> = proofs.iter().collect();
+        let (_, acc) = AccumulationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &KzgAccumulator::default(),
+            &[&vk, &vk, &vk],
+            &public_inputs_ref,
+            &proofs_ref,
+            E::Fr::rand(rng),
+        )?;
+        assert!(acc.decide(open_key)?);
+        let (_, inner1, inner2) = AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &[&vk, &vk, &vk],
+            &public_inputs_ref,
+            &proofs_ref,
+            E::Fr::rand(rng),
+        )?;
+        assert!(KzgAccumulator::<E> { inner1, inner2 }.decide(open_key)?);
+
+        // error path: inconsistent numbers of keys and proofs
+        assert!(AccumulationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &acc,
+            &[&vk],
+            &public_inputs_ref,
+            &proofs_ref,
+            E::Fr::one(),
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_accumulation_argument_single_step() -> Result<(), PlonkError> {
+        // a reduced end-to-end accumulation of a single proof
+        test_accumulation_argument_helper::<Bls12_377, _, Param377, BW6_761, _, _, StandardTranscript>(
+            1,
+        )
+    }
+
+    #[test]
+    #[ignore]
+    fn test_accumulation_argument() -> Result<(), PlonkError> {
+        test_accumulation_argument_helper::<Bls12_377, _, Param377, BW6_761, _, _, StandardTranscript>(
+            2,
+        )
+    }
+
+    // Accumulate `num_steps` proofs, one per step.
+    fn test_accumulation_argument_helper<E, F, P, E2, F2, P2, T>(
+        num_steps: usize,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + TEModelParameters + Clone,
+        E2: PairingEngine<Fr = F, Fq = F2, G1Affine = GroupAffine<P2>>,
+        F2: RescueParameter + SWToTEConParam,
+        P2: SWModelParameters<BaseField = F2> + Clone,
+        T: PlonkTranscript<F2>,
+    {
+        let rng = &mut test_rng();
+        let srs = PlonkKzgSnark::<E>::universal_setup(64 + 2, rng)?;
+        let (vk, public_inputs, proofs) = build_proofs(rng, &srs, num_steps)?;
+        let open_key = &vk.open_key;
+        let beta_g = &srs.0.powers_of_g[1];
+
+        let (circuit, _) = AccumulationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &KzgAccumulator::default(),
+            &[&vk],
+            &[public_inputs[0].as_slice()],
+            &[&proofs[0]],
+            E::Fr::one(),
+        )?;
+        let srs2 = PlonkKzgSnark::<E2>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, step_vk) = AccumulationArgument::<E, E2>::preprocess(
+            &srs2,
+            beta_g,
+            open_key,
+            &[&vk],
+            &[public_inputs[0].as_slice()],
+            &[&proofs[0]],
+        )?;
+
+        let mut acc = KzgAccumulator::default();
+        for (public_input, proof) in public_inputs.iter().zip(proofs.iter()) {
+            let public_inputs_ref = [public_input.as_slice()];
+            let step_proof = AccumulationArgument::<E, E2>::accumulate::<_, T>(
+                rng,
+                &pk,
+                beta_g,
+                open_key,
+                &acc,
+                &[&vk],
+                &public_inputs_ref,
+                &[proof],
+            )?;
+            assert!(AccumulationArgument::<E, E2>::verify_step::<T>(
+                &step_vk,
+                &acc,
+                &public_inputs_ref,
+                &step_proof
+            )
+            .is_ok());
+
+            // wrong previous accumulator
+            assert!(AccumulationArgument::<E, E2>::verify_step::<T>(
+                &step_vk,
+                &step_proof.acc,
+                &public_inputs_ref,
+                &step_proof
+            )
+            .is_err());
+            acc = step_proof.acc;
+        }
+        assert!(acc.decide(open_key)?);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,622 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! An argument aggregating independent Plonk proofs into a single proof.
+//!
+//! The proofs, of the same or different circuits over a curve `E`, are
+//! partially verified in a circuit over the base field of `E`, which is proven
+//! with a curve `E2` whose scalar field is this base field, e.g. BLS12-377 and
+//! BW6-761. The pairing inputs of the proofs are combined in the circuit, so
+//! that the aggregated proof is a proof of the circuit and the two group
+//! elements of a single final pairing.
+use crate::{
+    circuit::{
+        customized::{
+            ecc::{Point, SWToTEConParam},
+            transcript::RescueTranscriptVar,
+            ultraplonk::{
+                mod_arith::FpElemVar,
+                plonk_verifier::{fr_split_param, VerifyingKeyVar},
+            },
+        },
+        Circuit, PlonkCircuit,
+    },
+    errors::PlonkError,
+    proof_system::{
+        batch_arg::BatchArgument,
+        structs::{BatchProof, OpenKey, Proof, ProvingKey, UniversalSrs, VerifyingKey},
+        PlonkKzgSnark, Snark,
+    },
+    transcript::PlonkTranscript,
+};
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, AffineCurve, PairingEngine, SWModelParameters,
+    TEModelParameters,
+};
+use ark_ff::{One, PrimeField};
+use ark_serialize::*;
+use ark_std::{
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    vec::Vec,
+    UniformRand,
+};
+use jf_rescue::RescueParameter;
+use jf_utils::field_switching;
+
+/// The range bit length of the aggregation circuit, which determines how the
+/// scalars of the aggregated proofs are split in the circuit.
+pub(crate) const RANGE_BIT_LEN: usize = 16;
+
+/// An aggregation argument of Plonk proofs over the curve `E`, proven with
+/// the curve `E2`.
+pub struct AggregationArgument<E: PairingEngine, E2: PairingEngine>(PhantomData<(E, E2)>);
+
+/// A proof attesting to several Plonk proofs.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct AggregatedProof<E: PairingEngine, E2: PairingEngine> {
+    /// The proof of the aggregation circuit.
+    pub proof: Proof<E2>,
+    /// The first group element of the final pairing.
+    pub inner1: E::G1Affine,
+    /// The second group element of the final pairing.
+    pub inner2: E::G1Affine,
+}
+
+impl<E, F, P, E2, F2, P2> AggregationArgument<E, E2>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + TEModelParameters + Clone,
+    E2: PairingEngine<Fr = F, Fq = F2, G1Affine = GroupAffine<P2>>,
+    F2: RescueParameter + SWToTEConParam,
+    P2: SWModelParameters<BaseField = F2> + Clone,
+{
+    /// Build the finalized aggregation circuit of the proofs `proofs` of the
+    /// verifying keys `vks` with the public inputs `public_inputs`, computed
+    /// with the Rescue transcript, with or without lookups, under the SRS
+    /// whose first powers of the generator are `open_key.g` and `beta_g`. The
+    /// verifying keys are constants of the circuit, whose public inputs are
+    /// the public inputs of the proofs, followed by the coordinates and the
+    /// infinity flag of each group element of the final pairing.
+    /// Return the circuit and the two group elements of the final pairing,
+    /// which are blinded by `blinding_factor`.
+    /// Return error if the numbers of keys, public inputs and proofs differ,
+    /// or if they are inconsistent.
+    pub fn build_circuit(
+        beta_g: &E::G1Affine,
+        open_key: &OpenKey<E>,
+        vks: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        proofs: &[&Proof<E>],
+        blinding_factor: E::Fr,
+    ) -> Result<(PlonkCircuit<F>, E::G1Affine, E::G1Affine), PlonkError> {
+        let mut circuit = PlonkCircuit::new_ultra_plonk(RANGE_BIT_LEN);
+        let public_input_vars = public_inputs
+            .iter()
+            .map(|public_input| circuit.create_public_fp_elem_inputs::<E>(public_input))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let vk_vars = vks
+            .iter()
+            .map(|vk| VerifyingKeyVar::new_constant(&mut circuit, vk))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let m = fr_split_param::<E>(RANGE_BIT_LEN);
+        let two_power_m = Some(F::from(2u8).pow(&[m as u64]));
+        let proof_vars = proofs
+            .iter()
+            .map(|&proof| {
+                BatchProof::from(proof.clone()).create_variables(&mut circuit, m, two_power_m)
+            })
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let blinding_factor_var = circuit.create_variable(field_switching(&blinding_factor))?;
+
+        let public_input_vars_ref: Vec<&[FpElemVar<F>]> =
+            public_input_vars.iter().map(|x| x.as_slice()).collect();
+        let (inner1, inner2) =
+            VerifyingKeyVar::aggregate_partial_verify_circuit::<_, _, RescueTranscriptVar<F>>(
+                &mut circuit,
+                &Point::from(beta_g),
+                &Point::from(&open_key.g),
+                &vk_vars,
+                &public_input_vars_ref,
+                &proof_vars,
+                blinding_factor_var,
+            )?;
+
+        // the verifier knows the pairing inputs in SW form
+        let mut inner = Vec::with_capacity(2);
+        for point_var in [inner1, inner2] {
+            let sw_point_var = circuit.te_to_sw_point(&point_var)?;
+            circuit.set_variable_public(sw_point_var.get_x())?;
+            circuit.set_variable_public(sw_point_var.get_y())?;
+            circuit.set_variable_public(sw_point_var.get_infinity().into())?;
+            inner.push(circuit.sw_point_witness::<P>(&sw_point_var)?);
+        }
+        circuit.finalize_for_arithmetization()?;
+        Ok((circuit, inner[0], inner[1]))
+    }
+
+    /// Compute the proving key and the verifying key of the aggregation
+    /// circuit, given sample proofs of the verifying keys `vks`, c.f.
+    /// `build_circuit`. The circuit only depends on the keys and on the
+    /// numbers of public inputs, thus the proving key aggregates any proofs
+    /// of the keys.
+    pub fn preprocess<'a>(
+        srs: &'a UniversalSrs<E2>,
+        beta_g: &E::G1Affine,
+        open_key: &OpenKey<E>,
+        vks: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        proofs: &[&Proof<E>],
+    ) -> Result<(ProvingKey<'a, E2>, VerifyingKey<E2>), PlonkError> {
+        let (circuit, ..) =
+            Self::build_circuit(beta_g, open_key, vks, public_inputs, proofs, E::Fr::one())?;
+        PlonkKzgSnark::preprocess(srs, &circuit)
+    }
+
+    /// Aggregate the proofs `proofs` of the verifying keys `vks` with the
+    /// public inputs `public_inputs`, using the proving key `prove_key` of
+    /// their aggregation circuit, c.f. `build_circuit`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn aggregate<R, T>(
+        prng: &mut R,
+        prove_key: &ProvingKey<E2>,
+        beta_g: &E::G1Affine,
+        open_key: &OpenKey<E>,
+        vks: &[&VerifyingKey<E>],
+        public_inputs: &[&[E::Fr]],
+        proofs: &[&Proof<E>],
+    ) -> Result<AggregatedProof<E, E2>, PlonkError>
+    where
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F2>,
+    {
+        let blinding_factor = E::Fr::rand(prng);
+        let (circuit, inner1, inner2) = Self::build_circuit(
+            beta_g,
+            open_key,
+            vks,
+            public_inputs,
+            proofs,
+            blinding_factor,
+        )?;
+        let proof = PlonkKzgSnark::<E2>::prove::<_, _, T>(prng, &circuit, prove_key, None)?;
+        Ok(AggregatedProof {
+            proof,
+            inner1,
+            inner2,
+        })
+    }
+
+    /// Verify an aggregated proof of proofs with the public inputs
+    /// `public_inputs`, using the verifying key `verify_key` of their
+    /// aggregation circuit and the open key `open_key` of the proofs.
+    /// Return error if the proof is invalid.
+    pub fn verify<T>(
+        verify_key: &VerifyingKey<E2>,
+        open_key: &OpenKey<E>,
+        public_inputs: &[&[E::Fr]],
+        proof: &AggregatedProof<E, E2>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F2>,
+    {
+        let public_input = aggregation_public_input(public_inputs, &proof.inner1, &proof.inner2);
+        PlonkKzgSnark::<E2>::verify::<T>(verify_key, &public_input, &proof.proof, None)?;
+        if !BatchArgument::decide(
+            open_key,
+            proof.inner1.into_projective(),
+            proof.inner2.into_projective(),
+        )? {
+            return Err(PlonkError::WrongProof);
+        }
+        Ok(())
+    }
+}
+
+// The public input of the aggregation circuit: the public inputs of the
+// aggregated proofs, then the coordinates and the infinity flag of the two
+// group elements of the final pairing.
+fn aggregation_public_input<F, E, P>(
+    public_inputs: &[&[E::Fr]],
+    inner1: &GroupAffine<P>,
+    inner2: &GroupAffine<P>,
+) -> Vec<F>
+where
+    F: PrimeField,
+    E: PairingEngine<Fq = F>,
+    P: SWModelParameters<BaseField = F>,
+{
+    let mut public_input: Vec<F> = public_inputs
+        .iter()
+        .flat_map(|x| x.iter())
+        .map(field_switching)
+        .collect();
+    for point in [inner1, inner2] {
+        extend_sw_point_public_input(&mut public_input, point);
+    }
+    public_input
+}
+
+// Append the coordinates and the infinity flag of a group element to the
+// public input of a circuit, as set by `build_circuit`.
+pub(crate) fn extend_sw_point_public_input<F, P>(public_input: &mut Vec<F>, point: &GroupAffine<P>)
+where
+    F: PrimeField,
+    P: SWModelParameters<BaseField = F>,
+{
+    match point.infinity {
+        true => public_input.extend([F::zero(), F::zero(), F::one()]),
+        false => public_input.extend([point.x, point.y, F::zero()]),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::Arithmetization,
+        proof_system::{
+            batch_arg::new_mergeable_circuit_for_test, snark::test::gen_circuit_for_test,
+        },
+        transcript::{RescueTranscript, StandardTranscript},
+        MergeableCircuitType, PlonkType,
+    };
+    use ark_bls12_377::{g1::Parameters as Param377, Bls12_377};
+    use ark_bw6_761::BW6_761;
+    use ark_std::{test_rng, vec};
+
+    // Two proofs of a circuit of 5 additions with different public inputs,
+    // and a proof of a circuit of 20 multiplications.
+    #[allow(clippy::type_complexity)]
+    fn build_proofs<E, F, P, R>(
+        rng: &mut R,
+        srs: &UniversalSrs<E>,
+    ) -> Result<(Vec<VerifyingKey<E>>, Vec<Vec<E::Fr>>, Vec<Proof<E>>), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+        R: CryptoRng + RngCore,
+    {
+        let mut vks = vec![];
+        let mut public_inputs = vec![];
+        let mut proofs = vec![];
+        for (i, circuit_type) in [
+            (5, MergeableCircuitType::TypeA),
+            (5, MergeableCircuitType::TypeA),
+            (20, MergeableCircuitType::TypeB),
+        ] {
+            let public_input = E::Fr::rand(rng);
+            let mut circuit = new_mergeable_circuit_for_test::<E>(public_input, i, circuit_type)?;
+            circuit.finalize_for_arithmetization()?;
+            let (pk, vk) = PlonkKzgSnark::preprocess(srs, &circuit)?;
+            let proof =
+                PlonkKzgSnark::<E>::prove::<_, _, RescueTranscript<F>>(rng, &circuit, &pk, None)?;
+            PlonkKzgSnark::<E>::verify::<RescueTranscript<F>>(&vk, &[public_input], &proof, None)?;
+            vks.push(vk);
+            public_inputs.push(vec![public_input]);
+            proofs.push(proof);
+        }
+        Ok((vks, public_inputs, proofs))
+    }
+
+    #[test]
+    fn test_aggregation_circuit() -> Result<(), PlonkError> {
+        test_aggregation_circuit_helper::<Bls12_377, _, Param377, BW6_761, _, _>()
+    }
+
+    fn test_aggregation_circuit_helper<E, F, P, E2, F2, P2>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + TEModelParameters + Clone,
+        E2: PairingEngine<Fr = F, Fq = F2, G1Affine = GroupAffine<P2>>,
+        F2: RescueParameter + SWToTEConParam,
+        P2: SWModelParameters<BaseField = F2> + Clone,
+    {
+        let rng = &mut test_rng();
+        let srs = PlonkKzgSnark::<E>::universal_setup(64 + 2, rng)?;
+        let (vks, public_inputs, proofs) = build_proofs(rng, &srs)?;
+        let vks_ref: Vec<&VerifyingKey<E>> = vks.iter().collect();
+        let public_inputs_ref: Vec<&[E::Fr]> = public_inputs.iter().map(|x| x.as_slice()).collect();
+        let proofs_ref: Vec<&Proof<E>> = proofs.iter().collect();
+        let open_key = &vks[0].open_key;
+        let beta_g = &srs.0.powers_of_g[1];
+        let blinding_factor = E::Fr::rand(rng);
+
+        let (circuit, inner1, inner2) = AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &proofs_ref,
+            blinding_factor,
+        )?;
+        let public_input =
+            aggregation_public_input::<F, E, P>(&public_inputs_ref, &inner1, &inner2);
+        assert_eq!(circuit.num_inputs(), public_input.len());
+        assert!(circuit.check_circuit_satisfiability(&public_input).is_ok());
+        assert!(BatchArgument::decide(
+            open_key,
+            inner1.into_projective(),
+            inner2.into_projective()
+        )?);
+
+        // wrong public inputs of a proof or pairing inputs
+        let mut bad_public_input = public_input.clone();
+        bad_public_input[1] += F::one();
+        assert!(circuit
+            .check_circuit_satisfiability(&bad_public_input)
+            .is_err());
+        let mut bad_public_input = public_input.clone();
+        bad_public_input[3] += F::one();
+        assert!(circuit
+            .check_circuit_satisfiability(&bad_public_input)
+            .is_err());
+
+        // the circuit of proofs with swapped public inputs is satisfied, but
+        // the final pairing fails
+        let bad_public_inputs_ref = [
+            public_inputs_ref[1],
+            public_inputs_ref[0],
+            public_inputs_ref[2],
+        ];
+        let (circuit, inner1, inner2) = AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &vks_ref,
+            &bad_public_inputs_ref,
+            &proofs_ref,
+            blinding_factor,
+        )?;
+        let public_input =
+            aggregation_public_input::<F, E, P>(&bad_public_inputs_ref, &inner1, &inner2);
+        assert!(circuit.check_circuit_satisfiability(&public_input).is_ok());
+        assert!(!BatchArgument::decide(
+            open_key,
+            inner1.into_projective(),
+            inner2.into_projective()
+        )?);
+
+        // error paths
+        // inconsistent numbers of keys, public inputs and proofs
+        assert!(AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &vks_ref[1..],
+            &public_inputs_ref,
+            &proofs_ref,
+            blinding_factor,
+        )
+        .is_err());
+        assert!(AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &proofs_ref[1..],
+            blinding_factor,
+        )
+        .is_err());
+        // no proof
+        assert!(AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &[],
+            &[],
+            &[],
+            blinding_factor
+        )
+        .is_err());
+        // a proof verified with the key of another circuit
+        let bad_vks_ref = [vks_ref[2], vks_ref[1], vks_ref[2]];
+        let (_, inner1, inner2) = AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &bad_vks_ref,
+            &public_inputs_ref,
+            &proofs_ref,
+            blinding_factor,
+        )?;
+        assert!(!BatchArgument::decide(
+            open_key,
+            inner1.into_projective(),
+            inner2.into_projective()
+        )?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_aggregation_circuit_with_lookup() -> Result<(), PlonkError> {
+        test_aggregation_circuit_with_lookup_helper::<Bls12_377, _, Param377, BW6_761, _, _>()
+    }
+
+    fn test_aggregation_circuit_with_lookup_helper<E, F, P, E2, F2, P2>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + TEModelParameters + Clone,
+        E2: PairingEngine<Fr = F, Fq = F2, G1Affine = GroupAffine<P2>>,
+        F2: RescueParameter + SWToTEConParam,
+        P2: SWModelParameters<BaseField = F2> + Clone,
+    {
+        // a TurboPlonk proof and an UltraPlonk proof with lookups
+        let rng = &mut test_rng();
+        let srs = PlonkKzgSnark::<E>::universal_setup(64 + 2, rng)?;
+        let mut vks = vec![];
+        let mut public_inputs = vec![];
+        let mut proofs = vec![];
+        for plonk_type in [PlonkType::TurboPlonk, PlonkType::UltraPlonk] {
+            let circuit = gen_circuit_for_test::<E::Fr>(3, 4, plonk_type)?;
+            let (pk, vk) = PlonkKzgSnark::preprocess(&srs, &circuit)?;
+            let proof =
+                PlonkKzgSnark::<E>::prove::<_, _, RescueTranscript<F>>(rng, &circuit, &pk, None)?;
+            public_inputs.push(circuit.public_input()?);
+            vks.push(vk);
+            proofs.push(proof);
+        }
+        assert!(proofs[1].plookup_proof.is_some());
+        let vks_ref: Vec<&VerifyingKey<E>> = vks.iter().collect();
+        let public_inputs_ref: Vec<&[E::Fr]> = public_inputs.iter().map(|x| x.as_slice()).collect();
+        let proofs_ref: Vec<&Proof<E>> = proofs.iter().collect();
+        let open_key = &vks[0].open_key;
+        let beta_g = &srs.0.powers_of_g[1];
+        let blinding_factor = E::Fr::rand(rng);
+
+        let (circuit, inner1, inner2) = AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &proofs_ref,
+            blinding_factor,
+        )?;
+        let public_input =
+            aggregation_public_input::<F, E, P>(&public_inputs_ref, &inner1, &inner2);
+        assert!(circuit.check_circuit_satisfiability(&public_input).is_ok());
+        assert!(BatchArgument::decide(
+            open_key,
+            inner1.into_projective(),
+            inner2.into_projective()
+        )?);
+
+        // a wrong evaluation of the Plookup proof
+        let mut bad_proof = proofs[1].clone();
+        if let Some(plookup_proof) = bad_proof.plookup_proof.as_mut() {
+            plookup_proof.poly_evals.h_1_eval += E::Fr::one();
+        }
+        let (_, inner1, inner2) = AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &[proofs_ref[0], &bad_proof],
+            blinding_factor,
+        )?;
+        assert!(!BatchArgument::decide(
+            open_key,
+            inner1.into_projective(),
+            inner2.into_projective()
+        )?);
+
+        // error path: a proof without lookups verified with a key with lookups
+        assert!(AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &[vks_ref[1], vks_ref[1]],
+            &public_inputs_ref,
+            &proofs_ref,
+            blinding_factor,
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_aggregation_argument_single_proof() -> Result<(), PlonkError> {
+        // a reduced end-to-end aggregation of a single proof
+        test_aggregation_argument_helper::<Bls12_377, _, Param377, BW6_761, _, _, StandardTranscript>(
+            1,
+        )
+    }
+
+    #[test]
+    #[ignore]
+    fn test_aggregation_argument() -> Result<(), PlonkError> {
+        test_aggregation_argument_helper::<Bls12_377, _, Param377, BW6_761, _, _, StandardTranscript>(
+            3,
+        )
+    }
+
+    // Aggregate the first `num_proofs` proofs of `build_proofs`.
+    fn test_aggregation_argument_helper<E, F, P, E2, F2, P2, T>(
+        num_proofs: usize,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + TEModelParameters + Clone,
+        E2: PairingEngine<Fr = F, Fq = F2, G1Affine = GroupAffine<P2>>,
+        F2: RescueParameter + SWToTEConParam,
+        P2: SWModelParameters<BaseField = F2> + Clone,
+        T: PlonkTranscript<F2>,
+    {
+        let rng = &mut test_rng();
+        let srs = PlonkKzgSnark::<E>::universal_setup(64 + 2, rng)?;
+        let (vks, public_inputs, proofs) = build_proofs(rng, &srs)?;
+        let vks_ref: Vec<&VerifyingKey<E>> = vks.iter().take(num_proofs).collect();
+        let public_inputs_ref: Vec<&[E::Fr]> = public_inputs
+            .iter()
+            .take(num_proofs)
+            .map(|x| x.as_slice())
+            .collect();
+        let proofs_ref: Vec<&Proof<E>> = proofs.iter().take(num_proofs).collect();
+        let open_key = &vks[0].open_key;
+        let beta_g = &srs.0.powers_of_g[1];
+
+        let (circuit, ..) = AggregationArgument::<E, E2>::build_circuit(
+            beta_g,
+            open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &proofs_ref,
+            E::Fr::one(),
+        )?;
+        let srs2 = PlonkKzgSnark::<E2>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = AggregationArgument::<E, E2>::preprocess(
+            &srs2,
+            beta_g,
+            open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &proofs_ref,
+        )?;
+        let proof = AggregationArgument::<E, E2>::aggregate::<_, T>(
+            rng,
+            &pk,
+            beta_g,
+            open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &proofs_ref,
+        )?;
+        assert!(AggregationArgument::<E, E2>::verify::<T>(
+            &vk,
+            open_key,
+            &public_inputs_ref,
+            &proof
+        )
+        .is_ok());
+
+        // wrong public inputs
+        let mut bad_public_input = public_inputs[0].clone();
+        bad_public_input[0] += E::Fr::one();
+        let mut bad_public_inputs_ref = public_inputs_ref.clone();
+        bad_public_inputs_ref[0] = &bad_public_input;
+        assert!(AggregationArgument::<E, E2>::verify::<T>(
+            &vk,
+            open_key,
+            &bad_public_inputs_ref,
+            &proof
+        )
+        .is_err());
+        // wrong pairing inputs
+        let mut bad_proof = proof.clone();
+        bad_proof.inner1 = proof.inner2;
+        assert!(AggregationArgument::<E, E2>::verify::<T>(
+            &vk,
+            open_key,
+            &public_inputs_ref,
+            &bad_proof
+        )
+        .is_err());
+
+        Ok(())
+    }
+}
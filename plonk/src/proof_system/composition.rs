@@ -0,0 +1,369 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Two-layer composition of Plonk proofs over an inner curve `E` into a
+//! proof over an outer curve `E2` whose scalar field is the base field of
+//! `E`, e.g. BLS12-377 and BW6-761, c.f. `Bls12_377Bw6_761Composition`.
+//!
+//! The inner layer proves circuits over the scalar field of `E` with the
+//! Rescue transcript. The outer layer is the verifier circuit of the inner
+//! proofs, c.f. `AggregationArgument`, where the group elements of `E` and
+//! their arithmetic are native, and only the scalars of `E` are emulated.
+use crate::{
+    circuit::{customized::ecc::SWToTEConParam, Circuit, PlonkCircuit},
+    errors::{PlonkError, SnarkError::ParameterError},
+    proof_system::{
+        aggregation::{AggregatedProof, AggregationArgument},
+        structs::{OpenKey, Proof, ProvingKey, UniversalSrs, VerifyingKey},
+        PlonkKzgSnark, Snark,
+    },
+    transcript::{PlonkTranscript, RescueTranscript},
+};
+use ark_bls12_377::Bls12_377;
+use ark_bw6_761::BW6_761;
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters, TEModelParameters,
+};
+use ark_ff::One;
+use ark_std::{
+    format,
+    marker::PhantomData,
+    rand::{CryptoRng, RngCore},
+    string::ToString,
+    vec::Vec,
+};
+use jf_rescue::RescueParameter;
+
+/// The composition of BLS12-377 proofs into a BW6-761 proof, the base field
+/// of BLS12-377 being the scalar field of BW6-761.
+#[allow(non_camel_case_types)]
+pub type Bls12_377Bw6_761Composition = TwoLayerComposition<Bls12_377, BW6_761>;
+
+/// The proving key of a two-layer composition: the proving keys of the inner
+/// circuits, and the proving key of the outer verifier circuit.
+#[derive(Debug, Clone)]
+pub struct ComposedProvingKey<'a, E: PairingEngine, E2: PairingEngine> {
+    /// The proving keys of the inner circuits.
+    pub inner_prove_keys: Vec<ProvingKey<'a, E>>,
+    /// The first power of the generator of the inner SRS.
+    pub beta_g: E::G1Affine,
+    /// The proving key of the outer verifier circuit.
+    pub outer_prove_key: ProvingKey<'a, E2>,
+}
+
+/// The verifying key of a two-layer composition: the open key of the inner
+/// proofs, and the verifying key of the outer verifier circuit, which is
+/// bound to the verifying keys of the inner circuits.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComposedVerifyingKey<E: PairingEngine, E2: PairingEngine> {
+    /// The open key of the inner proofs.
+    pub open_key: OpenKey<E>,
+    /// The verifying key of the outer verifier circuit.
+    pub outer_verify_key: VerifyingKey<E2>,
+}
+
+/// A two-layer composition of Plonk proofs over the curve `E` into a Plonk
+/// proof over the curve `E2`.
+pub struct TwoLayerComposition<E: PairingEngine, E2: PairingEngine>(PhantomData<(E, E2)>);
+
+impl<E, F, P, E2, F2, P2> TwoLayerComposition<E, E2>
+where
+    E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+    F: RescueParameter + SWToTEConParam,
+    P: SWModelParameters<BaseField = F> + TEModelParameters + Clone,
+    E2: PairingEngine<Fr = F, Fq = F2, G1Affine = GroupAffine<P2>>,
+    F2: RescueParameter + SWToTEConParam,
+    P2: SWModelParameters<BaseField = F2> + Clone,
+{
+    /// Build the outer verifier circuit of sample proofs of the finalized
+    /// inner circuits `circuits` under the inner SRS `inner_srs`, e.g. to
+    /// compute the size of the outer SRS with `srs_size`.
+    /// Return error if there is no inner circuit, or if the inner SRS is too
+    /// small for an inner circuit.
+    pub fn build_outer_circuit<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        inner_srs: &UniversalSrs<E>,
+        circuits: &[&PlonkCircuit<E::Fr>],
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let (beta_g, vks, public_inputs, proofs) = Self::sample_proofs(prng, inner_srs, circuits)?;
+        let vks_ref: Vec<&VerifyingKey<E>> = vks.iter().collect();
+        let public_inputs_ref: Vec<&[E::Fr]> = public_inputs.iter().map(|x| x.as_slice()).collect();
+        let proofs_ref: Vec<&Proof<E>> = proofs.iter().collect();
+        let (circuit, ..) = AggregationArgument::<E, E2>::build_circuit(
+            &beta_g,
+            &vks[0].open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &proofs_ref,
+            E::Fr::one(),
+        )?;
+        Ok(circuit)
+    }
+
+    /// Compute the proving key and the verifying key of the composition of
+    /// the finalized inner circuits `circuits`, from the inner SRS
+    /// `inner_srs` and the outer SRS `outer_srs`. The inner circuits are only
+    /// used for their shapes, and are proven once with their witnesses to
+    /// build the outer verifier circuit.
+    /// Return error if there is no inner circuit, or if an SRS is too small,
+    /// c.f. `build_outer_circuit`.
+    pub fn preprocess<'a, R: CryptoRng + RngCore>(
+        prng: &mut R,
+        inner_srs: &'a UniversalSrs<E>,
+        outer_srs: &'a UniversalSrs<E2>,
+        circuits: &[&PlonkCircuit<E::Fr>],
+    ) -> Result<(ComposedProvingKey<'a, E, E2>, ComposedVerifyingKey<E, E2>), PlonkError> {
+        let inner_prove_keys = circuits
+            .iter()
+            .map(|&circuit| PlonkKzgSnark::preprocess(inner_srs, circuit).map(|(pk, _)| pk))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let (beta_g, vks, public_inputs, proofs) = Self::sample_proofs(prng, inner_srs, circuits)?;
+        let vks_ref: Vec<&VerifyingKey<E>> = vks.iter().collect();
+        let public_inputs_ref: Vec<&[E::Fr]> = public_inputs.iter().map(|x| x.as_slice()).collect();
+        let proofs_ref: Vec<&Proof<E>> = proofs.iter().collect();
+        let open_key = vks[0].open_key.clone();
+        let (outer_prove_key, outer_verify_key) = AggregationArgument::<E, E2>::preprocess(
+            outer_srs,
+            &beta_g,
+            &open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &proofs_ref,
+        )?;
+        Ok((
+            ComposedProvingKey {
+                inner_prove_keys,
+                beta_g,
+                outer_prove_key,
+            },
+            ComposedVerifyingKey {
+                open_key,
+                outer_verify_key,
+            },
+        ))
+    }
+
+    /// Prove the finalized inner circuits `circuits` with the inner proving
+    /// keys of `prove_key`, and compose the inner proofs into an outer proof
+    /// with the transcript `T`.
+    /// Return error if the circuits are not those of the proving key.
+    pub fn prove<R, T>(
+        prng: &mut R,
+        prove_key: &ComposedProvingKey<E, E2>,
+        circuits: &[&PlonkCircuit<E::Fr>],
+    ) -> Result<AggregatedProof<E, E2>, PlonkError>
+    where
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F2>,
+    {
+        if circuits.len() != prove_key.inner_prove_keys.len() {
+            return Err(ParameterError(format!(
+                "the proving key has {} inner circuits, {} are given",
+                prove_key.inner_prove_keys.len(),
+                circuits.len()
+            ))
+            .into());
+        }
+        let mut public_inputs = Vec::with_capacity(circuits.len());
+        let mut proofs = Vec::with_capacity(circuits.len());
+        for (&circuit, pk) in circuits.iter().zip(prove_key.inner_prove_keys.iter()) {
+            proofs.push(PlonkKzgSnark::<E>::prove::<_, _, RescueTranscript<F>>(
+                prng, circuit, pk, None,
+            )?);
+            public_inputs.push(circuit.public_input()?);
+        }
+        let vks_ref: Vec<&VerifyingKey<E>> =
+            prove_key.inner_prove_keys.iter().map(|pk| &pk.vk).collect();
+        let public_inputs_ref: Vec<&[E::Fr]> = public_inputs.iter().map(|x| x.as_slice()).collect();
+        let proofs_ref: Vec<&Proof<E>> = proofs.iter().collect();
+        AggregationArgument::<E, E2>::aggregate::<_, T>(
+            prng,
+            &prove_key.outer_prove_key,
+            &prove_key.beta_g,
+            &vks_ref[0].open_key,
+            &vks_ref,
+            &public_inputs_ref,
+            &proofs_ref,
+        )
+    }
+
+    /// Verify a composed proof of the inner circuits with the public inputs
+    /// `public_inputs`, using the verifying key `verify_key` and the
+    /// transcript `T`.
+    /// Return error if the proof is invalid.
+    pub fn verify<T>(
+        verify_key: &ComposedVerifyingKey<E, E2>,
+        public_inputs: &[&[E::Fr]],
+        proof: &AggregatedProof<E, E2>,
+    ) -> Result<(), PlonkError>
+    where
+        T: PlonkTranscript<F2>,
+    {
+        AggregationArgument::<E, E2>::verify::<T>(
+            &verify_key.outer_verify_key,
+            &verify_key.open_key,
+            public_inputs,
+            proof,
+        )
+    }
+
+    // Preprocess and prove the inner circuits, returning the first power of
+    // the generator of the SRS, and the verifying keys, public inputs and
+    // proofs of the circuits.
+    #[allow(clippy::type_complexity)]
+    fn sample_proofs<R: CryptoRng + RngCore>(
+        prng: &mut R,
+        inner_srs: &UniversalSrs<E>,
+        circuits: &[&PlonkCircuit<E::Fr>],
+    ) -> Result<
+        (
+            E::G1Affine,
+            Vec<VerifyingKey<E>>,
+            Vec<Vec<E::Fr>>,
+            Vec<Proof<E>>,
+        ),
+        PlonkError,
+    > {
+        if circuits.is_empty() {
+            return Err(ParameterError("no inner circuit".to_string()).into());
+        }
+        let powers_of_g = inner_srs.powers_of_g_ref();
+        if powers_of_g.len() < 2 {
+            return Err(ParameterError("the inner SRS is too small".to_string()).into());
+        }
+        let mut vks = Vec::with_capacity(circuits.len());
+        let mut public_inputs = Vec::with_capacity(circuits.len());
+        let mut proofs = Vec::with_capacity(circuits.len());
+        for &circuit in circuits.iter() {
+            let (pk, vk) = PlonkKzgSnark::preprocess(inner_srs, circuit)?;
+            proofs.push(PlonkKzgSnark::<E>::prove::<_, _, RescueTranscript<F>>(
+                prng, circuit, &pk, None,
+            )?);
+            public_inputs.push(circuit.public_input()?);
+            vks.push(vk);
+        }
+        Ok((powers_of_g[1], vks, public_inputs, proofs))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        circuit::Arithmetization,
+        proof_system::{
+            batch_arg::new_mergeable_circuit_for_test, snark::test::gen_circuit_for_test,
+        },
+        transcript::StandardTranscript,
+        MergeableCircuitType, PlonkType,
+    };
+    use ark_std::{test_rng, vec, UniformRand};
+
+    // A TurboPlonk circuit of additions and an UltraPlonk circuit with
+    // lookups.
+    fn build_inner_circuits<E: PairingEngine>() -> Result<Vec<PlonkCircuit<E::Fr>>, PlonkError> {
+        let rng = &mut test_rng();
+        let mut circuit =
+            new_mergeable_circuit_for_test::<E>(E::Fr::rand(rng), 5, MergeableCircuitType::TypeA)?;
+        circuit.finalize_for_arithmetization()?;
+        Ok(vec![
+            circuit,
+            gen_circuit_for_test::<E::Fr>(3, 4, PlonkType::UltraPlonk)?,
+        ])
+    }
+
+    #[test]
+    fn test_composition_outer_circuit() -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let inner_srs = PlonkKzgSnark::<Bls12_377>::universal_setup(64 + 2, rng)?;
+        let circuits = build_inner_circuits::<Bls12_377>()?;
+        let circuits_ref: Vec<&PlonkCircuit<_>> = circuits.iter().collect();
+
+        // the outer circuit is over the base field of BLS12-377, and its
+        // shape does not depend on the witnesses of the inner circuits
+        let outer_circuit =
+            Bls12_377Bw6_761Composition::build_outer_circuit(rng, &inner_srs, &circuits_ref)?;
+        assert!(outer_circuit
+            .check_circuit_satisfiability(&outer_circuit.public_input()?)
+            .is_ok());
+        let mut other_circuits = build_inner_circuits::<Bls12_377>()?;
+        other_circuits[0] = {
+            let mut circuit = new_mergeable_circuit_for_test::<Bls12_377>(
+                <Bls12_377 as PairingEngine>::Fr::one(),
+                5,
+                MergeableCircuitType::TypeA,
+            )?;
+            circuit.finalize_for_arithmetization()?;
+            circuit
+        };
+        let other_circuits_ref: Vec<&PlonkCircuit<_>> = other_circuits.iter().collect();
+        let other_outer_circuit =
+            Bls12_377Bw6_761Composition::build_outer_circuit(rng, &inner_srs, &other_circuits_ref)?;
+        assert_eq!(outer_circuit.num_gates(), other_outer_circuit.num_gates());
+        assert_eq!(outer_circuit.srs_size()?, other_outer_circuit.srs_size()?);
+
+        // error paths
+        // no inner circuit
+        assert!(Bls12_377Bw6_761Composition::build_outer_circuit(rng, &inner_srs, &[]).is_err());
+        // an inner SRS too small for the inner circuits
+        let small_srs = PlonkKzgSnark::<Bls12_377>::universal_setup(4, rng)?;
+        assert!(
+            Bls12_377Bw6_761Composition::build_outer_circuit(rng, &small_srs, &circuits_ref)
+                .is_err()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_composition_single_circuit() -> Result<(), PlonkError> {
+        // a reduced end-to-end composition of a small TurboPlonk circuit
+        let circuit =
+            gen_circuit_for_test::<<Bls12_377 as PairingEngine>::Fr>(1, 3, PlonkType::TurboPlonk)?;
+        test_composition_helper::<StandardTranscript>(vec![circuit])
+    }
+
+    #[test]
+    #[ignore]
+    fn test_composition() -> Result<(), PlonkError> {
+        test_composition_helper::<StandardTranscript>(build_inner_circuits::<Bls12_377>()?)
+    }
+
+    fn test_composition_helper<T: PlonkTranscript<<BW6_761 as PairingEngine>::Fq>>(
+        circuits: Vec<PlonkCircuit<<Bls12_377 as PairingEngine>::Fr>>,
+    ) -> Result<(), PlonkError> {
+        let rng = &mut test_rng();
+        let inner_srs = PlonkKzgSnark::<Bls12_377>::universal_setup(64 + 2, rng)?;
+        let circuits_ref: Vec<&PlonkCircuit<_>> = circuits.iter().collect();
+        let outer_circuit =
+            Bls12_377Bw6_761Composition::build_outer_circuit(rng, &inner_srs, &circuits_ref)?;
+        let outer_srs = PlonkKzgSnark::<BW6_761>::universal_setup(outer_circuit.srs_size()?, rng)?;
+        let (pk, vk) =
+            Bls12_377Bw6_761Composition::preprocess(rng, &inner_srs, &outer_srs, &circuits_ref)?;
+
+        let proof = Bls12_377Bw6_761Composition::prove::<_, T>(rng, &pk, &circuits_ref)?;
+        let public_inputs = circuits
+            .iter()
+            .map(|circuit| circuit.public_input())
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        let public_inputs_ref: Vec<&[_]> = public_inputs.iter().map(|x| x.as_slice()).collect();
+        assert!(Bls12_377Bw6_761Composition::verify::<T>(&vk, &public_inputs_ref, &proof).is_ok());
+
+        // wrong public inputs
+        let mut bad_public_input = public_inputs[0].clone();
+        bad_public_input[0] += <Bls12_377 as PairingEngine>::Fr::one();
+        let mut bad_public_inputs_ref = public_inputs_ref.clone();
+        bad_public_inputs_ref[0] = &bad_public_input;
+        assert!(
+            Bls12_377Bw6_761Composition::verify::<T>(&vk, &bad_public_inputs_ref, &proof).is_err()
+        );
+
+        // error path: a missing inner circuit
+        assert!(Bls12_377Bw6_761Composition::prove::<_, T>(rng, &pk, &circuits_ref[1..]).is_err());
+
+        Ok(())
+    }
+}
@@ -11,7 +11,10 @@ use ark_std::{
     rand::{CryptoRng, RngCore},
     vec::Vec,
 };
+pub mod accumulation;
+pub mod aggregation;
 pub mod batch_arg;
+pub mod composition;
 pub(crate) mod prover;
 pub(crate) mod snark;
 pub mod structs;
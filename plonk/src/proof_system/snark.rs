@@ -9,13 +9,15 @@ use super::{
     prover::Prover,
     structs::{
         trim, BatchProof, Challenges, Oracles, PlookupProof, PlookupProvingKey,
-        PlookupVerifyingKey, Proof, ProvingKey, VerifyingKey,
+        PlookupVerifyingKey, Proof, ProvingKey, PublicInputName, VerifyingKey,
     },
     verifier::Verifier,
     Snark,
 };
 use crate::{
-    circuit::{customized::ecc::SWToTEConParam, Arithmetization},
+    circuit::{
+        customized::ecc::SWToTEConParam, Arithmetization, Circuit, PlonkCircuit, WitnessVector,
+    },
     constants::{compute_coset_representatives, EXTRA_TRANSCRIPT_MSG_LABEL},
     errors::{PlonkError, SnarkError::ParameterError},
     proof_system::structs::UniversalSrs,
@@ -145,6 +147,14 @@ where
             open_key,
             plookup_vk,
             is_merged: false,
+            pub_input_names: circuit
+                .public_input_names()
+                .into_iter()
+                .map(|(name, position)| PublicInputName {
+                    name: name.into_bytes(),
+                    position,
+                })
+                .collect(),
         };
 
         // Compute ProvingKey (which includes the VerifyingKey)
@@ -175,6 +185,33 @@ where
         Ok(batch_proof)
     }
 
+    /// Compute a Plonk proof of the finalized circuit `circuit`, e.g.
+    /// reloaded from its compiled form with `PlonkCircuit::from_compiled`,
+    /// with the witness `witness` generated separately, e.g. by an untrusted
+    /// party with `generate_witness`. The witness is checked to satisfy the
+    /// circuit before proving.
+    ///
+    /// `circuit` and `prove_key` has to be consistent (with the same evaluation
+    /// domain etc.), otherwise return error. Return error as well if the
+    /// witness does not match the variables of the circuit or does not
+    /// satisfy it.
+    pub fn prove_with_witness<R, T>(
+        prng: &mut R,
+        circuit: &PlonkCircuit<E::Fr>,
+        prove_key: &ProvingKey<'a, E>,
+        witness: &WitnessVector<E::Fr>,
+        extra_transcript_init_msg: Option<Vec<u8>>,
+    ) -> Result<Proof<E>, PlonkError>
+    where
+        R: CryptoRng + RngCore,
+        T: PlonkTranscript<F>,
+    {
+        let mut circuit = circuit.clone();
+        circuit.set_witness(witness)?;
+        circuit.check_circuit_satisfiability(&circuit.public_input()?)?;
+        <Self as Snark<E>>::prove::<_, _, T>(prng, &circuit, prove_key, extra_transcript_init_msg)
+    }
+
     /// Verify a single aggregated Plonk proof.
     pub fn verify_batch_proof<T>(
         verify_keys: &[&VerifyingKey<E>],
@@ -200,6 +237,13 @@ where
     }
 
     /// Batch verify multiple SNARK proofs (w.r.t. different verifying keys).
+    ///
+    /// The pairing checks of the proofs are combined with the powers of a
+    /// random challenge into a single multi-pairing, c.f.
+    /// `Verifier::batch_verify_opening_proofs`, so that the cost of
+    /// verifying many proofs is dominated by the multi-scalar
+    /// multiplications rather than the pairings. The verifying keys have to be
+    /// derived from the same SRS, otherwise return error.
     pub fn batch_verify<T>(
         verify_keys: &[&VerifyingKey<E>],
         public_inputs: &[&[E::Fr]],
@@ -228,6 +272,14 @@ where
                 ParameterError("the number of instances cannot be zero".to_string()).into(),
             );
         }
+        if verify_keys
+            .iter()
+            .any(|vk| vk.open_key != verify_keys[0].open_key)
+        {
+            return Err(
+                ParameterError("the verifying keys have different open keys".to_string()).into(),
+            );
+        }
 
         let pcs_infos = verify_keys
             .par_iter()
@@ -375,8 +427,12 @@ where
         }
 
         // Round 2
-        challenges.beta = transcript.get_and_append_challenge::<E>(b"beta")?;
-        challenges.gamma = transcript.get_and_append_challenge::<E>(b"gamma")?;
+        // beta and gamma are derived together, from a single sponge invocation
+        // for a sponge based transcript
+        let beta_gamma =
+            transcript.get_and_append_challenges::<E>(&[b"beta".as_ref(), b"gamma".as_ref()])?;
+        challenges.beta = beta_gamma[0];
+        challenges.gamma = beta_gamma[1];
         let mut prod_perm_poly_comms_vec = vec![];
         for i in 0..circuits.len() {
             let (prod_perm_poly_comm, prod_perm_poly) =
@@ -586,7 +642,10 @@ where
 #[cfg(test)]
 pub mod test {
     use crate::{
-        circuit::{customized::ecc::SWToTEConParam, Arithmetization, Circuit, PlonkCircuit},
+        circuit::{
+            customized::ecc::SWToTEConParam, generate_witness, Arithmetization, Circuit,
+            CompiledCircuit, PlonkCircuit, WitnessVector,
+        },
         constants::GATE_WIDTH,
         errors::PlonkError,
         proof_system::{
@@ -992,6 +1051,19 @@ pub mod test {
             PlonkKzgSnark::<E>::batch_verify::<T>(&vec![], &vec![], &vec![], &vec![],).is_err()
         );
 
+        // Verifying keys from different SRSs
+        let mut bad_vk = vk2.clone();
+        bad_vk.open_key.beta_h = bad_vk.open_key.h;
+        let mut bad_vks = vks.clone();
+        bad_vks[5] = &bad_vk;
+        assert!(PlonkKzgSnark::<E>::batch_verify::<T>(
+            &bad_vks,
+            &public_inputs_ref,
+            &proofs_ref,
+            &extra_msgs,
+        )
+        .is_err());
+
         // Error paths
         let tmp_pi_ref = public_inputs_ref[0];
         public_inputs_ref[0] = public_inputs_ref[1];
@@ -1496,6 +1568,161 @@ pub mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_named_public_inputs() -> Result<(), PlonkError> {
+        test_named_public_inputs_helper::<Bn254, Fq254, _>()?;
+        test_named_public_inputs_helper::<Bls12_377, Fq377, _>()?;
+        test_named_public_inputs_helper::<Bls12_381, Fq381, _>()?;
+        test_named_public_inputs_helper::<BW6_761, Fq761, _>()
+    }
+
+    fn test_named_public_inputs_helper<E, F, P>() -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let rng = &mut test_rng();
+        let mut circuit = PlonkCircuit::<E::Fr>::new_turbo_plonk();
+        let x = circuit.create_named_public_variable("x", E::Fr::from(3u32))?;
+        let y = circuit.create_variable(E::Fr::from(4u32))?;
+        let z = circuit.mul(x, y)?;
+        circuit.set_variable_public_named("z", z)?;
+        circuit.finalize_for_arithmetization()?;
+
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+        assert_eq!(vk.public_input_position("z"), Some(1));
+        assert_eq!(vk.public_input_position("y"), None);
+        assert_eq!(
+            vk.public_input_names(),
+            vec![("x".to_string(), 0), ("z".to_string(), 1)]
+        );
+        let mut ser_bytes = Vec::new();
+        vk.serialize(&mut ser_bytes)?;
+        assert_eq!(VerifyingKey::<E>::deserialize(&ser_bytes[..])?, vk);
+
+        // the public input is assembled by name
+        let proof =
+            PlonkKzgSnark::<E>::prove::<_, _, StandardTranscript>(rng, &circuit, &pk, None)?;
+        let pub_input =
+            vk.named_public_input(&[("z", E::Fr::from(12u32)), ("x", E::Fr::from(3u32))])?;
+        assert_eq!(pub_input, circuit.public_input()?);
+        assert!(
+            PlonkKzgSnark::<E>::verify::<StandardTranscript>(&vk, &pub_input, &proof, None).is_ok()
+        );
+
+        // missing, unknown or repeated names
+        assert!(vk.named_public_input(&[("x", E::Fr::one())]).is_err());
+        assert!(vk
+            .named_public_input(&[("x", E::Fr::one()), ("y", E::Fr::one())])
+            .is_err());
+        assert!(vk
+            .named_public_input(&[
+                ("x", E::Fr::one()),
+                ("z", E::Fr::one()),
+                ("x", E::Fr::one())
+            ])
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_prove_with_witness() -> Result<(), PlonkError> {
+        test_prove_with_witness_helper::<Bn254, Fq254, _>(PlonkType::TurboPlonk)?;
+        test_prove_with_witness_helper::<Bn254, Fq254, _>(PlonkType::UltraPlonk)?;
+        test_prove_with_witness_helper::<Bls12_377, Fq377, _>(PlonkType::TurboPlonk)?;
+        test_prove_with_witness_helper::<Bls12_377, Fq377, _>(PlonkType::UltraPlonk)?;
+        test_prove_with_witness_helper::<Bls12_381, Fq381, _>(PlonkType::TurboPlonk)?;
+        test_prove_with_witness_helper::<Bls12_381, Fq381, _>(PlonkType::UltraPlonk)?;
+        test_prove_with_witness_helper::<BW6_761, Fq761, _>(PlonkType::TurboPlonk)?;
+        test_prove_with_witness_helper::<BW6_761, Fq761, _>(PlonkType::UltraPlonk)
+    }
+
+    fn test_prove_with_witness_helper<E, F, P>(plonk_type: PlonkType) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let rng = &mut test_rng();
+        let new_circuit = |shape_only: bool| match (plonk_type, shape_only) {
+            (PlonkType::TurboPlonk, false) => PlonkCircuit::<E::Fr>::new_turbo_plonk(),
+            (PlonkType::TurboPlonk, true) => PlonkCircuit::new_turbo_plonk_shape_only(),
+            (PlonkType::UltraPlonk, false) => PlonkCircuit::new_ultra_plonk(4),
+            (PlonkType::UltraPlonk, true) => PlonkCircuit::new_ultra_plonk_shape_only(4),
+        };
+        // x^3 + x + 5 = y for the public input y, with x of 6 bits
+        let synthesize = |circuit: &mut PlonkCircuit<E::Fr>, x: E::Fr| -> Result<(), PlonkError> {
+            let x = circuit.create_variable(x)?;
+            circuit.enforce_in_range(x, 6)?;
+            let x2 = circuit.mul(x, x)?;
+            let x3 = circuit.mul(x2, x)?;
+            let y = circuit.add(x3, x)?;
+            let y = circuit.add_constant(y, &E::Fr::from(5u32))?;
+            circuit.set_variable_public(y)
+        };
+
+        // the prover compiles the circuit once, and reloads it
+        let mut shape = new_circuit(true);
+        synthesize(&mut shape, E::Fr::zero())?;
+        shape.finalize_for_arithmetization()?;
+        let mut bytes = Vec::new();
+        shape.compile()?.serialize(&mut bytes)?;
+        let circuit =
+            PlonkCircuit::<E::Fr>::from_compiled(&CompiledCircuit::deserialize(&bytes[..])?)?;
+        let srs = PlonkKzgSnark::<E>::universal_setup(circuit.srs_size()?, rng)?;
+        let (pk, vk) = PlonkKzgSnark::<E>::preprocess(&srs, &circuit)?;
+
+        // the witness is generated separately
+        let witness = generate_witness(new_circuit(false), synthesize, E::Fr::from(3u32))?;
+        let mut bytes = Vec::new();
+        witness.serialize(&mut bytes)?;
+        let witness = WitnessVector::deserialize(&bytes[..])?;
+        assert_eq!(witness.len(), circuit.num_vars());
+        let proof = PlonkKzgSnark::<E>::prove_with_witness::<_, StandardTranscript>(
+            rng, &circuit, &pk, &witness, None,
+        )?;
+        let pub_input = [E::Fr::from(35u32)];
+        assert!(
+            PlonkKzgSnark::<E>::verify::<StandardTranscript>(&vk, &pub_input, &proof, None).is_ok()
+        );
+
+        // a witness which does not satisfy or match the circuit
+        let bad_witness = generate_witness(new_circuit(false), synthesize, E::Fr::from(64u32))?;
+        assert!(
+            PlonkKzgSnark::<E>::prove_with_witness::<_, StandardTranscript>(
+                rng,
+                &circuit,
+                &pk,
+                &bad_witness,
+                None
+            )
+            .is_err()
+        );
+        let bad_witness = generate_witness(
+            new_circuit(false),
+            |circuit, x| {
+                synthesize(circuit, x)?;
+                circuit.create_variable(x)?;
+                Ok(())
+            },
+            E::Fr::from(3u32),
+        )?;
+        assert!(
+            PlonkKzgSnark::<E>::prove_with_witness::<_, StandardTranscript>(
+                rng,
+                &circuit,
+                &pk,
+                &bad_witness,
+                None
+            )
+            .is_err()
+        );
+        assert!(generate_witness(new_circuit(true), synthesize, E::Fr::from(3u32)).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_key_aggregation_and_batch_prove() -> Result<(), PlonkError> {
         // merlin transcripts
@@ -11,7 +11,9 @@ use crate::{
             ecc::{Point, SWToTEConParam},
             ultraplonk::{
                 mod_arith::FpElemVar,
-                plonk_verifier::{BatchProofVar, ProofEvaluationsVar},
+                plonk_verifier::{
+                    BatchProofVar, PlookupEvaluationsVar, PlookupProofVar, ProofEvaluationsVar,
+                },
             },
         },
         PlonkCircuit,
@@ -33,7 +35,7 @@ use ark_std::{
     collections::HashMap,
     convert::{TryFrom, TryInto},
     format,
-    string::ToString,
+    string::{String, ToString},
     vec,
     vec::Vec,
 };
@@ -345,6 +347,73 @@ impl<T: PrimeField> ProofEvaluations<T> {
     }
 }
 
+impl<T: PrimeField> PlookupEvaluations<T> {
+    /// create variables for the PlookupEvaluations who's field
+    /// is smaller than plonk circuit field.
+    /// The output wires are in the FpElemVar form.
+    pub(crate) fn create_variables<F>(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        m: usize,
+        two_power_m: Option<F>,
+    ) -> Result<PlookupEvaluationsVar<F>, PlonkError>
+    where
+        F: RescueParameter + SWToTEConParam,
+    {
+        if T::size_in_bits() >= F::size_in_bits() {
+            return Err(PlonkError::InvalidParameters(format!(
+                "circuit field size {} is not greater than Plookup Evaluation field size {}",
+                F::size_in_bits(),
+                T::size_in_bits()
+            )));
+        }
+        let mut create_var =
+            |x: &T| FpElemVar::new_from_field_element(circuit, &field_switching(x), m, two_power_m);
+        Ok(PlookupEvaluationsVar {
+            range_table_eval: create_var(&self.range_table_eval)?,
+            key_table_eval: create_var(&self.key_table_eval)?,
+            h_1_eval: create_var(&self.h_1_eval)?,
+            q_lookup_eval: create_var(&self.q_lookup_eval)?,
+            prod_next_eval: create_var(&self.prod_next_eval)?,
+            range_table_next_eval: create_var(&self.range_table_next_eval)?,
+            key_table_next_eval: create_var(&self.key_table_next_eval)?,
+            h_1_next_eval: create_var(&self.h_1_next_eval)?,
+            h_2_next_eval: create_var(&self.h_2_next_eval)?,
+            q_lookup_next_eval: create_var(&self.q_lookup_next_eval)?,
+            w_3_next_eval: create_var(&self.w_3_next_eval)?,
+            w_4_next_eval: create_var(&self.w_4_next_eval)?,
+        })
+    }
+}
+
+impl<E: PairingEngine> PlookupProof<E> {
+    /// Create a `PlookupProofVar` variable from a `PlookupProof`.
+    pub(crate) fn create_variables<F, P>(
+        &self,
+        circuit: &mut PlonkCircuit<F>,
+        m: usize,
+        two_power_m: Option<F>,
+    ) -> Result<PlookupProofVar<F>, PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        F: RescueParameter + SWToTEConParam,
+        P: SWModelParameters<BaseField = F> + Clone,
+    {
+        let h_poly_comms = self
+            .h_poly_comms
+            .iter()
+            .map(|comm| circuit.create_point_variable(Point::from(&comm.0)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let prod_lookup_poly_comm =
+            circuit.create_point_variable(Point::from(&self.prod_lookup_poly_comm.0))?;
+        Ok(PlookupProofVar {
+            h_poly_comms,
+            prod_lookup_poly_comm,
+            poly_evals: self.poly_evals.create_variables(circuit, m, two_power_m)?,
+        })
+    }
+}
+
 impl<E: PairingEngine> BatchProof<E> {
     /// Create a `BatchProofVar` variable from a `BatchProof`.
     pub fn create_variables<F, P>(
@@ -379,6 +448,16 @@ impl<E: PairingEngine> BatchProof<E> {
             .map(|x| x.create_variables(circuit, m, two_power_m))
             .collect::<Result<Vec<_>, _>>()?;
 
+        let plookup_proofs_vec = self
+            .plookup_proofs_vec
+            .iter()
+            .map(|x| {
+                x.as_ref()
+                    .map(|proof| proof.create_variables(circuit, m, two_power_m))
+                    .transpose()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
         let mut split_quot_poly_comms = Vec::new();
         for e in self.split_quot_poly_comms.iter() {
             let p: Point<F> = (&e.0).into();
@@ -395,6 +474,7 @@ impl<E: PairingEngine> BatchProof<E> {
             wires_poly_comms_vec,
             prod_perm_poly_comms_vec,
             poly_evals_vec,
+            plookup_proofs_vec,
             split_quot_poly_comms,
             opening_proof,
             shifted_opening_proof,
@@ -664,6 +744,20 @@ pub struct VerifyingKey<E: PairingEngine> {
 
     /// Plookup verifying key, None if not support lookup.
     pub(crate) plookup_vk: Option<PlookupVerifyingKey<E>>,
+
+    /// The names of the named public inputs, in increasing order of
+    /// positions.
+    pub(crate) pub_input_names: Vec<PublicInputName>,
+}
+
+/// The name of a public input of a circuit and its position among the public
+/// inputs, c.f. `PlonkCircuit::set_variable_public_named`.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct PublicInputName {
+    /// The UTF-8 encoding of the name.
+    pub(crate) name: Vec<u8>,
+    /// The position of the public input.
+    pub(crate) position: usize,
 }
 
 impl<E, F, P1, P2> From<VerifyingKey<E>> for Vec<E::Fq>
@@ -750,8 +844,54 @@ impl<E: PairingEngine> VerifyingKey<E> {
             open_key: OpenKey::default(),
             is_merged: false,
             plookup_vk: None,
+            pub_input_names: vec![],
         }
     }
+
+    /// The position among the public inputs of the public input named
+    /// `name`, if any.
+    pub fn public_input_position(&self, name: &str) -> Option<usize> {
+        self.pub_input_names
+            .iter()
+            .find(|n| n.name == name.as_bytes())
+            .map(|n| n.position)
+    }
+
+    /// The names of the named public inputs with their positions, in
+    /// increasing order of positions.
+    pub fn public_input_names(&self) -> Vec<(String, usize)> {
+        self.pub_input_names
+            .iter()
+            .map(|n| (String::from_utf8_lossy(&n.name).into_owned(), n.position))
+            .collect()
+    }
+
+    /// Assemble the public input of the circuit from the values of its named
+    /// public inputs, all of which have to be named.
+    /// Return error if a name is unknown or given twice, or if a public input
+    /// has no value.
+    pub fn named_public_input(&self, values: &[(&str, E::Fr)]) -> Result<Vec<E::Fr>, PlonkError> {
+        let mut pub_input = vec![None; self.num_inputs];
+        for &(name, value) in values.iter() {
+            let position = self
+                .public_input_position(name)
+                .ok_or_else(|| ParameterError(format!("unknown public input name {}", name)))?;
+            if pub_input[position].replace(value).is_some() {
+                return Err(
+                    ParameterError(format!("the public input {} is given twice", name)).into(),
+                );
+            }
+        }
+        pub_input
+            .into_iter()
+            .enumerate()
+            .map(|(position, value)| {
+                value.ok_or_else(|| {
+                    ParameterError(format!("the public input {} has no value", position)).into()
+                })
+            })
+            .collect()
+    }
     /// Merge with another TurboPlonk verifying key to obtain a new TurboPlonk
     /// verifying key. Return error if any of the following holds:
     /// 1. the other verifying key has a different domain size;
@@ -791,6 +931,18 @@ impl<E: PairingEngine> VerifyingKey<E> {
             .map(|(com1, com2)| Commitment(com1.0 + com2.0))
             .collect();
 
+        // the names of the public inputs of `other_vk` which are not names of
+        // those of `self` are kept
+        let mut pub_input_names = self.pub_input_names.clone();
+        for n in other_vk.pub_input_names.iter() {
+            if !self.pub_input_names.iter().any(|m| m.name == n.name) {
+                pub_input_names.push(PublicInputName {
+                    name: n.name.clone(),
+                    position: n.position + self.num_inputs,
+                });
+            }
+        }
+
         Ok(Self {
             domain_size: self.domain_size,
             num_inputs: self.num_inputs + other_vk.num_inputs,
@@ -800,6 +952,7 @@ impl<E: PairingEngine> VerifyingKey<E> {
             open_key: self.open_key.clone(),
             plookup_vk: None,
             is_merged: true,
+            pub_input_names,
         })
     }
 
@@ -292,8 +292,10 @@ where
             }
         }
 
-        let beta = transcript.get_and_append_challenge::<E>(b"beta")?;
-        let gamma = transcript.get_and_append_challenge::<E>(b"gamma")?;
+        // as the prover, derive beta and gamma together
+        let beta_gamma =
+            transcript.get_and_append_challenges::<E>(&[b"beta".as_ref(), b"gamma".as_ref()])?;
+        let (beta, gamma) = (beta_gamma[0], beta_gamma[1]);
         for prod_perm_poly_comm in batch_proof.prod_perm_poly_comms_vec.iter() {
             transcript.append_commitment(b"perm_poly_comms", prod_perm_poly_comm)?;
         }
@@ -878,7 +880,7 @@ where
     /// * pub_input[l/2+i]
     ///
     /// TODO: reuse the lagrange values
-    fn evaluate_pi_poly(
+    pub(crate) fn evaluate_pi_poly(
         &self,
         pub_input: &[E::Fr],
         z: &E::Fr,
@@ -4,19 +4,25 @@
 // You should have received a copy of the MIT License
 // along with the Jellyfish library. If not, see <https://mit-license.org/>.
 
-//! This module implements three different types of transcripts that are
+//! This module implements four different types of transcripts that are
 //! supported.
 
+pub(crate) mod poseidon;
 pub(crate) mod rescue;
 pub(crate) mod solidity;
 pub(crate) mod standard;
 
-pub use rescue::RescueTranscript;
+pub use poseidon::PoseidonTranscript;
+pub use rescue::{RescueTranscript, RescueTranscriptState};
 pub use solidity::SolidityTranscript;
+/// Transcript with the EVM-native `keccak256` hash function, for proofs that
+/// are verified on Ethereum. See `SolidityTranscript` for how a Solidity
+/// verifier reproduces the challenges.
+pub type KeccakTranscript = SolidityTranscript;
 pub use standard::StandardTranscript;
 
 use crate::{
-    errors::PlonkError,
+    errors::{PlonkError, SnarkError::ParameterError},
     proof_system::structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
 };
 use ark_ec::{
@@ -24,6 +30,7 @@ use ark_ec::{
 };
 use ark_ff::PrimeField;
 use ark_poly_commit::kzg10::Commitment;
+use ark_std::{string::ToString, vec::Vec};
 use jf_utils::to_bytes;
 
 /// Defines transcript APIs.
@@ -33,9 +40,10 @@ use jf_utils::to_bytes;
 ///
 /// The transcript can be either a Merlin transcript
 /// (instantiated with Sha-3/keccak), or a Rescue transcript
-/// (instantiated with Rescue hash), or a Solidity-friendly transcript
+/// (instantiated with Rescue hash), or a Poseidon transcript
+/// (instantiated with Poseidon hash), or a Solidity-friendly transcript
 /// (instantiated with Keccak256 hash).
-/// The second is only used for recursive snarks.
+/// The second and the third are only used for recursive snarks.
 pub trait PlonkTranscript<F> {
     /// Create a new plonk transcript.
     fn new(label: &'static [u8]) -> Self;
@@ -208,4 +216,29 @@ pub trait PlonkTranscript<F> {
     fn get_and_append_challenge<E>(&mut self, label: &'static [u8]) -> Result<E::Fr, PlonkError>
     where
         E: PairingEngine;
+
+    /// Generate one challenge per label in `labels` for the current
+    /// transcript, with no message appended in between, and then append them,
+    /// in order, to the transcript.
+    ///
+    /// By default this is the same as calling `get_and_append_challenge` for
+    /// each label. A sponge based transcript may squeeze all of them from a
+    /// single sponge invocation instead, c.f.
+    /// `RescueTranscript::get_and_append_n_challenges`.
+    /// Return error if `labels` is empty.
+    fn get_and_append_challenges<E>(
+        &mut self,
+        labels: &[&'static [u8]],
+    ) -> Result<Vec<E::Fr>, PlonkError>
+    where
+        E: PairingEngine,
+    {
+        if labels.is_empty() {
+            return Err(ParameterError("the number of challenges is zero".to_string()).into());
+        }
+        labels
+            .iter()
+            .map(|label| self.get_and_append_challenge::<E>(label))
+            .collect()
+    }
 }
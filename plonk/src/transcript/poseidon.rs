@@ -0,0 +1,213 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! This module defines the Poseidon transcript.
+use super::PlonkTranscript;
+use crate::{
+    circuit::customized::{
+        ecc::{Point, SWToTEConParam},
+        poseidon::PoseidonParams,
+    },
+    errors::PlonkError,
+    proof_system::structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
+};
+use ark_ec::{
+    short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters as SWParam,
+};
+use ark_ff::PrimeField;
+use ark_poly_commit::kzg10::Commitment;
+use ark_std::{vec, vec::Vec};
+use jf_utils::{bytes_to_field_elements, field_switching, fq_to_fr_with_mask};
+
+/// Width of the Poseidon permutation of the transcript: a capacity element
+/// followed by 3 rate elements, the rate of the Rescue transcript.
+pub(crate) const POSEIDON_TRANSCRIPT_WIDTH: usize = 4;
+/// Number of full rounds of the Poseidon permutation of the transcript.
+const POSEIDON_TRANSCRIPT_FULL_ROUNDS: usize = 8;
+/// Number of partial rounds of the Poseidon permutation of the transcript,
+/// which targets the 128-bit security level with the `x^5` S-box for a width
+/// of 4, as circomlib's Poseidon of the same width.
+const POSEIDON_TRANSCRIPT_PARTIAL_ROUNDS: usize = 56;
+
+/// The parameters of the Poseidon permutation of the transcript over `F`,
+/// generated with the Grain LFSR, c.f. `PoseidonParams::from_grain_lfsr`.
+/// Return error if `x^5` is not a permutation of `F`.
+pub(crate) fn poseidon_transcript_params<F: PrimeField>() -> Result<PoseidonParams<F>, PlonkError> {
+    PoseidonParams::from_grain_lfsr(
+        POSEIDON_TRANSCRIPT_WIDTH,
+        POSEIDON_TRANSCRIPT_FULL_ROUNDS,
+        POSEIDON_TRANSCRIPT_PARTIAL_ROUNDS,
+    )
+}
+
+/// Transcript with the Poseidon hash function, for recursive verifiers and
+/// for interoperability with Poseidon based proof systems.
+///
+/// It follows `RescueTranscript` with the Poseidon permutation instead of the
+/// Rescue one: the elements are appended to a vector, and when a challenge is
+/// to be generated,
+///
+/// 1. state = absorb(state, transcript)
+/// 2. challenge = state\[1\]
+/// 3. transcript = vec!\[challenge\]
+///
+/// where `absorb` is a duplex sponge step: the transcript, padded with a one
+/// and then zeros to a multiple of the rate, is added block by block into the
+/// rate part of the state, i.e. after the capacity element at index 0, each
+/// block followed by a Poseidon permutation. The state is initialized with 0.
+///
+/// The Poseidon permutation has 8 full and 56 partial rounds with the `x^5`
+/// S-box, which must be a permutation of `F`. This holds for the base fields
+/// of BN254, BLS12-377, BLS12-381 and BW6-761; creating a transcript over
+/// another field panics otherwise.
+pub struct PoseidonTranscript<F: PrimeField> {
+    params: PoseidonParams<F>,
+    transcript: Vec<F>,
+    state: Vec<F>,
+}
+
+impl<F: PrimeField> PoseidonTranscript<F> {
+    // Absorb the pending transcript into a copy of the state.
+    fn absorb(&self) -> Result<Vec<F>, PlonkError> {
+        let rate = POSEIDON_TRANSCRIPT_WIDTH - 1;
+        let mut padded = self.transcript.clone();
+        padded.push(F::one());
+        padded.resize((padded.len() + rate - 1) / rate * rate, F::zero());
+
+        let mut state = self.state.clone();
+        for block in padded.chunks_exact(rate) {
+            for (s, b) in state[1..].iter_mut().zip(block.iter()) {
+                *s += b;
+            }
+            state = self.params.eval(&state)?;
+        }
+        Ok(state)
+    }
+}
+
+impl<F> PlonkTranscript<F> for PoseidonTranscript<F>
+where
+    F: PrimeField + SWToTEConParam,
+{
+    /// Create a new plonk transcript. `_label` is omitted for efficiency.
+    fn new(_label: &'static [u8]) -> Self {
+        PoseidonTranscript {
+            params: poseidon_transcript_params()
+                .expect("x^5 is not a permutation of the transcript field"),
+            transcript: Vec::new(),
+            state: vec![F::zero(); POSEIDON_TRANSCRIPT_WIDTH],
+        }
+    }
+
+    fn append_vk_and_pub_input<E, P>(
+        &mut self,
+        vk: &VerifyingKey<E>,
+        pub_input: &[E::Fr],
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        P: SWParam<BaseField = F> + Clone,
+    {
+        // as for the Rescue transcript, the field size in bits, the domain
+        // size, the number of inputs and the wire subsets separators are
+        // omitted for a more efficient verifier circuit
+        for com in vk.selector_comms.iter().chain(vk.sigma_comms.iter()) {
+            // convert the SW form commitments into TE form
+            let te_point: Point<F> = (&com.0).into();
+            self.transcript.push(te_point.get_x());
+            self.transcript.push(te_point.get_y());
+        }
+        for e in pub_input {
+            self.transcript.push(field_switching(e))
+        }
+        Ok(())
+    }
+
+    /// Append the message to the transcript. `_label` is omitted for
+    /// efficiency.
+    fn append_message(&mut self, _label: &'static [u8], msg: &[u8]) -> Result<(), PlonkError> {
+        let mut f = bytes_to_field_elements(&msg);
+        self.transcript.append(&mut f);
+        Ok(())
+    }
+
+    /// Append a single commitment to the transcript. `_label` is omitted for
+    /// efficiency. The commitment is absorbed in TE form; in particular, a
+    /// commitment to the zero polynomial (the point at infinity) is absorbed
+    /// as the TE neutral point (0, 1).
+    fn append_commitment<E, P>(
+        &mut self,
+        _label: &'static [u8],
+        comm: &Commitment<E>,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
+        P: SWParam<BaseField = F> + Clone,
+    {
+        let te_point: Point<F> = (&comm.0).into();
+        self.transcript.push(te_point.get_x());
+        self.transcript.push(te_point.get_y());
+        Ok(())
+    }
+
+    /// Append a challenge to the transcript. `_label` is omitted for
+    /// efficiency.
+    fn append_challenge<E>(
+        &mut self,
+        _label: &'static [u8],
+        challenge: &E::Fr,
+    ) -> Result<(), PlonkError>
+    where
+        E: PairingEngine<Fq = F>,
+    {
+        self.transcript.push(field_switching(challenge));
+        Ok(())
+    }
+
+    fn append_proof_evaluations<E: PairingEngine>(
+        &mut self,
+        evals: &ProofEvaluations<E::Fr>,
+    ) -> Result<(), PlonkError> {
+        for e in evals
+            .wires_evals
+            .iter()
+            .chain(evals.wire_sigma_evals.iter())
+        {
+            self.transcript.push(field_switching(e))
+        }
+        self.transcript.push(field_switching(&evals.perm_next_eval));
+        Ok(())
+    }
+
+    fn append_plookup_evaluations<E: PairingEngine>(
+        &mut self,
+        evals: &PlookupEvaluations<E::Fr>,
+    ) -> Result<(), PlonkError> {
+        for eval in evals
+            .evals_vec()
+            .iter()
+            .chain(evals.next_evals_vec().iter())
+        {
+            self.transcript.push(field_switching(eval));
+        }
+        Ok(())
+    }
+
+    /// Generate the challenge for the current transcript, and then append it
+    /// to the transcript. `_label` is omitted for efficiency. The challenge
+    /// is the lowest `E::Fr::size_in_bits() / 8` bytes of the squeezed
+    /// element, c.f. `fq_to_fr_with_mask`.
+    fn get_and_append_challenge<E>(&mut self, _label: &'static [u8]) -> Result<E::Fr, PlonkError>
+    where
+        E: PairingEngine,
+    {
+        let state = self.absorb()?;
+        let challenge = fq_to_fr_with_mask::<F, E::Fr>(&state[1]);
+        self.state = state;
+        self.transcript = vec![field_switching(&challenge)];
+        Ok(challenge)
+    }
+}
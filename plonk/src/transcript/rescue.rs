@@ -7,16 +7,21 @@
 //! This module is a defines rescue transcript.
 use super::PlonkTranscript;
 use crate::{
-    circuit::customized::ecc::{Point, SWToTEConParam},
-    errors::PlonkError,
+    circuit::customized::{
+        ecc::{Point, SWToTEConParam},
+        ultraplonk::mod_arith::FpElem,
+    },
+    errors::{PlonkError, SnarkError::ParameterError},
     proof_system::structs::{PlookupEvaluations, ProofEvaluations, VerifyingKey},
 };
 use ark_ec::{
     short_weierstrass_jacobian::GroupAffine, PairingEngine, SWModelParameters as SWParam,
 };
+use ark_ff::{BigInteger, PrimeField};
 use ark_poly_commit::kzg10::Commitment;
-use ark_std::vec::Vec;
-use jf_rescue::{Permutation as RescueHash, RescueParameter, STATE_SIZE};
+use ark_serialize::*;
+use ark_std::{format, string::ToString, vec, vec::Vec};
+use jf_rescue::{Permutation as RescueHash, RescueParameter, RescueVector, RATE, STATE_SIZE};
 use jf_utils::{bytes_to_field_elements, field_switching, fq_to_fr_with_mask};
 
 /// Transcript with rescue hash function.
@@ -29,15 +34,372 @@ use jf_utils::{bytes_to_field_elements, field_switching, fq_to_fr_with_mask};
 /// and when a challenge is to be generated,
 /// we reset the state with the fresh challenge.
 ///
-/// 1. state: \[F: STATE_SIZE\] = hash(state|transcript)
+/// 1. state: \[F: STATE_SIZE\] = absorb(state, transcript)
 /// 2. challenge = state\[0\]
 /// 3. transcript = vec!\[challenge\]
+///
+/// where `absorb` is a duplex sponge step: the transcript, padded with a one
+/// and then zeros to a multiple of the rate, is added block by block into the
+/// rate part of the state, each block followed by a Rescue permutation. The
+/// capacity element of the state is carried over from one challenge to the
+/// next and is never absorbed as data.
 pub struct RescueTranscript<F>
 where
     F: RescueParameter,
 {
     transcript: Vec<F>,
     state: [F; STATE_SIZE],
+    permutation_count: usize,
+    merlin_compat: bool,
+    absorb_labels: bool,
+    labels: Vec<Vec<u8>>,
+    challenge_bit_len: Option<usize>,
+}
+
+/// A snapshot of the Fiat-Shamir state of a `RescueTranscript`: the sponge
+/// state and the elements that are absorbed but not yet hashed into it.
+///
+/// It can be serialized to checkpoint a transcript, or to hand it over to
+/// another process, and restored with `RescueTranscript::from_state`, or
+/// with `RescueTranscriptVar::from_state` inside a circuit.
+#[derive(Debug, Clone, PartialEq, Eq, CanonicalSerialize, CanonicalDeserialize)]
+pub struct RescueTranscriptState<F: PrimeField> {
+    pub(crate) state: Vec<F>,
+    pub(crate) pending: Vec<F>,
+    pub(crate) permutation_count: usize,
+    pub(crate) merlin_compat: bool,
+    pub(crate) absorb_labels: bool,
+    pub(crate) labels: Vec<Vec<u8>>,
+    pub(crate) challenge_bit_len: Option<usize>,
+}
+
+impl<F: PrimeField> RescueTranscriptState<F> {
+    // Check that the state has the expected size, which is not guaranteed for
+    // a deserialized state.
+    pub(crate) fn check(&self) -> Result<(), PlonkError> {
+        if self.state.len() != STATE_SIZE {
+            return Err(ParameterError(format!(
+                "transcript state has {} elements, expected {}",
+                self.state.len(),
+                STATE_SIZE
+            ))
+            .into());
+        }
+        Ok(())
+    }
+}
+
+impl<F> RescueTranscript<F>
+where
+    F: RescueParameter,
+{
+    /// Number of field elements that are absorbed but not yet hashed into the
+    /// state.
+    pub fn absorbed_len(&self) -> usize {
+        self.transcript.len()
+    }
+
+    /// Iterate, in order, over the field elements absorbed since the last
+    /// challenge was squeezed (the last challenge included).
+    pub fn iter_pending(&self) -> impl Iterator<Item = F> + '_ {
+        self.transcript.iter().copied()
+    }
+
+    /// Number of sponge invocations performed so far to squeeze challenges:
+    /// one per squeezed challenge, or, for `get_and_append_n_challenges`, one
+    /// per `RATE` challenges squeezed at once.
+    pub fn permutation_count(&self) -> usize {
+        self.permutation_count
+    }
+
+    /// Enable or disable the Merlin compatible mode (disabled by default).
+    ///
+    /// When enabled, `append_vk_and_pub_input` additionally absorbs the
+    /// messages that are otherwise omitted for a cheaper verifier circuit,
+    /// in the same order as the Merlin transcript: the field size in bits,
+    /// the domain size, the number of inputs and the wire subsets
+    /// separators. Each of them is absorbed as a single field element.
+    /// A `RescueTranscriptVar` in the verifier circuit must use the same mode.
+    pub fn set_merlin_compat(&mut self, enabled: bool) {
+        self.merlin_compat = enabled;
+    }
+
+    /// Enable or disable the absorption of labels (disabled by default).
+    ///
+    /// By default labels are omitted for efficiency. When enabled, the labels
+    /// used since the last challenge, including the label of the challenge
+    /// itself, are hashed into a single tag that is absorbed right before the
+    /// challenge is squeezed, so that each challenge is bound to the labels
+    /// of its phase. Consecutive repetitions of a label are recorded once,
+    /// e.g., for a message or a slice of commitments absorbed element-wise.
+    /// A `RescueTranscriptVar` in the verifier circuit must use the same mode.
+    pub fn set_absorb_labels(&mut self, enabled: bool) {
+        self.absorb_labels = enabled;
+    }
+
+    /// Set the bit length of the challenges, or reset it to the default with
+    /// `None`.
+    ///
+    /// By default a challenge is the lowest `E::Fr::size_in_bits() / 8` bytes
+    /// of the squeezed element. A custom bit length `bit_len` trades the
+    /// soundness of the challenges, which are uniform in `[0, 2^bit_len)`,
+    /// against the cost of the truncation in the verifier circuit. It must be
+    /// within `[1, E::Fr::size_in_bits())`, which is checked when a challenge
+    /// is squeezed. A `RescueTranscriptVar` in the verifier circuit must use
+    /// the same bit length.
+    pub fn set_challenge_bit_len(&mut self, bit_len: Option<usize>) {
+        self.challenge_bit_len = bit_len;
+    }
+
+    /// Run `f` within the domain `domain`: a domain-separation element is
+    /// absorbed before running `f`, and a closing element is absorbed after
+    /// it succeeds. Nested calls compose, so that the same appends under
+    /// different domain stacks produce different challenges.
+    /// A `RescueTranscriptVar` absorbs the same elements in its `with_domain`.
+    pub fn with_domain<R>(
+        &mut self,
+        domain: &'static [u8],
+        f: impl FnOnce(&mut Self) -> Result<R, PlonkError>,
+    ) -> Result<R, PlonkError> {
+        self.transcript
+            .append(&mut domain_separator_elems(domain, true));
+        let res = f(self)?;
+        self.transcript
+            .append(&mut domain_separator_elems(domain, false));
+        Ok(res)
+    }
+
+    /// Derive an independent child transcript for the sub-protocol `label`.
+    ///
+    /// The child starts from the current state and pending elements of this
+    /// transcript, followed by a fork separator for `label`, so that children
+    /// with different labels, and this transcript itself, derive different
+    /// challenges from then on. This transcript is left unchanged. To fork
+    /// one child per instance, append the instance index to each child.
+    /// A `RescueTranscriptVar` absorbs the same elements in its `fork`.
+    pub fn fork(&self, label: &'static [u8]) -> Self {
+        let mut transcript = self.transcript.clone();
+        transcript.extend(fork_separator_elems::<F>(label));
+        Self {
+            transcript,
+            state: self.state,
+            permutation_count: self.permutation_count,
+            merlin_compat: self.merlin_compat,
+            absorb_labels: self.absorb_labels,
+            labels: self.labels.clone(),
+            challenge_bit_len: self.challenge_bit_len,
+        }
+    }
+
+    /// Export the Fiat-Shamir state of the transcript, including the pending
+    /// elements, the permutation count and the Merlin compatible mode.
+    pub fn export_state(&self) -> RescueTranscriptState<F> {
+        RescueTranscriptState {
+            state: self.state.to_vec(),
+            pending: self.transcript.clone(),
+            permutation_count: self.permutation_count,
+            merlin_compat: self.merlin_compat,
+            absorb_labels: self.absorb_labels,
+            labels: self.labels.clone(),
+            challenge_bit_len: self.challenge_bit_len,
+        }
+    }
+
+    /// Restore a transcript from a state exported with `export_state`. The
+    /// restored transcript derives the same challenges as the original one
+    /// would from that point on.
+    pub fn from_state(state: &RescueTranscriptState<F>) -> Result<Self, PlonkError> {
+        state.check()?;
+        let mut sponge_state = [F::zero(); STATE_SIZE];
+        sponge_state.copy_from_slice(&state.state);
+        Ok(Self {
+            transcript: state.pending.clone(),
+            state: sponge_state,
+            permutation_count: state.permutation_count,
+            merlin_compat: state.merlin_compat,
+            absorb_labels: state.absorb_labels,
+            labels: state.labels.clone(),
+            challenge_bit_len: state.challenge_bit_len,
+        })
+    }
+
+    /// Append a 64-bit integer to the transcript as a single field element.
+    /// `label` is omitted for efficiency, c.f. `set_absorb_labels`.
+    pub fn append_u64(&mut self, label: &'static [u8], value: u64) -> Result<(), PlonkError> {
+        self.record_label(label);
+        self.transcript.push(F::from(value));
+        Ok(())
+    }
+
+    /// Append a field element in its `FpElem` form to the transcript as its
+    /// two limbs, matching `RescueTranscriptVar::append_fp_elem_var`.
+    /// `label` is omitted for efficiency, c.f. `set_absorb_labels`.
+    pub fn append_fp_elem(
+        &mut self,
+        label: &'static [u8],
+        fp_elem: &FpElem<F>,
+    ) -> Result<(), PlonkError> {
+        let (p0, p1) = fp_elem.components();
+        self.record_label(label);
+        self.transcript.push(p0);
+        self.transcript.push(p1);
+        Ok(())
+    }
+
+    /// Generate `n` challenges from a single sponge invocation, and then
+    /// append them, in order, to the transcript. `label` is omitted for
+    /// efficiency, c.f. `set_absorb_labels`.
+    ///
+    /// The transcript is absorbed as in `get_and_append_challenge`, and the
+    /// i-th challenge is the i-th rate element of the resulting state in
+    /// `E::Fr`. If more than `RATE` challenges are requested, the state is
+    /// permuted again to squeeze the next `RATE` ones. For `n = 1` this is the
+    /// same as `get_and_append_challenge`.
+    pub fn get_and_append_n_challenges<E>(
+        &mut self,
+        label: &'static [u8],
+        n: usize,
+    ) -> Result<Vec<E::Fr>, PlonkError>
+    where
+        E: PairingEngine,
+    {
+        if n == 0 {
+            return Err(ParameterError("the number of challenges is zero".to_string()).into());
+        }
+        let bit_len = checked_challenge_bit_len::<E::Fr>(self.challenge_bit_len)?;
+        self.append_label_tag(label);
+        let hasher = RescueHash::default();
+        let mut state = self.absorb();
+        let mut challenges = Vec::with_capacity(n);
+        loop {
+            let remaining = n - challenges.len();
+            challenges.extend(
+                state[..RATE]
+                    .iter()
+                    .take(remaining)
+                    .map(|x| squeezed_to_challenge::<F, E::Fr>(x, bit_len)),
+            );
+            if challenges.len() == n {
+                break;
+            }
+            state.copy_from_slice(&hasher.eval(&RescueVector::from(&state)).elems());
+        }
+
+        self.state = state;
+        self.transcript = challenges.iter().map(field_switching).collect();
+        self.record_label(label);
+        self.permutation_count += (n + RATE - 1) / RATE;
+
+        Ok(challenges)
+    }
+
+    // Record `label` for the label tag of the current phase, if labels are
+    // absorbed and `label` is not a repetition of the previous label.
+    fn record_label(&mut self, label: &[u8]) {
+        if self.absorb_labels && self.labels.last().map(|l| l.as_slice()) != Some(label) {
+            self.labels.push(label.to_vec());
+        }
+    }
+
+    // If labels are absorbed, record the challenge label `label` and append
+    // the tag of the labels of the current phase to the transcript.
+    fn append_label_tag(&mut self, label: &[u8]) {
+        if self.absorb_labels {
+            self.record_label(label);
+            self.transcript.push(label_tag(&self.labels));
+            self.labels.clear();
+        }
+    }
+
+    // Absorb the pending transcript into a copy of the state: the transcript,
+    // padded with a one and then zeros to a multiple of RATE, is added block by
+    // block into the rate part of the state, keeping the capacity element of
+    // the state as is, with a permutation after each block.
+    fn absorb(&self) -> [F; STATE_SIZE] {
+        let hasher = RescueHash::default();
+
+        let mut padded = self.transcript.clone();
+        padded.push(F::one());
+        padded.resize((padded.len() + RATE - 1) / RATE * RATE, F::zero());
+
+        let mut state = self.state;
+        for block in padded.chunks_exact(RATE) {
+            for (s, b) in state.iter_mut().zip(block.iter()) {
+                *s += b;
+            }
+            state.copy_from_slice(&hasher.eval(&RescueVector::from(&state)).elems());
+        }
+        state
+    }
+}
+
+/// The bit length of the challenges in the scalar field `T`: the given
+/// `bit_len`, which must be within `[1, T::size_in_bits())`, or by default the
+/// bit length of the lowest `T::size_in_bits() / 8` bytes.
+pub(crate) fn checked_challenge_bit_len<T: PrimeField>(
+    bit_len: Option<usize>,
+) -> Result<usize, PlonkError> {
+    match bit_len {
+        None => Ok((T::size_in_bits() >> 3) << 3),
+        Some(bit_len) if bit_len == 0 || bit_len >= T::size_in_bits() => {
+            Err(ParameterError(format!(
+                "challenge bit length {} is not within [1, {})",
+                bit_len,
+                T::size_in_bits()
+            ))
+            .into())
+        },
+        Some(bit_len) => Ok(bit_len),
+    }
+}
+
+// The challenge in `T` made of the lowest `bit_len` bits of the squeezed
+// element `base`. For a whole number of bytes this is `fq_to_fr_with_mask`.
+fn squeezed_to_challenge<F: PrimeField, T: PrimeField>(base: &F, bit_len: usize) -> T {
+    if bit_len == (T::size_in_bits() >> 3) << 3 {
+        return fq_to_fr_with_mask::<F, T>(base);
+    }
+    let mut bytes = base.into_repr().to_bytes_le();
+    bytes.truncate((bit_len + 7) / 8);
+    if bit_len % 8 != 0 {
+        if let Some(last) = bytes.last_mut() {
+            *last &= (1u8 << (bit_len % 8)) - 1;
+        }
+    }
+    T::from_le_bytes_mod_order(&bytes)
+}
+
+/// The tag that binds a sequence of labels in a rescue transcript: the
+/// Rescue sponge hash of the byte length of each label followed by its bytes
+/// packed into field elements.
+pub(crate) fn label_tag<F: RescueParameter>(labels: &[Vec<u8>]) -> F {
+    let mut elems = vec![];
+    for label in labels.iter() {
+        elems.push(F::from(label.len() as u64));
+        elems.extend(bytes_to_field_elements::<_, F>(label));
+    }
+    RescueHash::default().sponge_with_padding(&elems, 1)[0]
+}
+
+/// The field elements that delimit a domain `domain` in a rescue transcript:
+/// the byte length of `domain`, followed by its bytes packed into field
+/// elements, followed by 1 when opening the domain and 0 when closing it.
+pub(crate) fn domain_separator_elems<F: PrimeField>(domain: &[u8], opening: bool) -> Vec<F> {
+    let mut elems = vec![F::from(domain.len() as u64)];
+    elems.extend(bytes_to_field_elements::<_, F>(domain));
+    elems.push(if opening { F::one() } else { F::zero() });
+    elems
+}
+
+/// The field elements that separate a child transcript forked for `label`
+/// from its parent: the byte length of `label`, followed by its bytes packed
+/// into field elements, followed by 2, which distinguishes it from the
+/// delimiters of a domain.
+pub(crate) fn fork_separator_elems<F: PrimeField>(label: &[u8]) -> Vec<F> {
+    let mut elems = vec![F::from(label.len() as u64)];
+    elems.extend(bytes_to_field_elements::<_, F>(label));
+    elems.push(F::from(2u64));
+    elems
 }
 
 impl<F> PlonkTranscript<F> for RescueTranscript<F>
@@ -49,6 +411,11 @@ where
         RescueTranscript {
             transcript: Vec::new(),
             state: [F::zero(); STATE_SIZE],
+            permutation_count: 0,
+            merlin_compat: false,
+            absorb_labels: false,
+            labels: Vec::new(),
+            challenge_bit_len: None,
         }
     }
 
@@ -67,6 +434,15 @@ where
         //  - domain size
         //  - number of inputs
         //  - wire subsets separators
+        // unless the Merlin compatible mode is enabled
+        if self.merlin_compat {
+            self.transcript.push(F::from(E::Fr::size_in_bits() as u64));
+            self.transcript.push(F::from(vk.domain_size as u64));
+            self.transcript.push(F::from(vk.num_inputs as u64));
+            for ki in vk.k.iter() {
+                self.transcript.push(field_switching(ki));
+            }
+        }
 
         // selector commitments
         for com in vk.selector_comms.iter() {
@@ -90,27 +466,31 @@ where
         Ok(())
     }
 
-    /// Append the message to the transcript. `_label` is omitted for
-    /// efficiency.
-    fn append_message(&mut self, _label: &'static [u8], msg: &[u8]) -> Result<(), PlonkError> {
+    /// Append the message to the transcript. `label` is omitted for
+    /// efficiency, c.f. `set_absorb_labels`.
+    fn append_message(&mut self, label: &'static [u8], msg: &[u8]) -> Result<(), PlonkError> {
         // We remove the labels for better efficiency
+        self.record_label(label);
 
         let mut f = bytes_to_field_elements(&msg);
         self.transcript.append(&mut f);
         Ok(())
     }
 
-    /// Append a single commitment to the transcript. `_label` is omitted for
-    /// efficiency.
+    /// Append a single commitment to the transcript. `label` is omitted for
+    /// efficiency, c.f. `set_absorb_labels`. The commitment is absorbed in TE
+    /// form; in particular, a commitment to the zero polynomial (the point at
+    /// infinity) is absorbed as the TE neutral point (0, 1).
     fn append_commitment<E, P>(
         &mut self,
-        _label: &'static [u8],
+        label: &'static [u8],
         comm: &Commitment<E>,
     ) -> Result<(), PlonkError>
     where
         E: PairingEngine<Fq = F, G1Affine = GroupAffine<P>>,
         P: SWParam<BaseField = F> + Clone,
     {
+        self.record_label(label);
         // convert the SW form commitments into TE form
         let te_point: Point<F> = (&comm.0).into();
         // push the x and y coordinate of comm (in twisted
@@ -121,16 +501,17 @@ where
         Ok(())
     }
 
-    /// Append a challenge to the transcript. `_label` is omitted for
-    /// efficiency.
+    /// Append a challenge to the transcript. `label` is omitted for
+    /// efficiency, c.f. `set_absorb_labels`.
     fn append_challenge<E>(
         &mut self,
-        _label: &'static [u8],
+        label: &'static [u8],
         challenge: &E::Fr,
     ) -> Result<(), PlonkError>
     where
         E: PairingEngine<Fq = F>,
     {
+        self.record_label(label);
         self.transcript.push(field_switching(challenge));
         Ok(())
     }
@@ -140,11 +521,14 @@ where
         evals: &ProofEvaluations<E::Fr>,
     ) -> Result<(), PlonkError> {
         for e in &evals.wires_evals {
+            self.record_label(b"wire_evals");
             self.transcript.push(field_switching(e))
         }
         for e in &evals.wire_sigma_evals {
+            self.record_label(b"wire_sigma_evals");
             self.transcript.push(field_switching(e))
         }
+        self.record_label(b"perm_next_eval");
         self.transcript.push(field_switching(&evals.perm_next_eval));
         Ok(())
     }
@@ -163,25 +547,48 @@ where
     }
 
     /// Generate the challenge for the current transcript,
-    /// and then append it to the transcript. `_label` is omitted for
-    /// efficiency.
-    fn get_and_append_challenge<E>(&mut self, _label: &'static [u8]) -> Result<E::Fr, PlonkError>
+    /// and then append it to the transcript. `label` is omitted for
+    /// efficiency, c.f. `set_absorb_labels`.
+    fn get_and_append_challenge<E>(&mut self, label: &'static [u8]) -> Result<E::Fr, PlonkError>
     where
         E: PairingEngine,
     {
-        // 1. state: [F: STATE_SIZE] = hash(state|transcript)
+        // 1. state: [F: STATE_SIZE] = absorb(state, transcript)
         // 2. challenge = state[0] in Fr
         // 3. transcript = Vec::new()
 
-        let hasher = RescueHash::default();
+        let bit_len = checked_challenge_bit_len::<E::Fr>(self.challenge_bit_len)?;
+        self.append_label_tag(label);
+        let state = self.absorb();
 
-        let input = [self.state.as_ref(), self.transcript.as_ref()].concat();
-        let tmp = hasher.sponge_with_padding(&input, STATE_SIZE);
-        let challenge = fq_to_fr_with_mask::<F, E::Fr>(&tmp[0]);
-        self.state.copy_from_slice(&tmp);
+        let challenge = squeezed_to_challenge(&state[0], bit_len);
+        self.state = state;
         self.transcript = Vec::new();
         self.transcript.push(field_switching(&challenge));
+        self.record_label(label);
+        self.permutation_count += 1;
 
         Ok(challenge)
     }
+
+    /// Generate one challenge per label in `labels` from a single sponge
+    /// invocation, c.f. `get_and_append_n_challenges`, and then append them,
+    /// in order, to the transcript. The labels are omitted for efficiency,
+    /// c.f. `set_absorb_labels`: if labels are absorbed, all of them are bound
+    /// to the challenges by the label tag.
+    fn get_and_append_challenges<E>(
+        &mut self,
+        labels: &[&'static [u8]],
+    ) -> Result<Vec<E::Fr>, PlonkError>
+    where
+        E: PairingEngine,
+    {
+        let (last, rest) = labels
+            .split_last()
+            .ok_or_else(|| ParameterError("the number of challenges is zero".to_string()))?;
+        for label in rest {
+            self.record_label(label);
+        }
+        self.get_and_append_n_challenges::<E>(last, labels.len())
+    }
 }
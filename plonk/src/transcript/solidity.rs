@@ -18,16 +18,31 @@ use sha3::{Digest, Keccak256};
 /// `keccak256` for Solidity-friendly protocols.
 ///
 /// It is currently implemented simply as
-/// - an append only vector of field elements
-/// - a state that is initialized with 0
+/// - an append only vector of bytes
+/// - a state of 64 bytes that is initialized with 0
 ///
-/// We keep appending new elements to the transcript vector,
-/// and when a challenge is to be generated,
-/// we reset the state with the fresh challenge.
+/// We keep appending new messages to the transcript vector, and when a
+/// challenge is to be generated, we update the state from the whole
+/// transcript. The transcript is neither cleared nor extended with the
+/// challenge.
 ///
-/// 1. state: \[F: STATE_SIZE\] = hash(state|transcript)
-/// 2. challenge = state\[0\]
-/// 3. transcript = vec!\[challenge\]
+/// 1. state = keccak256(state|transcript|0) || keccak256(state|transcript|1)
+/// 2. challenge = state\[..48\] mod r
+///
+/// Since every challenge hashes the whole transcript again, the hashing cost
+/// is quadratic in the number of appends.
+///
+/// # Reproducing the transcript on the EVM
+///
+/// A Solidity verifier can re-derive the challenges with:
+/// - `append_message(msg)`: `transcript = abi.encodePacked(transcript, msg)`,
+///   labels are ignored;
+/// - `get_and_append_challenge()`:
+///   `state = abi.encodePacked(keccak256(abi.encodePacked(state, transcript,
+///   uint8(0))), keccak256(abi.encodePacked(state, transcript, uint8(1))))`
+///   where `state` is initially 64 zero bytes, and the challenge is the first
+///   48 bytes of `state` read as a little-endian integer reduced modulo the
+///   scalar field order `r`. `transcript` is left unchanged.
 pub struct SolidityTranscript {
     transcript: Vec<u8>,
     state: [u8; KECCAK256_STATE_SIZE], // 64 bytes state size
@@ -94,3 +109,34 @@ fn test_solidity_keccak() {
             .unwrap()
     );
 }
+
+#[test]
+fn test_solidity_transcript_challenge() {
+    use super::KeccakTranscript;
+    use ark_bls12_381::{Bls12_381, Fq, Fr};
+    use sha3::{Digest, Keccak256};
+
+    let label = "testing".as_ref();
+    let msgs: [&[u8]; 3] = [b"first message", b"", b"third message"];
+    let mut transcript = <KeccakTranscript as PlonkTranscript<Fq>>::new(label);
+
+    // re-derive the challenges as a Solidity verifier would
+    let mut state = [0u8; KECCAK256_STATE_SIZE];
+    let mut pending: Vec<u8> = Vec::new();
+    for msg in msgs.iter() {
+        <KeccakTranscript as PlonkTranscript<Fq>>::append_message(&mut transcript, label, msg)
+            .unwrap();
+        pending.extend_from_slice(msg);
+
+        let buf0 = Keccak256::digest(&[state.as_ref(), pending.as_ref(), &[0u8]].concat());
+        let buf1 = Keccak256::digest(&[state.as_ref(), pending.as_ref(), &[1u8]].concat());
+        state.copy_from_slice(&[buf0, buf1].concat());
+        let expected = Fr::from_le_bytes_mod_order(&state[..48]);
+
+        let challenge = <KeccakTranscript as PlonkTranscript<Fq>>::get_and_append_challenge::<
+            Bls12_381,
+        >(&mut transcript, label)
+        .unwrap();
+        assert_eq!(challenge, expected);
+    }
+}
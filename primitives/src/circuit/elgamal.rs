@@ -6,12 +6,15 @@
 
 //! Circuit implementation of the ElGamal scheme.
 
-use crate::elgamal::{Ciphertext, EncKey};
+use crate::{
+    circuit::rescue_ctr::RescueCtrGadget,
+    elgamal::{Ciphertext, EncKey, KeyPair},
+};
 use ark_ec::{
     twisted_edwards_extended::GroupAffine, AffineCurve, ProjectiveCurve, TEModelParameters,
 };
 use ark_ff::PrimeField;
-use ark_std::{vec, vec::Vec};
+use ark_std::{format, vec::Vec};
 use jf_plonk::{
     circuit::{
         customized::{
@@ -20,10 +23,10 @@ use jf_plonk::{
         },
         Circuit, PlonkCircuit, Variable,
     },
-    errors::PlonkError,
+    errors::{CircuitError::ParameterError, PlonkError},
 };
-use jf_rescue::{RescueParameter, PRP, STATE_SIZE};
-use jf_utils::compute_len_to_next_multiple;
+use jf_rescue::{RescueParameter, STATE_SIZE};
+use jf_utils::fr_to_fq;
 
 /// Variables holding an encryption key.
 #[derive(Debug)]
@@ -56,9 +59,8 @@ where
     ) -> Result<Vec<RescueStateVar>, PlonkError>;
 
     /// Rescue counter mode encryption with padding
-    /// The function pads the input data and then calls
-    /// apply_counter_mode_stream_no_padding The key should be a fresh one
-    /// in each call, and the nonce is initialized to zero.
+    /// The key should be a fresh one in each call, and the nonce is
+    /// initialized to zero.
     /// * `key_var` - variables corresponding to the symmetric key
     /// * `data_vars` - the variables for the data to be encrypted. The format
     ///   of this input is a list of variable of arbitrary length
@@ -69,6 +71,14 @@ where
         key_var: &RescueStateVar,
         data_vars: &[Variable],
     ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Derive the symmetric key from the Diffie-Hellman shared point
+    /// * `shared_key_var` - variables corresponding to the shared point
+    /// * `returns` - the variables corresponding to the symmetric key
+    fn derive_symmetric_key(
+        &mut self,
+        shared_key_var: &PointVariable,
+    ) -> Result<RescueStateVar, PlonkError>;
 }
 
 impl<F> ElGamalEncryptionHelperGadget<F> for PlonkCircuit<F>
@@ -80,43 +90,15 @@ where
         key_var: &RescueStateVar,
         data_vars: &[RescueStateVar],
     ) -> Result<Vec<RescueStateVar>, PlonkError> {
-        let zero_var = self.zero();
-
-        let mut output_vars = data_vars.to_vec();
-
-        // Schedule the keys
-        let prp_instance = PRP::default();
-        let mds_states = prp_instance.mds_matrix_ref();
-        let round_keys_var = self.key_schedule(mds_states, key_var, &prp_instance)?;
-
-        // Compute stream
-
-        // nonce == 0
-        let mut counter_var = zero_var;
-
-        output_vars
-            .iter_mut()
-            .try_for_each(|output_chunk_vars| -> Result<(), PlonkError> {
-                let stream_chunk_vars = self.prp_with_round_keys(
-                    &RescueStateVar::from([counter_var, zero_var, zero_var, zero_var]),
-                    mds_states,
-                    &round_keys_var,
-                )?;
-
-                // Increment the counter
-                counter_var = self.add_constant(counter_var, &F::one())?;
-
-                for (output_chunk_var, stream_chunk_var) in output_chunk_vars
-                    .array_mut()
-                    .iter_mut()
-                    .zip(stream_chunk_vars.array().iter())
-                {
-                    *output_chunk_var = self.add(*output_chunk_var, *stream_chunk_var)?;
-                }
-                Ok(())
-            })?;
-
-        Ok(output_vars)
+        let flat_data_vars: Vec<Variable> = data_vars
+            .iter()
+            .flat_map(|state| state.array().to_vec())
+            .collect();
+        let output_vars = self.apply_counter_mode_stream(key_var, &flat_data_vars)?;
+        Ok(output_vars
+            .chunks(STATE_SIZE)
+            .map(|block| RescueStateVar::from([block[0], block[1], block[2], block[3]]))
+            .collect())
     }
 
     fn apply_counter_mode_stream(
@@ -124,42 +106,23 @@ where
         key_var: &RescueStateVar,
         data_vars: &[Variable],
     ) -> Result<Vec<Variable>, PlonkError> {
+        // nonce == 0
         let zero_var = self.zero();
+        self.rescue_ctr_encrypt(key_var, zero_var, data_vars)
+    }
 
-        // Compute the length of padded input
-        let mut data_vars_vec = data_vars.to_vec();
-        let len = data_vars_vec.len();
-        let new_len = compute_len_to_next_multiple(len, STATE_SIZE);
-
-        // Pad the input
-        while data_vars_vec.len() < new_len {
-            data_vars_vec.push(zero_var);
-        }
-
-        // Group data_vars in chunks of state size
-        let mut data_vars_states = vec![];
-        for block in data_vars_vec.chunks(STATE_SIZE) {
-            let state = RescueStateVar::from([block[0], block[1], block[2], block[3]]);
-            data_vars_states.push(state);
-        }
-        let encrypted_output_var_states =
-            self.apply_counter_mode_stream_no_padding(key_var, data_vars_states.as_slice())?;
-
-        // Rebuild the output getting rid of the extra variables
-        let mut output_vars: Vec<Variable> = vec![];
-        let mut num_vars = 0;
-        for state in encrypted_output_var_states {
-            let state_array = state.array();
-            for variable in state_array.iter().take(STATE_SIZE) {
-                if num_vars == len {
-                    // We are not interested in the padding variables
-                    break;
-                }
-                output_vars.push(*variable);
-                num_vars += 1;
-            }
-        }
-        Ok(output_vars)
+    fn derive_symmetric_key(
+        &mut self,
+        shared_key_var: &PointVariable,
+    ) -> Result<RescueStateVar, PlonkError> {
+        let zero_var = self.zero();
+        let key_perm_input_var = RescueStateVar::from([
+            shared_key_var.get_x(),
+            shared_key_var.get_y(),
+            zero_var,
+            zero_var,
+        ]);
+        self.rescue_permutation(key_perm_input_var)
     }
 }
 
@@ -182,11 +145,42 @@ where
         r: Variable,
     ) -> Result<ElGamalHybridCtxtVars, PlonkError>;
 
+    /// Compute the gadget that check that a ciphertext is the Elgamal
+    /// encryption of a plaintext, e.g. for a ciphertext given as public input
+    /// that encrypts a committed plaintext
+    /// * `pk_vars` - variables corresponding to the encryption public key
+    /// * `data_vars` - variables corresponding to the plaintext
+    /// * `r` - variable corresponding to the encryption randomness
+    /// * `ctxts_vars` - variables corresponding to the ciphertext
+    fn elgamal_encryption_gate(
+        &mut self,
+        pk_vars: &EncKeyVars,
+        data_vars: &[Variable],
+        r: Variable,
+        ctxts_vars: &ElGamalHybridCtxtVars,
+    ) -> Result<(), PlonkError>;
+
+    /// Compute the gadget that decrypts an Elgamal ciphertext, e.g. for an
+    /// auditor or a view key holder
+    /// * `dec_key_var` - variable corresponding to the decryption key
+    /// * `ctxts_vars` - variables corresponding to the ciphertext
+    /// * `returns` - variables corresponding to the plaintext
+    fn elgamal_decrypt(
+        &mut self,
+        dec_key_var: Variable,
+        ctxts_vars: &ElGamalHybridCtxtVars,
+    ) -> Result<Vec<Variable>, PlonkError>;
+
     /// Helper function to create encryption key variables struct
     /// * `pk` - encryption public key
     /// * `returns` - struct containing the variables corresponding to `p`
     fn create_enc_key_variable(&mut self, pk: &EncKey<P>) -> Result<EncKeyVars, PlonkError>;
 
+    /// Helper function to create a decryption key variable
+    /// * `keypair` - encryption key pair
+    /// * `returns` - the variable corresponding to the decryption key
+    fn create_dec_key_variable(&mut self, keypair: &KeyPair<P>) -> Result<Variable, PlonkError>;
+
     /// Helper function to create a ciphertext variable
     fn create_ciphertext_variable(
         &mut self,
@@ -206,14 +200,7 @@ where
         r: Variable,
     ) -> Result<ElGamalHybridCtxtVars, PlonkError> {
         let shared_pk_var = self.variable_base_scalar_mul::<P>(r, &pk_var.0)?;
-        let zero_var = self.zero();
-        let key_perm_input_var = RescueStateVar::from([
-            shared_pk_var.get_x(),
-            shared_pk_var.get_y(),
-            zero_var,
-            zero_var,
-        ]);
-        let symm_key_vars = self.rescue_permutation(key_perm_input_var)?;
+        let symm_key_vars = self.derive_symmetric_key(&shared_pk_var)?;
 
         let symm_ctxts = self.apply_counter_mode_stream(&symm_key_vars, data_vars)?;
         let base = GroupAffine::<P>::prime_subgroup_generator();
@@ -224,12 +211,56 @@ where
         })
     }
 
+    fn elgamal_encryption_gate(
+        &mut self,
+        pk_vars: &EncKeyVars,
+        data_vars: &[Variable],
+        r: Variable,
+        ctxts_vars: &ElGamalHybridCtxtVars,
+    ) -> Result<(), PlonkError> {
+        if data_vars.len() != ctxts_vars.symm_ctxts.len() {
+            return Err(ParameterError(format!(
+                "plaintext length {} and ciphertext length {} mismatch",
+                data_vars.len(),
+                ctxts_vars.symm_ctxts.len()
+            ))
+            .into());
+        }
+        let expected_ctxts_vars = self.elgamal_encrypt(pk_vars, data_vars, r)?;
+        self.point_equal_gate(&expected_ctxts_vars.ephemeral, &ctxts_vars.ephemeral)?;
+        for (&expected, &ctxt) in expected_ctxts_vars
+            .symm_ctxts
+            .iter()
+            .zip(ctxts_vars.symm_ctxts.iter())
+        {
+            self.equal_gate(expected, ctxt)?;
+        }
+        Ok(())
+    }
+
+    fn elgamal_decrypt(
+        &mut self,
+        dec_key_var: Variable,
+        ctxts_vars: &ElGamalHybridCtxtVars,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let shared_key_var =
+            self.variable_base_scalar_mul::<P>(dec_key_var, &ctxts_vars.ephemeral)?;
+        let symm_key_vars = self.derive_symmetric_key(&shared_key_var)?;
+        // nonce == 0
+        let zero_var = self.zero();
+        self.rescue_ctr_decrypt(&symm_key_vars, zero_var, &ctxts_vars.symm_ctxts)
+    }
+
     fn create_enc_key_variable(&mut self, pk: &EncKey<P>) -> Result<EncKeyVars, PlonkError> {
         let point = Point::from(pk.key.into_affine());
         let point_variable = self.create_point_variable(point)?;
         Ok(EncKeyVars(point_variable))
     }
 
+    fn create_dec_key_variable(&mut self, keypair: &KeyPair<P>) -> Result<Variable, PlonkError> {
+        self.create_variable(fr_to_fq::<F, P>(&keypair.dec.key))
+    }
+
     fn create_ciphertext_variable(
         &mut self,
         ctxts: &Ciphertext<P>,
@@ -252,7 +283,8 @@ where
 mod tests {
     use crate::{
         circuit::elgamal::{ElGamalEncryptionGadget, ElGamalEncryptionHelperGadget},
-        elgamal::{apply_counter_mode_stream, Direction::Encrypt, KeyPair},
+        elgamal::KeyPair,
+        rescue_ctr::{apply_counter_mode_stream, Direction::Encrypt},
     };
     use ark_ec::{ProjectiveCurve, TEModelParameters};
     use ark_ed_on_bls12_377::{EdwardsParameters as ParamEd377, Fq as FqEd377};
@@ -308,7 +340,7 @@ mod tests {
             .apply_counter_mode_stream_no_padding(&key_var, data_vars.as_slice())
             .unwrap();
 
-        let encrypted_data = apply_counter_mode_stream::<F, P>(&key, &data, &F::zero(), Encrypt);
+        let encrypted_data = apply_counter_mode_stream(&key, &data, &F::zero(), Encrypt);
 
         let mut blocks = vec![];
 
@@ -412,7 +444,106 @@ mod tests {
     }
 
     #[test]
+    fn test_elgamal_decrypt_circuit() {
+        test_elgamal_decrypt_circuit_helper::<FqEd254, ParamEd254>();
+        test_elgamal_decrypt_circuit_helper::<FqEd377, ParamEd377>();
+        test_elgamal_decrypt_circuit_helper::<FqEd381, ParamEd381>();
+        test_elgamal_decrypt_circuit_helper::<FqEd381b, ParamEd381b>();
+    }
+    fn test_elgamal_decrypt_circuit_helper<F, P>()
+    where
+        F: RescueParameter,
+        P: TEModelParameters<BaseField = F> + Clone,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let mut prng = ark_std::test_rng();
+
+        let keypair = KeyPair::<P>::generate(&mut prng);
+        let data: Vec<F> = (0..2 * STATE_SIZE + 3).map(|i| F::from(i as u32)).collect();
+        let ctxts = keypair.enc_key_ref().encrypt(&mut prng, &data);
+        let dec_key_var = circuit.create_dec_key_variable(&keypair).unwrap();
+        let ctxts_vars = circuit.create_ciphertext_variable(&ctxts).unwrap();
+
+        let data_vars = ElGamalEncryptionGadget::<_, P>::elgamal_decrypt(
+            &mut circuit,
+            dec_key_var,
+            &ctxts_vars,
+        )
+        .unwrap();
+        assert_eq!(data.len(), data_vars.len());
+        for (msg, &msg_var) in data.iter().zip(data_vars.iter()) {
+            assert_eq!(*msg, circuit.witness(msg_var).unwrap());
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // Alter decryption key
+        *circuit.witness_mut(dec_key_var) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(dec_key_var) -= F::one();
+        // Alter plaintext
+        *circuit.witness_mut(data_vars[0]) = F::from(1_u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+    }
+
+    #[test]
+    fn test_elgamal_encryption_gate() {
+        test_elgamal_encryption_gate_helper::<FqEd254, ParamEd254>();
+        test_elgamal_encryption_gate_helper::<FqEd377, ParamEd377>();
+        test_elgamal_encryption_gate_helper::<FqEd381, ParamEd381>();
+        test_elgamal_encryption_gate_helper::<FqEd381b, ParamEd381b>();
+    }
+    fn test_elgamal_encryption_gate_helper<F, P>()
+    where
+        F: RescueParameter,
+        P: TEModelParameters<BaseField = F> + Clone,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let mut prng = ark_std::test_rng();
+
+        let keypair = KeyPair::<P>::generate(&mut prng);
+        let pk_var = circuit
+            .create_enc_key_variable(keypair.enc_key_ref())
+            .unwrap();
+        let data: Vec<F> = (0..STATE_SIZE + 1).map(|i| F::from(i as u32)).collect();
+        let data_vars: Vec<Variable> = data
+            .iter()
+            .map(|x| circuit.create_variable(*x).unwrap())
+            .collect();
+        let r = P::ScalarField::rand(&mut prng);
+        let enc_rand_var = circuit.create_variable(fr_to_fq::<F, P>(&r)).unwrap();
+        let ctxts = keypair.enc_key().deterministic_encrypt(r, &data);
+        let ctxts_vars = circuit.create_ciphertext_variable(&ctxts).unwrap();
+
+        ElGamalEncryptionGadget::<_, P>::elgamal_encryption_gate(
+            &mut circuit,
+            &pk_var,
+            &data_vars,
+            enc_rand_var,
+            &ctxts_vars,
+        )
+        .unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // Alter plaintext
+        *circuit.witness_mut(data_vars[STATE_SIZE]) = F::from(0_u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(data_vars[STATE_SIZE]) = data[STATE_SIZE];
+        // Alter ciphertext
+        *circuit.witness_mut(ctxts_vars.ephemeral.get_y()) = F::from(0_u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
 
+        // Mismatched lengths
+        assert!(ElGamalEncryptionGadget::<_, P>::elgamal_encryption_gate(
+            &mut circuit,
+            &pk_var,
+            &data_vars[1..],
+            enc_rand_var,
+            &ctxts_vars,
+        )
+        .is_err());
+    }
+
+    #[test]
     fn test_create_ciphertext_variable() {
         test_create_ciphertext_variable_helper::<FqEd254, ParamEd254>();
         test_create_ciphertext_variable_helper::<FqEd377, ParamEd377>();
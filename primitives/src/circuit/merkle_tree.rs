@@ -8,18 +8,21 @@
 
 //! Circuit implementation of a Merkle tree.
 
-use crate::merkle_tree::{AccMemberWitness, MerklePath, MerkleTree, NodePos, NodeValue};
+use crate::merkle_tree::{
+    check_multi_proof_uids, fold_merkle_multi_path, AccMemberWitness, MerkleMultiProof, MerklePath,
+    MerkleTree, NodePos, NodeValue,
+};
 use ark_ec::TEModelParameters as Parameters;
 use ark_ff::PrimeField;
-use ark_std::{vec, vec::Vec};
+use ark_std::{format, string::ToString, vec, vec::Vec};
 use jf_plonk::{
-    circuit::{customized::rescue::RescueGadget, Circuit, PlonkCircuit, Variable},
-    errors::PlonkError,
+    circuit::{customized::rescue::RescueGadget, BoolVar, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
 };
 use jf_rescue::RescueParameter;
 
 #[derive(Clone)]
-struct MerkleNodeBooleanEncoding<F: PrimeField> {
+pub(crate) struct MerkleNodeBooleanEncoding<F: PrimeField> {
     sibling1: NodeValue<F>,
     sibling2: NodeValue<F>,
     is_left_child: u8,
@@ -42,7 +45,7 @@ impl<F: PrimeField> MerkleNodeBooleanEncoding<F> {
     }
 }
 
-struct MerklePathBooleanEncoding<F: PrimeField> {
+pub(crate) struct MerklePathBooleanEncoding<F: PrimeField> {
     pub nodes: Vec<MerkleNodeBooleanEncoding<F>>,
 }
 
@@ -81,8 +84,8 @@ impl<F: PrimeField> From<&MerklePath<F>> for MerklePathBooleanEncoding<F> {
 pub struct MerkleNodeVars {
     pub sibling1: Variable,
     pub sibling2: Variable,
-    pub is_left_child: Variable,
-    pub is_right_child: Variable,
+    pub is_left_child: BoolVar,
+    pub is_right_child: BoolVar,
 }
 
 #[derive(Debug)]
@@ -91,6 +94,14 @@ pub struct MerklePathVars {
     pub nodes: Vec<MerkleNodeVars>,
 }
 
+/// Circuit variables for a Merkle multiproof. The leaf positions are
+/// constants of the circuit, as they determine which siblings are shared.
+#[derive(Debug)]
+pub struct MerkleMultiProofVars {
+    pub uids: Vec<u64>,
+    pub nodes: Vec<Variable>,
+}
+
 /// Circuit variables for an accumulated element
 pub struct AccElemVars {
     pub uid: Variable,
@@ -119,7 +130,8 @@ impl AccMemberWitnessVar {
         })
     }
 }
-trait MerkleTreeHelperGadget<F: PrimeField> {
+
+pub(crate) trait MerkleTreeHelperGadget<F: PrimeField> {
     /// Produces an ordered list of variables based on the relative position of
     /// a node and its siblings.
     /// * `node` - node to be inserted in the final list.
@@ -134,8 +146,8 @@ trait MerkleTreeHelperGadget<F: PrimeField> {
         node: Variable,
         sib1: Variable,
         sib2: Variable,
-        node_is_left: Variable,
-        node_is_right: Variable,
+        node_is_left: BoolVar,
+        node_is_right: BoolVar,
     ) -> Result<[Variable; 3], PlonkError>;
 
     /// Ensure that the position of each node of the path is correctly encoded
@@ -171,6 +183,80 @@ pub trait MerkleTreeGadget<F: PrimeField> {
         elem: AccElemVars,
         path_vars: &MerklePathVars,
     ) -> Result<Variable, PlonkError>;
+
+    /// Checks that an element is a member of a Merkle tree of height `height`,
+    /// i.e. that the Merkle root computed from the element and its
+    /// authentication path is `root_var`.
+    /// * `elem` - variables corresponding to the uid and the element value
+    ///   (e.g.: record commitment).
+    /// * `path_vars` - variables corresponding to the Merkle path.
+    /// * `root_var` - variable corresponding to the root value of the Merkle
+    ///   tree.
+    /// * `height` - height of the Merkle tree.
+    /// * `returns` - error if the Merkle path does not have `height` nodes.
+    fn check_merkle_membership(
+        &mut self,
+        elem: AccElemVars,
+        path_vars: &MerklePathVars,
+        root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError>;
+
+    /// Checks that replacing the element `old_elem` by `new_elem` at position
+    /// `uid` of a Merkle tree of height `height` changes its root from
+    /// `old_root_var` to `new_root_var`. Both roots are computed from the same
+    /// authentication path, so that the rest of the tree is unchanged.
+    /// * `uid` - variable corresponding to the position of the leaf.
+    /// * `old_elem` - variable corresponding to the element before the update.
+    /// * `new_elem` - variable corresponding to the element after the update.
+    /// * `path_vars` - variables corresponding to the Merkle path of the leaf.
+    /// * `old_root_var` - variable corresponding to the root value before the
+    ///   update.
+    /// * `new_root_var` - variable corresponding to the root value after the
+    ///   update.
+    /// * `height` - height of the Merkle tree.
+    /// * `returns` - error if the Merkle path does not have `height` nodes.
+    #[allow(clippy::too_many_arguments)]
+    fn check_merkle_update(
+        &mut self,
+        uid: Variable,
+        old_elem: Variable,
+        new_elem: Variable,
+        path_vars: &MerklePathVars,
+        old_root_var: Variable,
+        new_root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError>;
+
+    /// Adds the variables of the siblings of a Merkle multiproof.
+    /// * `proof` - multiproof of several leaves
+    /// * `returns` - the leaf positions and the variables of the siblings
+    fn add_merkle_multi_proof_variable(
+        &mut self,
+        proof: &MerkleMultiProof<F>,
+    ) -> Result<MerkleMultiProofVars, PlonkError>;
+
+    /// Checks that several elements are members of a Merkle tree of height
+    /// `height`, i.e. that the Merkle root computed from the elements and
+    /// their multiproof is `root_var`. This is cheaper than checking the
+    /// membership of each element, as the nodes shared by several paths are
+    /// only computed once.
+    /// * `elems` - variables corresponding to the element values, in the order
+    ///   of the leaf positions of `proof_vars`.
+    /// * `proof_vars` - leaf positions and variables corresponding to the
+    ///   siblings of the multiproof.
+    /// * `root_var` - variable corresponding to the root value of the Merkle
+    ///   tree.
+    /// * `height` - height of the Merkle tree.
+    /// * `returns` - error if the leaf positions are invalid, or if the number
+    ///   of elements or of siblings does not match them.
+    fn check_merkle_multi_membership(
+        &mut self,
+        elems: &[Variable],
+        proof_vars: &MerkleMultiProofVars,
+        root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError>;
 }
 
 impl<F> MerkleTreeGadget<F> for PlonkCircuit<F>
@@ -210,6 +296,111 @@ where
         }
         Ok(cur_label)
     }
+
+    fn check_merkle_membership(
+        &mut self,
+        elem: AccElemVars,
+        path_vars: &MerklePathVars,
+        root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError> {
+        if path_vars.nodes.len() != height as usize {
+            return Err(ParameterError(format!(
+                "the Merkle path has {} nodes, the tree height is {}",
+                path_vars.nodes.len(),
+                height
+            ))
+            .into());
+        }
+        let computed_root_var = self.compute_merkle_root(elem, path_vars)?;
+        self.equal_gate(computed_root_var, root_var)
+    }
+
+    fn check_merkle_update(
+        &mut self,
+        uid: Variable,
+        old_elem: Variable,
+        new_elem: Variable,
+        path_vars: &MerklePathVars,
+        old_root_var: Variable,
+        new_root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError> {
+        let old_elem = AccElemVars {
+            uid,
+            elem: old_elem,
+        };
+        self.check_merkle_membership(old_elem, path_vars, old_root_var, height)?;
+        let new_elem = AccElemVars {
+            uid,
+            elem: new_elem,
+        };
+        self.check_merkle_membership(new_elem, path_vars, new_root_var, height)
+    }
+
+    fn add_merkle_multi_proof_variable(
+        &mut self,
+        proof: &MerkleMultiProof<F>,
+    ) -> Result<MerkleMultiProofVars, PlonkError> {
+        let nodes = proof
+            .nodes
+            .iter()
+            .map(|node| self.create_variable(node.0))
+            .collect::<Result<Vec<_>, PlonkError>>()?;
+        Ok(MerkleMultiProofVars {
+            uids: proof.uids.clone(),
+            nodes,
+        })
+    }
+
+    fn check_merkle_multi_membership(
+        &mut self,
+        elems: &[Variable],
+        proof_vars: &MerkleMultiProofVars,
+        root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError> {
+        check_multi_proof_uids(&proof_vars.uids, height)
+            .map_err(|e| ParameterError(e.to_string()))?;
+        if elems.len() != proof_vars.uids.len() {
+            return Err(ParameterError(format!(
+                "the multiproof has {} positions but {} elements",
+                proof_vars.uids.len(),
+                elems.len()
+            ))
+            .into());
+        }
+
+        // leaf label = H(0, uid, elem)
+        let zero_var = self.zero();
+        let mut leaves = Vec::with_capacity(elems.len());
+        for (&uid, &elem) in proof_vars.uids.iter().zip(elems.iter()) {
+            let uid_var = self.create_constant_variable(F::from(uid))?;
+            let leaf = self.rescue_sponge_no_padding(&[zero_var, uid_var, elem], 1)?[0];
+            leaves.push((uid, leaf));
+        }
+        let mut nodes = proof_vars.nodes.iter();
+        let computed_root_var = fold_merkle_multi_path(
+            height,
+            &leaves,
+            |_, _| {
+                nodes.next().copied().ok_or_else(|| {
+                    PlonkError::from(ParameterError(
+                        "the multiproof has too few siblings".to_string(),
+                    ))
+                })
+            },
+            |input_labels| {
+                // check that the left child's label is non-zero
+                self.non_zero_gate(input_labels[0])?;
+                Ok(self.rescue_sponge_no_padding(input_labels, 1)?[0])
+            },
+        )?;
+        if nodes.next().is_some() {
+            return Err(ParameterError("the multiproof has too many siblings".to_string()).into());
+        }
+        self.equal_gate(computed_root_var, root_var)
+    }
 }
 
 impl<F> MerkleTreeHelperGadget<F> for PlonkCircuit<F>
@@ -221,8 +412,8 @@ where
         node: Variable,
         sib1: Variable,
         sib2: Variable,
-        node_is_left: Variable,
-        node_is_right: Variable,
+        node_is_left: BoolVar,
+        node_is_right: BoolVar,
     ) -> Result<[Variable; 3], PlonkError> {
         let one = F::one();
         let left_node = self.conditional_select(node_is_left, sib1, node)?;
@@ -239,29 +430,29 @@ where
         &mut self,
         merkle_path: &MerklePathBooleanEncoding<F>,
     ) -> Result<MerklePathVars, PlonkError> {
-        // Setup node variables
+        // Setup node variables, where `is_left_child` and `is_right_child` are
+        // boolean
         let nodes = merkle_path
             .nodes
             .clone()
             .into_iter()
             .map(|node| -> Result<MerkleNodeVars, PlonkError> {
+                let is_left_child = self.create_variable(F::from(node.is_left_child as u32))?;
+                let is_right_child = self.create_variable(F::from(node.is_right_child as u32))?;
                 Ok(MerkleNodeVars {
                     sibling1: self.create_variable(node.sibling1.0)?,
                     sibling2: self.create_variable(node.sibling2.0)?,
-                    is_left_child: self.create_variable(F::from(node.is_left_child as u32))?,
-                    is_right_child: self.create_variable(F::from(node.is_right_child as u32))?,
+                    is_left_child: self.enforce_bool(is_left_child)?,
+                    is_right_child: self.enforce_bool(is_right_child)?,
                 })
             })
             .collect::<Result<Vec<MerkleNodeVars>, PlonkError>>()?;
 
-        // `is_left_child`, `is_right_child` and `is_left_child+is_right_child` are
-        // boolean
+        // Boolean constrain `is_left_child + is_right_child` because a node
+        // can either be the left or the right child of its parent
         for node in nodes.iter() {
-            self.bool_gate(node.is_left_child)?;
-            self.bool_gate(node.is_right_child)?;
-            // Boolean constrain `is_left_child + is_right_child` because a node
-            // can either be the left or the right child of its parent
-            let left_plus_right = self.add(node.is_left_child, node.is_right_child)?;
+            let left_plus_right =
+                self.add(node.is_left_child.into(), node.is_right_child.into())?;
             self.bool_gate(left_plus_right)?;
         }
 
@@ -295,7 +486,10 @@ mod test {
             gen_merkle_path_for_test, AccElemVars, MerkleNodeBooleanEncoding,
             MerklePathBooleanEncoding, MerkleTreeGadget, MerkleTreeHelperGadget,
         },
-        merkle_tree::{hash, MerklePath, MerklePathNode, NodePos, NodeValue},
+        merkle_tree::{
+            hash, AccMemberWitness, MerkleMultiProof, MerklePath, MerklePathNode, MerkleTree,
+            NodePos, NodeValue,
+        },
     };
     use ark_bls12_377::Fq as Fq377;
     use ark_ed_on_bls12_377::Fq as FqEd377;
@@ -304,7 +498,10 @@ mod test {
     use ark_ed_on_bn254::Fq as FqEd254;
     use ark_ff::PrimeField;
     use ark_std::{vec, vec::Vec};
-    use jf_plonk::circuit::{Circuit, PlonkCircuit, Variable};
+    use jf_plonk::{
+        circuit::{Circuit, PlonkCircuit, Variable},
+        errors::PlonkError,
+    };
     use jf_rescue::RescueParameter;
 
     fn check_merkle_path<F: PrimeField>(is_left_child: u8, is_right_child: u8, accept: bool) {
@@ -360,17 +557,9 @@ mod test {
         expected_output_vars: &[Variable],
     ) {
         let zero = F::zero();
-        let one = F::one();
-
-        let node_is_left = match is_left {
-            false => circuit.create_variable(zero).unwrap(),
-            true => circuit.create_variable(one).unwrap(),
-        };
 
-        let node_is_right = match is_right {
-            false => circuit.create_variable(zero).unwrap(),
-            true => circuit.create_variable(one).unwrap(),
-        };
+        let node_is_left = circuit.create_bool_variable(is_left).unwrap();
+        let node_is_right = circuit.create_bool_variable(is_right).unwrap();
 
         let node = input_vars[0];
         let sib1 = input_vars[1];
@@ -521,4 +710,212 @@ mod test {
         // Circuit does not verify because a left node value is 0
         assert!(circuit.check_circuit_satisfiability(&[]).is_err());
     }
+
+    #[test]
+    fn test_merkle_membership() {
+        test_merkle_membership_helper::<FqEd254>();
+        test_merkle_membership_helper::<FqEd377>();
+        test_merkle_membership_helper::<FqEd381>();
+        test_merkle_membership_helper::<FqEd381b>();
+        test_merkle_membership_helper::<Fq377>();
+    }
+
+    fn test_merkle_membership_helper<F: RescueParameter>() {
+        let height = 3;
+        let mut mt = MerkleTree::<F>::new(height).unwrap();
+        for i in 0..10u32 {
+            mt.push(F::from(100 + i));
+        }
+        let root = mt.commitment().root_value.to_scalar();
+        let uid = 7u64;
+        let (comm, witness) = AccMemberWitness::lookup_from_tree(&mt, uid)
+            .expect_ok()
+            .unwrap();
+
+        let build_circuit = |comm: F, root: F| {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let elem = AccElemVars {
+                uid: circuit.create_variable(F::from(uid)).unwrap(),
+                elem: circuit.create_variable(comm).unwrap(),
+            };
+            let path_vars = circuit
+                .add_merkle_path_variable(&witness.merkle_path)
+                .unwrap();
+            let root_var = circuit.create_variable(root).unwrap();
+            circuit
+                .check_merkle_membership(elem, &path_vars, root_var, height)
+                .unwrap();
+            circuit
+        };
+
+        // Happy path
+        let circuit = build_circuit(comm, root);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // Bad paths: wrong element or wrong root
+        let circuit = build_circuit(comm + F::one(), root);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_circuit(comm, root + F::one());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // The height does not match the length of the path
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let elem = AccElemVars {
+            uid: circuit.create_variable(F::from(uid)).unwrap(),
+            elem: circuit.create_variable(comm).unwrap(),
+        };
+        let path_vars = circuit
+            .add_merkle_path_variable(&witness.merkle_path)
+            .unwrap();
+        let root_var = circuit.create_variable(root).unwrap();
+        assert!(circuit
+            .check_merkle_membership(elem, &path_vars, root_var, height + 1)
+            .is_err());
+    }
+
+    #[test]
+    fn test_merkle_update() {
+        test_merkle_update_helper::<FqEd254>();
+        test_merkle_update_helper::<FqEd377>();
+        test_merkle_update_helper::<FqEd381>();
+        test_merkle_update_helper::<FqEd381b>();
+        test_merkle_update_helper::<Fq377>();
+    }
+
+    fn test_merkle_update_helper<F: RescueParameter>() {
+        let height = 3;
+        let uid = 4u64;
+        let old_comm = F::from(104u32);
+        let new_comm = F::from(999u32);
+        let mut old_mt = MerkleTree::<F>::new(height).unwrap();
+        let mut new_mt = MerkleTree::<F>::new(height).unwrap();
+        for i in 0..10u32 {
+            old_mt.push(F::from(100 + i));
+            new_mt.push(if i as u64 == uid {
+                new_comm
+            } else {
+                F::from(100 + i)
+            });
+        }
+        let old_root = old_mt.commitment().root_value.to_scalar();
+        let new_root = new_mt.commitment().root_value.to_scalar();
+        let (_, witness) = AccMemberWitness::lookup_from_tree(&old_mt, uid)
+            .expect_ok()
+            .unwrap();
+
+        let build_circuit = |old_comm: F, new_comm: F, new_root: F, height: u8| {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let uid_var = circuit.create_variable(F::from(uid))?;
+            let old_comm_var = circuit.create_variable(old_comm)?;
+            let new_comm_var = circuit.create_variable(new_comm)?;
+            let path_vars = circuit.add_merkle_path_variable(&witness.merkle_path)?;
+            let old_root_var = circuit.create_variable(old_root)?;
+            let new_root_var = circuit.create_variable(new_root)?;
+            circuit.check_merkle_update(
+                uid_var,
+                old_comm_var,
+                new_comm_var,
+                &path_vars,
+                old_root_var,
+                new_root_var,
+                height,
+            )?;
+            Ok::<_, PlonkError>(circuit)
+        };
+
+        let circuit = build_circuit(old_comm, new_comm, new_root, height).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        // leaving the element unchanged keeps the root unchanged
+        let circuit = build_circuit(old_comm, old_comm, old_root, height).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // wrong old element, new element or new root
+        let circuit = build_circuit(new_comm, new_comm, new_root, height).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_circuit(old_comm, new_comm + F::one(), new_root, height).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let circuit = build_circuit(old_comm, new_comm, old_root, height).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // The height does not match the length of the path
+        assert!(build_circuit(old_comm, new_comm, new_root, height + 1).is_err());
+    }
+
+    #[test]
+    fn test_merkle_multi_membership() {
+        test_merkle_multi_membership_helper::<FqEd254>();
+        test_merkle_multi_membership_helper::<FqEd377>();
+        test_merkle_multi_membership_helper::<FqEd381>();
+        test_merkle_multi_membership_helper::<FqEd381b>();
+        test_merkle_multi_membership_helper::<Fq377>();
+    }
+
+    fn test_merkle_multi_membership_helper<F: RescueParameter>() {
+        let height = 3;
+        let mut mt = MerkleTree::<F>::new(height).unwrap();
+        for i in 0..20u64 {
+            mt.push(F::from(100 + i));
+        }
+        let root = mt.commitment().root_value.to_scalar();
+        let proof = mt.get_multi_proof(&[1, 2, 5, 13, 19]).unwrap();
+
+        let build_circuit = |proof: &MerkleMultiProof<F>, root: F, height: u8| {
+            let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+            let elems = proof
+                .leaves
+                .iter()
+                .map(|&leaf| circuit.create_variable(leaf))
+                .collect::<Result<Vec<_>, PlonkError>>()?;
+            let proof_vars = circuit.add_merkle_multi_proof_variable(proof)?;
+            let root_var = circuit.create_variable(root)?;
+            circuit.check_merkle_multi_membership(&elems, &proof_vars, root_var, height)?;
+            Ok::<_, PlonkError>((circuit, elems))
+        };
+
+        let (mut circuit, elems) = build_circuit(&proof, root, height).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        *circuit.witness_mut(elems[3]) = F::from(1000u64);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // the multiproof is cheaper than independent paths
+        let mut single_circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        for (&uid, &leaf) in proof.uids.iter().zip(proof.leaves.iter()) {
+            let (_, witness) = AccMemberWitness::lookup_from_tree(&mt, uid)
+                .expect_ok()
+                .unwrap();
+            let elem = AccElemVars {
+                uid: single_circuit.create_variable(F::from(uid)).unwrap(),
+                elem: single_circuit.create_variable(leaf).unwrap(),
+            };
+            let path_vars = single_circuit
+                .add_merkle_path_variable(&witness.merkle_path)
+                .unwrap();
+            let root_var = single_circuit.create_variable(root).unwrap();
+            single_circuit
+                .check_merkle_membership(elem, &path_vars, root_var, height)
+                .unwrap();
+        }
+        assert!(single_circuit.check_circuit_satisfiability(&[]).is_ok());
+        assert!(circuit.num_gates() < single_circuit.num_gates());
+
+        // wrong root or sibling
+        let (circuit, _) = build_circuit(&proof, F::one(), height).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.nodes[1] = NodeValue(F::from(1000u64));
+        let (circuit, _) = build_circuit(&bad_proof, root, height).unwrap();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // The height, the number of siblings or the positions do not match
+        assert!(build_circuit(&proof, root, height + 1).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.nodes.pop();
+        assert!(build_circuit(&bad_proof, root, height).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.uids.swap(0, 1);
+        assert!(build_circuit(&bad_proof, root, height).is_err());
+        let mut bad_proof = proof;
+        bad_proof.leaves.pop();
+        assert!(build_circuit(&bad_proof, root, height).is_err());
+    }
 }
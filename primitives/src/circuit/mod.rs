@@ -8,5 +8,9 @@
 pub mod commitment;
 pub mod elgamal;
 pub mod merkle_tree;
+pub mod nullifier;
+pub mod pedersen;
 pub mod prf;
+pub mod rescue_ctr;
 pub mod schnorr_dsa;
+pub mod sparse_merkle_tree;
@@ -0,0 +1,85 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the nullifier derivation.
+
+use crate::{circuit::prf::PrfGadget, nullifier::NULLIFIER_DOM_SEP};
+use jf_plonk::{
+    circuit::{Circuit, PlonkCircuit, Variable},
+    errors::PlonkError,
+};
+use jf_rescue::RescueParameter;
+
+/// Circuit implementation of the nullifier derivation.
+pub trait NullifierGadget {
+    /// Derive the nullifier of a note, as `Nullifier::derive`.
+    /// * `key` - the variable of the secret key of the owner
+    /// * `commitment` - the variable of the commitment of the note
+    /// * `returns` a variable that refers to the nullifier
+    fn derive_nullifier(
+        &mut self,
+        key: Variable,
+        commitment: Variable,
+    ) -> Result<Variable, PlonkError>;
+}
+
+impl<F> NullifierGadget for PlonkCircuit<F>
+where
+    F: RescueParameter,
+{
+    fn derive_nullifier(
+        &mut self,
+        key: Variable,
+        commitment: Variable,
+    ) -> Result<Variable, PlonkError> {
+        let dom_sep = self.create_constant_variable(F::from(NULLIFIER_DOM_SEP))?;
+        self.eval_prf(key, &[dom_sep, commitment])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::NullifierGadget;
+    use crate::{nullifier::Nullifier, prf::PrfKey};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bls12_381_bandersnatch::Fq as FqEd381b;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::UniformRand;
+    use jf_plonk::circuit::{Circuit, PlonkCircuit};
+    use jf_rescue::RescueParameter;
+
+    #[test]
+    fn test_nullifier_circuit() {
+        test_nullifier_circuit_helper::<FqEd254>();
+        test_nullifier_circuit_helper::<FqEd377>();
+        test_nullifier_circuit_helper::<FqEd381>();
+        test_nullifier_circuit_helper::<FqEd381b>();
+        test_nullifier_circuit_helper::<Fq377>();
+    }
+
+    fn test_nullifier_circuit_helper<F: RescueParameter>() {
+        let mut rng = ark_std::test_rng();
+        let key = F::rand(&mut rng);
+        let commitment = F::rand(&mut rng);
+        let expected = Nullifier::derive(&PrfKey::from(key), &commitment);
+
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let key_var = circuit.create_variable(key).unwrap();
+        let commitment_var = circuit.create_variable(commitment).unwrap();
+        let nullifier_var = circuit.derive_nullifier(key_var, commitment_var).unwrap();
+        assert_eq!(circuit.witness(nullifier_var).unwrap(), expected.internal());
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // a wrong nullifier, or a wrong commitment
+        *circuit.witness_mut(nullifier_var) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(nullifier_var) -= F::one();
+        *circuit.witness_mut(commitment_var) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+    }
+}
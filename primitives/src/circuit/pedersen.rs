@@ -0,0 +1,129 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the Pedersen commitment scheme.
+
+use crate::pedersen::PedersenCommitment;
+use ark_ec::TEModelParameters as Parameters;
+use ark_ff::PrimeField;
+use ark_std::format;
+use jf_plonk::{
+    circuit::{customized::ecc::PointVariable, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+
+/// Circuit implementation of the Pedersen commitment scheme.
+pub trait PedersenCommitmentGadget<F, P>
+where
+    F: PrimeField,
+    P: Parameters<BaseField = F> + Clone,
+{
+    /// Commitment function.
+    /// * `commitment` - the commitment instance, whose generators are
+    ///   constants of the circuit
+    /// * `input` - input variables, holding scalar field elements
+    /// * `blinding` - blinding variable, holding a scalar field element
+    /// * `returns` the variables of the commitment point
+    /// The input length must match the one of the commitment instance.
+    fn pedersen_commit(
+        &mut self,
+        commitment: &PedersenCommitment<P>,
+        input: &[Variable],
+        blinding: Variable,
+    ) -> Result<PointVariable, PlonkError>;
+}
+
+impl<F, P> PedersenCommitmentGadget<F, P> for PlonkCircuit<F>
+where
+    F: PrimeField,
+    P: Parameters<BaseField = F> + Clone,
+{
+    fn pedersen_commit(
+        &mut self,
+        commitment: &PedersenCommitment<P>,
+        input: &[Variable],
+        blinding: Variable,
+    ) -> Result<PointVariable, PlonkError> {
+        if input.len() != commitment.input_len() {
+            return Err(ParameterError(format!(
+                "input length ({}) does not match instance's message length ({})",
+                input.len(),
+                commitment.input_len()
+            ))
+            .into());
+        }
+        let mut result =
+            self.fixed_base_scalar_mul::<P>(blinding, commitment.blinding_generator())?;
+        for (generator, &scalar) in commitment.generators().iter().zip(input.iter()) {
+            let term = self.fixed_base_scalar_mul::<P>(scalar, generator)?;
+            result = self.ecc_add::<P>(&result, &term)?;
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{circuit::pedersen::PedersenCommitmentGadget, pedersen::PedersenCommitment};
+    use ark_ec::TEModelParameters as Parameters;
+    use ark_ed_on_bls12_377::{EdwardsParameters as ParamEd377, Fq as FqEd377};
+    use ark_ed_on_bls12_381::{EdwardsParameters as ParamEd381, Fq as FqEd381};
+    use ark_ed_on_bls12_381_bandersnatch::{EdwardsParameters as ParamEd381b, Fq as FqEd381b};
+    use ark_ed_on_bn254::{EdwardsParameters as ParamEd254, Fq as FqEd254};
+    use ark_ff::{PrimeField, UniformRand};
+    use ark_std::vec::Vec;
+    use jf_plonk::circuit::{customized::ecc::Point, Circuit, PlonkCircuit, Variable};
+    use jf_utils::fr_to_fq;
+
+    #[test]
+    fn test_pedersen_commit_circuit() {
+        test_pedersen_commit_circuit_helper::<FqEd254, ParamEd254>();
+        test_pedersen_commit_circuit_helper::<FqEd377, ParamEd377>();
+        test_pedersen_commit_circuit_helper::<FqEd381, ParamEd381>();
+        test_pedersen_commit_circuit_helper::<FqEd381b, ParamEd381b>();
+    }
+
+    fn test_pedersen_commit_circuit_helper<F, P>()
+    where
+        F: PrimeField,
+        P: Parameters<BaseField = F> + Clone,
+    {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let mut prng = ark_std::test_rng();
+        let commitment = PedersenCommitment::<P>::setup(&mut prng, 3);
+
+        let input: Vec<P::ScalarField> = (0..3).map(|_| P::ScalarField::rand(&mut prng)).collect();
+        let blind = P::ScalarField::rand(&mut prng);
+        let expected_c = commitment.commit(&input, &blind).unwrap();
+
+        let input_vars: Vec<Variable> = input
+            .iter()
+            .map(|x| circuit.create_variable(fr_to_fq::<F, P>(x)).unwrap())
+            .collect();
+        let blind_var = circuit.create_variable(fr_to_fq::<F, P>(&blind)).unwrap();
+        let c_var = circuit
+            .pedersen_commit(&commitment, &input_vars, blind_var)
+            .unwrap();
+        assert_eq!(
+            Point::from(expected_c),
+            circuit.point_witness(&c_var).unwrap()
+        );
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // Alter blinding factor
+        *circuit.witness_mut(blind_var) = F::from(1_u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(blind_var) = fr_to_fq::<F, P>(&blind);
+        // Alter input
+        *circuit.witness_mut(input_vars[2]) = F::from(1_u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
+        // Wrong input length
+        assert!(circuit
+            .pedersen_commit(&commitment, &input_vars[1..], blind_var)
+            .is_err());
+    }
+}
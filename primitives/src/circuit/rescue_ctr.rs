@@ -0,0 +1,194 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of the Rescue-based symmetric encryption in counter
+//! mode.
+
+use ark_ff::PrimeField;
+use ark_std::vec::Vec;
+use jf_plonk::{
+    circuit::{
+        customized::rescue::{RescueGadget, RescueStateVar},
+        Circuit, PlonkCircuit, Variable,
+    },
+    errors::PlonkError,
+};
+use jf_rescue::{RescueParameter, PRP, STATE_SIZE};
+
+/// Circuit implementation of the Rescue counter mode encryption.
+pub trait RescueCtrGadget<F>
+where
+    F: PrimeField,
+{
+    /// Rescue counter mode encryption, matching `rescue_ctr::encrypt`
+    /// * `key_var` - variables corresponding to the symmetric key
+    /// * `nonce_var` - variable corresponding to the nonce
+    /// * `data_vars` - variables corresponding to the plaintext. Can be of
+    ///   arbitrary length.
+    /// * `returns` - variables corresponding to the ciphertext, of the same
+    ///   length as the plaintext
+    fn rescue_ctr_encrypt(
+        &mut self,
+        key_var: &RescueStateVar,
+        nonce_var: Variable,
+        data_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Rescue counter mode decryption, matching `rescue_ctr::decrypt`
+    /// * `key_var` - variables corresponding to the symmetric key
+    /// * `nonce_var` - variable corresponding to the nonce
+    /// * `ctxt_vars` - variables corresponding to the ciphertext
+    /// * `returns` - variables corresponding to the plaintext, of the same
+    ///   length as the ciphertext
+    fn rescue_ctr_decrypt(
+        &mut self,
+        key_var: &RescueStateVar,
+        nonce_var: Variable,
+        ctxt_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError>;
+
+    /// Compute the key stream of the Rescue counter mode
+    /// * `key_var` - variables corresponding to the symmetric key
+    /// * `nonce_var` - variable corresponding to the nonce
+    /// * `len` - length of the key stream
+    /// * `returns` - variables corresponding to the key stream
+    fn rescue_ctr_key_stream(
+        &mut self,
+        key_var: &RescueStateVar,
+        nonce_var: Variable,
+        len: usize,
+    ) -> Result<Vec<Variable>, PlonkError>;
+}
+
+impl<F> RescueCtrGadget<F> for PlonkCircuit<F>
+where
+    F: RescueParameter,
+{
+    fn rescue_ctr_encrypt(
+        &mut self,
+        key_var: &RescueStateVar,
+        nonce_var: Variable,
+        data_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let stream_vars = self.rescue_ctr_key_stream(key_var, nonce_var, data_vars.len())?;
+        data_vars
+            .iter()
+            .zip(stream_vars.iter())
+            .map(|(&data_var, &stream_var)| self.add(data_var, stream_var))
+            .collect::<Result<Vec<_>, PlonkError>>()
+    }
+
+    fn rescue_ctr_decrypt(
+        &mut self,
+        key_var: &RescueStateVar,
+        nonce_var: Variable,
+        ctxt_vars: &[Variable],
+    ) -> Result<Vec<Variable>, PlonkError> {
+        let stream_vars = self.rescue_ctr_key_stream(key_var, nonce_var, ctxt_vars.len())?;
+        ctxt_vars
+            .iter()
+            .zip(stream_vars.iter())
+            .map(|(&ctxt_var, &stream_var)| self.sub(ctxt_var, stream_var))
+            .collect::<Result<Vec<_>, PlonkError>>()
+    }
+
+    fn rescue_ctr_key_stream(
+        &mut self,
+        key_var: &RescueStateVar,
+        nonce_var: Variable,
+        len: usize,
+    ) -> Result<Vec<Variable>, PlonkError> {
+        self.check_var_bound(nonce_var)?;
+        let zero_var = self.zero();
+
+        // Schedule the keys
+        let prp_instance = PRP::default();
+        let mds_states = prp_instance.mds_matrix_ref();
+        let round_keys_var = self.key_schedule(mds_states, key_var, &prp_instance)?;
+
+        // Compute stream
+        let mut stream_vars = Vec::with_capacity(len);
+        let mut counter_var = nonce_var;
+        for i in 0..(len + STATE_SIZE - 1) / STATE_SIZE {
+            if i > 0 {
+                // Increment the counter
+                counter_var = self.add_constant(counter_var, &F::one())?;
+            }
+            let stream_chunk_vars = self.prp_with_round_keys(
+                &RescueStateVar::from([counter_var, zero_var, zero_var, zero_var]),
+                mds_states,
+                &round_keys_var,
+            )?;
+            stream_vars.extend_from_slice(stream_chunk_vars.array());
+        }
+        stream_vars.truncate(len);
+        Ok(stream_vars)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{circuit::rescue_ctr::RescueCtrGadget, rescue_ctr};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_ff::UniformRand;
+    use ark_std::vec::Vec;
+    use jf_plonk::circuit::{customized::rescue::RescueGadget, Circuit, PlonkCircuit, Variable};
+    use jf_rescue::{RescueParameter, RescueVector, STATE_SIZE};
+
+    #[test]
+    fn test_rescue_ctr_circuit() {
+        test_rescue_ctr_circuit_helper::<FqEd254>();
+        test_rescue_ctr_circuit_helper::<FqEd377>();
+        test_rescue_ctr_circuit_helper::<FqEd381>();
+        test_rescue_ctr_circuit_helper::<Fq377>();
+    }
+
+    fn test_rescue_ctr_circuit_helper<F: RescueParameter>() {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let mut prng = ark_std::test_rng();
+        let key = RescueVector::from(&[
+            F::rand(&mut prng),
+            F::rand(&mut prng),
+            F::rand(&mut prng),
+            F::rand(&mut prng),
+        ]);
+        let nonce = F::rand(&mut prng);
+        let data: Vec<F> = (0..2 * STATE_SIZE + 1).map(|i| F::from(i as u32)).collect();
+        let ctxt = rescue_ctr::encrypt(&key, &nonce, &data);
+
+        let key_var = circuit.create_rescue_state_variable(&key).unwrap();
+        let nonce_var = circuit.create_variable(nonce).unwrap();
+        let data_vars: Vec<Variable> = data
+            .iter()
+            .map(|x| circuit.create_variable(*x).unwrap())
+            .collect();
+        let ctxt_vars = circuit
+            .rescue_ctr_encrypt(&key_var, nonce_var, &data_vars)
+            .unwrap();
+        assert_eq!(ctxt_vars.len(), data.len());
+        for (val, &var) in ctxt.iter().zip(ctxt_vars.iter()) {
+            assert_eq!(*val, circuit.witness(var).unwrap());
+        }
+        let dec_vars = circuit
+            .rescue_ctr_decrypt(&key_var, nonce_var, &ctxt_vars)
+            .unwrap();
+        for (val, &var) in data.iter().zip(dec_vars.iter()) {
+            assert_eq!(*val, circuit.witness(var).unwrap());
+        }
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+
+        // Alter nonce
+        *circuit.witness_mut(nonce_var) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(nonce_var) -= F::one();
+        // Alter ciphertext
+        *circuit.witness_mut(ctxt_vars[2 * STATE_SIZE]) = F::from(0_u32);
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+    }
+}
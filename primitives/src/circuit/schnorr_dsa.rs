@@ -19,7 +19,7 @@ use jf_plonk::{
             ecc::{Point, PointVariable},
             rescue::RescueGadget,
         },
-        Circuit, PlonkCircuit, Variable,
+        BoolVar, Circuit, PlonkCircuit, Variable,
     },
     errors::PlonkError,
 };
@@ -69,13 +69,15 @@ where
         vk: &VerKeyVar,
         msg: &[Variable],
         sig: &SignatureVar,
-    ) -> Result<Variable, PlonkError>;
+    ) -> Result<BoolVar, PlonkError>;
 
-    /// Create a signature variable from a signature `sig`.
+    /// Create a signature variable from a signature `sig`, whose point `R` is
+    /// constrained to be on the curve.
     fn create_signature_variable(&mut self, sig: &Signature<P>)
         -> Result<SignatureVar, PlonkError>;
 
-    /// Create a signature verification key variable from a key `vk`.
+    /// Create a signature verification key variable from a key `vk`, which is
+    /// constrained to be on the curve.
     fn create_signature_vk_variable(&mut self, vk: &VerKey<P>) -> Result<VerKeyVar, PlonkError>;
 
     /// Compute the two point variables to be compared in the signature
@@ -110,7 +112,7 @@ where
         vk: &VerKeyVar,
         msg: &[Variable],
         sig: &SignatureVar,
-    ) -> Result<Variable, PlonkError> {
+    ) -> Result<BoolVar, PlonkError> {
         let (p1, p2) = <Self as SignatureGadget<F, P>>::verify_sig_core(self, vk, msg, sig)?;
         self.is_equal_point(&p1, &p2)
     }
@@ -123,11 +125,13 @@ where
             s: self.create_variable(fr_to_fq::<F, P>(&sig.s))?,
             R: self.create_point_variable(Point::from(sig.R))?,
         };
+        self.on_curve_gate::<P>(&sig_var.R)?;
         Ok(sig_var)
     }
 
     fn create_signature_vk_variable(&mut self, vk: &VerKey<P>) -> Result<VerKeyVar, PlonkError> {
         let vk_var = VerKeyVar(self.create_point_variable(Point::from(vk.0))?);
+        self.on_curve_gate::<P>(&vk_var.0)?;
         Ok(vk_var)
     }
 
@@ -272,6 +276,17 @@ mod tests {
         *bad_circuit.witness_mut(bit) = F::one();
         assert!(bad_circuit.check_circuit_satisfiability(&[]).is_err());
 
+        // Points that are not on the curve
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let vk_var = SignatureGadget::<F, P>::create_signature_vk_variable(&mut circuit, vk)?;
+        let sig_var = SignatureGadget::<F, P>::create_signature_variable(&mut circuit, &sig)?;
+        assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+        *circuit.witness_mut(vk_var.0.get_x()) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        *circuit.witness_mut(vk_var.0.get_x()) -= F::one();
+        *circuit.witness_mut(sig_var.R.get_y()) += F::one();
+        assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+
         Ok(())
     }
 
@@ -313,6 +328,6 @@ mod tests {
             .collect::<Result<Vec<_>, PlonkError>>()?;
         let bit =
             SignatureGadget::<_, P>::is_valid_signature(&mut circuit, &vk_var, &msg_var, &sig_var)?;
-        Ok((circuit, bit))
+        Ok((circuit, bit.into()))
     }
 }
@@ -0,0 +1,246 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Circuit implementation of a sparse Merkle tree, with proofs of membership
+//! and of absence of keys (e.g. for nullifier-set non-inclusion checks).
+
+use crate::circuit::merkle_tree::{MerklePathVars, MerkleTreeHelperGadget};
+use ark_ff::PrimeField;
+use ark_std::format;
+use jf_plonk::{
+    circuit::{customized::rescue::RescueGadget, Circuit, PlonkCircuit, Variable},
+    errors::{CircuitError::ParameterError, PlonkError},
+};
+use jf_rescue::RescueParameter;
+
+/// Circuit implementation of a sparse Merkle tree. The Merkle path variables
+/// are created with `MerkleTreeGadget::add_merkle_path_variable` from the
+/// paths returned by `SparseMerkleTree::lookup`.
+pub trait SparseMerkleTreeGadget<F: PrimeField> {
+    /// Computes the root of a sparse Merkle tree from the value of the leaf at
+    /// `key` and its authentication path. The positions of the path nodes are
+    /// constrained to be the base-3 digits of `key`.
+    /// * `key` - variable corresponding to the key of the leaf.
+    /// * `leaf` - variable corresponding to the value of the leaf.
+    /// * `path_vars` - variables corresponding to the Merkle path.
+    /// * `returns` - variable corresponding to the root value of the tree.
+    fn compute_sparse_merkle_root(
+        &mut self,
+        key: Variable,
+        leaf: Variable,
+        path_vars: &MerklePathVars,
+    ) -> Result<Variable, PlonkError>;
+
+    /// Checks that `elem` is stored at `key` in a sparse Merkle tree of height
+    /// `height` and root `root_var`.
+    /// * `key` - variable corresponding to the key of the element.
+    /// * `elem` - variable corresponding to the element.
+    /// * `path_vars` - variables corresponding to the Merkle path.
+    /// * `root_var` - variable corresponding to the root value of the tree.
+    /// * `height` - height of the tree.
+    /// * `returns` - error if the Merkle path does not have `height` nodes.
+    fn check_sparse_merkle_membership(
+        &mut self,
+        key: Variable,
+        elem: Variable,
+        path_vars: &MerklePathVars,
+        root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError>;
+
+    /// Checks that the leaf at `key` is empty in a sparse Merkle tree of height
+    /// `height` and root `root_var`.
+    /// * `key` - variable corresponding to the key.
+    /// * `path_vars` - variables corresponding to the Merkle path.
+    /// * `root_var` - variable corresponding to the root value of the tree.
+    /// * `height` - height of the tree.
+    /// * `returns` - error if the Merkle path does not have `height` nodes.
+    fn check_sparse_merkle_non_membership(
+        &mut self,
+        key: Variable,
+        path_vars: &MerklePathVars,
+        root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError>;
+}
+
+impl<F> SparseMerkleTreeGadget<F> for PlonkCircuit<F>
+where
+    F: RescueParameter,
+{
+    fn compute_sparse_merkle_root(
+        &mut self,
+        key: Variable,
+        leaf: Variable,
+        path_vars: &MerklePathVars,
+    ) -> Result<Variable, PlonkError> {
+        let one = F::one();
+        let one_var = self.one();
+
+        // the position of a node is 1 - is_left_child + is_right_child, and the
+        // key is obtained from the positions starting from the root
+        let mut key_acc = self.zero();
+        for node in path_vars.nodes.iter().rev() {
+            key_acc = self.lc(
+                &[
+                    key_acc,
+                    node.is_left_child.into(),
+                    node.is_right_child.into(),
+                    one_var,
+                ],
+                &[F::from(3u32), -one, one, one],
+            )?;
+        }
+        self.equal_gate(key_acc, key)?;
+
+        let mut cur_label = leaf;
+        for node in path_vars.nodes.iter() {
+            let input_labels = self.permute(
+                cur_label,
+                node.sibling1,
+                node.sibling2,
+                node.is_left_child,
+                node.is_right_child,
+            )?;
+            cur_label = self.rescue_sponge_no_padding(&input_labels, 1)?[0];
+        }
+        Ok(cur_label)
+    }
+
+    fn check_sparse_merkle_membership(
+        &mut self,
+        key: Variable,
+        elem: Variable,
+        path_vars: &MerklePathVars,
+        root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError> {
+        check_path_len(path_vars, height)?;
+        // leaf label = H(0, key, elem)
+        let zero_var = self.zero();
+        let leaf = self.rescue_sponge_no_padding(&[zero_var, key, elem], 1)?[0];
+        let computed_root_var = self.compute_sparse_merkle_root(key, leaf, path_vars)?;
+        self.equal_gate(computed_root_var, root_var)
+    }
+
+    fn check_sparse_merkle_non_membership(
+        &mut self,
+        key: Variable,
+        path_vars: &MerklePathVars,
+        root_var: Variable,
+        height: u8,
+    ) -> Result<(), PlonkError> {
+        check_path_len(path_vars, height)?;
+        // the label of an empty leaf is zero
+        let zero_var = self.zero();
+        let computed_root_var = self.compute_sparse_merkle_root(key, zero_var, path_vars)?;
+        self.equal_gate(computed_root_var, root_var)
+    }
+}
+
+fn check_path_len(path_vars: &MerklePathVars, height: u8) -> Result<(), PlonkError> {
+    if path_vars.nodes.len() != height as usize {
+        return Err(ParameterError(format!(
+            "the Merkle path has {} nodes, the tree height is {}",
+            path_vars.nodes.len(),
+            height
+        ))
+        .into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        circuit::{merkle_tree::MerkleTreeGadget, sparse_merkle_tree::SparseMerkleTreeGadget},
+        sparse_merkle_tree::SparseMerkleTree,
+    };
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bls12_381_bandersnatch::Fq as FqEd381b;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use jf_plonk::{
+        circuit::{Circuit, PlonkCircuit},
+        errors::PlonkError,
+    };
+    use jf_rescue::RescueParameter;
+
+    const HEIGHT: u8 = 4;
+
+    // Builds a circuit checking the membership of `elem` at `key` if `elem` is
+    // some, or the absence of `key` otherwise, with the path of `path_key`.
+    fn build_circuit<F: RescueParameter>(
+        smt: &SparseMerkleTree<F>,
+        key: u64,
+        elem: Option<F>,
+        path_key: u64,
+        height: u8,
+    ) -> Result<PlonkCircuit<F>, PlonkError> {
+        let mut circuit = PlonkCircuit::<F>::new_turbo_plonk();
+        let (_, path) = smt.lookup(path_key).unwrap();
+        let key_var = circuit.create_variable(F::from(key))?;
+        let root_var = circuit.create_variable(smt.root().to_scalar())?;
+        let path_vars = circuit.add_merkle_path_variable(&path)?;
+        match elem {
+            Some(elem) => {
+                let elem_var = circuit.create_variable(elem)?;
+                circuit.check_sparse_merkle_membership(
+                    key_var, elem_var, &path_vars, root_var, height,
+                )?
+            },
+            None => {
+                circuit.check_sparse_merkle_non_membership(key_var, &path_vars, root_var, height)?
+            },
+        }
+        Ok(circuit)
+    }
+
+    #[test]
+    fn test_sparse_merkle_membership() -> Result<(), PlonkError> {
+        test_sparse_merkle_membership_helper::<FqEd254>()?;
+        test_sparse_merkle_membership_helper::<FqEd377>()?;
+        test_sparse_merkle_membership_helper::<FqEd381>()?;
+        test_sparse_merkle_membership_helper::<FqEd381b>()?;
+        test_sparse_merkle_membership_helper::<Fq377>()
+    }
+
+    fn test_sparse_merkle_membership_helper<F: RescueParameter>() -> Result<(), PlonkError> {
+        let mut smt = SparseMerkleTree::<F>::new(HEIGHT).unwrap();
+        let keys = [2u64, 13, 14, 60];
+        for &key in keys.iter() {
+            smt.insert(key, F::from(key * 7)).unwrap();
+        }
+
+        for &key in keys.iter() {
+            let elem = F::from(key * 7);
+            let circuit = build_circuit(&smt, key, Some(elem), key, HEIGHT)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            // wrong element
+            let circuit = build_circuit(&smt, key, Some(elem + F::one()), key, HEIGHT)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            // a present key is not absent
+            let circuit = build_circuit(&smt, key, None, key, HEIGHT)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        for &key in [0u64, 12, 15, 80].iter() {
+            let circuit = build_circuit(&smt, key, None, key, HEIGHT)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_ok());
+            // the path of an empty leaf does not prove the absence of another key
+            let circuit = build_circuit(&smt, 14, None, key, HEIGHT)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+            let circuit = build_circuit(&smt, (key + 27) % 81, None, key, HEIGHT)?;
+            assert!(circuit.check_circuit_satisfiability(&[]).is_err());
+        }
+
+        // wrong height
+        assert!(build_circuit(&smt, 13, None, 13, HEIGHT + 1).is_err());
+        assert!(build_circuit(&smt, 13, Some(F::from(91u64)), 13, HEIGHT - 1).is_err());
+        Ok(())
+    }
+}
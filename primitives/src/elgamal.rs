@@ -7,8 +7,11 @@
 //! Implements the ElGamal encryption scheme.
 
 use crate::{
-    elgamal::Direction::{Decrypt, Encrypt},
     errors::PrimitivesError,
+    rescue_ctr::{
+        apply_counter_mode_stream,
+        Direction::{Decrypt, Encrypt},
+    },
 };
 use ark_ec::{
     group::Group,
@@ -24,12 +27,7 @@ use ark_std::{
     vec,
     vec::Vec,
 };
-use jf_rescue::{Permutation, RescueParameter, RescueVector, PRP, STATE_SIZE};
-use jf_utils::pad_with_zeros;
-use rayon::{
-    iter::{IndexedParallelIterator, ParallelIterator},
-    prelude::ParallelSliceMut,
-};
+use jf_rescue::{Permutation, RescueParameter, RescueVector};
 use zeroize::Zeroize;
 
 // =====================================================
@@ -67,7 +65,7 @@ pub(crate) struct DecKey<P>
 where
     P: Parameters + Clone,
 {
-    key: P::ScalarField,
+    pub(crate) key: P::ScalarField,
 }
 
 impl<P: Parameters + Clone> Drop for DecKey<P> {
@@ -88,7 +86,7 @@ where
     P: Parameters + Clone,
 {
     pub(crate) enc: EncKey<P>,
-    dec: DecKey<P>,
+    pub(crate) dec: DecKey<P>,
 }
 
 // =====================================================
@@ -221,7 +219,7 @@ where
         // since key was just sampled and to be used only once, we can allow NONCE = 0
         Ciphertext {
             ephemeral: ephemeral_key_pair.enc_key(),
-            data: apply_counter_mode_stream::<F, P>(&key, msg, &F::zero(), Encrypt),
+            data: apply_counter_mode_stream(&key, msg, &F::zero(), Encrypt),
         }
     }
 
@@ -261,7 +259,7 @@ where
             F::zero(),
         ]));
         // since key was just samples and to be used only once, we can have NONCE = 0
-        apply_counter_mode_stream::<F, P>(&key, ctext.data.as_slice(), &F::zero(), Decrypt)
+        apply_counter_mode_stream(&key, ctext.data.as_slice(), &F::zero(), Decrypt)
     }
 }
 
@@ -287,55 +285,6 @@ where
     }
 }
 
-pub(crate) enum Direction {
-    Encrypt,
-    Decrypt,
-}
-
-pub(crate) fn apply_counter_mode_stream<F, P>(
-    key: &RescueVector<F>,
-    data: &[F],
-    nonce: &F,
-    direction: Direction,
-) -> Vec<F>
-where
-    F: RescueParameter,
-    P: Parameters<BaseField = F> + Clone,
-{
-    let prp = PRP::default();
-    let round_keys = prp.key_schedule(key);
-    // compute stream
-    let mut output = data.to_vec();
-    // temporarily append dummy padding element
-    pad_with_zeros(&mut output, STATE_SIZE);
-
-    output
-        .par_chunks_exact_mut(STATE_SIZE)
-        .enumerate()
-        .for_each(|(i, output_chunk)| {
-            let stream_chunk = prp.prp_with_round_keys(
-                &round_keys,
-                &RescueVector::from(&[
-                    nonce.add(F::from(i as u64)),
-                    F::zero(),
-                    F::zero(),
-                    F::zero(),
-                ]),
-            );
-            for (output_elem, stream_elem) in
-                output_chunk.iter_mut().zip(stream_chunk.elems().iter())
-            {
-                match direction {
-                    Direction::Encrypt => output_elem.add_assign(stream_elem),
-                    Direction::Decrypt => output_elem.sub_assign(stream_elem),
-                }
-            }
-        });
-    // remove dummy padding elements
-    output.truncate(data.len());
-    output
-}
-
 #[cfg(test)]
 mod test {
     use super::{Ciphertext, DecKey, EncKey, KeyPair, UniformRand};
@@ -23,7 +23,11 @@ pub mod constants;
 pub mod elgamal;
 pub mod errors;
 pub mod merkle_tree;
+pub mod nullifier;
+pub mod pedersen;
 pub mod prf;
+pub mod rescue_ctr;
 pub mod schnorr_dsa;
+pub mod sparse_merkle_tree;
 
 pub(crate) mod utils;
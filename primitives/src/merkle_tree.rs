@@ -19,7 +19,8 @@ use ark_ff::{BigInteger, Field, PrimeField};
 use ark_serialize::{CanonicalDeserialize, CanonicalSerialize, Read, SerializationError, Write};
 use ark_std::{
     boxed::Box,
-    mem,
+    collections::BTreeMap,
+    format, mem,
     rand::{
         distributions::{Distribution, Standard},
         Rng,
@@ -261,6 +262,68 @@ pub(crate) fn hash<F: RescueParameter>(
     NodeValue(digest)
 }
 
+/// Check that `uids` is a valid list of leaf positions for a multiproof of a
+/// Merkle tree of height `height`, i.e. that it is not empty, that it is
+/// sorted in strictly increasing order and that the positions are smaller
+/// than the capacity of the tree.
+pub(crate) fn check_multi_proof_uids(uids: &[u64], height: u8) -> Result<(), PrimitivesError> {
+    if uids.is_empty() {
+        return Err(PrimitivesError::ParameterError(
+            "a multiproof should contain at least one leaf".to_string(),
+        ));
+    }
+    if uids.windows(2).any(|w| w[0] >= w[1]) {
+        return Err(PrimitivesError::ParameterError(
+            "the leaf positions of a multiproof should be strictly increasing".to_string(),
+        ));
+    }
+    if let Some(capacity) = (3_u64).checked_pow(height as u32) {
+        if uids[uids.len() - 1] >= capacity {
+            return Err(PrimitivesError::ParameterError(format!(
+                "leaf position {} is out of range for a tree of height {}",
+                uids[uids.len() - 1],
+                height
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Compute the root of a Merkle tree of height `height` from the values of
+/// several leaves, given as `(uid, value)` pairs whose positions are checked
+/// by `check_multi_proof_uids`. The nodes on the paths from the leaves to the
+/// root are computed with `hash`, and the values of their other children are
+/// obtained with `sibling(level, index)`, which is called level by level
+/// starting from the leaves, and by increasing index within a level.
+pub(crate) fn fold_merkle_multi_path<T: Copy, E>(
+    height: u8,
+    leaves: &[(u64, T)],
+    mut sibling: impl FnMut(u8, u64) -> Result<T, E>,
+    mut hash: impl FnMut(&[T; 3]) -> Result<T, E>,
+) -> Result<T, E> {
+    let mut nodes = leaves.to_vec();
+    for level in 0..height {
+        let mut parents = Vec::with_capacity(nodes.len());
+        let mut i = 0;
+        while i < nodes.len() {
+            let parent = nodes[i].0 / 3;
+            let mut children = [nodes[i].1; 3];
+            for (j, child) in children.iter_mut().enumerate() {
+                let index = 3 * parent + j as u64;
+                if i < nodes.len() && nodes[i].0 == index {
+                    *child = nodes[i].1;
+                    i += 1;
+                } else {
+                    *child = sibling(level, index)?;
+                }
+            }
+            parents.push((parent, hash(&children)?));
+        }
+        nodes = parents;
+    }
+    Ok(nodes[0].1)
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
 /// The result of querying at an index in the tree
 pub enum LookupResult<F, P> {
@@ -857,6 +920,20 @@ where
     }
 }
 
+/// Inclusive proof of several merkle leaves, whose authentication paths share
+/// their nodes: the proof only contains the siblings that cannot be computed
+/// from the leaves, each of them once.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Default, CanonicalSerialize, CanonicalDeserialize)]
+pub struct MerkleMultiProof<F: PrimeField> {
+    /// The positions of the leaves, in increasing order.
+    pub uids: Vec<u64>,
+    /// The leaf elements, in the order of `uids`.
+    pub leaves: Vec<F>,
+    /// The siblings, level by level from the leaves to the root, and from
+    /// left to right within a level.
+    pub nodes: Vec<NodeValue<F>>,
+}
+
 /// A wrapper of the merkle membership proof.
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(bound = "")]
@@ -1054,6 +1131,111 @@ where
         }
     }
 
+    /// Returns a multiproof of the leaves at several positions
+    /// * `uids` - leaf positions, in strictly increasing order
+    /// * `returns` - error if the positions are invalid, or if one of the
+    ///   leaves is empty or has been forgotten
+    pub fn get_multi_proof(&self, uids: &[u64]) -> Result<MerkleMultiProof<F>, PrimitivesError> {
+        check_multi_proof_uids(uids, self.height)?;
+        let mut leaves = Vec::with_capacity(uids.len());
+        let mut siblings = BTreeMap::new();
+        for &uid in uids {
+            let (_, proof) = self.get_leaf(uid).expect_ok()?;
+            leaves.push(proof.leaf.0);
+            let mut index = uid;
+            for (level, node) in proof.path.nodes.iter().enumerate() {
+                let sibling_indices = (0..3).map(|j| 3 * (index / 3) + j).filter(|&j| j != index);
+                for (sibling_index, value) in
+                    sibling_indices.zip([node.sibling1, node.sibling2].iter())
+                {
+                    siblings.insert((level as u8, sibling_index), *value);
+                }
+                index /= 3;
+            }
+        }
+
+        // collect the siblings that are not on the paths
+        let mut nodes = vec![];
+        let positions: Vec<(u64, ())> = uids.iter().map(|&uid| (uid, ())).collect();
+        fold_merkle_multi_path(
+            self.height,
+            &positions,
+            |level, index| {
+                let value = siblings.get(&(level, index)).ok_or_else(|| {
+                    PrimitivesError::InternalError(format!(
+                        "missing sibling {} at level {}",
+                        index, level
+                    ))
+                })?;
+                nodes.push(*value);
+                Ok::<(), PrimitivesError>(())
+            },
+            |_| Ok(()),
+        )?;
+        Ok(MerkleMultiProof {
+            uids: uids.to_vec(),
+            leaves,
+            nodes,
+        })
+    }
+
+    /// Verify that several elements are leaves of a Merkle tree given the
+    /// root of the tree and a multiproof
+    /// * `root_value` - value of the root of the tree
+    /// * `height` - height of the tree
+    /// * `proof` - leaf positions and elements, and siblings of the paths
+    /// * `returns` - Ok(()) if the verification succeeds, an error otherwise
+    pub fn check_multi_proof(
+        root_value: NodeValue<F>,
+        height: u8,
+        proof: &MerkleMultiProof<F>,
+    ) -> Result<(), PrimitivesError> {
+        check_multi_proof_uids(&proof.uids, height)?;
+        if proof.leaves.len() != proof.uids.len() {
+            return Err(PrimitivesError::ParameterError(format!(
+                "the multiproof has {} positions but {} leaves",
+                proof.uids.len(),
+                proof.leaves.len()
+            )));
+        }
+        let leaves: Vec<(u64, NodeValue<F>)> = proof
+            .uids
+            .iter()
+            .zip(proof.leaves.iter())
+            .map(|(&uid, &elem)| (uid, MerkleNode::new_leaf(uid, elem).value()))
+            .collect();
+        let mut nodes = proof.nodes.iter();
+        let computed_root = fold_merkle_multi_path(
+            height,
+            &leaves,
+            |_, _| {
+                nodes.next().copied().ok_or_else(|| {
+                    PrimitivesError::VerificationError("the multiproof is too short".to_string())
+                })
+            },
+            |children| {
+                // Required to prevent tree extension attacks
+                if children[0] == NodeValue::empty_node_value() {
+                    return Err(PrimitivesError::VerificationError(
+                        "a left child of the multiproof is empty".to_string(),
+                    ));
+                }
+                Ok(hash(&children[0], &children[1], &children[2]))
+            },
+        )?;
+        if nodes.next().is_some() {
+            return Err(PrimitivesError::VerificationError(
+                "the multiproof is too long".to_string(),
+            ));
+        }
+        if computed_root != root_value {
+            return Err(PrimitivesError::VerificationError(
+                "the computed root does not match".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
     /// Trim the leaf at position `i` from memory, if present.
     /// Will not trim if position `i` is the last inserted leaf position.
     /// Return is identical to result if `get_leaf(pos)` were called before this
@@ -2291,4 +2473,61 @@ mod mt_tests {
         assert_eq!(full_tree_commitment, built_sparse_commitment);
         assert_eq!(full_tree_commitment, commitment_out);
     }
+
+    #[test]
+    fn test_mt_multi_proof() {
+        test_mt_multi_proof_helper::<Fq254>();
+        test_mt_multi_proof_helper::<Fq377>();
+        test_mt_multi_proof_helper::<Fq381>();
+    }
+
+    fn test_mt_multi_proof_helper<F: RescueParameter>() {
+        let height = 3;
+        let mut mt = MerkleTree::<F>::new(height).unwrap();
+        for i in 0..20u64 {
+            mt.push(F::from(100 + i));
+        }
+        let root = mt.commitment().root_value;
+
+        let uids = [1u64, 2, 5, 13, 19];
+        let proof = mt.get_multi_proof(&uids).unwrap();
+        assert_eq!(proof.uids, uids.to_vec());
+        assert_eq!(
+            proof.leaves,
+            uids.iter()
+                .map(|&uid| F::from(100 + uid))
+                .collect::<Vec<_>>()
+        );
+        // the shared siblings are deduplicated
+        assert!(proof.nodes.len() < uids.len() * 2 * height as usize);
+        assert!(MerkleTree::check_multi_proof(root, height, &proof).is_ok());
+
+        // a multiproof of a single leaf has the siblings of its path
+        let single_proof = mt.get_multi_proof(&[7]).unwrap();
+        assert_eq!(single_proof.nodes.len(), 2 * height as usize);
+        assert!(MerkleTree::check_multi_proof(root, height, &single_proof).is_ok());
+
+        // wrong elements, nodes, root or height
+        let mut bad_proof = proof.clone();
+        bad_proof.leaves[2] = F::from(1000u64);
+        assert!(MerkleTree::check_multi_proof(root, height, &bad_proof).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.nodes[0] = NodeValue::from(1000u64);
+        assert!(MerkleTree::check_multi_proof(root, height, &bad_proof).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.nodes.pop();
+        assert!(MerkleTree::check_multi_proof(root, height, &bad_proof).is_err());
+        let mut bad_proof = proof.clone();
+        bad_proof.nodes.push(NodeValue::from(1000u64));
+        assert!(MerkleTree::check_multi_proof(root, height, &bad_proof).is_err());
+        assert!(MerkleTree::check_multi_proof(NodeValue::from(1u64), height, &proof).is_err());
+        assert!(MerkleTree::check_multi_proof(root, height + 1, &proof).is_err());
+
+        // invalid or empty positions
+        assert!(mt.get_multi_proof(&[]).is_err());
+        assert!(mt.get_multi_proof(&[2, 1]).is_err());
+        assert!(mt.get_multi_proof(&[2, 2]).is_err());
+        assert!(mt.get_multi_proof(&[2, 20]).is_err());
+        assert!(mt.get_multi_proof(&[2, 27]).is_err());
+    }
 }
@@ -0,0 +1,83 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! This module implements the derivation of the nullifier of a note from the
+//! secret key of its owner and its commitment, with the rescue based PRF.
+//!
+//! The nullifier is `PRF(sk, [dom_sep, cm])`, where the domain separator
+//! distinguishes the nullifiers from the other PRF outputs of the same key.
+//! It is deterministic, so that a note can only be spent once, and it does
+//! not reveal the spent commitment without the key.
+
+use crate::prf::{PrfKey, PRF};
+use ark_ff::PrimeField;
+use ark_serialize::*;
+use jf_rescue::RescueParameter;
+
+/// The domain separator of the PRF inputs of the nullifiers, i.e. the
+/// big-endian encoding of "nullify".
+pub(crate) const NULLIFIER_DOM_SEP: u64 = 0x6e75_6c6c_6966_79;
+
+#[derive(
+    Clone, Copy, Default, Debug, PartialEq, Eq, Hash, CanonicalSerialize, CanonicalDeserialize,
+)]
+/// The nullifier of a note.
+pub struct Nullifier<F: PrimeField>(pub(crate) F);
+
+impl<F: PrimeField> Nullifier<F> {
+    /// return the internal field value
+    pub fn internal(&self) -> F {
+        self.0
+    }
+}
+
+impl<F: RescueParameter> Nullifier<F> {
+    /// Derive the nullifier of the note of commitment `commitment` owned by
+    /// the secret key `key`.
+    pub fn derive(key: &PrfKey<F>, commitment: &F) -> Self {
+        // safe unwrap: the input length matches the instance
+        let output = PRF::new(2, 1)
+            .eval(key, &[F::from(NULLIFIER_DOM_SEP), *commitment])
+            .unwrap();
+        Nullifier(output[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::UniformRand;
+
+    #[test]
+    fn test_nullifier() {
+        test_nullifier_helper::<FqEd254>();
+        test_nullifier_helper::<FqEd377>();
+        test_nullifier_helper::<FqEd381>();
+        test_nullifier_helper::<Fq377>();
+    }
+
+    fn test_nullifier_helper<F: RescueParameter>() {
+        let mut rng = ark_std::test_rng();
+        let prf = PRF::<F>::new(1, 1);
+        let key = prf.key_gen(&mut rng);
+        let other_key = prf.key_gen(&mut rng);
+        let commitment = F::rand(&mut rng);
+
+        let nullifier = Nullifier::derive(&key, &commitment);
+        assert_eq!(nullifier, Nullifier::derive(&key, &commitment));
+        assert_ne!(nullifier, Nullifier::derive(&other_key, &commitment));
+        assert_ne!(nullifier, Nullifier::derive(&key, &(commitment + F::one())));
+        // the nullifier differs from the PRF output on the commitment alone
+        assert_ne!(
+            nullifier.internal(),
+            prf.eval(&key, &[commitment]).unwrap()[0]
+        );
+    }
+}
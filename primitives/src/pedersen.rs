@@ -0,0 +1,156 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Implements the Pedersen (vector) commitment scheme over twisted Edwards
+//! curves.
+//!
+//! Unlike the rescue hash based commitment, the commitments are additively
+//! homomorphic: the sum of the commitments to two inputs is the commitment to
+//! the sum of the inputs under the sum of the blinding factors.
+
+use crate::errors::PrimitivesError;
+use ark_ec::{
+    twisted_edwards_extended::{GroupAffine, GroupProjective},
+    AffineCurve, ProjectiveCurve, TEModelParameters as Parameters,
+};
+use ark_ff::{PrimeField, UniformRand};
+use ark_std::{format, rand::Rng, string::String, vec::Vec};
+
+#[derive(Clone, Debug, PartialEq)]
+/// Pedersen commitment instance for user defined input size (in scalar
+/// elements)
+pub struct PedersenCommitment<P: Parameters + Clone> {
+    generators: Vec<GroupAffine<P>>,
+    blinding_generator: GroupAffine<P>,
+}
+
+impl<P: Parameters + Clone> PedersenCommitment<P> {
+    /// Create a new commitment instance for inputs of length `input_len`,
+    /// with generators sampled from `rng`. The discrete logarithms of the
+    /// generators must be unknown to the committer, hence the randomness
+    /// should be public, e.g. a seed derived from a public string.
+    pub fn setup<R: Rng>(rng: &mut R, input_len: usize) -> PedersenCommitment<P> {
+        assert!(input_len > 0, "input_len must be positive");
+        let generators = (0..input_len)
+            .map(|_| GroupProjective::<P>::rand(rng).into_affine())
+            .collect();
+        PedersenCommitment {
+            generators,
+            blinding_generator: GroupProjective::<P>::rand(rng).into_affine(),
+        }
+    }
+
+    /// The length of the inputs of the commitment instance.
+    pub fn input_len(&self) -> usize {
+        self.generators.len()
+    }
+
+    /// The generators of the inputs.
+    pub fn generators(&self) -> &[GroupAffine<P>] {
+        &self.generators
+    }
+
+    /// The generator of the blinding factor.
+    pub fn blinding_generator(&self) -> &GroupAffine<P> {
+        &self.blinding_generator
+    }
+
+    /// Commits to `input` slice using blinding `blind`. Return
+    /// Err(PrimitivesError::ParameterError) if input.len() !=
+    /// self.input_len()
+    pub fn commit(
+        &self,
+        input: &[P::ScalarField],
+        blind: &P::ScalarField,
+    ) -> Result<GroupAffine<P>, PrimitivesError> {
+        if input.len() != self.input_len() {
+            return Err(PrimitivesError::ParameterError(format!(
+                "Commitment error: input length ({}) does not match \
+                instance's message length ({})",
+                input.len(),
+                self.input_len(),
+            )));
+        }
+        let mut result = self.blinding_generator.mul(blind.into_repr());
+        for (generator, scalar) in self.generators.iter().zip(input.iter()) {
+            result += generator.mul(scalar.into_repr());
+        }
+        Ok(result.into_affine())
+    }
+
+    /// Verifies `commitment` against `input` and `blind`.
+    /// Returns Ok(()) on success. Otherwise, returns
+    /// PrimitivesError::ParameterError if input.len() != self.input_len(),
+    /// and PrimitivesError::VerificationError if commitment is not valid.
+    pub fn verify(
+        &self,
+        input: &[P::ScalarField],
+        blind: &P::ScalarField,
+        commitment: &GroupAffine<P>,
+    ) -> Result<(), PrimitivesError> {
+        if self.commit(input, blind)? == *commitment {
+            Ok(())
+        } else {
+            Err(PrimitivesError::VerificationError(String::from(
+                "Commitment verification failed",
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::pedersen::PedersenCommitment;
+    use ark_ec::TEModelParameters as Parameters;
+    use ark_ed_on_bls12_377::EdwardsParameters as ParamEd377;
+    use ark_ed_on_bls12_381::EdwardsParameters as ParamEd381;
+    use ark_ed_on_bls12_381_bandersnatch::EdwardsParameters as ParamEd381b;
+    use ark_ed_on_bn254::EdwardsParameters as ParamEd254;
+    use ark_ff::UniformRand;
+    use ark_std::vec::Vec;
+
+    #[test]
+    fn test_pedersen_commit() {
+        test_pedersen_commit_helper::<ParamEd254>();
+        test_pedersen_commit_helper::<ParamEd377>();
+        test_pedersen_commit_helper::<ParamEd381>();
+        test_pedersen_commit_helper::<ParamEd381b>();
+    }
+
+    fn test_pedersen_commit_helper<P: Parameters + Clone>() {
+        let mut prng = ark_std::test_rng();
+        let commitment = PedersenCommitment::<P>::setup(&mut prng, 3);
+        assert_eq!(commitment.input_len(), 3);
+
+        let input: Vec<P::ScalarField> = (0..3).map(|_| P::ScalarField::rand(&mut prng)).collect();
+        let blind = P::ScalarField::rand(&mut prng);
+        let c = commitment.commit(&input, &blind).unwrap();
+        assert!(commitment.verify(&input, &blind, &c).is_ok());
+        // bad blinding factor
+        let bad_blind = blind + P::ScalarField::from(1u8);
+        assert!(commitment.verify(&input, &bad_blind, &c).is_err());
+        // bad input
+        let bad_input = [input[1], input[0], input[2]];
+        assert!(commitment.verify(&bad_input, &blind, &c).is_err());
+        // bad input sizes
+        assert!(commitment.verify(&input[0..2], &blind, &c).is_err());
+        assert!(commitment.commit(&[input[0]; 4], &blind).is_err());
+
+        // the commitments are additively homomorphic
+        let other_input: Vec<P::ScalarField> =
+            (0..3).map(|_| P::ScalarField::rand(&mut prng)).collect();
+        let other_blind = P::ScalarField::rand(&mut prng);
+        let other_c = commitment.commit(&other_input, &other_blind).unwrap();
+        let sum_input: Vec<P::ScalarField> = input
+            .iter()
+            .zip(other_input.iter())
+            .map(|(a, b)| *a + b)
+            .collect();
+        assert!(commitment
+            .verify(&sum_input, &(blind + other_blind), &(c + other_c))
+            .is_ok());
+    }
+}
@@ -0,0 +1,116 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Implements the Rescue-based symmetric encryption in counter mode, which is
+//! the data encapsulation of the hybrid ElGamal encryption scheme.
+//!
+//! The `i`-th block of the key stream is the Rescue PRP of the state
+//! `(nonce + i, 0, 0, 0)` under the symmetric key, and the data is encrypted
+//! by adding the key stream to it.
+
+use ark_std::vec::Vec;
+use jf_rescue::{RescueParameter, RescueVector, PRP, STATE_SIZE};
+use jf_utils::pad_with_zeros;
+use rayon::{
+    iter::{IndexedParallelIterator, ParallelIterator},
+    prelude::ParallelSliceMut,
+};
+
+/// Rescue counter mode encryption function
+/// * `key` - symmetric key, which should not be reused with the same nonce
+/// * `nonce` - nonce, i.e. the initial counter
+/// * `data` - plaintext
+/// * `returns` - ciphertext of the same length as the plaintext
+pub fn encrypt<F: RescueParameter>(key: &RescueVector<F>, nonce: &F, data: &[F]) -> Vec<F> {
+    apply_counter_mode_stream(key, data, nonce, Direction::Encrypt)
+}
+
+/// Rescue counter mode decryption function
+/// * `key` - symmetric key
+/// * `nonce` - nonce used for the encryption
+/// * `ctxt` - ciphertext
+/// * `returns` - plaintext of the same length as the ciphertext
+pub fn decrypt<F: RescueParameter>(key: &RescueVector<F>, nonce: &F, ctxt: &[F]) -> Vec<F> {
+    apply_counter_mode_stream(key, ctxt, nonce, Direction::Decrypt)
+}
+
+pub(crate) enum Direction {
+    Encrypt,
+    Decrypt,
+}
+
+pub(crate) fn apply_counter_mode_stream<F: RescueParameter>(
+    key: &RescueVector<F>,
+    data: &[F],
+    nonce: &F,
+    direction: Direction,
+) -> Vec<F> {
+    let prp = PRP::default();
+    let round_keys = prp.key_schedule(key);
+    // compute stream
+    let mut output = data.to_vec();
+    // temporarily append dummy padding element
+    pad_with_zeros(&mut output, STATE_SIZE);
+
+    output
+        .par_chunks_exact_mut(STATE_SIZE)
+        .enumerate()
+        .for_each(|(i, output_chunk)| {
+            let stream_chunk = prp.prp_with_round_keys(
+                &round_keys,
+                &RescueVector::from(&[*nonce + F::from(i as u64), F::zero(), F::zero(), F::zero()]),
+            );
+            for (output_elem, stream_elem) in
+                output_chunk.iter_mut().zip(stream_chunk.elems().iter())
+            {
+                match direction {
+                    Direction::Encrypt => *output_elem += stream_elem,
+                    Direction::Decrypt => *output_elem -= stream_elem,
+                }
+            }
+        });
+    // remove dummy padding elements
+    output.truncate(data.len());
+    output
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decrypt, encrypt};
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+    use ark_std::{vec::Vec, UniformRand};
+    use jf_rescue::{RescueParameter, RescueVector, STATE_SIZE};
+
+    #[test]
+    fn test_enc_and_dec() {
+        test_enc_and_dec_helper::<FqEd254>();
+        test_enc_and_dec_helper::<FqEd377>();
+        test_enc_and_dec_helper::<FqEd381>();
+        test_enc_and_dec_helper::<Fq377>();
+    }
+
+    fn test_enc_and_dec_helper<F: RescueParameter>() {
+        let mut rng = ark_std::test_rng();
+        let key = RescueVector::from(&[
+            F::rand(&mut rng),
+            F::rand(&mut rng),
+            F::rand(&mut rng),
+            F::rand(&mut rng),
+        ]);
+        let nonce = F::rand(&mut rng);
+        let data: Vec<F> = (0..3 * STATE_SIZE + 2).map(|i| F::from(i as u32)).collect();
+        let ctxt = encrypt(&key, &nonce, &data);
+        assert_eq!(ctxt.len(), data.len());
+        assert_ne!(ctxt, data);
+        assert_eq!(decrypt(&key, &nonce, &ctxt), data);
+        // the key stream depends on the nonce
+        assert_ne!(encrypt(&key, &(nonce + F::one()), &data), ctxt);
+        assert_ne!(decrypt(&key, &(nonce + F::one()), &ctxt), data);
+    }
+}
@@ -0,0 +1,265 @@
+// Copyright (c) 2022 Espresso Systems (espressosys.com)
+// This file is part of the Jellyfish library.
+
+// You should have received a copy of the MIT License
+// along with the Jellyfish library. If not, see <https://mit-license.org/>.
+
+//! Implementation of a sparse Merkle tree, supporting proofs of membership
+//! and of absence of keys.
+//!
+//! As for the Merkle tree of `merkle_tree`, the tree is ternary and internal
+//! node values are obtained by computing `H(a,b,c)` with the rescue hash
+//! function, where `a,b,c` are the values of the children. The leaves are
+//! indexed by keys in `[0, 3^height)`, whose base-3 digits give the position
+//! of the leaf, and the leaf value of an element `elem` at key `key` is
+//! `H(0,key,elem)`. Empty leaves have value zero, and an empty subtree of
+//! height `i` has the default value `H(d,d,d)`, where `d` is the default value
+//! of the empty subtrees of height `i - 1`.
+
+use crate::{
+    errors::PrimitivesError,
+    merkle_tree::{hash, MerklePath, MerklePathNode, NodePos, NodeValue},
+};
+use ark_std::{collections::BTreeMap, format, vec, vec::Vec};
+use jf_rescue::RescueParameter;
+
+/// A sparse ternary Merkle tree of fixed height.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseMerkleTree<F: RescueParameter> {
+    height: u8,
+    capacity: u64,
+    leaves: BTreeMap<u64, F>,
+    default_values: Vec<NodeValue<F>>,
+}
+
+impl<F: RescueParameter> SparseMerkleTree<F> {
+    /// Create an empty sparse Merkle tree of a specific height.
+    /// * `height` - height of the tree (number of hops from the root to a
+    ///   leaf).
+    /// * `returns` - None if the capacity of the tree overflows a u64
+    pub fn new(height: u8) -> Option<Self> {
+        let capacity = (3_u64).checked_pow(height as u32)?;
+        let mut default_values = vec![NodeValue::empty_node_value()];
+        for _ in 0..height {
+            // safe unwrap: the vector is not empty
+            let d = *default_values.last().unwrap();
+            default_values.push(hash(&d, &d, &d));
+        }
+        Some(SparseMerkleTree {
+            height,
+            capacity,
+            leaves: BTreeMap::new(),
+            default_values,
+        })
+    }
+
+    /// Get the height of the tree.
+    pub fn height(&self) -> u8 {
+        self.height
+    }
+
+    /// Get the number of non-empty leaves.
+    pub fn num_leaves(&self) -> u64 {
+        self.leaves.len() as u64
+    }
+
+    /// The default value of the empty subtrees of height `level`, or None if
+    /// `level` is larger than the height of the tree.
+    pub fn default_value(&self, level: u8) -> Option<NodeValue<F>> {
+        self.default_values.get(level as usize).copied()
+    }
+
+    /// Get the value of the root of the tree.
+    pub fn root(&self) -> NodeValue<F> {
+        self.node_value(self.height, 0)
+    }
+
+    /// Get the element at `key`, or None if the leaf is empty.
+    pub fn get(&self, key: u64) -> Option<F> {
+        self.leaves.get(&key).copied()
+    }
+
+    /// Insert an element at `key`, replacing the previous one if any.
+    /// * `returns` - the previous element, or an error if `key` is out of
+    ///   range
+    pub fn insert(&mut self, key: u64, elem: F) -> Result<Option<F>, PrimitivesError> {
+        self.check_key(key)?;
+        Ok(self.leaves.insert(key, elem))
+    }
+
+    /// Remove the element at `key`, leaving an empty leaf.
+    /// * `returns` - the removed element, or an error if `key` is out of range
+    pub fn remove(&mut self, key: u64) -> Result<Option<F>, PrimitivesError> {
+        self.check_key(key)?;
+        Ok(self.leaves.remove(&key))
+    }
+
+    /// Get the element at `key`, if any, and the authentication path of the
+    /// leaf, which is a proof of membership of the element or a proof of
+    /// absence of the key.
+    /// * `returns` - error if `key` is out of range
+    pub fn lookup(&self, key: u64) -> Result<(Option<F>, MerklePath<F>), PrimitivesError> {
+        self.check_key(key)?;
+        let mut nodes = vec![];
+        let mut index = key;
+        for level in 0..self.height {
+            let parent = index / 3;
+            let siblings: Vec<NodeValue<F>> = (0..3)
+                .filter(|&i| parent * 3 + i != index)
+                .map(|i| self.node_value(level, parent * 3 + i))
+                .collect();
+            let pos = match index % 3 {
+                0 => NodePos::Left,
+                1 => NodePos::Middle,
+                _ => NodePos::Right,
+            };
+            nodes.push(MerklePathNode::new(pos, siblings[0], siblings[1]));
+            index = parent;
+        }
+        Ok((self.get(key), MerklePath::new(nodes)))
+    }
+
+    /// Verify a proof of membership of `elem` at `key`, or, if `elem` is None,
+    /// a proof of absence of `key`, in a tree with root `root`.
+    /// * `returns` - Ok(()) if the verification succeeds, an error otherwise
+    pub fn verify(
+        root: &NodeValue<F>,
+        key: u64,
+        elem: Option<F>,
+        path: &MerklePath<F>,
+    ) -> Result<(), PrimitivesError> {
+        let mut index = key;
+        let mut value = leaf_value(key, elem);
+        for node in path.nodes.iter() {
+            let expected_pos = match index % 3 {
+                0 => NodePos::Left,
+                1 => NodePos::Middle,
+                _ => NodePos::Right,
+            };
+            if node.pos != expected_pos {
+                return Err(PrimitivesError::VerificationError(format!(
+                    "the path does not lead to the leaf of key {}",
+                    key
+                )));
+            }
+            value = match node.pos {
+                NodePos::Left => hash(&value, &node.sibling1, &node.sibling2),
+                NodePos::Middle => hash(&node.sibling1, &value, &node.sibling2),
+                NodePos::Right => hash(&node.sibling1, &node.sibling2, &value),
+            };
+            index /= 3;
+        }
+        if index != 0 {
+            return Err(PrimitivesError::VerificationError(format!(
+                "key {} is out of range for a path of length {}",
+                key,
+                path.nodes.len()
+            )));
+        }
+        if value != *root {
+            return Err(PrimitivesError::VerificationError(
+                "the computed root does not match".into(),
+            ));
+        }
+        Ok(())
+    }
+
+    fn check_key(&self, key: u64) -> Result<(), PrimitivesError> {
+        if key >= self.capacity {
+            return Err(PrimitivesError::ParameterError(format!(
+                "key {} is out of range for a tree of capacity {}",
+                key, self.capacity
+            )));
+        }
+        Ok(())
+    }
+
+    // The value of the `index`-th node of height `level`.
+    fn node_value(&self, level: u8, index: u64) -> NodeValue<F> {
+        let width = 3_u64.pow(level as u32);
+        let mut leaves = self.leaves.range(index * width..(index + 1) * width);
+        match leaves.next() {
+            None => self.default_values[level as usize],
+            Some((&key, &elem)) if level == 0 => leaf_value(key, Some(elem)),
+            Some(_) => hash(
+                &self.node_value(level - 1, 3 * index),
+                &self.node_value(level - 1, 3 * index + 1),
+                &self.node_value(level - 1, 3 * index + 2),
+            ),
+        }
+    }
+}
+
+// The value of the leaf of an element at `key`, or of an empty leaf.
+fn leaf_value<F: RescueParameter>(key: u64, elem: Option<F>) -> NodeValue<F> {
+    match elem {
+        Some(elem) => hash(
+            &NodeValue::empty_node_value(),
+            &NodeValue::from(key),
+            &NodeValue::from_scalar(elem),
+        ),
+        None => NodeValue::empty_node_value(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ark_bls12_377::Fq as Fq377;
+    use ark_ed_on_bls12_377::Fq as FqEd377;
+    use ark_ed_on_bls12_381::Fq as FqEd381;
+    use ark_ed_on_bn254::Fq as FqEd254;
+
+    #[test]
+    fn test_sparse_merkle_tree() {
+        test_sparse_merkle_tree_helper::<FqEd254>();
+        test_sparse_merkle_tree_helper::<FqEd377>();
+        test_sparse_merkle_tree_helper::<FqEd381>();
+        test_sparse_merkle_tree_helper::<Fq377>();
+    }
+
+    fn test_sparse_merkle_tree_helper<F: RescueParameter>() {
+        let height = 4;
+        let mut smt = SparseMerkleTree::<F>::new(height).unwrap();
+        let empty_root = smt.root();
+        assert_eq!(Some(empty_root), smt.default_value(height));
+        assert!(SparseMerkleTree::<F>::new(41).is_none());
+
+        let keys = [0u64, 5, 42, 80];
+        for &key in keys.iter() {
+            assert_eq!(smt.insert(key, F::from(key + 100)).unwrap(), None);
+        }
+        assert_eq!(smt.num_leaves(), 4);
+        assert!(smt.insert(81, F::one()).is_err());
+        let root = smt.root();
+
+        // proofs of membership
+        for &key in keys.iter() {
+            let (elem, path) = smt.lookup(key).unwrap();
+            assert_eq!(elem, Some(F::from(key + 100)));
+            assert_eq!(path.nodes.len(), height as usize);
+            assert!(SparseMerkleTree::verify(&root, key, elem, &path).is_ok());
+            assert!(SparseMerkleTree::verify(&root, key, None, &path).is_err());
+            assert!(SparseMerkleTree::verify(&root, key, Some(F::one()), &path).is_err());
+        }
+
+        // proofs of absence
+        for &key in [1u64, 6, 41, 79].iter() {
+            let (elem, path) = smt.lookup(key).unwrap();
+            assert_eq!(elem, None);
+            assert!(SparseMerkleTree::verify(&root, key, None, &path).is_ok());
+            // the path of a key does not prove the absence of another key
+            assert!(SparseMerkleTree::verify(&root, key + 1, None, &path).is_err());
+        }
+
+        // the root only depends on the content of the tree
+        assert_eq!(smt.insert(42, F::one()).unwrap(), Some(F::from(142u64)));
+        assert_ne!(smt.root(), root);
+        smt.insert(42, F::from(142u64)).unwrap();
+        assert_eq!(smt.root(), root);
+        for &key in keys.iter() {
+            smt.remove(key).unwrap();
+        }
+        assert_eq!(smt.root(), empty_root);
+    }
+}
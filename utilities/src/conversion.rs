@@ -6,9 +6,58 @@
 
 use ark_ec::ModelParameters;
 use ark_ff::{BigInteger, PrimeField};
-use ark_std::{cmp::min, vec::Vec};
+use ark_std::{cmp::min, fmt, vec::Vec};
 use sha2::{Digest, Sha512};
 
+/// Errors raised by the fallible field conversion functions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The element does not fit in the target field and would be reduced.
+    ModReductionOccurred,
+    /// The bytes do not encode a canonical element of the target field.
+    NonCanonicalEncoding,
+    /// The requested trunk length exceeds what the field can hold without
+    /// mod reduction.
+    TrunkTooLarge {
+        /// requested trunk length in bytes
+        requested: usize,
+        /// maximal trunk length in bytes
+        max: usize,
+    },
+    /// The requested trunk length is zero.
+    ZeroTrunkLength,
+    /// The element at position `index` does not fit in the target field.
+    ElementTooLarge {
+        /// position of the offending element
+        index: usize,
+    },
+}
+
+impl fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ModReductionOccurred => write!(f, "mod reduction occurred during conversion"),
+            Self::NonCanonicalEncoding => write!(f, "non-canonical field element encoding"),
+            Self::TrunkTooLarge { requested, max } => write!(
+                f,
+                "trunk length {} bytes is larger than the maximum {} bytes",
+                requested, max
+            ),
+            Self::ZeroTrunkLength => write!(f, "trunk length cannot be zero"),
+            Self::ElementTooLarge { index } => {
+                write!(
+                    f,
+                    "the {}-th element does not fit in the target field",
+                    index
+                )
+            },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConversionError {}
+
 /// Convert a scalar field element to a base field element.
 /// Mod reduction is not performed since the conversion occurs
 /// for fields on a same curve.
@@ -75,6 +124,77 @@ where
     t
 }
 
+/// Convert a field element in F(rom) to a field element in T(o).
+/// Return `ConversionError::ModReductionOccurred` instead of panicking
+/// if the element does not fit in T.
+pub fn try_field_switching<F, T>(base: &F) -> Result<T, ConversionError>
+where
+    F: PrimeField,
+    T: PrimeField,
+{
+    let bytes = base.into_repr().to_bytes_le();
+    let t = T::from_le_bytes_mod_order(&bytes);
+
+    let bytes_rec = t.into_repr().to_bytes_le();
+    let length = min(bytes.len(), bytes_rec.len());
+    if bytes_rec[0..length] != bytes[0..length] || bytes[length..].iter().any(|&b| b != 0) {
+        return Err(ConversionError::ModReductionOccurred);
+    }
+    Ok(t)
+}
+
+/// Convert a slice of field elements in F(rom) to field elements in T(o).
+/// Return `ConversionError::ElementTooLarge` with the position of the first
+/// element that does not fit in T.
+pub fn try_fields_switching<F, T>(bases: &[F]) -> Result<Vec<T>, ConversionError>
+where
+    F: PrimeField,
+    T: PrimeField,
+{
+    bases
+        .iter()
+        .enumerate()
+        .map(|(index, e)| {
+            try_field_switching(e).map_err(|_| ConversionError::ElementTooLarge { index })
+        })
+        .collect()
+}
+
+/// Fallible version of `fq_to_fr_with_mask`.
+/// Return `ConversionError::TrunkTooLarge` if |T| is not smaller than |F|,
+/// in which case masking cannot guarantee the absence of mod reduction.
+pub fn try_fq_to_fr_with_mask<F, T>(base: &F) -> Result<T, ConversionError>
+where
+    F: PrimeField,
+    T: PrimeField,
+{
+    if T::size_in_bits() >= F::size_in_bits() {
+        return Err(ConversionError::TrunkTooLarge {
+            requested: T::size_in_bits() >> 3,
+            max: (F::size_in_bits() - 1) >> 3,
+        });
+    }
+    let length = T::size_in_bits() >> 3;
+    Ok(T::from_le_bytes_mod_order(
+        &base.into_repr().to_bytes_le()[0..length],
+    ))
+}
+
+/// Parse a big-endian byte string into a field element.
+/// Return `ConversionError::NonCanonicalEncoding` if the encoded integer is
+/// not smaller than the field modulus.
+pub fn bytes_be_to_field<F: PrimeField>(bytes: &[u8]) -> Result<F, ConversionError> {
+    let f = F::from_be_bytes_mod_order(bytes);
+    let bytes_rec = f.into_repr().to_bytes_be();
+
+    // compare the integers ignoring leading zeros
+    let strip = |b: &[u8]| -> Vec<u8> { b.iter().copied().skip_while(|&x| x == 0).collect() };
+    if strip(bytes) != strip(&bytes_rec) {
+        return Err(ConversionError::NonCanonicalEncoding);
+    }
+    Ok(f)
+}
+
 /// Hash a sequence of bytes to into a field
 /// element, whose order is less than 256 bits.
 pub fn hash_to_field<B, F>(bytes: B) -> F
@@ -122,13 +242,68 @@ where
     result
 }
 
+/// Recommended trunk length in bytes for `bytes_to_field_elements_fixed`.
+///
+/// 31 bytes (248 bits) fit without mod reduction in every field used across
+/// the library, the smallest being the 251-bit scalar field of the twisted
+/// Edwards curve over BN254, so that the encoding is the same integer
+/// sequence whatever the target field is.
+pub const RECOMMENDED_TRUNK_LEN: usize = 31;
+
+/// Field-independent, infallible-by-construction conversion from arbitrary
+/// bytes to field elements.
+///
+/// Unlike `bytes_to_field_elements`, whose trunk size depends on the target
+/// field, the bytes are segmented into trunks of exactly `trunk_len` bytes
+/// (the last one zero-padded), each read as a little-endian integer. Using
+/// the same `trunk_len` (e.g. `RECOMMENDED_TRUNK_LEN`) for different fields
+/// yields the same integers, as long as no mod reduction can occur.
+///
+/// Return `ConversionError::ZeroTrunkLength` if `trunk_len` is zero, and
+/// `ConversionError::TrunkTooLarge` if a `trunk_len`-byte integer may not fit
+/// in `F`.
+pub fn bytes_to_field_elements_fixed<B, F>(
+    bytes: B,
+    trunk_len: usize,
+) -> Result<Vec<F>, ConversionError>
+where
+    B: AsRef<[u8]>,
+    F: PrimeField,
+{
+    if trunk_len == 0 {
+        return Err(ConversionError::ZeroTrunkLength);
+    }
+    // a trunk of `trunk_len` bytes is smaller than 2^(8 * trunk_len), which
+    // is at most 2^(|F| - 1) < modulus
+    let max = (F::size_in_bits() - 1) / 8;
+    if trunk_len > max {
+        return Err(ConversionError::TrunkTooLarge {
+            requested: trunk_len,
+            max,
+        });
+    }
+
+    Ok(bytes
+        .as_ref()
+        .chunks(trunk_len)
+        .map(|chunk| {
+            let mut trunk = chunk.to_vec();
+            trunk.resize(trunk_len, 0u8);
+            F::from_le_bytes_mod_order(&trunk)
+        })
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use ark_bls12_377::{Fq as Fq377, Fr as Fr377Bls};
+    use ark_bls12_381::Fr as Fr381Bls;
     use ark_ed_on_bls12_377::{EdwardsParameters as Param377, Fr as Fr377};
     use ark_ed_on_bls12_381::{EdwardsParameters as Param381, Fr as Fr381};
     use ark_ed_on_bn254::{EdwardsParameters as Param254, Fr as Fr254};
-    use ark_std::UniformRand;
+    use ark_ff::FpParameters;
+    use ark_std::{vec, UniformRand};
 
     #[test]
     fn test_bn254_scalar_conversion() {
@@ -159,4 +334,106 @@ mod tests {
             assert!(jj.into_repr() == jj_bls.into_repr());
         }
     }
+
+    #[test]
+    fn test_conversion_errors() {
+        let mut rng = ark_std::test_rng();
+
+        // ModReductionOccurred
+        let small = Fr377Bls::rand(&mut rng);
+        let large: Fq377 = field_switching(&small);
+        assert_eq!(try_field_switching::<_, Fr377Bls>(&large), Ok(small));
+        let too_large = -Fq377::from(1u64);
+        assert_eq!(
+            try_field_switching::<_, Fr377Bls>(&too_large),
+            Err(ConversionError::ModReductionOccurred)
+        );
+
+        // ElementTooLarge
+        let elems = [large, large, too_large, large];
+        assert_eq!(
+            try_fields_switching::<_, Fr377Bls>(&elems),
+            Err(ConversionError::ElementTooLarge { index: 2 })
+        );
+        assert!(try_fields_switching::<_, Fr377Bls>(&elems[0..2]).is_ok());
+
+        // TrunkTooLarge
+        assert_eq!(
+            try_fq_to_fr_with_mask::<_, Fr377Bls>(&too_large),
+            Ok(fq_to_fr_with_mask::<_, Fr377Bls>(&too_large))
+        );
+        assert_eq!(
+            try_fq_to_fr_with_mask::<_, Fq377>(&small),
+            Err(ConversionError::TrunkTooLarge {
+                requested: Fq377::size_in_bits() >> 3,
+                max: (Fr377Bls::size_in_bits() - 1) >> 3,
+            })
+        );
+
+        // NonCanonicalEncoding
+        let bytes = small.into_repr().to_bytes_be();
+        assert_eq!(bytes_be_to_field::<Fr377Bls>(&bytes), Ok(small));
+        let mut padded = vec![0u8; 4];
+        padded.extend_from_slice(&bytes);
+        assert_eq!(bytes_be_to_field::<Fr377Bls>(&padded), Ok(small));
+        let modulus = <Fr377Bls as PrimeField>::Params::MODULUS.to_bytes_be();
+        assert_eq!(
+            bytes_be_to_field::<Fr377Bls>(&modulus),
+            Err(ConversionError::NonCanonicalEncoding)
+        );
+    }
+
+    #[test]
+    fn test_bytes_to_field_elements_fixed() {
+        let mut rng = ark_std::test_rng();
+        for len in [0, 1, 30, 31, 32, 62, 100] {
+            let bytes: Vec<u8> = (0..len).map(|_| u8::rand(&mut rng)).collect();
+
+            let elems_377 =
+                bytes_to_field_elements_fixed::<_, Fr377Bls>(&bytes, RECOMMENDED_TRUNK_LEN)
+                    .unwrap();
+            let elems_381 =
+                bytes_to_field_elements_fixed::<_, Fr381Bls>(&bytes, RECOMMENDED_TRUNK_LEN)
+                    .unwrap();
+            let elems_254 =
+                bytes_to_field_elements_fixed::<_, Fr254>(&bytes, RECOMMENDED_TRUNK_LEN).unwrap();
+            assert_eq!(
+                elems_377.len(),
+                (len + RECOMMENDED_TRUNK_LEN - 1) / RECOMMENDED_TRUNK_LEN
+            );
+
+            // the same integers in both BLS fields, and in the smallest field
+            for ((a, b), c) in elems_377.iter().zip(elems_381.iter()).zip(elems_254.iter()) {
+                assert_eq!(a.into_repr().to_bytes_le(), b.into_repr().to_bytes_le());
+                assert_eq!(a.into_repr().to_bytes_le(), c.into_repr().to_bytes_le());
+            }
+
+            // no mod reduction: the bytes are recovered
+            let mut recovered = Vec::new();
+            for e in elems_377.iter() {
+                recovered.extend_from_slice(&e.into_repr().to_bytes_le()[..RECOMMENDED_TRUNK_LEN]);
+            }
+            assert_eq!(&recovered[..len], &bytes[..]);
+        }
+
+        // the trunk must fit in the field
+        assert_eq!(
+            bytes_to_field_elements_fixed::<_, Fr377Bls>([0u8; 64], 32),
+            Err(ConversionError::TrunkTooLarge {
+                requested: 32,
+                max: 31
+            })
+        );
+        assert!(bytes_to_field_elements_fixed::<_, Fq377>([0u8; 64], 32).is_ok());
+
+        // the trunk cannot be empty
+        assert_eq!(
+            bytes_to_field_elements_fixed::<_, Fr377Bls>([0u8; 64], 0),
+            Err(ConversionError::ZeroTrunkLength)
+        );
+        assert_eq!(
+            bytes_to_field_elements_fixed::<_, Fr377Bls>([0u8; 0], 0),
+            Err(ConversionError::ZeroTrunkLength)
+        );
+    }
 }
@@ -6,6 +6,9 @@
 
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
 mod conversion;
 mod hash_to_group;
 mod macros;